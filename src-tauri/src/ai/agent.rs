@@ -12,11 +12,13 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 use super::config::AIProvider;
+use crate::models::ReasoningSettings;
 use super::llm::{
-    ChatMessage, ChatRequest, ChatResponse, LlmError, MessageRole,
-    ToolCall as LlmToolCall, ToolDefinition as LlmToolDefinition, TokenUsage,
+    ChatMessage, ChatRequest, ChatResponse, ImagePart, LlmClient, LlmError, MessageRole,
+    StreamEvent, ThinkingBlock, ToolCall as LlmToolCall, ToolDefinition as LlmToolDefinition, TokenUsage,
 };
 
 /// Convert agent tool definitions to LLM tool definitions
@@ -58,6 +60,8 @@ fn convert_messages_to_llm(messages: &[AgentMessage]) -> Vec<ChatMessage> {
                 }).collect()
             }),
             tool_call_id: m.tool_call_id.clone(),
+            images: m.images.clone(),
+            thinking_blocks: m.thinking_blocks.clone(),
         }
     }).collect()
 }
@@ -141,6 +145,10 @@ pub enum AgentProgress {
     Thinking {
         message: String,
     },
+    /// Partial assistant text, streamed as it is generated
+    ContentChunk {
+        content: String,
+    },
     /// Agent completed successfully
     Completed {
         result: AgentResult,
@@ -250,6 +258,12 @@ pub struct AgentMessage {
     pub tool_calls: Option<Vec<ToolCall>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    /// Images attached to a user message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImagePart>,
+    /// Signed thinking behind an assistant message's tool calls
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thinking_blocks: Vec<ThinkingBlock>,
 }
 
 impl AgentMessage {
@@ -260,6 +274,8 @@ impl AgentMessage {
             content: Some(content.to_string()),
             tool_calls: None,
             tool_call_id: None,
+            images: Vec::new(),
+            thinking_blocks: Vec::new(),
         }
     }
 
@@ -270,16 +286,41 @@ impl AgentMessage {
             content: Some(content.to_string()),
             tool_calls: None,
             tool_call_id: None,
+            images: Vec::new(),
+            thinking_blocks: Vec::new(),
+        }
+    }
+
+    /// Create a user message with attached images
+    pub fn user_with_images(content: &str, images: Vec<ImagePart>) -> Self {
+        Self {
+            images,
+            ..Self::user(content)
         }
     }
 
-    /// Create an assistant message with tool calls (no content)
-    pub fn assistant_tool_calls(tool_calls: Vec<ToolCall>) -> Self {
+    /// Create an assistant message
+    pub fn assistant(content: &str) -> Self {
+        Self {
+            role: "assistant".to_string(),
+            content: Some(content.to_string()),
+            tool_calls: None,
+            tool_call_id: None,
+            images: Vec::new(),
+            thinking_blocks: Vec::new(),
+        }
+    }
+
+    /// Create an assistant message with tool calls (no content) and the
+    /// signed thinking that led to them
+    pub fn assistant_tool_calls(tool_calls: Vec<ToolCall>, thinking_blocks: Vec<ThinkingBlock>) -> Self {
         Self {
             role: "assistant".to_string(),
             content: None,
             tool_calls: Some(tool_calls),
             tool_call_id: None,
+            images: Vec::new(),
+            thinking_blocks,
         }
     }
 
@@ -290,6 +331,8 @@ impl AgentMessage {
             content: Some(content.to_string()),
             tool_calls: None,
             tool_call_id: Some(tool_call_id.to_string()),
+            images: Vec::new(),
+            thinking_blocks: Vec::new(),
         }
     }
 }
@@ -328,6 +371,9 @@ pub struct AgentResult {
     /// Cumulative token usage across all iterations
     #[serde(skip_serializing_if = "Option::is_none")]
     pub token_usage: Option<CumulativeTokenUsage>,
+    /// What the executor's finalize hook committed, if anything
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finalized: Option<String>,
 }
 
 /// Record of a tool call and its result
@@ -347,18 +393,129 @@ pub struct ToolCallRecord {
 pub trait ToolExecutor: Send + Sync {
     /// Execute a tool by name with the given arguments
     async fn execute(&self, name: &str, args: Value) -> Result<String, String>;
+
+    /// How long a single tool call may run before it is abandoned
+    fn tool_timeout(&self) -> Duration {
+        DEFAULT_TOOL_TIMEOUT
+    }
+
+    /// Most tool calls a single run may make before the model must finalize
+    fn max_tool_calls(&self) -> usize {
+        DEFAULT_MAX_TOOL_CALLS
+    }
+
+    /// Commit work the model left pending when the run ends
+    ///
+    /// Called once after the model's final response (not on errors or
+    /// cancellation). Agents whose tools stage changes, like a document
+    /// draft, save them here if the model never called its save tool.
+    /// Returns a short description of what was committed, if anything.
+    async fn finalize(
+        &self,
+        _final_response: &str,
+        _tool_calls: &[ToolCallRecord],
+    ) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+}
+
+/// Run the executor's finalize hook
+///
+/// A failing hook is logged rather than failing the run, since the model's
+/// response is still worth returning.
+pub async fn finalize_run<E: ToolExecutor + ?Sized>(
+    executor: &E,
+    final_response: &str,
+    tool_calls: &[ToolCallRecord],
+) -> Option<String> {
+    match executor.finalize(final_response, tool_calls).await {
+        Ok(Some(note)) => {
+            log::info!("Agent finalize hook committed pending work: {}", note);
+            Some(note)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            log::error!("Agent finalize hook failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Default time allowed for a single tool call
+pub const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default cap on tool calls across a single agent run
+pub const DEFAULT_MAX_TOOL_CALLS: usize = 100;
+
+/// Tool result sent for calls made after the budget is spent
+pub const TOOL_BUDGET_SKIPPED_RESULT: &str =
+    "Skipped: the tool call budget for this run is used up. Do not call any more tools.";
+
+/// Message telling the model to stop calling tools and give its final answer
+pub fn tool_budget_exhausted_message(max_tool_calls: usize) -> String {
+    format!(
+        "You have used all {} tool calls allowed for this task. Do not call any more tools. \
+         Write your final response now using the information you already have.",
+        max_tool_calls
+    )
+}
+
+/// Run a synchronous tool body (database or search index work) on the blocking pool
+///
+/// Executors should use this for anything that doesn't await: run inline, a slow
+/// body holds the runtime thread and `execute_tool_with_timeout` can't interrupt it.
+pub async fn run_blocking_tool<F>(tool: F) -> Result<String, String>
+where
+    F: FnOnce() -> Result<String, String> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(tool)
+        .await
+        .map_err(|e| format!("Tool task failed: {}", e))?
 }
 
-/// Run an agent with the given configuration
+/// Execute a tool, giving up after the executor's timeout
 ///
-/// This function implements the core agent loop:
+/// A timeout is returned as an error describing what happened, so the agent
+/// loop passes it back to the model and the run carries on. Blocking tool
+/// bodies have to go through `run_blocking_tool` for the timeout to apply.
+pub async fn execute_tool_with_timeout<E: ToolExecutor + ?Sized>(
+    executor: &E,
+    name: &str,
+    args: Value,
+) -> Result<String, String> {
+    let timeout = executor.tool_timeout();
+    match tokio::time::timeout(timeout, executor.execute(name, args)).await {
+        Ok(result) => result,
+        Err(_) => {
+            log::warn!("Tool {} timed out after {:?}", name, timeout);
+            Err(serde_json::json!({
+                "error": "tool_timeout",
+                "tool": name,
+                "timeoutSecs": timeout.as_secs_f64(),
+                "message": format!(
+                    "The {} tool did not finish within {}s. Continue without its result or try a different approach.",
+                    name,
+                    timeout.as_secs_f64()
+                ),
+            })
+            .to_string())
+        }
+    }
+}
+
+/// Run the agent loop against an already-constructed LLM client
+///
+/// The core agent loop:
 /// 1. Send messages + tools to LLM via LlmClient
 /// 2. If response has tool_calls, execute each tool
 /// 3. Add tool results as tool role messages
 /// 4. Repeat until LLM responds with content only (no tool calls)
 /// 5. Return final response + all tool calls made + token usage
-pub async fn run_agent<E: ToolExecutor>(
-    provider: &AIProvider,
+///
+/// Callers can share one client across many runs (or substitute their own
+/// `LlmClient`).
+pub async fn run_agent_with_client<E: ToolExecutor>(
+    client: &dyn LlmClient,
     model: &str,
     system_prompt: &str,
     initial_message: &str,
@@ -366,18 +523,43 @@ pub async fn run_agent<E: ToolExecutor>(
     executor: &E,
     max_iterations: usize,
 ) -> Result<AgentResult, AgentError> {
-    // Create LLM client from provider config
-    let client = super::llm::create_client(provider)?;
-    
-    // Initialize message history
-    let mut messages = vec![
+    let messages = vec![
         AgentMessage::system(system_prompt),
         AgentMessage::user(initial_message),
     ];
+
+    run_agent_with_messages(
+        client,
+        model,
+        messages,
+        tools,
+        executor,
+        max_iterations,
+        &ReasoningSettings::default(),
+    )
+    .await
+}
+
+/// Run the agent loop starting from an existing message history
+///
+/// Used when the agent continues a conversation (e.g. chat with tools), so
+/// earlier turns are sent along with the latest user message.
+pub async fn run_agent_with_messages<E: ToolExecutor>(
+    client: &dyn LlmClient,
+    model: &str,
+    initial_messages: Vec<AgentMessage>,
+    tools: Vec<ToolDefinition>,
+    executor: &E,
+    max_iterations: usize,
+    reasoning: &ReasoningSettings,
+) -> Result<AgentResult, AgentError> {
+    let mut messages = initial_messages;
     
     let mut all_tool_calls: Vec<ToolCallRecord> = Vec::new();
     let mut cumulative_usage = CumulativeTokenUsage::default();
     let mut iterations = 0;
+    let max_tool_calls = executor.max_tool_calls();
+    let mut budget_exhausted = false;
     
     // Convert tools once
     let llm_tools = convert_tools_to_llm(&tools);
@@ -393,12 +575,19 @@ pub async fn run_agent<E: ToolExecutor>(
             model: model.to_string(),
             messages: convert_messages_to_llm(&messages),
             tools: if llm_tools.is_empty() { None } else { Some(llm_tools.clone()) },
-            tool_choice: if llm_tools.is_empty() { None } else { Some("auto".to_string()) },
+            tool_choice: if llm_tools.is_empty() {
+                None
+            } else if budget_exhausted {
+                Some("none".to_string())
+            } else {
+                Some("auto".to_string())
+            },
             max_tokens: None,
             temperature: None,
-            enable_reasoning: false,
-            reasoning_effort: None,
-            thinking_budget: None,
+            enable_reasoning: reasoning.is_enabled(),
+            reasoning_effort: reasoning.reasoning_effort.clone(),
+            thinking_budget: reasoning.thinking_budget,
+            use_prompt_cache: false,
         };
         
         // Call via LlmClient - this handles all provider-specific formatting
@@ -409,20 +598,27 @@ pub async fn run_agent<E: ToolExecutor>(
             cumulative_usage.add(usage);
         }
         
-        // Check if there are tool calls
+        // Check if there are tool calls (ignored once the budget is spent)
         if let Some(tool_calls) = extract_tool_calls_from_response(&response) {
-            if !tool_calls.is_empty() {
+            if budget_exhausted && !tool_calls.is_empty() {
+                log::warn!("Agent kept calling tools after its budget of {} was spent; finishing", max_tool_calls);
+            } else if !tool_calls.is_empty() {
                 // Add the assistant message with tool calls to history
-                messages.push(AgentMessage::assistant_tool_calls(tool_calls.clone()));
+                messages.push(AgentMessage::assistant_tool_calls(tool_calls.clone(), response.thinking_blocks.clone()));
                 
                 // Execute each tool call
                 for tool_call in &tool_calls {
+                    if all_tool_calls.len() >= max_tool_calls {
+                        messages.push(AgentMessage::tool_result(&tool_call.id, TOOL_BUDGET_SKIPPED_RESULT));
+                        continue;
+                    }
+                    
                     let args: Value = serde_json::from_str(&tool_call.function.arguments)
                         .unwrap_or(Value::Object(serde_json::Map::new()));
                     
                     log::info!("Agent calling tool: {} with args: {}", tool_call.function.name, args);
                     
-                    let result = executor.execute(&tool_call.function.name, args.clone()).await;
+                    let result = execute_tool_with_timeout(executor, &tool_call.function.name, args.clone()).await;
                     
                     let result_str = match &result {
                         Ok(s) => s.clone(),
@@ -442,6 +638,13 @@ pub async fn run_agent<E: ToolExecutor>(
                     messages.push(AgentMessage::tool_result(&tool_call.id, &result_str));
                 }
                 
+                // Out of tool calls: tell the model to wrap up
+                if all_tool_calls.len() >= max_tool_calls {
+                    log::warn!("Agent reached its tool call budget of {}", max_tool_calls);
+                    budget_exhausted = true;
+                    messages.push(AgentMessage::user(&tool_budget_exhausted_message(max_tool_calls)));
+                }
+                
                 // Continue the loop to get the next response
                 continue;
             }
@@ -449,19 +652,21 @@ pub async fn run_agent<E: ToolExecutor>(
         
         // No tool calls - we have the final response
         let final_response = response.content;
+        let finalized = finalize_run(executor, &final_response, &all_tool_calls).await;
         
         return Ok(AgentResult {
             final_response,
             tool_calls_made: all_tool_calls,
             iterations,
             token_usage: Some(cumulative_usage),
+            finalized,
         });
     }
 }
 
 /// Run an agent with Tauri event streaming for real-time UI updates
 ///
-/// This is similar to `run_agent` but emits AgentProgress events via Tauri
+/// This is similar to `run_agent_with_client` but emits AgentProgress events via Tauri
 /// for each step of the execution, allowing the UI to show real-time progress.
 ///
 /// # Arguments
@@ -489,25 +694,199 @@ pub async fn run_agent_with_events<E: ToolExecutor>(
     executor: &E,
     max_iterations: usize,
     cancellation_token: Option<&CancellationToken>,
+) -> Result<AgentResult, AgentError> {
+    run_agent_with_progress(
+        app_handle,
+        execution_id,
+        agent_name,
+        provider,
+        model,
+        system_prompt,
+        initial_message,
+        tools,
+        executor,
+        max_iterations,
+        cancellation_token,
+        false,
+    )
+    .await
+}
+
+/// Run an agent with Tauri events, streaming the assistant's text as it is generated
+///
+/// Same as `run_agent_with_events`, but each LLM call is streamed and partial
+/// text is emitted as `AgentProgress::ContentChunk` events before the final
+/// `Completed` event.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_agent_with_streaming_events<E: ToolExecutor>(
+    app_handle: &tauri::AppHandle,
+    execution_id: &str,
+    agent_name: &str,
+    provider: &AIProvider,
+    model: &str,
+    system_prompt: &str,
+    initial_message: &str,
+    tools: Vec<ToolDefinition>,
+    executor: &E,
+    max_iterations: usize,
+    cancellation_token: Option<&CancellationToken>,
+) -> Result<AgentResult, AgentError> {
+    run_agent_with_progress(
+        app_handle,
+        execution_id,
+        agent_name,
+        provider,
+        model,
+        system_prompt,
+        initial_message,
+        tools,
+        executor,
+        max_iterations,
+        cancellation_token,
+        true,
+    )
+    .await
+}
+
+/// Create the client and emit progress on `agent-progress-{execution_id}`
+#[allow(clippy::too_many_arguments)]
+async fn run_agent_with_progress<E: ToolExecutor>(
+    app_handle: &tauri::AppHandle,
+    execution_id: &str,
+    agent_name: &str,
+    provider: &AIProvider,
+    model: &str,
+    system_prompt: &str,
+    initial_message: &str,
+    tools: Vec<ToolDefinition>,
+    executor: &E,
+    max_iterations: usize,
+    cancellation_token: Option<&CancellationToken>,
+    stream_content: bool,
 ) -> Result<AgentResult, AgentError> {
     use tauri::Emitter;
     
     let event_name = format!("agent-progress-{}", execution_id);
     
+    // Helper to emit progress events
+    let emit_progress = |progress: AgentProgress| {
+        let _ = app_handle.emit(&event_name, &progress);
+    };
+    
     // Create LLM client from provider config
     let client = super::llm::create_client(provider).map_err(|e| {
         let error = AgentError::from(e);
-        let _ = app_handle.emit(&event_name, &AgentProgress::Error {
+        emit_progress(AgentProgress::Error {
             message: error.to_string(),
         });
         error
     })?;
     
-    // Helper to emit progress events
-    let emit_progress = |progress: AgentProgress| {
-        let _ = app_handle.emit(&event_name, &progress);
-    };
-    
+    run_agent_with_progress_and_client(
+        client.as_ref(),
+        &emit_progress,
+        execution_id,
+        agent_name,
+        model,
+        system_prompt,
+        initial_message,
+        tools,
+        executor,
+        max_iterations,
+        cancellation_token,
+        stream_content,
+    )
+    .await
+}
+
+/// Stream a chat completion, passing each content delta to `on_chunk`
+///
+/// Returns the assembled response, so callers can treat it like `chat`.
+async fn chat_streamed(
+    client: &dyn LlmClient,
+    request: ChatRequest,
+    on_chunk: &(dyn Fn(String) + Send + Sync),
+) -> Result<ChatResponse, LlmError> {
+    let mut rx = client.chat_stream(request).await?;
+
+    let mut content = String::new();
+    let mut thinking = String::new();
+    let mut thinking_blocks = Vec::new();
+    let mut tool_calls: Vec<LlmToolCall> = Vec::new();
+    let mut finish_reason = String::new();
+    let mut usage = None;
+
+    while let Some(event) = rx.recv().await {
+        match event {
+            StreamEvent::Content { delta } => {
+                content.push_str(&delta);
+                on_chunk(delta);
+            }
+            StreamEvent::Thinking { delta } => thinking.push_str(&delta),
+            StreamEvent::ThinkingBlockEnd { thinking, signature } => {
+                thinking_blocks.push(ThinkingBlock { thinking, signature });
+            }
+            StreamEvent::ToolCallStart { id, name, thought_signature } => {
+                tool_calls.push(LlmToolCall {
+                    id,
+                    call_type: "function".to_string(),
+                    function: super::llm::FunctionCall {
+                        name,
+                        arguments: String::new(),
+                    },
+                    thought_signature,
+                });
+            }
+            StreamEvent::ToolCallDelta { id, arguments_delta } => {
+                if let Some(tc) = tool_calls.iter_mut().find(|tc| tc.id == id) {
+                    tc.function.arguments.push_str(&arguments_delta);
+                }
+            }
+            StreamEvent::Usage { prompt_tokens, completion_tokens } => {
+                usage = Some(TokenUsage {
+                    prompt_tokens,
+                    completion_tokens,
+                    total_tokens: prompt_tokens + completion_tokens,
+                });
+            }
+            StreamEvent::Done { finish_reason: reason } => {
+                // Keep reading until the stream closes: usage can follow Done
+                if finish_reason.is_empty() {
+                    finish_reason = reason;
+                }
+            }
+            StreamEvent::Error { message } => {
+                return Err(LlmError::InvalidResponse(message));
+            }
+        }
+    }
+
+    Ok(ChatResponse {
+        content,
+        thinking: if thinking.is_empty() { None } else { Some(thinking) },
+        thinking_blocks,
+        tool_calls: if tool_calls.is_empty() { None } else { Some(tool_calls) },
+        finish_reason,
+        usage,
+    })
+}
+
+/// The agent loop behind `run_agent_with_events`, emitting progress through `emit_progress`
+#[allow(clippy::too_many_arguments)]
+async fn run_agent_with_progress_and_client<E: ToolExecutor>(
+    client: &dyn LlmClient,
+    emit_progress: &(dyn Fn(AgentProgress) + Send + Sync),
+    execution_id: &str,
+    agent_name: &str,
+    model: &str,
+    system_prompt: &str,
+    initial_message: &str,
+    tools: Vec<ToolDefinition>,
+    executor: &E,
+    max_iterations: usize,
+    cancellation_token: Option<&CancellationToken>,
+    stream_content: bool,
+) -> Result<AgentResult, AgentError> {
     // Check for cancellation
     let check_cancelled = || -> Result<(), AgentError> {
         if let Some(token) = cancellation_token {
@@ -533,6 +912,8 @@ pub async fn run_agent_with_events<E: ToolExecutor>(
     let mut all_tool_calls: Vec<ToolCallRecord> = Vec::new();
     let mut cumulative_usage = CumulativeTokenUsage::default();
     let mut iterations = 0;
+    let max_tool_calls = executor.max_tool_calls();
+    let mut budget_exhausted = false;
     
     // Convert tools once
     let llm_tools = convert_tools_to_llm(&tools);
@@ -558,16 +939,29 @@ pub async fn run_agent_with_events<E: ToolExecutor>(
             model: model.to_string(),
             messages: convert_messages_to_llm(&messages),
             tools: if llm_tools.is_empty() { None } else { Some(llm_tools.clone()) },
-            tool_choice: if llm_tools.is_empty() { None } else { Some("auto".to_string()) },
+            tool_choice: if llm_tools.is_empty() {
+                None
+            } else if budget_exhausted {
+                Some("none".to_string())
+            } else {
+                Some("auto".to_string())
+            },
             max_tokens: None,
             temperature: None,
             enable_reasoning: false,
             reasoning_effort: None,
             thinking_budget: None,
+            use_prompt_cache: false,
         };
         
-        // Call via LlmClient
-        let response = client.chat(request).await.map_err(|e| {
+        // Call via LlmClient, streaming text to the UI when requested
+        let response = if stream_content {
+            let on_chunk = |content: String| emit_progress(AgentProgress::ContentChunk { content });
+            chat_streamed(client, request, &on_chunk).await
+        } else {
+            client.chat(request).await
+        };
+        let response = response.map_err(|e| {
             let error = AgentError::from(e);
             emit_progress(AgentProgress::Error {
                 message: error.to_string(),
@@ -582,16 +976,23 @@ pub async fn run_agent_with_events<E: ToolExecutor>(
             cumulative_usage.add(usage);
         }
         
-        // Check if there are tool calls
+        // Check if there are tool calls (ignored once the budget is spent)
         if let Some(tool_calls) = extract_tool_calls_from_response(&response) {
-            if !tool_calls.is_empty() {
+            if budget_exhausted && !tool_calls.is_empty() {
+                log::warn!("Agent kept calling tools after its budget of {} was spent; finishing", max_tool_calls);
+            } else if !tool_calls.is_empty() {
                 // Add the assistant message with tool calls to history
-                messages.push(AgentMessage::assistant_tool_calls(tool_calls.clone()));
+                messages.push(AgentMessage::assistant_tool_calls(tool_calls.clone(), response.thinking_blocks.clone()));
                 
                 // Execute each tool call
                 for tool_call in &tool_calls {
                     check_cancelled()?;
                     
+                    if all_tool_calls.len() >= max_tool_calls {
+                        messages.push(AgentMessage::tool_result(&tool_call.id, TOOL_BUDGET_SKIPPED_RESULT));
+                        continue;
+                    }
+                    
                     let args: Value = serde_json::from_str(&tool_call.function.arguments)
                         .unwrap_or(Value::Object(serde_json::Map::new()));
                     
@@ -603,7 +1004,7 @@ pub async fn run_agent_with_events<E: ToolExecutor>(
                     
                     log::info!("Agent calling tool: {} with args: {}", tool_call.function.name, args);
                     
-                    let result = executor.execute(&tool_call.function.name, args.clone()).await;
+                    let result = execute_tool_with_timeout(executor, &tool_call.function.name, args.clone()).await;
                     
                     let (result_str, success) = match &result {
                         Ok(s) => (s.clone(), true),
@@ -632,6 +1033,13 @@ pub async fn run_agent_with_events<E: ToolExecutor>(
                     messages.push(AgentMessage::tool_result(&tool_call.id, &result_str));
                 }
                 
+                // Out of tool calls: tell the model to wrap up
+                if all_tool_calls.len() >= max_tool_calls {
+                    log::warn!("Agent reached its tool call budget of {}", max_tool_calls);
+                    budget_exhausted = true;
+                    messages.push(AgentMessage::user(&tool_budget_exhausted_message(max_tool_calls)));
+                }
+                
                 // Continue the loop to get the next response
                 continue;
             }
@@ -639,12 +1047,14 @@ pub async fn run_agent_with_events<E: ToolExecutor>(
         
         // No tool calls - we have the final response
         let final_response = response.content;
+        let finalized = finalize_run(executor, &final_response, &all_tool_calls).await;
         
         let result = AgentResult {
             final_response,
             tool_calls_made: all_tool_calls,
             iterations,
             token_usage: Some(cumulative_usage),
+            finalized,
         };
         
         // Emit completed event
@@ -690,4 +1100,401 @@ mod tests {
         assert_eq!(tool_result.role, "tool");
         assert_eq!(tool_result.tool_call_id, Some("call_123".to_string()));
     }
+
+    /// Calls search_notes once, then answers with the tool result it was given
+    struct MockSearchClient {
+        requests: std::sync::Mutex<Vec<ChatRequest>>,
+    }
+
+    #[async_trait]
+    impl LlmClient for MockSearchClient {
+        async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LlmError> {
+            self.requests.lock().unwrap().push(request.clone());
+            let last = request.messages.last().unwrap();
+            if matches!(last.role, MessageRole::Tool) {
+                return Ok(ChatResponse {
+                    content: format!("Based on your notes: {}", last.content.clone().unwrap_or_default()),
+                    thinking: None,
+                    thinking_blocks: Vec::new(),
+                    tool_calls: None,
+                    finish_reason: "stop".to_string(),
+                    usage: None,
+                });
+            }
+            Ok(ChatResponse {
+                content: String::new(),
+                thinking: None,
+                thinking_blocks: Vec::new(),
+                tool_calls: Some(vec![LlmToolCall {
+                    id: "call_1".to_string(),
+                    call_type: "function".to_string(),
+                    function: super::super::llm::FunctionCall {
+                        name: "search_notes".to_string(),
+                        arguments: r#"{"query":"rust"}"#.to_string(),
+                    },
+                    thought_signature: None,
+                }]),
+                finish_reason: "tool_calls".to_string(),
+                usage: None,
+            })
+        }
+
+        async fn chat_stream(
+            &self,
+            _request: ChatRequest,
+        ) -> Result<tokio::sync::mpsc::Receiver<super::super::llm::StreamEvent>, LlmError> {
+            Err(LlmError::NotConfigured("streaming not supported by mock".to_string()))
+        }
+    }
+
+    struct MockSearchExecutor;
+
+    #[async_trait]
+    impl ToolExecutor for MockSearchExecutor {
+        async fn execute(&self, name: &str, args: Value) -> Result<String, String> {
+            assert_eq!(name, "search_notes");
+            Ok(format!("Found 'Ownership' for {}", args["query"].as_str().unwrap_or_default()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_agent_with_messages_feeds_tool_results_back() {
+        let client = MockSearchClient { requests: std::sync::Mutex::new(Vec::new()) };
+        let messages = vec![
+            AgentMessage::system("You are helpful"),
+            AgentMessage::user("Hi"),
+            AgentMessage::assistant("Hello! How can I help?"),
+            AgentMessage::user("What do my notes say about rust?"),
+        ];
+        let tools = vec![ToolDefinition::function(
+            "search_notes",
+            "Search notes",
+            serde_json::json!({ "type": "object", "properties": { "query": { "type": "string" } } }),
+        )];
+
+        let result = run_agent_with_messages(
+            &client,
+            "mock-model",
+            messages,
+            tools,
+            &MockSearchExecutor,
+            5,
+            &ReasoningSettings::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.final_response, "Based on your notes: Found 'Ownership' for rust");
+        assert_eq!(result.tool_calls_made.len(), 1);
+        assert_eq!(result.iterations, 2);
+
+        let requests = client.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        // Earlier turns are sent along, and tools are attached
+        assert_eq!(requests[0].messages.len(), 4);
+        assert!(requests[0].tools.is_some());
+        // The second request carries the assistant tool call and its result
+        let followup = &requests[1].messages;
+        assert_eq!(followup.len(), 6);
+        assert_eq!(followup[5].tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(followup[5].content.as_deref(), Some("Found 'Ownership' for rust"));
+    }
+
+    #[tokio::test]
+    async fn test_run_agent_with_messages_applies_reasoning_settings() {
+        let client = MockSearchClient { requests: std::sync::Mutex::new(Vec::new()) };
+        let reasoning = ReasoningSettings {
+            reasoning_effort: Some("high".to_string()),
+            thinking_budget: Some(4096),
+        };
+
+        run_agent_with_messages(
+            &client,
+            "mock-model",
+            vec![AgentMessage::user("What do my notes say about rust?")],
+            vec![],
+            &MockSearchExecutor,
+            5,
+            &reasoning,
+        )
+        .await
+        .unwrap();
+
+        // Every request in the loop carries the conversation's settings
+        let requests = client.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        for request in requests.iter() {
+            assert!(request.enable_reasoning);
+            assert_eq!(request.reasoning_effort.as_deref(), Some("high"));
+            assert_eq!(request.thinking_budget, Some(4096));
+        }
+    }
+
+    /// Never finishes a search within its (short) timeout
+    struct SlowSearchExecutor;
+
+    #[async_trait]
+    impl ToolExecutor for SlowSearchExecutor {
+        async fn execute(&self, _name: &str, _args: Value) -> Result<String, String> {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok("too late".to_string())
+        }
+
+        fn tool_timeout(&self) -> Duration {
+            Duration::from_millis(50)
+        }
+    }
+
+    /// Blocks its thread well past its timeout, like a slow database query
+    struct BlockingSearchExecutor;
+
+    #[async_trait]
+    impl ToolExecutor for BlockingSearchExecutor {
+        async fn execute(&self, _name: &str, _args: Value) -> Result<String, String> {
+            run_blocking_tool(|| {
+                std::thread::sleep(Duration::from_millis(500));
+                Ok("too late".to_string())
+            })
+            .await
+        }
+
+        fn tool_timeout(&self) -> Duration {
+            Duration::from_millis(50)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blocking_tool_times_out() {
+        let started = std::time::Instant::now();
+        let result = execute_tool_with_timeout(&BlockingSearchExecutor, "search_notes", Value::Null).await;
+
+        assert!(result.unwrap_err().contains("tool_timeout"));
+        assert!(started.elapsed() < Duration::from_millis(400), "took {:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_tool_timeout_is_reported_and_run_continues() {
+        let client = MockSearchClient { requests: std::sync::Mutex::new(Vec::new()) };
+        let tools = vec![ToolDefinition::function(
+            "search_notes",
+            "Search notes",
+            serde_json::json!({ "type": "object", "properties": { "query": { "type": "string" } } }),
+        )];
+
+        let result = run_agent_with_client(&client, "mock-model", "You are helpful", "Search rust", tools, &SlowSearchExecutor, 5)
+            .await
+            .unwrap();
+
+        // The model got the timeout as the tool result and still answered
+        assert_eq!(result.iterations, 2);
+        assert_eq!(result.tool_calls_made.len(), 1);
+        assert!(result.tool_calls_made[0].result.contains("tool_timeout"));
+        assert!(result.final_response.starts_with("Based on your notes: Error:"));
+        assert!(result.final_response.contains("did not finish within"));
+    }
+
+    /// Keeps calling search_notes until told to stop calling tools
+    struct MockRunawayClient {
+        requests: std::sync::Mutex<Vec<ChatRequest>>,
+    }
+
+    #[async_trait]
+    impl LlmClient for MockRunawayClient {
+        async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LlmError> {
+            let mut requests = self.requests.lock().unwrap();
+            requests.push(request.clone());
+            if request.tool_choice.as_deref() == Some("none") {
+                return Ok(ChatResponse {
+                    content: "Final answer".to_string(),
+                    thinking: None,
+                    thinking_blocks: Vec::new(),
+                    tool_calls: None,
+                    finish_reason: "stop".to_string(),
+                    usage: None,
+                });
+            }
+            // Two calls per response, so the budget runs out mid-batch
+            let call = |i: usize| LlmToolCall {
+                id: format!("call_{}_{}", requests.len(), i),
+                call_type: "function".to_string(),
+                function: super::super::llm::FunctionCall {
+                    name: "search_notes".to_string(),
+                    arguments: r#"{"query":"more"}"#.to_string(),
+                },
+                thought_signature: None,
+            };
+            Ok(ChatResponse {
+                content: String::new(),
+                thinking: None,
+                thinking_blocks: Vec::new(),
+                tool_calls: Some(vec![call(0), call(1)]),
+                finish_reason: "tool_calls".to_string(),
+                usage: None,
+            })
+        }
+
+        async fn chat_stream(
+            &self,
+            _request: ChatRequest,
+        ) -> Result<tokio::sync::mpsc::Receiver<StreamEvent>, LlmError> {
+            Err(LlmError::NotConfigured("streaming not supported by mock".to_string()))
+        }
+    }
+
+    struct BudgetedSearchExecutor;
+
+    #[async_trait]
+    impl ToolExecutor for BudgetedSearchExecutor {
+        async fn execute(&self, _name: &str, _args: Value) -> Result<String, String> {
+            Ok("More results".to_string())
+        }
+
+        fn max_tool_calls(&self) -> usize {
+            3
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_budget_halts_runaway_agent() {
+        let client = MockRunawayClient { requests: std::sync::Mutex::new(Vec::new()) };
+        let tools = vec![ToolDefinition::function(
+            "search_notes",
+            "Search notes",
+            serde_json::json!({ "type": "object", "properties": { "query": { "type": "string" } } }),
+        )];
+
+        let result = run_agent_with_client(&client, "mock-model", "You are helpful", "Research", tools, &BudgetedSearchExecutor, 50)
+            .await
+            .unwrap();
+
+        assert_eq!(result.final_response, "Final answer");
+        assert_eq!(result.tool_calls_made.len(), 3);
+        assert_eq!(result.iterations, 3);
+
+        let requests = client.requests.lock().unwrap();
+        let last = requests.last().unwrap();
+        assert_eq!(last.tool_choice.as_deref(), Some("none"));
+        // The fourth call was answered without running, then the model was told to finish
+        let skipped = last.messages.iter().filter(|m| m.content.as_deref() == Some(TOOL_BUDGET_SKIPPED_RESULT)).count();
+        assert_eq!(skipped, 1);
+        assert_eq!(
+            last.messages.last().unwrap().content.as_deref(),
+            Some(tool_budget_exhausted_message(3).as_str())
+        );
+    }
+
+    /// Stages search results in a draft that only finalize commits
+    struct DraftingExecutor {
+        draft: std::sync::Mutex<Vec<String>>,
+        committed: std::sync::Mutex<Option<String>>,
+    }
+
+    #[async_trait]
+    impl ToolExecutor for DraftingExecutor {
+        async fn execute(&self, _name: &str, args: Value) -> Result<String, String> {
+            let query = args["query"].as_str().unwrap_or_default().to_string();
+            self.draft.lock().unwrap().push(query);
+            Ok("Staged".to_string())
+        }
+
+        async fn finalize(
+            &self,
+            final_response: &str,
+            tool_calls: &[ToolCallRecord],
+        ) -> Result<Option<String>, String> {
+            let draft = self.draft.lock().unwrap().join(",");
+            *self.committed.lock().unwrap() = Some(format!("{} | {}", draft, final_response));
+            Ok(Some(format!("Committed draft after {} tool calls", tool_calls.len())))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_finalize_commits_work_when_model_ends_without_saving() {
+        let client = MockSearchClient { requests: std::sync::Mutex::new(Vec::new()) };
+        let executor = DraftingExecutor {
+            draft: std::sync::Mutex::new(Vec::new()),
+            committed: std::sync::Mutex::new(None),
+        };
+        let tools = vec![ToolDefinition::function(
+            "search_notes",
+            "Search notes",
+            serde_json::json!({ "type": "object", "properties": { "query": { "type": "string" } } }),
+        )];
+
+        let result = run_agent_with_client(&client, "mock-model", "You are helpful", "Draft", tools, &executor, 5)
+            .await
+            .unwrap();
+
+        // The model never called a save tool, so the hook committed the draft
+        assert_eq!(result.finalized.as_deref(), Some("Committed draft after 1 tool calls"));
+        assert_eq!(
+            executor.committed.lock().unwrap().as_deref(),
+            Some("rust | Based on your notes: Staged")
+        );
+    }
+
+    /// Streams a reply in several content chunks
+    struct MockChunkStreamClient;
+
+    #[async_trait]
+    impl LlmClient for MockChunkStreamClient {
+        async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, LlmError> {
+            Err(LlmError::NotConfigured("non-streaming not supported by mock".to_string()))
+        }
+
+        async fn chat_stream(
+            &self,
+            _request: ChatRequest,
+        ) -> Result<tokio::sync::mpsc::Receiver<StreamEvent>, LlmError> {
+            let (tx, rx) = tokio::sync::mpsc::channel(16);
+            for delta in ["The note ", "covers ", "ownership."] {
+                tx.send(StreamEvent::Content { delta: delta.to_string() }).await.unwrap();
+            }
+            tx.send(StreamEvent::Usage { prompt_tokens: 10, completion_tokens: 5 }).await.unwrap();
+            tx.send(StreamEvent::Done { finish_reason: "stop".to_string() }).await.unwrap();
+            Ok(rx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streaming_progress_emits_chunks_before_completion() {
+        let events = std::sync::Mutex::new(Vec::new());
+        let emit = |progress: AgentProgress| events.lock().unwrap().push(progress);
+
+        let result = run_agent_with_progress_and_client(
+            &MockChunkStreamClient,
+            &emit,
+            "exec-1",
+            "Summarization",
+            "mock-model",
+            "Summarize",
+            "Some text",
+            vec![],
+            &MockSearchExecutor,
+            5,
+            None,
+            true,
+        )
+        .await
+        .unwrap();
+
+        // The final structured result is still returned
+        assert_eq!(result.final_response, "The note covers ownership.");
+        assert_eq!(result.token_usage.unwrap().total_tokens, 15);
+
+        let events = events.lock().unwrap();
+        let chunks: Vec<&str> = events
+            .iter()
+            .filter_map(|e| match e {
+                AgentProgress::ContentChunk { content } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(chunks, vec!["The note ", "covers ", "ownership."]);
+
+        let first_chunk = events.iter().position(|e| matches!(e, AgentProgress::ContentChunk { .. })).unwrap();
+        let completed = events.iter().position(|e| matches!(e, AgentProgress::Completed { .. })).unwrap();
+        assert!(first_chunk < completed);
+        assert_eq!(completed, events.len() - 1);
+    }
 }
@@ -4,6 +4,7 @@
 //! text content from those attachments, enabling them to be included in embeddings.
 
 use crate::ai::tools::read_attachment::extract_text_from_attachment;
+use crate::sidecar::ocr;
 use crate::vault;
 use regex::Regex;
 use std::path::Path;
@@ -25,7 +26,8 @@ pub struct AttachmentReference {
 /// - `[text](../attachments/file.pdf)`
 /// - `![alt](../attachments/image.png)`
 ///
-/// Returns only document types that can have text extracted (excludes images)
+/// Returns only types that can have text extracted. Images are included only
+/// when OCR is available.
 pub fn parse_attachment_references(content: &str) -> Vec<AttachmentReference> {
     // Match ../attachments/ paths in markdown links and images
     // Pattern: ../attachments/ followed by any non-whitespace, non-closing chars
@@ -33,6 +35,7 @@ pub fn parse_attachment_references(content: &str) -> Vec<AttachmentReference> {
     
     let mut references = Vec::new();
     let mut seen = std::collections::HashSet::new();
+    let ocr_available = ocr::is_ocr_available();
     
     for cap in re.captures_iter(content) {
         let filename = cap[1].to_string();
@@ -43,14 +46,17 @@ pub fn parse_attachment_references(content: &str) -> Vec<AttachmentReference> {
         }
         seen.insert(filename.clone());
         
-        // Skip image files (they can't be text-extracted)
+        // Skip image files unless they can be OCR'd
         let ext = Path::new(&filename)
             .extension()
             .and_then(|e| e.to_str())
             .map(|e| e.to_lowercase())
             .unwrap_or_default();
         
-        if matches!(ext.as_str(), "png" | "jpg" | "jpeg" | "gif" | "webp" | "svg" | "ico" | "bmp") {
+        if matches!(ext.as_str(), "svg" | "ico") {
+            continue;
+        }
+        if ocr::is_ocr_image_extension(&ext) && !ocr_available {
             continue;
         }
         
@@ -150,12 +156,13 @@ Some text with inline path ../attachments/notes.docx mentioned.
         
         let refs = parse_attachment_references(content);
         
-        // Should find PDF, XLSX, DOCX but not JPG (image)
-        assert_eq!(refs.len(), 3);
+        // Should find PDF, XLSX, DOCX, and the JPG only when it can be OCR'd
+        let ocr_available = ocr::is_ocr_available();
+        assert_eq!(refs.len(), if ocr_available { 4 } else { 3 });
         assert!(refs.iter().any(|r| r.filename == "report.pdf"));
         assert!(refs.iter().any(|r| r.filename == "data.xlsx"));
         assert!(refs.iter().any(|r| r.filename == "notes.docx"));
-        assert!(!refs.iter().any(|r| r.filename == "photo.jpg"));
+        assert_eq!(refs.iter().any(|r| r.filename == "photo.jpg"), ocr_available);
     }
     
     #[test]
@@ -6,11 +6,12 @@
 
 use async_trait::async_trait;
 use serde_json::Value;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 
-use crate::db::connection::DbPool;
+use crate::db::connection::{get_connection, DbPool};
+use crate::exports::DocumentProvenance;
 
-use super::agent::ToolExecutor;
+use super::agent::{run_blocking_tool, ToolExecutor};
 use super::config::AIProvider;
 use super::tools::{
     // Knowledge retrieval tools
@@ -19,22 +20,25 @@ use super::tools::{
     format_results_for_agent,
     // Tag tools
     execute_get_note_tags, execute_search_by_tag,
+    execute_add_tag_to_note, execute_remove_tag_from_note,
     // Calendar tools
-    execute_get_calendar_events, execute_create_calendar_event,
+    execute_get_calendar_events, execute_get_upcoming_events, execute_create_calendar_event,
+    execute_create_meeting_note,
     // Daily notes
     execute_get_daily_note,
     // Recent notes
     execute_get_recent_notes,
     // Folder tools
     execute_list_folders, execute_get_notes_in_folder,
+    execute_create_folder, execute_move_note_to_folder,
     // Cross-note connection tools
     execute_get_related_notes, execute_get_notes_sharing_tags,
     // Export tools
     execute_export_notes_pdf, execute_export_notes_docx, execute_export_selection_xlsx,
     // Content creation
-    execute_create_note, execute_append_content_to_note,
+    execute_create_note, execute_append_content_to_note, WriteMode,
     // Document builder
-    get_document_builder_tool_function,
+    get_document_builder_tool_function, execute_add_image, execute_save_document_with_provenance,
     // Config types
     AgentConfig,
 };
@@ -49,6 +53,8 @@ pub struct UnifiedToolExecutor {
     provider: AIProvider,
     config: AgentConfig,
     app_handle: Option<AppHandle>,
+    /// Model the agent runs on, recorded on documents it saves
+    model: Option<String>,
 }
 
 impl UnifiedToolExecutor {
@@ -59,6 +65,7 @@ impl UnifiedToolExecutor {
             provider,
             config,
             app_handle: None,
+            model: None,
         }
     }
 
@@ -69,6 +76,53 @@ impl UnifiedToolExecutor {
             provider,
             config,
             app_handle: Some(app_handle),
+            model: None,
+        }
+    }
+
+    /// Record `model` as the generator of documents saved by this executor
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = Some(model.to_string());
+        self
+    }
+
+    /// Run a synchronous tool against the database on the blocking pool
+    async fn blocking<F>(&self, tool: F) -> Result<String, String>
+    where
+        F: FnOnce(&DbPool) -> Result<String, String> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+        run_blocking_tool(move || tool(&pool)).await
+    }
+
+    /// Bring the search index up to date with the note a tool just wrote
+    ///
+    /// Mirrors what the note commands do after saving. Without an app handle
+    /// there's no search index to update.
+    fn index_written_note(&self, result: &str) {
+        let Some(ref app) = self.app_handle else {
+            return;
+        };
+        let Some(note_id) = written_note_id(result) else {
+            return;
+        };
+        let search_index = app.state::<crate::AppSearchIndex>();
+        let search_guard = search_index.0.read().unwrap();
+        let Some(ref index) = *search_guard else {
+            return;
+        };
+
+        let note = get_connection(&self.pool)
+            .map_err(|e| e.to_string())
+            .and_then(|conn| crate::db::notes::get_note(&conn, &note_id).map_err(|e| e.to_string()));
+        match note {
+            Ok(Some(note)) => {
+                if let Err(e) = index.update_note(&note.id, &note.title, note.content.as_deref()) {
+                    log::warn!("[UnifiedToolExecutor] Failed to index note {}: {}", note.id, e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("[UnifiedToolExecutor] Failed to load note {} for indexing: {}", note_id, e),
         }
     }
 
@@ -128,10 +182,12 @@ impl UnifiedToolExecutor {
             .get("content")
             .and_then(|v| v.as_str())
             .ok_or("Missing 'content' argument")?;
+        let mode = WriteMode::from_args(&args)?;
 
         Ok(serde_json::json!({
             "success": true,
             "content": content,
+            "mode": mode,
             "message": "Content ready for insertion"
         })
         .to_string())
@@ -168,26 +224,32 @@ impl ToolExecutor for UnifiedToolExecutor {
             // Knowledge retrieval tools
             "search_notes" => execute_search_notes(&self.pool, &self.provider, args).await,
             "search_url_embeddings" => execute_search_url_embeddings(&self.pool, args).await,
-            "read_note" => execute_read_note(&self.pool, args),
-            "get_note_links" => execute_get_note_links(&self.pool, args),
-            "read_url_content" => execute_read_url_content(&self.pool, args),
-            "get_note_tags" => execute_get_note_tags(&self.pool, args),
-            "search_by_tag" => execute_search_by_tag(&self.pool, args),
-            "get_related_notes" => execute_get_related_notes(&self.pool, args),
-            "get_notes_sharing_tags" => execute_get_notes_sharing_tags(&self.pool, args),
-            "get_calendar_events" => execute_get_calendar_events(&self.pool, args),
-            "get_daily_note" => execute_get_daily_note(&self.pool, args),
-            "get_recent_notes" => execute_get_recent_notes(&self.pool, args),
-            "list_folders" => execute_list_folders(&self.pool, args),
-            "get_notes_in_folder" => execute_get_notes_in_folder(&self.pool, args),
+            "read_note" => self.blocking(move |pool| execute_read_note(pool, args)).await,
+            "get_note_links" => self.blocking(move |pool| execute_get_note_links(pool, args)).await,
+            "read_url_content" => self.blocking(move |pool| execute_read_url_content(pool, args)).await,
+            "get_note_tags" => self.blocking(move |pool| execute_get_note_tags(pool, args)).await,
+            "search_by_tag" => self.blocking(move |pool| execute_search_by_tag(pool, args)).await,
+            "get_related_notes" => self.blocking(move |pool| execute_get_related_notes(pool, args)).await,
+            "get_notes_sharing_tags" => self.blocking(move |pool| execute_get_notes_sharing_tags(pool, args)).await,
+            "get_calendar_events" => self.blocking(move |pool| execute_get_calendar_events(pool, args)).await,
+            "get_upcoming_events" => self.blocking(move |pool| execute_get_upcoming_events(pool, args)).await,
+            "get_daily_note" => self.blocking(move |pool| execute_get_daily_note(pool, args)).await,
+            "get_recent_notes" => self.blocking(move |pool| execute_get_recent_notes(pool, args)).await,
+            "list_folders" => self.blocking(move |pool| execute_list_folders(pool, args)).await,
+            "get_notes_in_folder" => self.blocking(move |pool| execute_get_notes_in_folder(pool, args)).await,
             "web_search" => self.web_search(args).await,
             
             // Content creation tools
             "create_mermaid" => self.create_mermaid(args),
             "write_content" => self.write_content(args),
-            "create_note" => execute_create_note(&self.pool, args),
+            "create_note" => {
+                let result = self.blocking(move |pool| execute_create_note(pool, args)).await?;
+                self.index_written_note(&result);
+                Ok(result)
+            }
             "append_content_to_note" => {
-                let result = execute_append_content_to_note(&self.pool, args)?;
+                let result = self.blocking(move |pool| execute_append_content_to_note(pool, args)).await?;
+                self.index_written_note(&result);
                 
                 // Emit event to notify frontend to refresh the note
                 if let Some(ref app) = self.app_handle {
@@ -205,29 +267,70 @@ impl ToolExecutor for UnifiedToolExecutor {
                 
                 Ok(result)
             }
-            "create_calendar_event" => execute_create_calendar_event(&self.pool, args),
+            "create_calendar_event" => self.blocking(move |pool| execute_create_calendar_event(pool, args)).await,
+            "create_meeting_note" => {
+                let result = self.blocking(move |pool| execute_create_meeting_note(pool, args)).await?;
+                self.index_written_note(&result);
+                Ok(result)
+            }
+            "add_tag_to_note" => self.blocking(move |pool| execute_add_tag_to_note(pool, args)).await,
+            "remove_tag_from_note" => self.blocking(move |pool| execute_remove_tag_from_note(pool, args)).await,
+            "create_folder" => self.blocking(move |pool| execute_create_folder(pool, args)).await,
+            "move_note_to_folder" => self.blocking(move |pool| execute_move_note_to_folder(pool, args)).await,
             
             // Export tools
-            "export_notes_pdf" => execute_export_notes_pdf(&self.pool, args),
-            "export_notes_docx" => execute_export_notes_docx(&self.pool, args),
-            "export_selection_xlsx" => execute_export_selection_xlsx(&self.pool, args),
+            "export_notes_pdf" => self.blocking(move |pool| execute_export_notes_pdf(pool, args)).await,
+            "export_notes_docx" => self.blocking(move |pool| execute_export_notes_docx(pool, args)).await,
+            "export_selection_xlsx" => self.blocking(move |pool| execute_export_selection_xlsx(pool, args)).await,
             
             // Document builder tools
-            "create_document" | "add_section" | "add_table" | "save_document" | "cancel_document" => {
+            "save_document" => {
+                let provenance = DocumentProvenance {
+                    model: self.model.clone(),
+                    ..Default::default()
+                };
+                run_blocking_tool(move || execute_save_document_with_provenance(&args, &provenance)).await
+            }
+            "create_document" | "add_section" | "add_table" | "cancel_document" => {
                 if let Some(tool_fn) = get_document_builder_tool_function(name) {
-                    tool_fn(args)
+                    run_blocking_tool(move || tool_fn(args)).await
                 } else {
                     Err(format!("Document builder tool not found: {}", name))
                 }
             }
+            "add_image" => execute_add_image(&args).await,
             
             _ => Err(format!("Unknown tool: {}", name)),
         }
     }
+
+    fn tool_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.config.tool_timeout_secs.max(1))
+    }
+}
+
+/// ID of the note a note-writing tool reports, as `note.id` or `note_id`
+fn written_note_id(result: &str) -> Option<String> {
+    let parsed: Value = serde_json::from_str(result).ok()?;
+    parsed
+        .pointer("/note/id")
+        .or_else(|| parsed.get("note_id"))
+        .and_then(|v| v.as_str())
+        .map(String::from)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::written_note_id;
+
+    #[test]
+    fn test_written_note_id() {
+        assert_eq!(written_note_id(r#"{"note": {"id": "n1"}}"#).as_deref(), Some("n1"));
+        assert_eq!(written_note_id(r#"{"note_id": "n2", "title": "Standup"}"#).as_deref(), Some("n2"));
+        assert_eq!(written_note_id(r#"{"success": true}"#), None);
+        assert_eq!(written_note_id("not json"), None);
+    }
+
     #[test]
     fn test_create_mermaid_with_code() {
         // We can't easily test without a real pool, but we can test the mermaid logic
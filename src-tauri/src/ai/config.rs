@@ -3,10 +3,12 @@
 //! Handles storage and retrieval of AI provider settings,
 //! including API keys, endpoints, and model configurations.
 
+use super::llm::{ChatMessage, ChatRequest, ClientTimeouts, LlmClient, LlmError};
 use r2d2::PooledConnection;
 use r2d2_sqlite::SqliteConnectionManager;
 use serde::{Deserialize, Serialize};
 use std::env;
+use std::time::Duration;
 
 /// Type of AI provider
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -19,6 +21,8 @@ pub enum ProviderType {
     Ollama,
     LMStudio,
     VLLM,
+    /// In-process embedding models, no server or API key needed
+    Local,
     Custom,
 }
 
@@ -51,6 +55,33 @@ pub struct AIProvider {
     /// Primarily useful for local providers where context size may vary
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context_length: Option<u32>,
+    /// Seconds to wait for a whole LLM request before giving up (default 300)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_timeout_secs: Option<f64>,
+    /// Seconds to wait for the connection to be established (default 10)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<f64>,
+    /// Seconds a streamed response may go without new tokens before it's
+    /// treated as stalled (default 90)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_idle_timeout_secs: Option<f64>,
+}
+
+impl AIProvider {
+    /// Timeouts for this provider's LLM client, falling back to the defaults
+    /// for unset or invalid values
+    pub fn client_timeouts(&self) -> ClientTimeouts {
+        let to_duration = |secs: Option<f64>| {
+            secs.filter(|s| *s > 0.0)
+                .and_then(|s| Duration::try_from_secs_f64(s).ok())
+        };
+        let defaults = ClientTimeouts::default();
+        ClientTimeouts {
+            request: to_duration(self.request_timeout_secs).unwrap_or(defaults.request),
+            connect: to_duration(self.connect_timeout_secs).unwrap_or(defaults.connect),
+            stream_idle: to_duration(self.stream_idle_timeout_secs).unwrap_or(defaults.stream_idle),
+        }
+    }
 }
 
 impl Default for AIProvider {
@@ -65,6 +96,9 @@ impl Default for AIProvider {
             models: Vec::new(),
             selected_model: None,
             context_length: None,
+            request_timeout_secs: None,
+            connect_timeout_secs: None,
+            stream_idle_timeout_secs: None,
         }
     }
 }
@@ -110,6 +144,11 @@ impl EmbeddingConfig {
     pub fn full_model_id(&self) -> String {
         format!("{}/{}", self.provider, self.model)
     }
+
+    /// The dimension new embeddings must match, or `None` if it hasn't been set
+    pub fn expected_dimension(&self) -> Option<u32> {
+        (self.dimension > 0).then_some(self.dimension)
+    }
 }
 
 /// Complete AI configuration
@@ -121,6 +160,9 @@ pub struct AIConfig {
     /// ID of the default provider to use
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default_provider: Option<String>,
+    /// Providers to try in order when the default one cannot be reached
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_provider_ids: Vec<String>,
     /// Embedding configuration
     #[serde(default)]
     pub embedding: EmbeddingConfig,
@@ -129,7 +171,7 @@ pub struct AIConfig {
 impl AIConfig {
     /// Create a new config with default providers and curated models
     pub fn new() -> Self {
-        Self {
+        let mut config = Self {
             providers: vec![
                 AIProvider {
                     id: "openai".to_string(),
@@ -154,6 +196,9 @@ impl AIConfig {
                     ],
                     selected_model: None,
                     context_length: None,
+                    request_timeout_secs: None,
+                    connect_timeout_secs: None,
+                    stream_idle_timeout_secs: None,
                 },
                 AIProvider {
                     id: "anthropic".to_string(),
@@ -176,6 +221,9 @@ impl AIConfig {
                     ],
                     selected_model: None,
                     context_length: None,
+                    request_timeout_secs: None,
+                    connect_timeout_secs: None,
+                    stream_idle_timeout_secs: None,
                 },
                 AIProvider {
                     id: "google".to_string(),
@@ -198,6 +246,9 @@ impl AIConfig {
                     ],
                     selected_model: None,
                     context_length: None,
+                    request_timeout_secs: None,
+                    connect_timeout_secs: None,
+                    stream_idle_timeout_secs: None,
                 },
                 AIProvider {
                     id: "ollama".to_string(),
@@ -209,6 +260,9 @@ impl AIConfig {
                     models: Vec::new(), // Will be populated by detection
                     selected_model: None,
                     context_length: None,
+                    request_timeout_secs: None,
+                    connect_timeout_secs: None,
+                    stream_idle_timeout_secs: None,
                 },
                 AIProvider {
                     id: "lmstudio".to_string(),
@@ -220,6 +274,9 @@ impl AIConfig {
                     models: Vec::new(), // Will be populated by detection
                     selected_model: None,
                     context_length: None,
+                    request_timeout_secs: None,
+                    connect_timeout_secs: None,
+                    stream_idle_timeout_secs: None,
                 },
                 AIProvider {
                     id: "vllm".to_string(),
@@ -231,11 +288,39 @@ impl AIConfig {
                     models: Vec::new(), // Will be populated by detection
                     selected_model: None,
                     context_length: None,
+                    request_timeout_secs: None,
+                    connect_timeout_secs: None,
+                    stream_idle_timeout_secs: None,
                 },
             ],
             default_provider: None,
+            fallback_provider_ids: Vec::new(),
             embedding: EmbeddingConfig::default(),
+        };
+
+        // Only offer the offline models when this build can actually run them
+        if super::local_embeddings::is_available() {
+            config.providers.push(AIProvider {
+                id: "local".to_string(),
+                name: "Offline (Embeddings only)".to_string(),
+                provider_type: ProviderType::Local,
+                api_key: None,
+                base_url: None,
+                is_enabled: false,
+                models: vec![
+                    "bge-small-en-v1.5".to_string(),
+                    "all-minilm-l6-v2".to_string(),
+                    "nomic-embed-text-v1.5".to_string(),
+                ],
+                selected_model: None,
+                context_length: None,
+                request_timeout_secs: None,
+                connect_timeout_secs: None,
+                stream_idle_timeout_secs: None,
+            });
         }
+
+        config
     }
 }
 
@@ -382,6 +467,8 @@ pub fn load_ai_config(
 
     match result {
         Ok(json) => {
+            // A saved offline provider is kept even when this build can't run
+            // it; using it reports that the build doesn't include offline embeddings
             serde_json::from_str(&json).map_err(|e| format!("Failed to parse AI config: {}", e))
         }
         Err(rusqlite::Error::QueryReturnedNoRows) => {
@@ -400,6 +487,23 @@ pub struct ProviderTestResult {
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub models: Option<Vec<String>>,
+    /// Outcome of the test completion, when the selected model was checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_status: Option<ModelStatus>,
+}
+
+/// Whether a provider actually serves a given model
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ModelStatus {
+    /// The test completion succeeded
+    Available,
+    /// The provider rejected the credentials
+    AuthFailed,
+    /// The provider doesn't know the model
+    ModelNotFound,
+    /// Any other failure (network, rate limit, server error)
+    Failed,
 }
 
 /// Detect local models available via Ollama
@@ -426,6 +530,7 @@ pub async fn detect_ollama_models(base_url: &str) -> ProviderTestResult {
                         success: true,
                         message: format!("Found {} models", model_names.len()),
                         models: Some(model_names),
+                        model_status: None,
                     };
                 }
             }
@@ -433,17 +538,20 @@ pub async fn detect_ollama_models(base_url: &str) -> ProviderTestResult {
                 success: true,
                 message: "Ollama is running but couldn't list models".to_string(),
                 models: None,
+                model_status: None,
             }
         }
         Ok(response) => ProviderTestResult {
             success: false,
             message: format!("Ollama returned error: {}", response.status()),
             models: None,
+            model_status: None,
         },
         Err(e) => ProviderTestResult {
             success: false,
             message: format!("Could not connect to Ollama: {}", e),
             models: None,
+            model_status: None,
         },
     }
 }
@@ -478,6 +586,7 @@ pub async fn detect_lmstudio(base_url: &str) -> ProviderTestResult {
                         success: true,
                         message: format!("Found {} models", model_names.len()),
                         models: Some(model_names),
+                        model_status: None,
                     };
                 }
             }
@@ -485,21 +594,173 @@ pub async fn detect_lmstudio(base_url: &str) -> ProviderTestResult {
                 success: true,
                 message: "LM Studio is running".to_string(),
                 models: None,
+                model_status: None,
             }
         }
         Ok(response) => ProviderTestResult {
             success: false,
             message: format!("LM Studio returned error: {}", response.status()),
             models: None,
+            model_status: None,
         },
         Err(e) => ProviderTestResult {
             success: false,
             message: format!("Could not connect to LM Studio: {}", e),
             models: None,
+            model_status: None,
         },
     }
 }
 
+/// Base URLs of the cloud providers' model-list endpoints
+const OPENAI_MODELS_URL: &str = "https://api.openai.com/v1/models";
+const ANTHROPIC_MODELS_URL: &str = "https://api.anthropic.com/v1/models";
+const GOOGLE_MODELS_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+
+/// Append `/v1/models` to an OpenAI-compatible base URL
+fn openai_compatible_models_url(base_url: &str) -> String {
+    let base = base_url.trim_end_matches('/');
+    if base.ends_with("/v1") {
+        format!("{}/models", base)
+    } else {
+        format!("{}/v1/models", base)
+    }
+}
+
+/// Name fragments of models that can't be used for chat (embeddings, speech, images)
+const NON_CHAT_MODEL_MARKERS: &[&str] = &["embed", "tts", "whisper", "transcribe", "dall-e", "moderation"];
+
+/// Whether a model ID looks usable for chat
+fn is_chat_model(id: &str) -> bool {
+    let id = id.to_lowercase();
+    !NON_CHAT_MODEL_MARKERS.iter().any(|marker| id.contains(marker))
+}
+
+/// Extract model IDs from a provider's model-list response
+///
+/// OpenAI-compatible APIs and Anthropic return `data[].id`, Ollama returns
+/// `models[].name` and Google returns `models[].name` prefixed with `models/`.
+/// Models that can't be used for chat are skipped: Google's by their supported
+/// generation methods, everyone's by name (embedding, speech and image models).
+pub fn parse_model_list(provider_type: &ProviderType, body: &serde_json::Value) -> Option<Vec<String>> {
+    let models: Vec<String> = match provider_type {
+        ProviderType::Ollama => body
+            .get("models")?
+            .as_array()?
+            .iter()
+            .filter_map(|m| m.get("name").and_then(|n| n.as_str()))
+            .map(String::from)
+            .collect(),
+        ProviderType::Google => body
+            .get("models")?
+            .as_array()?
+            .iter()
+            .filter(|m| {
+                m.get("supportedGenerationMethods")
+                    .and_then(|g| g.as_array())
+                    .map(|methods| methods.iter().any(|g| g.as_str() == Some("generateContent")))
+                    .unwrap_or(true)
+            })
+            .filter_map(|m| m.get("name").and_then(|n| n.as_str()))
+            .map(|name| name.trim_start_matches("models/").to_string())
+            .collect(),
+        _ => body
+            .get("data")?
+            .as_array()?
+            .iter()
+            .filter_map(|m| m.get("id").and_then(|n| n.as_str()))
+            .map(String::from)
+            .collect(),
+    };
+
+    Some(models.into_iter().filter(|id| is_chat_model(id)).collect())
+}
+
+/// Query a provider's model-list endpoint
+///
+/// Returns `Ok(None)` when the provider has no model-list endpoint, so the
+/// caller can keep the configured models instead of failing.
+pub async fn fetch_provider_models(provider: &AIProvider) -> Result<Option<Vec<String>>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let api_key = provider.api_key.as_deref().filter(|k| !k.is_empty());
+    let require_key = || api_key.ok_or_else(|| "API key is required".to_string());
+
+    let request = match provider.provider_type {
+        ProviderType::OpenAI => client.get(OPENAI_MODELS_URL).bearer_auth(require_key()?),
+        ProviderType::Anthropic => client
+            .get(ANTHROPIC_MODELS_URL)
+            .query(&[("limit", "1000")])
+            .header("x-api-key", require_key()?)
+            .header("anthropic-version", "2023-06-01"),
+        ProviderType::Google => client
+            .get(GOOGLE_MODELS_URL)
+            .query(&[("key", require_key()?), ("pageSize", "1000")]),
+        ProviderType::Ollama => {
+            let base_url = provider.base_url.as_deref().unwrap_or("http://localhost:11434");
+            client.get(format!("{}/api/tags", base_url.trim_end_matches('/')))
+        }
+        // Local models are a fixed list, there's nothing to query
+        ProviderType::Local => return Ok(None),
+        ProviderType::LMStudio | ProviderType::VLLM | ProviderType::Custom => {
+            let default_url = match provider.provider_type {
+                ProviderType::LMStudio => Some("http://localhost:1234"),
+                ProviderType::VLLM => Some("http://localhost:8000"),
+                _ => None,
+            };
+            let Some(base_url) = provider.base_url.as_deref().or(default_url) else {
+                return Ok(None);
+            };
+            let request = client.get(openai_compatible_models_url(base_url));
+            match api_key {
+                Some(key) => request.bearer_auth(key),
+                None => request,
+            }
+        }
+    };
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Could not connect to {}: {}", provider.name, e))?;
+
+    // Some OpenAI-compatible servers don't implement /models
+    if matches!(
+        response.status(),
+        reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::METHOD_NOT_ALLOWED
+    ) {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("{} returned error {}: {}", provider.name, status, error_text));
+    }
+
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid model list response: {}", e))?;
+
+    Ok(parse_model_list(&provider.provider_type, &body))
+}
+
+/// Replace a provider's model list
+///
+/// Returns false if no provider with that ID is configured.
+pub fn apply_provider_models(config: &mut AIConfig, provider_id: &str, models: Vec<String>) -> bool {
+    match config.providers.iter_mut().find(|p| p.id == provider_id) {
+        Some(provider) => {
+            provider.models = models;
+            true
+        }
+        None => false,
+    }
+}
+
 /// Test connection to a provider
 pub async fn test_provider_connection(provider: &AIProvider, _base_url: &str) -> ProviderTestResult {
     // For local providers, test directly
@@ -516,6 +777,23 @@ pub async fn test_provider_connection(provider: &AIProvider, _base_url: &str) ->
             let base_url = provider.base_url.as_deref().unwrap_or("http://localhost:8000/v1");
             return detect_lmstudio(base_url).await; // VLLM uses OpenAI-compatible API
         }
+        ProviderType::Local => {
+            return if super::local_embeddings::is_available() {
+                ProviderTestResult {
+                    success: true,
+                    message: "Offline embeddings available".to_string(),
+                    models: Some(provider.models.clone()),
+                    model_status: None,
+                }
+            } else {
+                ProviderTestResult {
+                    success: false,
+                    message: "This build doesn't include offline embeddings".to_string(),
+                    models: None,
+                    model_status: None,
+                }
+            };
+        }
         _ => {}
     }
 
@@ -525,6 +803,7 @@ pub async fn test_provider_connection(provider: &AIProvider, _base_url: &str) ->
             success: false,
             message: "API key is required".to_string(),
             models: None,
+            model_status: None,
         };
     }
 
@@ -534,5 +813,226 @@ pub async fn test_provider_connection(provider: &AIProvider, _base_url: &str) ->
         success: true,
         message: "API key configured".to_string(),
         models: Some(provider.models.clone()),
+        model_status: None,
+    }
+}
+
+/// Classify a failed test completion
+pub fn classify_model_error(error: &LlmError) -> ModelStatus {
+    match error {
+        LlmError::MissingApiKey => ModelStatus::AuthFailed,
+        LlmError::ApiError { status: 401 | 403, .. } => ModelStatus::AuthFailed,
+        LlmError::ApiError { status: 404, .. } => ModelStatus::ModelNotFound,
+        // Some OpenAI-compatible servers report unknown models as a bad request
+        LlmError::ApiError { status: 400, message } => {
+            let message = message.to_lowercase();
+            let mentions_missing = ["not found", "not_found", "does not exist", "unknown model"]
+                .iter()
+                .any(|phrase| message.contains(phrase));
+            if message.contains("model") && mentions_missing {
+                ModelStatus::ModelNotFound
+            } else {
+                ModelStatus::Failed
+            }
+        }
+        _ => ModelStatus::Failed,
+    }
+}
+
+/// Check that a model works by sending it a tiny completion
+///
+/// No token limit is sent: reasoning models (GPT-5, the o-series) reject
+/// `max_tokens`, and the prompt keeps the reply short anyway.
+pub async fn check_model_availability(client: &dyn LlmClient, model: &str) -> (ModelStatus, String) {
+    let request = ChatRequest {
+        model: model.to_string(),
+        messages: vec![ChatMessage::user("Reply with OK.")],
+        tools: None,
+        tool_choice: None,
+        max_tokens: None,
+        temperature: None,
+        enable_reasoning: false,
+        reasoning_effort: None,
+        thinking_budget: None,
+        use_prompt_cache: false,
+    };
+
+    match client.chat(request).await {
+        Ok(_) => (ModelStatus::Available, format!("Model {} is available", model)),
+        Err(e) => {
+            let status = classify_model_error(&e);
+            let message = match status {
+                ModelStatus::AuthFailed => format!("Authentication failed: {}", e),
+                ModelStatus::ModelNotFound => format!("Model {} was not found: {}", model, e),
+                _ => format!("Model {} failed: {}", model, e),
+            };
+            (status, message)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::llm::{ChatResponse, StreamEvent};
+    use crate::db::connection::init_test_pool;
+    use async_trait::async_trait;
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    /// Serves "known-model" and answers 404 for anything else, like the hosted APIs do.
+    /// Like reasoning models, it rejects `max_tokens`.
+    struct MockModelClient;
+
+    #[async_trait]
+    impl LlmClient for MockModelClient {
+        async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LlmError> {
+            if request.model != "known-model" {
+                return Err(LlmError::ApiError {
+                    status: 404,
+                    message: format!("The model `{}` does not exist", request.model),
+                });
+            }
+            if request.max_tokens.is_some() {
+                return Err(LlmError::ApiError {
+                    status: 400,
+                    message: "Unsupported parameter: 'max_tokens' is not supported with this model".to_string(),
+                });
+            }
+            Ok(ChatResponse {
+                content: "OK".to_string(),
+                thinking: None,
+                thinking_blocks: Vec::new(),
+                tool_calls: None,
+                finish_reason: "stop".to_string(),
+                usage: None,
+            })
+        }
+
+        async fn chat_stream(&self, _request: ChatRequest) -> Result<mpsc::Receiver<StreamEvent>, LlmError> {
+            Err(LlmError::NotConfigured("streaming not supported by mock".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_model_availability() {
+        let (status, _) = check_model_availability(&MockModelClient, "known-model").await;
+        assert_eq!(status, ModelStatus::Available);
+
+        let (status, message) = check_model_availability(&MockModelClient, "typo-model").await;
+        assert_eq!(status, ModelStatus::ModelNotFound);
+        assert!(message.contains("typo-model"));
+    }
+
+    #[test]
+    fn test_classify_model_error() {
+        let api_error = |status: u16, message: &str| LlmError::ApiError { status, message: message.to_string() };
+
+        assert_eq!(classify_model_error(&LlmError::MissingApiKey), ModelStatus::AuthFailed);
+        assert_eq!(classify_model_error(&api_error(401, "invalid x-api-key")), ModelStatus::AuthFailed);
+        assert_eq!(classify_model_error(&api_error(404, "not_found_error")), ModelStatus::ModelNotFound);
+        assert_eq!(
+            classify_model_error(&api_error(400, "model 'llama9' not found")),
+            ModelStatus::ModelNotFound
+        );
+        assert_eq!(classify_model_error(&api_error(400, "max_tokens too large")), ModelStatus::Failed);
+        assert_eq!(classify_model_error(&api_error(429, "rate limited")), ModelStatus::Failed);
+    }
+
+    #[test]
+    fn test_parse_model_list_formats() {
+        let openai = json!({ "object": "list", "data": [
+            { "id": "gpt-4o" },
+            { "id": "text-embedding-3-small" },
+            { "id": "tts-1-hd" },
+            { "id": "whisper-1" },
+            { "id": "dall-e-3" },
+            { "id": "gpt-4o-mini" }
+        ]});
+        assert_eq!(
+            parse_model_list(&ProviderType::OpenAI, &openai),
+            Some(vec!["gpt-4o".to_string(), "gpt-4o-mini".to_string()])
+        );
+
+        let google = json!({ "models": [
+            { "name": "models/gemini-2.5-pro", "supportedGenerationMethods": ["generateContent"] },
+            { "name": "models/text-embedding-004", "supportedGenerationMethods": ["embedContent"] }
+        ]});
+        assert_eq!(
+            parse_model_list(&ProviderType::Google, &google),
+            Some(vec!["gemini-2.5-pro".to_string()])
+        );
+
+        let ollama = json!({ "models": [{ "name": "llama3.2:latest" }, { "name": "nomic-embed-text:latest" }] });
+        assert_eq!(
+            parse_model_list(&ProviderType::Ollama, &ollama),
+            Some(vec!["llama3.2:latest".to_string()])
+        );
+
+        assert_eq!(parse_model_list(&ProviderType::Anthropic, &json!({ "error": "nope" })), None);
+    }
+
+    #[test]
+    fn test_refreshed_models_are_persisted() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let mut config = load_ai_config(&conn).unwrap();
+
+        let response = json!({ "data": [
+            { "type": "model", "id": "claude-new-model", "display_name": "Claude New" }
+        ]});
+        let models = parse_model_list(&ProviderType::Anthropic, &response).unwrap();
+        assert!(apply_provider_models(&mut config, "anthropic", models));
+        assert!(!apply_provider_models(&mut config, "missing", vec![]));
+        save_ai_config(&conn, &config).unwrap();
+
+        let reloaded = load_ai_config(&conn).unwrap();
+        let anthropic = reloaded.providers.iter().find(|p| p.id == "anthropic").unwrap();
+        assert_eq!(anthropic.models, vec!["claude-new-model".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_saved_offline_provider_is_kept() {
+        let available = crate::ai::local_embeddings::is_available();
+
+        // New configs only offer the offline models when this build can run them
+        let offered = AIConfig::new().providers.iter().any(|p| p.provider_type == ProviderType::Local);
+        assert_eq!(offered, available);
+        assert_eq!(
+            crate::ai::embeddings::get_embedding_models().iter().any(|m| m.provider == "local"),
+            available
+        );
+
+        // A provider saved by a build that had them stays configured
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let mut saved = AIConfig::new();
+        saved.providers.retain(|p| p.provider_type != ProviderType::Local);
+        saved.providers.push(AIProvider {
+            id: "local".to_string(),
+            name: "Offline (Embeddings only)".to_string(),
+            provider_type: ProviderType::Local,
+            api_key: None,
+            base_url: None,
+            is_enabled: true,
+            models: vec!["bge-small-en-v1.5".to_string()],
+            selected_model: None,
+            context_length: None,
+            request_timeout_secs: None,
+            connect_timeout_secs: None,
+            stream_idle_timeout_secs: None,
+        });
+        save_ai_config(&conn, &saved).unwrap();
+
+        let reloaded = load_ai_config(&conn).unwrap();
+        let local = reloaded.providers.iter().find(|p| p.id == "local").unwrap();
+        assert!(local.is_enabled);
+
+        // ...and says why it can't be used when the backend isn't built
+        let result = test_provider_connection(local, "").await;
+        assert_eq!(result.success, available);
+        if !available {
+            assert_eq!(result.message, "This build doesn't include offline embeddings");
+        }
     }
 }
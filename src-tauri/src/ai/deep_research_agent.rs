@@ -23,7 +23,8 @@ use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
 
 use super::agent::{
-    run_agent_with_events, AgentError, CancellationToken, ToolDefinition, ToolExecutor,
+    run_agent_with_events, run_agent_with_streaming_events, AgentError, CancellationToken,
+    ToolCallRecord, ToolDefinition, ToolExecutor,
 };
 use super::config::AIProvider;
 use super::tools::{
@@ -362,6 +363,28 @@ impl ToolExecutor for DeepResearchAgent {
             _ => Err(format!("Unknown tool: {}", name)),
         }
     }
+
+    /// Write the final answer into the note if the model never appended anything
+    async fn finalize(
+        &self,
+        final_response: &str,
+        _tool_calls: &[ToolCallRecord],
+    ) -> Result<Option<String>, String> {
+        if self.get_chunks_appended() > 0 || final_response.trim().is_empty() {
+            return Ok(None);
+        }
+
+        execute_append_to_note(
+            &self.app_handle,
+            &self.execution_id,
+            serde_json::json!({ "content": final_response, "is_final": true }),
+        )?;
+        self.chunks_appended.fetch_add(1, Ordering::SeqCst);
+        Ok(Some(format!(
+            "Appended the final response ({} chars) to the note",
+            final_response.len()
+        )))
+    }
 }
 
 /// Get the tool definitions for the deep research agent
@@ -562,7 +585,7 @@ pub async fn run_deep_research_agent(
 
     agent.emit_progress(DeepResearchProgress::Synthesizing);
 
-    let result = run_agent_with_events(
+    let result = run_agent_with_streaming_events(
         app_handle,
         execution_id,
         "DeepResearch",
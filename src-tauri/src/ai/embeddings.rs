@@ -1,6 +1,7 @@
 //! Embedding generation for AI providers
 //!
-//! Supports both local (Ollama/LM Studio) and cloud (OpenAI, Google) embeddings.
+//! Supports local servers (Ollama/LM Studio), in-process models (fastembed)
+//! and cloud (OpenAI, Google) embeddings.
 //!
 //! NOTE: Contains utility functions for future embedding features.
 
@@ -15,6 +16,12 @@ pub enum EmbeddingError {
     ApiError(String),
     #[error("Empty embedding returned")]
     EmptyEmbedding,
+    #[error("Local embedding error: {0}")]
+    LocalError(String),
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+    #[error("Request too large: {0}")]
+    RequestTooLarge(String),
 }
 
 // ============================================================================
@@ -32,11 +39,12 @@ struct EmbeddingRequest<'a> {
     dimensions: Option<u32>,
 }
 
-/// Input can be a single string
+/// Input can be a single string or an array of strings
 #[derive(Debug, Serialize)]
 #[serde(untagged)]
 enum EmbeddingInput<'a> {
     Single(&'a str),
+    Batch(&'a [String]),
 }
 
 /// Response from the OpenAI-compatible embeddings API
@@ -50,8 +58,8 @@ struct EmbeddingResponse {
 #[derive(Debug, Deserialize)]
 struct EmbeddingData {
     embedding: Vec<f32>,
-    #[serde(rename = "index")]
-    _index: usize,
+    #[serde(default)]
+    index: usize,
 }
 
 #[derive(Debug, Deserialize)]
@@ -92,6 +100,25 @@ struct GoogleEmbedding {
     values: Vec<f32>,
 }
 
+/// Request body for Google's batchEmbedContents endpoint
+#[derive(Debug, Serialize)]
+struct GoogleBatchEmbeddingRequest<'a> {
+    requests: Vec<GoogleBatchEmbeddingItem<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct GoogleBatchEmbeddingItem<'a> {
+    model: String,
+    content: GoogleContent<'a>,
+}
+
+/// Response from Google's batchEmbedContents endpoint
+#[derive(Debug, Deserialize)]
+struct GoogleBatchEmbeddingResponse {
+    #[serde(default)]
+    embeddings: Vec<GoogleEmbedding>,
+}
+
 /// Result of generating an embedding
 #[derive(Debug, Clone, Serialize)]
 pub struct EmbeddingResult {
@@ -151,7 +178,7 @@ async fn generate_google_embedding(
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
-        return Err(EmbeddingError::ApiError(format!(
+        return Err(status_error(status, format!(
             "Google API error - Status {}: {}",
             status, error_text
         )));
@@ -171,6 +198,67 @@ async fn generate_google_embedding(
     })
 }
 
+/// Generate embeddings for several texts with one request to Google's batchEmbedContents endpoint
+async fn generate_google_embeddings_batch(
+    texts: &[String],
+    model_name: &str,
+    api_key: Option<&str>,
+) -> Result<Vec<EmbeddingResult>, EmbeddingError> {
+    let api_key = api_key.filter(|k| !k.is_empty()).ok_or_else(|| {
+        EmbeddingError::ApiError("Google API key is required for Gemini embeddings".to_string())
+    })?;
+    
+    let url = format!(
+        "https://generativelanguage.googleapis.com/v1beta/models/{}:batchEmbedContents",
+        model_name
+    );
+    
+    log::info!("[Google Embedding] Sending {} inputs to: {}", texts.len(), url);
+    
+    let request = GoogleBatchEmbeddingRequest {
+        requests: texts
+            .iter()
+            .map(|text| GoogleBatchEmbeddingItem {
+                model: format!("models/{}", model_name),
+                content: GoogleContent {
+                    parts: vec![GooglePart { text }],
+                },
+            })
+            .collect(),
+    };
+    
+    let response = reqwest::Client::new()
+        .post(&url)
+        .header("x-goog-api-key", api_key)
+        .json(&request)
+        .send()
+        .await?;
+    
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(status_error(status, format!(
+            "Google API error - Status {}: {}",
+            status, error_text
+        )));
+    }
+    
+    let response: GoogleBatchEmbeddingResponse = response.json().await?;
+    if response.embeddings.len() != texts.len() {
+        return Err(EmbeddingError::ApiError(format!(
+            "Expected {} embeddings, got {}",
+            texts.len(),
+            response.embeddings.len()
+        )));
+    }
+    
+    response
+        .embeddings
+        .into_iter()
+        .map(|e| embedding_result(e.values, model_name.to_string()))
+        .collect()
+}
+
 /// Generate an embedding directly using provider URL
 /// 
 /// The model parameter should be in "provider/model" format (e.g., "lmstudio/model-name").
@@ -187,10 +275,223 @@ pub async fn generate_embedding_direct(
         return generate_google_embedding(text, model_name, api_key).await;
     }
     
-    let client = reqwest::Client::new();
+    // Local models run in-process, no provider URL needed
+    if let Some(model_name) = model.strip_prefix("local/") {
+        return super::local_embeddings::generate_local_embedding(text, model_name).await;
+    }
+    
+    let (url, model_name) = resolve_openai_endpoint(model, provider_url)?;
+    
+    log::info!("[Embedding Direct] Sending request to: {} with model: {}", url, model_name);
     
-    // Determine URL and model name based on provider prefix (OpenAI-compatible providers)
-    let (url, model_name) = if let Some(base_url) = provider_url {
+    let response = post_openai_embeddings(&url, model_name, EmbeddingInput::Single(text), api_key).await?;
+    
+    let data = response
+        .data
+        .into_iter()
+        .next()
+        .ok_or(EmbeddingError::EmptyEmbedding)?;
+
+    if data.embedding.is_empty() {
+        return Err(EmbeddingError::EmptyEmbedding);
+    }
+
+    Ok(EmbeddingResult {
+        dimension: data.embedding.len(),
+        embedding: data.embedding,
+        model: response.model,
+        tokens_used: response.usage.map(|u| u.total_tokens),
+    })
+}
+
+/// Maximum number of texts sent in a single batched request
+pub const MAX_BATCH_INPUTS: usize = 64;
+/// Maximum total characters sent in a single batched request
+const MAX_BATCH_CHARS: usize = 200_000;
+
+/// Generate embeddings for several texts, batching them into as few requests as possible
+///
+/// Texts are split into requests of at most `MAX_BATCH_INPUTS` inputs and
+/// `MAX_BATCH_CHARS` characters. Results are returned in the same order as `texts`.
+/// Takes the same model, provider_url and api_key arguments as `generate_embedding_direct`.
+pub async fn generate_embeddings_batch(
+    texts: &[String],
+    model: &str,
+    provider_url: Option<&str>,
+    api_key: Option<&str>,
+) -> Result<Vec<EmbeddingResult>, EmbeddingError> {
+    let mut results = Vec::with_capacity(texts.len());
+
+    // Local models run in-process, there's no request overhead to save
+    if let Some(model_name) = model.strip_prefix("local/") {
+        for text in texts {
+            results.push(super::local_embeddings::generate_local_embedding(text, model_name).await?);
+        }
+        return Ok(results);
+    }
+
+    for batch in split_batches(texts) {
+        let batch_results =
+            retry_rate_limited(|| send_embeddings_batch(batch, model, provider_url, api_key)).await?;
+        results.extend(batch_results);
+    }
+
+    Ok(results)
+}
+
+/// Send one batch of texts in a single request
+async fn send_embeddings_batch(
+    batch: &[String],
+    model: &str,
+    provider_url: Option<&str>,
+    api_key: Option<&str>,
+) -> Result<Vec<EmbeddingResult>, EmbeddingError> {
+    if let Some(model_name) = model.strip_prefix("google/") {
+        return generate_google_embeddings_batch(batch, model_name, api_key).await;
+    }
+
+    let (url, model_name) = resolve_openai_endpoint(model, provider_url)?;
+    log::info!("[Embedding Batch] Sending {} inputs to: {} with model: {}", batch.len(), url, model_name);
+
+    let response = post_openai_embeddings(&url, model_name, EmbeddingInput::Batch(batch), api_key).await?;
+    let mut data = response.data;
+    if data.len() != batch.len() {
+        return Err(EmbeddingError::ApiError(format!(
+            "Expected {} embeddings, got {}",
+            batch.len(),
+            data.len()
+        )));
+    }
+    // Providers may return the vectors out of order
+    data.sort_by_key(|d| d.index);
+
+    data.into_iter()
+        .map(|d| embedding_result(d.embedding, response.model.clone()))
+        .collect()
+}
+
+/// Number of times a rate-limited request is retried before giving up
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+/// Wait before the first retry of a rate-limited request, doubled on each retry
+const RATE_LIMIT_BACKOFF: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Run a request, backing off and retrying while the provider answers 429
+async fn retry_rate_limited<T, F, Fut>(mut send: F) -> Result<T, EmbeddingError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, EmbeddingError>>,
+{
+    let mut delay = RATE_LIMIT_BACKOFF;
+    for _ in 0..MAX_RATE_LIMIT_RETRIES {
+        match send().await {
+            Err(EmbeddingError::RateLimited(e)) => {
+                log::warn!("[Embedding Batch] Rate limited, retrying in {:?}: {}", delay, e);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            result => return result,
+        }
+    }
+    send().await
+}
+
+/// Generate embeddings for several texts, retrying one at a time if the batch is too large
+///
+/// A single input over the model's limit fails the whole batched request, so
+/// when the provider rejects the batch for its size each text is sent on its own
+/// and only the oversized ones come back as errors. Any other failure (auth,
+/// server errors, rate limits that outlast the backoff) is returned for every
+/// text without re-sending them. Returns one result per text, in order.
+pub async fn generate_embeddings_with_fallback(
+    texts: &[String],
+    model: &str,
+    provider_url: Option<&str>,
+    api_key: Option<&str>,
+) -> Vec<Result<EmbeddingResult, EmbeddingError>> {
+    match generate_embeddings_batch(texts, model, provider_url, api_key).await {
+        Ok(results) => results.into_iter().map(Ok).collect(),
+        Err(e @ EmbeddingError::RequestTooLarge(_)) => {
+            log::warn!(
+                "[Embedding Batch] Batch of {} inputs was too large, retrying one at a time: {}",
+                texts.len(),
+                e
+            );
+            let mut results = Vec::with_capacity(texts.len());
+            for text in texts {
+                results.push(
+                    retry_rate_limited(|| generate_embedding_direct(text, model, provider_url, api_key)).await,
+                );
+            }
+            results
+        }
+        Err(e) => {
+            log::warn!("[Embedding Batch] Batch of {} inputs failed: {}", texts.len(), e);
+            let message = e.to_string();
+            texts.iter().map(|_| Err(EmbeddingError::ApiError(message.clone()))).collect()
+        }
+    }
+}
+
+/// Split texts into consecutive batches that respect the per-request limits
+fn split_batches(texts: &[String]) -> Vec<&[String]> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut chars = 0;
+
+    for (i, text) in texts.iter().enumerate() {
+        let full = i - start >= MAX_BATCH_INPUTS || (i > start && chars + text.len() > MAX_BATCH_CHARS);
+        if full {
+            batches.push(&texts[start..i]);
+            start = i;
+            chars = 0;
+        }
+        chars += text.len();
+    }
+    if start < texts.len() {
+        batches.push(&texts[start..]);
+    }
+
+    batches
+}
+
+/// Classify an unsuccessful response so callers can react to rate limits and oversized requests
+fn status_error(status: reqwest::StatusCode, message: String) -> EmbeddingError {
+    let lower = message.to_lowercase();
+    let too_large = status == reqwest::StatusCode::PAYLOAD_TOO_LARGE
+        || (status == reqwest::StatusCode::BAD_REQUEST
+            && ["too long", "too large", "too many", "maximum context", "token", "length", "size"]
+                .iter()
+                .any(|hint| lower.contains(hint)));
+
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        EmbeddingError::RateLimited(message)
+    } else if too_large {
+        EmbeddingError::RequestTooLarge(message)
+    } else {
+        EmbeddingError::ApiError(message)
+    }
+}
+
+/// Wrap a vector from a batched response, rejecting empty ones
+fn embedding_result(embedding: Vec<f32>, model: String) -> Result<EmbeddingResult, EmbeddingError> {
+    if embedding.is_empty() {
+        return Err(EmbeddingError::EmptyEmbedding);
+    }
+    Ok(EmbeddingResult {
+        dimension: embedding.len(),
+        embedding,
+        model,
+        // Usage is reported per request, not per input
+        tokens_used: None,
+    })
+}
+
+/// Determine the embeddings URL and bare model name for an OpenAI-compatible provider
+fn resolve_openai_endpoint<'a>(
+    model: &'a str,
+    provider_url: Option<&str>,
+) -> Result<(String, &'a str), EmbeddingError> {
+    if let Some(base_url) = provider_url {
         let base = base_url.trim_end_matches('/');
         let model_name = if let Some(stripped) = model.strip_prefix("lmstudio/") {
             stripped
@@ -209,35 +510,43 @@ pub async fn generate_embedding_direct(
         } else {
             format!("{}/v1/embeddings", base)
         };
-        (url, model_name)
+        Ok((url, model_name))
     } else {
         // No provider URL - try to extract from model prefix and use default ports
         if let Some(model_name) = model.strip_prefix("ollama/") {
-            ("http://localhost:11434/v1/embeddings".to_string(), model_name)
+            Ok(("http://localhost:11434/v1/embeddings".to_string(), model_name))
         } else if let Some(model_name) = model.strip_prefix("lmstudio/") {
-            ("http://localhost:1234/v1/embeddings".to_string(), model_name)
+            Ok(("http://localhost:1234/v1/embeddings".to_string(), model_name))
         } else if let Some(model_name) = model.strip_prefix("vllm/") {
-            ("http://localhost:8000/v1/embeddings".to_string(), model_name)
+            Ok(("http://localhost:8000/v1/embeddings".to_string(), model_name))
         } else if let Some(model_name) = model.strip_prefix("openai/") {
-            ("https://api.openai.com/v1/embeddings".to_string(), model_name)
+            Ok(("https://api.openai.com/v1/embeddings".to_string(), model_name))
         } else {
-            return Err(EmbeddingError::ApiError(
+            Err(EmbeddingError::ApiError(
                 "No provider URL and unable to determine from model prefix".to_string()
-            ));
+            ))
         }
-    };
-    
-    log::info!("[Embedding Direct] Sending request to: {} with model: {}", url, model_name);
+    }
+}
+
+/// Send an OpenAI-compatible embeddings request
+async fn post_openai_embeddings(
+    url: &str,
+    model_name: &str,
+    input: EmbeddingInput<'_>,
+    api_key: Option<&str>,
+) -> Result<EmbeddingResponse, EmbeddingError> {
+    let client = reqwest::Client::new();
     
     let request = EmbeddingRequest {
         model: model_name,
-        input: EmbeddingInput::Single(text),
+        input,
         encoding_format: Some("float"),
         dimensions: None,
     };
     
     // Build request with optional authorization header
-    let mut req_builder = client.post(&url).json(&request);
+    let mut req_builder = client.post(url).json(&request);
     if let Some(key) = api_key {
         if !key.is_empty() {
             req_builder = req_builder.header("Authorization", format!("Bearer {}", key));
@@ -249,30 +558,13 @@ pub async fn generate_embedding_direct(
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
-        return Err(EmbeddingError::ApiError(format!(
+        return Err(status_error(status, format!(
             "Status {}: {}",
             status, error_text
         )));
     }
 
-    let response: EmbeddingResponse = response.json().await?;
-    
-    let data = response
-        .data
-        .into_iter()
-        .next()
-        .ok_or(EmbeddingError::EmptyEmbedding)?;
-
-    if data.embedding.is_empty() {
-        return Err(EmbeddingError::EmptyEmbedding);
-    }
-
-    Ok(EmbeddingResult {
-        dimension: data.embedding.len(),
-        embedding: data.embedding,
-        model: response.model,
-        tokens_used: response.usage.map(|u| u.total_tokens),
-    })
+    Ok(response.json().await?)
 }
 
 /// Known embedding models with their dimensions
@@ -389,12 +681,37 @@ const KNOWN_EMBEDDING_MODELS: &[KnownEmbeddingModel] = &[
         provider: "google",
         is_local: false,
     },
+    // In-process models (require the local-embeddings feature)
+    KnownEmbeddingModel {
+        model: "bge-small-en-v1.5",
+        display_name: "BGE Small EN v1.5 (Offline)",
+        dimension: 384,
+        provider: "local",
+        is_local: true,
+    },
+    KnownEmbeddingModel {
+        model: "all-minilm-l6-v2",
+        display_name: "all-MiniLM-L6-v2 (Offline)",
+        dimension: 384,
+        provider: "local",
+        is_local: true,
+    },
+    KnownEmbeddingModel {
+        model: "nomic-embed-text-v1.5",
+        display_name: "Nomic Embed Text v1.5 (Offline)",
+        dimension: 768,
+        provider: "local",
+        is_local: true,
+    },
 ];
 
 /// Get information about known embedding models (static list)
+///
+/// The offline models are left out when this build has no local backend.
 pub fn get_embedding_models() -> Vec<EmbeddingModelInfo> {
     KNOWN_EMBEDDING_MODELS
         .iter()
+        .filter(|m| m.provider != "local" || super::local_embeddings::is_available())
         .map(|m| EmbeddingModelInfo {
             id: m.model.to_string(),
             display_name: m.display_name.to_string(),
@@ -409,6 +726,168 @@ pub fn get_embedding_models() -> Vec<EmbeddingModelInfo> {
 mod tests {
     use super::*;
 
+    /// Start an OpenAI-compatible embeddings server that answers `requests` requests
+    ///
+    /// Each response returns one vector per input as `[position, batch size]`, listed
+    /// in reverse order to check that results are put back in input order.
+    fn spawn_mock_provider(requests: usize) -> String {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+
+        std::thread::spawn(move || {
+            for mut request in server.incoming_requests().take(requests) {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+                let inputs = body["input"].as_array().map(|a| a.len()).unwrap_or(1);
+
+                let data: Vec<serde_json::Value> = (0..inputs)
+                    .rev()
+                    .map(|i| serde_json::json!({ "index": i, "embedding": [i as f32, inputs as f32] }))
+                    .collect();
+                let response = serde_json::json!({ "data": data, "model": "mock-embed" });
+                request
+                    .respond(tiny_http::Response::from_string(response.to_string()))
+                    .unwrap();
+            }
+        });
+
+        url
+    }
+
+    #[tokio::test]
+    async fn test_generate_embeddings_batch_sends_one_request() {
+        // Only one request is answered, so a per-text fallback would fail
+        let url = spawn_mock_provider(1);
+        let texts: Vec<String> = ["first", "second", "third"].iter().map(|t| t.to_string()).collect();
+
+        let results = generate_embeddings_batch(&texts, "openai/mock-embed", Some(&url), None)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        for (i, result) in results.iter().enumerate() {
+            assert_eq!(result.embedding, vec![i as f32, 3.0]);
+            assert_eq!(result.dimension, 2);
+            assert_eq!(result.model, "mock-embed");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oversized_batch_retries_each_text() {
+        // Rejects any request that includes "bad", like a provider refusing one input over the limit
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        std::thread::spawn(move || {
+            for mut request in server.incoming_requests().take(4) {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                if body.contains("bad") {
+                    request
+                        .respond(
+                            tiny_http::Response::from_string("maximum context length exceeded")
+                                .with_status_code(400),
+                        )
+                        .unwrap();
+                    continue;
+                }
+                let response = serde_json::json!({
+                    "data": [{ "index": 0, "embedding": [1.0, 2.0] }],
+                    "model": "mock-embed"
+                });
+                request
+                    .respond(tiny_http::Response::from_string(response.to_string()))
+                    .unwrap();
+            }
+        });
+
+        let texts: Vec<String> = ["first", "bad", "third"].iter().map(|t| t.to_string()).collect();
+        let results = generate_embeddings_with_fallback(&texts, "openai/mock-embed", Some(&url), None).await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().embedding, vec![1.0, 2.0]);
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap().embedding, vec![1.0, 2.0]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_is_not_resent_per_text() {
+        // Only one request is answered, so a per-text fallback would hang
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        std::thread::spawn(move || {
+            if let Ok(request) = server.recv() {
+                request
+                    .respond(tiny_http::Response::from_string("invalid api key").with_status_code(401))
+                    .unwrap();
+            }
+        });
+
+        let texts: Vec<String> = ["first", "second"].iter().map(|t| t.to_string()).collect();
+        let results = generate_embeddings_with_fallback(&texts, "openai/mock-embed", Some(&url), None).await;
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.as_ref().unwrap_err().to_string().contains("401")));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_batch_backs_off_and_retries() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        std::thread::spawn(move || {
+            for (i, request) in server.incoming_requests().take(2).enumerate() {
+                if i == 0 {
+                    request
+                        .respond(tiny_http::Response::from_string("slow down").with_status_code(429))
+                        .unwrap();
+                    continue;
+                }
+                let response = serde_json::json!({
+                    "data": [{ "index": 0, "embedding": [1.0] }, { "index": 1, "embedding": [2.0] }],
+                    "model": "mock-embed"
+                });
+                request
+                    .respond(tiny_http::Response::from_string(response.to_string()))
+                    .unwrap();
+            }
+        });
+
+        let texts: Vec<String> = ["first", "second"].iter().map(|t| t.to_string()).collect();
+        let results = generate_embeddings_batch(&texts, "openai/mock-embed", Some(&url), None)
+            .await
+            .unwrap();
+
+        assert_eq!(results[0].embedding, vec![1.0]);
+        assert_eq!(results[1].embedding, vec![2.0]);
+    }
+
+    #[test]
+    fn test_status_error_classification() {
+        use reqwest::StatusCode;
+
+        assert!(matches!(status_error(StatusCode::TOO_MANY_REQUESTS, String::new()), EmbeddingError::RateLimited(_)));
+        assert!(matches!(status_error(StatusCode::PAYLOAD_TOO_LARGE, String::new()), EmbeddingError::RequestTooLarge(_)));
+        assert!(matches!(
+            status_error(StatusCode::BAD_REQUEST, "Input is too long for the model".to_string()),
+            EmbeddingError::RequestTooLarge(_)
+        ));
+        assert!(matches!(status_error(StatusCode::UNAUTHORIZED, String::new()), EmbeddingError::ApiError(_)));
+        assert!(matches!(status_error(StatusCode::INTERNAL_SERVER_ERROR, String::new()), EmbeddingError::ApiError(_)));
+    }
+
+    #[test]
+    fn test_split_batches_respects_limits() {
+        let short: Vec<String> = (0..MAX_BATCH_INPUTS * 2 + 2).map(|i| i.to_string()).collect();
+        let sizes: Vec<usize> = split_batches(&short).iter().map(|b| b.len()).collect();
+        assert_eq!(sizes, vec![MAX_BATCH_INPUTS, MAX_BATCH_INPUTS, 2]);
+
+        let long: Vec<String> = (0..3).map(|_| "x".repeat(MAX_BATCH_CHARS / 2 + 1)).collect();
+        let sizes: Vec<usize> = split_batches(&long).iter().map(|b| b.len()).collect();
+        assert_eq!(sizes, vec![1, 1, 1]);
+
+        assert!(split_batches(&[]).is_empty());
+    }
+
     #[test]
     fn test_get_embedding_models() {
         let models = get_embedding_models();
@@ -10,7 +10,8 @@ use serde_json::Value;
 use tauri::{AppHandle, Emitter};
 
 use super::agent::{
-    run_agent_with_events, AgentError, CancellationToken, ToolDefinition, ToolExecutor,
+    run_agent_with_events, AgentError, CancellationToken, ToolCallRecord, ToolDefinition,
+    ToolExecutor,
 };
 use super::config::AIProvider;
 
@@ -48,9 +49,41 @@ pub enum ExportProgress {
 use crate::db::connection::DbPool;
 use crate::db::exports::ExportFormat;
 use crate::exports::{
-    add_document_section, add_document_table, create_document, save_document, SectionType,
+    add_document_section, add_document_table, create_document, save_document_with_provenance, DocumentProvenance, SectionType,
 };
 
+/// Tool call budget for an export run (one add_section call per content block)
+const EXPORT_MAX_TOOL_CALLS: usize = 500;
+
+/// Caps on how much content an export run may add to its document
+///
+/// Sections and tables count toward the same total. Past `soft_limit` each
+/// add_section/add_table result tells the model to wrap up; at `hard_limit`
+/// further content is refused and the result is marked truncated.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportLimits {
+    pub soft_limit: usize,
+    pub hard_limit: usize,
+}
+
+impl Default for ExportLimits {
+    fn default() -> Self {
+        Self {
+            soft_limit: 150,
+            hard_limit: 300,
+        }
+    }
+}
+
+/// Guidance added to tool results once the soft limit is passed
+fn wrap_up_guidance(added: usize, limits: &ExportLimits) -> String {
+    format!(
+        "The document already has {} sections and tables (soft limit {}, hard limit {}). Wrap up now: condense any remaining content into a few summary sections, then call save_document.",
+        added, limits.soft_limit, limits.hard_limit
+    )
+}
+
 // ============================================================================
 // Format-Specific System Prompts
 // ============================================================================
@@ -213,12 +246,20 @@ pub struct ExportAgentResult {
     pub iterations: usize,
     /// Tools that were used
     pub tools_used: Vec<String>,
+    /// Whether content was dropped because the hard section limit was hit
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 /// The export agent that implements ToolExecutor and ProgressTracker
 pub struct ExportAgent {
     pool: DbPool,
-    app_handle: AppHandle,
+    /// Progress events are only emitted when an app handle is available
+    app_handle: Option<AppHandle>,
+    /// Content caps for this run
+    limits: ExportLimits,
+    /// Set once content is refused for exceeding the hard limit
+    truncated: std::sync::atomic::AtomicBool,
     /// Current document ID being built
     pub current_document_id: std::sync::Mutex<Option<String>>,
     /// Export result after save
@@ -231,23 +272,87 @@ pub struct ExportAgent {
     notes_processed: std::sync::atomic::AtomicUsize,
     /// Last section type added (for summary)
     last_section_type: std::sync::Mutex<Option<String>>,
+    /// Recorded on the export when the document is saved
+    provenance: DocumentProvenance,
 }
 
 impl ExportAgent {
     /// Create a new export agent
     pub fn new(pool: DbPool, app_handle: AppHandle) -> Self {
+        Self::build(pool, Some(app_handle))
+    }
+
+    fn build(pool: DbPool, app_handle: Option<AppHandle>) -> Self {
         Self {
             pool,
             app_handle,
+            limits: ExportLimits::default(),
+            truncated: std::sync::atomic::AtomicBool::new(false),
             current_document_id: std::sync::Mutex::new(None),
             export_result: std::sync::Mutex::new(None),
             sections_added: std::sync::atomic::AtomicUsize::new(0),
             tables_added: std::sync::atomic::AtomicUsize::new(0),
             notes_processed: std::sync::atomic::AtomicUsize::new(0),
             last_section_type: std::sync::Mutex::new(None),
+            provenance: DocumentProvenance::default(),
         }
     }
     
+    /// Record the model, source notes and start time on the saved export
+    pub fn with_provenance(mut self, provenance: DocumentProvenance) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
+    /// Use custom content limits
+    pub fn with_limits(mut self, limits: ExportLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sections and tables added so far
+    fn content_added(&self) -> usize {
+        self.sections_added.load(std::sync::atomic::Ordering::SeqCst)
+            + self.tables_added.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Whether the hard limit was hit and content was dropped
+    pub fn was_truncated(&self) -> bool {
+        self.truncated.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Refuse more content once the hard limit is reached
+    fn check_hard_limit(&self) -> Option<String> {
+        let added = self.content_added();
+        if added < self.limits.hard_limit {
+            return None;
+        }
+        self.truncated.store(true, std::sync::atomic::Ordering::SeqCst);
+        log::warn!("[ExportAgent] Hard limit of {} sections reached, dropping content", self.limits.hard_limit);
+        Some(serde_json::json!({
+            "success": false,
+            "truncated": true,
+            "message": format!(
+                "Content not added: the document has reached the limit of {} sections and tables. Call save_document now.",
+                self.limits.hard_limit
+            )
+        })
+        .to_string())
+    }
+
+    /// Build a successful add result, with wrap-up guidance past the soft limit
+    fn added_result(&self, message: String) -> String {
+        let added = self.content_added();
+        let mut result = serde_json::json!({
+            "success": true,
+            "message": message
+        });
+        if added >= self.limits.soft_limit {
+            result["guidance"] = serde_json::Value::String(wrap_up_guidance(added, &self.limits));
+        }
+        result.to_string()
+    }
+
     /// Increment sections added counter
     fn increment_sections(&self) {
         self.sections_added.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
@@ -272,7 +377,10 @@ impl ExportAgent {
     
     /// Emit a progress event
     fn emit_progress(&self, progress: ExportProgress) {
-        if let Err(e) = self.app_handle.emit("export-agent-progress", &progress) {
+        let Some(app_handle) = &self.app_handle else {
+            return;
+        };
+        if let Err(e) = app_handle.emit("export-agent-progress", &progress) {
             log::warn!("[ExportAgent] Failed to emit progress event: {}", e);
         }
     }
@@ -397,6 +505,10 @@ impl ExportAgent {
             _ => return Err(format!("Unknown section type: {}", section_type_str)),
         };
 
+        if let Some(refused) = self.check_hard_limit() {
+            return Ok(refused);
+        }
+
         // Emit progress with a preview
         let preview = if content.len() > 50 {
             format!("{}...", &content[..50])
@@ -414,11 +526,7 @@ impl ExportAgent {
         self.increment_sections();
         self.set_last_section_type(section_type_str);
 
-        Ok(serde_json::json!({
-            "success": true,
-            "message": format!("Added {} section", section_type_str)
-        })
-        .to_string())
+        Ok(self.added_result(format!("Added {} section", section_type_str)))
     }
 
     /// Execute add_table tool
@@ -455,6 +563,10 @@ impl ExportAgent {
             })
             .collect::<Vec<_>>();
 
+        if let Some(refused) = self.check_hard_limit() {
+            return Ok(refused);
+        }
+
         self.emit_progress(ExportProgress::AddingTable { 
             rows: rows.len(),
             cols: headers.len(),
@@ -465,11 +577,7 @@ impl ExportAgent {
         // Track progress
         self.increment_tables();
 
-        Ok(serde_json::json!({
-            "success": true,
-            "message": format!("Added table with {} columns and {} rows", headers.len(), rows.len())
-        })
-        .to_string())
+        Ok(self.added_result(format!("Added table with {} columns and {} rows", headers.len(), rows.len())))
     }
 
     /// Execute save_document tool
@@ -486,7 +594,7 @@ impl ExportAgent {
 
         self.emit_progress(ExportProgress::Saving);
 
-        let export = save_document(&document_id)?;
+        let export = save_document_with_provenance(&document_id, &self.provenance)?;
 
         // Store the result
         if let Ok(mut guard) = self.export_result.lock() {
@@ -554,6 +662,34 @@ impl ToolExecutor for ExportAgent {
         
         result
     }
+
+    fn max_tool_calls(&self) -> usize {
+        EXPORT_MAX_TOOL_CALLS
+    }
+
+    /// Save the draft if the model finished without calling save_document
+    async fn finalize(
+        &self,
+        _final_response: &str,
+        _tool_calls: &[ToolCallRecord],
+    ) -> Result<Option<String>, String> {
+        if self.get_export_result().is_some() {
+            return Ok(None);
+        }
+        let has_draft = self
+            .current_document_id
+            .lock()
+            .map(|guard| guard.is_some())
+            .unwrap_or(false);
+        if !has_draft {
+            return Ok(None);
+        }
+
+        self.execute_save_document(&serde_json::json!({}))?;
+        Ok(self
+            .get_export_result()
+            .map(|export| format!("Auto-saved document as {}", export.filename)))
+    }
 }
 
 /// Get tool definitions for the export agent
@@ -663,7 +799,8 @@ pub fn get_system_prompt_for_format(format: &ExportFormat) -> &'static str {
         ExportFormat::Pdf => PDF_EXPORT_SYSTEM_PROMPT,
         ExportFormat::Docx => DOCX_EXPORT_SYSTEM_PROMPT,
         ExportFormat::Pptx => PPTX_EXPORT_SYSTEM_PROMPT,
-        ExportFormat::Xlsx => PDF_EXPORT_SYSTEM_PROMPT, // XLSX uses PDF prompt as fallback
+        // XLSX and Markdown use the PDF prompt as a fallback
+        ExportFormat::Xlsx | ExportFormat::Markdown => PDF_EXPORT_SYSTEM_PROMPT,
     }
 }
 
@@ -679,6 +816,7 @@ pub fn get_system_prompt_for_format(format: &ExportFormat) -> &'static str {
 /// * `title` - Document title
 /// * `format` - Export format (pdf, docx, pptx)
 /// * `custom_instructions` - Optional custom instructions from user
+/// * `limits` - Soft and hard caps on sections and tables added
 /// * `cancellation_token` - Optional cancellation token
 #[allow(clippy::too_many_arguments)]
 pub async fn run_export_agent(
@@ -691,9 +829,16 @@ pub async fn run_export_agent(
     title: &str,
     format: ExportFormat,
     custom_instructions: Option<&str>,
+    limits: ExportLimits,
     cancellation_token: Option<&CancellationToken>,
 ) -> Result<ExportAgentResult, AgentError> {
-    let agent = ExportAgent::new(pool.clone(), app_handle.clone());
+    let agent = ExportAgent::new(pool.clone(), app_handle.clone())
+        .with_limits(limits)
+        .with_provenance(DocumentProvenance {
+            model: Some(model.to_string()),
+            source_note_ids: note_ids.to_vec(),
+            started: Some(std::time::Instant::now()),
+        });
     
     // Emit started event
     agent.emit_progress(ExportProgress::Started {
@@ -716,6 +861,7 @@ pub async fn run_export_agent(
         ExportFormat::Docx => "Word document",
         ExportFormat::Pptx => "PowerPoint presentation",
         ExportFormat::Xlsx => "Excel spreadsheet",
+        ExportFormat::Markdown => "Markdown document",
     };
 
     let initial_message = if let Some(instructions) = custom_instructions {
@@ -786,24 +932,9 @@ Start by reading the note content. After reading, identify all the content block
         .into_iter()
         .collect();
 
-    // Get export result if available
-    let mut export = agent.get_export_result();
-    
-    // If agent didn't call save_document but we have a document, auto-save it
-    if export.is_none() {
-        if let Ok(guard) = agent.current_document_id.lock() {
-            if let Some(ref doc_id) = *guard {
-                match save_document(doc_id) {
-                    Ok(saved_export) => {
-                        export = Some(saved_export);
-                    }
-                    Err(e) => {
-                        log::error!("[ExportAgent] Auto-save failed: {}", e);
-                    }
-                }
-            }
-        }
-    }
+    // Get export result if available (the finalize hook auto-saves a draft
+    // the model forgot to save)
+    let export = agent.get_export_result();
     
     if export.is_none() {
         log::error!("[ExportAgent] Export failed - no document was saved");
@@ -816,12 +947,15 @@ Start by reading the note content. After reading, identify all the content block
         export_filename: export.as_ref().map(|e| e.filename.clone()),
         iterations: result.iterations,
         tools_used,
+        truncated: agent.was_truncated(),
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ai::agent::run_agent_with_client;
+    use crate::ai::llm::{ChatRequest, ChatResponse, FunctionCall, LlmClient, LlmError, StreamEvent, ToolCall as LlmToolCall};
 
     #[test]
     fn test_get_export_agent_tools() {
@@ -850,5 +984,97 @@ mod tests {
         assert!(get_system_prompt_for_format(&ExportFormat::Docx).contains("Word"));
         assert!(get_system_prompt_for_format(&ExportFormat::Pptx).contains("PowerPoint"));
     }
-}
 
+    /// Creates a document, then adds a paragraph per turn until `turns` is used up
+    struct MockRunawayExportClient {
+        turns: usize,
+        requests: std::sync::Mutex<Vec<ChatRequest>>,
+    }
+
+    #[async_trait]
+    impl LlmClient for MockRunawayExportClient {
+        async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LlmError> {
+            let mut requests = self.requests.lock().unwrap();
+            requests.push(request);
+            let turn = requests.len();
+
+            let (name, arguments) = match turn {
+                1 => ("create_document", r#"{"title":"Long note","format":"pdf"}"#.to_string()),
+                t if t <= self.turns => ("add_section", format!(r#"{{"section_type":"paragraph","content":"Paragraph {}"}}"#, t)),
+                _ => {
+                    return Ok(ChatResponse {
+                        content: "Done".to_string(),
+                        thinking: None,
+                        thinking_blocks: Vec::new(),
+                        tool_calls: None,
+                        finish_reason: "stop".to_string(),
+                        usage: None,
+                    })
+                }
+            };
+            Ok(ChatResponse {
+                content: String::new(),
+                thinking: None,
+                thinking_blocks: Vec::new(),
+                tool_calls: Some(vec![LlmToolCall {
+                    id: format!("call_{}", turn),
+                    call_type: "function".to_string(),
+                    function: FunctionCall { name: name.to_string(), arguments },
+                    thought_signature: None,
+                }]),
+                finish_reason: "tool_calls".to_string(),
+                usage: None,
+            })
+        }
+
+        async fn chat_stream(
+            &self,
+            _request: ChatRequest,
+        ) -> Result<tokio::sync::mpsc::Receiver<StreamEvent>, LlmError> {
+            Err(LlmError::NotConfigured("streaming not supported by mock".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_soft_limit_injects_wrap_up_guidance() {
+        let pool = crate::db::connection::init_test_pool().unwrap();
+        let limits = ExportLimits { soft_limit: 3, hard_limit: 5 };
+        let agent = ExportAgent::build(pool, None).with_limits(limits);
+        // create_document + 7 add_section calls, ignoring the guidance
+        let client = MockRunawayExportClient { turns: 8, requests: std::sync::Mutex::new(Vec::new()) };
+
+        let result = run_agent_with_client(
+            &client,
+            "mock-model",
+            PDF_EXPORT_SYSTEM_PROMPT,
+            "Export the note",
+            get_export_agent_tools(),
+            &agent,
+            20,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.final_response, "Done");
+
+        let guidance = wrap_up_guidance(3, &limits);
+        let tool_results: Vec<String> = client.requests.lock().unwrap().last().unwrap()
+            .messages
+            .iter()
+            .filter(|m| m.tool_call_id.is_some())
+            .filter_map(|m| m.content.clone())
+            .collect();
+
+        // Results 1-3 are create_document and the first two sections; the third section hits the soft limit
+        assert!(tool_results[..3].iter().all(|r| !r.contains("Wrap up now")));
+        assert!(tool_results[3].contains(&guidance), "guidance should be injected: {}", tool_results[3]);
+
+        // Sections past the hard limit are refused and the run is marked truncated
+        assert!(tool_results[6].contains("\"truncated\":true"));
+        assert!(agent.was_truncated());
+        assert_eq!(agent.content_added(), 5);
+
+        // The model never called save_document, so the finalize hook took the draft to auto-save it
+        let document_id = agent.current_document_id.lock().unwrap().clone().unwrap();
+        assert!(crate::exports::delete_document(&document_id).is_err());
+    }
+}
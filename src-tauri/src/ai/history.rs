@@ -0,0 +1,157 @@
+//! Conversation History Truncation
+//!
+//! Keeps chat requests inside the model's context window. The system prompt
+//! is always sent; the history is cut down to the most recent messages that
+//! fit, and older turns can be replaced by a running summary.
+
+use super::llm::{ChatMessage, ChatRequest, LlmClient, LlmError};
+
+/// Most recent history messages sent with a chat request (20 turns)
+pub const MAX_HISTORY_MESSAGES: usize = 40;
+
+/// Rough characters-per-token ratio used for estimates
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Tokens set aside for the running summary of dropped turns
+const SUMMARY_TOKEN_RESERVE: usize = 512;
+
+/// System prompt for summarizing dropped turns
+const HISTORY_SUMMARY_PROMPT: &str = "You summarize the earlier part of a conversation between a user and an assistant. \
+Write a concise summary (under 200 words) of the topics discussed, facts established, decisions made and open questions. \
+If a previous summary is given, merge it with the new messages. Output only the summary.";
+
+/// History that fits the context window
+#[derive(Debug, Clone)]
+pub struct TruncatedHistory {
+    /// Messages to send, oldest first, as (role, content)
+    pub messages: Vec<(String, String)>,
+    /// Number of leading messages that were dropped
+    pub dropped: usize,
+}
+
+/// Estimate the token count of some text
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Keep the most recent messages that fit alongside the system prompt and the new user message
+///
+/// At most `max_messages` are kept. When `context_length` is known, the
+/// messages must also fit in three quarters of it (the rest is left for the
+/// response), after the system prompt, the user message and room for a summary.
+pub fn truncate_history(
+    history: Vec<(String, String)>,
+    system_prompt: &str,
+    user_message: &str,
+    context_length: Option<u32>,
+    max_messages: usize,
+) -> TruncatedHistory {
+    let mut budget = context_length.map(|len| {
+        (len as usize * 3 / 4)
+            .saturating_sub(estimate_tokens(system_prompt))
+            .saturating_sub(estimate_tokens(user_message))
+            .saturating_sub(SUMMARY_TOKEN_RESERVE)
+    });
+
+    let mut keep = 0;
+    for (_, content) in history.iter().rev().take(max_messages) {
+        if let Some(remaining) = budget.as_mut() {
+            let tokens = estimate_tokens(content);
+            if tokens > *remaining {
+                break;
+            }
+            *remaining -= tokens;
+        }
+        keep += 1;
+    }
+
+    let dropped = history.len() - keep;
+    TruncatedHistory {
+        messages: history.into_iter().skip(dropped).collect(),
+        dropped,
+    }
+}
+
+/// Append a summary of earlier turns to the system prompt
+pub fn with_history_summary(system_prompt: &str, summary: &str) -> String {
+    format!(
+        "{}\n\n## Earlier in this conversation\n\nOlder messages were left out to save space. Summary:\n\n{}",
+        system_prompt,
+        summary.trim()
+    )
+}
+
+/// Summarize dropped messages, folding in the previous summary if there is one
+pub async fn summarize_history(
+    client: &dyn LlmClient,
+    model: &str,
+    previous_summary: Option<&str>,
+    messages: &[(String, String)],
+) -> Result<String, LlmError> {
+    let mut transcript = String::new();
+    if let Some(summary) = previous_summary {
+        transcript.push_str(&format!("Previous summary:\n{}\n\nNew messages:\n", summary));
+    }
+    for (role, content) in messages {
+        transcript.push_str(&format!("{}: {}\n\n", role, content));
+    }
+
+    let request = ChatRequest {
+        model: model.to_string(),
+        messages: vec![
+            ChatMessage::system(HISTORY_SUMMARY_PROMPT),
+            ChatMessage::user(&transcript),
+        ],
+        temperature: Some(0.3),
+        ..Default::default()
+    };
+
+    let response = client.chat(request).await?;
+    Ok(response.content.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(count: usize) -> Vec<(String, String)> {
+        (0..count)
+            .map(|i| {
+                let role = if i % 2 == 0 { "user" } else { "assistant" };
+                (role.to_string(), format!("message {}", i))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_truncate_history_keeps_most_recent_messages() {
+        let truncated = truncate_history(history(10), "system", "next", None, 4);
+        assert_eq!(truncated.dropped, 6);
+        let contents: Vec<&str> = truncated.messages.iter().map(|(_, c)| c.as_str()).collect();
+        assert_eq!(contents, vec!["message 6", "message 7", "message 8", "message 9"]);
+
+        let untouched = truncate_history(history(3), "system", "next", None, 4);
+        assert_eq!(untouched.dropped, 0);
+        assert_eq!(untouched.messages.len(), 3);
+    }
+
+    #[test]
+    fn test_truncate_history_respects_context_length() {
+        // Each old message is ~250 tokens; only the short recent ones fit
+        let mut messages: Vec<(String, String)> =
+            (0..6).map(|_| ("user".to_string(), "x".repeat(1000))).collect();
+        messages.push(("user".to_string(), "recent question".to_string()));
+        messages.push(("assistant".to_string(), "recent answer".to_string()));
+
+        let system_prompt = "You are a helpful assistant.";
+        let truncated = truncate_history(messages, system_prompt, "follow-up", Some(1024), MAX_HISTORY_MESSAGES);
+        assert_eq!(truncated.dropped, 6);
+        assert_eq!(truncated.messages[0].1, "recent question");
+        assert_eq!(truncated.messages[1].1, "recent answer");
+
+        // The system prompt is kept, with the summary appended
+        let prompt = with_history_summary(system_prompt, "We discussed x.");
+        assert!(prompt.starts_with(system_prompt));
+        assert!(prompt.ends_with("We discussed x."));
+    }
+}
@@ -10,13 +10,14 @@
 use serde::{Deserialize, Serialize};
 
 use crate::db::connection::DbPool;
+use crate::models::ReasoningSettings;
 
 use super::agent::{AgentError, CancellationToken, ToolCallRecord, ToolDefinition};
 use super::chat_executor::UnifiedToolExecutor;
 use super::config::AIProvider;
 use super::llm::ChatMessage;
 use super::streaming_agent::run_streaming_agent;
-use super::tools::{get_unified_agent_tools, AgentConfig};
+use super::tools::{get_unified_agent_tools, AgentConfig, WriteMode};
 
 /// System prompt for the inline assistant
 pub const INLINE_ASSISTANT_SYSTEM_PROMPT: &str = r#"You are an inline writing assistant for a note-taking app called Inkling.
@@ -34,6 +35,7 @@ Knowledge Retrieval:
 - get_related_notes: Find semantically similar notes to a given note
 - get_notes_sharing_tags: Find notes that share tags with a given note
 - get_calendar_events: Get calendar events within a date range
+- get_upcoming_events: Get today's or the coming days' schedule, grouped by day
 - get_daily_note: Get the daily journal note for a specific date
 - get_recent_notes: Get recently modified notes
 - list_folders: Browse the folder structure
@@ -42,9 +44,13 @@ Knowledge Retrieval:
 
 Content Creation:
 - create_mermaid: Create a Mermaid diagram (flowcharts, sequences, etc.)
-- write_content: Output the final markdown content to be inserted
+- write_content: Output the final markdown content; set mode to "replace_selection" when rewriting selected text or "append_to_note" to add to the end of the note (default "insert" at the cursor)
 - create_note: Create a new note in the vault
 - create_calendar_event: Schedule a new calendar event
+- create_meeting_note: Create a meeting notes note linked to a calendar event
+- add_tag_to_note / remove_tag_from_note: Tag or untag a note
+- create_folder: Create a folder to organize notes
+- move_note_to_folder: Move a note into a folder
 
 Document Export:
 - export_notes_pdf: Export notes to a PDF document
@@ -55,6 +61,7 @@ DOCUMENT BUILDER TOOLS (for creating documents incrementally):
 - create_document: Start a new document draft (returns document_id)
 - add_section: Add sections (headings, paragraphs, lists, etc.)
 - add_table: Add tables with headers and rows
+- add_image: Embed an image from an attachment path or URL
 - save_document: Save the completed document to file
 - cancel_document: Discard a document draft
 
@@ -72,7 +79,7 @@ Quick export (single action):
 Custom document building (multi-step):
 1. Use create_document to start a new document
 2. Use add_section multiple times to add content (headings, paragraphs, lists)
-3. Use add_table to add tables
+3. Use add_table to add tables and add_image to embed images
 4. Use save_document to generate the final file
 5. Use write_content to provide a link to the exported file
 
@@ -95,6 +102,8 @@ GUIDELINES:
 pub struct InlineAssistantResult {
     /// The generated content (markdown)
     pub content: String,
+    /// How the frontend should apply `content`
+    pub write_mode: WriteMode,
     /// Tools that were used
     pub tools_used: Vec<String>,
     /// Number of iterations
@@ -115,12 +124,12 @@ pub fn get_inline_assistant_tools(config: &AgentConfig) -> Vec<ToolDefinition> {
 /// while still leaving room for the system prompt and conversation
 const MAX_NOTE_CONTEXT_CHARS: usize = 32000;
 
-/// Extract content from write_content tool calls
+/// Extract content and write mode from write_content tool calls
 /// 
 /// The inline assistant uses write_content to output the final markdown content.
 /// This function extracts the content from the most recent write_content call,
 /// which should be used instead of the LLM's conversational response.
-fn extract_write_content(tool_calls: &[ToolCallRecord]) -> Option<String> {
+fn extract_write_content(tool_calls: &[ToolCallRecord]) -> Option<(String, WriteMode)> {
     // Find the last write_content tool call (in case there are multiple)
     tool_calls
         .iter()
@@ -128,7 +137,9 @@ fn extract_write_content(tool_calls: &[ToolCallRecord]) -> Option<String> {
         .find(|tc| tc.tool_name == "write_content")
         .and_then(|tc| {
             // The content is in the arguments, not the result
-            tc.arguments.get("content").and_then(|v| v.as_str()).map(|s| s.to_string())
+            let content = tc.arguments.get("content").and_then(|v| v.as_str())?;
+            let mode = WriteMode::from_args(&tc.arguments).unwrap_or_default();
+            Some((content.to_string(), mode))
         })
 }
 
@@ -208,7 +219,7 @@ pub async fn run_inline_assistant_with_events(
         .collect();
     
     // Create the unified tool executor
-    let executor = UnifiedToolExecutor::new(pool.clone(), provider.clone(), config);
+    let executor = UnifiedToolExecutor::new(pool.clone(), provider.clone(), config).with_model(model);
     
     log::info!(
         "[InlineAssistant] Running streaming agent with {} tools for execution {}",
@@ -226,6 +237,7 @@ pub async fn run_inline_assistant_with_events(
         llm_tools,
         &executor,
         30, // Max 30 iterations
+        &ReasoningSettings::default(),
         None, // No cancellation for inline assistant
     )
     .await
@@ -243,11 +255,12 @@ pub async fn run_inline_assistant_with_events(
     // Extract content from write_content tool calls if present
     // The write_content tool is the primary way for the inline assistant to output content
     // We should use its content instead of the LLM's final conversational response
-    let final_content = extract_write_content(&result.tool_calls)
-        .unwrap_or(result.content);
+    let (final_content, write_mode) = extract_write_content(&result.tool_calls)
+        .unwrap_or((result.content, WriteMode::Insert));
 
     Ok(InlineAssistantResult {
         content: final_content,
+        write_mode,
         tools_used,
         iterations: result.iterations,
         tool_calls: result.tool_calls,
@@ -299,7 +312,10 @@ mod tests {
         ];
         
         let content = extract_write_content(&tool_calls);
-        assert_eq!(content, Some("## My Heading\n\nSome content here".to_string()));
+        assert_eq!(
+            content,
+            Some(("## My Heading\n\nSome content here".to_string(), WriteMode::Insert))
+        );
     }
 
     #[test]
@@ -328,12 +344,24 @@ mod tests {
             },
             ToolCallRecord {
                 tool_name: "write_content".to_string(),
-                arguments: serde_json::json!({"content": "Final version"}),
+                arguments: serde_json::json!({"content": "Final version", "mode": "replace_selection"}),
                 result: r#"{"success":true}"#.to_string(),
             },
         ];
         
         let content = extract_write_content(&tool_calls);
-        assert_eq!(content, Some("Final version".to_string()));
+        assert_eq!(content, Some(("Final version".to_string(), WriteMode::ReplaceSelection)));
+    }
+
+    #[test]
+    fn test_extract_write_content_modes() {
+        for mode in WriteMode::ALL {
+            let tool_calls = vec![ToolCallRecord {
+                tool_name: "write_content".to_string(),
+                arguments: serde_json::json!({"content": "Text", "mode": mode.as_str()}),
+                result: r#"{"success":true}"#.to_string(),
+            }];
+            assert_eq!(extract_write_content(&tool_calls), Some(("Text".to_string(), mode)));
+        }
     }
 }
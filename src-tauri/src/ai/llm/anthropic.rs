@@ -14,29 +14,44 @@ use std::time::Duration;
 use tokio::sync::mpsc;
 
 use super::{
-    ChatMessage, ChatRequest, ChatResponse, FunctionCall, LlmClient, LlmError, MessageRole,
-    StreamEvent, TokenUsage, ToolCall, ToolDefinition,
+    ChatMessage, ChatRequest, ChatResponse, ClientTimeouts, FunctionCall, LlmClient, LlmError,
+    MessageRole, StreamEvent, ThinkingBlock, TokenUsage, ToolCall, ToolDefinition,
 };
 
 const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1";
 const ANTHROPIC_VERSION: &str = "2023-06-01";
 const ANTHROPIC_BETA_THINKING: &str = "extended-thinking-2025-05-14";
 
+/// Extended thinking budget for a request, if thinking was asked for
+///
+/// Only a thinking budget turns thinking on; a reasoning effort is an OpenAI
+/// setting and leaves Claude's thinking off.
+fn thinking_budget(request: &ChatRequest) -> Option<u32> {
+    request.thinking_budget.filter(|_| request.enable_reasoning)
+}
+
 /// Anthropic Claude API client
 pub struct AnthropicClient {
     api_key: String,
     client: Client,
+    stream_idle: Duration,
 }
 
 impl AnthropicClient {
     /// Create a new Anthropic client
     pub fn new(api_key: String) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(300))
-            .build()
-            .expect("Failed to create HTTP client");
+        Self {
+            api_key,
+            client: ClientTimeouts::default().build_client(),
+            stream_idle: ClientTimeouts::default().stream_idle,
+        }
+    }
 
-        Self { api_key, client }
+    /// Replace the default HTTP timeouts
+    pub fn with_timeouts(mut self, timeouts: ClientTimeouts) -> Self {
+        self.client = timeouts.build_client();
+        self.stream_idle = timeouts.stream_idle;
+        self
     }
 
     /// Build request headers
@@ -69,6 +84,27 @@ impl AnthropicClient {
                         system_prompt = Some(content.clone());
                     }
                 }
+                MessageRole::User if !msg.images.is_empty() => {
+                    // Images go first, as Anthropic recommends for image questions
+                    let mut blocks: Vec<AnthropicContentBlock> = msg
+                        .images
+                        .iter()
+                        .map(|image| AnthropicContentBlock::Image {
+                            source: AnthropicImageSource {
+                                source_type: "base64".to_string(),
+                                media_type: image.mime_type.clone(),
+                                data: image.data.clone(),
+                            },
+                        })
+                        .collect();
+                    if let Some(text) = msg.content.as_ref().filter(|t| !t.is_empty()) {
+                        blocks.push(AnthropicContentBlock::Text { text: text.clone() });
+                    }
+                    anthropic_messages.push(AnthropicMessage {
+                        role: "user".to_string(),
+                        content: AnthropicContent::Blocks(blocks),
+                    });
+                }
                 MessageRole::User => {
                     if let Some(ref content) = msg.content {
                         anthropic_messages.push(AnthropicMessage {
@@ -83,21 +119,26 @@ impl AnthropicClient {
                     let has_content = msg.content.as_ref().map(|c| !c.is_empty()).unwrap_or(false);
                     let has_tool_calls = msg.tool_calls.as_ref().map(|tc| !tc.is_empty()).unwrap_or(false);
                     
-                    if has_content && has_tool_calls {
-                        // Both text and tool calls - combine into blocks
-                        let mut blocks = Vec::new();
-                        if let Some(ref content) = msg.content {
-                            blocks.push(AnthropicContentBlock::Text { text: content.clone() });
+                    if has_tool_calls {
+                        // Signed thinking has to lead the turn, unchanged, then any text and the tool calls
+                        let mut blocks: Vec<AnthropicContentBlock> = msg
+                            .thinking_blocks
+                            .iter()
+                            .map(|block| AnthropicContentBlock::Thinking {
+                                thinking: block.thinking.clone(),
+                                signature: block.signature.clone(),
+                            })
+                            .collect();
+                        if has_content {
+                            blocks.push(AnthropicContentBlock::Text { text: msg.content.clone().unwrap() });
                         }
-                        if let Some(ref tool_calls) = msg.tool_calls {
-                            for tc in tool_calls {
-                                blocks.push(AnthropicContentBlock::ToolUse {
-                                    id: tc.id.clone(),
-                                    name: tc.function.name.clone(),
-                                    input: serde_json::from_str(&tc.function.arguments)
-                                        .unwrap_or(Value::Object(serde_json::Map::new())),
-                                });
-                            }
+                        for tc in msg.tool_calls.as_ref().unwrap() {
+                            blocks.push(AnthropicContentBlock::ToolUse {
+                                id: tc.id.clone(),
+                                name: tc.function.name.clone(),
+                                input: serde_json::from_str(&tc.function.arguments)
+                                    .unwrap_or(Value::Object(serde_json::Map::new())),
+                            });
                         }
                         anthropic_messages.push(AnthropicMessage {
                             role: "assistant".to_string(),
@@ -109,21 +150,6 @@ impl AnthropicClient {
                             role: "assistant".to_string(),
                             content: AnthropicContent::Text(msg.content.clone().unwrap()),
                         });
-                    } else if has_tool_calls {
-                        // Only tool calls
-                        let blocks: Vec<AnthropicContentBlock> = msg.tool_calls.as_ref().unwrap()
-                            .iter()
-                            .map(|tc| AnthropicContentBlock::ToolUse {
-                                id: tc.id.clone(),
-                                name: tc.function.name.clone(),
-                                input: serde_json::from_str(&tc.function.arguments)
-                                    .unwrap_or(Value::Object(serde_json::Map::new())),
-                            })
-                            .collect();
-                        anthropic_messages.push(AnthropicMessage {
-                            role: "assistant".to_string(),
-                            content: AnthropicContent::Blocks(blocks),
-                        });
                     }
                 }
                 MessageRole::Tool => {
@@ -145,23 +171,8 @@ impl AnthropicClient {
         (system_prompt, anthropic_messages)
     }
 
-    /// Convert our tools to Anthropic format
-    fn convert_tools(&self, tools: &[ToolDefinition]) -> Vec<AnthropicTool> {
-        tools
-            .iter()
-            .map(|t| AnthropicTool {
-                name: t.function.name.clone(),
-                description: t.function.description.clone(),
-                input_schema: t.function.parameters.clone(),
-            })
-            .collect()
-    }
-}
-
-#[async_trait]
-impl LlmClient for AnthropicClient {
-    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LlmError> {
-        let url = format!("{}/messages", ANTHROPIC_API_URL);
+    /// Build the JSON body for a Messages API request
+    fn build_body(&self, request: &ChatRequest, stream: bool) -> Value {
         let (system, messages) = self.convert_messages(&request.messages);
 
         let mut body = serde_json::json!({
@@ -170,6 +181,10 @@ impl LlmClient for AnthropicClient {
             "max_tokens": request.max_tokens.unwrap_or(4096),
         });
 
+        if stream {
+            body["stream"] = serde_json::json!(true);
+        }
+
         if let Some(system_prompt) = system {
             body["system"] = serde_json::json!(system_prompt);
         }
@@ -177,25 +192,63 @@ impl LlmClient for AnthropicClient {
         if let Some(ref tools) = request.tools {
             if !tools.is_empty() {
                 body["tools"] = serde_json::to_value(self.convert_tools(tools)).unwrap();
+                // Tools stay declared so earlier tool_use blocks remain valid; "auto" is the default
+                match request.tool_choice.as_deref() {
+                    Some("none") => body["tool_choice"] = serde_json::json!({ "type": "none" }),
+                    Some("required") => body["tool_choice"] = serde_json::json!({ "type": "any" }),
+                    _ => {}
+                }
             }
         }
 
-        if let Some(temperature) = request.temperature {
-            body["temperature"] = serde_json::json!(temperature);
-        }
-
         // Extended thinking support
-        if request.enable_reasoning {
+        if let Some(budget) = thinking_budget(request) {
             body["thinking"] = serde_json::json!({
                 "type": "enabled",
-                "budget_tokens": request.thinking_budget.unwrap_or(10000)
+                "budget_tokens": budget
             });
+            // The thinking budget counts toward max_tokens, which must leave room for the answer
+            let max_tokens = request.max_tokens.unwrap_or(4096).max(budget + 4096);
+            body["max_tokens"] = serde_json::json!(max_tokens);
+        } else if let Some(temperature) = request.temperature {
+            // Temperature can't be changed while thinking is enabled
+            body["temperature"] = serde_json::json!(temperature);
         }
 
+        if request.use_prompt_cache {
+            apply_prompt_cache(&mut body);
+        }
+
+        body
+    }
+
+    /// Convert our tools to Anthropic format
+    fn convert_tools(&self, tools: &[ToolDefinition]) -> Vec<AnthropicTool> {
+        tools
+            .iter()
+            .map(|t| AnthropicTool {
+                name: t.function.name.clone(),
+                description: t.function.description.clone(),
+                input_schema: t.function.parameters.clone(),
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl LlmClient for AnthropicClient {
+    fn stream_idle_timeout(&self) -> Duration {
+        self.stream_idle
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LlmError> {
+        let url = format!("{}/messages", ANTHROPIC_API_URL);
+        let body = self.build_body(&request, false);
+
         let response = self
             .client
             .post(&url)
-            .headers(self.headers(request.enable_reasoning))
+            .headers(self.headers(thinking_budget(&request).is_some()))
             .json(&body)
             .send()
             .await?;
@@ -214,6 +267,7 @@ impl LlmClient for AnthropicClient {
         // Extract content, thinking, and tool calls from content blocks
         let mut content = String::new();
         let mut thinking = String::new();
+        let mut thinking_blocks = Vec::new();
         let mut tool_calls = Vec::new();
 
         for block in &response_body.content {
@@ -221,8 +275,12 @@ impl LlmClient for AnthropicClient {
                 AnthropicContentBlock::Text { text } => {
                     content.push_str(text);
                 }
-                AnthropicContentBlock::Thinking { thinking: think_text } => {
+                AnthropicContentBlock::Thinking { thinking: think_text, signature } => {
                     thinking.push_str(think_text);
+                    thinking_blocks.push(ThinkingBlock {
+                        thinking: think_text.clone(),
+                        signature: signature.clone(),
+                    });
                 }
                 AnthropicContentBlock::ToolUse { id, name, input } => {
                     tool_calls.push(ToolCall {
@@ -246,6 +304,7 @@ impl LlmClient for AnthropicClient {
             } else {
                 Some(thinking)
             },
+            thinking_blocks,
             tool_calls: if tool_calls.is_empty() {
                 None
             } else {
@@ -265,40 +324,12 @@ impl LlmClient for AnthropicClient {
         request: ChatRequest,
     ) -> Result<mpsc::Receiver<StreamEvent>, LlmError> {
         let url = format!("{}/messages", ANTHROPIC_API_URL);
-        let (system, messages) = self.convert_messages(&request.messages);
-
-        let mut body = serde_json::json!({
-            "model": request.model,
-            "messages": messages,
-            "max_tokens": request.max_tokens.unwrap_or(4096),
-            "stream": true,
-        });
-
-        if let Some(system_prompt) = system {
-            body["system"] = serde_json::json!(system_prompt);
-        }
-
-        if let Some(ref tools) = request.tools {
-            if !tools.is_empty() {
-                body["tools"] = serde_json::to_value(self.convert_tools(tools)).unwrap();
-            }
-        }
-
-        if let Some(temperature) = request.temperature {
-            body["temperature"] = serde_json::json!(temperature);
-        }
-
-        if request.enable_reasoning {
-            body["thinking"] = serde_json::json!({
-                "type": "enabled",
-                "budget_tokens": request.thinking_budget.unwrap_or(10000)
-            });
-        }
+        let body = self.build_body(&request, true);
 
         let response = self
             .client
             .post(&url)
-            .headers(self.headers(request.enable_reasoning))
+            .headers(self.headers(thinking_budget(&request).is_some()))
             .json(&body)
             .send()
             .await?;
@@ -320,6 +351,12 @@ impl LlmClient for AnthropicClient {
             let mut buffer = String::new();
             let mut current_tool_id = String::new();
             let mut _current_tool_name = String::new();
+            // message_delta only reliably carries output tokens, so keep the
+            // input count from message_start
+            let mut input_tokens = 0;
+            // Thinking block being streamed, and its signature once it arrives
+            let mut current_thinking: Option<String> = None;
+            let mut current_signature = String::new();
 
             while let Some(chunk_result) = stream.next().await {
                 match chunk_result {
@@ -339,8 +376,13 @@ impl LlmClient for AnthropicClient {
                                 if let Ok(event) = serde_json::from_str::<AnthropicStreamEvent>(data)
                                 {
                                     match event.r#type.as_str() {
-                                        "content_block_start" => {
-                                            if let Some(AnthropicContentBlock::ToolUse { id, name, .. }) = event.content_block {
+                                        "message_start" => {
+                                            if let Some(usage) = event.message.and_then(|m| m.usage) {
+                                                input_tokens = usage.input_tokens;
+                                            }
+                                        }
+                                        "content_block_start" => match event.content_block {
+                                            Some(AnthropicContentBlock::ToolUse { id, name, .. }) => {
                                                 current_tool_id = id.clone();
                                                 _current_tool_name = name.clone();
                                                 let _ = tx
@@ -351,6 +393,21 @@ impl LlmClient for AnthropicClient {
                                                     })
                                                     .await;
                                             }
+                                            Some(AnthropicContentBlock::Thinking { thinking, signature }) => {
+                                                current_thinking = Some(thinking);
+                                                current_signature = signature;
+                                            }
+                                            _ => {}
+                                        },
+                                        "content_block_stop" => {
+                                            if let Some(thinking) = current_thinking.take() {
+                                                let _ = tx
+                                                    .send(StreamEvent::ThinkingBlockEnd {
+                                                        thinking,
+                                                        signature: std::mem::take(&mut current_signature),
+                                                    })
+                                                    .await;
+                                            }
                                         }
                                         "content_block_delta" => {
                                             if let Some(delta) = event.delta {
@@ -363,6 +420,9 @@ impl LlmClient for AnthropicClient {
                                                             .await;
                                                     }
                                                     AnthropicDelta::ThinkingDelta { thinking } => {
+                                                        if let Some(block) = current_thinking.as_mut() {
+                                                            block.push_str(&thinking);
+                                                        }
                                                         let _ = tx
                                                             .send(StreamEvent::Thinking {
                                                                 delta: thinking,
@@ -379,7 +439,10 @@ impl LlmClient for AnthropicClient {
                                                             })
                                                             .await;
                                                     }
-                                                    _ => {}
+                                                    AnthropicDelta::SignatureDelta { signature } => {
+                                                        current_signature = signature;
+                                                    }
+                                                    AnthropicDelta::MessageDelta { .. } => {}
                                                 }
                                             }
                                         }
@@ -394,7 +457,7 @@ impl LlmClient for AnthropicClient {
                                             if let Some(usage) = event.usage {
                                                 let _ = tx
                                                     .send(StreamEvent::Usage {
-                                                        prompt_tokens: usage.input_tokens,
+                                                        prompt_tokens: usage.input_tokens.max(input_tokens),
                                                         completion_tokens: usage.output_tokens,
                                                     })
                                                     .await;
@@ -435,6 +498,69 @@ impl LlmClient for AnthropicClient {
     }
 }
 
+// ============================================================================
+// Prompt Caching
+// ============================================================================
+
+/// Messages shorter than this aren't worth a cache breakpoint
+/// (Anthropic won't cache prefixes under ~1024 tokens)
+const MIN_CACHEABLE_CHARS: usize = 4000;
+
+/// Anthropic allows at most 4 cache breakpoints per request
+const MAX_CACHE_BREAKPOINTS: usize = 4;
+
+/// Mark the system prompt and the latest large message blocks as cacheable
+///
+/// A breakpoint caches the whole prefix up to it, so the most recent large
+/// blocks (e.g. RAG context or long tool results) get the remaining slots.
+fn apply_prompt_cache(body: &mut Value) {
+    let cache_control = serde_json::json!({ "type": "ephemeral" });
+    let mut breakpoints = 0;
+
+    if let Some(system) = body.get("system").and_then(|s| s.as_str()).map(str::to_string) {
+        body["system"] = serde_json::json!([{
+            "type": "text",
+            "text": system,
+            "cache_control": cache_control,
+        }]);
+        breakpoints += 1;
+    }
+
+    let Some(messages) = body.get_mut("messages").and_then(|m| m.as_array_mut()) else {
+        return;
+    };
+    for message in messages.iter_mut().rev() {
+        if breakpoints >= MAX_CACHE_BREAKPOINTS {
+            break;
+        }
+        if message_text_len(&message["content"]) < MIN_CACHEABLE_CHARS {
+            continue;
+        }
+
+        // Plain string content has to become a block to carry cache_control
+        if let Some(text) = message["content"].as_str().map(str::to_string) {
+            message["content"] = serde_json::json!([{ "type": "text", "text": text }]);
+        }
+        if let Some(last_block) = message["content"].as_array_mut().and_then(|b| b.last_mut()) {
+            last_block["cache_control"] = cache_control.clone();
+            breakpoints += 1;
+        }
+    }
+}
+
+/// Total length of the text carried by a message's content
+fn message_text_len(content: &Value) -> usize {
+    match content {
+        Value::String(text) => text.len(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").or_else(|| b.get("content")).and_then(|t| t.as_str()))
+            .map(str::len)
+            .sum(),
+        _ => 0,
+    }
+}
+
 // ============================================================================
 // Anthropic API Types
 // ============================================================================
@@ -460,6 +586,9 @@ enum AnthropicContentBlock {
     },
     Thinking {
         thinking: String,
+        /// Signs the thinking so it can be sent back with tool results
+        #[serde(default)]
+        signature: String,
     },
     #[serde(rename = "tool_use")]
     ToolUse {
@@ -472,6 +601,17 @@ enum AnthropicContentBlock {
         tool_use_id: String,
         content: String,
     },
+    Image {
+        source: AnthropicImageSource,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AnthropicImageSource {
+    #[serde(rename = "type")]
+    source_type: String,
+    media_type: String,
+    data: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -490,14 +630,25 @@ struct AnthropicResponse {
 
 #[derive(Debug, Deserialize)]
 struct AnthropicUsage {
+    #[serde(default)]
     input_tokens: u32,
+    #[serde(default)]
     output_tokens: u32,
 }
 
+/// The message a stream opens with, carrying the input token count
+#[derive(Debug, Deserialize)]
+struct AnthropicStreamMessage {
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
 #[derive(Debug, Deserialize)]
 struct AnthropicStreamEvent {
     r#type: String,
     #[serde(default)]
+    message: Option<AnthropicStreamMessage>,
+    #[serde(default)]
     content_block: Option<AnthropicContentBlock>,
     #[serde(default)]
     delta: Option<AnthropicDelta>,
@@ -520,6 +671,10 @@ enum AnthropicDelta {
     InputJsonDelta {
         partial_json: String,
     },
+    SignatureDelta {
+        signature: String,
+    },
+    // Every field is optional, so this has to stay last
     MessageDelta {
         stop_reason: Option<String>,
     },
@@ -529,3 +684,230 @@ enum AnthropicDelta {
 struct AnthropicError {
     message: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(use_prompt_cache: bool) -> ChatRequest {
+        ChatRequest {
+            model: "claude-sonnet-4-5".to_string(),
+            messages: vec![
+                ChatMessage::system("You are a helpful assistant."),
+                ChatMessage::user(&format!("Context:\n{}", "note text ".repeat(500))),
+                ChatMessage::assistant("Noted."),
+                ChatMessage::user("What did the notes say?"),
+            ],
+            use_prompt_cache,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_prompt_cache_marks_system_and_large_blocks() {
+        let client = AnthropicClient::new("test-key".to_string());
+        let body = client.build_body(&request(true), false);
+
+        assert_eq!(body["system"][0]["text"], "You are a helpful assistant.");
+        assert_eq!(body["system"][0]["cache_control"]["type"], "ephemeral");
+
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages[0]["content"][0]["cache_control"]["type"], "ephemeral");
+        // Short messages stay plain strings without a breakpoint
+        assert_eq!(messages[1]["content"], "Noted.");
+        assert_eq!(messages[2]["content"], "What did the notes say?");
+    }
+
+    #[test]
+    fn test_prompt_cache_disabled_leaves_body_unchanged() {
+        let client = AnthropicClient::new("test-key".to_string());
+        let body = client.build_body(&request(false), true);
+
+        assert_eq!(body["system"], "You are a helpful assistant.");
+        assert_eq!(body["stream"], true);
+        assert!(!body.to_string().contains("cache_control"));
+    }
+
+    #[test]
+    fn test_user_images_become_image_blocks() {
+        let client = AnthropicClient::new("test-key".to_string());
+        let image = crate::ai::llm::ImagePart {
+            mime_type: "image/jpeg".to_string(),
+            data: "/9j/4AAQ".to_string(),
+        };
+        let (_, messages) = client.convert_messages(&[ChatMessage::user_with_images(
+            "Describe this photo",
+            vec![image],
+        )]);
+        let body = serde_json::json!({ "messages": messages });
+
+        assert_eq!(
+            body["messages"][0]["content"],
+            serde_json::json!([
+                {
+                    "type": "image",
+                    "source": { "type": "base64", "media_type": "image/jpeg", "data": "/9j/4AAQ" },
+                },
+                { "type": "text", "text": "Describe this photo" },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_thinking_budget_raises_max_tokens() {
+        let client = AnthropicClient::new("test-key".to_string());
+        let request = ChatRequest {
+            model: "claude-sonnet-4-5".to_string(),
+            messages: vec![ChatMessage::user("Prove it")],
+            temperature: Some(0.2),
+            enable_reasoning: true,
+            thinking_budget: Some(8000),
+            ..Default::default()
+        };
+        let body = client.build_body(&request, true);
+
+        assert_eq!(body["thinking"]["type"], "enabled");
+        assert_eq!(body["thinking"]["budget_tokens"], 8000);
+        assert_eq!(body["max_tokens"], 12096);
+        assert!(body.get("temperature").is_none());
+    }
+
+    #[test]
+    fn test_reasoning_effort_alone_leaves_thinking_off() {
+        let client = AnthropicClient::new("test-key".to_string());
+        let request = ChatRequest {
+            model: "claude-sonnet-4-5".to_string(),
+            messages: vec![ChatMessage::user("Prove it")],
+            temperature: Some(0.2),
+            enable_reasoning: true,
+            reasoning_effort: Some("high".to_string()),
+            ..Default::default()
+        };
+        let body = client.build_body(&request, true);
+
+        assert!(body.get("thinking").is_none());
+        assert_eq!(body["max_tokens"], 4096);
+        assert_eq!(body["temperature"], serde_json::json!(0.2f32));
+    }
+
+    #[test]
+    fn test_tool_choice_none_is_mapped() {
+        let client = AnthropicClient::new("test-key".to_string());
+        let mut request = ChatRequest {
+            model: "claude-sonnet-4-5".to_string(),
+            messages: vec![ChatMessage::user("Wrap up")],
+            tools: Some(vec![ToolDefinition {
+                tool_type: "function".to_string(),
+                function: crate::ai::llm::FunctionDefinition {
+                    name: "search_notes".to_string(),
+                    description: "Search notes".to_string(),
+                    parameters: serde_json::json!({ "type": "object", "properties": {} }),
+                },
+            }]),
+            tool_choice: Some("none".to_string()),
+            ..Default::default()
+        };
+        let body = client.build_body(&request, true);
+        assert_eq!(body["tools"][0]["name"], "search_notes");
+        assert_eq!(body["tool_choice"], serde_json::json!({ "type": "none" }));
+
+        request.tool_choice = Some("auto".to_string());
+        assert!(client.build_body(&request, true).get("tool_choice").is_none());
+    }
+
+    /// Streams signed thinking and a tool call, then answers once the tool result is in
+    struct SignedThinkingToolClient {
+        requests: std::sync::Mutex<Vec<ChatRequest>>,
+    }
+
+    #[async_trait]
+    impl LlmClient for SignedThinkingToolClient {
+        async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, LlmError> {
+            Err(LlmError::NotConfigured("non-streaming not supported by mock".to_string()))
+        }
+
+        async fn chat_stream(&self, request: ChatRequest) -> Result<mpsc::Receiver<StreamEvent>, LlmError> {
+            let answered = matches!(request.messages.last().map(|m| &m.role), Some(MessageRole::Tool));
+            self.requests.lock().unwrap().push(request);
+            let (tx, rx) = mpsc::channel(16);
+            let events = if answered {
+                vec![
+                    StreamEvent::Content { delta: "Found it".to_string() },
+                    StreamEvent::Done { finish_reason: "end_turn".to_string() },
+                ]
+            } else {
+                vec![
+                    StreamEvent::Thinking { delta: "Search the notes first".to_string() },
+                    StreamEvent::ThinkingBlockEnd {
+                        thinking: "Search the notes first".to_string(),
+                        signature: "sig-1".to_string(),
+                    },
+                    StreamEvent::ToolCallStart {
+                        id: "toolu_1".to_string(),
+                        name: "search_notes".to_string(),
+                        thought_signature: None,
+                    },
+                    StreamEvent::ToolCallDelta {
+                        id: "toolu_1".to_string(),
+                        arguments_delta: r#"{"query":"rust"}"#.to_string(),
+                    },
+                    StreamEvent::Done { finish_reason: "tool_use".to_string() },
+                ]
+            };
+            for event in events {
+                tx.send(event).await.unwrap();
+            }
+            Ok(rx)
+        }
+    }
+
+    struct OneNoteExecutor;
+
+    #[async_trait]
+    impl crate::ai::agent::ToolExecutor for OneNoteExecutor {
+        async fn execute(&self, _name: &str, _args: Value) -> Result<String, String> {
+            Ok("1 note found".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signed_thinking_is_replayed_with_tool_results() {
+        let mock = SignedThinkingToolClient { requests: std::sync::Mutex::new(Vec::new()) };
+        let emit = |_event: crate::ai::streaming_agent::AgentStreamEvent| {};
+        let reasoning = crate::models::ReasoningSettings {
+            reasoning_effort: None,
+            thinking_budget: Some(2048),
+        };
+
+        let result = crate::ai::streaming_agent::run_streaming_agent_with_client(
+            &mock,
+            &emit,
+            "claude-sonnet-4-5",
+            vec![ChatMessage::user("Find my rust notes")],
+            vec![],
+            &OneNoteExecutor,
+            5,
+            &reasoning,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.content, "Found it");
+
+        let requests = mock.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let body = AnthropicClient::new("test-key".to_string()).build_body(&requests[1], true);
+        assert_eq!(body["thinking"]["budget_tokens"], 2048);
+        assert_eq!(
+            body["messages"][1],
+            serde_json::json!({
+                "role": "assistant",
+                "content": [
+                    { "type": "thinking", "thinking": "Search the notes first", "signature": "sig-1" },
+                    { "type": "tool_use", "id": "toolu_1", "name": "search_notes", "input": { "query": "rust" } },
+                ],
+            })
+        );
+        assert_eq!(body["messages"][2]["content"][0]["type"], "tool_result");
+    }
+}
@@ -14,8 +14,8 @@ use std::time::Duration;
 use tokio::sync::mpsc;
 
 use super::{
-    ChatMessage, ChatRequest, ChatResponse, FunctionCall, LlmClient, LlmError, MessageRole,
-    StreamEvent, TokenUsage, ToolCall, ToolDefinition,
+    ChatMessage, ChatRequest, ChatResponse, ClientTimeouts, FunctionCall, LlmClient, LlmError,
+    MessageRole, StreamEvent, TokenUsage, ToolCall, ToolDefinition,
 };
 
 const GEMINI_API_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
@@ -24,17 +24,24 @@ const GEMINI_API_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
 pub struct GoogleClient {
     api_key: String,
     client: Client,
+    stream_idle: Duration,
 }
 
 impl GoogleClient {
     /// Create a new Google Gemini client
     pub fn new(api_key: String) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(300))
-            .build()
-            .expect("Failed to create HTTP client");
+        Self {
+            api_key,
+            client: ClientTimeouts::default().build_client(),
+            stream_idle: ClientTimeouts::default().stream_idle,
+        }
+    }
 
-        Self { api_key, client }
+    /// Replace the default HTTP timeouts
+    pub fn with_timeouts(mut self, timeouts: ClientTimeouts) -> Self {
+        self.client = timeouts.build_client();
+        self.stream_idle = timeouts.stream_idle;
+        self
     }
 
     /// Convert our messages to Gemini format
@@ -50,10 +57,25 @@ impl GoogleClient {
                     }
                 }
                 MessageRole::User => {
+                    let mut parts = Vec::new();
+
                     if let Some(ref content) = msg.content {
+                        parts.push(GeminiPart::Text { text: content.clone() });
+                    }
+
+                    for image in &msg.images {
+                        parts.push(GeminiPart::InlineData {
+                            inline_data: GeminiInlineData {
+                                mime_type: image.mime_type.clone(),
+                                data: image.data.clone(),
+                            },
+                        });
+                    }
+
+                    if !parts.is_empty() {
                         contents.push(GeminiContent {
                             role: "user".to_string(),
-                            parts: vec![GeminiPart::Text { text: content.clone() }],
+                            parts,
                         });
                     }
                 }
@@ -108,31 +130,8 @@ impl GoogleClient {
         (system_instruction, contents)
     }
 
-    /// Convert our tools to Gemini format
-    fn convert_tools(&self, tools: &[ToolDefinition]) -> Vec<GeminiTool> {
-        let function_declarations: Vec<GeminiFunctionDeclaration> = tools
-            .iter()
-            .map(|t| GeminiFunctionDeclaration {
-                name: t.function.name.clone(),
-                description: t.function.description.clone(),
-                parameters: t.function.parameters.clone(),
-            })
-            .collect();
-
-        vec![GeminiTool {
-            function_declarations,
-        }]
-    }
-}
-
-#[async_trait]
-impl LlmClient for GoogleClient {
-    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LlmError> {
-        let url = format!(
-            "{}/models/{}:generateContent?key={}",
-            GEMINI_API_URL, request.model, self.api_key
-        );
-
+    /// Build the JSON body for a generateContent request
+    fn build_body(&self, request: &ChatRequest) -> Value {
         let (system_instruction, contents) = self.convert_messages(&request.messages);
 
         let mut body = serde_json::json!({
@@ -148,6 +147,17 @@ impl LlmClient for GoogleClient {
         if let Some(ref tools) = request.tools {
             if !tools.is_empty() {
                 body["tools"] = serde_json::to_value(self.convert_tools(tools)).unwrap();
+                // Tools stay declared so earlier function calls remain valid; AUTO is the default
+                let mode = match request.tool_choice.as_deref() {
+                    Some("none") => Some("NONE"),
+                    Some("required") => Some("ANY"),
+                    _ => None,
+                };
+                if let Some(mode) = mode {
+                    body["toolConfig"] = serde_json::json!({
+                        "functionCallingConfig": { "mode": mode }
+                    });
+                }
             }
         }
 
@@ -161,17 +171,53 @@ impl LlmClient for GoogleClient {
             generation_config["temperature"] = serde_json::json!(temperature);
         }
 
-        // Thinking mode for Gemini 2.5+
+        // Thinking mode for Gemini 2.5+; thoughts are only returned when asked for
         if request.enable_reasoning {
-            body["thinkingConfig"] = serde_json::json!({
-                "thinkingMode": "ENABLED"
-            });
+            let mut thinking_config = serde_json::json!({ "includeThoughts": true });
+            if let Some(budget) = request.thinking_budget {
+                thinking_config["thinkingBudget"] = serde_json::json!(budget);
+            }
+            generation_config["thinkingConfig"] = thinking_config;
         }
 
         if generation_config != serde_json::json!({}) {
             body["generationConfig"] = generation_config;
         }
 
+        body
+    }
+
+    /// Convert our tools to Gemini format
+    fn convert_tools(&self, tools: &[ToolDefinition]) -> Vec<GeminiTool> {
+        let function_declarations: Vec<GeminiFunctionDeclaration> = tools
+            .iter()
+            .map(|t| GeminiFunctionDeclaration {
+                name: t.function.name.clone(),
+                description: t.function.description.clone(),
+                parameters: t.function.parameters.clone(),
+            })
+            .collect();
+
+        vec![GeminiTool {
+            function_declarations,
+        }]
+    }
+}
+
+#[async_trait]
+impl LlmClient for GoogleClient {
+    fn stream_idle_timeout(&self) -> Duration {
+        self.stream_idle
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LlmError> {
+        let url = format!(
+            "{}/models/{}:generateContent?key={}",
+            GEMINI_API_URL, request.model, self.api_key
+        );
+
+        let body = self.build_body(&request);
+
         let response = self
             .client
             .post(&url)
@@ -242,6 +288,7 @@ impl LlmClient for GoogleClient {
             } else {
                 Some(thinking)
             },
+            thinking_blocks: Vec::new(),
             tool_calls: if tool_calls.is_empty() {
                 None
             } else {
@@ -265,43 +312,7 @@ impl LlmClient for GoogleClient {
             GEMINI_API_URL, request.model, self.api_key
         );
 
-        let (system_instruction, contents) = self.convert_messages(&request.messages);
-
-        let mut body = serde_json::json!({
-            "contents": contents,
-        });
-
-        if let Some(system) = system_instruction {
-            body["systemInstruction"] = serde_json::json!({
-                "parts": [{"text": system}]
-            });
-        }
-
-        if let Some(ref tools) = request.tools {
-            if !tools.is_empty() {
-                body["tools"] = serde_json::to_value(self.convert_tools(tools)).unwrap();
-            }
-        }
-
-        let mut generation_config = serde_json::json!({});
-
-        if let Some(max_tokens) = request.max_tokens {
-            generation_config["maxOutputTokens"] = serde_json::json!(max_tokens);
-        }
-
-        if let Some(temperature) = request.temperature {
-            generation_config["temperature"] = serde_json::json!(temperature);
-        }
-
-        if request.enable_reasoning {
-            body["thinkingConfig"] = serde_json::json!({
-                "thinkingMode": "ENABLED"
-            });
-        }
-
-        if generation_config != serde_json::json!({}) {
-            body["generationConfig"] = generation_config;
-        }
+        let body = self.build_body(&request);
 
         let response = self
             .client
@@ -449,14 +460,16 @@ struct GeminiContent {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 enum GeminiPart {
-    Text {
-        text: String,
-    },
+    // Must come before Text: untagged variants are tried in order, and a
+    // thought part would otherwise parse as plain text
     Thought {
         thought: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
         text: Option<String>,
     },
+    Text {
+        text: String,
+    },
     FunctionCall {
         #[serde(rename = "functionCall")]
         function_call: GeminiFunctionCall,
@@ -469,6 +482,17 @@ enum GeminiPart {
         #[serde(rename = "functionResponse")]
         function_response: GeminiFunctionResponse,
     },
+    InlineData {
+        #[serde(rename = "inlineData")]
+        inline_data: GeminiInlineData,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiInlineData {
+    mime_type: String,
+    data: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -524,3 +548,82 @@ struct GeminiUsage {
     #[serde(rename = "totalTokenCount", default)]
     total_token_count: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::llm::ImagePart;
+
+    #[test]
+    fn test_user_images_become_inline_data() {
+        let client = GoogleClient::new("test-key".to_string());
+        let image = ImagePart {
+            mime_type: "image/png".to_string(),
+            data: "iVBORw0KGgo=".to_string(),
+        };
+        let (_, contents) = client.convert_messages(&[
+            ChatMessage::user("Plain question"),
+            ChatMessage {
+                images: vec![image],
+                ..ChatMessage::user("What is in this picture?")
+            },
+        ]);
+        let body = serde_json::json!({ "contents": contents });
+
+        // Text-only messages are unchanged
+        assert_eq!(body["contents"][0]["parts"], serde_json::json!([{ "text": "Plain question" }]));
+
+        let parts = &body["contents"][1]["parts"];
+        assert_eq!(parts[0]["text"], "What is in this picture?");
+        assert_eq!(
+            parts[1],
+            serde_json::json!({ "inlineData": { "mimeType": "image/png", "data": "iVBORw0KGgo=" } })
+        );
+    }
+
+    #[test]
+    fn test_tool_choice_none_is_mapped() {
+        let client = GoogleClient::new("test-key".to_string());
+        let mut request = ChatRequest {
+            model: "gemini-2.5-pro".to_string(),
+            messages: vec![ChatMessage::user("Wrap up")],
+            tools: Some(vec![ToolDefinition {
+                tool_type: "function".to_string(),
+                function: crate::ai::llm::FunctionDefinition {
+                    name: "search_notes".to_string(),
+                    description: "Search notes".to_string(),
+                    parameters: serde_json::json!({ "type": "object", "properties": {} }),
+                },
+            }]),
+            tool_choice: Some("none".to_string()),
+            ..Default::default()
+        };
+        let body = client.build_body(&request);
+        assert_eq!(body["toolConfig"]["functionCallingConfig"]["mode"], "NONE");
+
+        request.tool_choice = Some("auto".to_string());
+        assert!(client.build_body(&request).get("toolConfig").is_none());
+    }
+
+    #[test]
+    fn test_reasoning_requests_and_parses_thoughts() {
+        let client = GoogleClient::new("test-key".to_string());
+        let request = ChatRequest {
+            model: "gemini-2.5-pro".to_string(),
+            messages: vec![ChatMessage::user("Think it through")],
+            enable_reasoning: true,
+            thinking_budget: Some(2048),
+            ..Default::default()
+        };
+        let body = client.build_body(&request);
+        assert_eq!(
+            body["generationConfig"]["thinkingConfig"],
+            serde_json::json!({ "includeThoughts": true, "thinkingBudget": 2048 })
+        );
+
+        let thought: GeminiPart = serde_json::from_str(r#"{"text": "Hmm", "thought": true}"#).unwrap();
+        assert!(matches!(thought, GeminiPart::Thought { thought: true, .. }));
+        let text: GeminiPart = serde_json::from_str(r#"{"text": "Answer"}"#).unwrap();
+        assert!(matches!(text, GeminiPart::Text { .. }));
+    }
+}
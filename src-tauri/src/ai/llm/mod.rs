@@ -21,6 +21,7 @@ pub use anthropic::AnthropicClient;
 pub use google::GoogleClient;
 
 use async_trait::async_trait;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
 use super::config::{AIProvider, ProviderType};
@@ -42,25 +43,32 @@ pub trait LlmClient: Send + Sync {
         &self,
         request: ChatRequest,
     ) -> Result<mpsc::Receiver<StreamEvent>, LlmError>;
+
+    /// How long a stream may go without sending an event before it's abandoned
+    fn stream_idle_timeout(&self) -> Duration {
+        ClientTimeouts::default().stream_idle
+    }
 }
 
 /// Create an LLM client for the given provider configuration
 pub fn create_client(provider: &AIProvider) -> Result<Box<dyn LlmClient>, LlmError> {
+    let timeouts = provider.client_timeouts();
     match provider.provider_type {
         ProviderType::OpenAI => {
             let api_key = provider.api_key.clone().ok_or(LlmError::MissingApiKey)?;
-            Ok(Box::new(OpenAIClient::new(
-                "https://api.openai.com/v1",
-                Some(api_key),
-            )))
+            Ok(Box::new(
+                OpenAIClient::new("https://api.openai.com/v1", Some(api_key))
+                    .with_timeouts(timeouts)
+                    .with_image_input(),
+            ))
         }
         ProviderType::Anthropic => {
             let api_key = provider.api_key.clone().ok_or(LlmError::MissingApiKey)?;
-            Ok(Box::new(AnthropicClient::new(api_key)))
+            Ok(Box::new(AnthropicClient::new(api_key).with_timeouts(timeouts)))
         }
         ProviderType::Google => {
             let api_key = provider.api_key.clone().ok_or(LlmError::MissingApiKey)?;
-            Ok(Box::new(GoogleClient::new(api_key)))
+            Ok(Box::new(GoogleClient::new(api_key).with_timeouts(timeouts)))
         }
         ProviderType::Ollama => {
             let base_url = provider
@@ -73,7 +81,7 @@ pub fn create_client(provider: &AIProvider) -> Result<Box<dyn LlmClient>, LlmErr
             } else {
                 format!("{}/v1", base_url.trim_end_matches('/'))
             };
-            Ok(Box::new(OpenAIClient::new(&url, None)))
+            Ok(Box::new(OpenAIClient::new(&url, None).with_timeouts(timeouts)))
         }
         ProviderType::LMStudio => {
             let base_url = provider
@@ -86,7 +94,9 @@ pub fn create_client(provider: &AIProvider) -> Result<Box<dyn LlmClient>, LlmErr
                 format!("{}/v1", base_url.trim_end_matches('/'))
             };
             // LMStudio doesn't support streaming + tools together, so use the special constructor
-            Ok(Box::new(OpenAIClient::new_with_tool_streaming_disabled(&url, None)))
+            Ok(Box::new(
+                OpenAIClient::new_with_tool_streaming_disabled(&url, None).with_timeouts(timeouts),
+            ))
         }
         ProviderType::VLLM => {
             let base_url = provider
@@ -98,15 +108,22 @@ pub fn create_client(provider: &AIProvider) -> Result<Box<dyn LlmClient>, LlmErr
             } else {
                 format!("{}/v1", base_url.trim_end_matches('/'))
             };
-            Ok(Box::new(OpenAIClient::new(&url, provider.api_key.clone())))
+            Ok(Box::new(
+                OpenAIClient::new(&url, provider.api_key.clone()).with_timeouts(timeouts),
+            ))
         }
+        ProviderType::Local => Err(LlmError::NotConfigured(
+            "The offline provider only supports embeddings".to_string(),
+        )),
         ProviderType::Custom => {
             // Custom providers are assumed to be OpenAI-compatible
             let base_url = provider
                 .base_url
                 .as_deref()
                 .ok_or_else(|| LlmError::NotConfigured("Custom provider requires base_url".to_string()))?;
-            Ok(Box::new(OpenAIClient::new(base_url, provider.api_key.clone())))
+            Ok(Box::new(
+                OpenAIClient::new(base_url, provider.api_key.clone()).with_timeouts(timeouts),
+            ))
         }
     }
 }
@@ -127,6 +144,9 @@ mod tests {
             models: vec!["gpt-4o".to_string()],
             selected_model: Some("gpt-4o".to_string()),
             context_length: None,
+            request_timeout_secs: None,
+            connect_timeout_secs: None,
+            stream_idle_timeout_secs: None,
         };
 
         let client = create_client(&provider);
@@ -145,6 +165,9 @@ mod tests {
             models: vec!["llama3".to_string()],
             selected_model: Some("llama3".to_string()),
             context_length: None,
+            request_timeout_secs: None,
+            connect_timeout_secs: None,
+            stream_idle_timeout_secs: None,
         };
 
         let client = create_client(&provider);
@@ -163,6 +186,9 @@ mod tests {
             models: Vec::new(),
             selected_model: None,
             context_length: None,
+            request_timeout_secs: None,
+            connect_timeout_secs: None,
+            stream_idle_timeout_secs: None,
         };
 
         let result = create_client(&provider);
@@ -173,4 +199,69 @@ mod tests {
             Ok(_) => panic!("Expected error, got Ok"),
         }
     }
+
+    #[test]
+    fn test_client_timeouts_fall_back_to_defaults() {
+        let mut provider = AIProvider::default();
+        assert_eq!(provider.client_timeouts(), ClientTimeouts::default());
+
+        provider.request_timeout_secs = Some(1.5);
+        provider.connect_timeout_secs = Some(-1.0);
+        provider.stream_idle_timeout_secs = Some(30.0);
+        let timeouts = provider.client_timeouts();
+        assert_eq!(timeouts.request, std::time::Duration::from_millis(1500));
+        assert_eq!(timeouts.connect, ClientTimeouts::default().connect);
+        assert_eq!(timeouts.stream_idle, std::time::Duration::from_secs(30));
+
+        // Values too large for a Duration fall back instead of panicking
+        provider.request_timeout_secs = Some(1e30);
+        provider.stream_idle_timeout_secs = Some(f64::INFINITY);
+        let timeouts = provider.client_timeouts();
+        assert_eq!(timeouts.request, ClientTimeouts::default().request);
+        assert_eq!(timeouts.stream_idle, ClientTimeouts::default().stream_idle);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_against_slow_endpoint() {
+        // Accepts the request but never answers in time
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/v1", server.server_addr().to_ip().unwrap());
+        std::thread::spawn(move || {
+            if let Ok(request) = server.recv() {
+                std::thread::sleep(std::time::Duration::from_secs(2));
+                let _ = request.respond(tiny_http::Response::from_string("{}"));
+            }
+        });
+
+        let provider = AIProvider {
+            provider_type: ProviderType::Custom,
+            base_url: Some(base_url),
+            request_timeout_secs: Some(0.001),
+            ..AIProvider::default()
+        };
+        let client = create_client(&provider).unwrap();
+
+        let started = std::time::Instant::now();
+        let result = client
+            .chat(ChatRequest {
+                model: "slow-model".to_string(),
+                messages: vec![ChatMessage::user("hello")],
+                tools: None,
+                tool_choice: None,
+                max_tokens: None,
+                temperature: None,
+                enable_reasoning: false,
+                reasoning_effort: None,
+                thinking_budget: None,
+                use_prompt_cache: false,
+            })
+            .await;
+
+        match result {
+            Err(LlmError::HttpError(e)) => assert!(e.is_timeout(), "expected a timeout, got {}", e),
+            Err(other) => panic!("Expected a timeout error, got: {:?}", other),
+            Ok(_) => panic!("Expected a timeout error, got Ok"),
+        }
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
 }
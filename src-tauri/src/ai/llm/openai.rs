@@ -16,8 +16,8 @@ use std::time::Duration;
 use tokio::sync::mpsc;
 
 use super::{
-    ChatMessage, ChatRequest, ChatResponse, FunctionCall, LlmClient, LlmError, StreamEvent,
-    TokenUsage, ToolCall, ToolDefinition,
+    ChatMessage, ChatRequest, ChatResponse, ClientTimeouts, FunctionCall, LlmClient, LlmError,
+    StreamEvent, TokenUsage, ToolCall, ToolDefinition,
 };
 
 /// OpenAI-compatible LLM client
@@ -25,26 +25,40 @@ pub struct OpenAIClient {
     base_url: String,
     api_key: Option<String>,
     client: Client,
+    stream_idle: Duration,
     /// If true, disable streaming when tools are being used (for LMStudio compatibility)
     disable_streaming_with_tools: bool,
+    /// If true, attached images are sent as image_url parts; otherwise they're
+    /// replaced with "[image omitted]" text
+    supports_images: bool,
 }
 
 impl OpenAIClient {
     /// Create a new OpenAI-compatible client
     pub fn new(base_url: &str, api_key: Option<String>) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(300)) // 5 minute timeout for long generations
-            .build()
-            .expect("Failed to create HTTP client");
-
         Self {
             base_url: base_url.trim_end_matches('/').to_string(),
             api_key,
-            client,
+            client: ClientTimeouts::default().build_client(),
+            stream_idle: ClientTimeouts::default().stream_idle,
             disable_streaming_with_tools: false,
+            supports_images: false,
         }
     }
 
+    /// Replace the default HTTP timeouts
+    pub fn with_timeouts(mut self, timeouts: ClientTimeouts) -> Self {
+        self.client = timeouts.build_client();
+        self.stream_idle = timeouts.stream_idle;
+        self
+    }
+
+    /// Send attached images to the endpoint (for vision-capable APIs)
+    pub fn with_image_input(mut self) -> Self {
+        self.supports_images = true;
+        self
+    }
+
     /// Create a new OpenAI-compatible client with streaming disabled when tools are used
     /// This is needed for LMStudio which doesn't support streaming + tools together
     pub fn new_with_tool_streaming_disabled(base_url: &str, api_key: Option<String>) -> Self {
@@ -75,7 +89,7 @@ impl OpenAIClient {
             .iter()
             .map(|m| OpenAIMessage {
                 role: m.role.as_str().to_string(),
-                content: m.content.clone(),
+                content: self.convert_content(m),
                 tool_calls: m.tool_calls.as_ref().map(|calls| {
                     calls
                         .iter()
@@ -94,6 +108,24 @@ impl OpenAIClient {
             .collect()
     }
 
+    /// Message content, as text and image_url parts when images are attached
+    fn convert_content(&self, message: &ChatMessage) -> Option<OpenAIContent> {
+        if message.images.is_empty() || !self.supports_images {
+            return message.text_with_image_placeholders().map(OpenAIContent::Text);
+        }
+
+        let mut parts = Vec::new();
+        if let Some(text) = message.content.as_ref().filter(|t| !t.is_empty()) {
+            parts.push(OpenAIContentPart::Text { text: text.clone() });
+        }
+        for image in &message.images {
+            parts.push(OpenAIContentPart::ImageUrl {
+                image_url: OpenAIImageUrl { url: image.data_url() },
+            });
+        }
+        Some(OpenAIContent::Parts(parts))
+    }
+
     /// Convert our ToolDefinition to OpenAI API format
     fn convert_tools(&self, tools: &[ToolDefinition]) -> Vec<OpenAITool> {
         tools
@@ -199,6 +231,10 @@ impl OpenAIClient {
 
 #[async_trait]
 impl LlmClient for OpenAIClient {
+    fn stream_idle_timeout(&self) -> Duration {
+        self.stream_idle
+    }
+
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LlmError> {
         let url = format!("{}/chat/completions", self.base_url);
 
@@ -272,6 +308,7 @@ impl LlmClient for OpenAIClient {
         Ok(ChatResponse {
             content: choice.message.content.clone().unwrap_or_default(),
             thinking: choice.message.reasoning_content.clone(),
+            thinking_blocks: Vec::new(),
             tool_calls,
             finish_reason: choice.finish_reason.clone().unwrap_or_default(),
             usage: response_body.usage.map(|u| TokenUsage {
@@ -301,6 +338,8 @@ impl LlmClient for OpenAIClient {
             "model": request.model,
             "messages": self.convert_messages(&request.messages),
             "stream": true,
+            // Usage is only streamed (as a final chunk) when asked for
+            "stream_options": { "include_usage": true },
         });
 
         if let Some(ref tools) = request.tools {
@@ -485,13 +524,32 @@ impl LlmClient for OpenAIClient {
 struct OpenAIMessage {
     role: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    content: Option<String>,
+    content: Option<OpenAIContent>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_calls: Option<Vec<OpenAIToolCall>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_call_id: Option<String>,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum OpenAIContent {
+    Text(String),
+    Parts(Vec<OpenAIContentPart>),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum OpenAIContentPart {
+    Text { text: String },
+    ImageUrl { image_url: OpenAIImageUrl },
+}
+
+#[derive(Debug, Serialize)]
+struct OpenAIImageUrl {
+    url: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct OpenAIToolCall {
     id: String,
@@ -577,3 +635,45 @@ struct OpenAIStreamFunctionCall {
     name: Option<String>,
     arguments: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::llm::ImagePart;
+
+    fn messages_with_image() -> Vec<ChatMessage> {
+        vec![ChatMessage::user_with_images(
+            "What is in this picture?",
+            vec![ImagePart {
+                mime_type: "image/png".to_string(),
+                data: "iVBORw0KGgo=".to_string(),
+            }],
+        )]
+    }
+
+    #[test]
+    fn test_images_sent_as_image_url_parts() {
+        let client = OpenAIClient::new("https://api.openai.com/v1", None).with_image_input();
+        let body = serde_json::to_value(client.convert_messages(&messages_with_image())).unwrap();
+
+        assert_eq!(
+            body[0]["content"],
+            serde_json::json!([
+                { "type": "text", "text": "What is in this picture?" },
+                { "type": "image_url", "image_url": { "url": "data:image/png;base64,iVBORw0KGgo=" } },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_images_omitted_for_text_only_endpoints() {
+        let client = OpenAIClient::new("http://localhost:11434/v1", None);
+        let body = serde_json::to_value(client.convert_messages(&messages_with_image())).unwrap();
+
+        assert_eq!(body[0]["content"], "What is in this picture?\n\n[image omitted]");
+
+        // Messages without images keep plain string content
+        let body = serde_json::to_value(client.convert_messages(&[ChatMessage::user("Hi")])).unwrap();
+        assert_eq!(body[0]["content"], "Hi");
+    }
+}
@@ -4,6 +4,7 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Errors that can occur during LLM operations
@@ -21,6 +22,48 @@ pub enum LlmError {
     NotConfigured(String),
 }
 
+impl LlmError {
+    /// Whether the provider could not be reached at all, e.g. a local server that isn't running
+    pub fn is_connection_error(&self) -> bool {
+        matches!(self, LlmError::HttpError(e) if e.is_connect())
+    }
+}
+
+/// HTTP timeouts applied when building an LLM client
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClientTimeouts {
+    /// Limit for a whole request, including streaming the response
+    pub request: Duration,
+    /// Limit for establishing the connection
+    pub connect: Duration,
+    /// Longest gap allowed between streamed events before the generation is
+    /// treated as stalled
+    pub stream_idle: Duration,
+}
+
+impl Default for ClientTimeouts {
+    fn default() -> Self {
+        Self {
+            // Long generations can legitimately take minutes
+            request: Duration::from_secs(300),
+            connect: Duration::from_secs(10),
+            // Reasoning models can think for a while before the first token
+            stream_idle: Duration::from_secs(90),
+        }
+    }
+}
+
+impl ClientTimeouts {
+    /// Build a reqwest client that enforces these timeouts
+    pub fn build_client(&self) -> reqwest::Client {
+        reqwest::Client::builder()
+            .timeout(self.request)
+            .connect_timeout(self.connect)
+            .build()
+            .expect("Failed to create HTTP client")
+    }
+}
+
 /// Role of a message in a conversation
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -53,6 +96,40 @@ pub struct ChatMessage {
     /// Tool call ID (for tool response messages)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    /// Images attached to a user message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImagePart>,
+    /// Signed thinking that led to an assistant turn's tool calls, replayed
+    /// with them (Anthropic rejects tool results whose thinking was dropped)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thinking_blocks: Vec<ThinkingBlock>,
+}
+
+/// A block of extended thinking with the signature the provider issued for it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ThinkingBlock {
+    pub thinking: String,
+    pub signature: String,
+}
+
+/// An inline image sent alongside message text
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImagePart {
+    /// MIME type, e.g. "image/png"
+    pub mime_type: String,
+    /// Base64-encoded image bytes
+    pub data: String,
+}
+
+/// Stands in for each image when the provider only accepts text
+pub const IMAGE_OMITTED: &str = "[image omitted]";
+
+impl ImagePart {
+    /// The image as a `data:` URL (OpenAI's image_url format)
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type, self.data)
+    }
 }
 
 impl ChatMessage {
@@ -62,6 +139,8 @@ impl ChatMessage {
             content: Some(content.to_string()),
             tool_calls: None,
             tool_call_id: None,
+            images: Vec::new(),
+            thinking_blocks: Vec::new(),
         }
     }
 
@@ -71,6 +150,15 @@ impl ChatMessage {
             content: Some(content.to_string()),
             tool_calls: None,
             tool_call_id: None,
+            images: Vec::new(),
+            thinking_blocks: Vec::new(),
+        }
+    }
+
+    pub fn user_with_images(content: &str, images: Vec<ImagePart>) -> Self {
+        Self {
+            images,
+            ..Self::user(content)
         }
     }
 
@@ -80,7 +168,22 @@ impl ChatMessage {
             content: Some(content.to_string()),
             tool_calls: None,
             tool_call_id: None,
+            images: Vec::new(),
+            thinking_blocks: Vec::new(),
+        }
+    }
+
+    /// Message text with an "[image omitted]" marker per attached image,
+    /// for providers that can't take image input
+    pub fn text_with_image_placeholders(&self) -> Option<String> {
+        if self.images.is_empty() {
+            return self.content.clone();
         }
+        let placeholders = vec![IMAGE_OMITTED; self.images.len()].join(" ");
+        Some(match self.content.as_deref() {
+            Some(text) if !text.is_empty() => format!("{}\n\n{}", text, placeholders),
+            _ => placeholders,
+        })
     }
 }
 
@@ -142,6 +245,8 @@ pub struct ChatRequest {
     pub reasoning_effort: Option<String>,
     /// Budget tokens for thinking (Anthropic)
     pub thinking_budget: Option<u32>,
+    /// Mark the system prompt and large context blocks as cacheable (Anthropic only)
+    pub use_prompt_cache: bool,
 }
 
 
@@ -152,6 +257,8 @@ pub struct ChatResponse {
     pub content: String,
     /// Reasoning/thinking content if available
     pub thinking: Option<String>,
+    /// Signed thinking blocks to send back with the tool calls (Anthropic)
+    pub thinking_blocks: Vec<ThinkingBlock>,
     /// Tool calls made by the model
     pub tool_calls: Option<Vec<ToolCall>>,
     /// Reason for completion
@@ -176,6 +283,8 @@ pub enum StreamEvent {
     Content { delta: String },
     /// Reasoning/thinking token (for UI thinking panel)
     Thinking { delta: String },
+    /// A thinking block finished, with the signature needed to replay it
+    ThinkingBlockEnd { thinking: String, signature: String },
     /// Tool call being streamed (may come in parts)
     ToolCallStart {
         id: String,
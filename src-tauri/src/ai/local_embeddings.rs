@@ -0,0 +1,143 @@
+//! Offline embedding generation using fastembed (ONNX Runtime)
+//!
+//! Used for the "local/" model prefix so semantic search works without an API
+//! key or a running model server. Model files are downloaded into the app cache
+//! directory on first use and loaded models are kept for the rest of the session.
+//!
+//! Requires the `local-embeddings` cargo feature; without it every call returns
+//! an error explaining that this build has no local backend.
+
+use super::embeddings::{EmbeddingError, EmbeddingResult};
+
+/// Whether this build includes the local embedding backend
+pub fn is_available() -> bool {
+    cfg!(feature = "local-embeddings")
+}
+
+/// Generate an embedding on this machine
+///
+/// `model_name` is the model ID without the "local/" prefix (e.g. "bge-small-en-v1.5").
+/// Inference runs on a blocking thread since it's CPU-bound.
+#[cfg(feature = "local-embeddings")]
+pub async fn generate_local_embedding(
+    text: &str,
+    model_name: &str,
+) -> Result<EmbeddingResult, EmbeddingError> {
+    let text = text.to_string();
+    let model = model_name.to_string();
+
+    let embedding = tauri::async_runtime::spawn_blocking(move || backend::embed(&text, &model))
+        .await
+        .map_err(|e| EmbeddingError::LocalError(format!("Embedding task failed: {}", e)))??;
+
+    Ok(EmbeddingResult {
+        dimension: embedding.len(),
+        embedding,
+        model: model_name.to_string(),
+        tokens_used: None,
+    })
+}
+
+/// Generate an embedding on this machine
+///
+/// Always fails: this build was compiled without the `local-embeddings` feature.
+#[cfg(not(feature = "local-embeddings"))]
+pub async fn generate_local_embedding(
+    _text: &str,
+    model_name: &str,
+) -> Result<EmbeddingResult, EmbeddingError> {
+    Err(EmbeddingError::LocalError(format!(
+        "Local embeddings are not available in this build (model: {})",
+        model_name
+    )))
+}
+
+#[cfg(feature = "local-embeddings")]
+mod backend {
+    use super::EmbeddingError;
+    use directories::ProjectDirs;
+    use fastembed::{EmbeddingModel, TextEmbedding, TextInitOptions};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::{Mutex, OnceLock};
+
+    /// Models loaded this session, keyed by model ID
+    static LOADED_MODELS: OnceLock<Mutex<HashMap<String, TextEmbedding>>> = OnceLock::new();
+
+    /// Map a model ID from the known embedding models list to a fastembed model
+    fn resolve_model(model_name: &str) -> Option<EmbeddingModel> {
+        match model_name.to_lowercase().as_str() {
+            "bge-small-en-v1.5" => Some(EmbeddingModel::BGESmallENV15),
+            "all-minilm-l6-v2" => Some(EmbeddingModel::AllMiniLML6V2),
+            "nomic-embed-text-v1.5" => Some(EmbeddingModel::NomicEmbedTextV15),
+            // Fall back to fastembed's own names (e.g. "BGEBaseENV15")
+            other => other.parse().ok(),
+        }
+    }
+
+    /// Directory the model files are downloaded into
+    fn model_cache_dir() -> Result<PathBuf, EmbeddingError> {
+        ProjectDirs::from("com", "inkling", "Inkling")
+            .map(|dirs| dirs.cache_dir().join("embedding-models"))
+            .ok_or_else(|| EmbeddingError::LocalError("Could not determine cache directory".to_string()))
+    }
+
+    /// Embed `text`, downloading and loading the model first if needed
+    pub fn embed(text: &str, model_name: &str) -> Result<Vec<f32>, EmbeddingError> {
+        let model = resolve_model(model_name).ok_or_else(|| {
+            EmbeddingError::LocalError(format!("Unknown local embedding model: {}", model_name))
+        })?;
+
+        let mut loaded = LOADED_MODELS
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .map_err(|_| EmbeddingError::LocalError("Local model cache is poisoned".to_string()))?;
+
+        let key = model_name.to_lowercase();
+        if !loaded.contains_key(&key) {
+            log::info!("[Local Embedding] Loading model {} (downloads on first use)", model_name);
+            let options = TextInitOptions::new(model).with_cache_dir(model_cache_dir()?);
+            let embedder = TextEmbedding::try_new(options)
+                .map_err(|e| EmbeddingError::LocalError(format!("Failed to load model: {}", e)))?;
+            loaded.insert(key.clone(), embedder);
+        }
+
+        let embedder = loaded.get_mut(&key).expect("model was just loaded");
+        embedder
+            .embed(vec![text], None)
+            .map_err(|e| EmbeddingError::LocalError(format!("Inference failed: {}", e)))?
+            .into_iter()
+            .next()
+            .filter(|embedding| !embedding.is_empty())
+            .ok_or(EmbeddingError::EmptyEmbedding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "local-embeddings")]
+    #[tokio::test]
+    #[ignore = "downloads the model on first run"]
+    async fn test_local_embedding_has_model_dimension() {
+        let first = generate_local_embedding("Local-first notes", "bge-small-en-v1.5")
+            .await
+            .unwrap();
+        assert_eq!(first.dimension, 384);
+        assert_eq!(first.embedding.len(), 384);
+
+        let second = generate_local_embedding("A different sentence entirely", "bge-small-en-v1.5")
+            .await
+            .unwrap();
+        assert_eq!(second.dimension, first.dimension);
+    }
+
+    #[cfg(not(feature = "local-embeddings"))]
+    #[tokio::test]
+    async fn test_local_embedding_unavailable_without_feature() {
+        assert!(!is_available());
+        let result = generate_local_embedding("Local-first notes", "bge-small-en-v1.5").await;
+        assert!(matches!(result, Err(EmbeddingError::LocalError(_))));
+    }
+}
@@ -5,8 +5,11 @@ mod config;
 pub mod deep_research_agent;
 pub mod embeddings;
 pub mod export_agent;
+pub mod history;
 pub mod inline_assistant;
 pub mod llm;
+pub mod local_embeddings;
+pub mod pricing;
 pub mod rag;
 pub mod streaming_agent;
 pub mod summarization_agent;
@@ -21,8 +24,10 @@ pub use chat_executor::*;
 pub use config::*;
 pub use deep_research_agent::*;
 pub use embeddings::*;
+pub use history::*;
 pub use inline_assistant::*;
 pub use llm::create_client;
+pub use pricing::*;
 pub use rag::*;
 pub use streaming_agent::*;
 pub use summarization_agent::*;
@@ -0,0 +1,204 @@
+//! Model Pricing and Cost Estimation
+//!
+//! Estimates what chat responses cost from the token usage stored on assistant
+//! messages and a per-model price table. Prices are in USD per million tokens
+//! and can be overridden in settings; models without a price (e.g. local ones)
+//! are counted as free and reported separately.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::db::settings;
+use crate::models::TokenUsage;
+
+/// Settings key holding the pricing table as JSON
+pub const MODEL_PRICING_SETTING: &str = "model_pricing";
+
+/// Price of one model in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl ModelPrice {
+    /// Estimated cost in USD of a request with these token counts
+    pub fn cost(&self, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+        (prompt_tokens as f64 * self.input_per_million + completion_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// Prices keyed by model name or model name prefix
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPricing {
+    pub models: BTreeMap<String, ModelPrice>,
+}
+
+impl Default for ModelPricing {
+    fn default() -> Self {
+        let price = |input_per_million, output_per_million| ModelPrice {
+            input_per_million,
+            output_per_million,
+        };
+        let models = [
+            ("gpt-5", price(1.25, 10.0)),
+            ("gpt-5-mini", price(0.25, 2.0)),
+            ("gpt-5-nano", price(0.05, 0.4)),
+            ("gpt-4.1", price(2.0, 8.0)),
+            ("gpt-4.1-mini", price(0.4, 1.6)),
+            ("gpt-4.1-nano", price(0.1, 0.4)),
+            ("gpt-4o", price(2.5, 10.0)),
+            ("gpt-4o-mini", price(0.15, 0.6)),
+            ("claude-opus-4", price(15.0, 75.0)),
+            ("claude-sonnet-4", price(3.0, 15.0)),
+            ("claude-haiku-4", price(1.0, 5.0)),
+            ("claude-3-5-haiku", price(0.8, 4.0)),
+            ("gemini-2.5-pro", price(1.25, 10.0)),
+            ("gemini-2.5-flash", price(0.3, 2.5)),
+        ];
+        Self {
+            models: models.into_iter().map(|(model, price)| (model.to_string(), price)).collect(),
+        }
+    }
+}
+
+impl ModelPricing {
+    /// Price for a model: an exact match, or else the longest key the model name starts with
+    /// (so "claude-sonnet-4-5-20250929" uses the "claude-sonnet-4" price)
+    pub fn price_for(&self, model: &str) -> Option<&ModelPrice> {
+        self.models.get(model).or_else(|| {
+            self.models
+                .iter()
+                .filter(|(key, _)| model.starts_with(key.as_str()))
+                .max_by_key(|(key, _)| key.len())
+                .map(|(_, price)| price)
+        })
+    }
+}
+
+/// Token totals and estimated cost for a set of responses
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostEstimate {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub estimated_cost_usd: f64,
+    /// Models that had usage but no price, so counted as free
+    pub unpriced_models: Vec<String>,
+}
+
+/// Estimate the cost of responses given as (model, usage) pairs
+pub fn estimate_cost<'a>(
+    usage: impl IntoIterator<Item = (Option<&'a str>, &'a TokenUsage)>,
+    pricing: &ModelPricing,
+) -> CostEstimate {
+    let mut estimate = CostEstimate::default();
+    for (model, usage) in usage {
+        let (prompt, completion) = (usage.prompt_tokens as u64, usage.completion_tokens as u64);
+        estimate.prompt_tokens += prompt;
+        estimate.completion_tokens += completion;
+
+        let model = model.unwrap_or("unknown");
+        match pricing.price_for(model) {
+            Some(price) => estimate.estimated_cost_usd += price.cost(prompt, completion),
+            None => {
+                if !estimate.unpriced_models.iter().any(|m| m == model) {
+                    estimate.unpriced_models.push(model.to_string());
+                }
+            }
+        }
+    }
+    estimate
+}
+
+/// Load the pricing table, or the built-in one if none is saved
+pub fn load_model_pricing(conn: &Connection) -> ModelPricing {
+    match settings::get_setting(conn, MODEL_PRICING_SETTING) {
+        Ok(Some(json)) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            log::warn!("Invalid model pricing setting, using the defaults: {}", e);
+            ModelPricing::default()
+        }),
+        _ => ModelPricing::default(),
+    }
+}
+
+/// Save the pricing table
+pub fn save_model_pricing(conn: &Connection, pricing: &ModelPricing) -> Result<(), String> {
+    let json = serde_json::to_string(pricing).map_err(|e| e.to_string())?;
+    settings::set_setting(conn, MODEL_PRICING_SETTING, &json).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+
+    fn usage(prompt_tokens: u32, completion_tokens: u32) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    }
+
+    #[test]
+    fn test_estimate_cost_from_token_counts() {
+        let mut pricing = ModelPricing { models: BTreeMap::new() };
+        pricing.models.insert(
+            "test-model".to_string(),
+            ModelPrice {
+                input_per_million: 2.0,
+                output_per_million: 8.0,
+            },
+        );
+
+        let first = usage(1_000_000, 250_000);
+        let second = usage(500_000, 0);
+        let local = usage(1_000, 1_000);
+        let estimate = estimate_cost(
+            [
+                (Some("test-model"), &first),
+                (Some("test-model-2024"), &second),
+                (Some("llama3"), &local),
+            ],
+            &pricing,
+        );
+
+        // 1M in at $2 + 250k out at $8, then 500k in at $2 via the prefix match
+        assert!((estimate.estimated_cost_usd - 5.0).abs() < 1e-9);
+        assert_eq!(estimate.prompt_tokens, 1_501_000);
+        assert_eq!(estimate.completion_tokens, 251_000);
+        assert_eq!(estimate.unpriced_models, vec!["llama3"]);
+    }
+
+    #[test]
+    fn test_price_lookup_prefers_longest_prefix() {
+        let pricing = ModelPricing::default();
+        assert_eq!(pricing.price_for("gpt-4o-mini-2024-07-18"), pricing.models.get("gpt-4o-mini"));
+        assert_eq!(pricing.price_for("gpt-4o"), pricing.models.get("gpt-4o"));
+        assert_eq!(pricing.price_for("claude-sonnet-4-5-20250929"), pricing.models.get("claude-sonnet-4"));
+        assert_eq!(pricing.price_for("llama3.2"), None);
+    }
+
+    #[test]
+    fn test_pricing_setting_round_trip() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        assert_eq!(load_model_pricing(&conn), ModelPricing::default());
+
+        let mut pricing = ModelPricing::default();
+        pricing.models.insert(
+            "llama3".to_string(),
+            ModelPrice {
+                input_per_million: 0.0,
+                output_per_million: 0.0,
+            },
+        );
+        save_model_pricing(&conn, &pricing).unwrap();
+        assert_eq!(load_model_pricing(&conn), pricing);
+    }
+}
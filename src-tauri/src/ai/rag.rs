@@ -95,6 +95,8 @@ impl RagContext {
                 note_id: ctx.note_id.clone(),
                 note_title: ctx.title.clone(),
                 relevance: 1.0, // Explicit context has max relevance
+                start_offset: None,
+                end_offset: None,
             });
         }
         
@@ -103,6 +105,8 @@ impl RagContext {
                 note_id: ctx.note_id.clone(),
                 note_title: ctx.title.clone(),
                 relevance: ctx.relevance.unwrap_or(0.5),
+                start_offset: None,
+                end_offset: None,
             });
         }
         
@@ -415,6 +419,8 @@ pub fn resolve_citations(references: &[String], context: &RagContext) -> Vec<Cit
                     note_id: ctx.note_id.clone(),
                     note_title: ctx.title.clone(),
                     relevance: 1.0,
+                    start_offset: None,
+                    end_offset: None,
                 });
             }
         }
@@ -430,6 +436,8 @@ pub fn resolve_citations(references: &[String], context: &RagContext) -> Vec<Cit
                     note_id: ctx.note_id.clone(),
                     note_title: ctx.title.clone(),
                     relevance: ctx.relevance.unwrap_or(0.5),
+                    start_offset: None,
+                    end_offset: None,
                 });
             }
         }
@@ -444,6 +452,165 @@ pub fn resolve_citations(references: &[String], context: &RagContext) -> Vec<Cit
     citations
 }
 
+/// Minimum share of a claim's words a note sentence must contain to support it
+const MIN_SPAN_OVERLAP: f32 = 0.3;
+
+/// Common words ignored when matching claims to note text
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "your", "all", "any", "can", "has", "have",
+    "had", "was", "were", "this", "that", "these", "those", "with", "from", "into", "about",
+    "they", "them", "their", "there", "what", "when", "which", "who", "will", "would", "should",
+    "could", "its", "our", "also", "than", "then", "based", "according", "note", "notes",
+];
+
+/// Extract the claims attributed to each note reference in a response
+///
+/// A claim is the sentence containing a `[Note: Title]` marker, with the
+/// markers removed. Returns (title, claim) pairs.
+pub fn extract_cited_claims(content: &str) -> Vec<(String, String)> {
+    let re = regex::Regex::new(r"\[Note:\s*([^\]]+)\]").unwrap();
+    let is_boundary = |c: char| matches!(c, '.' | '!' | '?' | '\n');
+
+    re.captures_iter(content)
+        .filter_map(|cap| {
+            let marker = cap.get(0)?;
+            let title = cap.get(1)?.as_str().trim().to_string();
+
+            // The sentence around the marker. A marker right after a full stop
+            // ("... Friday. [Note: X]") belongs to the sentence before it.
+            let before = &content[..marker.start()];
+            let trimmed = before.trim_end();
+            let search_end = if trimmed.ends_with(['.', '!', '?']) && !before.ends_with('\n') {
+                trimmed.len() - 1
+            } else {
+                before.len()
+            };
+            let start = content[..search_end].rfind(is_boundary).map(|i| i + 1).unwrap_or(0);
+            let end = content[marker.end()..]
+                .find(is_boundary)
+                .map(|i| marker.end() + i)
+                .unwrap_or(content.len());
+
+            let claim = re.replace_all(&content[start..end], "");
+            let claim = claim.trim().trim_start_matches(['-', '*', ' ']).trim().to_string();
+            Some((title, claim))
+        })
+        .filter(|(_, claim)| !claim.is_empty())
+        .collect()
+}
+
+/// Lowercased content words used for matching
+fn match_words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3 || w.chars().all(|c| c.is_ascii_digit()))
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Split text into sentence-like spans, as trimmed (start, end) byte ranges
+fn sentence_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let at_end = match c {
+            '\n' => true,
+            '.' | '!' | '?' => chars.peek().is_none_or(|(_, next)| next.is_whitespace()),
+            _ => false,
+        };
+        if at_end {
+            let end = if c == '\n' { i } else { i + c.len_utf8() };
+            spans.push((start, end));
+            start = i + c.len_utf8();
+        }
+    }
+    spans.push((start, text.len()));
+
+    spans
+        .into_iter()
+        .filter_map(|(s, e)| {
+            let slice = &text[s..e];
+            let lead = slice.len() - slice.trim_start().len();
+            let trimmed = slice.trim();
+            (!trimmed.is_empty()).then(|| (s + lead, s + lead + trimmed.len()))
+        })
+        .collect()
+}
+
+/// Find the span of a note that best supports a claim, as UTF-16 offsets
+///
+/// Prefers the claim appearing verbatim in the note; otherwise picks the
+/// sentence sharing the most words with the claim. Offsets count UTF-16 code
+/// units, like find-in-note, so they index directly into a JavaScript string.
+pub fn find_supporting_span(note_content: &str, claim: &str) -> Option<(usize, usize)> {
+    let to_utf16 = |byte: usize| note_content[..byte].encode_utf16().count();
+
+    // Verbatim match (ASCII-case-insensitive keeps byte offsets aligned)
+    let claim_trimmed = claim.trim().trim_end_matches(['.', '!', '?']);
+    if claim_trimmed.len() >= 10 {
+        let haystack = note_content.to_ascii_lowercase();
+        if let Some(pos) = haystack.find(&claim_trimmed.to_ascii_lowercase()) {
+            return Some((to_utf16(pos), to_utf16(pos + claim_trimmed.len())));
+        }
+    }
+
+    let claim_words = match_words(claim);
+    if claim_words.is_empty() {
+        return None;
+    }
+
+    let mut best: Option<((usize, usize), usize)> = None;
+    for (start, end) in sentence_spans(note_content) {
+        let overlap = match_words(&note_content[start..end])
+            .intersection(&claim_words)
+            .count();
+        if overlap > best.map(|(_, o)| o).unwrap_or(0) {
+            best = Some(((start, end), overlap));
+        }
+    }
+
+    let ((start, end), overlap) = best?;
+    if (overlap as f32) / (claim_words.len() as f32) < MIN_SPAN_OVERLAP {
+        return None;
+    }
+    Some((to_utf16(start), to_utf16(end)))
+}
+
+/// Fill in the supporting span for citations whose claims can be located
+///
+/// Only full notes have offsets, since snippets don't map onto the note content.
+pub fn add_citation_offsets(citations: &mut [Citation], response: &str, context: &RagContext) {
+    let claims = extract_cited_claims(response);
+
+    for citation in citations.iter_mut() {
+        let Some(note) = context
+            .explicit_context
+            .iter()
+            .chain(context.retrieved_context.iter())
+            .find(|ctx| ctx.note_id == citation.note_id && ctx.is_full_note)
+        else {
+            continue;
+        };
+
+        let title = note.title.to_lowercase();
+        let span = claims
+            .iter()
+            .filter(|(ref_title, _)| {
+                let ref_lower = ref_title.to_lowercase();
+                title.contains(&ref_lower) || ref_lower.contains(&title)
+            })
+            .find_map(|(_, claim)| find_supporting_span(&note.content, claim));
+
+        if let Some((start, end)) = span {
+            citation.start_offset = Some(start);
+            citation.end_offset = Some(end);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -458,6 +625,46 @@ mod tests {
         assert!(refs.contains(&"Meeting Notes 2024".to_string()));
     }
     
+    #[test]
+    fn test_citation_offsets_point_at_supporting_sentence() {
+        let note_content = "# Launch Plan\n\nWe reviewed the budget last week. The product launch is scheduled for March 3 in Berlin. Marketing starts earlier.";
+        let context = RagContext {
+            explicit_context: vec![NoteContext {
+                note_id: "n1".to_string(),
+                title: "Launch Plan".to_string(),
+                content: note_content.to_string(),
+                is_full_note: true,
+                relevance: None,
+            }],
+            retrieved_context: vec![],
+            url_context: vec![],
+            all_note_ids: vec!["n1".to_string()],
+        };
+
+        let response = "Here's what I found. The launch happens on March 3, in Berlin [Note: Launch Plan]. Anything else?";
+        let mut citations = resolve_citations(&extract_note_references(response), &context);
+        add_citation_offsets(&mut citations, response, &context);
+
+        assert_eq!(citations.len(), 1);
+        let utf16_span = |text: &str, start: usize, end: usize| {
+            String::from_utf16(&text.encode_utf16().collect::<Vec<_>>()[start..end]).unwrap()
+        };
+        let (start, end) = (citations[0].start_offset.unwrap(), citations[0].end_offset.unwrap());
+        assert_eq!(
+            utf16_span(note_content, start, end),
+            "The product launch is scheduled for March 3 in Berlin."
+        );
+
+        // Verbatim quotes match exactly, with UTF-16 (not byte or char) offsets
+        let note = "Café ☕🫘 notes: the roaster ships on Tuesdays.";
+        let (start, end) = find_supporting_span(note, "The roaster ships on Tuesdays.").unwrap();
+        assert_eq!(start, 16);
+        assert_eq!(utf16_span(note, start, end), "the roaster ships on Tuesdays");
+
+        // Unrelated claims get no offsets
+        assert_eq!(find_supporting_span(note_content, "Quarterly revenue doubled"), None);
+    }
+
     #[test]
     fn test_truncate_for_context() {
         let short = "Hello world";
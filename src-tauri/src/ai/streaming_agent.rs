@@ -10,16 +10,21 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 use thiserror::Error;
 use tokio::sync::watch;
 
-use super::agent::{ToolCallRecord, ToolExecutor};
+use super::agent::{
+    execute_tool_with_timeout, finalize_run, tool_budget_exhausted_message, CumulativeTokenUsage,
+    ToolCallRecord, ToolExecutor, TOOL_BUDGET_SKIPPED_RESULT,
+};
 use super::config::AIProvider;
 use super::llm::{
-    create_client, ChatMessage, ChatRequest, LlmError, MessageRole, StreamEvent,
-    ToolCall, ToolDefinition,
+    create_client, ChatMessage, ChatRequest, LlmClient, LlmError, MessageRole, StreamEvent,
+    ThinkingBlock, TokenUsage, ToolCall, ToolDefinition,
 };
+use crate::models::{ReasoningSettings, ToolCallStatus};
 
 /// Errors that can occur during streaming agent execution
 #[derive(Error, Debug)]
@@ -32,6 +37,12 @@ pub enum StreamingAgentError {
     MaxIterationsExceeded(usize),
     #[error("Agent execution was cancelled")]
     Cancelled,
+    /// The provider could not be reached before anything was generated
+    #[error("Could not connect to provider: {0}")]
+    ConnectionFailed(LlmError),
+    /// The provider stopped sending events without closing the stream
+    #[error("Provider stopped responding (no events for {}s)", .0.as_secs_f64())]
+    IdleTimeout(Duration),
 }
 
 /// Result of running the streaming agent
@@ -46,6 +57,12 @@ pub struct StreamingAgentResult {
     pub tool_calls: Vec<ToolCallRecord>,
     /// Number of LLM iterations (including tool call rounds)
     pub iterations: usize,
+    /// What the executor's finalize hook committed, if anything
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finalized: Option<String>,
+    /// Token usage across all iterations, if the provider reported any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_usage: Option<CumulativeTokenUsage>,
 }
 
 /// Events emitted during streaming agent execution
@@ -58,10 +75,7 @@ pub enum AgentStreamEvent {
     /// Thinking/reasoning content from the LLM
     Thinking { content: String },
     /// Tool execution started
-    ToolStart {
-        tool: String,
-        args: Value,
-    },
+    ToolStart { tool: String, args: Value },
     /// Tool execution completed
     ToolResult {
         tool: String,
@@ -69,6 +83,11 @@ pub enum AgentStreamEvent {
         #[serde(skip_serializing_if = "Option::is_none")]
         preview: Option<String>,
     },
+    /// Progress of a tool call, from the moment the model starts requesting it
+    ToolCall {
+        name: String,
+        status: ToolCallStatus,
+    },
     /// Error occurred
     Error { message: String },
 }
@@ -97,6 +116,7 @@ struct PendingToolCall {
 /// * `tools` - Available tools for the agent
 /// * `executor` - Tool executor implementation
 /// * `max_iterations` - Maximum number of LLM calls (including tool rounds)
+/// * `reasoning` - Reasoning effort / thinking budget applied to every request
 /// * `cancel_rx` - Optional receiver for cancellation signals
 ///
 /// # Returns
@@ -111,16 +131,53 @@ pub async fn run_streaming_agent<E: ToolExecutor>(
     tools: Vec<ToolDefinition>,
     executor: &E,
     max_iterations: usize,
+    reasoning: &ReasoningSettings,
     cancel_rx: Option<watch::Receiver<bool>>,
 ) -> Result<StreamingAgentResult, StreamingAgentError> {
     let client = create_client(provider)?;
     let event_name = format!("chat-stream-{}", event_key);
-    
+    let emit = |event: AgentStreamEvent| {
+        let _ = app_handle.emit(&event_name, event);
+    };
+
+    run_streaming_agent_with_client(
+        client.as_ref(),
+        &emit,
+        model,
+        initial_messages,
+        tools,
+        executor,
+        max_iterations,
+        reasoning,
+        cancel_rx,
+    )
+    .await
+}
+
+/// Run the streaming agent loop against an existing LLM client
+///
+/// Events are passed to `emit` instead of being sent through the app handle,
+/// which lets callers (and tests) decide where they go.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_streaming_agent_with_client<E: ToolExecutor>(
+    client: &dyn LlmClient,
+    emit: &(dyn Fn(AgentStreamEvent) + Send + Sync),
+    model: &str,
+    initial_messages: Vec<ChatMessage>,
+    tools: Vec<ToolDefinition>,
+    executor: &E,
+    max_iterations: usize,
+    reasoning: &ReasoningSettings,
+    cancel_rx: Option<watch::Receiver<bool>>,
+) -> Result<StreamingAgentResult, StreamingAgentError> {
     let mut messages = initial_messages;
     let mut all_tool_calls: Vec<ToolCallRecord> = Vec::new();
     let mut all_thinking_content = String::new();
     let mut iterations = 0;
-    
+    let mut token_usage: Option<CumulativeTokenUsage> = None;
+    let max_tool_calls = executor.max_tool_calls();
+    let mut budget_exhausted = false;
+
     // Convert tool definitions to LLM format
     let llm_tools: Vec<super::llm::ToolDefinition> = tools
         .iter()
@@ -133,7 +190,7 @@ pub async fn run_streaming_agent<E: ToolExecutor>(
             },
         })
         .collect();
-    
+
     // Helper to check if cancelled
     let is_cancelled = || -> bool {
         if let Some(ref rx) = cancel_rx {
@@ -142,26 +199,29 @@ pub async fn run_streaming_agent<E: ToolExecutor>(
             false
         }
     };
-    
+
     loop {
         // Check for cancellation at the start of each iteration
         if is_cancelled() {
-            log::info!("[StreamingAgent] Cancelled before iteration {}", iterations + 1);
+            log::info!(
+                "[StreamingAgent] Cancelled before iteration {}",
+                iterations + 1
+            );
             return Err(StreamingAgentError::Cancelled);
         }
-        
+
         iterations += 1;
         if iterations > max_iterations {
             return Err(StreamingAgentError::MaxIterationsExceeded(max_iterations));
         }
-        
+
         log::debug!(
             "[StreamingAgent] Iteration {} - {} messages, {} tools",
             iterations,
             messages.len(),
             llm_tools.len()
         );
-        
+
         // Build chat request
         let request = ChatRequest {
             model: model.to_string(),
@@ -173,29 +233,56 @@ pub async fn run_streaming_agent<E: ToolExecutor>(
             },
             tool_choice: if llm_tools.is_empty() {
                 None
+            } else if budget_exhausted {
+                Some("none".to_string())
             } else {
                 Some("auto".to_string())
             },
             max_tokens: None,
             temperature: None,
-            enable_reasoning: false,
-            reasoning_effort: None,
-            thinking_budget: None,
+            enable_reasoning: reasoning.is_enabled(),
+            reasoning_effort: reasoning.reasoning_effort.clone(),
+            thinking_budget: reasoning.thinking_budget,
+            // Every iteration resends the same system prompt and context
+            use_prompt_cache: true,
         };
-        
+
         // Start streaming
-        let mut rx = client.chat_stream(request).await?;
-        
+        let mut rx = match client.chat_stream(request).await {
+            Err(e) if iterations == 1 && e.is_connection_error() => {
+                return Err(StreamingAgentError::ConnectionFailed(e));
+            }
+            result => result?,
+        };
+
         // Track state during streaming
         let mut content_buffer = String::new();
         let mut thinking_buffer = String::new();
+        let mut thinking_blocks = Vec::new();
         let mut pending_tool_calls: HashMap<String, PendingToolCall> = HashMap::new();
         // finish_reason is captured but not currently used (different providers use different values)
         let mut _finish_reason = String::new();
         let mut was_cancelled = false;
-        
-        // Process stream events
-        while let Some(event) = rx.recv().await {
+        let idle_timeout = client.stream_idle_timeout();
+        // Providers may report usage after Done, or resend a running total
+        // with every chunk (Gemini), so only the last report for this request counts
+        let mut request_usage: Option<TokenUsage> = None;
+
+        // Process stream events until the provider closes the stream, giving
+        // up if it goes quiet mid-stream
+        loop {
+            let event = match tokio::time::timeout(idle_timeout, rx.recv()).await {
+                Ok(Some(event)) => event,
+                Ok(None) => break,
+                Err(_) => {
+                    let error = StreamingAgentError::IdleTimeout(idle_timeout);
+                    log::error!("[StreamingAgent] {}", error);
+                    emit(AgentStreamEvent::Error {
+                        message: error.to_string(),
+                    });
+                    return Err(error);
+                }
+            };
             // Check for cancellation during streaming
             if is_cancelled() {
                 log::info!("[StreamingAgent] Cancelled during streaming");
@@ -206,22 +293,33 @@ pub async fn run_streaming_agent<E: ToolExecutor>(
                 StreamEvent::Content { delta } => {
                     content_buffer.push_str(&delta);
                     // Emit content chunk to frontend
-                    let _ = app_handle.emit(
-                        &event_name,
-                        AgentStreamEvent::Chunk { content: delta },
-                    );
+                    emit(AgentStreamEvent::Chunk { content: delta });
                 }
                 StreamEvent::Thinking { delta } => {
                     // Accumulate thinking content
                     thinking_buffer.push_str(&delta);
                     // Emit thinking content to frontend
-                    let _ = app_handle.emit(
-                        &event_name,
-                        AgentStreamEvent::Thinking { content: delta },
-                    );
+                    emit(AgentStreamEvent::Thinking { content: delta });
+                }
+                StreamEvent::ThinkingBlockEnd {
+                    thinking,
+                    signature,
+                } => {
+                    thinking_blocks.push(ThinkingBlock {
+                        thinking,
+                        signature,
+                    });
                 }
-                StreamEvent::ToolCallStart { id, name, thought_signature } => {
+                StreamEvent::ToolCallStart {
+                    id,
+                    name,
+                    thought_signature,
+                } => {
                     log::info!("[StreamingAgent] Tool call started: {} ({})", name, id);
+                    emit(AgentStreamEvent::ToolCall {
+                        name: name.clone(),
+                        status: ToolCallStatus::Started,
+                    });
                     pending_tool_calls.insert(
                         id.clone(),
                         PendingToolCall {
@@ -232,34 +330,50 @@ pub async fn run_streaming_agent<E: ToolExecutor>(
                         },
                     );
                 }
-                StreamEvent::ToolCallDelta { id, arguments_delta } => {
+                StreamEvent::ToolCallDelta {
+                    id,
+                    arguments_delta,
+                } => {
                     if let Some(tc) = pending_tool_calls.get_mut(&id) {
                         tc.arguments.push_str(&arguments_delta);
                     }
                 }
-                StreamEvent::Done { finish_reason: reason } => {
-                    _finish_reason = reason;
-                    break;
+                StreamEvent::Done {
+                    finish_reason: reason,
+                } => {
+                    // Keep reading: usage can still follow
+                    if _finish_reason.is_empty() {
+                        _finish_reason = reason;
+                    }
                 }
                 StreamEvent::Error { message } => {
                     log::error!("[StreamingAgent] Stream error: {}", message);
-                    let _ = app_handle.emit(
-                        &event_name,
-                        AgentStreamEvent::Error { message: message.clone() },
-                    );
+                    emit(AgentStreamEvent::Error {
+                        message: message.clone(),
+                    });
                     return Err(StreamingAgentError::ToolError(message));
                 }
-                StreamEvent::Usage { .. } => {
-                    // Token usage - can be tracked if needed
+                StreamEvent::Usage {
+                    prompt_tokens,
+                    completion_tokens,
+                } => {
+                    request_usage = Some(TokenUsage {
+                        prompt_tokens,
+                        completion_tokens,
+                        total_tokens: prompt_tokens + completion_tokens,
+                    });
                 }
             }
         }
-        
+        if let Some(usage) = request_usage {
+            token_usage.get_or_insert_with(Default::default).add(&usage);
+        }
+
         // Merge thinking content from this iteration
         if !thinking_buffer.is_empty() {
             all_thinking_content.push_str(&thinking_buffer);
         }
-        
+
         // If cancelled during streaming, return partial content
         if was_cancelled {
             log::info!(
@@ -272,9 +386,11 @@ pub async fn run_streaming_agent<E: ToolExecutor>(
                 thinking_content: all_thinking_content,
                 tool_calls: all_tool_calls,
                 iterations,
+                finalized: None,
+                token_usage,
             });
         }
-        
+
         // Check if we have tool calls to execute
         // If there are pending tool calls, execute them regardless of the specific finish_reason.
         // Different providers use different finish_reason values:
@@ -282,12 +398,25 @@ pub async fn run_streaming_agent<E: ToolExecutor>(
         // - Anthropic: "tool_use" or "end_turn"
         // - Google Gemini: "STOP" or other values
         // The key check is whether we accumulated tool calls during streaming.
-        if !pending_tool_calls.is_empty() {
+        // Once the tool call budget is spent, further calls are ignored and
+        // the streamed content is the final response.
+        if budget_exhausted && !pending_tool_calls.is_empty() {
+            log::warn!(
+                "[StreamingAgent] Model kept calling tools after its budget of {} was spent; finishing",
+                max_tool_calls
+            );
+            for tc in pending_tool_calls.values() {
+                emit(AgentStreamEvent::ToolCall {
+                    name: tc.name.clone(),
+                    status: ToolCallStatus::Failed,
+                });
+            }
+        } else if !pending_tool_calls.is_empty() {
             log::info!(
                 "[StreamingAgent] Processing {} tool calls",
                 pending_tool_calls.len()
             );
-            
+
             // Convert pending tool calls to the format needed for messages
             let tool_calls: Vec<ToolCall> = pending_tool_calls
                 .values()
@@ -301,7 +430,7 @@ pub async fn run_streaming_agent<E: ToolExecutor>(
                     thought_signature: tc.thought_signature.clone(),
                 })
                 .collect();
-            
+
             // Add assistant message with tool calls
             messages.push(ChatMessage {
                 role: MessageRole::Assistant,
@@ -312,47 +441,68 @@ pub async fn run_streaming_agent<E: ToolExecutor>(
                 },
                 tool_calls: Some(tool_calls.clone()),
                 tool_call_id: None,
+                images: Vec::new(),
+                thinking_blocks,
             });
-            
+
             // Execute each tool call
             for tc in tool_calls {
                 // Check for cancellation before each tool execution
                 if is_cancelled() {
-                    log::info!("[StreamingAgent] Cancelled before tool execution: {}", tc.function.name);
+                    log::info!(
+                        "[StreamingAgent] Cancelled before tool execution: {}",
+                        tc.function.name
+                    );
                     return Ok(StreamingAgentResult {
                         content: content_buffer,
                         thinking_content: all_thinking_content,
                         tool_calls: all_tool_calls,
                         iterations,
+                        finalized: None,
+                        token_usage,
                     });
                 }
-                
+
+                if all_tool_calls.len() >= max_tool_calls {
+                    emit(AgentStreamEvent::ToolCall {
+                        name: tc.function.name.clone(),
+                        status: ToolCallStatus::Failed,
+                    });
+                    messages.push(ChatMessage {
+                        role: MessageRole::Tool,
+                        content: Some(TOOL_BUDGET_SKIPPED_RESULT.to_string()),
+                        tool_calls: None,
+                        tool_call_id: Some(tc.id.clone()),
+                        images: Vec::new(),
+                        thinking_blocks: Vec::new(),
+                    });
+                    continue;
+                }
+
                 let args: Value = serde_json::from_str(&tc.function.arguments)
                     .unwrap_or(Value::Object(serde_json::Map::new()));
-                
+
                 // Emit tool start event
-                let _ = app_handle.emit(
-                    &event_name,
-                    AgentStreamEvent::ToolStart {
-                        tool: tc.function.name.clone(),
-                        args: args.clone(),
-                    },
-                );
-                
+                emit(AgentStreamEvent::ToolStart {
+                    tool: tc.function.name.clone(),
+                    args: args.clone(),
+                });
+
                 log::info!(
                     "[StreamingAgent] Executing tool: {} with args: {}",
                     tc.function.name,
                     args
                 );
-                
+
                 // Execute the tool
-                let result = executor.execute(&tc.function.name, args.clone()).await;
-                
+                let result =
+                    execute_tool_with_timeout(executor, &tc.function.name, args.clone()).await;
+
                 let (result_str, success) = match &result {
                     Ok(s) => (s.clone(), true),
                     Err(e) => (format!("Error: {}", e), false),
                 };
-                
+
                 log::info!(
                     "[StreamingAgent] Tool {} result (success={}): {}",
                     tc.function.name,
@@ -363,60 +513,550 @@ pub async fn run_streaming_agent<E: ToolExecutor>(
                         result_str.clone()
                     }
                 );
-                
+
                 // Emit tool result event with full content (no truncation)
                 let preview = Some(result_str.clone());
-                
-                let _ = app_handle.emit(
-                    &event_name,
-                    AgentStreamEvent::ToolResult {
-                        tool: tc.function.name.clone(),
-                        success,
-                        preview,
+
+                emit(AgentStreamEvent::ToolResult {
+                    tool: tc.function.name.clone(),
+                    success,
+                    preview,
+                });
+
+                emit(AgentStreamEvent::ToolCall {
+                    name: tc.function.name.clone(),
+                    status: if success {
+                        ToolCallStatus::Completed
+                    } else {
+                        ToolCallStatus::Failed
                     },
-                );
-                
+                });
+
                 // Record the tool call
                 all_tool_calls.push(ToolCallRecord {
                     tool_name: tc.function.name.clone(),
                     arguments: args,
                     result: result_str.clone(),
                 });
-                
+
                 // Add tool result message
                 messages.push(ChatMessage {
                     role: MessageRole::Tool,
                     content: Some(result_str),
                     tool_calls: None,
                     tool_call_id: Some(tc.id.clone()),
+                    images: Vec::new(),
+                    thinking_blocks: Vec::new(),
                 });
             }
-            
+
+            // Out of tool calls: tell the model to wrap up
+            if all_tool_calls.len() >= max_tool_calls {
+                log::warn!(
+                    "[StreamingAgent] Reached tool call budget of {}",
+                    max_tool_calls
+                );
+                budget_exhausted = true;
+                messages.push(ChatMessage::user(&tool_budget_exhausted_message(
+                    max_tool_calls,
+                )));
+            }
+
             // Continue the loop to get the next response
             continue;
         }
-        
+
         // No tool calls - this is the final response
         log::info!(
             "[StreamingAgent] Completed after {} iterations, {} tool calls",
             iterations,
             all_tool_calls.len()
         );
-        
+        let finalized = finalize_run(executor, &content_buffer, &all_tool_calls).await;
+
         return Ok(StreamingAgentResult {
             content: content_buffer,
             thinking_content: all_thinking_content,
             tool_calls: all_tool_calls,
             iterations,
+            finalized,
+            token_usage,
         });
     }
 }
 
+/// Run the streaming agent with the first of `candidates` (model, provider) that can be reached
+///
+/// The next candidate is only tried when the previous provider could not be
+/// connected to before anything was generated, so tools never run twice. Each
+/// candidate runs tools with an executor from `make_executor` for its model and provider.
+/// Returns the result and the index of the candidate that produced it.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_streaming_agent_with_fallback<E: ToolExecutor>(
+    app_handle: &AppHandle,
+    event_key: &str,
+    candidates: &[(String, AIProvider)],
+    initial_messages: Vec<ChatMessage>,
+    tools: Vec<ToolDefinition>,
+    make_executor: impl Fn(&str, &AIProvider) -> E,
+    max_iterations: usize,
+    reasoning: &ReasoningSettings,
+    cancel_rx: Option<watch::Receiver<bool>>,
+) -> Result<(StreamingAgentResult, usize), StreamingAgentError> {
+    // A misconfigured fallback shouldn't stop the providers before it from being used
+    let mut clients = Vec::new();
+    for (index, (model, provider)) in candidates.iter().enumerate() {
+        match create_client(provider) {
+            Ok(client) => clients.push((
+                client,
+                model.as_str(),
+                make_executor(model, provider),
+                index,
+            )),
+            Err(e) if index > 0 => {
+                log::warn!(
+                    "[StreamingAgent] Skipping fallback provider {}: {}",
+                    provider.name,
+                    e
+                );
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let client_refs: Vec<(&dyn LlmClient, &str, &E)> = clients
+        .iter()
+        .map(|(client, model, executor, _)| (client.as_ref(), *model, executor))
+        .collect();
+
+    let event_name = format!("chat-stream-{}", event_key);
+    let emit = |event: AgentStreamEvent| {
+        let _ = app_handle.emit(&event_name, event);
+    };
+
+    let (result, used) = run_streaming_agent_with_fallback_clients(
+        &client_refs,
+        &emit,
+        initial_messages,
+        tools,
+        max_iterations,
+        reasoning,
+        cancel_rx,
+    )
+    .await?;
+    Ok((result, clients[used].3))
+}
+
+/// Run the streaming agent loop against each (client, model, executor) in turn until one can be reached
+///
+/// Returns the result and the index of the client that produced it.
+pub async fn run_streaming_agent_with_fallback_clients<E: ToolExecutor>(
+    clients: &[(&dyn LlmClient, &str, &E)],
+    emit: &(dyn Fn(AgentStreamEvent) + Send + Sync),
+    initial_messages: Vec<ChatMessage>,
+    tools: Vec<ToolDefinition>,
+    max_iterations: usize,
+    reasoning: &ReasoningSettings,
+    cancel_rx: Option<watch::Receiver<bool>>,
+) -> Result<(StreamingAgentResult, usize), StreamingAgentError> {
+    for (index, (client, model, executor)) in clients.iter().enumerate() {
+        let result = run_streaming_agent_with_client(
+            *client,
+            emit,
+            model,
+            initial_messages.clone(),
+            tools.clone(),
+            *executor,
+            max_iterations,
+            reasoning,
+            cancel_rx.clone(),
+        )
+        .await;
+        match result {
+            Err(StreamingAgentError::ConnectionFailed(e)) if index + 1 < clients.len() => {
+                log::warn!(
+                    "[StreamingAgent] Could not reach provider for {}, falling back: {}",
+                    model,
+                    e
+                );
+            }
+            result => return result.map(|r| (r, index)),
+        }
+    }
+    Err(LlmError::NotConfigured("No AI provider available".to_string()).into())
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use crate::ai::llm::ChatResponse;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use tokio::sync::mpsc;
+
+    /// Streams a tool call on the first request and a text answer once the tool result is in
+    struct MockToolStreamClient;
+
+    #[async_trait]
+    impl LlmClient for MockToolStreamClient {
+        async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, LlmError> {
+            Err(LlmError::NotConfigured(
+                "non-streaming not supported by mock".to_string(),
+            ))
+        }
+
+        async fn chat_stream(
+            &self,
+            request: ChatRequest,
+        ) -> Result<mpsc::Receiver<StreamEvent>, LlmError> {
+            let (tx, rx) = mpsc::channel(16);
+            let answered = matches!(
+                request.messages.last().map(|m| &m.role),
+                Some(MessageRole::Tool)
+            );
+            let events = if answered {
+                vec![
+                    StreamEvent::Content {
+                        delta: "Found it".to_string(),
+                    },
+                    // Running totals, reported after Done like Anthropic and OpenAI do
+                    StreamEvent::Done {
+                        finish_reason: "stop".to_string(),
+                    },
+                    StreamEvent::Usage {
+                        prompt_tokens: 120,
+                        completion_tokens: 4,
+                    },
+                    StreamEvent::Usage {
+                        prompt_tokens: 120,
+                        completion_tokens: 8,
+                    },
+                ]
+            } else {
+                vec![
+                    StreamEvent::ToolCallStart {
+                        id: "call_1".to_string(),
+                        name: "search_notes".to_string(),
+                        thought_signature: None,
+                    },
+                    StreamEvent::ToolCallDelta {
+                        id: "call_1".to_string(),
+                        arguments_delta: r#"{"query":"rust"}"#.to_string(),
+                    },
+                    StreamEvent::Done {
+                        finish_reason: "tool_calls".to_string(),
+                    },
+                    StreamEvent::Usage {
+                        prompt_tokens: 100,
+                        completion_tokens: 12,
+                    },
+                ]
+            };
+            for event in events {
+                tx.send(event).await.unwrap();
+            }
+            Ok(rx)
+        }
+    }
+
+    /// Resolves no host names, so requests fail to connect without opening a socket
+    struct FailingResolver;
+
+    impl reqwest::dns::Resolve for FailingResolver {
+        fn resolve(&self, _name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+            Box::pin(async { Err("no such host".into()) })
+        }
+    }
+
+    /// Fails the way a provider that isn't running does: the connection can't be made
+    struct UnreachableClient;
+
+    #[async_trait]
+    impl LlmClient for UnreachableClient {
+        async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, LlmError> {
+            Err(LlmError::NotConfigured(
+                "non-streaming not supported by mock".to_string(),
+            ))
+        }
+
+        async fn chat_stream(
+            &self,
+            _request: ChatRequest,
+        ) -> Result<mpsc::Receiver<StreamEvent>, LlmError> {
+            let client = reqwest::Client::builder()
+                .no_proxy()
+                .dns_resolver(std::sync::Arc::new(FailingResolver))
+                .build()?;
+            Err(client
+                .get("http://provider.invalid")
+                .send()
+                .await
+                .expect_err("resolution always fails")
+                .into())
+        }
+    }
+
+    /// Sends one chunk, then keeps the stream open without sending anything else
+    struct SilentStreamClient {
+        open_stream: Mutex<Option<mpsc::Sender<StreamEvent>>>,
+    }
+
+    #[async_trait]
+    impl LlmClient for SilentStreamClient {
+        async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, LlmError> {
+            Err(LlmError::NotConfigured(
+                "non-streaming not supported by mock".to_string(),
+            ))
+        }
+
+        async fn chat_stream(
+            &self,
+            _request: ChatRequest,
+        ) -> Result<mpsc::Receiver<StreamEvent>, LlmError> {
+            let (tx, rx) = mpsc::channel(16);
+            tx.send(StreamEvent::Content {
+                delta: "Let me".to_string(),
+            })
+            .await
+            .unwrap();
+            *self.open_stream.lock().unwrap() = Some(tx);
+            Ok(rx)
+        }
+
+        fn stream_idle_timeout(&self) -> Duration {
+            Duration::from_millis(50)
+        }
+    }
+
+    struct MockExecutor;
+
+    #[async_trait]
+    impl ToolExecutor for MockExecutor {
+        async fn execute(&self, _name: &str, _args: Value) -> Result<String, String> {
+            Ok("1 note found".to_string())
+        }
+    }
+
+    /// Answers every tool call with its own name, to tell executors apart
+    struct NamedExecutor(&'static str);
+
+    #[async_trait]
+    impl ToolExecutor for NamedExecutor {
+        async fn execute(&self, _name: &str, _args: Value) -> Result<String, String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_when_provider_unreachable() {
+        let emit = |_event: AgentStreamEvent| {};
+        let (local, cloud) = (
+            NamedExecutor("local executor"),
+            NamedExecutor("cloud executor"),
+        );
+        let clients: Vec<(&dyn LlmClient, &str, &NamedExecutor)> = vec![
+            (&UnreachableClient, "local-model", &local),
+            (&MockToolStreamClient, "cloud-model", &cloud),
+        ];
+
+        let (result, used) = run_streaming_agent_with_fallback_clients(
+            &clients,
+            &emit,
+            vec![ChatMessage::user("Find my rust notes")],
+            vec![],
+            5,
+            &ReasoningSettings::default(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(used, 1);
+        assert_eq!(result.content, "Found it");
+        // Tools ran with the executor for the provider that answered
+        assert_eq!(result.tool_calls[0].result, "cloud executor");
+
+        // With nothing left to fall back to, the connection error is returned
+        let err = run_streaming_agent_with_fallback_clients(
+            &clients[..1],
+            &emit,
+            vec![ChatMessage::user("Find my rust notes")],
+            vec![],
+            5,
+            &ReasoningSettings::default(),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, StreamingAgentError::ConnectionFailed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_stalled_stream_times_out() {
+        let client = SilentStreamClient {
+            open_stream: Mutex::new(None),
+        };
+        let events = Mutex::new(Vec::new());
+        let emit = |event: AgentStreamEvent| events.lock().unwrap().push(event);
+
+        let err = run_streaming_agent_with_client(
+            &client,
+            &emit,
+            "mock-model",
+            vec![ChatMessage::user("Summarize my week")],
+            vec![],
+            &MockExecutor,
+            5,
+            &ReasoningSettings::default(),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(err, StreamingAgentError::IdleTimeout(d) if d == Duration::from_millis(50))
+        );
+
+        // The stream was still open, so the loop gave up rather than seeing it end
+        assert!(client
+            .open_stream
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|tx| tx.is_closed()));
+        let events = events.lock().unwrap();
+        assert!(matches!(&events[0], AgentStreamEvent::Chunk { content } if content == "Let me"));
+        assert!(matches!(
+            events.last(),
+            Some(AgentStreamEvent::Error { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_progress_events() {
+        let events = Mutex::new(Vec::new());
+        let emit = |event: AgentStreamEvent| events.lock().unwrap().push(event);
+
+        let result = run_streaming_agent_with_client(
+            &MockToolStreamClient,
+            &emit,
+            "mock-model",
+            vec![ChatMessage::user("Find my rust notes")],
+            vec![],
+            &MockExecutor,
+            5,
+            &ReasoningSettings::default(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.content, "Found it");
+        assert_eq!(result.tool_calls.len(), 1);
+        // One report per request: the tool call round plus the final answer's last total
+        let usage = result.token_usage.unwrap();
+        assert_eq!(
+            (usage.total_prompt_tokens, usage.total_completion_tokens),
+            (220, 20)
+        );
+
+        let tool_calls: Vec<(String, ToolCallStatus)> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|e| match e {
+                AgentStreamEvent::ToolCall { name, status } => Some((name.clone(), *status)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            tool_calls,
+            vec![
+                ("search_notes".to_string(), ToolCallStatus::Started),
+                ("search_notes".to_string(), ToolCallStatus::Completed),
+            ]
+        );
+
+        let json = serde_json::to_string(&AgentStreamEvent::ToolCall {
+            name: "search_notes".to_string(),
+            status: ToolCallStatus::Started,
+        })
+        .unwrap();
+        assert!(json.contains("\"type\":\"tool_call\""));
+        assert!(json.contains("\"status\":\"started\""));
+    }
+
+    /// Records each request and streams a thought followed by the answer
+    struct MockThinkingClient {
+        requests: Mutex<Vec<ChatRequest>>,
+    }
+
+    #[async_trait]
+    impl LlmClient for MockThinkingClient {
+        async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, LlmError> {
+            Err(LlmError::NotConfigured(
+                "non-streaming not supported by mock".to_string(),
+            ))
+        }
+
+        async fn chat_stream(
+            &self,
+            request: ChatRequest,
+        ) -> Result<mpsc::Receiver<StreamEvent>, LlmError> {
+            self.requests.lock().unwrap().push(request);
+            let (tx, rx) = mpsc::channel(16);
+            for event in [
+                StreamEvent::Thinking {
+                    delta: "Weighing options".to_string(),
+                },
+                StreamEvent::Content {
+                    delta: "Go with B".to_string(),
+                },
+                StreamEvent::Done {
+                    finish_reason: "stop".to_string(),
+                },
+            ] {
+                tx.send(event).await.unwrap();
+            }
+            Ok(rx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reasoning_settings_reach_request_and_thinking_is_streamed() {
+        let client = MockThinkingClient {
+            requests: Mutex::new(Vec::new()),
+        };
+        let events = Mutex::new(Vec::new());
+        let emit = |event: AgentStreamEvent| events.lock().unwrap().push(event);
+        let reasoning = ReasoningSettings {
+            reasoning_effort: Some("medium".to_string()),
+            thinking_budget: Some(2048),
+        };
+
+        let result = run_streaming_agent_with_client(
+            &client,
+            &emit,
+            "mock-model",
+            vec![ChatMessage::user("A or B?")],
+            vec![],
+            &MockExecutor,
+            5,
+            &reasoning,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let requests = client.requests.lock().unwrap();
+        assert!(requests[0].enable_reasoning);
+        assert_eq!(requests[0].reasoning_effort.as_deref(), Some("medium"));
+        assert_eq!(requests[0].thinking_budget, Some(2048));
+
+        assert_eq!(result.thinking_content, "Weighing options");
+        assert_eq!(result.content, "Go with B");
+        assert!(events.lock().unwrap().iter().any(|e| matches!(
+            e,
+            AgentStreamEvent::Thinking { content } if content == "Weighing options"
+        )));
+    }
+
     #[test]
     fn test_streaming_agent_result_serialization() {
         let result = StreamingAgentResult {
@@ -424,13 +1064,15 @@ mod tests {
             thinking_content: String::new(),
             tool_calls: vec![],
             iterations: 1,
+            finalized: None,
+            token_usage: None,
         };
-        
+
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("\"content\":\"Hello\""));
         assert!(json.contains("\"iterations\":1"));
     }
-    
+
     #[test]
     fn test_agent_stream_event_serialization() {
         let event = AgentStreamEvent::Chunk {
@@ -438,7 +1080,7 @@ mod tests {
         };
         let json = serde_json::to_string(&event).unwrap();
         assert!(json.contains("\"type\":\"chunk\""));
-        
+
         let tool_event = AgentStreamEvent::ToolStart {
             tool: "search_notes".to_string(),
             args: serde_json::json!({"query": "test"}),
@@ -448,4 +1090,3 @@ mod tests {
         assert!(json.contains("\"tool\":\"search_notes\""));
     }
 }
-
@@ -10,7 +10,7 @@ use std::sync::Arc;
 use tauri::AppHandle;
 
 use super::agent::{
-    run_agent_with_events, AgentError, CancellationToken, ToolDefinition, ToolExecutor,
+    run_agent_with_streaming_events, AgentError, CancellationToken, ToolDefinition, ToolExecutor,
 };
 use super::config::AIProvider;
 use super::tools::{
@@ -32,22 +32,111 @@ WORKFLOW:
 3. Use append_to_note to write a structured summary to the note
 4. You may search for related notes to provide additional context
 
-OUTPUT FORMAT:
-Structure your summary with:
-- A brief overview (1-2 sentences)
-- Key points as bullet points
-- Important details or takeaways
-- Any relevant connections to other topics
-
 GUIDELINES:
 - Be concise but capture all essential information
 - Use clear, simple language
 - Maintain the original meaning without adding interpretation
 - Use markdown formatting (headings, bullets, bold) for clarity
 - If summarizing a document, mention its type (e.g., "This Excel spreadsheet contains...")
-- Call append_to_note with is_final=true for your last piece of content
+- Call append_to_note with is_final=true for your last piece of content"##;
+
+/// How long the summary should be
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryLength {
+    Short,
+    #[default]
+    Medium,
+    Long,
+}
+
+impl SummaryLength {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SummaryLength::Short => "short",
+            SummaryLength::Medium => "medium",
+            SummaryLength::Long => "long",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "short" => Some(SummaryLength::Short),
+            "medium" => Some(SummaryLength::Medium),
+            "long" => Some(SummaryLength::Long),
+            _ => None,
+        }
+    }
+
+    fn instructions(&self) -> &'static str {
+        match self {
+            SummaryLength::Short => "Keep it brief: about 50-100 words. Only the most important points.",
+            SummaryLength::Medium => "Aim for about 150-300 words. Cover the main points and key details.",
+            SummaryLength::Long => {
+                "Be thorough: about 400-700 words. Cover the main points, supporting details and nuances."
+            }
+        }
+    }
+}
 
-Start your summary with a level-2 heading titled Summary."##;
+/// How the summary should be written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryStyle {
+    #[default]
+    Bullets,
+    Paragraph,
+    Abstract,
+}
+
+impl SummaryStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SummaryStyle::Bullets => "bullets",
+            SummaryStyle::Paragraph => "paragraph",
+            SummaryStyle::Abstract => "abstract",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "bullets" => Some(SummaryStyle::Bullets),
+            "paragraph" => Some(SummaryStyle::Paragraph),
+            "abstract" => Some(SummaryStyle::Abstract),
+            _ => None,
+        }
+    }
+
+    fn instructions(&self) -> &'static str {
+        match self {
+            SummaryStyle::Bullets => {
+                "Structure your summary with:
+- A brief overview (1-2 sentences)
+- Key points as bullet points
+- Important details or takeaways
+- Any relevant connections to other topics"
+            }
+            SummaryStyle::Paragraph => {
+                "Write the summary as flowing prose in short paragraphs. \
+Do not use bullet points or numbered lists; connect ideas with transitions."
+            }
+            SummaryStyle::Abstract => {
+                "Write the summary as a single abstract-style paragraph: context and purpose, \
+main content or approach, key findings, and conclusions. No bullet points or extra headings."
+            }
+        }
+    }
+}
+
+/// Build the summarization system prompt for the requested length and style
+pub fn build_summarization_system_prompt(length: SummaryLength, style: SummaryStyle) -> String {
+    format!(
+        "{}\n\nLENGTH:\n{}\n\nOUTPUT FORMAT:\n{}\n\nStart your summary with a level-2 heading titled Summary.",
+        SUMMARIZATION_AGENT_SYSTEM_PROMPT,
+        length.instructions(),
+        style.instructions()
+    )
+}
 
 /// Result of running the summarization agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -135,6 +224,8 @@ pub fn get_summarization_tools() -> Vec<ToolDefinition> {
 /// * `content` - The content to summarize
 /// * `content_type` - Type of content ("selection" or "attachment")
 /// * `vault_path` - Path to the vault for resolving attachments
+/// * `length` - Target summary length
+/// * `style` - Summary format (bullets, paragraphs or an abstract)
 /// * `cancellation_token` - Optional cancellation token
 #[allow(clippy::too_many_arguments)]
 pub async fn run_summarization_agent(
@@ -146,6 +237,8 @@ pub async fn run_summarization_agent(
     content: &str,
     content_type: &str,
     _vault_path: &str,
+    length: SummaryLength,
+    style: SummaryStyle,
     cancellation_token: Option<&CancellationToken>,
 ) -> Result<SummarizationResult, AgentError> {
     log::info!(
@@ -176,13 +269,21 @@ pub async fn run_summarization_agent(
         ),
     };
 
-    let result = run_agent_with_events(
+    let initial_message = format!(
+        "{}\n\n---\n\nWrite a {} summary in {} style.",
+        initial_message,
+        length.as_str(),
+        style.as_str()
+    );
+    let system_prompt = build_summarization_system_prompt(length, style);
+
+    let result = run_agent_with_streaming_events(
         app_handle,
         execution_id,
         "Summarization",
         provider,
         model,
-        SUMMARIZATION_AGENT_SYSTEM_PROMPT,
+        &system_prompt,
         &initial_message,
         tools,
         &agent,
@@ -224,8 +325,44 @@ mod tests {
 
     #[test]
     fn test_system_prompt_contains_key_elements() {
-        assert!(SUMMARIZATION_AGENT_SYSTEM_PROMPT.contains("summarization"));
-        assert!(SUMMARIZATION_AGENT_SYSTEM_PROMPT.contains("append_to_note"));
-        assert!(SUMMARIZATION_AGENT_SYSTEM_PROMPT.contains("Summary"));
+        let prompt = build_summarization_system_prompt(SummaryLength::default(), SummaryStyle::default());
+        assert!(prompt.contains("summarization"));
+        assert!(prompt.contains("append_to_note"));
+        assert!(prompt.contains("Summary"));
+    }
+
+    #[test]
+    fn test_prompt_varies_with_length_and_style() {
+        let lengths = [SummaryLength::Short, SummaryLength::Medium, SummaryLength::Long];
+        let styles = [SummaryStyle::Bullets, SummaryStyle::Paragraph, SummaryStyle::Abstract];
+
+        let mut prompts = std::collections::HashSet::new();
+        for length in lengths {
+            for style in styles {
+                let prompt = build_summarization_system_prompt(length, style);
+                assert!(prompt.starts_with(SUMMARIZATION_AGENT_SYSTEM_PROMPT));
+                assert!(prompt.contains(length.instructions()));
+                assert!(prompt.contains(style.instructions()));
+                prompts.insert(prompt);
+            }
+        }
+        // Every combination produces a distinct prompt
+        assert_eq!(prompts.len(), 9);
+
+        let short = build_summarization_system_prompt(SummaryLength::Short, SummaryStyle::Bullets);
+        assert!(short.contains("50-100 words"));
+        assert!(short.contains("bullet points"));
+        let abstract_prompt = build_summarization_system_prompt(SummaryLength::Long, SummaryStyle::Abstract);
+        assert!(abstract_prompt.contains("400-700 words"));
+        assert!(abstract_prompt.contains("abstract-style paragraph"));
+    }
+
+    #[test]
+    fn test_parse_length_and_style() {
+        assert_eq!(SummaryLength::from_str("Short"), Some(SummaryLength::Short));
+        assert_eq!(SummaryLength::from_str(" long "), Some(SummaryLength::Long));
+        assert_eq!(SummaryLength::from_str("huge"), None);
+        assert_eq!(SummaryStyle::from_str("ABSTRACT"), Some(SummaryStyle::Abstract));
+        assert_eq!(SummaryStyle::from_str("haiku"), None);
     }
 }
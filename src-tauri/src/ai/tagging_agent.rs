@@ -4,15 +4,20 @@
 //! Uses the agent infrastructure for multi-turn tool calling.
 
 use async_trait::async_trait;
+use futures_util::stream::{self, StreamExt};
 use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use crate::db::{self, DbPool};
-use crate::models::Tag;
+use crate::models::{Note, Tag};
 
-use super::agent::{run_agent, AgentError, ToolDefinition, ToolExecutor};
+use super::agent::{run_agent_with_client, AgentError, CancellationToken, ToolDefinition, ToolExecutor};
 use super::config::AIProvider;
+use super::llm::{create_client, LlmClient};
+
+/// Number of notes tagged concurrently in batch mode
+pub const TAGGING_BATCH_CONCURRENCY: usize = 3;
 
 /// System prompt for the tagging agent
 pub const TAGGING_AGENT_SYSTEM_PROMPT: &str = r#"You are a tagging agent for a note-taking app. Your job is to analyze the given note and assign appropriate tags.
@@ -122,14 +127,21 @@ impl TaggingAgent {
             .and_then(|v| v.as_str())
             .ok_or("Missing 'name' argument")?;
 
-        let color = args.get("color").and_then(|v| v.as_str());
+        let color = args.get("color").and_then(|v| v.as_str()).filter(|c| {
+            let valid = db::tags::validate_tag_color(c).is_ok();
+            if !valid {
+                warn!("[TaggingAgent] Ignoring invalid color '{}' for tag '{}'", c, name);
+            }
+            valid
+        });
+        let description = args.get("description").and_then(|v| v.as_str());
         
         info!("[TaggingAgent] create_tag called: name='{}', color={:?}", name, color);
         let conn = self.pool.get().map_err(|e| {
             warn!("[TaggingAgent] Failed to get DB connection: {}", e);
             e.to_string()
         })?;
-        let tag = db::create_tag(&conn, name, color).map_err(|e| {
+        let tag = db::create_tag(&conn, name, color, description).map_err(|e| {
             warn!("[TaggingAgent] Failed to create tag '{}': {}", name, e);
             e.to_string()
         })?;
@@ -250,8 +262,12 @@ pub fn get_tagging_tools() -> Vec<ToolDefinition> {
                     },
                     "color": {
                         "type": "string",
-                        "enum": ["red", "orange", "yellow", "green", "blue", "purple", "pink", "gray"],
+                        "enum": db::tags::TAG_PALETTE_COLORS,
                         "description": "Tag color - choose based on semantic meaning"
+                    },
+                    "description": {
+                        "type": "string",
+                        "description": "Optional short description of what the tag covers"
                     }
                 },
                 "required": ["name", "color"]
@@ -301,6 +317,19 @@ pub async fn run_tagging_agent(
     note_id: &str,
     note_title: &str,
     note_content: &str,
+) -> Result<TaggingResult, AgentError> {
+    let client = create_client(provider)?;
+    run_tagging_agent_with_client(pool, client.as_ref(), model, note_id, note_title, note_content).await
+}
+
+/// Run the tagging agent on a note using an existing LLM client
+pub async fn run_tagging_agent_with_client(
+    pool: &DbPool,
+    client: &dyn LlmClient,
+    model: &str,
+    note_id: &str,
+    note_title: &str,
+    note_content: &str,
 ) -> Result<TaggingResult, AgentError> {
     info!("[TaggingAgent] ========== Starting tagging agent ==========");
     info!("[TaggingAgent] Note ID: {}", note_id);
@@ -329,8 +358,8 @@ pub async fn run_tagging_agent(
 
     // Run the agent
     info!("[TaggingAgent] Starting agent loop...");
-    let result = run_agent(
-        provider,
+    let result = run_agent_with_client(
+        client,
         model,
         TAGGING_AGENT_SYSTEM_PROMPT,
         &initial_message,
@@ -381,6 +410,156 @@ pub async fn run_tagging_agent(
     })
 }
 
+/// Whether a note has enough text for the tagging agent to work with
+pub fn has_taggable_content(title: &str, content: &str) -> bool {
+    content.len() >= 10 || title.len() >= 5
+}
+
+/// Collect the notes to tag in a folder, optionally including all subfolders
+pub fn get_notes_for_tagging(
+    conn: &rusqlite::Connection,
+    folder_id: &str,
+    recursive: bool,
+) -> Result<Vec<Note>, String> {
+    let mut folder_ids: Vec<String> = if recursive {
+        db::folders::get_folder_subtree_ids(conn, folder_id)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .collect()
+    } else {
+        vec![folder_id.to_string()]
+    };
+    folder_ids.sort();
+
+    let mut notes = Vec::new();
+    for id in &folder_ids {
+        notes.extend(db::notes::get_notes_in_folder(conn, Some(id)).map_err(|e| e.to_string())?);
+    }
+    Ok(notes)
+}
+
+/// Progress update emitted after each note in a batch run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaggingBatchProgress {
+    /// Notes processed so far (including skipped and failed ones)
+    pub completed: usize,
+    /// Total number of notes in the batch
+    pub total: usize,
+    pub note_id: String,
+    pub note_title: String,
+    pub success: bool,
+    /// Error message if tagging this note failed
+    pub error: Option<String>,
+}
+
+/// A note the tagging agent failed on during a batch run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaggingBatchFailure {
+    pub note_id: String,
+    pub note_title: String,
+    pub error: String,
+}
+
+/// Result of running the tagging agent over a set of notes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaggingBatchResult {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Notes skipped because their content is too short to analyze
+    pub skipped: usize,
+    /// Whether the batch was cancelled before every note was processed
+    pub cancelled: bool,
+    pub failures: Vec<TaggingBatchFailure>,
+}
+
+/// Run the tagging agent over many notes
+///
+/// At most `concurrency` notes are tagged at once. Notes that haven't started
+/// when the cancellation token fires are left untouched, and `on_progress` is
+/// called once for every note that was processed.
+pub async fn run_tagging_agent_batch_with_client<F>(
+    pool: &DbPool,
+    client: &dyn LlmClient,
+    model: &str,
+    notes: Vec<Note>,
+    concurrency: usize,
+    cancellation_token: &CancellationToken,
+    mut on_progress: F,
+) -> TaggingBatchResult
+where
+    F: FnMut(&TaggingBatchProgress),
+{
+    let mut result = TaggingBatchResult {
+        total: notes.len(),
+        ..Default::default()
+    };
+    info!("[TaggingAgent] Starting batch over {} notes", result.total);
+
+    let mut runs = stream::iter(notes)
+        .map(|note| async move {
+            if cancellation_token.is_cancelled() {
+                return (note, None);
+            }
+            let content = note.content.clone().unwrap_or_default();
+            if !has_taggable_content(&note.title, &content) {
+                return (note, Some(None));
+            }
+            let outcome =
+                run_tagging_agent_with_client(pool, client, model, &note.id, &note.title, &content).await;
+            (note, Some(Some(outcome)))
+        })
+        .buffer_unordered(concurrency.max(1));
+
+    let mut completed = 0;
+    while let Some((note, outcome)) = runs.next().await {
+        let error = match outcome {
+            // Cancelled before this note was started
+            None => {
+                result.cancelled = true;
+                continue;
+            }
+            Some(None) => {
+                result.skipped += 1;
+                None
+            }
+            Some(Some(Ok(_))) => {
+                result.succeeded += 1;
+                None
+            }
+            Some(Some(Err(e))) => {
+                warn!("[TaggingAgent] Batch: failed to tag note {}: {}", note.id, e);
+                result.failed += 1;
+                result.failures.push(TaggingBatchFailure {
+                    note_id: note.id.clone(),
+                    note_title: note.title.clone(),
+                    error: e.to_string(),
+                });
+                Some(e.to_string())
+            }
+        };
+
+        completed += 1;
+        on_progress(&TaggingBatchProgress {
+            completed,
+            total: result.total,
+            note_id: note.id,
+            note_title: note.title,
+            success: error.is_none(),
+            error,
+        });
+    }
+
+    info!(
+        "[TaggingAgent] Batch complete: {} succeeded, {} failed, {} skipped, cancelled={}",
+        result.succeeded, result.failed, result.skipped, result.cancelled
+    );
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -398,4 +577,157 @@ mod tests {
         assert!(tool_names.contains(&"assign_tag"));
         assert!(tool_names.contains(&"remove_tag"));
     }
+
+    use crate::ai::llm::{ChatRequest, ChatResponse, FunctionCall, LlmError, MessageRole, StreamEvent, ToolCall};
+    use crate::db::connection::init_test_pool;
+    use crate::db::folders::create_folder;
+    use crate::db::notes::create_note;
+    use crate::models::{CreateFolderInput, CreateNoteInput};
+    use tokio::sync::mpsc;
+
+    /// Assigns a fixed tag on the first turn and finishes after seeing the tool result.
+    /// Notes whose content contains "FAIL" produce an API error.
+    struct MockTaggingClient {
+        tag_id: String,
+    }
+
+    #[async_trait]
+    impl LlmClient for MockTaggingClient {
+        async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LlmError> {
+            let last = request.messages.last().unwrap();
+            if matches!(last.role, MessageRole::Tool) {
+                return Ok(ChatResponse {
+                    content: "Tagged the note".to_string(),
+                    thinking: None,
+                    thinking_blocks: Vec::new(),
+                    tool_calls: None,
+                    finish_reason: "stop".to_string(),
+                    usage: None,
+                });
+            }
+            if last.content.as_deref().unwrap_or_default().contains("FAIL") {
+                return Err(LlmError::ApiError { status: 500, message: "boom".to_string() });
+            }
+            Ok(ChatResponse {
+                content: String::new(),
+                thinking: None,
+                thinking_blocks: Vec::new(),
+                tool_calls: Some(vec![ToolCall {
+                    id: "call_1".to_string(),
+                    call_type: "function".to_string(),
+                    function: FunctionCall {
+                        name: "assign_tag".to_string(),
+                        arguments: json!({ "tag_id": self.tag_id }).to_string(),
+                    },
+                    thought_signature: None,
+                }]),
+                finish_reason: "tool_calls".to_string(),
+                usage: None,
+            })
+        }
+
+        async fn chat_stream(&self, _request: ChatRequest) -> Result<mpsc::Receiver<StreamEvent>, LlmError> {
+            Err(LlmError::NotConfigured("streaming not supported by mock".to_string()))
+        }
+    }
+
+    fn note_in(conn: &rusqlite::Connection, folder_id: &str, title: &str, content: &str) -> Note {
+        create_note(
+            conn,
+            CreateNoteInput {
+                title: title.to_string(),
+                content: Some(content.to_string()),
+                content_html: None,
+                folder_id: Some(folder_id.to_string()),
+            },
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_batch_tags_every_note_in_subtree() {
+        let pool = init_test_pool().unwrap();
+        let (tag, root, notes) = {
+            let conn = pool.get().unwrap();
+            let tag = db::create_tag(&conn, "imported", Some("blue"), None).unwrap();
+            let root = create_folder(&conn, CreateFolderInput { name: "Vault".to_string(), parent_id: None }).unwrap();
+            let child = create_folder(
+                &conn,
+                CreateFolderInput { name: "Sub".to_string(), parent_id: Some(root.id.clone()) },
+            )
+            .unwrap();
+            let notes = vec![
+                note_in(&conn, &root.id, "First note", "Some content about rust"),
+                note_in(&conn, &root.id, "Second note", "More content about sqlite"),
+                note_in(&conn, &child.id, "Nested note", "Content in a subfolder"),
+            ];
+            (tag, root, notes)
+        };
+
+        let to_tag = {
+            let conn = pool.get().unwrap();
+            assert_eq!(get_notes_for_tagging(&conn, &root.id, false).unwrap().len(), 2);
+            get_notes_for_tagging(&conn, &root.id, true).unwrap()
+        };
+        assert_eq!(to_tag.len(), 3);
+
+        let client = MockTaggingClient { tag_id: tag.id.clone() };
+        let mut progress = Vec::new();
+        let result = run_tagging_agent_batch_with_client(
+            &pool,
+            &client,
+            "mock-model",
+            to_tag,
+            2,
+            &CancellationToken::new(),
+            |p| progress.push(p.clone()),
+        )
+        .await;
+
+        assert_eq!(result.total, 3);
+        assert_eq!(result.succeeded, 3);
+        assert_eq!(result.failed, 0);
+        assert!(!result.cancelled);
+        assert_eq!(progress.len(), 3);
+        assert_eq!(progress.last().unwrap().completed, 3);
+
+        let conn = pool.get().unwrap();
+        for note in &notes {
+            let tags = db::get_note_tags(&conn, &note.id).unwrap();
+            assert_eq!(tags.len(), 1, "note '{}' should be tagged", note.title);
+            assert_eq!(tags[0].id, tag.id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_counts_failures_skips_and_cancellation() {
+        let pool = init_test_pool().unwrap();
+        let (tag, notes) = {
+            let conn = pool.get().unwrap();
+            let tag = db::create_tag(&conn, "imported", None, None).unwrap();
+            let folder = create_folder(&conn, CreateFolderInput { name: "Vault".to_string(), parent_id: None }).unwrap();
+            let notes = vec![
+                note_in(&conn, &folder.id, "Good note", "Plenty of content here"),
+                note_in(&conn, &folder.id, "Bad note", "This one should FAIL"),
+                note_in(&conn, &folder.id, "", "tiny"),
+            ];
+            (tag, notes)
+        };
+        let client = MockTaggingClient { tag_id: tag.id.clone() };
+
+        let result = run_tagging_agent_batch_with_client(
+            &pool, &client, "mock-model", notes.clone(), 1, &CancellationToken::new(), |_| {},
+        )
+        .await;
+        assert_eq!((result.succeeded, result.failed, result.skipped), (1, 1, 1));
+        assert_eq!(result.failures.len(), 1);
+        assert_eq!(result.failures[0].note_id, notes[1].id);
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let result =
+            run_tagging_agent_batch_with_client(&pool, &client, "mock-model", notes, 1, &token, |_| {}).await;
+        assert!(result.cancelled);
+        assert_eq!((result.succeeded, result.failed, result.skipped), (0, 0, 0));
+    }
 }
@@ -251,12 +251,17 @@ pub fn execute_append_content_to_note(
         None
     };
 
+    let (tag_names, aliases) = vault_sync::note_metadata(&conn, &note.id)
+        .map_err(|e| e.to_string())?;
+
     // Write directly to the file (this is the source of truth)
     markdown::write_note_file(
         &file_path,
         &note.id,
         &note.title,
         Some(&combined_content),
+        &tag_names,
+        &aliases,
         note.folder_id.as_deref(),
         folder_name.as_deref(),
         note.created_at,
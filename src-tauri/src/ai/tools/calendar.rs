@@ -2,12 +2,14 @@
 //!
 //! Tools for accessing calendar events and creating new events.
 
-use chrono::{Duration, Local, Utc};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use crate::ai::agent::ToolDefinition;
 use crate::db::{self, connection::DbPool};
+use crate::models::{CalendarEvent, CalendarEventWithNote, EventResponseStatus};
 
 /// A calendar event result for the agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +24,7 @@ pub struct CalendarEventResult {
     pub linked_note_id: Option<String>,
     pub linked_note_title: Option<String>,
     pub meeting_link: Option<String>,
+    pub location: Option<String>,
     /// Your RSVP status: "accepted", "declined", "tentative", or "needsAction"
     pub response_status: Option<String>,
 }
@@ -118,7 +121,7 @@ pub fn execute_get_calendar_events(
     let query_end = end + Duration::days(1);
 
     // Get events in range (with padded boundaries)
-    let events = db::calendar_events::get_events_in_range(&conn, query_start, query_end)
+    let events = db::calendar_events::get_events_in_range(&conn, query_start, query_end, None)
         .map_err(|e| format!("Failed to get events: {}", e))?;
 
     // For debugging: get total count of all events in the database
@@ -172,6 +175,7 @@ pub fn execute_get_calendar_events(
                 linked_note_id: e.event.linked_note_id,
                 linked_note_title: e.linked_note_title,
                 meeting_link: e.event.meeting_link,
+                location: e.event.location,
                 response_status,
             }
         })
@@ -220,6 +224,163 @@ pub fn execute_get_calendar_events(
     }
 }
 
+// ============================================================================
+// get_upcoming_events Tool
+// ============================================================================
+
+/// Most days `get_upcoming_events` will look ahead
+const MAX_DAYS_AHEAD: u64 = 31;
+
+/// Get the tool definition for get_upcoming_events
+pub fn get_upcoming_events_tool() -> ToolDefinition {
+    ToolDefinition::function(
+        "get_upcoming_events",
+        "Get the user's upcoming calendar events as a readable schedule, grouped by day. Use this to answer questions like \"what's on my schedule today\" or \"what do I have this week\".",
+        json!({
+            "type": "object",
+            "properties": {
+                "days_ahead": {
+                    "type": "integer",
+                    "description": "Number of days to include, starting with today: 1 for today only, 7 for the coming week (default: 1, max: 31)",
+                    "default": 1,
+                    "minimum": 1,
+                    "maximum": 31
+                }
+            },
+            "required": []
+        }),
+    )
+}
+
+/// Execute the get_upcoming_events tool
+pub fn execute_get_upcoming_events(
+    pool: &DbPool,
+    args: Value,
+) -> Result<String, String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+
+    let days_ahead = args
+        .get("days_ahead")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1)
+        .clamp(1, MAX_DAYS_AHEAD) as u32;
+    let tz = db::calendar_events::parse_timezone(&db::calendar_events::local_timezone_name())
+        .unwrap_or(Tz::UTC);
+
+    let events = upcoming_events(&conn, Utc::now(), days_ahead, &tz)?;
+    let schedule = if events.is_empty() {
+        match days_ahead {
+            1 => "No more events today.".to_string(),
+            n => format!("No upcoming events in the next {} days.", n),
+        }
+    } else {
+        format_schedule(&events, &tz)
+    };
+
+    Ok(json!({
+        "success": true,
+        "count": events.len(),
+        "days_ahead": days_ahead,
+        "timezone": tz.name(),
+        "schedule": schedule
+    }).to_string())
+}
+
+/// Events from `now` to the end of the `days_ahead`th day in `tz`, including
+/// ones already in progress and leaving out ones the user declined
+fn upcoming_events(
+    conn: &rusqlite::Connection,
+    now: DateTime<Utc>,
+    days_ahead: u32,
+    tz: &Tz,
+) -> Result<Vec<CalendarEventWithNote>, String> {
+    let today = now.with_timezone(tz).date_naive();
+    let start = local_midnight(tz, today);
+    let end = local_midnight(tz, today + Duration::days(days_ahead as i64));
+
+    let events = db::calendar_events::get_events_in_range(conn, start, end, Some(tz))
+        .map_err(|e| format!("Failed to get events: {}", e))?;
+
+    Ok(events
+        .into_iter()
+        .filter(|e| e.event.response_status != Some(EventResponseStatus::Declined))
+        .filter(|e| e.event.all_day || e.event.end_time.unwrap_or(e.event.start_time) > now)
+        .collect())
+}
+
+/// Start of `date` in `tz`, as UTC
+fn local_midnight(tz: &Tz, date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_time(NaiveTime::MIN);
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        // Midnight skipped by a DST change: treat the date as UTC
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+/// Local calendar date of an event in `tz`
+///
+/// All-day events are stored at midnight UTC on their calendar date, so their
+/// date is read in UTC rather than converted.
+fn event_day(event: &CalendarEvent, tz: &Tz) -> NaiveDate {
+    if event.all_day {
+        event.start_time.date_naive()
+    } else {
+        event.start_time.with_timezone(tz).date_naive()
+    }
+}
+
+/// Render events as a schedule grouped under a heading per day
+///
+/// Events are ordered by local date, then all-day events first, then start
+/// time: stored order can put an all-day event (at UTC midnight) among the
+/// previous evening's events west of UTC.
+fn format_schedule(events: &[CalendarEventWithNote], tz: &Tz) -> String {
+    let mut lines = Vec::new();
+    let mut current_day = None;
+
+    let mut events: Vec<&CalendarEventWithNote> = events.iter().collect();
+    events.sort_by_key(|e| (event_day(&e.event, tz), !e.event.all_day, e.event.start_time));
+
+    for e in events {
+        let event = &e.event;
+        let day = event_day(event, tz);
+        let time = if event.all_day {
+            "All day".to_string()
+        } else {
+            let start = event.start_time.with_timezone(tz);
+            match event.end_time {
+                Some(end) => format!("{} - {}", start.format("%-I:%M %p"), end.with_timezone(tz).format("%-I:%M %p")),
+                None => start.format("%-I:%M %p").to_string(),
+            }
+        };
+
+        if current_day != Some(day) {
+            if current_day.is_some() {
+                lines.push(String::new());
+            }
+            lines.push(day.format("%A, %B %-d, %Y").to_string());
+            current_day = Some(day);
+        }
+
+        let mut line = format!("- {}: {}", time, event.title);
+        match event.response_status {
+            Some(EventResponseStatus::Tentative) => line.push_str(" (tentative)"),
+            Some(EventResponseStatus::NeedsAction) => line.push_str(" (not yet responded)"),
+            _ => {}
+        }
+        if let Some(ref link) = event.meeting_link {
+            line.push_str(&format!(" [meeting link: {}]", link));
+        }
+        if let Some(ref title) = e.linked_note_title {
+            line.push_str(&format!(" [linked note: \"{}\"]", title));
+        }
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
 // ============================================================================
 // create_calendar_event Tool (Inline Assistant Only)
 // ============================================================================
@@ -240,6 +401,10 @@ pub fn get_create_calendar_event_tool() -> ToolDefinition {
                     "type": "string",
                     "description": "Optional description or notes for the event"
                 },
+                "location": {
+                    "type": "string",
+                    "description": "Optional location: a room, an address, or a video call link"
+                },
                 "start_date": {
                     "type": "string",
                     "description": "Start date in YYYY-MM-DD format"
@@ -287,6 +452,7 @@ pub fn execute_create_calendar_event(
         .to_string();
 
     let description = args.get("description").and_then(|v| v.as_str()).map(|s| s.to_string());
+    let location = args.get("location").and_then(|v| v.as_str()).map(|s| s.to_string());
 
     let start_date = args
         .get("start_date")
@@ -342,6 +508,8 @@ pub fn execute_create_calendar_event(
         response_status: None,
         attendees: None,
         meeting_link: None,
+        location,
+        timezone: None,
     };
 
     let event = db::calendar_events::create_event(&conn, input)
@@ -360,6 +528,51 @@ pub fn execute_create_calendar_event(
     }).to_string())
 }
 
+// ============================================================================
+// create_meeting_note Tool
+// ============================================================================
+
+/// Get the tool definition for create_meeting_note
+pub fn get_create_meeting_note_tool() -> ToolDefinition {
+    ToolDefinition::function(
+        "create_meeting_note",
+        "Create a meeting notes note for a calendar event, pre-filled with the event's time, attendees, meeting link and agenda, and link it to the event. If the event already has a linked note, returns that note instead. Get event IDs from get_calendar_events.",
+        json!({
+            "type": "object",
+            "properties": {
+                "event_id": {
+                    "type": "string",
+                    "description": "ID of the calendar event"
+                }
+            },
+            "required": ["event_id"]
+        }),
+    )
+}
+
+/// Execute the create_meeting_note tool
+pub fn execute_create_meeting_note(
+    pool: &DbPool,
+    args: Value,
+) -> Result<String, String> {
+    let event_id = args
+        .get("event_id")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'event_id' argument")?;
+    let tz = db::calendar_events::parse_timezone(&db::calendar_events::local_timezone_name())
+        .unwrap_or(Tz::UTC);
+
+    let note = crate::commands::calendar::create_meeting_note_for_event(pool, event_id, &tz)?;
+
+    Ok(json!({
+        "success": true,
+        "message": format!("Meeting note ready: {}", note.title),
+        "note_id": note.id,
+        "title": note.title,
+        "event_id": event_id
+    }).to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -378,6 +591,132 @@ mod tests {
         assert!(tool.function.description.contains("event"));
     }
 
+    #[test]
+    fn test_get_upcoming_events_tool() {
+        let tool = get_upcoming_events_tool();
+        assert_eq!(tool.function.name, "get_upcoming_events");
+        assert!(tool.function.description.contains("schedule"));
+        assert_eq!(tool.function.parameters["properties"]["days_ahead"]["type"], "integer");
+    }
+
+    #[test]
+    fn test_get_create_meeting_note_tool() {
+        let tool = get_create_meeting_note_tool();
+        assert_eq!(tool.function.name, "create_meeting_note");
+        assert_eq!(tool.function.parameters["required"], json!(["event_id"]));
+    }
+
+    #[test]
+    fn test_upcoming_events_window() {
+        use crate::db::calendar_events::create_event;
+        use crate::models::CreateCalendarEventInput;
+
+        let pool = crate::db::connection::init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let event = |title: &str, start: &str, end: Option<&str>, all_day: bool, status: Option<EventResponseStatus>| {
+            create_event(
+                &conn,
+                CreateCalendarEventInput {
+                    title: title.to_string(),
+                    description: None,
+                    start_time: at(start),
+                    end_time: end.map(at),
+                    all_day,
+                    recurrence_rule: None,
+                    linked_note_id: None,
+                    event_type: None,
+                    response_status: status,
+                    attendees: None,
+                    meeting_link: None,
+                    location: None,
+                    timezone: Some("UTC".to_string()),
+                },
+            )
+            .unwrap();
+        };
+
+        event("Yesterday", "2030-03-03T10:00:00Z", Some("2030-03-03T11:00:00Z"), false, None);
+        event("Finished", "2030-03-04T07:00:00Z", Some("2030-03-04T08:00:00Z"), false, None);
+        event("In progress", "2030-03-04T08:30:00Z", Some("2030-03-04T09:30:00Z"), false, None);
+        event("Standup", "2030-03-04T10:00:00Z", Some("2030-03-04T10:15:00Z"), false, Some(EventResponseStatus::Tentative));
+        event("Holiday", "2030-03-04T00:00:00Z", None, true, None);
+        event("Declined", "2030-03-04T13:00:00Z", Some("2030-03-04T14:00:00Z"), false, Some(EventResponseStatus::Declined));
+        event("Tomorrow", "2030-03-05T09:00:00Z", Some("2030-03-05T10:00:00Z"), false, None);
+        event("Next week", "2030-03-12T09:00:00Z", Some("2030-03-12T10:00:00Z"), false, None);
+
+        let tz = Tz::UTC;
+        let now = at("2030-03-04T09:00:00Z");
+        let titles = |days: u32| -> Vec<String> {
+            upcoming_events(&conn, now, days, &tz).unwrap().into_iter().map(|e| e.event.title).collect()
+        };
+
+        assert_eq!(titles(1), vec!["Holiday", "In progress", "Standup"]);
+        assert_eq!(titles(7), vec!["Holiday", "In progress", "Standup", "Tomorrow"]);
+
+        let schedule = format_schedule(&upcoming_events(&conn, now, 2, &tz).unwrap(), &tz);
+        assert_eq!(
+            schedule,
+            "Monday, March 4, 2030\n\
+             - All day: Holiday\n\
+             - 8:30 AM - 9:30 AM: In progress\n\
+             - 10:00 AM - 10:15 AM: Standup (tentative)\n\
+             \n\
+             Tuesday, March 5, 2030\n\
+             - 9:00 AM - 10:00 AM: Tomorrow"
+        );
+    }
+
+    #[test]
+    fn test_schedule_groups_by_local_day_west_of_utc() {
+        use crate::db::calendar_events::create_event;
+        use crate::models::CreateCalendarEventInput;
+
+        let pool = crate::db::connection::init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let event = |title: &str, start: &str, end: Option<&str>, all_day: bool| {
+            create_event(
+                &conn,
+                CreateCalendarEventInput {
+                    title: title.to_string(),
+                    description: None,
+                    start_time: at(start),
+                    end_time: end.map(at),
+                    all_day,
+                    recurrence_rule: None,
+                    linked_note_id: None,
+                    event_type: None,
+                    response_status: None,
+                    attendees: None,
+                    meeting_link: None,
+                    location: None,
+                    timezone: Some("America/Los_Angeles".to_string()),
+                },
+            )
+            .unwrap();
+        };
+
+        // 6 PM on March 4 in Los Angeles is already March 5 in UTC, after the
+        // March 5 holiday's UTC midnight
+        event("Dinner", "2030-03-05T02:00:00Z", Some("2030-03-05T03:00:00Z"), false);
+        event("Holiday", "2030-03-05T00:00:00Z", None, true);
+        event("Breakfast", "2030-03-05T16:00:00Z", Some("2030-03-05T17:00:00Z"), false);
+
+        let tz: Tz = "America/Los_Angeles".parse().unwrap();
+        let now = at("2030-03-04T17:00:00Z");
+        let schedule = format_schedule(&upcoming_events(&conn, now, 2, &tz).unwrap(), &tz);
+        assert_eq!(
+            schedule,
+            "Monday, March 4, 2030\n\
+             - 6:00 PM - 7:00 PM: Dinner\n\
+             \n\
+             Tuesday, March 5, 2030\n\
+             - All day: Holiday\n\
+             - 8:00 AM - 9:00 AM: Breakfast"
+        );
+    }
+
     #[test]
     fn test_parse_date() {
         let date = parse_date("2025-12-28");
@@ -8,9 +8,10 @@ use serde_json::{json, Value};
 use crate::ai::tools::ToolFunction;
 use crate::db::exports::ExportFormat;
 use crate::exports::{
-    create_document, add_document_section, add_document_table, 
-    save_document, delete_document, SectionType
+    create_document, add_document_section, add_document_table, add_document_image,
+    save_document_with_provenance, delete_document, DocumentProvenance, SectionType
 };
+use crate::exports::images::{download_image, is_remote_image};
 
 /// Get the create_document tool definition
 pub fn get_create_document_tool() -> Value {
@@ -108,6 +109,35 @@ pub fn get_add_table_tool() -> Value {
     })
 }
 
+/// Get the add_image tool definition
+pub fn get_add_image_tool() -> Value {
+    json!({
+        "type": "function",
+        "function": {
+            "name": "add_image",
+            "description": "Add an image to a document draft. Accepts an attachment path from a note (e.g. '../attachments/diagram.png'), an attachment filename, an absolute file path, or an http(s) URL. The image is embedded in the generated PDF, DOCX, or PPTX.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "document_id": {
+                        "type": "string",
+                        "description": "The document ID returned from create_document"
+                    },
+                    "source": {
+                        "type": "string",
+                        "description": "Attachment path, filename, absolute path, or URL of the image"
+                    },
+                    "caption": {
+                        "type": "string",
+                        "description": "Optional caption shown below the image"
+                    }
+                },
+                "required": ["document_id", "source"]
+            }
+        }
+    })
+}
+
 /// Get the save_document tool definition
 pub fn get_save_document_tool() -> Value {
     json!({
@@ -248,13 +278,52 @@ pub fn execute_add_table(args: &Value) -> Result<String, String> {
     }).to_string())
 }
 
+/// Execute the add_image tool
+///
+/// Async because URL sources are downloaded before being added to the draft.
+pub async fn execute_add_image(args: &Value) -> Result<String, String> {
+    let document_id = args.get("document_id")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing document_id argument")?;
+
+    let source = args.get("source")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing source argument")?;
+
+    let caption = args.get("caption")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+
+    let local_source = if is_remote_image(source) {
+        download_image(source).await?.to_string_lossy().to_string()
+    } else {
+        source.to_string()
+    };
+
+    add_document_image(document_id, &local_source)?;
+    if let Some(caption) = caption {
+        add_document_section(document_id, SectionType::Paragraph, caption.to_string(), None)?;
+    }
+
+    Ok(json!({
+        "success": true,
+        "message": format!("Added image from {}", source)
+    }).to_string())
+}
+
 /// Execute the save_document tool
 pub fn execute_save_document(args: &Value) -> Result<String, String> {
+    execute_save_document_with_provenance(args, &DocumentProvenance::default())
+}
+
+/// Execute the save_document tool, recording who produced the document on its export
+pub fn execute_save_document_with_provenance(args: &Value, provenance: &DocumentProvenance) -> Result<String, String> {
     let document_id = args.get("document_id")
         .and_then(|v| v.as_str())
         .ok_or("Missing document_id argument")?;
 
-    let export = save_document(document_id)?;
+    let export = save_document_with_provenance(document_id, provenance)?;
 
     Ok(json!({
         "success": true,
@@ -285,6 +354,8 @@ pub fn execute_cancel_document(args: &Value) -> Result<String, String> {
 }
 
 /// Get the tool function for document builder tools
+///
+/// `add_image` isn't included since it's async; call `execute_add_image` directly.
 pub fn get_document_builder_tool_function(name: &str) -> Option<ToolFunction> {
     match name {
         "create_document" => Some(Box::new(|args| execute_create_document(&args))),
@@ -302,8 +373,58 @@ pub fn get_all_document_builder_tools() -> Vec<Value> {
         get_create_document_tool(),
         get_add_section_tool(),
         get_add_table_tool(),
+        get_add_image_tool(),
         get_save_document_tool(),
         get_cancel_document_tool(),
     ]
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_image_tool_definition() {
+        let tool = get_add_image_tool();
+        let function = &tool["function"];
+        assert_eq!(function["name"], "add_image");
+
+        let required: Vec<&str> = function["parameters"]["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect();
+        assert_eq!(required, vec!["document_id", "source"]);
+        assert!(function["parameters"]["properties"]["caption"].is_object());
+
+        assert!(get_all_document_builder_tools()
+            .iter()
+            .any(|t| t["function"]["name"] == "add_image"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_add_image_adds_section() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let image_path = crate::exports::images::tests::write_test_png(dir.path());
+        let document_id = create_document("Images".to_string(), ExportFormat::Pdf).unwrap();
+
+        let result = execute_add_image(&json!({
+            "document_id": document_id,
+            "source": image_path.to_string_lossy(),
+            "caption": "A red box"
+        }))
+        .await
+        .unwrap();
+        assert!(result.contains("\"success\":true"));
+
+        let missing = execute_add_image(&json!({
+            "document_id": document_id,
+            "source": "/no/such/image.png"
+        }))
+        .await;
+        assert!(missing.is_err());
+
+        delete_document(&document_id).unwrap();
+    }
+}
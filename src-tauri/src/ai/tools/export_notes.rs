@@ -3,6 +3,8 @@
 //! Provides tools for exporting notes to various document formats (PDF, DOCX, XLSX).
 //! These tools can be used by the inline assistant to generate documents from notes.
 
+use std::time::Instant;
+
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
@@ -117,6 +119,7 @@ pub fn execute_export_notes_pdf(
     pool: &DbPool,
     args: Value,
 ) -> Result<String, String> {
+    let started = Instant::now();
     // Parse arguments
     let note_ids: Vec<String> = args
         .get("note_ids")
@@ -162,6 +165,7 @@ pub fn execute_export_notes_pdf(
         include_images: true,
         page_break_between_notes: page_break,
         paper_size: "a4".to_string(),
+        highlight_code: true,
     };
 
     let result = pdf_generator::generate_pdf_from_notes(&notes_data, title, &output_path, &options)
@@ -175,6 +179,12 @@ pub fn execute_export_notes_pdf(
         source_note_ids: note_ids,
         file_size: Some(result.file_size as i64),
         path: result.path.clone(),
+        duration_ms: Some(started.elapsed().as_millis() as i64),
+        generator: exports::ExportGenerator::Mechanical,
+        model: None,
+        source: exports::ExportSource::Notes,
+        conversation_id: None,
+        options: options.to_recorded(),
     };
 
     exports::create_export(&conn, export_input)
@@ -194,6 +204,7 @@ pub fn execute_export_notes_docx(
     pool: &DbPool,
     args: Value,
 ) -> Result<String, String> {
+    let started = Instant::now();
     // Parse arguments
     let note_ids: Vec<String> = args
         .get("note_ids")
@@ -238,6 +249,7 @@ pub fn execute_export_notes_docx(
     let options = DocxExportOptions {
         include_images: true,
         page_break_between_notes: page_break,
+        highlight_code: true,
     };
 
     let result = docx_generator::generate_docx_from_notes(&notes_data, title, &output_path, &options)
@@ -251,6 +263,12 @@ pub fn execute_export_notes_docx(
         source_note_ids: note_ids,
         file_size: Some(result.file_size as i64),
         path: result.path.clone(),
+        duration_ms: Some(started.elapsed().as_millis() as i64),
+        generator: exports::ExportGenerator::Mechanical,
+        model: None,
+        source: exports::ExportSource::Notes,
+        conversation_id: None,
+        options: options.to_recorded(),
     };
 
     exports::create_export(&conn, export_input)
@@ -270,6 +288,7 @@ pub fn execute_export_selection_xlsx(
     pool: &DbPool,
     args: Value,
 ) -> Result<String, String> {
+    let started = Instant::now();
     // Parse arguments
     let content = args
         .get("content")
@@ -301,6 +320,12 @@ pub fn execute_export_selection_xlsx(
         source_note_ids: vec![], // Selection-based, no specific note IDs
         file_size: Some(result.file_size as i64),
         path: result.path.clone(),
+        duration_ms: Some(started.elapsed().as_millis() as i64),
+        generator: exports::ExportGenerator::Mechanical,
+        model: None,
+        source: exports::ExportSource::Content,
+        conversation_id: None,
+        options: exports::ExportRenderOptions::default(),
     };
 
     exports::create_export(&conn, export_input)
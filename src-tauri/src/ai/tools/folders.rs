@@ -1,12 +1,16 @@
 //! Folder Tools
 //!
-//! Tools for browsing folder structure and getting notes in folders.
+//! Tools for browsing folder structure and getting notes in folders, plus
+//! creating folders and moving notes between them (requires folders.write).
 
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use crate::ai::agent::ToolDefinition;
+use crate::commands::folders::create_folder_on_disk_from_pool;
 use crate::db::{self, connection::DbPool};
+use crate::models::CreateFolderInput;
+use crate::vault::sync as vault_sync;
 
 /// A folder result for the agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -179,18 +183,7 @@ pub fn execute_get_notes_in_folder(
             Some(id.to_string())
         }
     } else if let Some(name) = args.get("folder_name").and_then(|v| v.as_str()) {
-        // Find folder by name
-        let folders = db::folders::get_all_folders(&conn)
-            .map_err(|e| format!("Failed to get folders: {}", e))?;
-        
-        let folder = folders.iter()
-            .find(|f| f.name.to_lowercase() == name.to_lowercase())
-            .or_else(|| folders.iter().find(|f| f.name.to_lowercase().contains(&name.to_lowercase())));
-        
-        match folder {
-            Some(f) => Some(f.id.clone()),
-            None => return Err(format!("No folder found with name matching '{}'", name)),
-        }
+        Some(find_folder_by_name(&conn, name)?)
     } else {
         // Get unfiled notes
         None
@@ -243,6 +236,239 @@ pub fn execute_get_notes_in_folder(
     }
 }
 
+// ============================================================================
+// create_folder Tool
+// ============================================================================
+
+/// Get the tool definition for create_folder
+pub fn get_create_folder_tool() -> ToolDefinition {
+    ToolDefinition::function(
+        "create_folder",
+        "Create a new folder in the vault, optionally inside an existing folder. Use this to organize notes.",
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "The name of the new folder"
+                },
+                "parent_id": {
+                    "type": "string",
+                    "description": "Optional ID of the parent folder. Leave empty to create a root folder."
+                },
+                "parent_name": {
+                    "type": "string",
+                    "description": "Optional name of the parent folder (used if parent_id is not provided)"
+                }
+            },
+            "required": ["name"]
+        }),
+    )
+}
+
+/// Execute the create_folder tool
+pub fn execute_create_folder(
+    pool: &DbPool,
+    args: Value,
+) -> Result<String, String> {
+    let name = args
+        .get("name")
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+        .ok_or("Missing 'name' argument")?;
+
+    if name.is_empty() {
+        return Err("Folder name cannot be empty".to_string());
+    }
+    if name.contains(['/', '\\']) {
+        return Err("Folder name cannot contain path separators".to_string());
+    }
+
+    let folder = {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        let parent_id = resolve_folder_id(&conn, &args, "parent_id", "parent_name")?;
+
+        // Don't create duplicate siblings
+        let siblings = db::folders::get_child_folders(&conn, parent_id.as_deref())
+            .map_err(|e| format!("Failed to get folders: {}", e))?;
+        if let Some(existing) = siblings.iter().find(|f| f.name.to_lowercase() == name.to_lowercase()) {
+            return Err(format!("A folder named '{}' already exists here (id: {})", existing.name, existing.id));
+        }
+
+        db::folders::create_folder(&conn, CreateFolderInput { name, parent_id })
+            .map_err(|e| format!("Failed to create folder: {}", e))?
+    };
+
+    if let Err(e) = create_folder_on_disk_from_pool(pool, &folder) {
+        log::warn!("[create_folder] Failed to sync folder to disk: {}", e);
+    }
+
+    Ok(json!({
+        "success": true,
+        "message": format!("Created folder: {}", folder.name),
+        "folder": {
+            "id": folder.id,
+            "name": folder.name,
+            "parent_id": folder.parent_id
+        }
+    }).to_string())
+}
+
+// ============================================================================
+// move_note_to_folder Tool
+// ============================================================================
+
+/// Get the tool definition for move_note_to_folder
+pub fn get_move_note_to_folder_tool() -> ToolDefinition {
+    ToolDefinition::function(
+        "move_note_to_folder",
+        "Move a note into a folder, or out of all folders. Use list_folders to find folder IDs.",
+        json!({
+            "type": "object",
+            "properties": {
+                "note_id": {
+                    "type": "string",
+                    "description": "The ID of the note to move"
+                },
+                "folder_id": {
+                    "type": "string",
+                    "description": "The ID of the destination folder. Use an empty string to make the note unfiled."
+                },
+                "folder_name": {
+                    "type": "string",
+                    "description": "The name of the destination folder (used if folder_id is not provided)"
+                }
+            },
+            "required": ["note_id"]
+        }),
+    )
+}
+
+/// Execute the move_note_to_folder tool
+pub fn execute_move_note_to_folder(
+    pool: &DbPool,
+    args: Value,
+) -> Result<String, String> {
+    let note_id = args
+        .get("note_id")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'note_id' argument")?
+        .to_string();
+
+    if args.get("folder_id").is_none() && args.get("folder_name").is_none() {
+        return Err("Provide 'folder_id' or 'folder_name' for the destination".to_string());
+    }
+
+    let (note, folder_id) = {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        let note = db::notes::get_note(&conn, &note_id)
+            .map_err(|e| format!("Failed to get note: {}", e))?
+            .filter(|n| !n.is_deleted)
+            .ok_or_else(|| format!("Note with ID '{}' not found", note_id))?;
+        let folder_id = resolve_folder_id(&conn, &args, "folder_id", "folder_name")?;
+        (note, folder_id)
+    };
+
+    if note.folder_id == folder_id {
+        return Ok(json!({
+            "success": true,
+            "message": format!("Note '{}' is already in that folder", note.title),
+            "note": { "id": note.id, "title": note.title, "folder_id": note.folder_id }
+        }).to_string());
+    }
+
+    {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        db::notes::move_note_to_folder(&conn, &note_id, folder_id.as_deref())
+            .map_err(|e| format!("Failed to move note: {}", e))?;
+    }
+
+    // The old file is only removed once the move is saved and the new file written
+    if let Err(e) = vault_sync::move_note_file(pool, &note) {
+        log::warn!("[move_note_to_folder] Failed to move note file: {}", e);
+    }
+
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    let folder_name = match folder_id {
+        Some(ref id) => db::folders::get_folder(&conn, id).ok().flatten().map(|f| f.name),
+        None => Some("Unfiled".to_string()),
+    };
+
+    Ok(json!({
+        "success": true,
+        "message": format!("Moved '{}' to {}", note.title, folder_name.clone().unwrap_or_default()),
+        "note": {
+            "id": note.id,
+            "title": note.title,
+            "folder_id": folder_id,
+            "folder_name": folder_name
+        }
+    }).to_string())
+}
+
+/// Resolve a folder from an ID or name argument
+///
+/// An empty or "null" ID means no folder (root). Names must match exactly
+/// (ignoring case); see `find_folder_by_name`.
+fn resolve_folder_id(
+    conn: &rusqlite::Connection,
+    args: &Value,
+    id_key: &str,
+    name_key: &str,
+) -> Result<Option<String>, String> {
+    if let Some(id) = args.get(id_key).and_then(|v| v.as_str()) {
+        if id.is_empty() || id == "null" {
+            return Ok(None);
+        }
+        return match db::folders::get_folder(conn, id) {
+            Ok(Some(folder)) => Ok(Some(folder.id)),
+            Ok(None) => Err(format!("Folder with ID '{}' not found", id)),
+            Err(e) => Err(format!("Failed to verify folder: {}", e)),
+        };
+    }
+
+    let Some(name) = args.get(name_key).and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+    find_folder_by_name(conn, name).map(Some)
+}
+
+/// Find the one folder whose name is `name`, ignoring case
+///
+/// When no folder or several folders have that name, the error lists the
+/// candidates (partial matches, or the duplicates with their IDs) so the
+/// model can retry with a folder ID instead of acting on a guess.
+fn find_folder_by_name(conn: &rusqlite::Connection, name: &str) -> Result<String, String> {
+    let folders = db::folders::get_all_folders(conn)
+        .map_err(|e| format!("Failed to get folders: {}", e))?;
+    let wanted = name.to_lowercase();
+    let describe = |matches: &[&crate::models::Folder]| {
+        matches
+            .iter()
+            .map(|f| format!("'{}' (id: {})", f.name, f.id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let exact: Vec<_> = folders.iter().filter(|f| f.name.to_lowercase() == wanted).collect();
+    match exact.as_slice() {
+        [folder] => Ok(folder.id.clone()),
+        [] => {
+            let partial: Vec<_> = folders.iter().filter(|f| f.name.to_lowercase().contains(&wanted)).collect();
+            if partial.is_empty() {
+                Err(format!("No folder named '{}'", name))
+            } else {
+                Err(format!("No folder named '{}'. Did you mean: {}?", name, describe(&partial)))
+            }
+        }
+        _ => Err(format!(
+            "Several folders are named '{}': {}. Use folder_id to pick one.",
+            name,
+            describe(&exact)
+        )),
+    }
+}
+
 /// Create a snippet from content
 fn create_snippet(content: &str, max_len: usize) -> String {
     if content.len() <= max_len {
@@ -260,6 +486,8 @@ fn create_snippet(content: &str, max_len: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::connection::init_test_pool;
+    use crate::models::CreateNoteInput;
 
     #[test]
     fn test_get_list_folders_tool() {
@@ -275,6 +503,78 @@ mod tests {
         assert!(tool.function.description.contains("notes"));
     }
 
+    #[test]
+    fn test_folder_write_tools() {
+        assert_eq!(get_create_folder_tool().function.name, "create_folder");
+        assert_eq!(get_move_note_to_folder_tool().function.name, "move_note_to_folder");
+    }
+
+    #[test]
+    fn test_create_folder_and_move_note() {
+        let pool = init_test_pool().unwrap();
+        let note = {
+            let conn = pool.get().unwrap();
+            db::notes::create_note(
+                &conn,
+                CreateNoteInput {
+                    title: "Loose note".to_string(),
+                    content: None,
+                    content_html: None,
+                    folder_id: None,
+                },
+            )
+            .unwrap()
+        };
+
+        let result = execute_create_folder(&pool, json!({ "name": "Projects" })).unwrap();
+        let folder_id = serde_json::from_str::<Value>(&result).unwrap()["folder"]["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(execute_create_folder(&pool, json!({ "name": "projects" })).is_err());
+
+        execute_move_note_to_folder(&pool, json!({ "note_id": note.id, "folder_name": "Projects" })).unwrap();
+        let moved = db::notes::get_note(&pool.get().unwrap(), &note.id).unwrap().unwrap();
+        assert_eq!(moved.folder_id, Some(folder_id));
+
+        execute_move_note_to_folder(&pool, json!({ "note_id": note.id, "folder_id": "" })).unwrap();
+        let unfiled = db::notes::get_note(&pool.get().unwrap(), &note.id).unwrap().unwrap();
+        assert_eq!(unfiled.folder_id, None);
+    }
+
+    #[test]
+    fn test_folder_names_must_match_exactly() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let folder = |name: &str, parent_id: Option<&str>| {
+            db::folders::create_folder(
+                &conn,
+                CreateFolderInput {
+                    name: name.to_string(),
+                    parent_id: parent_id.map(str::to_string),
+                },
+            )
+            .unwrap()
+        };
+        let work = folder("Work", None);
+        let archive = folder("Archive", None);
+        let home = folder("Home", None);
+        let old_work = folder("work", Some(&archive.id));
+        folder("Homework", None);
+
+        assert_eq!(find_folder_by_name(&conn, "HOME").unwrap(), home.id);
+
+        // A partial match is offered, not used
+        let err = find_folder_by_name(&conn, "Hom").unwrap_err();
+        assert!(err.contains("Did you mean"), "{}", err);
+        assert!(err.contains("'Home'") && err.contains("'Homework'"), "{}", err);
+        assert_eq!(find_folder_by_name(&conn, "Travel").unwrap_err(), "No folder named 'Travel'");
+
+        // Same name in different places is ambiguous
+        let err = find_folder_by_name(&conn, "work").unwrap_err();
+        assert!(err.contains(&work.id) && err.contains(&old_work.id), "{}", err);
+    }
+
     #[test]
     fn test_create_snippet() {
         let short = "Hello world";
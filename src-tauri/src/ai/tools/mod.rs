@@ -85,6 +85,11 @@ impl RWPermission {
     pub fn full() -> Self {
         Self { read: true, write: true }
     }
+
+    /// Create a read-only permission
+    pub fn read_only() -> Self {
+        Self { read: true, write: false }
+    }
 }
 
 /// Configuration for data sources the agent can access
@@ -101,12 +106,12 @@ pub struct SourceConfig {
     pub notes: RWPermission,
     
     /// Tags: read (get_note_tags, search_by_tag, get_notes_sharing_tags)
-    ///       write (add/remove tags - future)
+    ///       write (add_tag_to_note, remove_tag_from_note)
     #[serde(default)]
     pub tags: RWPermission,
     
-    /// Calendar: read (get_calendar_events)
-    ///           write (create_calendar_event)
+    /// Calendar: read (get_calendar_events, get_upcoming_events)
+    ///           write (create_calendar_event, create_meeting_note with notes write)
     #[serde(default)]
     pub calendar: RWPermission,
     
@@ -116,8 +121,8 @@ pub struct SourceConfig {
     pub daily_notes: RWPermission,
     
     /// Folders: read (list_folders, get_notes_in_folder)
-    ///          write (create folders, move notes - future)
-    #[serde(default)]
+    ///          write (create_folder, move_note_to_folder) - off unless enabled
+    #[serde(default = "RWPermission::read_only")]
     pub folders: RWPermission,
     
     // =========================================================================
@@ -141,7 +146,8 @@ impl Default for SourceConfig {
             tags: RWPermission::full(),
             calendar: RWPermission::full(),
             daily_notes: RWPermission::full(),
-            folders: RWPermission::full(),
+            // Reorganizing the vault is opt-in
+            folders: RWPermission::read_only(),
             // Read-only sources
             url_attachments: true,
             web_search: false, // Requires API key configuration
@@ -193,6 +199,10 @@ pub struct AgentConfig {
     /// Capability toggles
     #[serde(default)]
     pub capabilities: CapabilityConfig,
+    
+    /// Seconds a single tool call may run before the agent moves on without it
+    #[serde(default = "default_tool_timeout_secs")]
+    pub tool_timeout_secs: u64,
 }
 
 impl Default for AgentConfig {
@@ -203,6 +213,7 @@ impl Default for AgentConfig {
             web_search: WebSearchConfig::default(),
             sources: SourceConfig::default(),
             capabilities: CapabilityConfig::default(),
+            tool_timeout_secs: default_tool_timeout_secs(),
         }
     }
 }
@@ -211,6 +222,10 @@ fn default_enabled() -> bool {
     true
 }
 
+fn default_tool_timeout_secs() -> u64 {
+    crate::ai::agent::DEFAULT_TOOL_TIMEOUT.as_secs()
+}
+
 fn default_enabled_tools() -> Vec<String> {
     vec![
         "search_notes".to_string(),
@@ -222,6 +237,7 @@ fn default_enabled_tools() -> Vec<String> {
         "create_document".to_string(),
         "add_section".to_string(),
         "add_table".to_string(),
+        "add_image".to_string(),
         "save_document".to_string(),
         "cancel_document".to_string(),
     ]
@@ -299,6 +315,44 @@ pub fn get_create_mermaid_tool() -> ToolDefinition {
     )
 }
 
+/// How the frontend should apply content from write_content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteMode {
+    /// Insert at the cursor
+    #[default]
+    Insert,
+    /// Replace the current selection
+    ReplaceSelection,
+    /// Add to the end of the note
+    AppendToNote,
+}
+
+impl WriteMode {
+    pub const ALL: [WriteMode; 3] = [WriteMode::Insert, WriteMode::ReplaceSelection, WriteMode::AppendToNote];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WriteMode::Insert => "insert",
+            WriteMode::ReplaceSelection => "replace_selection",
+            WriteMode::AppendToNote => "append_to_note",
+        }
+    }
+
+    /// Read the `mode` argument of a write_content call (Insert when absent)
+    pub fn from_args(args: &serde_json::Value) -> Result<Self, String> {
+        match args.get("mode").and_then(|v| v.as_str()) {
+            None => Ok(WriteMode::default()),
+            Some(mode) => WriteMode::ALL
+                .into_iter()
+                .find(|m| m.as_str() == mode)
+                .ok_or_else(|| {
+                    format!("Invalid mode '{}' (expected insert, replace_selection or append_to_note)", mode)
+                }),
+        }
+    }
+}
+
 /// Get the tool definition for write_content
 pub fn get_write_content_tool() -> ToolDefinition {
     ToolDefinition::function(
@@ -310,6 +364,11 @@ pub fn get_write_content_tool() -> ToolDefinition {
                 "content": {
                     "type": "string",
                     "description": "The markdown content to insert"
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": WriteMode::ALL.map(|m| m.as_str()),
+                    "description": "How to apply the content: 'insert' at the cursor (default), 'replace_selection' to replace the text the user selected, or 'append_to_note' to add it to the end of the note"
                 }
             },
             "required": ["content"]
@@ -351,20 +410,28 @@ pub fn get_unified_agent_tools(config: &AgentConfig, include_write_content: bool
         }
     }
     
-    // Tags: read (get tags, search by tag, notes sharing tags) / write (future: add/remove tags)
+    // Tags: read (get tags, search by tag, notes sharing tags) / write (add/remove tags)
     if config.sources.tags.read {
         tools.push(get_note_tags_tool());
         tools.push(get_search_by_tag_tool());
         tools.push(get_notes_sharing_tags_tool());
     }
-    // Note: tags.write is reserved for future tag modification tools
+    if config.sources.tags.write {
+        tools.push(get_add_tag_to_note_tool());
+        tools.push(get_remove_tag_from_note_tool());
+    }
     
-    // Calendar: read (get events) / write (create events)
+    // Calendar: read (get events, upcoming schedule) / write (create events)
     if config.sources.calendar.read {
         tools.push(get_calendar_events_tool());
+        tools.push(get_upcoming_events_tool());
     }
     if config.sources.calendar.write {
         tools.push(get_create_calendar_event_tool());
+        // Meeting notes write a note as well as the event's link
+        if config.sources.notes.write {
+            tools.push(get_create_meeting_note_tool());
+        }
     }
     
     // Daily Notes: read (get daily note) / write (future: create/modify)
@@ -373,12 +440,15 @@ pub fn get_unified_agent_tools(config: &AgentConfig, include_write_content: bool
     }
     // Note: daily_notes.write is reserved for future daily note modification tools
     
-    // Folders: read (list folders, get notes in folder) / write (future: create, move)
+    // Folders: read (list folders, get notes in folder) / write (create folders, move notes)
     if config.sources.folders.read {
         tools.push(get_list_folders_tool());
         tools.push(get_notes_in_folder_tool());
     }
-    // Note: folders.write is reserved for future folder management tools
+    if config.sources.folders.write {
+        tools.push(get_create_folder_tool());
+        tools.push(get_move_note_to_folder_tool());
+    }
     
     // =========================================================================
     // Read-Only Data Sources
@@ -472,7 +542,7 @@ mod tests {
         assert!(config.daily_notes.read);
         assert!(config.daily_notes.write);
         assert!(config.folders.read);
-        assert!(config.folders.write);
+        assert!(!config.folders.write);
         // Read-only sources
         assert!(config.url_attachments);
         assert!(!config.web_search); // Disabled by default (requires API key)
@@ -519,6 +589,37 @@ mod tests {
         assert!(tool_names.contains(&"export_notes_pdf"));
     }
     
+    #[test]
+    fn test_write_content_tool_has_mode_enum() {
+        let tool = get_write_content_tool();
+        let mode = &tool.function.parameters["properties"]["mode"];
+
+        assert_eq!(mode["type"], "string");
+        assert_eq!(mode["enum"], json!(["insert", "replace_selection", "append_to_note"]));
+        // Mode is optional so older prompts keep working
+        assert_eq!(tool.function.parameters["required"], json!(["content"]));
+    }
+
+    #[test]
+    fn test_write_mode_from_args() {
+        assert_eq!(WriteMode::from_args(&json!({ "content": "x" })).unwrap(), WriteMode::Insert);
+        assert_eq!(WriteMode::from_args(&json!({ "mode": "insert" })).unwrap(), WriteMode::Insert);
+        assert_eq!(
+            WriteMode::from_args(&json!({ "mode": "replace_selection" })).unwrap(),
+            WriteMode::ReplaceSelection
+        );
+        assert_eq!(
+            WriteMode::from_args(&json!({ "mode": "append_to_note" })).unwrap(),
+            WriteMode::AppendToNote
+        );
+        assert!(WriteMode::from_args(&json!({ "mode": "overwrite" })).unwrap_err().contains("overwrite"));
+
+        // Serialized form matches the tool schema values
+        for mode in WriteMode::ALL {
+            assert_eq!(serde_json::to_value(mode).unwrap(), json!(mode.as_str()));
+        }
+    }
+
     #[test]
     fn test_get_unified_agent_tools_without_write_content() {
         let config = AgentConfig::default();
@@ -556,6 +657,39 @@ mod tests {
         assert!(!tool_names.contains(&"append_content_to_note"));
     }
     
+    #[test]
+    fn test_get_unified_agent_tools_tag_write_permission() {
+        let mut config = AgentConfig::default();
+        let tools = get_unified_agent_tools(&config, false);
+        let tool_names: Vec<&str> = tools.iter().map(|t| t.function.name.as_str()).collect();
+        assert!(tool_names.contains(&"add_tag_to_note"));
+        assert!(tool_names.contains(&"remove_tag_from_note"));
+
+        config.sources.tags = RWPermission { read: true, write: false };
+        let tools = get_unified_agent_tools(&config, false);
+        let tool_names: Vec<&str> = tools.iter().map(|t| t.function.name.as_str()).collect();
+        assert!(tool_names.contains(&"get_note_tags"));
+        assert!(!tool_names.contains(&"add_tag_to_note"));
+        assert!(!tool_names.contains(&"remove_tag_from_note"));
+    }
+    
+    #[test]
+    fn test_get_unified_agent_tools_folder_write_permission() {
+        // Folder writes are off by default
+        let mut config = AgentConfig::default();
+        let tools = get_unified_agent_tools(&config, false);
+        let tool_names: Vec<&str> = tools.iter().map(|t| t.function.name.as_str()).collect();
+        assert!(tool_names.contains(&"list_folders"));
+        assert!(!tool_names.contains(&"create_folder"));
+        assert!(!tool_names.contains(&"move_note_to_folder"));
+
+        config.sources.folders = RWPermission::full();
+        let tools = get_unified_agent_tools(&config, false);
+        let tool_names: Vec<&str> = tools.iter().map(|t| t.function.name.as_str()).collect();
+        assert!(tool_names.contains(&"create_folder"));
+        assert!(tool_names.contains(&"move_note_to_folder"));
+    }
+    
     #[test]
     fn test_get_unified_agent_tools_disabled_sources() {
         let mut config = AgentConfig::default();
@@ -574,7 +708,9 @@ mod tests {
         
         // Should NOT have calendar tools
         assert!(!tool_names.contains(&"get_calendar_events"));
+        assert!(!tool_names.contains(&"get_upcoming_events"));
         assert!(!tool_names.contains(&"create_calendar_event"));
+        assert!(!tool_names.contains(&"create_meeting_note"));
         
         // Should still have other sources
         assert!(tool_names.contains(&"search_url_embeddings"));
@@ -1,6 +1,7 @@
 //! Note Tags Tools
 //!
-//! Tools for accessing tag information on notes and finding notes by tag.
+//! Tools for accessing tag information on notes and finding notes by tag,
+//! plus adding and removing tags (requires tags.write).
 
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -57,24 +58,7 @@ pub fn execute_get_note_tags(
     args: Value,
 ) -> Result<String, String> {
     let conn = pool.get().map_err(|e| e.to_string())?;
-
-    // Resolve note_id
-    let note_id = if let Some(id) = args.get("note_id").and_then(|v| v.as_str()) {
-        id.to_string()
-    } else if let Some(title) = args.get("note_title").and_then(|v| v.as_str()) {
-        // Find note by title
-        let notes = db::notes::get_all_notes(&conn).map_err(|e| e.to_string())?;
-        let note = notes.iter()
-            .find(|n| n.title.to_lowercase() == title.to_lowercase())
-            .or_else(|| notes.iter().find(|n| n.title.to_lowercase().contains(&title.to_lowercase())));
-        
-        match note {
-            Some(n) => n.id.clone(),
-            None => return Err(format!("No note found with title matching '{}'", title)),
-        }
-    } else {
-        return Err("Either 'note_id' or 'note_title' must be provided".to_string());
-    };
+    let note_id = resolve_note_id(&conn, &args)?;
 
     // Get tags for the note
     let tags = db::tags::get_note_tags(&conn, &note_id)
@@ -104,6 +88,165 @@ pub fn execute_get_note_tags(
     }
 }
 
+/// Resolve the target note from a note_id or note_title argument
+fn resolve_note_id(conn: &rusqlite::Connection, args: &Value) -> Result<String, String> {
+    if let Some(id) = args.get("note_id").and_then(|v| v.as_str()) {
+        Ok(id.to_string())
+    } else if let Some(title) = args.get("note_title").and_then(|v| v.as_str()) {
+        // Find note by title
+        let notes = db::notes::get_all_notes(conn).map_err(|e| e.to_string())?;
+        let note = notes.iter()
+            .find(|n| n.title.to_lowercase() == title.to_lowercase())
+            .or_else(|| notes.iter().find(|n| n.title.to_lowercase().contains(&title.to_lowercase())));
+        
+        match note {
+            Some(n) => Ok(n.id.clone()),
+            None => Err(format!("No note found with title matching '{}'", title)),
+        }
+    } else {
+        Err("Either 'note_id' or 'note_title' must be provided".to_string())
+    }
+}
+
+// ============================================================================
+// add_tag_to_note Tool
+// ============================================================================
+
+/// Get the tool definition for add_tag_to_note
+pub fn get_add_tag_to_note_tool() -> ToolDefinition {
+    ToolDefinition::function(
+        "add_tag_to_note",
+        "Add a tag to a note, creating the tag if it doesn't exist. Prefer existing tag names (use search_by_tag or get_note_tags to see them).",
+        json!({
+            "type": "object",
+            "properties": {
+                "note_id": {
+                    "type": "string",
+                    "description": "The unique ID of the note"
+                },
+                "note_title": {
+                    "type": "string",
+                    "description": "The title of the note (used if note_id is not provided)"
+                },
+                "tag_name": {
+                    "type": "string",
+                    "description": "The tag to add (e.g., 'project/alpha')"
+                },
+                "color": {
+                    "type": "string",
+                    "enum": db::tags::TAG_PALETTE_COLORS,
+                    "description": "Optional color for a newly created tag"
+                }
+            },
+            "required": ["tag_name"]
+        }),
+    )
+}
+
+/// Execute the add_tag_to_note tool
+pub fn execute_add_tag_to_note(
+    pool: &DbPool,
+    args: Value,
+) -> Result<String, String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+
+    let tag_name = args
+        .get("tag_name")
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().trim_start_matches('#'))
+        .filter(|s| !s.is_empty())
+        .ok_or("Missing 'tag_name' argument")?;
+    // An unusable color shouldn't stop the tag from being added
+    let color = args
+        .get("color")
+        .and_then(|v| v.as_str())
+        .filter(|c| db::tags::validate_tag_color(c).is_ok());
+
+    let note_id = resolve_note_id(&conn, &args)?;
+    let note = db::notes::get_note(&conn, &note_id)
+        .map_err(|e| format!("Failed to get note: {}", e))?
+        .filter(|n| !n.is_deleted)
+        .ok_or_else(|| format!("Note with ID '{}' not found", note_id))?;
+
+    let tag = db::tags::find_or_create_tag(&conn, tag_name, color)
+        .map_err(|e| format!("Failed to find or create tag: {}", e))?;
+    db::tags::add_tag_to_note(&conn, &note.id, &tag.id)
+        .map_err(|e| format!("Failed to add tag: {}", e))?;
+
+    Ok(json!({
+        "success": true,
+        "message": format!("Tagged '{}' with '{}'", note.title, tag.name),
+        "note_id": note.id,
+        "tag": TagResult { name: tag.name, color: tag.color }
+    }).to_string())
+}
+
+// ============================================================================
+// remove_tag_from_note Tool
+// ============================================================================
+
+/// Get the tool definition for remove_tag_from_note
+pub fn get_remove_tag_from_note_tool() -> ToolDefinition {
+    ToolDefinition::function(
+        "remove_tag_from_note",
+        "Remove a tag from a note. The tag itself is kept for other notes.",
+        json!({
+            "type": "object",
+            "properties": {
+                "note_id": {
+                    "type": "string",
+                    "description": "The unique ID of the note"
+                },
+                "note_title": {
+                    "type": "string",
+                    "description": "The title of the note (used if note_id is not provided)"
+                },
+                "tag_name": {
+                    "type": "string",
+                    "description": "The tag to remove"
+                }
+            },
+            "required": ["tag_name"]
+        }),
+    )
+}
+
+/// Execute the remove_tag_from_note tool
+pub fn execute_remove_tag_from_note(
+    pool: &DbPool,
+    args: Value,
+) -> Result<String, String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+
+    let tag_name = args
+        .get("tag_name")
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().trim_start_matches('#'))
+        .filter(|s| !s.is_empty())
+        .ok_or("Missing 'tag_name' argument")?;
+
+    let note_id = resolve_note_id(&conn, &args)?;
+
+    let removed = match db::tags::find_tag_by_name(&conn, tag_name)
+        .map_err(|e| format!("Failed to find tag: {}", e))?
+    {
+        Some(tag) => db::tags::remove_tag_from_note(&conn, &note_id, &tag.id)
+            .map_err(|e| format!("Failed to remove tag: {}", e))?,
+        None => false,
+    };
+
+    Ok(json!({
+        "success": true,
+        "removed": removed,
+        "note_id": note_id,
+        "message": if removed {
+            format!("Removed tag '{}'", tag_name)
+        } else {
+            format!("Note did not have tag '{}'", tag_name)
+        }
+    }).to_string())
+}
+
 // ============================================================================
 // search_by_tag Tool
 // ============================================================================
@@ -112,7 +255,7 @@ pub fn execute_get_note_tags(
 pub fn get_search_by_tag_tool() -> ToolDefinition {
     ToolDefinition::function(
         "search_by_tag",
-        "Find all notes that have a specific tag. Useful for discovering related content by category.",
+        "Find all notes that have a specific tag. Useful for discovering related content by category. Tags can be nested with '/' (e.g. 'project/alpha').",
         json!({
             "type": "object",
             "properties": {
@@ -120,6 +263,11 @@ pub fn get_search_by_tag_tool() -> ToolDefinition {
                     "type": "string",
                     "description": "The name of the tag to search for (case-insensitive)"
                 },
+                "include_nested": {
+                    "type": "boolean",
+                    "description": "Also match tags nested under this one, so 'project' matches 'project/alpha' (default: false)",
+                    "default": false
+                },
                 "limit": {
                     "type": "integer",
                     "description": "Maximum number of notes to return (default: 10, max: 20)",
@@ -151,6 +299,32 @@ pub fn execute_search_by_tag(
         .unwrap_or(10)
         .min(20) as usize;
 
+    let include_nested = args
+        .get("include_nested")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    if include_nested {
+        let notes: Vec<NoteWithTagsResult> = db::tags::get_notes_by_tag_prefix(&conn, tag_name)
+            .map_err(|e| format!("Failed to search notes by tag: {}", e))?
+            .into_iter()
+            .take(limit)
+            .map(|note| NoteWithTagsResult {
+                note_id: note.id,
+                title: note.title,
+                snippet: create_snippet(&note.content.unwrap_or_default(), 200),
+            })
+            .collect();
+
+        return Ok(json!({
+            "success": true,
+            "tag_name": tag_name,
+            "include_nested": true,
+            "notes": notes,
+            "count": notes.len()
+        }).to_string());
+    }
+
     // Find the tag by name (case-insensitive)
     let tag = db::tags::find_tag_by_name(&conn, tag_name)
         .map_err(|e| format!("Failed to find tag: {}", e))?;
@@ -246,6 +420,36 @@ mod tests {
         assert!(tool.function.description.contains("tag"));
     }
 
+    #[test]
+    fn test_add_and_remove_tag() {
+        let pool = crate::db::connection::init_test_pool().unwrap();
+        pool.get()
+            .unwrap()
+            .execute("INSERT INTO notes (id, title, is_deleted) VALUES ('n1', 'Roadmap', FALSE)", [])
+            .unwrap();
+
+        execute_add_tag_to_note(
+            &pool,
+            json!({ "note_title": "Roadmap", "tag_name": "#planning", "color": "teal-ish" }),
+        )
+        .unwrap();
+        let tags = db::tags::get_note_tags(&pool.get().unwrap(), "n1").unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name, "planning");
+        assert_eq!(tags[0].color, None);
+
+        let result: Value = serde_json::from_str(
+            &execute_remove_tag_from_note(&pool, json!({ "note_id": "n1", "tag_name": "Planning" })).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result["removed"], true);
+        assert!(db::tags::get_note_tags(&pool.get().unwrap(), "n1").unwrap().is_empty());
+        // The tag itself is kept
+        assert!(db::tags::find_tag_by_name(&pool.get().unwrap(), "planning").unwrap().is_some());
+
+        assert!(execute_add_tag_to_note(&pool, json!({ "note_id": "missing", "tag_name": "x" })).is_err());
+    }
+
     #[test]
     fn test_create_snippet() {
         let short = "Hello world";
@@ -256,5 +460,39 @@ mod tests {
         assert!(snippet.ends_with("..."));
         assert!(snippet.len() <= 45);
     }
-}
 
+    #[test]
+    fn test_search_by_tag_exact_vs_nested() {
+        let pool = crate::db::connection::init_test_pool().unwrap();
+        {
+            let conn = pool.get().unwrap();
+            for (note_id, tag_name) in [("n1", "project"), ("n2", "project/alpha"), ("n3", "projectx")] {
+                conn.execute(
+                    "INSERT INTO notes (id, title, is_deleted) VALUES (?1, ?1, FALSE)",
+                    [note_id],
+                )
+                .unwrap();
+                let tag = db::tags::create_tag(&conn, tag_name, None, None).unwrap();
+                db::tags::add_tag_to_note(&conn, note_id, &tag.id).unwrap();
+            }
+        }
+
+        let note_ids = |args: Value| -> Vec<String> {
+            let result: Value = serde_json::from_str(&execute_search_by_tag(&pool, args).unwrap()).unwrap();
+            let mut ids: Vec<String> = result["notes"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|n| n["noteId"].as_str().unwrap().to_string())
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(note_ids(json!({ "tag_name": "project" })), vec!["n1"]);
+        assert_eq!(
+            note_ids(json!({ "tag_name": "project", "include_nested": true })),
+            vec!["n1", "n2"]
+        );
+    }
+}
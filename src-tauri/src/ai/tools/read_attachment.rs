@@ -2,10 +2,11 @@
 //!
 //! Extracts text content from various document formats including:
 //! - PDF documents
-//! - Excel spreadsheets (xlsx, xls)
+//! - Excel spreadsheets (xlsx, xls) and CSV/TSV files, rendered as markdown tables
 //! - Word documents (docx)
 //! - PowerPoint presentations (pptx)
 //! - Plain text and code files
+//! - Images, via OCR when tesseract is available
 
 use std::fs::File;
 use std::io::{BufReader, Read};
@@ -16,6 +17,17 @@ use quick_xml::events::Event;
 use quick_xml::reader::Reader as XmlReader;
 use zip::ZipArchive;
 
+use crate::sidecar::ocr;
+
+/// Maximum data rows rendered per table (sheet or CSV file)
+const MAX_TABLE_ROWS: usize = 200;
+
+/// Maximum columns rendered per table
+const MAX_TABLE_COLUMNS: usize = 30;
+
+/// Maximum characters kept per cell
+const MAX_CELL_CHARS: usize = 200;
+
 /// Extract text from an attachment based on file type
 pub fn extract_text_from_attachment(path: &str, max_chars: Option<usize>) -> Result<String, String> {
     let max_chars = max_chars.unwrap_or(50000);
@@ -33,6 +45,8 @@ pub fn extract_text_from_attachment(path: &str, max_chars: Option<usize>) -> Res
     let content = match extension.as_str() {
         "pdf" => extract_pdf_text(path)?,
         "xlsx" | "xls" | "xlsm" | "xlsb" => extract_excel_text(path)?,
+        "csv" => extract_csv_text(path, None)?,
+        "tsv" => extract_csv_text(path, Some('\t'))?,
         "docx" => extract_docx_text(path)?,
         "pptx" => extract_pptx_text(path)?,
         // Text and code files - read directly
@@ -42,12 +56,16 @@ pub fn extract_text_from_attachment(path: &str, max_chars: Option<usize>) -> Res
         "html" | "htm" | "xml" | "json" | "yaml" | "yml" | "toml" | "sql" | "sh" | "bash" => {
             read_text_file(path)?
         }
-        _ => {
-            // Try to read as text anyway
-            read_text_file(path).unwrap_or_else(|_| {
-                format!("Unable to extract text from file type: .{}", extension)
-            })
-        }
+        ext if ocr::is_ocr_image_extension(ext) => extract_image_text(path)?,
+        // Unknown extension: sniff spreadsheets, then try to read as text anyway
+        _ if is_xlsx_file(path) => extract_excel_text(path)?,
+        _ => match read_text_file(path) {
+            Ok(text) => match detect_csv_delimiter(&text) {
+                Some(delimiter) => csv_to_markdown(&text, delimiter),
+                None => text,
+            },
+            Err(_) => format!("Unable to extract text from file type: .{}", extension),
+        },
     };
 
     // Truncate if necessary
@@ -72,6 +90,15 @@ fn extract_pdf_text(path: &str) -> Result<String, String> {
         .map_err(|e| format!("Failed to extract PDF text: {}", e))
 }
 
+/// Recognize text in an image with OCR
+fn extract_image_text(path: &str) -> Result<String, String> {
+    let text = ocr::recognize_text(path, ocr::DEFAULT_OCR_TIMEOUT)?;
+    if text.is_empty() {
+        return Err("No text recognized in image".to_string());
+    }
+    Ok(text)
+}
+
 /// Extract text from an Excel file
 fn extract_excel_text(path: &str) -> Result<String, String> {
     let mut workbook = open_workbook_auto(path)
@@ -82,35 +109,17 @@ fn extract_excel_text(path: &str) -> Result<String, String> {
 
     for sheet_name in sheet_names {
         if let Ok(range) = workbook.worksheet_range(&sheet_name) {
-            all_text.push_str(&format!("## Sheet: {}\n\n", sheet_name));
-
-            for row in range.rows() {
-                let row_text: Vec<String> = row
-                    .iter()
-                    .map(|cell| {
-                        // Use get_string/get_float etc. or convert to string representation
-                        if cell.is_empty() {
-                            String::new()
-                        } else if let Some(s) = cell.get_string() {
-                            s.to_string()
-                        } else if let Some(f) = cell.get_float() {
-                            f.to_string()
-                        } else if let Some(i) = cell.get_int() {
-                            i.to_string()
-                        } else if let Some(b) = cell.get_bool() {
-                            b.to_string()
-                        } else {
-                            // Fallback: try to format as string
-                            format!("{:?}", cell)
-                        }
-                    })
-                    .collect();
-
-                // Only add non-empty rows
-                if row_text.iter().any(|s| !s.is_empty()) {
-                    all_text.push_str(&format!("| {} |\n", row_text.join(" | ")));
-                }
+            let rows: Vec<Vec<String>> = range
+                .rows()
+                .map(|row| row.iter().map(excel_cell_to_string).collect())
+                .collect();
+            let table = rows_to_markdown_table(rows);
+            if table.is_empty() {
+                continue;
             }
+
+            all_text.push_str(&format!("## Sheet: {}\n\n", sheet_name));
+            all_text.push_str(&table);
             all_text.push('\n');
         }
     }
@@ -122,6 +131,170 @@ fn extract_excel_text(path: &str) -> Result<String, String> {
     Ok(all_text)
 }
 
+/// Convert a spreadsheet cell to display text
+fn excel_cell_to_string(cell: &calamine::Data) -> String {
+    if cell.is_empty() {
+        String::new()
+    } else if let Some(s) = cell.get_string() {
+        s.to_string()
+    } else if let Some(f) = cell.get_float() {
+        f.to_string()
+    } else if let Some(i) = cell.get_int() {
+        i.to_string()
+    } else if let Some(b) = cell.get_bool() {
+        b.to_string()
+    } else {
+        // Dates, durations and errors
+        cell.to_string()
+    }
+}
+
+/// Whether a file is an xlsx workbook, regardless of its extension
+fn is_xlsx_file(path: &str) -> bool {
+    File::open(path)
+        .ok()
+        .and_then(|file| ZipArchive::new(BufReader::new(file)).ok())
+        .map(|mut archive| archive.by_name("xl/workbook.xml").is_ok())
+        .unwrap_or(false)
+}
+
+/// Extract a CSV file as a markdown table, detecting the delimiter if not given
+fn extract_csv_text(path: &str, delimiter: Option<char>) -> Result<String, String> {
+    let text = read_text_file(path)?;
+    let delimiter = delimiter.or_else(|| detect_csv_delimiter(&text)).unwrap_or(',');
+    Ok(csv_to_markdown(&text, delimiter))
+}
+
+/// Guess the delimiter of delimited text
+///
+/// A delimiter qualifies when it appears the same number of times in each of
+/// the first few lines. Returns None for text that doesn't look tabular.
+fn detect_csv_delimiter(text: &str) -> Option<char> {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).take(10).collect();
+    if lines.len() < 2 {
+        return None;
+    }
+
+    [',', '\t', ';'].into_iter().find(|&delimiter| {
+        let counts: Vec<usize> = lines.iter().map(|line| parse_csv_line_count(line, delimiter)).collect();
+        counts[0] > 0 && counts.iter().all(|&c| c == counts[0])
+    })
+}
+
+/// Count delimiters outside quotes in a single line
+fn parse_csv_line_count(line: &str, delimiter: char) -> usize {
+    let mut in_quotes = false;
+    line.chars()
+        .filter(|&c| {
+            if c == '"' {
+                in_quotes = !in_quotes;
+            }
+            c == delimiter && !in_quotes
+        })
+        .count()
+}
+
+/// Parse delimited text into rows, handling quoted fields
+fn parse_csv(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows
+}
+
+/// Render delimited text as a markdown table
+fn csv_to_markdown(text: &str, delimiter: char) -> String {
+    rows_to_markdown_table(parse_csv(text, delimiter))
+}
+
+/// Escape and shorten a cell for a markdown table
+fn markdown_cell(cell: &str) -> String {
+    let cell = cell.trim().replace(['\r', '\n'], " ").replace('|', "\\|");
+    if cell.chars().count() > MAX_CELL_CHARS {
+        format!("{}…", cell.chars().take(MAX_CELL_CHARS).collect::<String>())
+    } else {
+        cell
+    }
+}
+
+/// Render rows as a markdown table, using the first row as the header
+///
+/// Empty rows are skipped. Rows and columns beyond the caps are left out with a note.
+fn rows_to_markdown_table(rows: Vec<Vec<String>>) -> String {
+    let mut rows = rows
+        .into_iter()
+        .filter(|row| row.iter().any(|cell| !cell.trim().is_empty()));
+
+    let Some(header) = rows.next() else {
+        return String::new();
+    };
+    let body: Vec<Vec<String>> = rows.collect();
+
+    let total_columns = std::iter::once(&header).chain(body.iter()).map(|r| r.len()).max().unwrap_or(0);
+    let columns = total_columns.min(MAX_TABLE_COLUMNS);
+
+    let render_row = |row: &[String]| {
+        let cells: Vec<String> = (0..columns)
+            .map(|i| row.get(i).map(|c| markdown_cell(c)).unwrap_or_default())
+            .collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+
+    let mut table = render_row(&header);
+    table.push_str(&format!("|{}\n", " --- |".repeat(columns)));
+    for row in body.iter().take(MAX_TABLE_ROWS) {
+        table.push_str(&render_row(row));
+    }
+
+    if body.len() > MAX_TABLE_ROWS {
+        table.push_str(&format!(
+            "\n[{} more rows not shown]\n",
+            body.len() - MAX_TABLE_ROWS
+        ));
+    }
+    if total_columns > columns {
+        table.push_str(&format!(
+            "\n[{} more columns not shown]\n",
+            total_columns - columns
+        ));
+    }
+
+    table
+}
+
 /// Extract text from a Word document (docx)
 fn extract_docx_text(path: &str) -> Result<String, String> {
     let file = File::open(path)
@@ -251,6 +424,67 @@ fn read_text_file(path: &str) -> Result<String, String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_csv_to_markdown_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sales.csv");
+        std::fs::write(
+            &path,
+            "Region,Revenue,Notes\nNorth,1200,\"Strong, steady\"\n\nSouth,950,\"Said \"\"ok\"\"\"\n",
+        )
+        .unwrap();
+
+        let text = extract_text_from_attachment(path.to_str().unwrap(), None).unwrap();
+        assert_eq!(
+            text,
+            "| Region | Revenue | Notes |\n\
+             | --- | --- | --- |\n\
+             | North | 1200 | Strong, steady |\n\
+             | South | 950 | Said \"ok\" |\n"
+        );
+    }
+
+    #[test]
+    fn test_xlsx_to_markdown_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("inventory.xlsx");
+
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        let sheet = workbook.add_worksheet().set_name("Stock").unwrap();
+        sheet.write_string(0, 0, "Item").unwrap();
+        sheet.write_string(0, 1, "Count").unwrap();
+        sheet.write_string(1, 0, "Pens").unwrap();
+        sheet.write_number(1, 1, 12.0).unwrap();
+        sheet.write_string(2, 0, "A | B").unwrap();
+        sheet.write_number(2, 1, 2.5).unwrap();
+        workbook.save(&path).unwrap();
+
+        let expected = "## Sheet: Stock\n\n\
+                        | Item | Count |\n\
+                        | --- | --- |\n\
+                        | Pens | 12 |\n\
+                        | A \\| B | 2.5 |\n";
+        let text = extract_text_from_attachment(path.to_str().unwrap(), None).unwrap();
+        assert_eq!(text.trim_end(), expected.trim_end());
+
+        // Detected by content when the extension doesn't say
+        let renamed = dir.path().join("inventory.bin");
+        std::fs::copy(&path, &renamed).unwrap();
+        let text = extract_text_from_attachment(renamed.to_str().unwrap(), None).unwrap();
+        assert_eq!(text.trim_end(), expected.trim_end());
+    }
+
+    #[test]
+    fn test_rows_to_markdown_table_caps_rows() {
+        let rows: Vec<Vec<String>> = (0..=MAX_TABLE_ROWS + 5)
+            .map(|i| vec![format!("r{}", i), "x".repeat(MAX_CELL_CHARS + 10)])
+            .collect();
+        let table = rows_to_markdown_table(rows);
+        assert!(table.ends_with("[5 more rows not shown]\n"));
+        assert_eq!(table.lines().filter(|l| l.starts_with("| r")).count(), MAX_TABLE_ROWS + 1);
+        assert!(table.contains('…'));
+    }
+
     #[test]
     fn test_extract_text_from_ooxml() {
         let xml = r#"<document><w:p><w:t>Hello</w:t><w:t> World</w:t></w:p></document>"#;
@@ -14,11 +14,25 @@ use crate::db::connection::DbPool;
 pub struct RecentNoteResult {
     pub note_id: String,
     pub title: String,
+    /// Start of the note's content, whitespace collapsed
     pub snippet: String,
+    /// When the note was last edited
     pub updated_at: String,
     pub folder_id: Option<String>,
 }
 
+/// Characters of snippet shared across all the notes in one result
+const SNIPPET_BUDGET: usize = 2000;
+/// Longest snippet for a single note, when only a few are returned
+const MAX_SNIPPET_LEN: usize = 200;
+/// Shortest snippet for a single note, when many are returned
+const MIN_SNIPPET_LEN: usize = 80;
+
+/// Snippet length for each of `count` notes, keeping the result within `SNIPPET_BUDGET`
+fn snippet_len(count: usize) -> usize {
+    (SNIPPET_BUDGET / count.max(1)).clamp(MIN_SNIPPET_LEN, MAX_SNIPPET_LEN)
+}
+
 // ============================================================================
 // get_recent_notes Tool
 // ============================================================================
@@ -89,7 +103,7 @@ pub fn execute_get_recent_notes(
     let mut stmt = conn.prepare(&query)
         .map_err(|e| format!("Query preparation failed: {}", e))?;
 
-    let notes: Vec<RecentNoteResult> = stmt
+    let mut notes: Vec<RecentNoteResult> = stmt
         .query_map([], |row| {
             let id: String = row.get(0)?;
             let title: String = row.get(1)?;
@@ -100,18 +114,21 @@ pub fn execute_get_recent_notes(
         })
         .map_err(|e| format!("Query failed: {}", e))?
         .filter_map(|r| r.ok())
-        .map(|(id, title, content, folder_id, updated_at)| {
-            let snippet = create_snippet(&content.unwrap_or_default(), 200);
-            RecentNoteResult {
-                note_id: id,
-                title,
-                snippet,
-                updated_at,
-                folder_id,
-            }
+        .map(|(id, title, content, folder_id, updated_at)| RecentNoteResult {
+            note_id: id,
+            title,
+            snippet: content.unwrap_or_default(),
+            updated_at,
+            folder_id,
         })
         .collect();
 
+    // Snippets shrink as more notes are returned
+    let max_len = snippet_len(notes.len());
+    for note in &mut notes {
+        note.snippet = create_snippet(&note.snippet, max_len);
+    }
+
     if notes.is_empty() {
         let message = if let Some(d) = days {
             format!("No notes modified in the last {} days", d)
@@ -133,13 +150,14 @@ pub fn execute_get_recent_notes(
     }
 }
 
-/// Create a snippet from content
+/// Create a snippet of at most `max_len` characters from content
 fn create_snippet(content: &str, max_len: usize) -> String {
-    if content.len() <= max_len {
-        return content.to_string();
-    }
+    let content = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    let Some((cut, _)) = content.char_indices().nth(max_len) else {
+        return content;
+    };
 
-    let truncated = &content[..max_len];
+    let truncated = &content[..cut];
     if let Some(pos) = truncated.rfind(|c: char| c.is_whitespace() || c == '.') {
         format!("{}...", &truncated[..pos].trim())
     } else {
@@ -166,6 +184,53 @@ mod tests {
         let long = "This is a long piece of content that should be truncated at a word boundary";
         let snippet = create_snippet(long, 40);
         assert!(snippet.ends_with("..."));
+
+        // Cuts on characters, not bytes
+        let accented = "é".repeat(50);
+        assert_eq!(create_snippet(&accented, 10), format!("{}...", "é".repeat(10)));
+
+        assert_eq!(create_snippet("# Title\n\n  Body   text", 100), "# Title Body text");
+    }
+
+    #[test]
+    fn test_recent_notes_include_snippet_and_timestamp() {
+        use crate::db::notes::create_note;
+        use crate::models::CreateNoteInput;
+
+        let pool = crate::db::connection::init_test_pool().unwrap();
+        {
+            let conn = pool.get().unwrap();
+            for i in 0..25 {
+                create_note(
+                    &conn,
+                    CreateNoteInput {
+                        title: format!("Note {}", i),
+                        content: Some(format!("Notes on topic {} ", i).repeat(40)),
+                        content_html: None,
+                        folder_id: None,
+                    },
+                )
+                .unwrap();
+            }
+        }
+
+        let result = execute_get_recent_notes(&pool, json!({ "limit": 2 })).unwrap();
+        let result: Value = serde_json::from_str(&result).unwrap();
+        let notes = result["notes"].as_array().unwrap();
+        assert_eq!(notes.len(), 2);
+        for note in notes {
+            assert!(note["snippet"].as_str().unwrap().starts_with("Notes on topic"));
+            assert!(note["snippet"].as_str().unwrap().chars().count() <= MAX_SNIPPET_LEN + 3);
+            assert!(!note["updatedAt"].as_str().unwrap().is_empty());
+        }
+
+        // Many notes share the snippet budget
+        let result = execute_get_recent_notes(&pool, json!({ "limit": 25 })).unwrap();
+        let result: Value = serde_json::from_str(&result).unwrap();
+        let notes = result["notes"].as_array().unwrap();
+        assert_eq!(notes.len(), 25);
+        let total: usize = notes.iter().map(|n| n["snippet"].as_str().unwrap().chars().count()).sum();
+        assert!(total <= SNIPPET_BUDGET + 25 * 3);
     }
 }
 
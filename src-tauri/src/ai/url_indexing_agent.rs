@@ -232,7 +232,7 @@ async fn generate_url_embedding(
     scraped: &ScrapedContent,
 ) -> Result<usize, String> {
     // Get embedding configuration
-    let (embedding_model, provider_url, api_key) = {
+    let (embedding_model, expected_dimension, provider_url, api_key) = {
         let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
         let config = load_ai_config(&conn)?;
         let embedding_provider = config
@@ -241,7 +241,12 @@ async fn generate_url_embedding(
             .find(|p| p.id == config.embedding.provider);
         let provider_url = embedding_provider.and_then(|p| p.base_url.clone());
         let api_key = embedding_provider.and_then(|p| p.api_key.clone());
-        (config.embedding.full_model_id(), provider_url, api_key)
+        (
+            config.embedding.full_model_id(),
+            config.embedding.expected_dimension(),
+            provider_url,
+            api_key,
+        )
     };
 
     // Build the base text including title and description
@@ -275,8 +280,14 @@ async fn generate_url_embedding(
         // Store as single embedding
         {
             let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
-            store_url_embedding(&conn, url_attachment_id, &result.embedding, &embedding_model)
-                .map_err(|e| format!("Failed to store embedding: {}", e))?;
+            store_url_embedding(
+                &conn,
+                url_attachment_id,
+                &result.embedding,
+                &embedding_model,
+                expected_dimension,
+            )
+            .map_err(|e| format!("Failed to store embedding: {}", e))?;
         }
 
         Ok(result.dimension)
@@ -320,6 +331,7 @@ async fn generate_url_embedding(
                 url_attachment_id,
                 &chunk_embeddings,
                 &embedding_model,
+                expected_dimension,
             )
             .map_err(|e| format!("Failed to store chunk embeddings: {}", e))?;
         }
@@ -0,0 +1,430 @@
+//! iCalendar (RFC 5545) import and export for calendar events.
+//!
+//! Import maps SUMMARY/DESCRIPTION/LOCATION/DTSTART/DTEND/RRULE of each VEVENT onto a
+//! `CreateCalendarEventInput`. Export writes timed events in their own timezone (TZID)
+//! when one is known, and in UTC otherwise.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use thiserror::Error;
+
+use crate::models::{CalendarEvent, CreateCalendarEventInput};
+
+const PRODID: &str = "-//Inkling//Inkling Calendar//EN";
+
+/// Maximum content line length in octets before folding (RFC 5545 section 3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum IcsError {
+    #[error("Not an iCalendar file: missing BEGIN:VCALENDAR")]
+    NotACalendar,
+    #[error("Event is missing DTSTART")]
+    MissingStart,
+    #[error("Invalid date/time value: {0}")]
+    InvalidDateTime(String),
+}
+
+/// Result of parsing an .ics file
+#[derive(Debug)]
+pub struct ParsedCalendar {
+    pub events: Vec<CreateCalendarEventInput>,
+    /// VEVENTs that could not be converted (e.g. missing or invalid DTSTART)
+    pub skipped: usize,
+}
+
+/// A content line split into name, parameters and value
+struct ContentLine {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl ContentLine {
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Parse the contents of an .ics file into event inputs
+pub fn parse_ics(content: &str) -> Result<ParsedCalendar, IcsError> {
+    let lines: Vec<ContentLine> = unfold_lines(content).iter().filter_map(|l| parse_content_line(l)).collect();
+
+    if !lines
+        .iter()
+        .any(|l| l.name == "BEGIN" && l.value.eq_ignore_ascii_case("VCALENDAR"))
+    {
+        return Err(IcsError::NotACalendar);
+    }
+
+    let mut events = Vec::new();
+    let mut skipped = 0;
+    let mut current: Option<Vec<&ContentLine>> = None;
+    // Depth of nested components (e.g. VALARM) inside the current VEVENT
+    let mut nested = 0;
+
+    for line in &lines {
+        match (line.name.as_str(), line.value.to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") if current.is_none() => current = Some(Vec::new()),
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", "VEVENT") if nested == 0 => {
+                if let Some(props) = current.take() {
+                    match event_from_properties(&props) {
+                        Ok(event) => events.push(event),
+                        Err(e) => {
+                            log::warn!("[ics] Skipping event: {}", e);
+                            skipped += 1;
+                        }
+                    }
+                }
+            }
+            ("END", _) if current.is_some() => nested -= 1,
+            _ => {
+                if nested == 0 {
+                    if let Some(props) = current.as_mut() {
+                        props.push(line);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(ParsedCalendar { events, skipped })
+}
+
+/// Serialize events into a VCALENDAR document
+pub fn events_to_ics(events: &[CalendarEvent]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    let dtstamp = format_utc(Utc::now());
+
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@inkling", event.id));
+        lines.push(format!("DTSTAMP:{}", dtstamp));
+        if event.all_day {
+            lines.push(format!("DTSTART;VALUE=DATE:{}", event.start_time.format("%Y%m%d")));
+            if let Some(end) = event.end_time {
+                lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+            }
+        } else {
+            let tz = event.timezone.as_deref().and_then(|name| name.parse::<Tz>().ok());
+            lines.push(format!("DTSTART{}", format_timed(event.start_time, tz)));
+            if let Some(end) = event.end_time {
+                lines.push(format!("DTEND{}", format_timed(end, tz)));
+            }
+        }
+        lines.push(format!("SUMMARY:{}", escape_text(&event.title)));
+        if let Some(description) = event.description.as_deref().filter(|d| !d.is_empty()) {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        if let Some(location) = event.location.as_deref().filter(|l| !l.is_empty()) {
+            lines.push(format!("LOCATION:{}", escape_text(location)));
+        }
+        if let Some(rule) = event.recurrence_rule.as_deref().filter(|r| !r.is_empty()) {
+            lines.push(format!("RRULE:{}", rule.strip_prefix("RRULE:").unwrap_or(rule)));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut output = String::new();
+    for line in lines {
+        output.push_str(&fold_line(&line));
+        output.push_str("\r\n");
+    }
+    output
+}
+
+fn event_from_properties(props: &[&ContentLine]) -> Result<CreateCalendarEventInput, IcsError> {
+    let find = |name: &str| props.iter().find(|p| p.name == name);
+
+    let dtstart = find("DTSTART").ok_or(IcsError::MissingStart)?;
+    let (start_time, all_day) = parse_date_value(dtstart)?;
+    let timezone = dtstart
+        .param("TZID")
+        .map(|tzid| tzid.trim_matches('"'))
+        .filter(|tzid| tzid.parse::<Tz>().is_ok())
+        .map(str::to_string);
+    let end_time = find("DTEND").map(|p| parse_date_value(p).map(|(dt, _)| dt)).transpose()?;
+
+    Ok(CreateCalendarEventInput {
+        title: find("SUMMARY")
+            .map(|p| unescape_text(&p.value))
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or_else(|| "(No title)".to_string()),
+        description: find("DESCRIPTION").map(|p| unescape_text(&p.value)),
+        start_time,
+        end_time,
+        all_day,
+        recurrence_rule: find("RRULE").map(|p| format!("RRULE:{}", p.value)),
+        linked_note_id: None,
+        event_type: None,
+        response_status: None,
+        attendees: None,
+        meeting_link: None,
+        location: find("LOCATION")
+            .map(|p| unescape_text(&p.value))
+            .filter(|l| !l.trim().is_empty()),
+        timezone,
+    })
+}
+
+/// Parse a DTSTART/DTEND property. Returns the time in UTC and whether it is a date-only value.
+///
+/// Date-only values are stored at midnight UTC, matching Google all-day events. Times with a
+/// TZID are converted from that zone; floating times are interpreted in the local timezone.
+fn parse_date_value(line: &ContentLine) -> Result<(DateTime<Utc>, bool), IcsError> {
+    let value = line.value.trim();
+    let invalid = || IcsError::InvalidDateTime(value.to_string());
+
+    if line.param("VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE")) || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| invalid())?;
+        let midnight = date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?;
+        return Ok((Utc.from_utc_datetime(&midnight), true));
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+        return Ok((Utc.from_utc_datetime(&naive), false));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+    let tz = line.param("TZID").and_then(|tzid| {
+        let tz = tzid.trim_matches('"').parse::<Tz>().ok();
+        if tz.is_none() {
+            log::warn!("[ics] Unknown TZID '{}', using local time", tzid);
+        }
+        tz
+    });
+    let utc = match tz {
+        Some(tz) => tz.from_local_datetime(&naive).earliest().map(|dt| dt.with_timezone(&Utc)),
+        None => Local.from_local_datetime(&naive).earliest().map(|dt| dt.with_timezone(&Utc)),
+    };
+    utc.map(|dt| (dt, false)).ok_or_else(invalid)
+}
+
+/// Join folded lines (continuations start with a space or tab)
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in content.lines() {
+        let raw = raw.trim_end_matches('\r');
+        if let Some(rest) = raw.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(rest);
+                continue;
+            }
+        }
+        if !raw.is_empty() {
+            lines.push(raw.to_string());
+        }
+    }
+    lines
+}
+
+/// Split `NAME;PARAM=value:VALUE`, honouring quoted parameter values
+fn parse_content_line(line: &str) -> Option<ContentLine> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.trim().to_ascii_uppercase(), v.trim().to_string()))
+        .collect();
+
+    Some(ContentLine {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+fn format_utc(dt: DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Format the parameters and value of a timed DTSTART/DTEND, e.g. `;TZID=Europe/Paris:20240101T090000`
+fn format_timed(dt: DateTime<Utc>, tz: Option<Tz>) -> String {
+    match tz.filter(|tz| *tz != Tz::UTC) {
+        Some(tz) => format!(
+            ";TZID={}:{}",
+            tz.name(),
+            dt.with_timezone(&tz).format("%Y%m%dT%H%M%S")
+        ),
+        None => format!(":{}", format_utc(dt)),
+    }
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Fold a content line at 75 octets without splitting UTF-8 characters
+fn fold_line(line: &str) -> String {
+    if line.len() <= MAX_LINE_OCTETS {
+        return line.to_string();
+    }
+
+    let mut folded = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+    let mut current_len = 0;
+    for c in line.chars() {
+        // Continuation lines start with a space, which counts towards the limit
+        if current_len + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            current_len = 1;
+        }
+        folded.push(c);
+        current_len += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::calendar_events::{create_event, get_all_events};
+    use crate::db::connection::init_test_pool;
+
+    fn input(title: &str, start: DateTime<Utc>, end: Option<DateTime<Utc>>, all_day: bool) -> CreateCalendarEventInput {
+        CreateCalendarEventInput {
+            title: title.to_string(),
+            description: None,
+            start_time: start,
+            end_time: end,
+            all_day,
+            recurrence_rule: None,
+            linked_note_id: None,
+            event_type: None,
+            response_status: None,
+            attendees: None,
+            meeting_link: None,
+            location: None,
+            timezone: None,
+        }
+    }
+
+    #[test]
+    fn test_round_trip_export_and_import() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let mut planning = input(
+            "Planning; Q3, roadmap",
+            Utc.with_ymd_and_hms(2024, 7, 1, 14, 30, 0).unwrap(),
+            Some(Utc.with_ymd_and_hms(2024, 7, 1, 15, 30, 0).unwrap()),
+            false,
+        );
+        planning.description = Some("Agenda:\n- goals\n- risks \\ blockers ".repeat(3));
+        planning.recurrence_rule = Some("RRULE:FREQ=WEEKLY;COUNT=4".to_string());
+        planning.timezone = Some("America/Chicago".to_string());
+        planning.location = Some("Room 4, Building B".to_string());
+        create_event(&conn, planning).unwrap();
+        create_event(
+            &conn,
+            input(
+                "Company holiday",
+                Utc.with_ymd_and_hms(2024, 7, 4, 0, 0, 0).unwrap(),
+                Some(Utc.with_ymd_and_hms(2024, 7, 5, 0, 0, 0).unwrap()),
+                true,
+            ),
+        )
+        .unwrap();
+
+        let exported = get_all_events(&conn).unwrap();
+        let ics = events_to_ics(&exported);
+        assert!(ics.lines().all(|l| l.len() <= MAX_LINE_OCTETS + 1));
+
+        let parsed = parse_ics(&ics).unwrap();
+        assert_eq!(parsed.skipped, 0);
+        assert_eq!(parsed.events.len(), exported.len());
+
+        let reimport_pool = init_test_pool().unwrap();
+        let reimport_conn = reimport_pool.get().unwrap();
+        for event in parsed.events {
+            create_event(&reimport_conn, event).unwrap();
+        }
+        let reimported = get_all_events(&reimport_conn).unwrap();
+
+        for (original, copy) in exported.iter().zip(reimported.iter()) {
+            assert_eq!(copy.title, original.title);
+            assert_eq!(copy.description, original.description);
+            assert_eq!(copy.start_time, original.start_time);
+            assert_eq!(copy.end_time, original.end_time);
+            assert_eq!(copy.all_day, original.all_day);
+            assert_eq!(copy.recurrence_rule, original.recurrence_rule);
+            assert_eq!(copy.location, original.location);
+            if !original.all_day {
+                assert_eq!(copy.timezone, original.timezone);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_timezones_and_all_day() {
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+            BEGIN:VEVENT\r\nSUMMARY:Standup\r\n\
+            DTSTART;TZID=America/New_York:20240115T090000\r\n\
+            DTEND;TZID=America/New_York:20240115T091500\r\n\
+            BEGIN:VALARM\r\nDESCRIPTION:Reminder\r\nEND:VALARM\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nSUMMARY:Off\r\n  site\r\nDTSTART;VALUE=DATE:20240120\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nSUMMARY:Broken\r\nEND:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let parsed = parse_ics(ics).unwrap();
+        assert_eq!(parsed.skipped, 1);
+        assert_eq!(parsed.events.len(), 2);
+
+        let standup = &parsed.events[0];
+        assert_eq!(standup.start_time, Utc.with_ymd_and_hms(2024, 1, 15, 14, 0, 0).unwrap());
+        assert_eq!(standup.end_time, Some(Utc.with_ymd_and_hms(2024, 1, 15, 14, 15, 0).unwrap()));
+        assert!(!standup.all_day);
+        assert_eq!(standup.timezone.as_deref(), Some("America/New_York"));
+        assert_eq!(standup.description, None);
+
+        let offsite = &parsed.events[1];
+        assert_eq!(offsite.title, "Off site");
+        assert!(offsite.all_day);
+        assert_eq!(offsite.start_time, Utc.with_ymd_and_hms(2024, 1, 20, 0, 0, 0).unwrap());
+
+        assert_eq!(parse_ics("hello").unwrap_err(), IcsError::NotACalendar);
+    }
+}
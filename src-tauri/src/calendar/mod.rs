@@ -0,0 +1,3 @@
+//! Calendar interchange formats
+
+pub mod ics;
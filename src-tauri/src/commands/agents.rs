@@ -11,8 +11,9 @@ use crate::ai::{
     extract_text_from_attachment, load_ai_config, run_deep_research_agent,
     run_inline_assistant_with_events, run_summarization_agent, AgentConfig,
     CancellationToken, DeepResearchConfig, DeepResearchResult, InlineAssistantResult,
-    SummarizationResult,
+    SummarizationResult, SummaryLength, SummaryStyle,
 };
+use crate::db::connection::get_connection;
 use crate::db;
 use crate::vault;
 use crate::AppPool;
@@ -29,7 +30,7 @@ pub struct AgentExecutions(pub std::sync::RwLock<HashMap<String, CancellationTok
 pub fn get_agent_config(pool: State<AppPool>) -> Result<AgentConfig, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     // Try to load from settings, or return default
     match db::settings::get_setting(&conn, "agent_config") {
@@ -46,7 +47,7 @@ pub fn get_agent_config(pool: State<AppPool>) -> Result<AgentConfig, String> {
 pub fn save_agent_config(pool: State<AppPool>, config: AgentConfig) -> Result<(), String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     let json_str = serde_json::to_string(&config).map_err(|e| e.to_string())?;
     db::settings::set_setting(&conn, "agent_config", &json_str).map_err(|e| e.to_string())?;
@@ -82,7 +83,7 @@ pub async fn execute_inline_agent(
 
     // Get agent config
     let config = {
-        let conn = db_pool.get().map_err(|e| e.to_string())?;
+        let conn = get_connection(&db_pool).map_err(|e| e.to_string())?;
         match db::settings::get_setting(&conn, "agent_config") {
             Ok(Some(json_str)) => serde_json::from_str(&json_str).unwrap_or_default(),
             _ => AgentConfig::default(),
@@ -97,7 +98,7 @@ pub async fn execute_inline_agent(
 
     // Get the model and provider to use
     let (model, provider) = {
-        let conn = db_pool.get().map_err(|e| e.to_string())?;
+        let conn = get_connection(&db_pool).map_err(|e| e.to_string())?;
         let ai_config = load_ai_config(&conn)?;
         get_model_and_provider(&ai_config)?
     };
@@ -174,6 +175,7 @@ pub fn cancel_agent_execution(
 /// - `agent-progress-{execution_id}` events for progress updates
 /// - `agent-content-{execution_id}` events for content to insert
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn execute_summarization_agent(
     app_handle: tauri::AppHandle,
     pool: State<'_, AppPool>,
@@ -182,7 +184,22 @@ pub async fn execute_summarization_agent(
     content: String,
     content_type: String, // "selection" or "attachment"
     attachment_path: Option<String>, // Path to attachment if content_type is "attachment"
+    length: Option<String>, // "short", "medium" (default) or "long"
+    style: Option<String>,  // "bullets" (default), "paragraph" or "abstract"
 ) -> Result<SummarizationResult, String> {
+    let length = match length.as_deref() {
+        Some(s) => SummaryLength::from_str(s).ok_or_else(|| {
+            format!("Invalid summary length '{}'. Expected short, medium or long.", s)
+        })?,
+        None => SummaryLength::default(),
+    };
+    let style = match style.as_deref() {
+        Some(s) => SummaryStyle::from_str(s).ok_or_else(|| {
+            format!("Invalid summary style '{}'. Expected bullets, paragraph or abstract.", s)
+        })?,
+        None => SummaryStyle::default(),
+    };
+
     log::info!(
         "[SummarizationAgent] Starting: execution_id={}, content_type={}, content_len={}",
         execution_id,
@@ -203,7 +220,7 @@ pub async fn execute_summarization_agent(
 
     // Get agent config
     let config = {
-        let conn = db_pool.get().map_err(|e| e.to_string())?;
+        let conn = get_connection(&db_pool).map_err(|e| e.to_string())?;
         match db::settings::get_setting(&conn, "agent_config") {
             Ok(Some(json_str)) => serde_json::from_str(&json_str).unwrap_or_default(),
             _ => AgentConfig::default(),
@@ -217,7 +234,7 @@ pub async fn execute_summarization_agent(
 
     // Get the model and provider
     let (model, provider) = {
-        let conn = db_pool.get().map_err(|e| e.to_string())?;
+        let conn = get_connection(&db_pool).map_err(|e| e.to_string())?;
         let ai_config = load_ai_config(&conn)?;
         get_model_and_provider(&ai_config)?
     };
@@ -263,6 +280,8 @@ pub async fn execute_summarization_agent(
         &actual_content,
         &content_type,
         &vault_path,
+        length,
+        style,
         Some(&cancellation_token),
     )
     .await;
@@ -329,7 +348,7 @@ pub async fn execute_deep_research_agent(
 
     // Get agent config
     let config: AgentConfig = {
-        let conn = db_pool.get().map_err(|e| e.to_string())?;
+        let conn = get_connection(&db_pool).map_err(|e| e.to_string())?;
         match db::settings::get_setting(&conn, "agent_config") {
             Ok(Some(json_str)) => serde_json::from_str(&json_str).unwrap_or_default(),
             _ => AgentConfig::default(),
@@ -343,7 +362,7 @@ pub async fn execute_deep_research_agent(
 
     // Get the model and provider
     let (model, provider) = {
-        let conn = db_pool.get().map_err(|e| e.to_string())?;
+        let conn = get_connection(&db_pool).map_err(|e| e.to_string())?;
         let ai_config = load_ai_config(&conn)?;
         get_model_and_provider(&ai_config)?
     };
@@ -438,7 +457,7 @@ pub fn extract_attachment_text(
 pub fn get_available_tools(pool: State<AppPool>) -> Result<Vec<ToolInfo>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     // Get agent config
     let config: AgentConfig = match db::settings::get_setting(&conn, "agent_config") {
@@ -1,10 +1,13 @@
 //! Tauri commands for AI operations
 
 use crate::ai::{
-    detect_lmstudio, detect_ollama_models, load_ai_config, save_ai_config, test_provider_connection,
-    AIConfig, AIProvider, ProviderTestResult, ProviderType,
+    apply_provider_models, check_model_availability, classify_model_error, create_client,
+    detect_lmstudio, detect_ollama_models, fetch_provider_models, load_ai_config, load_model_pricing,
+    save_ai_config, save_model_pricing, test_provider_connection, AIConfig, AIProvider, ModelPricing,
+    ModelStatus, ProviderTestResult, ProviderType,
 };
 use crate::AppPool;
+use crate::db::connection::get_connection;
 use tauri::State;
 
 // ============================================================================
@@ -16,7 +19,7 @@ use tauri::State;
 pub async fn get_ai_config(pool: State<'_, AppPool>) -> Result<AIConfig, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
     load_ai_config(&conn)
 }
 
@@ -28,7 +31,7 @@ pub async fn save_ai_config_cmd(
 ) -> Result<(), String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
     save_ai_config(&conn, &config)?;
     Ok(())
 }
@@ -41,7 +44,7 @@ pub async fn update_provider(
 ) -> Result<AIConfig, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
     let mut config = load_ai_config(&conn)?;
 
     // Find and update the provider
@@ -63,7 +66,7 @@ pub async fn set_default_provider(
 ) -> Result<AIConfig, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
     let mut config = load_ai_config(&conn)?;
 
     // Verify the provider exists
@@ -76,6 +79,56 @@ pub async fn set_default_provider(
     Ok(config)
 }
 
+/// Set the providers chat falls back to, in order, when the default one cannot be reached
+#[tauri::command]
+pub async fn set_fallback_providers(
+    pool: State<'_, AppPool>,
+    provider_ids: Vec<String>,
+) -> Result<AIConfig, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+    let mut config = load_ai_config(&conn)?;
+
+    if let Some(unknown) = provider_ids.iter().find(|id| !config.providers.iter().any(|p| &p.id == *id)) {
+        return Err(format!("Provider '{}' not found", unknown));
+    }
+
+    config.fallback_provider_ids = provider_ids;
+    save_ai_config(&conn, &config)?;
+    Ok(config)
+}
+
+/// Get the per-model prices used for cost estimates
+#[tauri::command]
+pub async fn get_model_pricing(pool: State<'_, AppPool>) -> Result<ModelPricing, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+    Ok(load_model_pricing(&conn))
+}
+
+/// Replace the per-model prices used for cost estimates
+#[tauri::command]
+pub async fn set_model_pricing(
+    pool: State<'_, AppPool>,
+    pricing: ModelPricing,
+) -> Result<ModelPricing, String> {
+    if let Some((model, _)) = pricing
+        .models
+        .iter()
+        .find(|(_, p)| !(p.input_per_million >= 0.0 && p.output_per_million >= 0.0))
+    {
+        return Err(format!("Prices for '{}' must be zero or more", model));
+    }
+
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+    save_model_pricing(&conn, &pricing)?;
+    Ok(pricing)
+}
+
 /// Apply AI configuration
 #[tauri::command]
 pub async fn apply_ai_config(
@@ -92,7 +145,7 @@ pub async fn apply_ai_config(
 pub async fn init_ai_config_cmd(pool: State<'_, AppPool>) -> Result<AIConfig, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
     crate::ai::init_ai_config(&conn)
 }
 
@@ -101,9 +154,27 @@ pub async fn init_ai_config_cmd(pool: State<'_, AppPool>) -> Result<AIConfig, St
 // ============================================================================
 
 /// Test a provider's API connection
+///
+/// With `check_model`, also sends a tiny completion to the provider's selected
+/// model so a misconfigured model name is reported instead of a false success.
 #[tauri::command]
-pub async fn test_provider(provider: AIProvider) -> ProviderTestResult {
-    test_provider_connection(&provider, "").await
+pub async fn test_provider(provider: AIProvider, check_model: Option<bool>) -> ProviderTestResult {
+    let mut result = test_provider_connection(&provider, "").await;
+    if !check_model.unwrap_or(false) || !result.success {
+        return result;
+    }
+    let Some(model) = provider.selected_model.clone() else {
+        return result;
+    };
+
+    let (status, message) = match create_client(&provider) {
+        Ok(client) => check_model_availability(client.as_ref(), &model).await,
+        Err(e) => (classify_model_error(&e), format!("Could not create client: {}", e)),
+    };
+    result.success = status == ModelStatus::Available;
+    result.message = message;
+    result.model_status = Some(status);
+    result
 }
 
 // ============================================================================
@@ -119,7 +190,7 @@ pub async fn detect_local_models(
     let (db_pool, mut config, ollama_url, lmstudio_url) = {
         let pool_guard = pool.0.read().unwrap();
         let pool = pool_guard.as_ref().ok_or("Database not initialized")?.clone();
-        let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+        let conn = get_connection(&pool).map_err(|e| format!("Database error: {}", e))?;
         let config = load_ai_config(&conn)?;
         
         let ollama_url = config.providers.iter()
@@ -163,7 +234,7 @@ pub async fn detect_local_models(
 
     // Save updated config (sync work after async)
     {
-        let conn = db_pool.get().map_err(|e| format!("Database error: {}", e))?;
+        let conn = get_connection(&db_pool).map_err(|e| format!("Database error: {}", e))?;
         save_ai_config(&conn, &config)?;
     }
     
@@ -184,6 +255,63 @@ pub async fn detect_lmstudio_cmd(base_url: Option<String>) -> ProviderTestResult
     detect_lmstudio(url).await
 }
 
+/// Refresh a provider's model list from its model-list endpoint
+///
+/// The new list is saved to the AI config. Providers without a model-list
+/// endpoint keep their configured models.
+#[tauri::command]
+pub async fn refresh_provider_models(
+    pool: State<'_, AppPool>,
+    provider_id: String,
+) -> Result<ProviderTestResult, String> {
+    let (db_pool, provider) = {
+        let pool_guard = pool.0.read().unwrap();
+        let pool = pool_guard.as_ref().ok_or("Database not initialized")?.clone();
+        let conn = get_connection(&pool).map_err(|e| format!("Database error: {}", e))?;
+        let provider = load_ai_config(&conn)?
+            .providers
+            .into_iter()
+            .find(|p| p.id == provider_id)
+            .ok_or_else(|| format!("Provider not found: {}", provider_id))?;
+        (pool, provider)
+    };
+
+    let models = match fetch_provider_models(&provider).await {
+        Ok(Some(models)) => models,
+        Ok(None) => {
+            return Ok(ProviderTestResult {
+                success: false,
+                message: format!("{} does not provide a model list", provider.name),
+                models: Some(provider.models),
+                model_status: None,
+            });
+        }
+        Err(message) => {
+            return Ok(ProviderTestResult {
+                success: false,
+                message,
+                models: None,
+                model_status: None,
+            });
+        }
+    };
+
+    log::info!("Refreshed {} models for provider {}", models.len(), provider_id);
+
+    // Reload so changes saved while the request was in flight aren't lost
+    let conn = get_connection(&db_pool).map_err(|e| format!("Database error: {}", e))?;
+    let mut config = load_ai_config(&conn)?;
+    apply_provider_models(&mut config, &provider_id, models.clone());
+    save_ai_config(&conn, &config)?;
+
+    Ok(ProviderTestResult {
+        success: true,
+        message: format!("Found {} models", models.len()),
+        models: Some(models),
+        model_status: None,
+    })
+}
+
 // ============================================================================
 // Provider Info
 // ============================================================================
@@ -234,6 +362,12 @@ pub fn get_provider_info(provider_type: ProviderType) -> ProviderInfo {
             default_base_url: Some("http://localhost:8000/v1".to_string()),
             description: "Run models locally with VLLM server".to_string(),
         },
+        ProviderType::Local => ProviderInfo {
+            name: "Offline".to_string(),
+            requires_api_key: false,
+            default_base_url: None,
+            description: "Embedding models that run inside Inkling, no server needed".to_string(),
+        },
         ProviderType::Custom => ProviderInfo {
             name: "Custom".to_string(),
             requires_api_key: true,
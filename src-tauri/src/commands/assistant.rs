@@ -8,15 +8,17 @@
 
 use chrono::{Datelike, Local, NaiveDate, Timelike};
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::watch;
 
 use crate::ai::{
     load_ai_config,
-    llm::{ChatMessage as LlmChatMessage, ChatRequest, LlmClient},
+    llm::{ChatMessage as LlmChatMessage, ChatRequest, LlmClient, StreamEvent},
     tools::{execute_web_search, AgentConfig, WebSearchConfig},
 };
+use crate::db::connection::get_connection;
 use crate::db;
-use crate::AppPool;
+use crate::{ActiveStreams, AppPool};
 
 // ============================================================================
 // Types
@@ -58,6 +60,28 @@ pub struct AssistantContentResponse {
     pub quote_author: String,
 }
 
+/// A part of the assistant content, generated by its own LLM call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AssistantSection {
+    Greeting,
+    DaySummary,
+    Quote,
+}
+
+/// Events emitted while streaming assistant content
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AssistantStreamEvent {
+    /// Text generated for one section
+    Chunk {
+        section: AssistantSection,
+        content: String,
+    },
+    /// All sections are done; carries the final (parsed, with fallbacks) content
+    Complete { content: AssistantContentResponse },
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -94,7 +118,7 @@ fn get_model_and_provider(
 
 /// Get agent config from database
 fn get_agent_config(pool: &crate::db::connection::DbPool) -> AgentConfig {
-    let conn = match pool.get() {
+    let conn = match get_connection(pool) {
         Ok(c) => c,
         Err(_) => return AgentConfig::default(),
     };
@@ -105,6 +129,58 @@ fn get_agent_config(pool: &crate::db::connection::DbPool) -> AgentConfig {
     }
 }
 
+/// How a section's text is fetched from the model
+enum Delivery<'a> {
+    /// A single non-streaming completion
+    Complete,
+    /// Streamed, with each delta emitted as a chunk event
+    Stream {
+        emit: &'a (dyn Fn(AssistantStreamEvent) + Send + Sync),
+        cancel_rx: Option<&'a watch::Receiver<bool>>,
+    },
+}
+
+impl Delivery<'_> {
+    /// Run `request` and return the generated text
+    ///
+    /// When streaming is cancelled, returns what was generated so far.
+    async fn request_text(
+        &self,
+        client: &dyn LlmClient,
+        request: ChatRequest,
+        section: AssistantSection,
+    ) -> Result<String, String> {
+        let (emit, cancel_rx) = match self {
+            Delivery::Complete => {
+                return client.chat(request).await.map(|r| r.content).map_err(|e| e.to_string())
+            }
+            Delivery::Stream { emit, cancel_rx } => (emit, cancel_rx),
+        };
+
+        let is_cancelled = || cancel_rx.map(|rx| *rx.borrow()).unwrap_or(false);
+        let mut rx = client.chat_stream(request).await.map_err(|e| e.to_string())?;
+        let mut content = String::new();
+
+        while let Some(event) = rx.recv().await {
+            if is_cancelled() {
+                log::info!("[Assistant] {:?} stream cancelled", section);
+                break;
+            }
+            match event {
+                StreamEvent::Content { delta } => {
+                    content.push_str(&delta);
+                    emit(AssistantStreamEvent::Chunk { section, content: delta });
+                }
+                StreamEvent::Error { message } => return Err(message),
+                StreamEvent::Done { .. } => break,
+                _ => {}
+            }
+        }
+
+        Ok(content)
+    }
+}
+
 /// Build a greeting based on time of day
 fn get_time_based_greeting() -> &'static str {
     let hour = Local::now().hour();
@@ -222,6 +298,7 @@ async fn generate_greeting_text(
     client: &dyn LlmClient,
     model: &str,
     date_str: &str,
+    delivery: &Delivery<'_>,
 ) -> Result<String, String> {
     let human_date = format_human_readable_date(date_str);
     let time_of_day = get_time_of_day_description();
@@ -266,14 +343,13 @@ Write ONLY the greeting, nothing else:"#,
         enable_reasoning: false,
         reasoning_effort: None,
         thinking_budget: None,
+        use_prompt_cache: false,
     };
 
-    let response = client
-        .chat(request)
+    let raw_content = delivery
+        .request_text(client, request, AssistantSection::Greeting)
         .await
         .map_err(|e| format!("Greeting generation failed: {}", e))?;
-
-    let raw_content = &response.content;
     let content = raw_content.trim().to_string();
     log::info!("[Assistant] Raw greeting response (len={}): {:?}", raw_content.len(), raw_content.chars().take(200).collect::<String>());
     
@@ -291,6 +367,7 @@ async fn generate_day_summary_text(
     model: &str,
     date_str: &str,
     events: &[CalendarEventSummary],
+    delivery: &Delivery<'_>,
 ) -> Result<String, String> {
     let human_date = format_human_readable_date(date_str);
     let events_text = format_events_list(events);
@@ -351,14 +428,13 @@ Write ONLY the summary, nothing else:"#,
         enable_reasoning: false,
         reasoning_effort: None,
         thinking_budget: None,
+        use_prompt_cache: false,
     };
 
-    let response = client
-        .chat(request)
+    let raw_content = delivery
+        .request_text(client, request, AssistantSection::DaySummary)
         .await
         .map_err(|e| format!("Day summary generation failed: {}", e))?;
-
-    let raw_content = &response.content;
     let content = raw_content.trim().to_string();
     log::info!("[Assistant] Raw summary response (len={}): {:?}", raw_content.len(), raw_content.chars().take(200).collect::<String>());
     
@@ -375,6 +451,7 @@ async fn generate_quote_text(
     client: &dyn LlmClient,
     model: &str,
     web_quote: Option<&str>,
+    delivery: &Delivery<'_>,
 ) -> Result<(String, String), String> {
     let system_prompt = r#"You are a helpful assistant that provides inspiring, motivational quotes. You know many famous quotes from philosophers, authors, leaders, and thinkers throughout history.
 
@@ -420,14 +497,13 @@ Examples:
         enable_reasoning: false,
         reasoning_effort: None,
         thinking_budget: None,
+        use_prompt_cache: false,
     };
 
-    let response = client
-        .chat(request)
+    let raw_content = delivery
+        .request_text(client, request, AssistantSection::Quote)
         .await
         .map_err(|e| format!("Quote generation failed: {}", e))?;
-
-    let raw_content = &response.content;
     let content = raw_content.trim().to_string();
     log::info!("[Assistant] Raw quote response (len={}): {:?}", raw_content.len(), raw_content.chars().take(200).collect::<String>());
     
@@ -450,15 +526,10 @@ Examples:
 // Commands
 // ============================================================================
 
-/// Generate personalized assistant content including day summary and quote
-/// Uses multiple parallel plain-text LLM calls for better compatibility with smaller models
-#[tauri::command]
-pub async fn generate_assistant_content(
-    pool: State<'_, AppPool>,
-    input: AssistantContentInput,
-) -> Result<AssistantContentResponse, String> {
-    log::info!("[Assistant] Generating content for date: {}", input.date);
-    
+/// Load the model, provider, and (if web search is configured) a quote to build on
+async fn prepare_generation(
+    pool: &State<'_, AppPool>,
+) -> Result<(String, crate::ai::AIProvider, Option<String>), String> {
     // Get database pool
     let db_pool = {
         let pool_guard = pool.0.read().unwrap();
@@ -467,7 +538,7 @@ pub async fn generate_assistant_content(
     
     // Get AI config
     let (model, provider) = {
-        let conn = db_pool.get().map_err(|e| e.to_string())?;
+        let conn = get_connection(&db_pool).map_err(|e| e.to_string())?;
         let ai_config = load_ai_config(&conn)?;
         get_model_and_provider(&ai_config)?
     };
@@ -502,11 +573,7 @@ pub async fn generate_assistant_content(
         log::info!("[Assistant] Web search not configured, AI will generate quote");
         None
     };
-    
-    // Create LLM client
-    let llm_client = crate::ai::llm::create_client(&provider)
-        .map_err(|e| format!("Failed to create LLM client: {}", e))?;
-    
+
     // Log the model and provider details for debugging
     log::info!(
         "[Assistant] Using provider: {} (type: {:?}), model: {}, base_url: {:?}",
@@ -516,13 +583,24 @@ pub async fn generate_assistant_content(
         provider.base_url
     );
 
+    Ok((model, provider, web_quote))
+}
+
+/// Generate all three sections in parallel, using fallbacks for any that fail
+async fn generate_content(
+    client: &dyn LlmClient,
+    model: &str,
+    input: &AssistantContentInput,
+    web_quote: Option<&str>,
+    delivery: &Delivery<'_>,
+) -> AssistantContentResponse {
     // Run all three content generation calls in parallel for better performance
     log::info!("[Assistant] Running parallel content generation...");
 
     let (greeting_result, summary_result, quote_result) = tokio::join!(
-        generate_greeting_text(llm_client.as_ref(), &model, &input.date),
-        generate_day_summary_text(llm_client.as_ref(), &model, &input.date, &input.events),
-        generate_quote_text(llm_client.as_ref(), &model, web_quote.as_deref())
+        generate_greeting_text(client, model, &input.date, delivery),
+        generate_day_summary_text(client, model, &input.date, &input.events, delivery),
+        generate_quote_text(client, model, web_quote, delivery)
     );
 
     // Extract results with fallbacks
@@ -572,12 +650,72 @@ pub async fn generate_assistant_content(
 
     log::info!("[Assistant] Content generation complete");
     
-    Ok(AssistantContentResponse {
+    AssistantContentResponse {
         greeting,
         day_summary,
         quote,
         quote_author,
-    })
+    }
+}
+
+/// Generate personalized assistant content including day summary and quote
+/// Uses multiple parallel plain-text LLM calls for better compatibility with smaller models
+#[tauri::command]
+pub async fn generate_assistant_content(
+    pool: State<'_, AppPool>,
+    input: AssistantContentInput,
+) -> Result<AssistantContentResponse, String> {
+    log::info!("[Assistant] Generating content for date: {}", input.date);
+
+    let (model, provider, web_quote) = prepare_generation(&pool).await?;
+    let llm_client = crate::ai::llm::create_client(&provider)
+        .map_err(|e| format!("Failed to create LLM client: {}", e))?;
+
+    Ok(generate_content(llm_client.as_ref(), &model, &input, web_quote.as_deref(), &Delivery::Complete).await)
+}
+
+/// Generate assistant content, streaming each section as it's written
+///
+/// Emits `AssistantStreamEvent`s on `assistant-stream-{session_id}`, ending with
+/// `complete`. `stop_generation` with the same session ID stops the streams;
+/// sections cut off before producing any text get their fallback.
+#[tauri::command]
+pub async fn stream_assistant_content(
+    app: AppHandle,
+    pool: State<'_, AppPool>,
+    active_streams: State<'_, ActiveStreams>,
+    session_id: String,
+    input: AssistantContentInput,
+) -> Result<AssistantContentResponse, String> {
+    log::info!("[Assistant] Streaming content for date: {}", input.date);
+
+    let (model, provider, web_quote) = prepare_generation(&pool).await?;
+    let llm_client = crate::ai::llm::create_client(&provider)
+        .map_err(|e| format!("Failed to create LLM client: {}", e))?;
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    {
+        let mut streams = active_streams.0.write().map_err(|e| format!("Lock error: {}", e))?;
+        streams.insert(session_id.clone(), cancel_tx);
+    }
+
+    let event_name = format!("assistant-stream-{}", session_id);
+    let emit = |event: AssistantStreamEvent| {
+        let _ = app.emit(&event_name, event);
+    };
+    let delivery = Delivery::Stream {
+        emit: &emit,
+        cancel_rx: Some(&cancel_rx),
+    };
+    let content = generate_content(llm_client.as_ref(), &model, &input, web_quote.as_deref(), &delivery).await;
+
+    {
+        let mut streams = active_streams.0.write().map_err(|e| format!("Lock error: {}", e))?;
+        streams.remove(&session_id);
+    }
+
+    emit(AssistantStreamEvent::Complete { content: content.clone() });
+    Ok(content)
 }
 
 /// Fetch a daily quote using web search
@@ -648,3 +786,100 @@ pub async fn get_assistant_fallback(
     })
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::llm::{ChatResponse, LlmError};
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use tokio::sync::mpsc;
+
+    /// Streams a canned answer in pieces, picked by which section the prompt asks for
+    struct MockSectionClient;
+
+    #[async_trait]
+    impl LlmClient for MockSectionClient {
+        async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, LlmError> {
+            Err(LlmError::NotConfigured("non-streaming not supported by mock".to_string()))
+        }
+
+        async fn chat_stream(&self, request: ChatRequest) -> Result<mpsc::Receiver<StreamEvent>, LlmError> {
+            let system = request.messages[0].content.clone().unwrap_or_default();
+            let deltas: &[&str] = if system.contains("quotes") {
+                &["\"Stay ", "curious.\"", " - Ada Lovelace"]
+            } else if system.contains("summaries") {
+                &["Standup at 9, ", "then a quiet afternoon."]
+            } else {
+                &["Good morning! ", "Let's make it a good one."]
+            };
+
+            let (tx, rx) = mpsc::channel(16);
+            for delta in deltas {
+                tx.send(StreamEvent::Content { delta: delta.to_string() }).await.unwrap();
+            }
+            tx.send(StreamEvent::Done { finish_reason: "stop".to_string() }).await.unwrap();
+            Ok(rx)
+        }
+    }
+
+    fn input() -> AssistantContentInput {
+        AssistantContentInput {
+            date: "2025-01-06".to_string(),
+            events: vec![CalendarEventSummary {
+                title: "Standup".to_string(),
+                start_time: "09:00".to_string(),
+                end_time: Some("09:15".to_string()),
+                all_day: false,
+                event_type: None,
+                meeting_link: None,
+            }],
+        }
+    }
+
+    /// Concatenate the streamed chunks for one section
+    fn streamed_text(events: &[AssistantStreamEvent], wanted: AssistantSection) -> String {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                AssistantStreamEvent::Chunk { section, content } if *section == wanted => Some(content.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_streamed_sections_match_final_content() {
+        let events = Mutex::new(Vec::new());
+        let emit = |event: AssistantStreamEvent| events.lock().unwrap().push(event);
+        let delivery = Delivery::Stream { emit: &emit, cancel_rx: None };
+
+        let content = generate_content(&MockSectionClient, "mock-model", &input(), None, &delivery).await;
+
+        let events = events.lock().unwrap();
+        assert_eq!(streamed_text(&events, AssistantSection::Greeting), content.greeting);
+        assert_eq!(streamed_text(&events, AssistantSection::DaySummary), content.day_summary);
+        assert_eq!(
+            streamed_text(&events, AssistantSection::Quote),
+            "\"Stay curious.\" - Ada Lovelace"
+        );
+        assert_eq!(content.greeting, "Good morning! Let's make it a good one.");
+        assert_eq!(content.quote, "Stay curious.");
+        assert_eq!(content.quote_author, "Ada Lovelace");
+        // Each section arrives in several deltas, not one final block
+        assert_eq!(events.len(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_stream_falls_back() {
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        cancel_tx.send(true).unwrap();
+        let emit = |_event: AssistantStreamEvent| panic!("nothing should be emitted after cancellation");
+        let delivery = Delivery::Stream { emit: &emit, cancel_rx: Some(&cancel_rx) };
+
+        let content = generate_content(&MockSectionClient, "mock-model", &input(), None, &delivery).await;
+
+        assert_eq!(content.day_summary, "You have 1 event scheduled today.");
+        assert_eq!(content.quote_author, "Steve Jobs");
+    }
+}
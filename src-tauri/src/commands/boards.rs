@@ -1,13 +1,17 @@
 //! Tauri commands for Kanban board operations
 
+use chrono::{DateTime, Utc};
 use tauri::State;
 
 use crate::db::boards as db;
+use crate::db::connection::get_connection;
 use crate::models::{
-    AddCardInput, Board, BoardCard, BoardCardWithNote, BoardLane, BoardWithDetails,
-    CreateBoardInput, CreateLaneInput, MoveCardInput, UpdateBoardInput, UpdateLaneInput,
+    AddCardInput, Board, BoardCard, BoardCardWithNote, BoardLane, BoardTemplate, BoardWithDetails,
+    CardPlacement, CardSortOrder, CreateBoardInput, CreateLaneInput, MoveCardInput, Note,
+    UpdateBoardInput, UpdateLaneInput,
 };
-use crate::AppPool;
+use crate::vault::sync as vault_sync;
+use crate::{AppPool, AppSearchIndex};
 
 // ============================================================================
 // Board Commands
@@ -22,7 +26,7 @@ pub fn create_board(
 ) -> Result<BoardWithDetails, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     let input = CreateBoardInput { folder_id, name };
     let board = db::create_board(&conn, input).map_err(|e| e.to_string())?;
@@ -33,12 +37,32 @@ pub fn create_board(
         .ok_or_else(|| "Failed to get created board".to_string())
 }
 
+/// Create a new board for a folder with a template's lanes
+#[tauri::command]
+pub fn create_board_from_template(
+    pool: State<AppPool>,
+    folder_id: String,
+    name: String,
+    template: BoardTemplate,
+) -> Result<BoardWithDetails, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+
+    let input = CreateBoardInput { folder_id, name };
+    let board = db::create_board_from_template(&conn, input, &template).map_err(|e| e.to_string())?;
+
+    db::get_board_with_details(&conn, &board.id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Failed to get created board".to_string())
+}
+
 /// Get a board by ID
 #[tauri::command]
 pub fn get_board(pool: State<AppPool>, id: String) -> Result<Option<Board>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_board(&conn, &id).map_err(|e| e.to_string())
 }
 
@@ -47,7 +71,7 @@ pub fn get_board(pool: State<AppPool>, id: String) -> Result<Option<Board>, Stri
 pub fn get_board_by_folder(pool: State<AppPool>, folder_id: String) -> Result<Option<Board>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_board_by_folder(&conn, &folder_id).map_err(|e| e.to_string())
 }
 
@@ -56,7 +80,7 @@ pub fn get_board_by_folder(pool: State<AppPool>, folder_id: String) -> Result<Op
 pub fn get_all_boards(pool: State<AppPool>) -> Result<Vec<Board>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_all_boards(&conn).map_err(|e| e.to_string())
 }
 
@@ -68,7 +92,7 @@ pub fn get_board_with_details(
 ) -> Result<Option<BoardWithDetails>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_board_with_details(&conn, &id).map_err(|e| e.to_string())
 }
 
@@ -78,12 +102,16 @@ pub fn update_board(
     pool: State<AppPool>,
     id: String,
     name: Option<String>,
+    enforce_wip_limits: Option<bool>,
 ) -> Result<Board, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
-    let input = UpdateBoardInput { name };
+    let input = UpdateBoardInput {
+        name,
+        enforce_wip_limits,
+    };
     db::update_board(&conn, &id, input).map_err(|e| e.to_string())
 }
 
@@ -92,7 +120,7 @@ pub fn update_board(
 pub fn delete_board(pool: State<AppPool>, id: String) -> Result<bool, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::delete_board(&conn, &id).map_err(|e| e.to_string())
 }
 
@@ -110,7 +138,7 @@ pub fn create_lane(
 ) -> Result<BoardLane, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     let input = CreateLaneInput {
         board_id,
@@ -125,7 +153,7 @@ pub fn create_lane(
 pub fn get_lanes_for_board(pool: State<AppPool>, board_id: String) -> Result<Vec<BoardLane>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_lanes_for_board(&conn, &board_id).map_err(|e| e.to_string())
 }
 
@@ -139,18 +167,31 @@ pub fn update_lane(
 ) -> Result<BoardLane, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     let input = UpdateLaneInput { name, color };
     db::update_lane(&conn, &id, input).map_err(|e| e.to_string())
 }
 
+/// Set or clear a lane's WIP limit
+#[tauri::command]
+pub fn set_lane_wip_limit(
+    pool: State<AppPool>,
+    id: String,
+    wip_limit: Option<u32>,
+) -> Result<BoardLane, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+    db::set_lane_wip_limit(&conn, &id, wip_limit).map_err(|e| e.to_string())
+}
+
 /// Delete a lane
 #[tauri::command]
 pub fn delete_lane(pool: State<AppPool>, id: String) -> Result<bool, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::delete_lane(&conn, &id).map_err(|e| e.to_string())
 }
 
@@ -163,7 +204,7 @@ pub fn reorder_lanes(
 ) -> Result<(), String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::reorder_lanes(&conn, &board_id, &lane_ids).map_err(|e| e.to_string())
 }
 
@@ -178,10 +219,11 @@ pub fn add_card(
     board_id: String,
     lane_id: String,
     note_id: String,
+    due_date: Option<DateTime<Utc>>,
 ) -> Result<BoardCard, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     // Check if note is already on this board
     if db::is_note_on_board(&conn, &note_id, &board_id).map_err(|e| e.to_string())? {
@@ -192,6 +234,7 @@ pub fn add_card(
         board_id,
         lane_id,
         note_id,
+        due_date,
     };
     db::add_card(&conn, input).map_err(|e| e.to_string())
 }
@@ -204,7 +247,7 @@ pub fn get_cards_for_board(
 ) -> Result<Vec<BoardCardWithNote>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_cards_for_board(&conn, &board_id).map_err(|e| e.to_string())
 }
 
@@ -216,10 +259,23 @@ pub fn get_cards_in_lane(
 ) -> Result<Vec<BoardCardWithNote>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_cards_in_lane(&conn, &lane_id).map_err(|e| e.to_string())
 }
 
+/// Get cards in a specific lane, sorted manually, by due date, or by title
+#[tauri::command]
+pub fn get_cards_in_lane_sorted(
+    pool: State<AppPool>,
+    lane_id: String,
+    by: CardSortOrder,
+) -> Result<Vec<BoardCardWithNote>, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+    db::get_cards_in_lane_sorted(&conn, &lane_id, by).map_err(|e| e.to_string())
+}
+
 /// Move a card to a different lane and/or position
 #[tauri::command]
 pub fn move_card(
@@ -227,25 +283,40 @@ pub fn move_card(
     card_id: String,
     target_lane_id: String,
     target_position: i32,
+    due_date: Option<DateTime<Utc>>,
 ) -> Result<BoardCard, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     let input = MoveCardInput {
         card_id,
         target_lane_id,
         target_position,
+        due_date,
     };
     db::move_card(&conn, input).map_err(|e| e.to_string())
 }
 
+/// Set or clear a card's due date
+#[tauri::command]
+pub fn set_card_due_date(
+    pool: State<AppPool>,
+    card_id: String,
+    due_date: Option<DateTime<Utc>>,
+) -> Result<BoardCard, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+    db::set_card_due_date(&conn, &card_id, due_date).map_err(|e| e.to_string())
+}
+
 /// Remove a card from a board
 #[tauri::command]
 pub fn remove_card(pool: State<AppPool>, id: String) -> Result<bool, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::remove_card(&conn, &id).map_err(|e| e.to_string())
 }
 
@@ -254,6 +325,47 @@ pub fn remove_card(pool: State<AppPool>, id: String) -> Result<bool, String> {
 pub fn get_boards_for_note(pool: State<AppPool>, note_id: String) -> Result<Vec<Board>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_boards_for_note(&conn, &note_id).map_err(|e| e.to_string())
 }
+
+/// Get every card a note appears as, with its board and lane
+#[tauri::command]
+pub fn get_card_placements_for_note(
+    pool: State<AppPool>,
+    note_id: String,
+) -> Result<Vec<CardPlacement>, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+    db::get_card_placements_for_note(&conn, &note_id).map_err(|e| e.to_string())
+}
+
+/// Snapshot a board into a new note with a checklist per lane
+#[tauri::command]
+pub fn export_board_to_note(
+    pool: State<AppPool>,
+    search_index: State<AppSearchIndex>,
+    board_id: String,
+) -> Result<Note, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
+
+    let note = db::export_board_to_note(&conn, &board_id).map_err(|e| e.to_string())?;
+
+    // Add to search index
+    let search_guard = search_index.0.read().unwrap();
+    if let Some(ref index) = *search_guard {
+        if let Err(e) = index.add_note(&note.id, &note.title, note.content.as_deref()) {
+            log::warn!("Failed to add board snapshot to search index: {}", e);
+        }
+    }
+
+    // Sync to filesystem
+    if let Err(e) = vault_sync::sync_note_to_file(pool_ref, &note.id) {
+        log::warn!("Failed to sync board snapshot to filesystem: {}", e);
+    }
+
+    Ok(note)
+}
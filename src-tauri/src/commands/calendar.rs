@@ -1,11 +1,15 @@
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use tauri::State;
 
-use crate::db::calendar_events;
+use crate::calendar::ics;
+use crate::db::{self, calendar_events, connection::{get_connection, DbPool}};
 use crate::models::{
-    CalendarEvent, CalendarEventWithNote, CreateCalendarEventInput, UpdateCalendarEventInput,
+    CalendarEvent, CalendarEventSource, CalendarEventWithNote, CreateCalendarEventInput, CreateNoteInput,
+    IcsImportResult, Note, UpdateCalendarEventInput,
 };
-use crate::AppPool;
+use crate::vault::sync as vault_sync;
+use crate::{AppPool, AppSearchIndex};
 
 /// Create a new calendar event
 #[tauri::command]
@@ -15,7 +19,7 @@ pub async fn create_calendar_event(
 ) -> Result<CalendarEvent, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     calendar_events::create_event(&conn, input).map_err(|e| e.to_string())
 }
@@ -28,7 +32,7 @@ pub async fn get_calendar_event(
 ) -> Result<Option<CalendarEvent>, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     calendar_events::get_event(&conn, &id).map_err(|e| e.to_string())
 }
@@ -41,7 +45,7 @@ pub async fn get_calendar_event_with_note(
 ) -> Result<Option<CalendarEventWithNote>, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     calendar_events::get_event_with_note(&conn, &id).map_err(|e| e.to_string())
 }
@@ -53,36 +57,73 @@ pub async fn get_all_calendar_events(
 ) -> Result<Vec<CalendarEvent>, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     calendar_events::get_all_events(&conn).map_err(|e| e.to_string())
 }
 
-/// Get calendar events within a date range
+/// Get calendar events within a date range.
+/// `timezone` is the viewer's IANA timezone; results carry start/end times converted to it.
 #[tauri::command]
 pub async fn get_calendar_events_in_range(
     pool: State<'_, AppPool>,
     start: DateTime<Utc>,
     end: DateTime<Utc>,
+    timezone: Option<String>,
 ) -> Result<Vec<CalendarEventWithNote>, String> {
+    let tz = timezone
+        .as_deref()
+        .map(calendar_events::parse_timezone)
+        .transpose()
+        .map_err(|e| e.to_string())?;
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
-    calendar_events::get_events_in_range(&conn, start, end).map_err(|e| e.to_string())
+    calendar_events::get_events_in_range(&conn, start, end, tz.as_ref()).map_err(|e| e.to_string())
 }
 
-/// Get calendar events for a specific date
+/// Get calendar events for a specific date.
+/// `timezone` is the viewer's IANA timezone; the day is taken in that timezone (UTC if omitted).
 #[tauri::command]
 pub async fn get_calendar_events_for_date(
     pool: State<'_, AppPool>,
     date: String, // YYYY-MM-DD format
+    timezone: Option<String>,
 ) -> Result<Vec<CalendarEventWithNote>, String> {
+    let tz = timezone
+        .as_deref()
+        .map(calendar_events::parse_timezone)
+        .transpose()
+        .map_err(|e| e.to_string())?;
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
-    calendar_events::get_events_for_date(&conn, &date).map_err(|e| e.to_string())
+    calendar_events::get_events_for_date(&conn, &date, tz.as_ref()).map_err(|e| e.to_string())
+}
+
+/// Find existing events that overlap a proposed start/end time
+#[tauri::command]
+pub async fn check_event_conflicts(
+    pool: State<'_, AppPool>,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+    all_day: Option<bool>,
+    exclude_event_id: Option<String>,
+) -> Result<Vec<CalendarEventWithNote>, String> {
+    let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+
+    calendar_events::find_conflicting_events(
+        &conn,
+        start,
+        end,
+        all_day.unwrap_or(false),
+        exclude_event_id.as_deref(),
+    )
+    .map_err(|e| e.to_string())
 }
 
 /// Update a calendar event
@@ -94,7 +135,7 @@ pub async fn update_calendar_event(
 ) -> Result<CalendarEvent, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     calendar_events::update_event(&conn, &id, input).map_err(|e| e.to_string())
 }
@@ -107,7 +148,7 @@ pub async fn delete_calendar_event(
 ) -> Result<bool, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     calendar_events::delete_event(&conn, &id).map_err(|e| e.to_string())
 }
@@ -121,7 +162,7 @@ pub async fn link_note_to_calendar_event(
 ) -> Result<CalendarEvent, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     calendar_events::link_note_to_event(&conn, &event_id, &note_id).map_err(|e| e.to_string())
 }
@@ -134,8 +175,255 @@ pub async fn unlink_note_from_calendar_event(
 ) -> Result<CalendarEvent, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     calendar_events::unlink_note_from_event(&conn, &event_id).map_err(|e| e.to_string())
 }
 
+/// Create a meeting notes note for a calendar event and link it to the event
+///
+/// The note is pre-filled with the event's time, attendees, meeting link and
+/// agenda. If the event already has a linked note, that note is returned instead.
+#[tauri::command]
+pub async fn create_meeting_note(
+    pool: State<'_, AppPool>,
+    search_index: State<'_, AppSearchIndex>,
+    event_id: String,
+) -> Result<Note, String> {
+    let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+
+    let tz = calendar_events::parse_timezone(&calendar_events::local_timezone_name()).unwrap_or(Tz::UTC);
+    let note = create_meeting_note_for_event(pool, &event_id, &tz)?;
+
+    let search_guard = search_index.0.read().map_err(|e| e.to_string())?;
+    if let Some(ref index) = *search_guard {
+        // The note may already exist and be indexed, so replace rather than add
+        if let Err(e) = index.update_note(&note.id, &note.title, note.content.as_deref()) {
+            log::warn!("Failed to add meeting note to search index: {}", e);
+        }
+    }
+
+    Ok(note)
+}
+
+/// Create (or find) the linked meeting note for `event_id`, with times shown in `tz`
+pub fn create_meeting_note_for_event(pool: &DbPool, event_id: &str, tz: &Tz) -> Result<Note, String> {
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+    let event = calendar_events::get_event(&conn, event_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Event not found: {}", event_id))?;
+
+    if let Some(ref note_id) = event.linked_note_id {
+        if let Some(note) = db::notes::get_note(&conn, note_id).map_err(|e| e.to_string())? {
+            if !note.is_deleted {
+                return Ok(note);
+            }
+        }
+    }
+
+    let note = db::notes::create_note(
+        &conn,
+        CreateNoteInput {
+            title: event.title.clone(),
+            content: Some(meeting_note_content(&event, tz)),
+            content_html: None,
+            folder_id: None,
+        },
+    )
+    .map_err(|e| format!("Failed to create note: {}", e))?;
+    calendar_events::link_note_to_event(&conn, &event.id, &note.id).map_err(|e| e.to_string())?;
+    drop(conn);
+
+    if let Err(e) = vault_sync::sync_note_to_file(pool, &note.id) {
+        log::warn!("Failed to sync note to filesystem: {}", e);
+    }
+
+    Ok(note)
+}
+
+/// Markdown for a new meeting note, matching the calendar view's "create meeting note"
+fn meeting_note_content(event: &CalendarEvent, tz: &Tz) -> String {
+    // Google events carry attendees and the meeting link in their description
+    let parsed = (event.source == CalendarEventSource::Google)
+        .then(|| crate::google::calendar::parse_meeting_info(event.description.as_deref()));
+
+    let mut attendees: Vec<String> = event
+        .attendees
+        .iter()
+        .flatten()
+        .map(|a| a.name.clone().filter(|n| !n.is_empty()).unwrap_or_else(|| a.email.clone()))
+        .collect();
+    if attendees.is_empty() {
+        if let Some(ref info) = parsed {
+            attendees = info.attendees.clone();
+        }
+    }
+    let meeting_link = event
+        .meeting_link
+        .clone()
+        .or_else(|| parsed.as_ref().and_then(|info| info.meeting_link.clone()));
+    let agenda = match parsed {
+        Some(info) => info.original_description,
+        None => event.description.clone(),
+    }
+    .filter(|a| !a.trim().is_empty());
+
+    let mut content = format!("# {}\n\n", event.title);
+
+    // All-day events are stored at midnight UTC on their calendar date
+    let date = if event.all_day {
+        event.start_time.date_naive().format("%A, %B %-d, %Y").to_string()
+    } else {
+        let start = event.start_time.with_timezone(tz);
+        let mut date = start.format("%A, %B %-d, %Y at %-I:%M %p").to_string();
+        if let Some(end) = event.end_time {
+            date.push_str(&end.with_timezone(tz).format(" - %-I:%M %p").to_string());
+        }
+        date
+    };
+    content.push_str(&format!("**Date:** {}\n", date));
+    if !attendees.is_empty() {
+        content.push_str(&format!("**Attendees:** {}\n", attendees.join(", ")));
+    }
+    if let Some(link) = meeting_link {
+        content.push_str(&format!("**Meeting Link:** {}\n", link));
+    }
+    content.push('\n');
+
+    if let Some(agenda) = agenda {
+        content.push_str(&format!("## Agenda\n{}\n\n", agenda.trim()));
+    }
+    content.push_str("## Notes\n\n\n");
+    content.push_str("## Action Items\n- [ ] \n");
+    content
+}
+
+
+/// Import events from an .ics file, creating a calendar event for each VEVENT
+#[tauri::command]
+pub async fn import_calendar_ics(
+    pool: State<'_, AppPool>,
+    file_path: String,
+) -> Result<IcsImportResult, String> {
+    let content = std::fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+    let parsed = ics::parse_ics(&content).map_err(|e| e.to_string())?;
+
+    let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+
+    // One transaction, so a failure midway doesn't leave a partial import
+    let imported = calendar_events::create_events(&conn, parsed.events).map_err(|e| e.to_string())?;
+
+    Ok(IcsImportResult {
+        imported,
+        skipped: parsed.skipped,
+    })
+}
+
+/// Export events starting within a date range as an iCalendar document.
+/// Recurring events are exported once with their RRULE. If `file_path` is given,
+/// the document is also written to that file.
+#[tauri::command]
+pub async fn export_calendar_ics(
+    pool: State<'_, AppPool>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    file_path: Option<String>,
+) -> Result<String, String> {
+    let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+
+    let events: Vec<CalendarEvent> = calendar_events::get_all_events(&conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|e| {
+            let recurring = e.recurrence_rule.as_deref().is_some_and(|r| !r.is_empty());
+            e.start_time < end && (recurring || e.start_time >= start)
+        })
+        .collect();
+
+    let document = ics::events_to_ics(&events);
+    if let Some(path) = file_path {
+        std::fs::write(&path, &document).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    }
+
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+    use crate::models::EventAttendee;
+
+    fn attendee(email: &str, name: Option<&str>) -> EventAttendee {
+        EventAttendee {
+            email: email.to_string(),
+            name: name.map(str::to_string),
+            response_status: None,
+            is_organizer: false,
+        }
+    }
+
+    #[test]
+    fn test_create_meeting_note_for_event() {
+        let pool = init_test_pool().unwrap();
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let event = {
+            let conn = pool.get().unwrap();
+            calendar_events::create_event(
+                &conn,
+                CreateCalendarEventInput {
+                    title: "Design Review".to_string(),
+                    description: Some("Walk through the new onboarding flow".to_string()),
+                    start_time: at("2030-03-04T14:00:00Z"),
+                    end_time: Some(at("2030-03-04T15:00:00Z")),
+                    all_day: false,
+                    recurrence_rule: None,
+                    linked_note_id: None,
+                    event_type: None,
+                    response_status: None,
+                    attendees: Some(vec![
+                        attendee("ana@example.com", Some("Ana")),
+                        attendee("bo@example.com", None),
+                    ]),
+                    meeting_link: Some("https://meet.example.com/abc".to_string()),
+                    location: None,
+                    timezone: Some("UTC".to_string()),
+                },
+            )
+            .unwrap()
+        };
+
+        let note = create_meeting_note_for_event(&pool, &event.id, &Tz::UTC).unwrap();
+        assert_eq!(note.title, "Design Review");
+        assert_eq!(
+            note.content.as_deref(),
+            Some(
+                "# Design Review\n\n\
+                 **Date:** Monday, March 4, 2030 at 2:00 PM - 3:00 PM\n\
+                 **Attendees:** Ana, bo@example.com\n\
+                 **Meeting Link:** https://meet.example.com/abc\n\n\
+                 ## Agenda\nWalk through the new onboarding flow\n\n\
+                 ## Notes\n\n\n\
+                 ## Action Items\n- [ ] \n"
+            )
+        );
+
+        let conn = pool.get().unwrap();
+        let linked = calendar_events::get_event_with_note(&conn, &event.id).unwrap().unwrap();
+        assert_eq!(linked.event.linked_note_id.as_deref(), Some(note.id.as_str()));
+        assert_eq!(linked.linked_note_title.as_deref(), Some("Design Review"));
+        drop(conn);
+
+        // Asking again returns the linked note rather than a duplicate
+        let again = create_meeting_note_for_event(&pool, &event.id, &Tz::UTC).unwrap();
+        assert_eq!(again.id, note.id);
+
+        assert!(create_meeting_note_for_event(&pool, "missing", &Tz::UTC).is_err());
+    }
+}
@@ -5,25 +5,42 @@
 
 use crate::ai::{
     build_context, create_client, format_system_prompt, load_ai_config,
-    resolve_citations, extract_note_references, DEFAULT_SYSTEM_PROMPT,
-    llm::{ChatMessage as LlmChatMessage, ChatRequest},
-    run_streaming_agent, UnifiedToolExecutor,
+    resolve_citations, extract_note_references, add_citation_offsets, summarize_history, truncate_history,
+    with_history_summary, estimate_cost, load_model_pricing, AIProvider, CostEstimate, CumulativeTokenUsage,
+    DEFAULT_SYSTEM_PROMPT, MAX_HISTORY_MESSAGES,
+    llm::{ChatMessage as LlmChatMessage, ChatRequest, ImagePart},
+    run_agent_with_messages, run_streaming_agent_with_fallback, AgentMessage, UnifiedToolExecutor,
     tools::{get_unified_agent_tools, AgentConfig},
 };
+use crate::db::connection::get_connection;
 use crate::db::generate_title_from_message;
+use crate::exports::conversation::split_think_tags;
 use crate::db::{self};
 use crate::models::{
-    ChatResponse, ChatStreamEvent, Conversation, ConversationWithMessages,
-    CreateConversationInput, Message, MessageMetadata, MessageRole, SendMessageInput,
-    TokenUsage, ToolCallRecord, UpdateConversationInput,
+    ChatImageInput, ChatResponse, ChatStreamEvent, Conversation, ConversationWithMessages,
+    CreateConversationInput, ImageAttachment, Message, MessageMetadata, MessageRole,
+    ReasoningSettings, SaveSystemPromptInput, SendMessageInput, SystemPrompt, TokenUsage,
+    ToolCallRecord, UpdateConversationInput,
 };
 use crate::{ActiveStreams, AppPool};
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine};
 use tauri::{AppHandle, Emitter, State};
 use tokio::sync::watch;
 
+/// Move `<think>` blocks some reasoning models inline in their reply into the thinking content
+fn separate_inline_thinking(content: &str, thinking: String) -> (String, String) {
+    let (content, inline_thinking) = split_think_tags(content);
+    let thinking = match (thinking.is_empty(), inline_thinking.is_empty()) {
+        (_, true) => thinking,
+        (true, false) => inline_thinking,
+        (false, false) => format!("{}\n\n{}", thinking, inline_thinking),
+    };
+    (content, thinking)
+}
+
 /// Load agent configuration from the database
 fn load_agent_config_from_db(pool: &crate::db::connection::DbPool) -> AgentConfig {
-    let conn = match pool.get() {
+    let conn = match get_connection(pool) {
         Ok(c) => c,
         Err(_) => return AgentConfig::default(),
     };
@@ -46,14 +63,17 @@ pub async fn create_conversation(
 ) -> Result<Conversation, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
 
-    db::create_conversation(
+    let system_prompt = db::system_prompts::resolve_system_prompt(
         &conn,
-        input.title.as_deref(),
         input.system_prompt.as_deref(),
+        input.system_prompt_id.as_deref(),
     )
-    .map_err(|e| format!("Failed to create conversation: {}", e))
+    .map_err(|e| format!("Failed to resolve system prompt: {}", e))?;
+
+    db::create_conversation(&conn, input.title.as_deref(), system_prompt.as_deref())
+        .map_err(|e| format!("Failed to create conversation: {}", e))
 }
 
 /// Get a conversation by ID
@@ -64,7 +84,7 @@ pub async fn get_conversation(
 ) -> Result<Option<Conversation>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
 
     db::get_conversation(&conn, &id).map_err(|e| format!("Failed to get conversation: {}", e))
 }
@@ -77,7 +97,7 @@ pub async fn get_conversation_with_messages(
 ) -> Result<Option<ConversationWithMessages>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
 
     let conversation = db::get_conversation(&conn, &id)
         .map_err(|e| format!("Failed to get conversation: {}", e))?;
@@ -101,7 +121,7 @@ pub async fn get_conversation_with_messages(
 pub async fn list_conversations(pool: State<'_, AppPool>) -> Result<Vec<Conversation>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
 
     db::list_conversations(&conn).map_err(|e| format!("Failed to list conversations: {}", e))
 }
@@ -113,12 +133,41 @@ pub async fn list_conversation_previews(
 ) -> Result<Vec<crate::models::ConversationPreview>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
 
     db::list_conversation_previews(&conn)
         .map_err(|e| format!("Failed to list conversation previews: {}", e))
 }
 
+/// Full-text search over conversation messages
+#[tauri::command]
+pub async fn search_conversations(
+    pool: State<'_, AppPool>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<crate::models::ConversationSearchResult>, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+
+    db::search_conversations(&conn, &query, limit.unwrap_or(20))
+        .map_err(|e| format!("Failed to search conversations: {}", e))
+}
+
+/// Fork a conversation at a message into a new conversation
+#[tauri::command]
+pub async fn branch_conversation(
+    pool: State<'_, AppPool>,
+    from_message_id: String,
+) -> Result<Conversation, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+
+    db::branch_conversation(&conn, &from_message_id)
+        .map_err(|e| format!("Failed to branch conversation: {}", e))
+}
+
 /// Update a conversation (title or system prompt)
 #[tauri::command]
 pub async fn update_conversation(
@@ -128,7 +177,7 @@ pub async fn update_conversation(
 ) -> Result<Conversation, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
 
     db::update_conversation(
         &conn,
@@ -139,12 +188,65 @@ pub async fn update_conversation(
     .map_err(|e| format!("Failed to update conversation: {}", e))
 }
 
+/// Get a conversation's reasoning effort / thinking budget
+#[tauri::command]
+pub async fn get_conversation_reasoning(
+    pool: State<'_, AppPool>,
+    id: String,
+) -> Result<ReasoningSettings, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+
+    db::get_reasoning_settings(&conn, &id)
+        .map_err(|e| format!("Failed to get reasoning settings: {}", e))
+}
+
+/// Set a conversation's reasoning effort / thinking budget (both unset turns reasoning off)
+#[tauri::command]
+pub async fn update_conversation_reasoning(
+    pool: State<'_, AppPool>,
+    id: String,
+    settings: ReasoningSettings,
+) -> Result<ReasoningSettings, String> {
+    validate_reasoning_settings(&settings)?;
+
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+
+    db::set_reasoning_settings(&conn, &id, &settings)
+        .map_err(|e| format!("Failed to update reasoning settings: {}", e))?;
+    Ok(settings)
+}
+
+/// Estimate what a conversation's responses cost from their stored token usage
+#[tauri::command]
+pub async fn get_conversation_cost(
+    pool: State<'_, AppPool>,
+    conversation_id: String,
+) -> Result<CostEstimate, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+
+    db::get_conversation(&conn, &conversation_id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Conversation not found: {}", conversation_id))?;
+    let usage = db::get_message_usage(&conn, Some(&conversation_id))
+        .map_err(|e| format!("Failed to get token usage: {}", e))?;
+    Ok(estimate_cost(
+        usage.iter().map(|(model, u)| (model.as_deref(), u)),
+        &load_model_pricing(&conn),
+    ))
+}
+
 /// Delete a conversation
 #[tauri::command]
 pub async fn delete_conversation(pool: State<'_, AppPool>, id: String) -> Result<bool, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
 
     db::delete_conversation(&conn, &id)
         .map_err(|e| format!("Failed to delete conversation: {}", e))
@@ -160,7 +262,7 @@ pub async fn get_conversation_messages(
 ) -> Result<Vec<Message>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
 
     let limit = limit.unwrap_or(100);
     let offset = offset.unwrap_or(0);
@@ -169,6 +271,50 @@ pub async fn get_conversation_messages(
         .map_err(|e| format!("Failed to get messages: {}", e))
 }
 
+// ============================================================================
+// System Prompt Presets
+// ============================================================================
+
+/// List system prompt presets
+#[tauri::command]
+pub async fn list_system_prompts(pool: State<'_, AppPool>) -> Result<Vec<SystemPrompt>, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+
+    db::system_prompts::list_system_prompts(&conn)
+        .map_err(|e| format!("Failed to list system prompts: {}", e))
+}
+
+/// Create or update a system prompt preset
+#[tauri::command]
+pub async fn save_system_prompt(
+    pool: State<'_, AppPool>,
+    input: SaveSystemPromptInput,
+) -> Result<SystemPrompt, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+
+    db::system_prompts::save_system_prompt(&conn, input)
+        .map_err(|e| format!("Failed to save system prompt: {}", e))
+}
+
+/// Delete a system prompt preset
+#[tauri::command]
+pub async fn delete_system_prompt(pool: State<'_, AppPool>, id: String) -> Result<bool, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+
+    db::system_prompts::delete_system_prompt(&conn, &id)
+        .map_err(|e| format!("Failed to delete system prompt: {}", e))
+}
+
+// ============================================================================
+// Chat
+// ============================================================================
+
 /// Send a chat message and get a response
 ///
 /// This command:
@@ -184,11 +330,16 @@ pub async fn send_chat_message(
     active_streams: State<'_, ActiveStreams>,
     input: SendMessageInput,
 ) -> Result<ChatResponse, String> {
+    let (image_parts, image_attachments) = load_attached_images(&input.images)?;
+    if let Some(ref reasoning) = input.reasoning {
+        validate_reasoning_settings(reasoning)?;
+    }
+
     // 1. Get or create conversation, save user message (sync db operations)
-    let (conversation, user_message, history, system_prompt_base, model, provider, agent_config, is_new_conversation) = {
+    let (conversation, user_message, history, system_prompt_base, candidates, agent_config, reasoning, is_new_conversation) = {
         let pool_guard = pool.0.read().unwrap();
         let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-        let conn = db_pool.get().map_err(|e| format!("Database error: {}", e))?;
+        let conn = get_connection(db_pool).map_err(|e| format!("Database error: {}", e))?;
         
         let (conversation, is_new) = if let Some(ref conv_id) = input.conversation_id {
             let conv = db::get_conversation(&conn, conv_id)
@@ -197,17 +348,27 @@ pub async fn send_chat_message(
             (conv, false)
         } else {
             // Create with placeholder title, will be updated with AI-generated title later
-            let conv = db::create_conversation(&conn, Some("New Chat"), None)
+            let default_prompt = db::system_prompts::get_default_system_prompt(&conn)
+                .map_err(|e| format!("Database error: {}", e))?
+                .map(|p| p.prompt);
+            let conv = db::create_conversation(&conn, Some("New Chat"), default_prompt.as_deref())
                 .map_err(|e| format!("Failed to create conversation: {}", e))?;
             (conv, true)
         };
 
+        if let Some(ref reasoning) = input.reasoning {
+            db::set_reasoning_settings(&conn, &conversation.id, reasoning)
+                .map_err(|e| format!("Failed to save reasoning settings: {}", e))?;
+        }
+        let reasoning = db::get_reasoning_settings(&conn, &conversation.id)
+            .map_err(|e| format!("Database error: {}", e))?;
+
         let user_message = db::create_message(
             &conn,
             &conversation.id,
             MessageRole::User,
             &input.content,
-            None,
+            user_message_metadata(image_attachments).as_ref(),
         )
         .map_err(|e| format!("Failed to save user message: {}", e))?;
 
@@ -236,12 +397,12 @@ pub async fn send_chat_message(
             .unwrap_or_else(|| DEFAULT_SYSTEM_PROMPT.to_string());
 
         let ai_config = load_ai_config(&conn)?;
-        let (model, provider) = get_chat_model_and_provider(&ai_config)?;
+        let candidates = get_chat_provider_chain(&ai_config)?;
         
         // Load agent config for tools
         let agent_config = load_agent_config_from_db(db_pool);
         
-        (conversation, user_message, history, system_prompt_base, model, provider, agent_config, is_new)
+        (conversation, user_message, history, system_prompt_base, candidates, agent_config, reasoning, is_new)
     };
 
     // 2. Build RAG context (this does async embedding work internally)
@@ -260,6 +421,15 @@ pub async fn send_chat_message(
 
     // 3. Format system prompt with RAG context
     let system_prompt = format_system_prompt(&system_prompt_base, &rag_context);
+    let (system_prompt, history) = fit_history_to_context(
+        &pool_clone,
+        &conversation.id,
+        &candidates,
+        system_prompt,
+        history,
+        &input.content,
+    )
+    .await;
 
     // 4. Build chat messages for the streaming agent
     let mut llm_messages = vec![LlmChatMessage::system(&system_prompt)];
@@ -273,7 +443,7 @@ pub async fn send_chat_message(
         llm_messages.push(msg);
     }
 
-    llm_messages.push(LlmChatMessage::user(&input.content));
+    llm_messages.push(LlmChatMessage::user_with_images(&input.content, image_parts));
 
     // 5. Get tools for chat (exclude write_content - it's for inline assistant cursor insertion)
     // Chat agent uses append_content_to_note to write to specific notes by ID
@@ -292,8 +462,11 @@ pub async fn send_chat_message(
         })
         .collect();
 
-    // 6. Create the unified tool executor with app handle for event emission
-    let executor = UnifiedToolExecutor::with_app_handle(pool_clone.clone(), provider.clone(), agent_config, app.clone());
+    // 6. Tools run with a unified executor, built for whichever provider answers
+    let make_executor = |model: &str, provider: &AIProvider| {
+        UnifiedToolExecutor::with_app_handle(pool_clone.clone(), provider.clone(), agent_config.clone(), app.clone())
+            .with_model(model)
+    };
 
     // 7. Use session_id if provided, fallback to conversation id
     let stream_key = input
@@ -316,15 +489,15 @@ pub async fn send_chat_message(
         stream_key
     );
     
-    let agent_result = run_streaming_agent(
+    let agent_result = run_streaming_agent_with_fallback(
         &app,
         &stream_key,
-        &provider,
-        &model,
+        &candidates,
         llm_messages,
         llm_tools,
-        &executor,
+        make_executor,
         10, // max iterations
+        &reasoning,
         Some(cancel_rx),
     )
     .await;
@@ -336,15 +509,17 @@ pub async fn send_chat_message(
     }
     log::debug!("[Chat] Removed cancellation channel for session: {}", stream_key);
     
-    let agent_result = agent_result.map_err(|e| format!("Agent error: {}", e))?;
+    let (agent_result, used) = agent_result.map_err(|e| format!("Agent error: {}", e))?;
+    let (model, provider) = candidates[used].clone();
 
-    let full_content = agent_result.content;
-    let thinking_content = agent_result.thinking_content;
+    let (full_content, thinking_content) =
+        separate_inline_thinking(&agent_result.content, agent_result.thinking_content);
     let tool_calls_made = agent_result.tool_calls;
 
     // 11. Extract citations from response
     let references = extract_note_references(&full_content);
-    let citations = resolve_citations(&references, &rag_context);
+    let mut citations = resolve_citations(&references, &rag_context);
+    add_citation_offsets(&mut citations, &full_content, &rag_context);
 
     // 12. Convert tool calls to the format for message metadata (full content, no truncation)
     let tool_call_records: Vec<ToolCallRecord> = tool_calls_made
@@ -369,14 +544,15 @@ pub async fn send_chat_message(
     let (assistant_message, updated_conversation) = {
         let pool_guard = pool.0.read().unwrap();
         let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-        let conn = db_pool.get().map_err(|e| format!("Database error: {}", e))?;
+        let conn = get_connection(db_pool).map_err(|e| format!("Database error: {}", e))?;
         
         let metadata = MessageMetadata {
             citations: citations.clone(),
             model: Some(model.clone()),
-            usage: None,
+            usage: agent_result.token_usage.as_ref().map(to_token_usage),
             tool_calls: tool_call_records,
             thinking_content: if thinking_content.is_empty() { None } else { Some(thinking_content) },
+            images: Vec::new(),
         };
 
         let assistant_message = db::create_message(
@@ -411,7 +587,7 @@ pub async fn send_chat_message(
                 // Update the conversation with the AI-generated title
                 let pool_guard = pool.0.read().unwrap();
                 if let Some(db_pool) = pool_guard.as_ref() {
-                    if let Ok(conn) = db_pool.get() {
+                    if let Ok(conn) = get_connection(db_pool) {
                         db::update_conversation(&conn, &updated_conversation.id, Some(&title), None)
                             .unwrap_or(updated_conversation.clone())
                     } else {
@@ -427,7 +603,7 @@ pub async fn send_chat_message(
                 let fallback_title = generate_title_from_message(&user_message.content, 50);
                 let pool_guard = pool.0.read().unwrap();
                 if let Some(db_pool) = pool_guard.as_ref() {
-                    if let Ok(conn) = db_pool.get() {
+                    if let Ok(conn) = get_connection(db_pool) {
                         db::update_conversation(&conn, &updated_conversation.id, Some(&fallback_title), None)
                             .unwrap_or(updated_conversation.clone())
                     } else {
@@ -455,11 +631,16 @@ pub async fn send_chat_message_sync(
     pool: State<'_, AppPool>,
     input: SendMessageInput,
 ) -> Result<ChatResponse, String> {
+    let (image_parts, image_attachments) = load_attached_images(&input.images)?;
+    if let Some(ref reasoning) = input.reasoning {
+        validate_reasoning_settings(reasoning)?;
+    }
+
     // 1. Sync db operations before async work
-    let (conversation, user_message, history, system_prompt_base, model, provider, is_new_conversation) = {
+    let (conversation, user_message, history, system_prompt_base, model, provider, agent_config, reasoning, is_new_conversation) = {
         let pool_guard = pool.0.read().unwrap();
         let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-        let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+        let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
 
         let (conversation, is_new) = if let Some(ref conv_id) = input.conversation_id {
             let conv = db::get_conversation(&conn, conv_id)
@@ -468,17 +649,27 @@ pub async fn send_chat_message_sync(
             (conv, false)
         } else {
             // Create with placeholder title, will be updated with AI-generated title later
-            let conv = db::create_conversation(&conn, Some("New Chat"), None)
+            let default_prompt = db::system_prompts::get_default_system_prompt(&conn)
+                .map_err(|e| format!("Database error: {}", e))?
+                .map(|p| p.prompt);
+            let conv = db::create_conversation(&conn, Some("New Chat"), default_prompt.as_deref())
                 .map_err(|e| format!("Failed to create conversation: {}", e))?;
             (conv, true)
         };
 
+        if let Some(ref reasoning) = input.reasoning {
+            db::set_reasoning_settings(&conn, &conversation.id, reasoning)
+                .map_err(|e| format!("Failed to save reasoning settings: {}", e))?;
+        }
+        let reasoning = db::get_reasoning_settings(&conn, &conversation.id)
+            .map_err(|e| format!("Database error: {}", e))?;
+
         let user_message = db::create_message(
             &conn,
             &conversation.id,
             MessageRole::User,
             &input.content,
-            None,
+            user_message_metadata(image_attachments).as_ref(),
         )
         .map_err(|e| format!("Failed to save user message: {}", e))?;
 
@@ -498,8 +689,9 @@ pub async fn send_chat_message_sync(
 
         let ai_config = load_ai_config(&conn)?;
         let (model, provider) = get_chat_model_and_provider(&ai_config)?;
+        let agent_config = load_agent_config_from_db(pool);
         
-        (conversation, user_message, history, system_prompt_base, model, provider, is_new)
+        (conversation, user_message, history, system_prompt_base, model, provider, agent_config, reasoning, is_new)
     };
 
     // 2. Build RAG context (async)
@@ -518,66 +710,120 @@ pub async fn send_chat_message_sync(
 
     // 3. Format system prompt with context
     let system_prompt = format_system_prompt(&system_prompt_base, &rag_context);
+    let (system_prompt, history) = fit_history_to_context(
+        &pool_clone,
+        &conversation.id,
+        &[(model.clone(), provider.clone())],
+        system_prompt,
+        history,
+        &input.content,
+    )
+    .await;
 
     // 4. Create LLM client
     let llm_client = create_client(&provider).map_err(|e| format!("Failed to create LLM client: {}", e))?;
 
-    // 5. Build messages
-    let mut llm_messages = vec![LlmChatMessage::system(&system_prompt)];
+    // 5-6. Make the request, running the multi-step tool loop when tools are enabled
+    let (content, usage, tool_call_records) = if input.use_tools {
+        let mut agent_messages = vec![AgentMessage::system(&system_prompt)];
+        for (role, content) in history {
+            let msg = match role.as_str() {
+                "assistant" => AgentMessage::assistant(&content),
+                "system" => AgentMessage::system(&content),
+                _ => AgentMessage::user(&content),
+            };
+            agent_messages.push(msg);
+        }
+        agent_messages.push(AgentMessage::user_with_images(&input.content, image_parts));
+
+        // Chat writes to notes by ID, so write_content (cursor insertion) is excluded
+        let tools = get_unified_agent_tools(&agent_config, false);
+        let executor = UnifiedToolExecutor::new(pool_clone.clone(), provider.clone(), agent_config).with_model(&model);
+
+        let result = run_agent_with_messages(
+            llm_client.as_ref(),
+            &model,
+            agent_messages,
+            tools,
+            &executor,
+            10, // max iterations
+            &reasoning,
+        )
+        .await
+        .map_err(|e| format!("Agent error: {}", e))?;
 
-    for (role, content) in history {
-        let msg = match role.as_str() {
-            "assistant" => LlmChatMessage::assistant(&content),
-            "system" => LlmChatMessage::system(&content),
-            _ => LlmChatMessage::user(&content),
-        };
-        llm_messages.push(msg);
-    }
+        let usage = result.token_usage.as_ref().map(to_token_usage);
+        let tool_call_records: Vec<ToolCallRecord> = result
+            .tool_calls_made
+            .iter()
+            .map(|tc| ToolCallRecord {
+                tool: tc.tool_name.clone(),
+                success: !tc.result.starts_with("Error:"),
+                preview: Some(tc.result.clone()),
+            })
+            .collect();
 
-    llm_messages.push(LlmChatMessage::user(&input.content));
+        (result.final_response, usage, tool_call_records)
+    } else {
+        let mut llm_messages = vec![LlmChatMessage::system(&system_prompt)];
+
+        for (role, content) in history {
+            let msg = match role.as_str() {
+                "assistant" => LlmChatMessage::assistant(&content),
+                "system" => LlmChatMessage::system(&content),
+                _ => LlmChatMessage::user(&content),
+            };
+            llm_messages.push(msg);
+        }
 
-    let chat_request = ChatRequest {
-        model: model.clone(),
-        messages: llm_messages,
-        max_tokens: None, // Let the model decide, avoid compatibility issues
-        temperature: None,
-        tools: None,
-        tool_choice: None,
-        enable_reasoning: false,
-        reasoning_effort: None,
-        thinking_budget: None,
-    };
+        llm_messages.push(LlmChatMessage::user_with_images(&input.content, image_parts));
+
+        let chat_request = ChatRequest {
+            model: model.clone(),
+            messages: llm_messages,
+            max_tokens: None, // Let the model decide, avoid compatibility issues
+            temperature: None,
+            tools: None,
+            tool_choice: None,
+            enable_reasoning: reasoning.is_enabled(),
+            reasoning_effort: reasoning.reasoning_effort.clone(),
+            thinking_budget: reasoning.thinking_budget,
+            // The system prompt carries the RAG context, which repeats across turns
+            use_prompt_cache: true,
+        };
+
+        let response = llm_client.chat(chat_request).await
+            .map_err(|e| format!("Chat request failed: {}", e))?;
 
-    // 6. Make non-streaming request
-    let response = llm_client.chat(chat_request).await
-        .map_err(|e| format!("Chat request failed: {}", e))?;
+        let usage = response.usage.map(|u| TokenUsage {
+            prompt_tokens: u.prompt_tokens,
+            completion_tokens: u.completion_tokens,
+            total_tokens: u.total_tokens,
+        });
 
-    let content = response.content;
+        (response.content, usage, vec![])
+    };
 
     // Extract citations
     let references = extract_note_references(&content);
-    let citations = resolve_citations(&references, &rag_context);
+    let mut citations = resolve_citations(&references, &rag_context);
+    add_citation_offsets(&mut citations, &content, &rag_context);
 
     // Build metadata
-    let usage = response.usage.map(|u| TokenUsage {
-        prompt_tokens: u.prompt_tokens,
-        completion_tokens: u.completion_tokens,
-        total_tokens: u.total_tokens,
-    });
-
     let metadata = MessageMetadata {
         citations,
         model: Some(model.clone()),
         usage,
-        tool_calls: vec![],
+        tool_calls: tool_call_records,
         thinking_content: None,
+        images: Vec::new(),
     };
 
     // Save assistant message (sync db operation)
     let (assistant_message, updated_conversation) = {
         let pool_guard = pool.0.read().unwrap();
         let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-        let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+        let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
         
         let assistant_message =
             db::create_message(&conn, &conversation.id, MessageRole::Assistant, &content, Some(&metadata))
@@ -596,7 +842,7 @@ pub async fn send_chat_message_sync(
             Ok(title) => {
                 let pool_guard = pool.0.read().unwrap();
                 if let Some(pool) = pool_guard.as_ref() {
-                    if let Ok(conn) = pool.get() {
+                    if let Ok(conn) = get_connection(pool) {
                         db::update_conversation(&conn, &updated_conversation.id, Some(&title), None)
                             .unwrap_or(updated_conversation.clone())
                     } else {
@@ -611,7 +857,7 @@ pub async fn send_chat_message_sync(
                 let fallback_title = generate_title_from_message(&user_message.content, 50);
                 let pool_guard = pool.0.read().unwrap();
                 if let Some(pool) = pool_guard.as_ref() {
-                    if let Ok(conn) = pool.get() {
+                    if let Ok(conn) = get_connection(pool) {
                         db::update_conversation(&conn, &updated_conversation.id, Some(&fallback_title), None)
                             .unwrap_or(updated_conversation.clone())
                     } else {
@@ -647,7 +893,7 @@ pub async fn edit_message_and_regenerate(
     let (conversation, history_before, agent_config) = {
         let pool_guard = pool.0.read().unwrap();
         let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-        let conn = db_pool.get().map_err(|e| format!("Database error: {}", e))?;
+        let conn = get_connection(db_pool).map_err(|e| format!("Database error: {}", e))?;
         
         let message = db::get_message(&conn, &message_id)
             .map_err(|e| format!("Database error: {}", e))?
@@ -685,7 +931,7 @@ pub async fn edit_message_and_regenerate(
     let user_message = {
         let pool_guard = pool.0.read().unwrap();
         let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-        let conn = db_pool.get().map_err(|e| format!("Database error: {}", e))?;
+        let conn = get_connection(db_pool).map_err(|e| format!("Database error: {}", e))?;
         db::create_message(
             &conn,
             &conversation.id,
@@ -696,27 +942,473 @@ pub async fn edit_message_and_regenerate(
         .map_err(|e| format!("Failed to save user message: {}", e))?
     };
 
-    // 3. Build RAG context for the new message
-    // Use auto_retrieve_count = 0 to match regular chat behavior
-    // Agent should use tools to access notes instead of auto-retrieve
+    // 3. Stream and save the new assistant reply
     let pool_clone = {
         let pool_guard = pool.0.read().unwrap();
         pool_guard.as_ref().ok_or("Database not initialized")?.clone()
     };
-    let rag_context = build_context(
+    let (assistant_message, updated_conversation) = generate_assistant_reply(
+        &app,
         &pool_clone,
+        &active_streams,
+        conversation,
+        history_before,
         &new_content,
-        vec![], // No explicit context for edited messages
+        None,
+        agent_config,
+    )
+    .await?;
+
+    Ok(ChatResponse {
+        conversation: updated_conversation,
+        user_message,
+        assistant_message,
+    })
+}
+
+/// Regenerate the last assistant response
+///
+/// Streams a new reply to the same prompt from the existing history. The
+/// final assistant message is kept until the new reply is saved, then
+/// replaced in the same transaction. Fails if the conversation doesn't end
+/// with an assistant message.
+#[tauri::command]
+pub async fn regenerate_last_response(
+    app: AppHandle,
+    pool: State<'_, AppPool>,
+    active_streams: State<'_, ActiveStreams>,
+    conversation_id: String,
+) -> Result<ChatResponse, String> {
+    let db_pool = {
+        let pool_guard = pool.0.read().unwrap();
+        pool_guard.as_ref().ok_or("Database not initialized")?.clone()
+    };
+
+    let (conversation, history_before, user_message, last_response) = {
+        let conn = get_connection(&db_pool).map_err(|e| format!("Database error: {}", e))?;
+        find_last_response(&conn, &conversation_id)?
+    };
+    let agent_config = load_agent_config_from_db(&db_pool);
+
+    let history_before: Vec<(String, String)> = history_before
+        .iter()
+        .map(|m| (m.role.as_str().to_string(), m.content.clone()))
+        .collect();
+
+    let (assistant_message, updated_conversation) = generate_assistant_reply(
+        &app,
+        &db_pool,
+        &active_streams,
+        conversation,
+        history_before,
+        &user_message.content,
+        Some(&last_response.id),
+        agent_config,
+    )
+    .await?;
+
+    Ok(ChatResponse {
+        conversation: updated_conversation,
+        user_message,
+        assistant_message,
+    })
+}
+
+/// Get the default system prompt
+#[tauri::command]
+pub fn get_default_system_prompt() -> String {
+    DEFAULT_SYSTEM_PROMPT.to_string()
+}
+
+/// Stop an active generation stream
+/// 
+/// This signals the streaming loop to stop processing chunks and return early.
+/// The partial response (what was generated so far) will be saved.
+#[tauri::command]
+pub async fn stop_generation(
+    active_streams: State<'_, ActiveStreams>,
+    session_id: String,
+) -> Result<bool, String> {
+    let streams = active_streams.0.read().map_err(|e| format!("Lock error: {}", e))?;
+    
+    if let Some(cancel_tx) = streams.get(&session_id) {
+        // Signal cancellation
+        let _ = cancel_tx.send(true);
+        log::info!("Sent stop signal for session: {}", session_id);
+        Ok(true)
+    } else {
+        log::debug!("No active stream found for session: {}", session_id);
+        Ok(false)
+    }
+}
+
+/// List the session ids of the generation streams currently registered
+#[tauri::command]
+pub fn list_active_streams(active_streams: State<'_, ActiveStreams>) -> Result<Vec<String>, String> {
+    let streams = active_streams.0.read().map_err(|e| format!("Lock error: {}", e))?;
+    let mut session_ids: Vec<String> = streams.keys().cloned().collect();
+    session_ids.sort();
+    Ok(session_ids)
+}
+
+/// Drop registered streams whose generation is no longer listening
+///
+/// Entries are normally removed when a generation finishes, but one can be
+/// left behind if the command fails before cleaning up. Returns the session
+/// ids that were removed.
+#[tauri::command]
+pub fn prune_active_streams(active_streams: State<'_, ActiveStreams>) -> Result<Vec<String>, String> {
+    prune_closed_streams(&active_streams)
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Remove streams whose cancellation receiver has been dropped
+fn prune_closed_streams(active_streams: &ActiveStreams) -> Result<Vec<String>, String> {
+    let mut streams = active_streams.0.write().map_err(|e| format!("Lock error: {}", e))?;
+    let mut pruned: Vec<String> = streams
+        .iter()
+        .filter(|(_, cancel_tx)| cancel_tx.is_closed())
+        .map(|(session_id, _)| session_id.clone())
+        .collect();
+    for session_id in &pruned {
+        streams.remove(session_id);
+    }
+    if !pruned.is_empty() {
+        log::info!("Pruned {} stale stream(s): {:?}", pruned.len(), pruned);
+    }
+    pruned.sort();
+    Ok(pruned)
+}
+
+/// Fewest dropped messages worth a summary when the context window is unknown
+///
+/// Without a known limit, history is only capped by message count, so a turn
+/// or two falling off the front isn't worth a summarization call.
+const MIN_DROPPED_TO_SUMMARIZE: usize = 10;
+
+/// What to do about the history that fell out of the context window
+#[derive(Debug, PartialEq)]
+enum HistorySummaryPlan {
+    /// Send the truncated history as is
+    Skip,
+    /// The cached summary covers exactly the dropped messages
+    Reuse(String),
+    /// Summarize `start..end`, extending `previous` if it covers `0..start`
+    Summarize {
+        previous: Option<String>,
+        start: usize,
+        end: usize,
+    },
+}
+
+/// Decide how to summarize the first `dropped` history messages
+///
+/// The summary always covers exactly the dropped range, so nothing it
+/// describes is also sent verbatim. A cached summary of a shorter prefix is
+/// extended; one covering more than was dropped (e.g. after switching to a
+/// model with a larger window) is discarded.
+fn plan_history_summary(
+    cached: Option<(String, usize)>,
+    dropped: usize,
+    context_length_known: bool,
+) -> HistorySummaryPlan {
+    if dropped == 0 || (!context_length_known && dropped < MIN_DROPPED_TO_SUMMARIZE) {
+        return HistorySummaryPlan::Skip;
+    }
+    match cached {
+        Some((summary, count)) if count == dropped => HistorySummaryPlan::Reuse(summary),
+        Some((summary, count)) if count > 0 && count < dropped => HistorySummaryPlan::Summarize {
+            previous: Some(summary),
+            start: count,
+            end: dropped,
+        },
+        _ => HistorySummaryPlan::Summarize {
+            previous: None,
+            start: 0,
+            end: dropped,
+        },
+    }
+}
+
+/// Trim history to fit the context window of any of the candidate (model, provider) pairs
+///
+/// The smallest window among the candidates is used, since a fallback may end
+/// up answering. Dropped turns are replaced by a running summary appended to the
+/// system prompt, written by the first candidate that can be reached. The summary
+/// is cached on the conversation along with how many messages it covers, and is
+/// extended as more fall out of the window (see [`plan_history_summary`]).
+/// Summarization is best effort: on failure the history is just truncated.
+async fn fit_history_to_context(
+    db_pool: &crate::db::connection::DbPool,
+    conversation_id: &str,
+    candidates: &[(String, AIProvider)],
+    system_prompt: String,
+    history: Vec<(String, String)>,
+    user_message: &str,
+) -> (String, Vec<(String, String)>) {
+    let total = history.len();
+    let all_messages = history.clone();
+    let context_length = candidates.iter().filter_map(|(_, p)| p.context_length).min();
+    let truncated = truncate_history(
+        history,
+        &system_prompt,
+        user_message,
+        context_length,
+        MAX_HISTORY_MESSAGES,
+    );
+    if truncated.dropped == 0 {
+        return (system_prompt, truncated.messages);
+    }
+
+    log::info!(
+        "[Chat] Dropped {} of {} history messages to fit the context window",
+        truncated.dropped,
+        total
+    );
+
+    let cached = db_pool
+        .get()
+        .ok()
+        .and_then(|conn| db::get_history_summary(&conn, conversation_id).ok().flatten());
+
+    let summary = match plan_history_summary(cached, truncated.dropped, context_length.is_some()) {
+        HistorySummaryPlan::Skip => None,
+        HistorySummaryPlan::Reuse(summary) => Some(summary),
+        HistorySummaryPlan::Summarize { previous, start, end } => {
+            let mut result = Err("No AI provider available".to_string());
+            for (model, provider) in candidates {
+                let attempt = match create_client(provider) {
+                    Ok(client) => {
+                        summarize_history(client.as_ref(), model, previous.as_deref(), &all_messages[start..end]).await
+                    }
+                    Err(e) => Err(e),
+                };
+                match attempt {
+                    Err(e) if e.is_connection_error() => result = Err(e.to_string()),
+                    attempt => {
+                        result = attempt.map_err(|e| e.to_string());
+                        break;
+                    }
+                }
+            }
+            match result {
+                Ok(summary) if !summary.is_empty() => {
+                    if let Ok(conn) = get_connection(db_pool) {
+                        if let Err(e) = db::set_history_summary(&conn, conversation_id, &summary, end) {
+                            log::warn!("[Chat] Failed to cache history summary: {}", e);
+                        }
+                    }
+                    Some(summary)
+                }
+                Ok(_) => None,
+                Err(e) => {
+                    log::warn!("[Chat] Failed to summarize dropped history: {}", e);
+                    None
+                }
+            }
+        }
+    };
+
+    let system_prompt = match summary {
+        Some(summary) => with_history_summary(&system_prompt, &summary),
+        None => system_prompt,
+    };
+    (system_prompt, truncated.messages)
+}
+
+/// Anthropic rejects thinking budgets below this
+const MIN_THINKING_BUDGET: u32 = 1024;
+
+/// Check reasoning settings before they're saved on a conversation
+fn validate_reasoning_settings(settings: &ReasoningSettings) -> Result<(), String> {
+    if let Some(ref effort) = settings.reasoning_effort {
+        if !["minimal", "low", "medium", "high"].contains(&effort.as_str()) {
+            return Err(format!(
+                "Invalid reasoning effort '{}' (expected minimal, low, medium or high)",
+                effort
+            ));
+        }
+    }
+    if let Some(budget) = settings.thinking_budget {
+        if budget < MIN_THINKING_BUDGET {
+            return Err(format!("Thinking budget must be at least {} tokens", MIN_THINKING_BUDGET));
+        }
+    }
+    Ok(())
+}
+
+/// Read the images attached to a chat message
+///
+/// Returns the image parts sent to the model and the references saved on the
+/// user message. `data` may be plain base64 or a `data:` URL.
+fn load_attached_images(
+    images: &[ChatImageInput],
+) -> Result<(Vec<ImagePart>, Vec<ImageAttachment>), String> {
+    let mut parts = Vec::with_capacity(images.len());
+    let mut attachments = Vec::with_capacity(images.len());
+
+    for image in images {
+        let (bytes, data_url_mime) = match (&image.path, &image.data) {
+            (Some(path), _) => {
+                let bytes = std::fs::read(path)
+                    .map_err(|e| format!("Failed to read image {}: {}", path, e))?;
+                (bytes, None)
+            }
+            (None, Some(data)) => {
+                let (mime, payload) = match data.strip_prefix("data:").and_then(|d| d.split_once(";base64,")) {
+                    Some((mime, payload)) => (Some(mime.to_string()), payload),
+                    None => (None, data.as_str()),
+                };
+                let bytes = BASE64_STANDARD
+                    .decode(payload.trim())
+                    .map_err(|e| format!("Invalid image data: {}", e))?;
+                (bytes, mime)
+            }
+            (None, None) => return Err("Attached image needs a path or data".to_string()),
+        };
+
+        let mime_type = image
+            .mime_type
+            .clone()
+            .or(data_url_mime)
+            .or_else(|| image.path.as_deref().and_then(image_mime_type))
+            .ok_or_else(|| "Unsupported image type (use PNG, JPEG, GIF or WebP)".to_string())?;
+
+        attachments.push(ImageAttachment {
+            path: image.path.clone(),
+            mime_type: mime_type.clone(),
+            size_bytes: bytes.len(),
+        });
+        parts.push(ImagePart {
+            mime_type,
+            data: BASE64_STANDARD.encode(&bytes),
+        });
+    }
+
+    Ok((parts, attachments))
+}
+
+/// MIME type for an image file, from its extension
+fn image_mime_type(path: &str) -> Option<String> {
+    let extension = std::path::Path::new(path).extension()?.to_str()?.to_lowercase();
+    let mime = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        _ => return None,
+    };
+    Some(mime.to_string())
+}
+
+/// Usage summed over an agent run, in the form stored on messages
+fn to_token_usage(usage: &CumulativeTokenUsage) -> TokenUsage {
+    TokenUsage {
+        prompt_tokens: usage.total_prompt_tokens,
+        completion_tokens: usage.total_completion_tokens,
+        total_tokens: usage.total_tokens,
+    }
+}
+
+/// Metadata for a user message, recording its attached images
+fn user_message_metadata(images: Vec<ImageAttachment>) -> Option<MessageMetadata> {
+    if images.is_empty() {
+        return None;
+    }
+    Some(MessageMetadata {
+        citations: Vec::new(),
+        model: None,
+        usage: None,
+        tool_calls: Vec::new(),
+        thinking_content: None,
+        images,
+    })
+}
+
+/// Find the last assistant message of a conversation so it can be regenerated
+///
+/// Returns the conversation, the messages before the prompt that produced the
+/// reply, that prompt, and the reply itself. Fails if the conversation
+/// doesn't end with an assistant reply to a user message.
+fn find_last_response(
+    conn: &rusqlite::Connection,
+    conversation_id: &str,
+) -> Result<(Conversation, Vec<Message>, Message, Message), String> {
+    let conversation = db::get_conversation(conn, conversation_id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Conversation not found: {}", conversation_id))?;
+
+    let mut messages = db::get_conversation_messages(conn, conversation_id)
+        .map_err(|e| format!("Failed to get messages: {}", e))?;
+
+    let last_response = match messages.pop() {
+        Some(m) if m.role == MessageRole::Assistant => m,
+        Some(_) => return Err("The last message is not an assistant response".to_string()),
+        None => return Err("Conversation has no messages".to_string()),
+    };
+    let prompt = match messages.pop() {
+        Some(m) if m.role == MessageRole::User => m,
+        _ => return Err("No user message to regenerate a response for".to_string()),
+    };
+
+    Ok((conversation, messages, prompt, last_response))
+}
+
+/// Save an assistant reply, deleting the message it `replaces` in the same
+/// transaction so a failed save leaves the old reply in place
+fn save_assistant_reply(
+    conn: &rusqlite::Connection,
+    conversation_id: &str,
+    replaces: Option<&str>,
+    content: &str,
+    metadata: &MessageMetadata,
+) -> Result<Message, String> {
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Database error: {}", e))?;
+    if let Some(old_id) = replaces {
+        db::delete_message(&tx, old_id).map_err(|e| format!("Failed to delete message: {}", e))?;
+    }
+    let message = db::create_message(&tx, conversation_id, MessageRole::Assistant, content, Some(metadata))
+        .map_err(|e| format!("Failed to save assistant message: {}", e))?;
+    tx.commit().map_err(|e| format!("Database error: {}", e))?;
+    Ok(message)
+}
+
+/// Stream an assistant reply to `prompt` after `history_before` and save it
+///
+/// Shared by the edit and regenerate commands. When `replaces` is set, that
+/// message is swapped for the new reply once it's saved. Returns the saved
+/// assistant message and the updated conversation.
+#[allow(clippy::too_many_arguments)]
+async fn generate_assistant_reply(
+    app: &AppHandle,
+    db_pool: &crate::db::connection::DbPool,
+    active_streams: &ActiveStreams,
+    conversation: Conversation,
+    history_before: Vec<(String, String)>,
+    prompt: &str,
+    replaces: Option<&str>,
+    agent_config: AgentConfig,
+) -> Result<(Message, Conversation), String> {
+    // 1. Build RAG context for the prompt
+    // Use auto_retrieve_count = 0 to match regular chat behavior
+    // Agent should use tools to access notes instead of auto-retrieve
+    let rag_context = build_context(
+        db_pool,
+        prompt,
+        vec![], // No explicit context when regenerating
         0,      // Agent should use tools to access notes instead of auto-retrieve
     )
     .await
     .map_err(|e| format!("Failed to build context: {}", e))?;
 
-    // 4. Get model, provider, and format system prompt
-    let (system_prompt, model, provider) = {
-        let pool_guard = pool.0.read().unwrap();
-        let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-        let conn = db_pool.get().map_err(|e| format!("Database error: {}", e))?;
+    // 2. Get model, provider, reasoning settings, and format system prompt
+    let (system_prompt, candidates, reasoning) = {
+        let conn = get_connection(db_pool).map_err(|e| format!("Database error: {}", e))?;
         
         let base_prompt = conversation
             .system_prompt
@@ -725,12 +1417,23 @@ pub async fn edit_message_and_regenerate(
         let system_prompt = format_system_prompt(&base_prompt, &rag_context);
         
         let ai_config = load_ai_config(&conn)?;
-        let (model, provider) = get_chat_model_and_provider(&ai_config)?;
+        let candidates = get_chat_provider_chain(&ai_config)?;
+        let reasoning = db::get_reasoning_settings(&conn, &conversation.id)
+            .map_err(|e| format!("Database error: {}", e))?;
         
-        (system_prompt, model, provider)
+        (system_prompt, candidates, reasoning)
     };
+    let (system_prompt, history_before) = fit_history_to_context(
+        db_pool,
+        &conversation.id,
+        &candidates,
+        system_prompt,
+        history_before,
+        prompt,
+    )
+    .await;
 
-    // 5. Build messages for streaming agent
+    // 3. Build messages for streaming agent
     let mut llm_messages = vec![LlmChatMessage::system(&system_prompt)];
 
     for (role, content) in history_before {
@@ -742,9 +1445,9 @@ pub async fn edit_message_and_regenerate(
         llm_messages.push(msg);
     }
 
-    llm_messages.push(LlmChatMessage::user(&new_content));
+    llm_messages.push(LlmChatMessage::user(prompt));
 
-    // 6. Get tools for chat (exclude write_content - it's for inline assistant cursor insertion)
+    // 4. Get tools for chat (exclude write_content - it's for inline assistant cursor insertion)
     // Chat agent uses append_content_to_note to write to specific notes by ID
     let tools = get_unified_agent_tools(&agent_config, false);
     
@@ -761,56 +1464,61 @@ pub async fn edit_message_and_regenerate(
         })
         .collect();
 
-    // 7. Create the unified tool executor with app handle for event emission
-    let executor = UnifiedToolExecutor::with_app_handle(pool_clone.clone(), provider.clone(), agent_config, app.clone());
+    // 5. Tools run with a unified executor, built for whichever provider answers
+    let make_executor = |model: &str, provider: &AIProvider| {
+        UnifiedToolExecutor::with_app_handle(db_pool.clone(), provider.clone(), agent_config.clone(), app.clone())
+            .with_model(model)
+    };
 
-    // 8. Create cancellation channel and register in ActiveStreams
+    // 6. Create cancellation channel and register in ActiveStreams
     let stream_key = conversation.id.clone();
     let (cancel_tx, cancel_rx) = watch::channel(false);
     {
         let mut streams = active_streams.0.write().map_err(|e| format!("Lock error: {}", e))?;
         streams.insert(stream_key.clone(), cancel_tx);
     }
-    log::debug!("[Chat] Registered cancellation channel for edit session: {}", stream_key);
+    log::debug!("[Chat] Registered cancellation channel for regeneration session: {}", stream_key);
     
-    // 9. Run the streaming agent with tools
+    // 7. Run the streaming agent with tools
     log::info!(
-        "[Chat] Running streaming agent for edit with {} tools for session {}",
+        "[Chat] Running streaming agent for regeneration with {} tools for session {}",
         llm_tools.len(),
         stream_key
     );
     
-    let agent_result = run_streaming_agent(
-        &app,
+    let agent_result = run_streaming_agent_with_fallback(
+        app,
         &stream_key,
-        &provider,
-        &model,
+        &candidates,
         llm_messages,
         llm_tools,
-        &executor,
+        make_executor,
         10, // max iterations
+        &reasoning,
         Some(cancel_rx),
     )
     .await;
     
-    // 10. Clean up cancellation channel
+    // 8. Clean up cancellation channel
     {
         let mut streams = active_streams.0.write().map_err(|e| format!("Lock error: {}", e))?;
         streams.remove(&stream_key);
     }
-    log::debug!("[Chat] Removed cancellation channel for edit session: {}", stream_key);
+    log::debug!("[Chat] Removed cancellation channel for regeneration session: {}", stream_key);
     
-    let agent_result = agent_result.map_err(|e| format!("Agent error: {}", e))?;
+    let (agent_result, used) = agent_result.map_err(|e| format!("Agent error: {}", e))?;
+    let model = candidates[used].0.clone();
 
-    let full_content = agent_result.content;
-    let thinking_content = agent_result.thinking_content;
+    let (full_content, thinking_content) =
+        separate_inline_thinking(&agent_result.content, agent_result.thinking_content);
     let tool_calls_made = agent_result.tool_calls;
 
-    // 11. Extract citations from response
+    // 9. Extract citations from response
     let references = extract_note_references(&full_content);
-    let citations = resolve_citations(&references, &rag_context);
+    let mut citations = resolve_citations(&references, &rag_context);
+    add_citation_offsets(&mut citations, &full_content, &rag_context);
 
-    // 12. Convert tool calls to the format for message metadata (full content, no truncation)
+    // 10. Convert tool calls to the format for message metadata (full content, no truncation)
     let tool_call_records: Vec<ToolCallRecord> = tool_calls_made
         .iter()
         .map(|tc| ToolCallRecord {
@@ -823,34 +1531,27 @@ pub async fn edit_message_and_regenerate(
     // Log tool calls being saved for debugging
     if !tool_call_records.is_empty() {
         log::info!(
-            "[Chat] Saving {} tool calls to edited message metadata: {:?}",
+            "[Chat] Saving {} tool calls to regenerated message metadata: {:?}",
             tool_call_records.len(),
             tool_call_records.iter().map(|tc| &tc.tool).collect::<Vec<_>>()
         );
     }
 
-    // 13. Save assistant message
+    // 11. Save assistant message
     let (assistant_message, updated_conversation) = {
-        let pool_guard = pool.0.read().unwrap();
-        let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-        let conn = db_pool.get().map_err(|e| format!("Database error: {}", e))?;
+        let conn = get_connection(db_pool).map_err(|e| format!("Database error: {}", e))?;
         
         let metadata = MessageMetadata {
             citations: citations.clone(),
             model: Some(model),
-            usage: None,
+            usage: agent_result.token_usage.as_ref().map(to_token_usage),
             tool_calls: tool_call_records,
             thinking_content: if thinking_content.is_empty() { None } else { Some(thinking_content) },
+            images: Vec::new(),
         };
 
-        let assistant_message = db::create_message(
-            &conn,
-            &conversation.id,
-            MessageRole::Assistant,
-            &full_content,
-            Some(&metadata),
-        )
-        .map_err(|e| format!("Failed to save assistant message: {}", e))?;
+        let assistant_message =
+            save_assistant_reply(&conn, &conversation.id, replaces, &full_content, &metadata)?;
 
         let updated_conversation = db::get_conversation(&conn, &conversation.id)
             .map_err(|e| format!("Database error: {}", e))?
@@ -868,45 +1569,10 @@ pub async fn edit_message_and_regenerate(
         },
     );
 
-    Ok(ChatResponse {
-        conversation: updated_conversation,
-        user_message,
-        assistant_message,
-    })
-}
-
-/// Get the default system prompt
-#[tauri::command]
-pub fn get_default_system_prompt() -> String {
-    DEFAULT_SYSTEM_PROMPT.to_string()
-}
 
-/// Stop an active generation stream
-/// 
-/// This signals the streaming loop to stop processing chunks and return early.
-/// The partial response (what was generated so far) will be saved.
-#[tauri::command]
-pub async fn stop_generation(
-    active_streams: State<'_, ActiveStreams>,
-    session_id: String,
-) -> Result<bool, String> {
-    let streams = active_streams.0.read().map_err(|e| format!("Lock error: {}", e))?;
-    
-    if let Some(cancel_tx) = streams.get(&session_id) {
-        // Signal cancellation
-        let _ = cancel_tx.send(true);
-        log::info!("Sent stop signal for session: {}", session_id);
-        Ok(true)
-    } else {
-        log::debug!("No active stream found for session: {}", session_id);
-        Ok(false)
-    }
+    Ok((assistant_message, updated_conversation))
 }
 
-// ============================================================================
-// Helper Functions
-// ============================================================================
-
 /// Get the chat model and provider from AI config
 fn get_chat_model_and_provider(config: &crate::ai::AIConfig) -> Result<(String, crate::ai::AIProvider), String> {
     // Find the default provider or first enabled provider
@@ -937,6 +1603,27 @@ fn get_chat_model_and_provider(config: &crate::ai::AIConfig) -> Result<(String,
     Ok((model, provider))
 }
 
+/// The chat provider followed by the configured fallbacks, as (model, provider) pairs
+///
+/// Fallbacks that are disabled, unknown, have no model, or repeat an earlier entry are skipped.
+fn get_chat_provider_chain(
+    config: &crate::ai::AIConfig,
+) -> Result<Vec<(String, crate::ai::AIProvider)>, String> {
+    let mut chain = vec![get_chat_model_and_provider(config)?];
+    for id in &config.fallback_provider_ids {
+        let Some(provider) = config.providers.iter().find(|p| &p.id == id && p.is_enabled) else {
+            continue;
+        };
+        if chain.iter().any(|(_, p)| p.id == provider.id) {
+            continue;
+        }
+        if let Some(model) = provider.selected_model.clone().or_else(|| provider.models.first().cloned()) {
+            chain.push((model, provider.clone()));
+        }
+    }
+    Ok(chain)
+}
+
 /// Strip known provider prefixes from model name
 /// Only strips if the prefix matches a known provider (openai/, anthropic/, google/, ollama/, lmstudio/, vllm/)
 /// System prompt for title generation
@@ -966,6 +1653,7 @@ async fn generate_ai_title(
         enable_reasoning: false,
         reasoning_effort: None,
         thinking_budget: None,
+        use_prompt_cache: false,
     };
 
     let response = llm_client.chat(request).await
@@ -978,3 +1666,242 @@ async fn generate_ai_title(
     
     if title.is_empty() { Ok("New Chat".to_string()) } else { Ok(title) }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+
+    #[test]
+    fn test_prune_removes_streams_with_closed_receivers() {
+        let active_streams = ActiveStreams(std::sync::RwLock::new(std::collections::HashMap::new()));
+        let (live_tx, _live_rx) = watch::channel(false);
+        let (stale_tx, stale_rx) = watch::channel(false);
+        drop(stale_rx);
+        {
+            let mut streams = active_streams.0.write().unwrap();
+            streams.insert("live".to_string(), live_tx);
+            streams.insert("stale".to_string(), stale_tx);
+        }
+
+        assert_eq!(prune_closed_streams(&active_streams).unwrap(), vec!["stale"]);
+        let remaining: Vec<String> = active_streams.0.read().unwrap().keys().cloned().collect();
+        assert_eq!(remaining, vec!["live"]);
+        assert!(prune_closed_streams(&active_streams).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_regenerated_reply_replaces_only_the_last_reply() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let conv = db::create_conversation(&conn, Some("Chat"), None).unwrap();
+        db::create_message(&conn, &conv.id, MessageRole::User, "First question", None).unwrap();
+        db::create_message(&conn, &conv.id, MessageRole::Assistant, "First answer", None).unwrap();
+        let prompt = db::create_message(&conn, &conv.id, MessageRole::User, "Second question", None).unwrap();
+        db::create_message(&conn, &conv.id, MessageRole::Assistant, "Bad answer", None).unwrap();
+
+        let (conversation, history, user_message, last_response) =
+            find_last_response(&conn, &conv.id).unwrap();
+        assert_eq!(conversation.id, conv.id);
+        assert_eq!(user_message.id, prompt.id);
+        assert_eq!(last_response.content, "Bad answer");
+        let history: Vec<&str> = history.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(history, vec!["First question", "First answer"]);
+
+        let contents = |conn: &rusqlite::Connection| -> Vec<String> {
+            db::get_conversation_messages(conn, &conv.id)
+                .unwrap()
+                .into_iter()
+                .map(|m| m.content)
+                .collect()
+        };
+        // The old reply stays until the new one is saved
+        assert_eq!(contents(&conn).len(), 4);
+
+        let metadata = MessageMetadata {
+            citations: Vec::new(),
+            model: Some("gpt-4o".to_string()),
+            usage: None,
+            tool_calls: Vec::new(),
+            thinking_content: None,
+            images: Vec::new(),
+        };
+        save_assistant_reply(&conn, &conv.id, Some(&last_response.id), "Better answer", &metadata).unwrap();
+        assert_eq!(
+            contents(&conn),
+            vec!["First question", "First answer", "Second question", "Better answer"]
+        );
+    }
+
+    #[test]
+    fn test_find_last_response_requires_trailing_assistant_message() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let conv = db::create_conversation(&conn, Some("Chat"), None).unwrap();
+        assert!(find_last_response(&conn, &conv.id).is_err());
+
+        db::create_message(&conn, &conv.id, MessageRole::User, "Unanswered", None).unwrap();
+        let err = find_last_response(&conn, &conv.id).unwrap_err();
+        assert!(err.contains("not an assistant response"));
+
+        assert_eq!(db::get_conversation_messages(&conn, &conv.id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_load_attached_images_from_path_and_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("diagram.PNG");
+        std::fs::write(&path, b"png bytes").unwrap();
+
+        let images = vec![
+            ChatImageInput {
+                path: Some(path.to_string_lossy().to_string()),
+                data: None,
+                mime_type: None,
+            },
+            ChatImageInput {
+                path: None,
+                data: Some("data:image/webp;base64,d2VicA==".to_string()),
+                mime_type: None,
+            },
+        ];
+        let (parts, attachments) = load_attached_images(&images).unwrap();
+
+        assert_eq!(parts[0].mime_type, "image/png");
+        assert_eq!(parts[0].data, BASE64_STANDARD.encode(b"png bytes"));
+        assert_eq!(attachments[0].size_bytes, 9);
+        assert!(attachments[0].path.is_some());
+
+        assert_eq!(parts[1].mime_type, "image/webp");
+        assert_eq!(parts[1].data, "d2VicA==");
+        assert_eq!(attachments[1].path, None);
+    }
+
+    #[test]
+    fn test_load_attached_images_rejects_unknown_type() {
+        let missing_type = ChatImageInput {
+            path: None,
+            data: Some("aGVsbG8=".to_string()),
+            mime_type: None,
+        };
+        assert!(load_attached_images(&[missing_type]).is_err());
+
+        let bad_data = ChatImageInput {
+            path: None,
+            data: Some("not base64!".to_string()),
+            mime_type: Some("image/png".to_string()),
+        };
+        assert!(load_attached_images(&[bad_data]).unwrap_err().contains("Invalid image data"));
+    }
+
+    #[test]
+    fn test_user_images_recorded_in_message_metadata() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let conv = db::create_conversation(&conn, Some("Chat"), None).unwrap();
+
+        let attachment = ImageAttachment {
+            path: None,
+            mime_type: "image/png".to_string(),
+            size_bytes: 42,
+        };
+        let metadata = user_message_metadata(vec![attachment.clone()]);
+        let message = db::create_message(&conn, &conv.id, MessageRole::User, "Look", metadata.as_ref()).unwrap();
+
+        let saved = db::get_conversation_messages(&conn, &conv.id).unwrap();
+        assert_eq!(saved[0].id, message.id);
+        assert_eq!(saved[0].metadata.as_ref().unwrap().images, vec![attachment]);
+        assert!(user_message_metadata(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_inline_thinking_is_kept_out_of_saved_content() {
+        let (content, thinking) =
+            separate_inline_thinking("<think>Check the notes first</think>\n\nThe launch is in March.", String::new());
+        assert_eq!(content, "The launch is in March.");
+        assert_eq!(thinking, "Check the notes first");
+
+        // Streamed reasoning comes first when both are present
+        let (_, thinking) = separate_inline_thinking("<think>inline</think>Answer", "streamed".to_string());
+        assert_eq!(thinking, "streamed\n\ninline");
+
+        let (content, thinking) = separate_inline_thinking("Plain answer", String::new());
+        assert_eq!((content.as_str(), thinking.as_str()), ("Plain answer", ""));
+    }
+
+    #[test]
+    fn test_history_summary_covers_exactly_the_dropped_range() {
+        use HistorySummaryPlan::*;
+
+        assert_eq!(plan_history_summary(None, 0, true), Skip);
+        assert_eq!(
+            plan_history_summary(None, 3, true),
+            Summarize { previous: None, start: 0, end: 3 }
+        );
+        assert_eq!(
+            plan_history_summary(Some(("older".into(), 3)), 3, true),
+            Reuse("older".into())
+        );
+        assert_eq!(
+            plan_history_summary(Some(("older".into(), 3)), 5, true),
+            Summarize { previous: Some("older".into()), start: 3, end: 5 }
+        );
+        // A summary reaching past the dropped range would repeat messages still sent
+        assert_eq!(
+            plan_history_summary(Some(("wider".into(), 8)), 5, true),
+            Summarize { previous: None, start: 0, end: 5 }
+        );
+        // Without a known window, a small drop is just truncated
+        assert_eq!(plan_history_summary(None, 2, false), Skip);
+        assert_eq!(
+            plan_history_summary(None, MIN_DROPPED_TO_SUMMARIZE, false),
+            Summarize { previous: None, start: 0, end: MIN_DROPPED_TO_SUMMARIZE }
+        );
+    }
+
+    #[test]
+    fn test_validate_reasoning_settings() {
+        assert!(validate_reasoning_settings(&ReasoningSettings::default()).is_ok());
+        assert!(validate_reasoning_settings(&ReasoningSettings {
+            reasoning_effort: Some("high".to_string()),
+            thinking_budget: Some(16000),
+        })
+        .is_ok());
+
+        let bad_effort = ReasoningSettings {
+            reasoning_effort: Some("extreme".to_string()),
+            thinking_budget: None,
+        };
+        assert!(validate_reasoning_settings(&bad_effort).unwrap_err().contains("extreme"));
+
+        let tiny_budget = ReasoningSettings {
+            reasoning_effort: None,
+            thinking_budget: Some(100),
+        };
+        assert!(validate_reasoning_settings(&tiny_budget).is_err());
+    }
+
+    #[test]
+    fn test_provider_chain_follows_fallback_order() {
+        let mut config = crate::ai::AIConfig::new();
+        for provider in &mut config.providers {
+            provider.is_enabled = ["ollama", "openai", "anthropic"].contains(&provider.id.as_str());
+            if provider.id == "ollama" {
+                provider.selected_model = Some("llama3".to_string());
+            }
+        }
+        config.default_provider = Some("ollama".to_string());
+        // Disabled, unknown and repeated ids are skipped
+        config.fallback_provider_ids = ["google", "anthropic", "missing", "ollama", "openai"]
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+
+        let chain = get_chat_provider_chain(&config).unwrap();
+        let ids: Vec<&str> = chain.iter().map(|(_, p)| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["ollama", "anthropic", "openai"]);
+        assert_eq!(chain[0].0, "llama3");
+    }
+}
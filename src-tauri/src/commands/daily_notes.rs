@@ -1,7 +1,14 @@
+//! Periodic notes: daily, weekly and monthly notes kept in their own system folders
+//! and titled by the period they cover.
+
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
 use tauri::State;
 
+use crate::db::connection::get_connection;
 use crate::db::folders as folders_db;
 use crate::db::notes as notes_db;
+use crate::db::settings as settings_db;
 use crate::models::{CreateFolderInput, CreateNoteInput, Folder, Note};
 use crate::vault::sync as vault_sync;
 use crate::{AppPool, AppSearchIndex};
@@ -9,58 +16,173 @@ use crate::{AppPool, AppSearchIndex};
 /// The name of the system Daily Notes folder
 pub const DAILY_NOTES_FOLDER_NAME: &str = "Daily Notes";
 
+/// The name of the system Weekly Notes folder
+pub const WEEKLY_NOTES_FOLDER_NAME: &str = "Weekly Notes";
+
+/// The name of the system Monthly Notes folder
+pub const MONTHLY_NOTES_FOLDER_NAME: &str = "Monthly Notes";
+
+/// Settings key for the template applied to new daily notes
+pub const DAILY_NOTE_TEMPLATE_SETTING: &str = "daily_note_template";
+
+/// The period a periodic note covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotePeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl NotePeriod {
+    pub fn folder_name(&self) -> &'static str {
+        match self {
+            NotePeriod::Daily => DAILY_NOTES_FOLDER_NAME,
+            NotePeriod::Weekly => WEEKLY_NOTES_FOLDER_NAME,
+            NotePeriod::Monthly => MONTHLY_NOTES_FOLDER_NAME,
+        }
+    }
+
+    pub fn template_setting(&self) -> &'static str {
+        match self {
+            NotePeriod::Daily => DAILY_NOTE_TEMPLATE_SETTING,
+            NotePeriod::Weekly => "weekly_note_template",
+            NotePeriod::Monthly => "monthly_note_template",
+        }
+    }
+
+    /// First day of the period containing `date`. Weeks start on Monday (ISO 8601).
+    pub fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            NotePeriod::Daily => date,
+            NotePeriod::Weekly => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            NotePeriod::Monthly => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// Start of the period `steps` periods away from the one containing `date`
+    pub fn offset(&self, date: NaiveDate, steps: i32) -> NaiveDate {
+        let start = self.start_of(date);
+        match self {
+            NotePeriod::Daily => start + Duration::days(steps as i64),
+            NotePeriod::Weekly => start + Duration::weeks(steps as i64),
+            NotePeriod::Monthly if steps >= 0 => start + Months::new(steps as u32),
+            NotePeriod::Monthly => start - Months::new(steps.unsigned_abs()),
+        }
+    }
+
+    /// Note title for the period containing `date`:
+    /// `2025-03-14` (daily), `2025-W11` (ISO week) or `2025-03` (monthly)
+    pub fn title_for(&self, date: NaiveDate) -> String {
+        match self {
+            NotePeriod::Daily => date.format("%Y-%m-%d").to_string(),
+            NotePeriod::Weekly => {
+                let week = date.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            NotePeriod::Monthly => date.format("%Y-%m").to_string(),
+        }
+    }
+
+    /// Parse a note title back into the start of the period it names
+    pub fn parse_title(&self, title: &str) -> Option<NaiveDate> {
+        match self {
+            NotePeriod::Daily => {
+                if !is_valid_date_format(title) {
+                    return None;
+                }
+                NaiveDate::parse_from_str(title, "%Y-%m-%d").ok()
+            }
+            NotePeriod::Weekly => {
+                let (year, week) = title.split_once("-W")?;
+                if year.len() != 4 || week.len() != 2 {
+                    return None;
+                }
+                NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, Weekday::Mon)
+            }
+            NotePeriod::Monthly => {
+                let (year, month) = title.split_once('-')?;
+                if year.len() != 4 || month.len() != 2 {
+                    return None;
+                }
+                NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, 1)
+            }
+        }
+    }
+
+    /// Parse a `YYYY-MM-DD` date, or a title of this period, into the period's start
+    fn parse_date_or_title(&self, value: &str) -> Result<NaiveDate, String> {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .map(|d| self.start_of(d))
+            .or_else(|| self.parse_title(value))
+            .ok_or_else(|| format!("Invalid date: {}", value))
+    }
+}
+
 /// Get the Daily Notes folder, creating it if it doesn't exist
 #[tauri::command]
 pub fn get_or_create_daily_notes_folder(pool: State<AppPool>) -> Result<Folder, String> {
+    get_or_create_periodic_notes_folder(pool, NotePeriod::Daily)
+}
+
+/// Get the folder for a kind of periodic note, creating it if it doesn't exist
+#[tauri::command]
+pub fn get_or_create_periodic_notes_folder(pool: State<AppPool>, period: NotePeriod) -> Result<Folder, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
-    // Check if the Daily Notes folder already exists
+    // Check if the folder already exists
     let all_folders = folders_db::get_all_folders(&conn).map_err(|e| e.to_string())?;
     
-    if let Some(folder) = all_folders.iter().find(|f| f.name == DAILY_NOTES_FOLDER_NAME && f.parent_id.is_none()) {
+    if let Some(folder) = all_folders.iter().find(|f| f.name == period.folder_name() && f.parent_id.is_none()) {
         return Ok(folder.clone());
     }
 
-    // Create the Daily Notes folder
+    // Create the folder
     let input = CreateFolderInput {
-        name: DAILY_NOTES_FOLDER_NAME.to_string(),
+        name: period.folder_name().to_string(),
         parent_id: None,
     };
     let folder = folders_db::create_folder(&conn, input).map_err(|e| e.to_string())?;
     
     // Create the folder on disk
     if let Err(e) = crate::commands::folders::create_folder_on_disk_from_pool(pool_ref, &folder) {
-        log::warn!("Failed to create Daily Notes folder on disk: {}", e);
+        log::warn!("Failed to create {} folder on disk: {}", period.folder_name(), e);
     }
     
     Ok(folder)
 }
 
-/// Get the Daily Notes folder ID (creates if doesn't exist)
-fn get_daily_notes_folder_id(conn: &rusqlite::Connection) -> Result<String, String> {
+/// Get the folder ID for a kind of periodic note (creates if doesn't exist)
+fn get_periodic_notes_folder_id(conn: &rusqlite::Connection, period: NotePeriod) -> Result<String, String> {
     let all_folders = folders_db::get_all_folders(conn).map_err(|e| e.to_string())?;
     
-    if let Some(folder) = all_folders.iter().find(|f| f.name == DAILY_NOTES_FOLDER_NAME && f.parent_id.is_none()) {
+    if let Some(folder) = all_folders.iter().find(|f| f.name == period.folder_name() && f.parent_id.is_none()) {
         return Ok(folder.id.clone());
     }
 
-    // Create the Daily Notes folder
+    // Create the folder
     let input = CreateFolderInput {
-        name: DAILY_NOTES_FOLDER_NAME.to_string(),
+        name: period.folder_name().to_string(),
         parent_id: None,
     };
     let folder = folders_db::create_folder(conn, input).map_err(|e| e.to_string())?;
     Ok(folder.id)
 }
 
+/// Get the Daily Notes folder ID (creates if doesn't exist)
+fn get_daily_notes_folder_id(conn: &rusqlite::Connection) -> Result<String, String> {
+    get_periodic_notes_folder_id(conn, NotePeriod::Daily)
+}
+
 /// Get a daily note for a specific date (YYYY-MM-DD format)
 #[tauri::command]
 pub fn get_daily_note(pool: State<AppPool>, date: String) -> Result<Option<Note>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
     // Get the Daily Notes folder ID
     let folder_id = get_daily_notes_folder_id(&conn)?;
@@ -72,7 +194,23 @@ pub fn get_daily_note(pool: State<AppPool>, date: String) -> Result<Option<Note>
     Ok(daily_note)
 }
 
-/// Create a daily note for a specific date with initial content
+/// Get the periodic note covering a date (YYYY-MM-DD format)
+#[tauri::command]
+pub fn get_periodic_note(pool: State<AppPool>, period: NotePeriod, date: String) -> Result<Option<Note>, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
+
+    let title = period.title_for(period.parse_date_or_title(&date)?);
+    let folder_id = get_periodic_notes_folder_id(&conn, period)?;
+    let notes = notes_db::get_notes_in_folder(&conn, Some(&folder_id)).map_err(|e| e.to_string())?;
+    Ok(notes.into_iter().find(|n| n.title == title))
+}
+
+/// Create a daily note for a specific date with initial content.
+///
+/// When no content is given, the daily note template from settings is expanded
+/// and used instead. An existing note for the date is returned unchanged.
 #[tauri::command]
 pub fn create_daily_note(
     pool: State<AppPool>,
@@ -80,46 +218,156 @@ pub fn create_daily_note(
     date: String,
     content: Option<String>,
     content_html: Option<String>,
+) -> Result<Note, String> {
+    create_periodic_note(pool, search_index, NotePeriod::Daily, date, content, content_html)
+}
+
+/// Create the periodic note covering a date (YYYY-MM-DD format) with initial content.
+///
+/// When no content is given, the period's template from settings is expanded
+/// and used instead. An existing note for the period is returned unchanged.
+#[tauri::command]
+pub fn create_periodic_note(
+    pool: State<AppPool>,
+    search_index: State<AppSearchIndex>,
+    period: NotePeriod,
+    date: String,
+    content: Option<String>,
+    content_html: Option<String>,
 ) -> Result<Note, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
-    // Get or create the Daily Notes folder
-    let folder_id = get_daily_notes_folder_id(&conn)?;
-    
-    // Check if a note for this date already exists
-    let existing_notes = notes_db::get_notes_in_folder(&conn, Some(&folder_id)).map_err(|e| e.to_string())?;
-    if let Some(existing) = existing_notes.into_iter().find(|n| n.title == date) {
-        return Ok(existing);
+    let (note, created) = create_periodic_note_in_db(&conn, period, &date, content, content_html)?;
+    if !created {
+        return Ok(note);
     }
-    
-    // Create the daily note
-    let input = CreateNoteInput {
-        title: date.clone(),
-        content: content.clone(),
-        content_html,
-        folder_id: Some(folder_id),
-    };
-    
-    let note = notes_db::create_note(&conn, input).map_err(|e| e.to_string())?;
 
     // Add to search index
     let search_guard = search_index.0.read().unwrap();
     if let Some(ref index) = *search_guard {
         if let Err(e) = index.add_note(&note.id, &note.title, note.content.as_deref()) {
-            log::warn!("Failed to add daily note to search index: {}", e);
+            log::warn!("Failed to add periodic note to search index: {}", e);
         }
     }
     
     // Sync to filesystem
     if let Err(e) = vault_sync::sync_note_to_file(pool_ref, &note.id) {
-        log::warn!("Failed to sync daily note to filesystem: {}", e);
+        log::warn!("Failed to sync periodic note to filesystem: {}", e);
     }
 
     Ok(note)
 }
 
+/// Create the note row unless one already exists for the period.
+/// Returns the note and whether it was newly created.
+fn create_periodic_note_in_db(
+    conn: &rusqlite::Connection,
+    period: NotePeriod,
+    date: &str,
+    content: Option<String>,
+    content_html: Option<String>,
+) -> Result<(Note, bool), String> {
+    // Daily notes keep whatever title they were asked for; other periods are normalized
+    let (title, start) = match period {
+        NotePeriod::Daily => (date.to_string(), NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()),
+        _ => {
+            let start = period.parse_date_or_title(date)?;
+            (period.title_for(start), Some(start))
+        }
+    };
+
+    // Get or create the period's folder
+    let folder_id = get_periodic_notes_folder_id(conn, period)?;
+    
+    // Check if a note for this period already exists
+    let existing_notes = notes_db::get_notes_in_folder(conn, Some(&folder_id)).map_err(|e| e.to_string())?;
+    if let Some(existing) = existing_notes.into_iter().find(|n| n.title == title) {
+        return Ok((existing, false));
+    }
+
+    // Fall back to the configured template when no content was provided.
+    // HTML is left empty so the editor renders it from the markdown.
+    let (content, content_html) = match content.filter(|c| !c.is_empty()) {
+        Some(content) => (Some(content), content_html),
+        None => {
+            let template = settings_db::get_setting(conn, period.template_setting())
+                .map_err(|e| e.to_string())?
+                .filter(|t| !t.is_empty());
+            match (template, start) {
+                (Some(template), Some(start)) => (Some(expand_periodic_template(&template, period, start)), None),
+                _ => (None, content_html),
+            }
+        }
+    };
+    
+    // Create the note
+    let input = CreateNoteInput {
+        title,
+        content,
+        content_html,
+        folder_id: Some(folder_id),
+    };
+    
+    let note = notes_db::create_note(conn, input).map_err(|e| e.to_string())?;
+    Ok((note, true))
+}
+
+/// Expand the tokens in a periodic note template for the period containing `date`:
+/// `{title}`, `{date}` (first day of the period), `{weekday}`, `{previous_link}` and
+/// `{next_link}`. Daily templates also accept `{yesterday_link}` and `{tomorrow_link}`.
+/// Links are wikilinks to the adjacent periods' notes, which are titled by period.
+pub fn expand_periodic_template(template: &str, period: NotePeriod, date: NaiveDate) -> String {
+    let start = period.start_of(date);
+    let link = |steps: i32| format!("[[{}]]", period.title_for(period.offset(start, steps)));
+    template
+        .replace("{title}", &period.title_for(start))
+        .replace("{date}", &start.format("%Y-%m-%d").to_string())
+        .replace("{weekday}", &start.format("%A").to_string())
+        .replace("{previous_link}", &link(-1))
+        .replace("{next_link}", &link(1))
+        .replace("{yesterday_link}", &link(-1))
+        .replace("{tomorrow_link}", &link(1))
+}
+
+/// Get the template applied to new daily notes, if one is set
+#[tauri::command]
+pub fn get_daily_note_template(pool: State<AppPool>) -> Result<Option<String>, String> {
+    get_periodic_note_template(pool, NotePeriod::Daily)
+}
+
+/// Set the template applied to new daily notes. An empty template removes it.
+#[tauri::command]
+pub fn set_daily_note_template(pool: State<AppPool>, template: String) -> Result<(), String> {
+    set_periodic_note_template(pool, NotePeriod::Daily, template)
+}
+
+/// Get the template applied to new notes of a period, if one is set
+#[tauri::command]
+pub fn get_periodic_note_template(pool: State<AppPool>, period: NotePeriod) -> Result<Option<String>, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
+
+    settings_db::get_setting(&conn, period.template_setting()).map_err(|e| e.to_string())
+}
+
+/// Set the template applied to new notes of a period. An empty template removes it.
+#[tauri::command]
+pub fn set_periodic_note_template(pool: State<AppPool>, period: NotePeriod, template: String) -> Result<(), String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
+
+    if template.is_empty() {
+        settings_db::delete_setting(&conn, period.template_setting()).map_err(|e| e.to_string())?;
+    } else {
+        settings_db::set_setting(&conn, period.template_setting(), &template).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
 /// Get the adjacent daily note (previous or next)
 /// Returns the note if found, None if no adjacent note exists
 #[tauri::command]
@@ -127,31 +375,44 @@ pub fn get_adjacent_daily_note(
     pool: State<AppPool>,
     date: String,
     direction: String,
+) -> Result<Option<Note>, String> {
+    get_adjacent_periodic_note(pool, NotePeriod::Daily, date, direction)
+}
+
+/// Get the nearest existing periodic note before or after the period containing `date`
+/// (YYYY-MM-DD, or the period's title). Returns None if no adjacent note exists.
+#[tauri::command]
+pub fn get_adjacent_periodic_note(
+    pool: State<AppPool>,
+    period: NotePeriod,
+    date: String,
+    direction: String,
 ) -> Result<Option<Note>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
-    // Get the Daily Notes folder ID
-    let folder_id = get_daily_notes_folder_id(&conn)?;
-    
-    // Get all notes in the Daily Notes folder
-    let mut notes = notes_db::get_notes_in_folder(&conn, Some(&folder_id)).map_err(|e| e.to_string())?;
-    
-    // Filter to only valid date-formatted titles and sort
-    notes.retain(|n| is_valid_date_format(&n.title));
-    
-    match direction.as_str() {
-        "prev" => {
-            // Sort descending and find the first note with a date before the current date
-            notes.sort_by(|a, b| b.title.cmp(&a.title));
-            Ok(notes.into_iter().find(|n| n.title < date))
-        }
-        "next" => {
-            // Sort ascending and find the first note with a date after the current date
-            notes.sort_by(|a, b| a.title.cmp(&b.title));
-            Ok(notes.into_iter().find(|n| n.title > date))
-        }
+    let current = period.parse_date_or_title(&date)?;
+    let folder_id = get_periodic_notes_folder_id(&conn, period)?;
+    let notes = notes_db::get_notes_in_folder(&conn, Some(&folder_id)).map_err(|e| e.to_string())?;
+    find_adjacent_note(notes, period, current, &direction)
+}
+
+/// Pick the note whose period is closest to `current` in the given direction
+fn find_adjacent_note(
+    notes: Vec<Note>,
+    period: NotePeriod,
+    current: NaiveDate,
+    direction: &str,
+) -> Result<Option<Note>, String> {
+    // Only notes with valid titles for the period take part, ordered by period start
+    let dated = notes
+        .into_iter()
+        .filter_map(|n| period.parse_title(&n.title).map(|start| (start, n)));
+
+    match direction {
+        "prev" => Ok(dated.filter(|(start, _)| *start < current).max_by_key(|(start, _)| *start).map(|(_, n)| n)),
+        "next" => Ok(dated.filter(|(start, _)| *start > current).min_by_key(|(start, _)| *start).map(|(_, n)| n)),
         _ => Err(format!("Invalid direction: {}. Use 'prev' or 'next'", direction)),
     }
 }
@@ -161,7 +422,7 @@ pub fn get_adjacent_daily_note(
 pub fn get_all_daily_notes(pool: State<AppPool>) -> Result<Vec<Note>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
     // Get the Daily Notes folder ID
     let folder_id = get_daily_notes_folder_id(&conn)?;
@@ -181,7 +442,7 @@ pub fn get_all_daily_notes(pool: State<AppPool>) -> Result<Vec<Note>, String> {
 pub fn is_daily_note(pool: State<AppPool>, note_id: String) -> Result<bool, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
     // Get the note
     let note = notes_db::get_note(&conn, &note_id).map_err(|e| e.to_string())?;
@@ -251,6 +512,42 @@ fn is_valid_date_format(s: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::connection::init_test_pool;
+
+    #[test]
+    fn test_expand_daily_template() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let template = "# {weekday}, {date}\n\n{yesterday_link} | {tomorrow_link}\n\n## Agenda\n\n## Tasks\n";
+        assert_eq!(
+            expand_periodic_template(template, NotePeriod::Daily, date),
+            "# Saturday, 2025-03-01\n\n[[2025-02-28]] | [[2025-03-02]]\n\n## Agenda\n\n## Tasks\n"
+        );
+        // Unknown tokens are left alone
+        assert_eq!(expand_periodic_template("{mood}", NotePeriod::Daily, date), "{mood}");
+    }
+
+    #[test]
+    fn test_template_applied_only_on_first_creation() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        settings_db::set_setting(&conn, DAILY_NOTE_TEMPLATE_SETTING, "## {weekday}\n{yesterday_link}").unwrap();
+
+        let (note, created) = create_periodic_note_in_db(&conn, NotePeriod::Daily, "2025-12-20", None, None).unwrap();
+        assert!(created);
+        assert_eq!(note.content.as_deref(), Some("## Saturday\n[[2025-12-19]]"));
+
+        // Explicit content wins over the template
+        let (other, _) =
+            create_periodic_note_in_db(&conn, NotePeriod::Daily, "2025-12-21", Some("Custom".to_string()), None).unwrap();
+        assert_eq!(other.content.as_deref(), Some("Custom"));
+
+        // An existing daily note is returned as-is, even after the template changes
+        settings_db::set_setting(&conn, DAILY_NOTE_TEMPLATE_SETTING, "Changed").unwrap();
+        let (again, created) = create_periodic_note_in_db(&conn, NotePeriod::Daily, "2025-12-20", None, None).unwrap();
+        assert!(!created);
+        assert_eq!(again.id, note.id);
+        assert_eq!(again.content.as_deref(), Some("## Saturday\n[[2025-12-19]]"));
+    }
 
     #[test]
     fn test_is_valid_date_format() {
@@ -267,5 +564,66 @@ mod tests {
         assert!(!is_valid_date_format("December 20, 2025")); // Wrong format
         assert!(!is_valid_date_format("")); // Empty
     }
-}
 
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_period_titles_and_starts() {
+        // Wednesday 2025-01-01 falls in ISO week 1, which starts on Monday 2024-12-30
+        assert_eq!(NotePeriod::Weekly.start_of(date(2025, 1, 1)), date(2024, 12, 30));
+        assert_eq!(NotePeriod::Weekly.title_for(date(2025, 1, 1)), "2025-W01");
+        assert_eq!(NotePeriod::Weekly.title_for(date(2024, 12, 30)), "2025-W01");
+        // 2021-01-03 (Sunday) still belongs to the last ISO week of 2020
+        assert_eq!(NotePeriod::Weekly.title_for(date(2021, 1, 3)), "2020-W53");
+        assert_eq!(NotePeriod::Monthly.start_of(date(2025, 2, 28)), date(2025, 2, 1));
+        assert_eq!(NotePeriod::Monthly.title_for(date(2025, 2, 28)), "2025-02");
+
+        assert_eq!(NotePeriod::Weekly.parse_title("2025-W01"), Some(date(2024, 12, 30)));
+        assert_eq!(NotePeriod::Weekly.parse_title("2020-W53"), Some(date(2020, 12, 28)));
+        assert_eq!(NotePeriod::Weekly.parse_title("2025-W54"), None);
+        assert_eq!(NotePeriod::Monthly.parse_title("2025-12"), Some(date(2025, 12, 1)));
+        assert_eq!(NotePeriod::Monthly.parse_title("2025-13"), None);
+        assert_eq!(NotePeriod::Daily.parse_title("2025-W01"), None);
+    }
+
+    #[test]
+    fn test_period_offsets_across_year_boundaries() {
+        assert_eq!(NotePeriod::Daily.offset(date(2024, 12, 31), 1), date(2025, 1, 1));
+        assert_eq!(NotePeriod::Weekly.title_for(NotePeriod::Weekly.offset(date(2020, 12, 30), 1)), "2021-W01");
+        assert_eq!(NotePeriod::Weekly.title_for(NotePeriod::Weekly.offset(date(2025, 1, 1), -1)), "2024-W52");
+        assert_eq!(NotePeriod::Monthly.offset(date(2024, 12, 15), 1), date(2025, 1, 1));
+        assert_eq!(NotePeriod::Monthly.offset(date(2025, 1, 31), -1), date(2024, 12, 1));
+
+        let weekly = expand_periodic_template("{title}: {previous_link} {next_link} from {date}", NotePeriod::Weekly, date(2025, 1, 2));
+        assert_eq!(weekly, "2025-W01: [[2024-W52]] [[2025-W02]] from 2024-12-30");
+        let monthly = expand_periodic_template("{previous_link} {title} {next_link}", NotePeriod::Monthly, date(2025, 1, 20));
+        assert_eq!(monthly, "[[2024-12]] 2025-01 [[2025-02]]");
+    }
+
+    #[test]
+    fn test_adjacent_periodic_notes() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        for title in ["2024-W51", "2025-W02", "2024-11", "2025-01"] {
+            let period = if title.contains('W') { NotePeriod::Weekly } else { NotePeriod::Monthly };
+            create_periodic_note_in_db(&conn, period, title, None, None).unwrap();
+        }
+        // Creating by any date in the week reuses the existing note
+        let (_, created) = create_periodic_note_in_db(&conn, NotePeriod::Weekly, "2025-01-08", None, None).unwrap();
+        assert!(!created);
+
+        let adjacent = |period: NotePeriod, current: NaiveDate, direction: &str| {
+            let folder_id = get_periodic_notes_folder_id(&conn, period).unwrap();
+            let notes = notes_db::get_notes_in_folder(&conn, Some(&folder_id)).unwrap();
+            find_adjacent_note(notes, period, current, direction).unwrap().map(|n| n.title)
+        };
+        let week_one = NotePeriod::Weekly.start_of(date(2025, 1, 1));
+        assert_eq!(adjacent(NotePeriod::Weekly, week_one, "prev").as_deref(), Some("2024-W51"));
+        assert_eq!(adjacent(NotePeriod::Weekly, week_one, "next").as_deref(), Some("2025-W02"));
+        assert_eq!(adjacent(NotePeriod::Monthly, date(2024, 12, 1), "next").as_deref(), Some("2025-01"));
+        assert_eq!(adjacent(NotePeriod::Monthly, date(2025, 1, 1), "prev").as_deref(), Some("2024-11"));
+        assert_eq!(adjacent(NotePeriod::Monthly, date(2025, 1, 1), "next"), None);
+    }
+}
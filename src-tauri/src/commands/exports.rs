@@ -4,15 +4,17 @@
 
 use std::fs;
 use std::path::Path;
+use std::time::Instant;
 
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, State};
 
-use crate::ai::export_agent::{run_export_agent, ExportAgentResult};
+use crate::ai::export_agent::{run_export_agent, ExportAgentResult, ExportLimits};
 use crate::ai::{load_ai_config, CancellationToken};
-use crate::db::exports::{self, Export, ExportFormat};
+use crate::db::connection::get_connection;
+use crate::db::exports::{self, Export, ExportFormat, ExportGenerator, ExportRenderOptions, ExportSource};
 use crate::exports::{
-    docx_generator, generate_dated_filename, html_to_markdown, markdown_parser, pdf_generator, pptx_generator, xlsx_generator,
+    conversation, docx_generator, generate_dated_filename, html_to_markdown, markdown_parser, pdf_generator, pptx_generator, xlsx_generator,
     DocxExportOptions, ExportResult, PdfExportOptions, XlsxExportOptions,
 };
 use crate::vault::config::get_exports_dir;
@@ -23,7 +25,7 @@ use crate::{db, AgentExecutions, AppPool};
 pub fn list_exports(pool: State<AppPool>) -> Result<Vec<Export>, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     exports::get_all_exports(&conn).map_err(|e| e.to_string())
 }
@@ -33,7 +35,7 @@ pub fn list_exports(pool: State<AppPool>) -> Result<Vec<Export>, String> {
 pub fn list_exports_by_format(pool: State<AppPool>, format: String) -> Result<Vec<Export>, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     let format = ExportFormat::from_str(&format)
         .ok_or_else(|| format!("Invalid format: {}", format))?;
@@ -46,7 +48,7 @@ pub fn list_exports_by_format(pool: State<AppPool>, format: String) -> Result<Ve
 pub fn get_export(pool: State<AppPool>, id: String) -> Result<Option<Export>, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     exports::get_export(&conn, &id).map_err(|e| e.to_string())
 }
@@ -56,7 +58,7 @@ pub fn get_export(pool: State<AppPool>, id: String) -> Result<Option<Export>, St
 pub fn delete_export(pool: State<AppPool>, id: String) -> Result<bool, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     // Get the export to find the file path
     if let Some(export) = exports::get_export(&conn, &id).map_err(|e| e.to_string())? {
@@ -73,12 +75,58 @@ pub fn delete_export(pool: State<AppPool>, id: String) -> Result<bool, String> {
     }
 }
 
+/// Delete every export of a format (files and records), returning how many
+#[tauri::command]
+pub fn delete_exports_by_format(pool: State<AppPool>, format: String) -> Result<u32, String> {
+    let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+
+    let format = ExportFormat::from_str(&format)
+        .ok_or_else(|| format!("Invalid format: {}", format))?;
+
+    let deleted = exports::delete_exports_by_format(&conn, &format).map_err(|e| e.to_string())?;
+    remove_export_files(&deleted);
+
+    Ok(deleted.len() as u32)
+}
+
+/// Delete exports created more than `days` days ago (files and records),
+/// returning how many
+#[tauri::command]
+pub fn delete_exports_older_than(pool: State<AppPool>, days: u32) -> Result<u32, String> {
+    let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+    let deleted = exports::delete_exports_older_than(&conn, cutoff).map_err(|e| e.to_string())?;
+    remove_export_files(&deleted);
+
+    Ok(deleted.len() as u32)
+}
+
+/// Remove the files of deleted exports
+///
+/// The records are already gone, so a file that can't be removed is logged
+/// rather than failing the batch.
+fn remove_export_files(paths: &[String]) {
+    for path in paths {
+        let file_path = Path::new(path);
+        if file_path.exists() {
+            if let Err(e) = fs::remove_file(file_path) {
+                log::warn!("Failed to delete export file {}: {}", path, e);
+            }
+        }
+    }
+}
+
 /// Open an export file in the default system application
 #[tauri::command]
 pub fn open_export(id: String, pool: State<AppPool>) -> Result<(), String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     let export = exports::get_export(&conn, &id)
         .map_err(|e| e.to_string())?
@@ -167,9 +215,10 @@ pub fn export_note_to_pdf(
     note_id: String,
     title: Option<String>,
 ) -> Result<ExportResult, String> {
+    let started = Instant::now();
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     // Get the note
     let note = db::notes::get_note(&conn, &note_id)
@@ -206,6 +255,12 @@ pub fn export_note_to_pdf(
         source_note_ids: vec![note_id],
         file_size: Some(result.file_size as i64),
         path: result.path.clone(),
+        duration_ms: Some(started.elapsed().as_millis() as i64),
+        generator: ExportGenerator::Mechanical,
+        model: None,
+        source: ExportSource::Notes,
+        conversation_id: None,
+        options: options.to_recorded(),
     };
 
     exports::create_export(&conn, export_input).map_err(|e| e.to_string())?;
@@ -220,9 +275,10 @@ pub fn export_note_to_docx(
     note_id: String,
     title: Option<String>,
 ) -> Result<ExportResult, String> {
+    let started = Instant::now();
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     // Get the note
     let note = db::notes::get_note(&conn, &note_id)
@@ -259,6 +315,12 @@ pub fn export_note_to_docx(
         source_note_ids: vec![note_id],
         file_size: Some(result.file_size as i64),
         path: result.path.clone(),
+        duration_ms: Some(started.elapsed().as_millis() as i64),
+        generator: ExportGenerator::Mechanical,
+        model: None,
+        source: ExportSource::Notes,
+        conversation_id: None,
+        options: options.to_recorded(),
     };
 
     exports::create_export(&conn, export_input).map_err(|e| e.to_string())?;
@@ -273,10 +335,12 @@ pub fn export_notes_to_pdf(
     note_ids: Vec<String>,
     title: String,
     page_break_between_notes: Option<bool>,
+    highlight_code: Option<bool>,
 ) -> Result<ExportResult, String> {
+    let started = Instant::now();
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     if note_ids.is_empty() {
         return Err("No notes selected".to_string());
@@ -304,6 +368,7 @@ pub fn export_notes_to_pdf(
     // Generate PDF
     let options = PdfExportOptions {
         page_break_between_notes: page_break_between_notes.unwrap_or(true),
+        highlight_code: highlight_code.unwrap_or(true),
         ..Default::default()
     };
     let result = pdf_generator::generate_pdf_from_notes(&notes_data, &title, &output_path, &options)
@@ -317,6 +382,12 @@ pub fn export_notes_to_pdf(
         source_note_ids: note_ids,
         file_size: Some(result.file_size as i64),
         path: result.path.clone(),
+        duration_ms: Some(started.elapsed().as_millis() as i64),
+        generator: ExportGenerator::Mechanical,
+        model: None,
+        source: ExportSource::Notes,
+        conversation_id: None,
+        options: options.to_recorded(),
     };
 
     exports::create_export(&conn, export_input).map_err(|e| e.to_string())?;
@@ -331,10 +402,12 @@ pub fn export_notes_to_docx(
     note_ids: Vec<String>,
     title: String,
     page_break_between_notes: Option<bool>,
+    highlight_code: Option<bool>,
 ) -> Result<ExportResult, String> {
+    let started = Instant::now();
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     if note_ids.is_empty() {
         return Err("No notes selected".to_string());
@@ -362,6 +435,7 @@ pub fn export_notes_to_docx(
     // Generate DOCX
     let options = DocxExportOptions {
         page_break_between_notes: page_break_between_notes.unwrap_or(true),
+        highlight_code: highlight_code.unwrap_or(true),
         ..Default::default()
     };
     let result = docx_generator::generate_docx_from_notes(&notes_data, &title, &output_path, &options)
@@ -375,6 +449,12 @@ pub fn export_notes_to_docx(
         source_note_ids: note_ids,
         file_size: Some(result.file_size as i64),
         path: result.path.clone(),
+        duration_ms: Some(started.elapsed().as_millis() as i64),
+        generator: ExportGenerator::Mechanical,
+        model: None,
+        source: ExportSource::Notes,
+        conversation_id: None,
+        options: options.to_recorded(),
     };
 
     exports::create_export(&conn, export_input).map_err(|e| e.to_string())?;
@@ -389,9 +469,10 @@ pub fn export_content_to_xlsx(
     content: String,
     title: String,
 ) -> Result<ExportResult, String> {
+    let started = Instant::now();
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     // Generate filename and path
     let exports_dir = get_exports_dir().map_err(|e| e.to_string())?;
@@ -411,6 +492,12 @@ pub fn export_content_to_xlsx(
         source_note_ids: vec![],
         file_size: Some(result.file_size as i64),
         path: result.path.clone(),
+        duration_ms: Some(started.elapsed().as_millis() as i64),
+        generator: ExportGenerator::Mechanical,
+        model: None,
+        source: ExportSource::Content,
+        conversation_id: None,
+        options: ExportRenderOptions::default(),
     };
 
     exports::create_export(&conn, export_input).map_err(|e| e.to_string())?;
@@ -418,6 +505,122 @@ pub fn export_content_to_xlsx(
     Ok(result)
 }
 
+/// A conversation's title, its transcript as markdown, and the notes it cites
+fn conversation_transcript(
+    conn: &rusqlite::Connection,
+    conversation_id: &str,
+    include_thinking: bool,
+) -> Result<(String, String, Vec<String>), String> {
+    let conv = db::get_conversation(conn, conversation_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Conversation not found: {}", conversation_id))?;
+    let messages = db::get_conversation_messages(conn, conversation_id).map_err(|e| e.to_string())?;
+
+    let title = conv.title.unwrap_or_else(|| "Conversation".to_string());
+    let markdown = conversation::conversation_to_markdown(&title, &messages, include_thinking);
+
+    let mut cited_note_ids: Vec<String> = Vec::new();
+    for citation in messages.iter().filter_map(|m| m.metadata.as_ref()).flat_map(|m| &m.citations) {
+        if !cited_note_ids.contains(&citation.note_id) {
+            cited_note_ids.push(citation.note_id.clone());
+        }
+    }
+
+    Ok((title, markdown, cited_note_ids))
+}
+
+/// File extension for a conversation export, or an error for formats
+/// conversations can't be exported as
+fn conversation_extension(format: &ExportFormat) -> Result<&'static str, String> {
+    match format {
+        ExportFormat::Markdown => Ok("md"),
+        ExportFormat::Pdf => Ok("pdf"),
+        ExportFormat::Docx => Ok("docx"),
+        other => Err(format!("Conversations can't be exported as {}", other)),
+    }
+}
+
+/// Render a conversation transcript to `output_path`
+fn render_transcript(
+    markdown: &str,
+    title: &str,
+    format: &ExportFormat,
+    options: &ExportRenderOptions,
+    output_path: &Path,
+) -> Result<ExportResult, String> {
+    match format {
+        ExportFormat::Markdown => conversation::generate_markdown(markdown, title, output_path),
+        ExportFormat::Pdf => {
+            let parsed = markdown_parser::parse_markdown(markdown);
+            pdf_generator::generate_pdf(&parsed, title, output_path, &PdfExportOptions::from_recorded(options))
+        }
+        ExportFormat::Docx => {
+            let parsed = markdown_parser::parse_markdown(markdown);
+            docx_generator::generate_docx(&parsed, title, output_path, &DocxExportOptions::from_recorded(options))
+        }
+        other => return Err(format!("Conversations can't be exported as {}", other)),
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Render a conversation and write it to `exports_dir` in the given format,
+/// recording the export. Notes cited in the conversation become its sources.
+fn export_conversation_to_dir(
+    conn: &rusqlite::Connection,
+    conversation_id: &str,
+    format: ExportFormat,
+    include_thinking: bool,
+    exports_dir: &Path,
+) -> Result<ExportResult, String> {
+    let started = Instant::now();
+    let extension = conversation_extension(&format)?;
+    let (title, markdown, source_note_ids) = conversation_transcript(conn, conversation_id, include_thinking)?;
+
+    let options = ExportRenderOptions {
+        include_thinking: Some(include_thinking),
+        ..Default::default()
+    };
+    let output_path = exports_dir.join(generate_dated_filename(&title, extension));
+    let result = render_transcript(&markdown, &title, &format, &options, &output_path)?;
+
+    let export_input = exports::CreateExportInput {
+        filename: result.filename.clone(),
+        title,
+        format,
+        source_note_ids,
+        file_size: Some(result.file_size as i64),
+        path: result.path.clone(),
+        duration_ms: Some(started.elapsed().as_millis() as i64),
+        generator: ExportGenerator::Mechanical,
+        model: None,
+        source: ExportSource::Conversation,
+        conversation_id: Some(conversation_id.to_string()),
+        options,
+    };
+
+    exports::create_export(conn, export_input).map_err(|e| e.to_string())?;
+
+    Ok(result)
+}
+
+/// Export a chat conversation to Markdown, PDF or DOCX
+#[tauri::command]
+pub fn export_conversation(
+    pool: State<AppPool>,
+    conversation_id: String,
+    format: String,
+    include_thinking: Option<bool>,
+) -> Result<ExportResult, String> {
+    let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+
+    let format = ExportFormat::from_str(&format).ok_or_else(|| format!("Unknown export format: {}", format))?;
+    let exports_dir = get_exports_dir().map_err(|e| e.to_string())?;
+
+    export_conversation_to_dir(&conn, &conversation_id, format, include_thinking.unwrap_or(false), &exports_dir)
+}
+
 /// Export multiple notes to PPTX (PowerPoint)
 #[tauri::command]
 pub fn export_notes_to_pptx(
@@ -425,9 +628,10 @@ pub fn export_notes_to_pptx(
     note_ids: Vec<String>,
     title: String,
 ) -> Result<ExportResult, String> {
+    let started = Instant::now();
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     if note_ids.is_empty() {
         return Err("No notes selected".to_string());
@@ -473,6 +677,12 @@ pub fn export_notes_to_pptx(
         source_note_ids: note_ids,
         file_size: Some(result.file_size as i64),
         path: result.path.clone(),
+        duration_ms: Some(started.elapsed().as_millis() as i64),
+        generator: ExportGenerator::Mechanical,
+        model: None,
+        source: ExportSource::Notes,
+        conversation_id: None,
+        options: ExportRenderOptions::default(),
     };
 
     exports::create_export(&conn, export_input).map_err(|e| e.to_string())?;
@@ -480,6 +690,169 @@ pub fn export_notes_to_pptx(
     Ok(result)
 }
 
+/// Result of regenerating an export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegeneratedExport {
+    pub export: Export,
+    /// Source notes that have since been deleted and were left out
+    pub missing_note_ids: Vec<String>,
+}
+
+/// Regenerate an export from the current content of its sources
+///
+/// The file is replaced in place and the record updated. Note exports drop
+/// source notes that were deleted and fail if none are left; conversation
+/// exports are rendered from the conversation again.
+#[tauri::command]
+pub fn regenerate_export(pool: State<AppPool>, export_id: String) -> Result<RegeneratedExport, String> {
+    let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+
+    regenerate_export_file(&conn, &export_id)
+}
+
+/// Re-run the mechanical export behind an export record, replacing its file
+fn regenerate_export_file(conn: &rusqlite::Connection, export_id: &str) -> Result<RegeneratedExport, String> {
+    let started = Instant::now();
+
+    let export = exports::get_export(conn, export_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Export not found: {}", export_id))?;
+
+    if export.generator == Some(ExportGenerator::Agent) {
+        return Err("Exports written by the AI agent can't be regenerated; run the export agent again".to_string());
+    }
+
+    // Write next to the original and swap it in, so a failure leaves the old file intact
+    let output_path = Path::new(&export.path);
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let temp_path = output_path.with_file_name(format!(".{}.regenerating", export.filename));
+
+    let rendered = match export.source {
+        Some(ExportSource::Notes) => render_from_notes(conn, &export, &temp_path),
+        Some(ExportSource::Conversation) => render_from_conversation(conn, &export, &temp_path),
+        Some(ExportSource::Content) => {
+            Err("This export was made from pasted content and has no source to regenerate from".to_string())
+        }
+        None => Err("This export was created before its source was recorded; export it again instead".to_string()),
+    };
+    let (result, source_note_ids, missing_note_ids) = match rendered {
+        Ok(rendered) => rendered,
+        Err(e) => {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e);
+        }
+    };
+    fs::rename(&temp_path, output_path).map_err(|e| e.to_string())?;
+
+    let export = exports::update_export_file(
+        conn,
+        &export.id,
+        &source_note_ids,
+        Some(result.file_size as i64),
+        Some(started.elapsed().as_millis() as i64),
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(RegeneratedExport { export, missing_note_ids })
+}
+
+/// Render a note export from its source notes that still exist, returning
+/// the result, the notes used and the notes that were deleted
+fn render_from_notes(
+    conn: &rusqlite::Connection,
+    export: &Export,
+    output_path: &Path,
+) -> Result<(ExportResult, Vec<String>, Vec<String>), String> {
+    if export.source_note_ids.is_empty() {
+        return Err("This export has no source notes to regenerate from".to_string());
+    }
+
+    // Collect the notes that still exist (convert HTML to markdown)
+    let mut notes_data: Vec<(String, String)> = Vec::new();
+    let mut note_ids = Vec::new();
+    let mut missing_note_ids = Vec::new();
+    for note_id in &export.source_note_ids {
+        match db::notes::get_note(conn, note_id).map_err(|e| e.to_string())? {
+            Some(note) if !note.is_deleted => {
+                let content = if let Some(html) = &note.content_html {
+                    html_to_markdown::html_to_markdown(html)
+                } else {
+                    note.content.clone().unwrap_or_default()
+                };
+                notes_data.push((note.title, content));
+                note_ids.push(note_id.clone());
+            }
+            _ => missing_note_ids.push(note_id.clone()),
+        }
+    }
+    if notes_data.is_empty() {
+        return Err("All of this export's source notes have been deleted".to_string());
+    }
+
+    let combined_content = || {
+        notes_data
+            .iter()
+            .map(|(title, content)| format!("# {}\n\n{}", title, content))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    };
+    let title = export.title.as_str();
+    let pdf_options = || PdfExportOptions::from_recorded(&export.options);
+    let docx_options = || DocxExportOptions::from_recorded(&export.options);
+    let result = match export.format {
+        ExportFormat::Pdf if notes_data.len() == 1 => {
+            let parsed = markdown_parser::parse_markdown(&notes_data[0].1);
+            pdf_generator::generate_pdf(&parsed, title, output_path, &pdf_options())
+        }
+        ExportFormat::Pdf => pdf_generator::generate_pdf_from_notes(&notes_data, title, output_path, &pdf_options()),
+        ExportFormat::Docx if notes_data.len() == 1 => {
+            let parsed = markdown_parser::parse_markdown(&notes_data[0].1);
+            docx_generator::generate_docx(&parsed, title, output_path, &docx_options())
+        }
+        ExportFormat::Docx => {
+            docx_generator::generate_docx_from_notes(&notes_data, title, output_path, &docx_options())
+        }
+        ExportFormat::Pptx => {
+            let parsed = markdown_parser::parse_markdown(&combined_content());
+            pptx_generator::generate_pptx(&parsed, title, output_path, &pptx_generator::PptxExportOptions::default())
+        }
+        ExportFormat::Xlsx => {
+            xlsx_generator::generate_xlsx_from_markdown(&combined_content(), title, output_path, &XlsxExportOptions::default())
+        }
+        ExportFormat::Markdown => conversation::generate_markdown(&combined_content(), title, output_path),
+    }
+    .map_err(|e| e.to_string())?;
+
+    Ok((result, note_ids, missing_note_ids))
+}
+
+/// Render a conversation export from the conversation's current messages,
+/// returning the result and the notes it now cites
+fn render_from_conversation(
+    conn: &rusqlite::Connection,
+    export: &Export,
+    output_path: &Path,
+) -> Result<(ExportResult, Vec<String>, Vec<String>), String> {
+    let conversation_id = export
+        .conversation_id
+        .as_deref()
+        .ok_or("This export doesn't record which conversation it came from")?;
+    if db::get_conversation(conn, conversation_id).map_err(|e| e.to_string())?.is_none() {
+        return Err("The conversation this export came from has been deleted".to_string());
+    }
+
+    let include_thinking = export.options.include_thinking.unwrap_or(false);
+    let (_, markdown, cited_note_ids) = conversation_transcript(conn, conversation_id, include_thinking)?;
+    let result = render_transcript(&markdown, &export.title, &export.format, &export.options, output_path)?;
+
+    Ok((result, cited_note_ids, Vec::new()))
+}
+
 /// Input for the AI export agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -488,6 +861,9 @@ pub struct RunExportAgentInput {
     pub title: String,
     pub format: String,
     pub custom_instructions: Option<String>,
+    /// Overrides the default section limits for the run
+    #[serde(default)]
+    pub limits: Option<ExportLimits>,
 }
 
 /// Run the AI export agent to create a document
@@ -510,12 +886,17 @@ pub async fn run_export_agent_cmd(
         pool_guard.clone().ok_or_else(|| "Database not initialized".to_string())?
     };
     
-    let conn = db_pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(&db_pool).map_err(|e| e.to_string())?;
 
     // Parse format
     let format = ExportFormat::from_str(&input.format)
         .ok_or_else(|| format!("Invalid format: {}", input.format))?;
 
+    let limits = input.limits.unwrap_or_default();
+    if limits.soft_limit > limits.hard_limit {
+        return Err("Soft section limit cannot exceed the hard limit".to_string());
+    }
+
     // Get AI config and find provider
     let ai_config_result = load_ai_config(&conn);
     
@@ -582,6 +963,7 @@ pub async fn run_export_agent_cmd(
         &input.title,
         format.clone(),
         input.custom_instructions.as_deref(),
+        limits,
         Some(&cancellation_token),
     )
     .await;
@@ -620,7 +1002,8 @@ fn run_mechanical_export(
     title: &str,
     format: &ExportFormat,
 ) -> Result<ExportAgentResult, String> {
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let started = Instant::now();
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     
     // Get exports directory
     let exports_dir = get_exports_dir()
@@ -685,6 +1068,13 @@ fn run_mechanical_export(
                 .map_err(|e| e.to_string())?;
             (result, ExportFormat::Xlsx)
         }
+        ExportFormat::Markdown => {
+            let filename = generate_dated_filename(title, "md");
+            let output_path = exports_dir.join(&filename);
+            let result = conversation::generate_markdown(&combined_content, title, &output_path)
+                .map_err(|e| e.to_string())?;
+            (result, ExportFormat::Markdown)
+        }
     };
     
     // Record in database
@@ -695,6 +1085,12 @@ fn run_mechanical_export(
         source_note_ids: note_ids.to_vec(),
         file_size: Some(result.file_size as i64),
         path: result.path.clone(),
+        duration_ms: Some(started.elapsed().as_millis() as i64),
+        generator: ExportGenerator::Mechanical,
+        model: None,
+        source: ExportSource::Notes,
+        conversation_id: None,
+        options: ExportRenderOptions::default(),
     };
     
     let export = exports::create_export(&conn, export_input)
@@ -707,6 +1103,263 @@ fn run_mechanical_export(
         export_filename: Some(result.filename),
         iterations: 0,
         tools_used: vec!["mechanical_export".to_string()],
+        truncated: false,
     })
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+    use crate::models::{Citation, CreateNoteInput, MessageMetadata, MessageRole, UpdateNoteInput};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export_conversation_to_markdown() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let temp_dir = TempDir::new().unwrap();
+
+        let conv = db::create_conversation(&conn, Some("Borrowing"), None).unwrap();
+        db::create_message(&conn, &conv.id, MessageRole::User, "What does `&mut` mean?", None).unwrap();
+        db::create_message(
+            &conn,
+            &conv.id,
+            MessageRole::Assistant,
+            "It is a **mutable** reference.",
+            None,
+        )
+        .unwrap();
+
+        let result =
+            export_conversation_to_dir(&conn, &conv.id, ExportFormat::Markdown, false, temp_dir.path()).unwrap();
+        assert!(result.filename.ends_with(".md"));
+
+        let written = fs::read_to_string(&result.path).unwrap();
+        assert!(written.starts_with("# Borrowing"));
+        assert!(written.contains("## User\n\nWhat does `&mut` mean?"));
+        assert!(written.contains("## Assistant\n\nIt is a **mutable** reference."));
+
+        let recorded = exports::get_exports_by_format(&conn, &ExportFormat::Markdown).unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].title, "Borrowing");
+
+        let err = export_conversation_to_dir(&conn, &conv.id, ExportFormat::Xlsx, false, temp_dir.path());
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_batch_delete_removes_only_targeted_files() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let temp_dir = TempDir::new().unwrap();
+
+        let seed = |filename: &str, format: ExportFormat| {
+            let path = temp_dir.path().join(filename);
+            fs::write(&path, filename).unwrap();
+            exports::create_export(&conn, exports::CreateExportInput {
+                filename: filename.to_string(),
+                title: filename.to_string(),
+                format,
+                source_note_ids: vec![],
+                file_size: None,
+                path: path.to_string_lossy().to_string(),
+                duration_ms: None,
+                generator: ExportGenerator::Mechanical,
+                model: None,
+                source: ExportSource::Notes,
+                conversation_id: None,
+                options: ExportRenderOptions::default(),
+            })
+            .unwrap()
+        };
+        let old_docx = seed("old.docx", ExportFormat::Docx);
+        seed("new.pdf", ExportFormat::Pdf);
+        seed("new.pptx", ExportFormat::Pptx);
+        let old_unknown = seed("old.odt", ExportFormat::Docx);
+        conn.execute(
+            "UPDATE exports SET created_at = '2020-01-01 00:00:00' WHERE id IN (?1, ?2)",
+            [&old_docx.id, &old_unknown.id],
+        )
+        .unwrap();
+        // A format from a newer build is still cleaned up with its file
+        conn.execute_batch("PRAGMA ignore_check_constraints = ON;").unwrap();
+        conn.execute("UPDATE exports SET format = 'odt' WHERE id = ?1", [&old_unknown.id])
+            .unwrap();
+        conn.execute_batch("PRAGMA ignore_check_constraints = OFF;").unwrap();
+
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(7);
+        let deleted = exports::delete_exports_older_than(&conn, cutoff).unwrap();
+        assert_eq!(deleted.len(), 2);
+        remove_export_files(&deleted);
+        assert!(!temp_dir.path().join("old.docx").exists());
+        assert!(!temp_dir.path().join("old.odt").exists());
+        assert!(temp_dir.path().join("new.pdf").exists());
+
+        let deleted = exports::delete_exports_by_format(&conn, &ExportFormat::Pdf).unwrap();
+        remove_export_files(&deleted);
+        assert!(!temp_dir.path().join("new.pdf").exists());
+        assert!(temp_dir.path().join("new.pptx").exists());
+
+        let remaining = exports::get_all_exports(&conn).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].filename, "new.pptx");
+    }
+
+    #[test]
+    fn test_regenerate_export_picks_up_note_edits() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let temp_dir = TempDir::new().unwrap();
+
+        let kept = db::notes::create_note(&conn, CreateNoteInput {
+            title: "Kept".to_string(),
+            content: Some("First draft".to_string()),
+            content_html: None,
+            folder_id: None,
+        })
+        .unwrap();
+        let deleted = db::notes::create_note(&conn, CreateNoteInput {
+            title: "Deleted".to_string(),
+            content: Some("Going away".to_string()),
+            content_html: None,
+            folder_id: None,
+        })
+        .unwrap();
+
+        let path = temp_dir.path().join("notes.md");
+        fs::write(&path, "# Kept\n\nFirst draft\n\n# Deleted\n\nGoing away").unwrap();
+        let export = exports::create_export(&conn, exports::CreateExportInput {
+            filename: "notes.md".to_string(),
+            title: "Notes".to_string(),
+            format: ExportFormat::Markdown,
+            source_note_ids: vec![kept.id.clone(), deleted.id.clone()],
+            file_size: None,
+            path: path.to_string_lossy().to_string(),
+            duration_ms: None,
+            generator: ExportGenerator::Mechanical,
+            model: None,
+            source: ExportSource::Notes,
+            conversation_id: None,
+            options: ExportRenderOptions::default(),
+        })
+        .unwrap();
+
+        db::notes::update_note(&conn, &kept.id, UpdateNoteInput {
+            title: None,
+            content: Some("Second draft".to_string()),
+            content_html: None,
+            folder_id: None,
+        })
+        .unwrap();
+        db::notes::delete_note(&conn, &deleted.id).unwrap();
+
+        let regenerated = regenerate_export_file(&conn, &export.id).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(written.contains("Second draft"));
+        assert!(!written.contains("First draft"));
+        assert!(!written.contains("Going away"));
+
+        assert_eq!(regenerated.missing_note_ids, vec![deleted.id.clone()]);
+        assert_eq!(regenerated.export.id, export.id);
+        assert_eq!(regenerated.export.source_note_ids, vec![kept.id.clone()]);
+        assert_eq!(regenerated.export.file_size, Some(written.len() as i64));
+        // No temp file left behind
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+
+        // Once every source note is gone there is nothing to regenerate
+        db::notes::delete_note(&conn, &kept.id).unwrap();
+        assert!(regenerate_export_file(&conn, &export.id).is_err());
+        assert!(fs::read_to_string(&path).unwrap().contains("Second draft"));
+    }
+
+    #[test]
+    fn test_regenerate_conversation_export_keeps_transcript() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let temp_dir = TempDir::new().unwrap();
+
+        let cited = db::notes::create_note(&conn, CreateNoteInput {
+            title: "Ownership".to_string(),
+            content: Some("Cited note body".to_string()),
+            content_html: None,
+            folder_id: None,
+        })
+        .unwrap();
+        let conv = db::create_conversation(&conn, Some("Borrowing"), None).unwrap();
+        db::create_message(&conn, &conv.id, MessageRole::User, "What does `&mut` mean?", None).unwrap();
+        let metadata = MessageMetadata {
+            citations: vec![Citation {
+                note_id: cited.id.clone(),
+                note_title: cited.title.clone(),
+                relevance: 0.9,
+                start_offset: None,
+                end_offset: None,
+            }],
+            model: None,
+            usage: None,
+            tool_calls: Vec::new(),
+            thinking_content: Some("The user asks about references".to_string()),
+            images: Vec::new(),
+        };
+        db::create_message(
+            &conn,
+            &conv.id,
+            MessageRole::Assistant,
+            "It is a mutable reference.",
+            Some(&metadata),
+        )
+        .unwrap();
+
+        let result =
+            export_conversation_to_dir(&conn, &conv.id, ExportFormat::Markdown, true, temp_dir.path()).unwrap();
+        let export = exports::get_exports_by_format(&conn, &ExportFormat::Markdown).unwrap().remove(0);
+        assert_eq!(export.source, Some(ExportSource::Conversation));
+        assert_eq!(export.conversation_id.as_deref(), Some(conv.id.as_str()));
+        assert_eq!(export.options.include_thinking, Some(true));
+        assert_eq!(export.source_note_ids, vec![cited.id.clone()]);
+
+        db::create_message(&conn, &conv.id, MessageRole::User, "And `&`?", None).unwrap();
+
+        let regenerated = regenerate_export_file(&conn, &export.id).unwrap();
+        let written = fs::read_to_string(&result.path).unwrap();
+        assert!(written.contains("It is a mutable reference."));
+        assert!(written.contains("And `&`?"));
+        assert!(written.contains("The user asks about references"));
+        assert!(!written.contains("Cited note body"));
+        assert!(regenerated.missing_note_ids.is_empty());
+        assert_eq!(regenerated.export.source_note_ids, vec![cited.id.clone()]);
+
+        // Without the conversation there is nothing to regenerate from
+        db::delete_conversation(&conn, &conv.id).unwrap();
+        assert!(regenerate_export_file(&conn, &export.id).is_err());
+        assert!(fs::read_to_string(&result.path).unwrap().contains("And `&`?"));
+    }
+
+    #[test]
+    fn test_regenerate_refuses_exports_without_a_recorded_source() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let temp_dir = TempDir::new().unwrap();
+
+        let note = db::notes::create_note(&conn, CreateNoteInput {
+            title: "Note".to_string(),
+            content: Some("Body".to_string()),
+            content_html: None,
+            folder_id: None,
+        })
+        .unwrap();
+        let path = temp_dir.path().join("old.md");
+        fs::write(&path, "Original").unwrap();
+        conn.execute(
+            "INSERT INTO exports (id, filename, title, format, source_note_ids, path)
+             VALUES ('old', 'old.md', 'Old', 'markdown', ?1, ?2)",
+            rusqlite::params![format!("[\"{}\"]", note.id), path.to_string_lossy()],
+        )
+        .unwrap();
+
+        assert!(regenerate_export_file(&conn, "old").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Original");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+}
@@ -2,7 +2,7 @@ use std::fs;
 use std::path::PathBuf;
 use tauri::State;
 
-use crate::db::connection::DbPool;
+use crate::db::connection::{get_connection, DbPool};
 use crate::db::folders as db;
 use crate::db::notes as notes_db;
 use crate::models::{CreateFolderInput, Folder, UpdateFolderInput};
@@ -56,7 +56,7 @@ fn create_folder_on_disk(conn: &rusqlite::Connection, folder: &Folder) -> Result
 
 /// Create a folder on the filesystem (public helper for other modules)
 pub fn create_folder_on_disk_from_pool(pool: &DbPool, folder: &Folder) -> Result<(), String> {
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     create_folder_on_disk(&conn, folder)
 }
 
@@ -107,7 +107,7 @@ pub fn create_folder(
 ) -> Result<Folder, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     let input = CreateFolderInput { name, parent_id };
 
@@ -126,7 +126,7 @@ pub fn create_folder(
 pub fn get_folder(pool: State<AppPool>, id: String) -> Result<Option<Folder>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_folder(&conn, &id).map_err(|e| e.to_string())
 }
 
@@ -135,7 +135,7 @@ pub fn get_folder(pool: State<AppPool>, id: String) -> Result<Option<Folder>, St
 pub fn get_all_folders(pool: State<AppPool>) -> Result<Vec<Folder>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_all_folders(&conn).map_err(|e| e.to_string())
 }
 
@@ -147,7 +147,7 @@ pub fn get_child_folders(
 ) -> Result<Vec<Folder>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_child_folders(&conn, parent_id.as_deref()).map_err(|e| e.to_string())
 }
 
@@ -161,7 +161,7 @@ pub fn update_folder(
 ) -> Result<Folder, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
     // Get the old folder path before updating
     let old_path = get_folder_path_by_id(&conn, &id)?;
@@ -196,7 +196,7 @@ pub fn update_folder(
 pub fn delete_folder(pool: State<AppPool>, id: String) -> Result<bool, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
     
     // Get the folder path before deleting from DB
     let folder_path = match get_folder_path_by_id(&conn, &id) {
@@ -6,8 +6,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
+use crate::db::connection::get_connection;
 use crate::google::{self, GoogleAccount};
-use crate::google::calendar::SyncResult;
+use crate::google::calendar::{PushResult, SyncResult};
 use crate::AppPool;
 
 /// Serializable Google account info for frontend
@@ -23,7 +24,7 @@ pub struct GoogleConnectionStatus {
 pub async fn is_google_configured(pool: State<'_, AppPool>) -> Result<bool, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = db_pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(db_pool).map_err(|e| e.to_string())?;
     
     Ok(google::oauth::get_client_id_with_db(&conn).is_ok())
 }
@@ -50,7 +51,7 @@ pub async fn get_google_connection_status(
 ) -> Result<GoogleConnectionStatus, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = db_pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(db_pool).map_err(|e| e.to_string())?;
 
     match google::get_connection_status(&conn) {
         Ok(Some(account)) => Ok(GoogleConnectionStatus {
@@ -72,7 +73,7 @@ pub async fn get_google_connection_status(
 pub async fn disconnect_google_account(pool: State<'_, AppPool>) -> Result<(), String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = db_pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(db_pool).map_err(|e| e.to_string())?;
 
     google::disconnect_account(&conn).map_err(|e| e.to_string())
 }
@@ -95,6 +96,21 @@ pub async fn sync_google_calendar(
         .map_err(|e| e.to_string())
 }
 
+/// Push locally created or edited events to Google Calendar, and delete
+/// Google copies of pushed events that were deleted locally
+#[tauri::command]
+pub async fn push_google_calendar(pool: State<'_, AppPool>) -> Result<PushResult, String> {
+    // Clone the pool Arc to use across await points
+    let db_pool = {
+        let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
+        pool_guard.clone().ok_or("Database not initialized")?
+    };
+
+    google::calendar::push_local_events_to_google(&db_pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Get meeting info for creating a note from a Google event
 #[tauri::command]
 pub async fn get_event_meeting_info(
@@ -103,19 +119,13 @@ pub async fn get_event_meeting_info(
 ) -> Result<Option<crate::google::calendar::EventMeetingInfo>, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = db_pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(db_pool).map_err(|e| e.to_string())?;
 
     // Get the event
     let event = crate::db::calendar_events::get_event(&conn, &event_id)
         .map_err(|e| e.to_string())?;
     
-    match event {
-        Some(e) => {
-            let info = google::calendar::parse_meeting_info(e.description.as_deref());
-            Ok(Some(info))
-        }
-        None => Ok(None),
-    }
+    Ok(event.map(|e| google::calendar::event_meeting_info(&e)))
 }
 
 /// Save Google OAuth credentials to settings
@@ -127,7 +137,7 @@ pub async fn save_google_credentials(
 ) -> Result<(), String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = db_pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(db_pool).map_err(|e| e.to_string())?;
 
     // Save to settings table
     crate::db::settings::set_setting(&conn, "google_client_id", &client_id)
@@ -143,7 +153,7 @@ pub async fn save_google_credentials(
 pub async fn clear_google_credentials(pool: State<'_, AppPool>) -> Result<(), String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = db_pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(db_pool).map_err(|e| e.to_string())?;
 
     // Delete from settings table
     crate::db::settings::delete_setting(&conn, "google_client_id")
@@ -160,7 +170,7 @@ pub async fn clear_google_credentials(pool: State<'_, AppPool>) -> Result<(), St
 pub async fn get_google_credential_source(pool: State<'_, AppPool>) -> Result<String, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = db_pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(db_pool).map_err(|e| e.to_string())?;
 
     // Check database first
     if let Ok(Some(id)) = crate::db::settings::get_setting(&conn, "google_client_id") {
@@ -197,7 +207,7 @@ pub struct CurrentCredentialsResponse {
 pub async fn get_current_google_credentials(pool: State<'_, AppPool>) -> Result<CurrentCredentialsResponse, String> {
     let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
     let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = db_pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(db_pool).map_err(|e| e.to_string())?;
 
     // Use the same logic as get_client_id_with_db to get client ID and determine source
     let (client_id, source) = {
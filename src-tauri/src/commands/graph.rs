@@ -1,8 +1,10 @@
 //! Tauri commands for knowledge graph visualization
 
+use crate::db::connection::get_connection;
 use crate::db;
 use crate::AppPool;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 use tauri::State;
 
 /// A node in the knowledge graph (represents a note)
@@ -35,68 +37,173 @@ pub struct GraphData {
     pub edges: Vec<GraphEdge>,
 }
 
+/// Optional restrictions applied when building the graph
+#[derive(Debug, Clone, Default)]
+struct GraphFilter {
+    /// Only include notes within `max_depth` link hops of this note
+    root_note_id: Option<String>,
+    /// Maximum number of hops from the root (defaults to 1 when a root is set)
+    max_depth: Option<u32>,
+    /// Only include notes carrying this tag (case-insensitive)
+    tag_filter: Option<String>,
+}
+
 /// Get all graph data (nodes and edges) for knowledge graph visualization
+///
+/// `root_note_id` + `max_depth` restrict the graph to the neighborhood of a
+/// note (links are followed in both directions), and `tag_filter` keeps only
+/// notes carrying the given tag.
 #[tauri::command]
 pub async fn get_graph_data(
     pool: State<'_, AppPool>,
     include_similarity: Option<bool>,
     similarity_threshold: Option<f32>,
+    root_note_id: Option<String>,
+    max_depth: Option<u32>,
+    tag_filter: Option<String>,
 ) -> Result<GraphData, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+
+    let filter = GraphFilter {
+        root_note_id,
+        max_depth,
+        tag_filter,
+    };
 
+    let mut graph = build_graph(&conn, &filter)?;
+
+    // Optionally add similarity edges
+    if include_similarity.unwrap_or(false) {
+        let threshold = similarity_threshold.unwrap_or(0.7);
+        
+        // Get similarity edges for notes that have embeddings
+        if let Ok(similarity_edges) = get_similarity_edges(&conn, threshold) {
+            let node_ids: HashSet<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+            let similarity_edges: Vec<GraphEdge> = similarity_edges
+                .into_iter()
+                .filter(|e| node_ids.contains(e.source.as_str()) && node_ids.contains(e.target.as_str()))
+                .collect();
+            graph.edges.extend(similarity_edges);
+        }
+    }
+
+    Ok(graph)
+}
+
+/// Build the link graph, applying any neighborhood and tag restrictions
+fn build_graph(conn: &rusqlite::Connection, filter: &GraphFilter) -> Result<GraphData, String> {
     // Get all notes as nodes
-    let notes = db::notes::get_all_notes(&conn)
+    let notes = db::notes::get_all_notes(conn)
         .map_err(|e| format!("Failed to get notes: {}", e))?;
 
     // Get all links
-    let links = db::links::get_all_links(&conn)
+    let links = db::links::get_all_links(conn)
         .map_err(|e| format!("Failed to get links: {}", e))?;
 
-    // Build link count map
-    let mut link_counts: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
-    for link in &links {
-        *link_counts.entry(link.source_note_id.clone()).or_insert(0) += 1;
-        *link_counts.entry(link.target_note_id.clone()).or_insert(0) += 1;
+    // Notes carrying the requested tag
+    let tagged: Option<HashSet<String>> = match filter.tag_filter {
+        Some(ref tag) => Some(get_note_ids_with_tag(conn, tag)?),
+        None => None,
+    };
+
+    // Neighborhood around the root note
+    let neighborhood: Option<HashSet<String>> = filter.root_note_id.as_ref().map(|root| {
+        collect_neighborhood(&links, root, filter.max_depth.unwrap_or(1))
+    });
+
+    let include = |id: &str| {
+        tagged.as_ref().is_none_or(|ids| ids.contains(id))
+            && neighborhood.as_ref().is_none_or(|ids| ids.contains(id))
+    };
+
+    // Convert links to graph edges
+    let edges: Vec<GraphEdge> = links
+        .into_iter()
+        .filter(|l| include(&l.source_note_id) && include(&l.target_note_id))
+        .map(|l| GraphEdge {
+            source: l.source_note_id,
+            target: l.target_note_id,
+            edge_type: "link".to_string(),
+            weight: Some(1.0),
+        })
+        .collect();
+
+    // Count only the links shown, so counts match the filtered graph
+    let mut link_counts: HashMap<&str, u32> = HashMap::new();
+    for edge in &edges {
+        *link_counts.entry(edge.source.as_str()).or_insert(0) += 1;
+        *link_counts.entry(edge.target.as_str()).or_insert(0) += 1;
     }
 
     // Convert notes to graph nodes
     let nodes: Vec<GraphNode> = notes
         .into_iter()
-        .filter(|n| !n.is_deleted)
+        .filter(|n| !n.is_deleted && include(&n.id))
         .map(|n| GraphNode {
             id: n.id.clone(),
             title: n.title,
             folder_id: n.folder_id,
-            link_count: *link_counts.get(&n.id).unwrap_or(&0),
+            link_count: *link_counts.get(n.id.as_str()).unwrap_or(&0),
             created_at: n.created_at.to_rfc3339(),
             updated_at: n.updated_at.to_rfc3339(),
         })
         .collect();
 
-    // Convert links to graph edges
-    let mut edges: Vec<GraphEdge> = links
-        .into_iter()
-        .map(|l| GraphEdge {
-            source: l.source_note_id,
-            target: l.target_note_id,
-            edge_type: "link".to_string(),
-            weight: Some(1.0),
-        })
-        .collect();
+    Ok(GraphData { nodes, edges })
+}
 
-    // Optionally add similarity edges
-    if include_similarity.unwrap_or(false) {
-        let threshold = similarity_threshold.unwrap_or(0.7);
-        
-        // Get similarity edges for notes that have embeddings
-        if let Ok(similarity_edges) = get_similarity_edges(&conn, threshold) {
-            edges.extend(similarity_edges);
+/// Breadth-first search over links (in either direction) from a root note
+fn collect_neighborhood(links: &[db::NoteLink], root: &str, max_depth: u32) -> HashSet<String> {
+    let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+    for link in links {
+        adjacency
+            .entry(link.source_note_id.as_str())
+            .or_default()
+            .push(link.target_note_id.as_str());
+        adjacency
+            .entry(link.target_note_id.as_str())
+            .or_default()
+            .push(link.source_note_id.as_str());
+    }
+
+    let mut visited: HashSet<String> = HashSet::new();
+    let mut queue: VecDeque<(&str, u32)> = VecDeque::new();
+    visited.insert(root.to_string());
+    queue.push_back((root, 0));
+
+    while let Some((id, depth)) = queue.pop_front() {
+        if depth >= max_depth {
+            continue;
+        }
+        for &neighbor in adjacency.get(id).map(|v| v.as_slice()).unwrap_or_default() {
+            if visited.insert(neighbor.to_string()) {
+                queue.push_back((neighbor, depth + 1));
+            }
         }
     }
 
-    Ok(GraphData { nodes, edges })
+    visited
+}
+
+/// Get the IDs of all notes carrying a tag (matched by name, case-insensitive)
+fn get_note_ids_with_tag(conn: &rusqlite::Connection, tag: &str) -> Result<HashSet<String>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT nt.note_id FROM note_tags nt
+             JOIN tags t ON t.id = nt.tag_id
+             WHERE LOWER(t.name) = LOWER(?1)",
+        )
+        .map_err(|e| format!("Failed to prepare tag query: {}", e))?;
+
+    let ids = stmt
+        .query_map([tag.trim()], |row| row.get(0))
+        .map_err(|e| format!("Failed to query tagged notes: {}", e))?
+        .filter_map(Result::ok)
+        .collect();
+
+    Ok(ids)
 }
 
 /// Get similarity edges between notes based on embedding similarity
@@ -144,3 +251,105 @@ fn get_similarity_edges(
     Ok(edges)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+
+    /// Seed a chain a - b - c - d plus an isolated note e
+    fn seed_graph(conn: &rusqlite::Connection) {
+        for id in ["a", "b", "c", "d", "e"] {
+            conn.execute(
+                "INSERT INTO notes (id, title) VALUES (?1, ?2)",
+                [id, &format!("Note {}", id.to_uppercase())],
+            )
+            .unwrap();
+        }
+        db::links::create_link(conn, "a", "b", None).unwrap();
+        db::links::create_link(conn, "c", "b", None).unwrap();
+        db::links::create_link(conn, "c", "d", None).unwrap();
+
+        let tag = db::tags::create_tag(conn, "project", None, None).unwrap();
+        for id in ["a", "b", "e"] {
+            db::tags::add_tag_to_note(conn, id, &tag.id).unwrap();
+        }
+    }
+
+    fn node_ids(graph: &GraphData) -> Vec<String> {
+        let mut ids: Vec<String> = graph.nodes.iter().map(|n| n.id.clone()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_unfiltered_graph() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        seed_graph(&conn);
+
+        let graph = build_graph(&conn, &GraphFilter::default()).unwrap();
+        assert_eq!(graph.nodes.len(), 5);
+        assert_eq!(graph.edges.len(), 3);
+    }
+
+    #[test]
+    fn test_depth_limited_neighborhood() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        seed_graph(&conn);
+
+        let filter = |depth| GraphFilter {
+            root_note_id: Some("a".to_string()),
+            max_depth: Some(depth),
+            tag_filter: None,
+        };
+
+        let graph = build_graph(&conn, &filter(1)).unwrap();
+        assert_eq!(node_ids(&graph), vec!["a", "b"]);
+        assert_eq!(graph.edges.len(), 1);
+
+        // Links are followed against their direction (c -> b)
+        let graph = build_graph(&conn, &filter(2)).unwrap();
+        assert_eq!(node_ids(&graph), vec!["a", "b", "c"]);
+        assert_eq!(graph.edges.len(), 2);
+
+        let graph = build_graph(&conn, &filter(0)).unwrap();
+        assert_eq!(node_ids(&graph), vec!["a"]);
+        assert!(graph.edges.is_empty());
+    }
+
+    #[test]
+    fn test_tag_filter() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        seed_graph(&conn);
+
+        let graph = build_graph(
+            &conn,
+            &GraphFilter {
+                tag_filter: Some("Project".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(node_ids(&graph), vec!["a", "b", "e"]);
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].source, "a");
+        // b also links to c, which is filtered out, so only the a - b link counts
+        let link_count = |id: &str| graph.nodes.iter().find(|n| n.id == id).unwrap().link_count;
+        assert_eq!((link_count("a"), link_count("b"), link_count("e")), (1, 1, 0));
+
+        // Tag and depth combine
+        let graph = build_graph(
+            &conn,
+            &GraphFilter {
+                root_note_id: Some("c".to_string()),
+                max_depth: Some(1),
+                tag_filter: Some("project".to_string()),
+            },
+        )
+        .unwrap();
+        assert_eq!(node_ids(&graph), vec!["b"]);
+    }
+}
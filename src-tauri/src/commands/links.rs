@@ -1,7 +1,11 @@
 //! Tauri commands for wiki-style note links
 
+use crate::db::connection::get_connection;
 use crate::db;
-use crate::AppPool;
+use crate::models::{Note, UpdateNoteInput};
+use crate::vault::sync as vault_sync;
+use crate::{AppPool, AppSearchIndex};
+use crate::db::links::{strip_backlinks_section, BACKLINKS_END, BACKLINKS_START};
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -13,7 +17,7 @@ pub async fn get_backlinks(
 ) -> Result<Vec<db::Backlink>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
     db::links::get_backlinks(&conn, &note_id)
         .map_err(|e| format!("Failed to get backlinks: {}", e))
 }
@@ -26,7 +30,7 @@ pub async fn get_link_stats(
 ) -> Result<db::LinkStats, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
     db::links::get_link_stats(&conn, &note_id)
         .map_err(|e| format!("Failed to get link stats: {}", e))
 }
@@ -48,9 +52,9 @@ pub async fn sync_note_links(
 ) -> Result<(), String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
 
-    let link_inputs: Vec<db::LinkInput> = links
+    let mut link_inputs: Vec<db::LinkInput> = links
         .into_iter()
         .map(|l| db::LinkInput {
             target_note_id: l.target_note_id,
@@ -58,6 +62,22 @@ pub async fn sync_note_links(
         })
         .collect();
 
+    // Also pick up [[Title]] / [[Title|display]] / alias references written as plain text
+    if let Ok(Some(note)) = db::notes::get_note(&conn, &note_id) {
+        let content_links = db::links::resolve_content_links(
+            &conn,
+            &note_id,
+            note.content.as_deref().unwrap_or(""),
+        )
+        .map_err(|e| format!("Failed to resolve links: {}", e))?;
+
+        for link in content_links {
+            if !link_inputs.iter().any(|l| l.target_note_id == link.target_note_id) {
+                link_inputs.push(link);
+            }
+        }
+    }
+
     db::links::sync_links(&conn, &note_id, &link_inputs)
         .map_err(|e| format!("Failed to sync links: {}", e))
 }
@@ -72,7 +92,7 @@ pub async fn search_notes_for_mention(
 ) -> Result<Vec<db::NoteSummary>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
     let limit = limit.unwrap_or(10);
 
     db::links::search_notes_by_title(&conn, &query, exclude_id.as_deref(), limit)
@@ -87,7 +107,206 @@ pub async fn get_outgoing_links(
 ) -> Result<Vec<db::NoteLink>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
     db::links::get_outgoing_links(&conn, &note_id)
         .map_err(|e| format!("Failed to get outgoing links: {}", e))
 }
+
+/// Get notes that have neither outgoing links nor backlinks
+/// When `untagged_only` is true, notes with any tags are also excluded
+#[tauri::command]
+pub async fn get_orphan_notes(
+    pool: State<'_, AppPool>,
+    untagged_only: Option<bool>,
+) -> Result<Vec<db::NoteSummary>, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+    db::links::get_orphan_notes(&conn, untagged_only.unwrap_or(false))
+        .map_err(|e| format!("Failed to get orphan notes: {}", e))
+}
+
+/// Find wiki-links that point to notes which don't exist
+#[tauri::command]
+pub async fn get_broken_links(
+    pool: State<'_, AppPool>,
+) -> Result<Vec<db::BrokenLink>, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+    db::links::get_broken_links(&conn)
+        .map_err(|e| format!("Failed to get broken links: {}", e))
+}
+
+/// Get the alternate titles declared for a note
+#[tauri::command]
+pub async fn get_note_aliases(
+    pool: State<'_, AppPool>,
+    note_id: String,
+) -> Result<Vec<String>, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+    db::links::get_note_aliases(&conn, &note_id)
+        .map_err(|e| format!("Failed to get aliases: {}", e))
+}
+
+/// Add an alternate title that wiki-links can resolve to
+#[tauri::command]
+pub async fn add_note_alias(
+    pool: State<'_, AppPool>,
+    note_id: String,
+    alias: String,
+) -> Result<(), String> {
+    if alias.trim().is_empty() {
+        return Err("Alias cannot be empty".to_string());
+    }
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+    db::links::add_note_alias(&conn, &note_id, &alias)
+        .map_err(|e| format!("Failed to add alias: {}", e))
+}
+
+/// Remove an alternate title from a note
+#[tauri::command]
+pub async fn remove_note_alias(
+    pool: State<'_, AppPool>,
+    note_id: String,
+    alias: String,
+) -> Result<bool, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+    db::links::remove_note_alias(&conn, &note_id, &alias)
+        .map_err(|e| format!("Failed to remove alias: {}", e))
+}
+
+/// Rewrite the "Linked references" block at the end of a note
+///
+/// Gathers the note's backlinks and replaces the delimited
+/// `<!-- backlinks:start -->…<!-- backlinks:end -->` region with a bullet
+/// list of linking notes. Re-running replaces the block rather than appending,
+/// and the block is removed entirely when the note has no backlinks.
+#[tauri::command]
+pub fn update_backlinks_section(
+    pool: State<AppPool>,
+    search_index: State<AppSearchIndex>,
+    note_id: String,
+) -> Result<Note, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool_ref).map_err(|e| format!("Database error: {}", e))?;
+
+    let note = db::notes::get_note(&conn, &note_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Note not found: {}", note_id))?;
+
+    let backlinks = db::links::get_backlinks(&conn, &note_id)
+        .map_err(|e| format!("Failed to get backlinks: {}", e))?;
+    let titles: Vec<String> = backlinks.into_iter().map(|b| b.source_title).collect();
+
+    let content = note.content.clone().unwrap_or_default();
+    let new_content = apply_backlinks_section(&content, &titles);
+    if new_content == content {
+        return Ok(note);
+    }
+    // Removing the block from a note that holds nothing else would wipe it
+    if new_content.trim().is_empty() {
+        return Err("Updating the backlinks section would leave the note empty".to_string());
+    }
+
+    // Clear stale HTML so the frontend regenerates it from the markdown content
+    let updated = db::notes::update_note_with_revision(
+        &conn,
+        &note_id,
+        UpdateNoteInput {
+            title: None,
+            content: Some(new_content),
+            content_html: Some(String::new()),
+            folder_id: None,
+        },
+    )
+    .map_err(|e| e.to_string())?;
+
+    let search_guard = search_index.0.read().unwrap();
+    if let Some(ref index) = *search_guard {
+        if let Err(e) = index.update_note(&updated.id, &updated.title, updated.content.as_deref()) {
+            log::warn!("Failed to update note in search index: {}", e);
+        }
+    }
+
+    if let Err(e) = vault_sync::sync_note_to_file(pool_ref, &updated.id) {
+        log::warn!("Failed to sync note to filesystem: {}", e);
+    }
+
+    Ok(updated)
+}
+
+/// Replace (or insert) the backlinks block at the end of the content
+/// An empty title list removes the block.
+fn apply_backlinks_section(content: &str, titles: &[String]) -> String {
+    let stripped = strip_backlinks_section(content);
+
+    if titles.is_empty() {
+        return stripped;
+    }
+
+    let mut section = format!("{}\n## Linked references\n\n", BACKLINKS_START);
+    for title in titles {
+        section.push_str(&format!("- [[{}]]\n", title));
+    }
+    section.push_str(BACKLINKS_END);
+
+    if stripped.is_empty() {
+        section
+    } else {
+        format!("{}\n\n{}", stripped, section)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_apply_backlinks_section_inserts() {
+        let result = apply_backlinks_section("Body text\n", &titles(&["Alpha", "Beta"]));
+        assert_eq!(
+            result,
+            "Body text\n\n<!-- backlinks:start -->\n## Linked references\n\n- [[Alpha]]\n- [[Beta]]\n<!-- backlinks:end -->"
+        );
+    }
+
+    #[test]
+    fn test_apply_backlinks_section_replaces_existing() {
+        let first = apply_backlinks_section("Body text", &titles(&["Alpha"]));
+        let second = apply_backlinks_section(&first, &titles(&["Gamma"]));
+
+        assert_eq!(second.matches(BACKLINKS_START).count(), 1);
+        assert!(second.contains("- [[Gamma]]"));
+        assert!(!second.contains("- [[Alpha]]"));
+        assert!(second.starts_with("Body text\n\n"));
+
+        // Idempotent when backlinks are unchanged
+        assert_eq!(apply_backlinks_section(&second, &titles(&["Gamma"])), second);
+    }
+
+    #[test]
+    fn test_apply_backlinks_section_without_backlinks() {
+        // Nothing to add and no block present leaves content untouched
+        assert_eq!(apply_backlinks_section("Body text", &[]), "Body text");
+
+        // An existing block is removed once backlinks disappear
+        let with_block = apply_backlinks_section("Body text", &titles(&["Alpha"]));
+        assert_eq!(apply_backlinks_section(&with_block, &[]), "Body text");
+
+        // Content typed after the block is preserved
+        let trailing = format!("{}\n\nLater notes", with_block);
+        assert_eq!(apply_backlinks_section(&trailing, &[]), "Body text\n\nLater notes");
+    }
+}
@@ -0,0 +1,87 @@
+//! Tauri commands for Microsoft integration
+//!
+//! Provides commands for Microsoft OAuth authentication and Outlook calendar sync.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::db::connection::get_connection;
+use crate::google::calendar::SyncResult;
+use crate::microsoft::{self, MicrosoftAccount};
+use crate::AppPool;
+
+/// Serializable Microsoft account info for frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MicrosoftConnectionStatus {
+    pub connected: bool,
+    pub email: Option<String>,
+    pub connected_at: Option<String>,
+}
+
+/// Initiate Microsoft OAuth flow
+/// Opens the browser to the Microsoft sign-in page
+#[tauri::command]
+pub async fn initiate_microsoft_auth(pool: State<'_, AppPool>) -> Result<MicrosoftAccount, String> {
+    // Clone the pool Arc to use across await points
+    let db_pool = {
+        let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
+        pool_guard.clone().ok_or("Database not initialized")?
+    };
+
+    microsoft::oauth::initiate_auth_with_pool(&db_pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get current Microsoft connection status
+#[tauri::command]
+pub async fn get_microsoft_connection_status(
+    pool: State<'_, AppPool>,
+) -> Result<MicrosoftConnectionStatus, String> {
+    let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
+    let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(db_pool).map_err(|e| e.to_string())?;
+
+    match microsoft::get_connection_status(&conn) {
+        Ok(Some(account)) => Ok(MicrosoftConnectionStatus {
+            connected: true,
+            email: Some(account.email),
+            connected_at: Some(account.connected_at),
+        }),
+        Ok(None) => Ok(MicrosoftConnectionStatus {
+            connected: false,
+            email: None,
+            connected_at: None,
+        }),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Disconnect Microsoft account
+#[tauri::command]
+pub async fn disconnect_microsoft_account(pool: State<'_, AppPool>) -> Result<(), String> {
+    let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
+    let db_pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(db_pool).map_err(|e| e.to_string())?;
+
+    microsoft::disconnect_account(&conn).map_err(|e| e.to_string())
+}
+
+/// Sync Outlook calendar events for a date range
+#[tauri::command]
+pub async fn sync_microsoft_calendar(
+    pool: State<'_, AppPool>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<SyncResult, String> {
+    // Clone the pool Arc to use across await points
+    let db_pool = {
+        let pool_guard = pool.0.read().map_err(|e| e.to_string())?;
+        pool_guard.clone().ok_or("Database not initialized")?
+    };
+
+    microsoft::calendar::sync_events_to_db_with_pool(&db_pool, start, end)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -10,8 +10,10 @@ pub mod folders;
 pub mod google;
 pub mod graph;
 pub mod links;
+pub mod microsoft;
 pub mod notes;
 pub mod search;
+pub mod stats;
 pub mod tags;
 pub mod urls;
 pub mod vault;
@@ -28,8 +30,10 @@ pub use folders::*;
 pub use google::*;
 pub use graph::*;
 pub use links::*;
+pub use microsoft::*;
 pub use notes::*;
 pub use search::*;
+pub use stats::*;
 pub use tags::*;
 pub use urls::*;
 pub use vault::*;
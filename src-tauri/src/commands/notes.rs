@@ -1,7 +1,9 @@
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 
+use crate::db::connection::get_connection;
+use crate::db::note_revisions;
 use crate::db::notes as db;
-use crate::models::{CreateNoteInput, Note, UpdateNoteInput};
+use crate::models::{CreateNoteInput, Note, NotePage, NoteRevision, NoteSort, UpdateNoteInput};
 use crate::vault::sync as vault_sync;
 use crate::{AppPool, AppSearchIndex};
 
@@ -17,7 +19,7 @@ pub fn create_note(
 ) -> Result<Note, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
     let input = CreateNoteInput {
         title: title.clone(),
@@ -49,16 +51,32 @@ pub fn create_note(
 pub fn get_note(pool: State<AppPool>, id: String) -> Result<Option<Note>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_note(&conn, &id).map_err(|e| e.to_string())
 }
 
+/// Get one page of non-deleted notes, with the total for paging
+///
+/// `sort_by` defaults to most recently updated first.
+#[tauri::command]
+pub fn get_notes_paginated(
+    pool: State<AppPool>,
+    limit: usize,
+    offset: usize,
+    sort_by: Option<NoteSort>,
+) -> Result<NotePage, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+    db::get_notes_paginated(&conn, limit, offset, sort_by.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
 /// Get all non-deleted notes
 #[tauri::command]
 pub fn get_all_notes(pool: State<AppPool>) -> Result<Vec<Note>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_all_notes(&conn).map_err(|e| e.to_string())
 }
 
@@ -70,13 +88,15 @@ pub fn get_notes_in_folder(
 ) -> Result<Vec<Note>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_notes_in_folder(&conn, folder_id.as_deref()).map_err(|e| e.to_string())
 }
 
 /// Update an existing note
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn update_note(
+    app: AppHandle,
     pool: State<AppPool>,
     search_index: State<AppSearchIndex>,
     id: String,
@@ -87,19 +107,13 @@ pub fn update_note(
 ) -> Result<Note, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
     // Get the old note to check for title/folder changes
     let old_note = db::get_note(&conn, &id)
         .map_err(|e| e.to_string())?
         .ok_or_else(|| format!("Note not found: {}", id))?;
     
-    let old_title = old_note.title.clone();
-    let old_folder_id = old_note.folder_id.clone();
-    
-    let is_title_changing = title.as_ref().is_some_and(|t| t != &old_title);
-    let is_folder_changing = folder_id != old_folder_id;
-
     let input = UpdateNoteInput {
         title,
         content,
@@ -108,7 +122,22 @@ pub fn update_note(
     };
 
     let note = db::update_note(&conn, &id, input).map_err(|e| e.to_string())?;
+    sync_updated_note(&app, pool_ref, &search_index, &old_note, &note);
 
+    Ok(note)
+}
+
+/// Bring the search index and the note's file up to date after an update
+///
+/// A file that was also edited outside the app is left alone and reported to
+/// the frontend as a sync conflict.
+fn sync_updated_note(
+    app: &AppHandle,
+    pool: &crate::db::connection::DbPool,
+    search_index: &AppSearchIndex,
+    old_note: &Note,
+    note: &Note,
+) {
     // Update search index
     let search_guard = search_index.0.read().unwrap();
     if let Some(ref index) = *search_guard {
@@ -118,18 +147,52 @@ pub fn update_note(
     }
     
     // Handle filesystem sync
-    if is_title_changing || is_folder_changing {
+    if note.title != old_note.title || note.folder_id != old_note.folder_id {
         // Title or folder changed - delete old file first, then sync new one
         // We need to manually construct the old path since the note in DB is already updated
-        if let Err(e) = delete_old_note_file(pool_ref, &old_title, old_folder_id.as_deref()) {
+        if let Err(e) = delete_old_note_file(pool, &old_note.title, old_note.folder_id.as_deref()) {
             log::warn!("Failed to delete old note file: {}", e);
         }
     }
     
     // Sync to filesystem (creates the new file)
-    if let Err(e) = vault_sync::sync_note_to_file(pool_ref, &note.id) {
-        log::warn!("Failed to sync note to filesystem: {}", e);
+    match vault_sync::sync_note_to_file(pool, &note.id) {
+        Ok(_) => {}
+        Err(vault_sync::SyncError::Conflict(conflict)) => {
+            log::warn!("Not overwriting {}: it was also edited on disk", conflict.file_path);
+            let _ = app.emit("note-sync-conflict", *conflict);
+        }
+        Err(e) => log::warn!("Failed to sync note to filesystem: {}", e),
     }
+}
+
+/// List a note's saved revisions, newest first
+#[tauri::command]
+pub fn list_note_revisions(pool: State<AppPool>, note_id: String) -> Result<Vec<NoteRevision>, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
+    note_revisions::list_note_revisions(&conn, &note_id).map_err(|e| e.to_string())
+}
+
+/// Restore a note's title and content from a saved revision
+#[tauri::command]
+pub fn revert_note_to_revision(
+    app: AppHandle,
+    pool: State<AppPool>,
+    search_index: State<AppSearchIndex>,
+    note_id: String,
+    revision_id: String,
+) -> Result<Note, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
+
+    let old_note = db::get_note(&conn, &note_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Note not found: {}", note_id))?;
+    let note = db::revert_note_to_revision(&conn, &note_id, &revision_id).map_err(|e| e.to_string())?;
+    sync_updated_note(&app, pool_ref, &search_index, &old_note, &note);
 
     Ok(note)
 }
@@ -143,7 +206,7 @@ fn delete_old_note_file(
     use crate::vault::{config as vault_config, markdown};
     
     let notes_dir = vault_config::get_notes_dir().map_err(|e| e.to_string())?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     
     // Build the old folder path
     let old_folder_path = if let Some(folder_id) = old_folder_id {
@@ -193,7 +256,7 @@ pub fn delete_note(
 ) -> Result<bool, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
     
     // Delete the file from filesystem before soft-deleting in DB
     // (we need the note data to find the file path)
@@ -207,7 +270,7 @@ pub fn delete_note(
     if result {
         let search_guard = search_index.0.read().unwrap();
         if let Some(ref index) = *search_guard {
-            if let Err(e) = index.delete_note(&id) {
+            if let Err(e) = index.delete_document(&id) {
                 eprintln!("Warning: Failed to delete note from search index: {}", e);
             }
         }
@@ -226,7 +289,7 @@ pub fn move_note_to_folder(
 ) -> Result<Note, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
     // Get the old note to find old folder for file deletion
     let old_note = db::get_note(&conn, &note_id)
@@ -239,17 +302,8 @@ pub fn move_note_to_folder(
     // Check if folder is actually changing
     let is_folder_changing = folder_id != old_folder_id;
 
-    // Update the note's folder_id directly in the database
-    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-    conn.execute(
-        "UPDATE notes SET folder_id = ?1, updated_at = ?2 WHERE id = ?3",
-        rusqlite::params![folder_id, now, note_id],
-    ).map_err(|e| format!("Failed to move note: {}", e))?;
-    
-    // Get the updated note
-    let note = db::get_note(&conn, &note_id)
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| format!("Note not found: {}", note_id))?;
+    let note = db::move_note_to_folder(&conn, &note_id, folder_id.as_deref())
+        .map_err(|e| format!("Failed to move note: {}", e))?;
 
     // Handle filesystem sync
     if is_folder_changing {
@@ -286,7 +340,7 @@ pub fn search_notes(
     // Fetch full notes from SQLite in the order of search results
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     let mut notes = Vec::with_capacity(search_results.len());
 
     for result in search_results {
@@ -1,11 +1,17 @@
 //! Tauri commands for search operations (fulltext, semantic, hybrid)
 
-use crate::ai::{extract_attachments_text, generate_embedding_direct, load_ai_config, EmbeddingModelInfo};
-use crate::db::{self, connection::DbPool, url_attachments};
-use crate::search::SearchIndex;
+use crate::ai::{
+    extract_attachments_text, generate_embedding_direct, generate_embeddings_batch,
+    generate_embeddings_with_fallback, load_ai_config, save_ai_config, EmbeddingModelInfo,
+    MAX_BATCH_INPUTS,
+};
+use crate::db::{self, connection::{get_connection, DbPool}, url_attachments};
+use crate::models::Note;
+use crate::search::{AnalyzerConfig, SearchError, SearchIndex};
 use crate::{AppPool, AppSearchIndex};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::path::Path;
 use std::sync::Arc;
 use tauri::State;
 
@@ -35,16 +41,60 @@ pub struct SearchResult {
     pub mode: String,
 }
 
+/// Inclusive timestamp bounds applied to search results
+#[derive(Debug, Clone, Default)]
+struct DateRangeFilter {
+    created_after: Option<DateTime<Utc>>,
+    created_before: Option<DateTime<Utc>>,
+    updated_after: Option<DateTime<Utc>>,
+    updated_before: Option<DateTime<Utc>>,
+}
+
+impl DateRangeFilter {
+    fn is_empty(&self) -> bool {
+        self.created_after.is_none()
+            && self.created_before.is_none()
+            && self.updated_after.is_none()
+            && self.updated_before.is_none()
+    }
+
+    fn matches(&self, note: &Note) -> bool {
+        self.created_after.is_none_or(|t| note.created_at >= t)
+            && self.created_before.is_none_or(|t| note.created_at <= t)
+            && self.updated_after.is_none_or(|t| note.updated_at >= t)
+            && self.updated_before.is_none_or(|t| note.updated_at <= t)
+    }
+}
+
+/// How many extra candidates to fetch when results are post-filtered
+const POST_FILTER_OVERFETCH: usize = 5;
+
 /// Search notes using the specified mode
+///
+/// When `folder_id` is set, results are restricted to notes in that folder
+/// or any of its descendants. The optional `created_*`/`updated_*` bounds
+/// further restrict results by note timestamps.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn search_notes_unified(
     pool: State<'_, AppPool>,
     search_index: State<'_, AppSearchIndex>,
     query: String,
     mode: SearchMode,
     limit: Option<usize>,
+    folder_id: Option<String>,
+    created_after: Option<DateTime<Utc>>,
+    created_before: Option<DateTime<Utc>>,
+    updated_after: Option<DateTime<Utc>>,
+    updated_before: Option<DateTime<Utc>>,
 ) -> Result<Vec<SearchResult>, String> {
     let limit = limit.unwrap_or(20);
+    let date_range = DateRangeFilter {
+        created_after,
+        created_before,
+        updated_after,
+        updated_before,
+    };
 
     // Clone pool and index before async work
     let pool_clone = {
@@ -57,15 +107,186 @@ pub async fn search_notes_unified(
         search_guard.as_ref().ok_or("Search index not initialized")?.clone()
     };
 
-    match mode {
-        SearchMode::Fulltext => search_fulltext(&pool_clone, &index_clone, &query, limit),
+    // Resolve the folder subtree up front so we can post-filter results
+    let folder_scope = match folder_id {
+        Some(ref id) => {
+            let conn = get_connection(&pool_clone).map_err(|e| e.to_string())?;
+            Some(
+                db::folders::get_folder_subtree_ids(&conn, id)
+                    .map_err(|e| format!("Failed to resolve folder: {}", e))?,
+            )
+        }
+        None => None,
+    };
+
+    // Over-fetch when filtering so it doesn't starve the result list
+    let needs_filter = folder_scope.is_some() || !date_range.is_empty();
+    let fetch_limit = if needs_filter {
+        limit * POST_FILTER_OVERFETCH
+    } else {
+        limit
+    };
+
+    let results = match mode {
+        SearchMode::Fulltext => search_fulltext(&pool_clone, &index_clone, &query, fetch_limit)?,
         SearchMode::Semantic => {
-            search_semantic(&pool_clone, &query, limit).await
+            search_semantic(&pool_clone, &query, fetch_limit).await?
         }
         SearchMode::Hybrid => {
-            search_hybrid(&pool_clone, &index_clone, &query, limit).await
+            search_hybrid(&pool_clone, &index_clone, &query, fetch_limit).await?
         }
+    };
+
+    if !needs_filter {
+        return Ok(results);
     }
+
+    let conn = get_connection(&pool_clone).map_err(|e| e.to_string())?;
+    let mut filtered = filter_results(&conn, results, |note| {
+        let in_scope = folder_scope.as_ref().is_none_or(|ids| {
+            note.folder_id.as_ref().is_some_and(|f| ids.contains(f))
+        });
+        in_scope && date_range.matches(note)
+    });
+    filtered.truncate(limit);
+    Ok(filtered)
+}
+
+/// Search notes within a folder and all of its subfolders
+#[tauri::command]
+pub async fn search_notes_in_folder(
+    pool: State<'_, AppPool>,
+    search_index: State<'_, AppSearchIndex>,
+    query: String,
+    mode: SearchMode,
+    folder_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchResult>, String> {
+    search_notes_unified(
+        pool,
+        search_index,
+        query,
+        mode,
+        limit,
+        Some(folder_id),
+        None,
+        None,
+        None,
+        None,
+    )
+    .await
+}
+
+/// Find matches of `query` in a note's content, for in-note find
+///
+/// Returns `(start, end)` UTF-16 offsets into the note's plain-text content
+/// (the same units as JavaScript string indices), sorted and with overlapping
+/// matches merged. Each whitespace-separated term is matched on its own; wrap
+/// words in double quotes to match them as a phrase.
+#[tauri::command]
+pub fn find_in_note(
+    pool: State<'_, AppPool>,
+    note_id: String,
+    query: String,
+    case_sensitive: Option<bool>,
+) -> Result<Vec<(usize, usize)>, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+
+    let note = db::notes::get_note(&conn, &note_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Note not found: {}", note_id))?;
+    let content = note.content.unwrap_or_default();
+
+    Ok(find_matches(&content, &query, case_sensitive.unwrap_or(false)))
+}
+
+/// UTF-16 ranges in `content` matching any term of `query`, merged where they overlap
+fn find_matches(content: &str, query: &str, case_sensitive: bool) -> Vec<(usize, usize)> {
+    let fold = |c: char| -> char {
+        if case_sensitive {
+            c
+        } else {
+            // Single-char lowercase keeps offsets aligned with the original text
+            c.to_lowercase().next().unwrap_or(c)
+        }
+    };
+    let haystack: Vec<char> = content.chars().map(fold).collect();
+
+    let mut ranges = Vec::new();
+    for term in query_terms(query) {
+        let needle: Vec<char> = term.chars().map(fold).collect();
+        if needle.is_empty() || needle.len() > haystack.len() {
+            continue;
+        }
+        // Every start position, so overlapping occurrences are all found
+        ranges.extend(
+            haystack
+                .windows(needle.len())
+                .enumerate()
+                .filter(|(_, window)| *window == needle.as_slice())
+                .map(|(start, _)| (start, start + needle.len())),
+        );
+    }
+
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    // Convert char positions to UTF-16 offsets, so characters outside the BMP count as two
+    let utf16_offsets: Vec<usize> = std::iter::once(0)
+        .chain(content.chars().scan(0, |offset, c| {
+            *offset += c.len_utf16();
+            Some(*offset)
+        }))
+        .collect();
+    merged
+        .into_iter()
+        .map(|(start, end)| (utf16_offsets[start], utf16_offsets[end]))
+        .collect()
+}
+
+/// Split a find query into terms, keeping double-quoted phrases whole
+fn query_terms(query: &str) -> Vec<&str> {
+    query
+        .split('"')
+        .enumerate()
+        .flat_map(|(i, part)| {
+            // Odd parts sit between a pair of quotes
+            if i % 2 == 1 {
+                vec![part]
+            } else {
+                part.split_whitespace().collect()
+            }
+        })
+        .filter(|term| !term.trim().is_empty())
+        .collect()
+}
+
+/// Keep only results whose note satisfies the predicate
+fn filter_results<F>(
+    conn: &rusqlite::Connection,
+    results: Vec<SearchResult>,
+    predicate: F,
+) -> Vec<SearchResult>
+where
+    F: Fn(&Note) -> bool,
+{
+    results
+        .into_iter()
+        .filter(|result| {
+            matches!(
+                db::notes::get_note(conn, &result.note_id),
+                Ok(Some(note)) if predicate(&note)
+            )
+        })
+        .collect()
 }
 
 /// Full-text search using Tantivy
@@ -79,11 +300,15 @@ fn search_fulltext(
         .search(query, limit)
         .map_err(|e| format!("Search error: {}", e))?;
 
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     let mut search_results = Vec::with_capacity(results.len());
     for result in results {
+        // The index can briefly lag behind soft deletes, so check the note is still live
         if let Ok(Some(note)) = db::notes::get_note(&conn, &result.id) {
+            if note.is_deleted {
+                continue;
+            }
             let snippet = note.content.as_ref().map(|c| {
                 // Create a snippet around potential matches
                 create_snippet(c, query, 150)
@@ -99,7 +324,7 @@ fn search_fulltext(
         }
     }
 
-    Ok(search_results)
+    Ok(dedupe_results(search_results))
 }
 
 /// Semantic search using embeddings
@@ -111,7 +336,7 @@ async fn search_semantic(
     log::info!("[search_semantic] Starting semantic search for: '{}'", query);
     
     // Get embedding config
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     let config = load_ai_config(&conn)?;
     let model = config.embedding.full_model_id();
     
@@ -164,11 +389,9 @@ async fn search_semantic(
     log::info!("[search_semantic] Found {} similar notes", similar.len());
 
     let mut search_results = Vec::with_capacity(similar.len());
-    let mut seen_note_ids: HashSet<String> = HashSet::new();
     
     for result in similar {
         if let Ok(Some(note)) = db::notes::get_note(&conn, &result.note_id) {
-            seen_note_ids.insert(note.id.clone());
             let snippet = note.content.as_ref().map(|c| {
                 // For semantic search, just take the beginning
                 truncate_content(c, 150)
@@ -194,14 +417,7 @@ async fn search_semantic(
     log::info!("[search_semantic] Found {} similar URL attachments", url_similar.len());
 
     for url_result in url_similar {
-        // Skip if we already have this note in results
-        if seen_note_ids.contains(&url_result.note_id) {
-            continue;
-        }
-        
         if let Ok(Some(note)) = db::notes::get_note(&conn, &url_result.note_id) {
-            seen_note_ids.insert(note.id.clone());
-            
             // Create snippet mentioning the matched URL
             let url_title = url_result.title.unwrap_or_else(|| url_result.url.clone());
             let snippet = Some(format!("Matched via linked URL: {}", url_title));
@@ -216,8 +432,8 @@ async fn search_semantic(
         }
     }
 
-    // Re-sort by score after adding URL results
-    search_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    // Notes matched directly and through their URLs collapse into one result
+    let mut search_results = dedupe_results(search_results);
     search_results.truncate(limit);
 
     log::info!(
@@ -265,11 +481,15 @@ async fn search_hybrid(
 }
 
 /// Combine results from multiple search methods using reciprocal rank fusion
+///
+/// A note found by both methods becomes one result, keeping the fields of
+/// whichever method ranked it higher and the snippets of both.
 fn combine_results(
     fulltext: Vec<SearchResult>,
     semantic: Vec<SearchResult>,
     limit: usize,
 ) -> Vec<SearchResult> {
+    use std::collections::hash_map::Entry;
     use std::collections::HashMap;
 
     const K: f32 = 60.0; // RRF constant
@@ -277,20 +497,22 @@ fn combine_results(
     // Calculate RRF scores
     let mut scores: HashMap<String, (f32, SearchResult)> = HashMap::new();
 
-    for (rank, result) in fulltext.into_iter().enumerate() {
-        let rrf_score = 1.0 / (K + rank as f32 + 1.0);
-        scores
-            .entry(result.note_id.clone())
-            .or_insert((0.0, result.clone()))
-            .0 += rrf_score;
-    }
-
-    for (rank, result) in semantic.into_iter().enumerate() {
-        let rrf_score = 1.0 / (K + rank as f32 + 1.0);
-        let entry = scores
-            .entry(result.note_id.clone())
-            .or_insert((0.0, result.clone()));
-        entry.0 += rrf_score;
+    for results in [fulltext, semantic] {
+        for (rank, mut result) in results.into_iter().enumerate() {
+            let rrf_score = 1.0 / (K + rank as f32 + 1.0);
+            // Compare variants by their rank rather than by incomparable raw scores
+            result.score = rrf_score;
+            match scores.entry(result.note_id.clone()) {
+                Entry::Occupied(mut entry) => {
+                    let (total, kept) = entry.get_mut();
+                    *total += rrf_score;
+                    merge_duplicate(kept, result);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert((rrf_score, result));
+                }
+            }
+        }
     }
 
     // Sort by combined score
@@ -308,6 +530,33 @@ fn combine_results(
         .collect()
 }
 
+/// Collapse results for the same note into one, highest score first
+fn dedupe_results(results: Vec<SearchResult>) -> Vec<SearchResult> {
+    let mut deduped: Vec<SearchResult> = Vec::with_capacity(results.len());
+    for result in results {
+        match deduped.iter_mut().find(|r| r.note_id == result.note_id) {
+            Some(kept) => merge_duplicate(kept, result),
+            None => deduped.push(result),
+        }
+    }
+    deduped.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    deduped
+}
+
+/// Fold `other` into `kept`, both results for the same note
+///
+/// The higher-scoring result's fields win; snippets from both are kept.
+fn merge_duplicate(kept: &mut SearchResult, mut other: SearchResult) {
+    if other.score > kept.score {
+        std::mem::swap(kept, &mut other);
+    }
+    kept.snippet = match (kept.snippet.take(), other.snippet) {
+        (Some(a), Some(b)) if a.contains(&b) => Some(a),
+        (Some(a), Some(b)) => Some(format!("{} ... {}", a, b)),
+        (a, b) => a.or(b),
+    };
+}
+
 /// Get notes related to a specific note
 #[tauri::command]
 pub async fn get_related_notes(
@@ -318,7 +567,7 @@ pub async fn get_related_notes(
     let limit = limit.unwrap_or(5);
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
 
     // Find similar notes based on embedding
     let similar = db::embeddings::search_similar_to_note(&conn, &note_id, limit, Some(0.3))
@@ -342,6 +591,215 @@ pub async fn get_related_notes(
     Ok(search_results)
 }
 
+/// A note in a group of likely duplicates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateNote {
+    pub note_id: String,
+    pub title: String,
+    pub snippet: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Notes similar enough to be worth reviewing for a merge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateNoteGroup {
+    /// Most recently updated first
+    pub notes: Vec<DuplicateNote>,
+    /// Highest similarity between two notes in the group (0-1)
+    pub similarity: f32,
+}
+
+/// Default similarity above which notes are reported as duplicates
+const DEFAULT_DUPLICATE_THRESHOLD: f32 = 0.97;
+
+/// Find groups of near-duplicate notes by embedding similarity
+///
+/// Only embeddings from the configured embedding model are compared. The
+/// pairwise comparison runs on a blocking thread.
+#[tauri::command]
+pub async fn find_duplicate_notes(
+    pool: State<'_, AppPool>,
+    threshold: Option<f32>,
+) -> Result<Vec<DuplicateNoteGroup>, String> {
+    let threshold = threshold.unwrap_or(DEFAULT_DUPLICATE_THRESHOLD);
+    let pool_clone = {
+        let pool_guard = pool.0.read().unwrap();
+        pool_guard.as_ref().ok_or("Database not initialized")?.clone()
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = get_connection(&pool_clone).map_err(|e| e.to_string())?;
+        let model = load_ai_config(&conn)?.embedding.full_model_id();
+
+        let groups = db::embeddings::find_duplicate_groups(&conn, &model, threshold)
+            .map_err(|e| format!("Duplicate detection error: {}", e))?;
+
+        let mut duplicate_groups = Vec::with_capacity(groups.len());
+        for group in groups {
+            let notes: Vec<DuplicateNote> = group
+                .note_ids
+                .iter()
+                .filter_map(|id| db::notes::get_note(&conn, id).ok().flatten())
+                .map(|note| DuplicateNote {
+                    snippet: note.content.as_ref().map(|c| truncate_content(c, 100)),
+                    note_id: note.id,
+                    title: note.title,
+                    updated_at: note.updated_at,
+                })
+                .collect();
+            if notes.len() > 1 {
+                duplicate_groups.push(DuplicateNoteGroup {
+                    notes,
+                    similarity: group.similarity,
+                });
+            }
+        }
+
+        Ok(duplicate_groups)
+    })
+    .await
+    .map_err(|e| format!("Duplicate detection task failed: {}", e))?
+}
+
+/// Rebuild the full-text search index from the database
+///
+/// For recovering from an index that's out of step with the notes. Returns
+/// the number of notes indexed.
+#[tauri::command]
+pub fn rebuild_search_index(
+    pool: State<'_, AppPool>,
+    search_index: State<'_, AppSearchIndex>,
+) -> Result<u64, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+    let search_guard = search_index.0.read().unwrap();
+    let search_index = search_guard.as_ref().ok_or("Search index not initialized")?;
+
+    crate::reindex_notes(&conn, search_index)
+        .map_err(|e| format!("Failed to rebuild search index: {}", e))?;
+    log::info!("Rebuilt search index with {} notes", search_index.doc_count());
+    Ok(search_index.doc_count())
+}
+
+/// Settings key the search analyzer config is stored under, as JSON
+pub const SEARCH_ANALYZER_SETTING: &str = "search_analyzer";
+
+/// Load the configured search analyzer, or the default if none is saved
+pub fn load_search_analyzer(conn: &rusqlite::Connection) -> AnalyzerConfig {
+    match db::settings::get_setting(conn, SEARCH_ANALYZER_SETTING) {
+        Ok(Some(json)) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            log::warn!("Invalid search analyzer setting, using the default: {}", e);
+            AnalyzerConfig::default()
+        }),
+        _ => AnalyzerConfig::default(),
+    }
+}
+
+/// Get the language and stop word settings the search index uses
+#[tauri::command]
+pub fn get_search_analyzer(pool: State<'_, AppPool>) -> Result<AnalyzerConfig, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+    Ok(load_search_analyzer(&conn))
+}
+
+/// Save the search analyzer settings, reopening and rebuilding the index if they changed
+///
+/// The new index is built next to the live one and only swapped in, and the
+/// setting saved, once the rebuild succeeds. On failure search keeps working
+/// with the previous analyzer. Returns the number of notes in the index.
+#[tauri::command]
+pub fn set_search_analyzer(
+    pool: State<'_, AppPool>,
+    search_index: State<'_, AppSearchIndex>,
+    config: AnalyzerConfig,
+) -> Result<u64, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+
+    let mut search_guard = search_index.0.write().unwrap();
+    if let Some(index) = search_guard.as_ref().filter(|index| index.analyzer() == config) {
+        db::settings::set_setting(&conn, SEARCH_ANALYZER_SETTING, &json).map_err(|e| e.to_string())?;
+        return Ok(index.doc_count());
+    }
+
+    let index_path = db::connection::get_search_index_path().map_err(|e| e.to_string())?;
+    let staging_path = index_path.with_extension("rebuild");
+    build_staged_index(&conn, &staging_path, config)?;
+
+    // Close the old index first so its writer lock is released
+    let previous = search_guard.take().map(|index| index.analyzer()).unwrap_or_default();
+    let index = match swap_in_index(&index_path, &staging_path, config) {
+        Ok(index) => index,
+        Err(e) => {
+            *search_guard = SearchIndex::with_analyzer(index_path, previous).ok().map(Arc::new);
+            return Err(format!("Failed to reopen search index: {}", e));
+        }
+    };
+    if index.needs_rebuild() {
+        crate::reindex_notes(&conn, &index).map_err(|e| format!("Failed to rebuild search index: {}", e))?;
+    }
+    let count = index.doc_count();
+    *search_guard = Some(Arc::new(index));
+    db::settings::set_setting(&conn, SEARCH_ANALYZER_SETTING, &json).map_err(|e| e.to_string())?;
+    log::info!("Rebuilt search index for {:?} with {} notes", config, count);
+    Ok(count)
+}
+
+/// Build a fresh index of every note at `staging_path`, replacing anything left there
+fn build_staged_index(
+    conn: &rusqlite::Connection,
+    staging_path: &Path,
+    config: AnalyzerConfig,
+) -> Result<(), String> {
+    if staging_path.exists() {
+        std::fs::remove_dir_all(staging_path).map_err(|e| format!("Failed to clear staging index: {}", e))?;
+    }
+    let built = SearchIndex::with_analyzer(staging_path.to_path_buf(), config)
+        .and_then(|index| crate::reindex_notes(conn, &index));
+    if let Err(e) = built {
+        let _ = std::fs::remove_dir_all(staging_path);
+        return Err(format!("Failed to rebuild search index: {}", e));
+    }
+    Ok(())
+}
+
+/// Move the index built at `staging_path` to `index_path` and open it
+///
+/// The old index is set aside until the new one opens, and is put back if it doesn't.
+fn swap_in_index(index_path: &Path, staging_path: &Path, config: AnalyzerConfig) -> Result<SearchIndex, SearchError> {
+    let backup_path = index_path.with_extension("old");
+    if backup_path.exists() {
+        std::fs::remove_dir_all(&backup_path)?;
+    }
+    if index_path.exists() {
+        std::fs::rename(index_path, &backup_path)?;
+    }
+
+    let opened = std::fs::rename(staging_path, index_path)
+        .map_err(SearchError::from)
+        .and_then(|_| SearchIndex::with_analyzer(index_path.to_path_buf(), config));
+    match opened {
+        Ok(index) => {
+            let _ = std::fs::remove_dir_all(&backup_path);
+            Ok(index)
+        }
+        Err(e) => {
+            if backup_path.exists() {
+                let _ = std::fs::remove_dir_all(index_path);
+                std::fs::rename(&backup_path, index_path)?;
+            }
+            Err(e)
+        }
+    }
+}
+
 /// Get embedding statistics
 #[tauri::command]
 pub async fn get_embedding_stats(
@@ -349,7 +807,7 @@ pub async fn get_embedding_stats(
 ) -> Result<db::EmbeddingStats, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     let config = load_ai_config(&conn)?;
     
     db::embeddings::get_embedding_stats(&conn, &config.embedding.full_model_id())
@@ -380,7 +838,7 @@ pub async fn detect_embedding_dimension(
     let (provider_url, api_key) = {
         let pool_guard = pool.0.read().unwrap();
         let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-        let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+        let conn = get_connection(pool).map_err(|e| e.to_string())?;
         let config = load_ai_config(&conn)?;
         let embedding_provider = config.providers.iter()
             .find(|p| p.id == config.embedding.provider);
@@ -424,8 +882,8 @@ pub async fn reindex_embeddings(
     };
     
     // Do initial sync db work - get notes and URL attachments
-    let (notes, indexed_urls, embedding_model, provider_url, api_key) = {
-        let conn = pool_clone.get().map_err(|e| format!("Database error: {}", e))?;
+    let (notes, indexed_urls, embedding_model, configured_dimension, provider_url, api_key) = {
+        let conn = get_connection(&pool_clone).map_err(|e| e.to_string())?;
         
         // Delete all existing note embeddings
         db::embeddings::delete_all_embeddings(&conn)
@@ -458,7 +916,14 @@ pub async fn reindex_embeddings(
         let provider_url = embedding_provider.and_then(|p| p.base_url.clone());
         let api_key = embedding_provider.and_then(|p| p.api_key.clone());
         
-        (notes, indexed_urls, config.embedding.full_model_id(), provider_url, api_key)
+        (
+            notes,
+            indexed_urls,
+            config.embedding.full_model_id(),
+            config.embedding.dimension,
+            provider_url,
+            api_key,
+        )
     };
     
     let total_notes = notes.len() as u32;
@@ -469,13 +934,15 @@ pub async fn reindex_embeddings(
     let mut embedded_count = 0u32;
     let mut url_embedded_count = 0u32;
     let mut errors: Vec<String> = Vec::new();
+    // Every embedding in this run must match the first one produced
+    let mut run_dimension: Option<u32> = None;
     
-    // Embed notes
+    // Prepare note texts (title + content + attachment text)
+    let mut note_texts: Vec<(String, String)> = Vec::new();
     for note in notes {
         // Extract text from attachments referenced in the note
         let attachment_text = extract_attachments_text(&note.content, Some(10000));
         
-        // Prepare text to embed (title + content + attachment text)
         let base_content = note.content.unwrap_or_default();
         let text_to_embed = if attachment_text.is_empty() {
             format!("{}\n\n{}", note.title, base_content)
@@ -486,37 +953,41 @@ pub async fn reindex_embeddings(
             )
         };
         
-        if text_to_embed.trim().is_empty() {
-            continue;
+        if !text_to_embed.trim().is_empty() {
+            note_texts.push((note.id, text_to_embed));
         }
+    }
+    
+    // Embed notes in batches so each provider request carries many inputs
+    for batch in note_texts.chunks(MAX_BATCH_INPUTS) {
+        let texts: Vec<String> = batch.iter().map(|(_, text)| text.clone()).collect();
         
-        // Generate embedding using direct provider call
-        match generate_embedding_direct(&text_to_embed, &embedding_model, provider_url.as_deref(), api_key.as_deref()).await {
-            Ok(result) => {
-                // Store embedding (sync db work)
-                let conn = pool_clone.get().map_err(|e| format!("Database error: {}", e))?;
-                if let Err(e) = db::embeddings::store_embedding(
+        let results = generate_embeddings_with_fallback(&texts, &embedding_model, provider_url.as_deref(), api_key.as_deref()).await;
+
+        // Store embeddings (sync db work)
+        let conn = get_connection(&pool_clone).map_err(|e| e.to_string())?;
+        for ((note_id, _), result) in batch.iter().zip(results) {
+            let stored = result.map_err(|e| format!("Embedding failed for note {}: {}", note_id, e)).and_then(|result| {
+                let expected = *run_dimension.get_or_insert(result.embedding.len() as u32);
+                db::embeddings::store_embedding(
                     &conn,
-                    &note.id,
+                    note_id,
                     &result.embedding,
                     &embedding_model,
                     Some(&result.model),
-                ) {
-                    let err_msg = format!("Failed to store embedding: {}", e);
+                    Some(expected),
+                )
+                .map_err(|e| format!("Failed to store embedding: {}", e))
+            });
+
+            match stored {
+                Ok(_) => embedded_count += 1,
+                Err(err_msg) => {
                     log::warn!("{}", err_msg);
+                    // Only keep first few errors to avoid huge response
                     if errors.len() < 5 {
                         errors.push(err_msg);
                     }
-                } else {
-                    embedded_count += 1;
-                }
-            }
-            Err(e) => {
-                let err_msg = format!("Embedding failed: {}", e);
-                log::warn!("{}", err_msg);
-                // Only keep first few errors to avoid huge response
-                if errors.len() < 5 {
-                    errors.push(err_msg);
                 }
             }
         }
@@ -559,12 +1030,14 @@ pub async fn reindex_embeddings(
             
             match generate_embedding_direct(&text_to_embed, &embedding_model, provider_url.as_deref(), api_key.as_deref()).await {
                 Ok(result) => {
-                    let conn = pool_clone.get().map_err(|e| format!("Database error: {}", e))?;
+                    let expected = *run_dimension.get_or_insert(result.embedding.len() as u32);
+                    let conn = get_connection(&pool_clone).map_err(|e| e.to_string())?;
                     if let Err(e) = url_attachments::store_url_embedding(
                         &conn,
                         &url_attachment.id,
                         &result.embedding,
                         &embedding_model,
+                        Some(expected),
                     ) {
                         let err_msg = format!("Failed to store URL embedding: {}", e);
                         log::warn!("{}", err_msg);
@@ -589,30 +1062,32 @@ pub async fn reindex_embeddings(
             let mut chunk_embeddings: Vec<(String, usize, usize, Vec<f32>)> = Vec::new();
             let mut chunk_failed = false;
             
-            for (chunk_text, char_start, char_end) in &chunks {
-                match generate_embedding_direct(chunk_text, &embedding_model, provider_url.as_deref(), api_key.as_deref()).await {
-                    Ok(result) => {
-                        chunk_embeddings.push((chunk_text.clone(), *char_start, *char_end, result.embedding));
+            let chunk_texts: Vec<String> = chunks.iter().map(|(text, _, _)| text.clone()).collect();
+            match generate_embeddings_batch(&chunk_texts, &embedding_model, provider_url.as_deref(), api_key.as_deref()).await {
+                Ok(results) => {
+                    for ((chunk_text, char_start, char_end), result) in chunks.into_iter().zip(results) {
+                        chunk_embeddings.push((chunk_text, char_start, char_end, result.embedding));
                     }
-                    Err(e) => {
-                        let err_msg = format!("URL chunk embedding failed for {}: {}", url_attachment.url, e);
-                        log::warn!("{}", err_msg);
-                        if errors.len() < 5 {
-                            errors.push(err_msg);
-                        }
-                        chunk_failed = true;
-                        break;
+                }
+                Err(e) => {
+                    let err_msg = format!("URL chunk embedding failed for {}: {}", url_attachment.url, e);
+                    log::warn!("{}", err_msg);
+                    if errors.len() < 5 {
+                        errors.push(err_msg);
                     }
+                    chunk_failed = true;
                 }
             }
             
             if !chunk_failed && !chunk_embeddings.is_empty() {
-                let conn = pool_clone.get().map_err(|e| format!("Database error: {}", e))?;
+                let expected = *run_dimension.get_or_insert(chunk_embeddings[0].3.len() as u32);
+                let conn = get_connection(&pool_clone).map_err(|e| e.to_string())?;
                 if let Err(e) = url_attachments::store_url_embedding_chunks(
                     &conn,
                     &url_attachment.id,
                     &chunk_embeddings,
                     &embedding_model,
+                    Some(expected),
                 ) {
                     let err_msg = format!("Failed to store URL chunk embeddings: {}", e);
                     log::warn!("{}", err_msg);
@@ -628,6 +1103,15 @@ pub async fn reindex_embeddings(
     
     log::info!("[Reindex] Complete: {} notes, {} URLs embedded", embedded_count, url_embedded_count);
     
+    // Reindexing is how a model change gets picked up, so record the dimension it produced
+    if let Some(dimension) = run_dimension.filter(|d| *d != configured_dimension) {
+        let conn = get_connection(&pool_clone).map_err(|e| e.to_string())?;
+        let mut config = load_ai_config(&conn)?;
+        config.embedding.dimension = dimension;
+        save_ai_config(&conn, &config)?;
+        log::info!("[Reindex] Updated configured embedding dimension to {}", dimension);
+    }
+    
     Ok(ReindexResult {
         embedded_count,
         total_notes,
@@ -696,7 +1180,7 @@ async fn embed_note_internal(
     note_id: &str,
     check_auto_embed: bool,
 ) -> Result<bool, String> {
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     
     // Get the note
     let note = db::notes::get_note(&conn, note_id)
@@ -761,6 +1245,7 @@ async fn embed_note_internal(
         &result.embedding,
         &full_model_id,
         Some(&result.model),
+        config.embedding.expected_dimension(),
     )
     .map_err(|e| format!("Failed to store embedding: {}", e))?;
     
@@ -906,3 +1391,312 @@ fn find_char_boundary(s: &str, pos: usize) -> usize {
     }
     p
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+    use crate::db::folders::{create_folder, get_folder_subtree_ids};
+    use crate::db::notes::create_note;
+    use crate::models::{CreateFolderInput, CreateNoteInput};
+
+    fn result_for(note_id: &str) -> SearchResult {
+        SearchResult {
+            note_id: note_id.to_string(),
+            title: String::new(),
+            snippet: None,
+            score: 1.0,
+            mode: "fulltext".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_filter_results_by_folder_subtree() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let folder = |name: &str, parent_id: Option<String>| {
+            create_folder(
+                &conn,
+                CreateFolderInput {
+                    name: name.to_string(),
+                    parent_id,
+                },
+            )
+            .unwrap()
+        };
+        let note = |title: &str, folder_id: Option<String>| {
+            create_note(
+                &conn,
+                CreateNoteInput {
+                    title: title.to_string(),
+                    content: Some("shared keyword".to_string()),
+                    content_html: None,
+                    folder_id,
+                },
+            )
+            .unwrap()
+        };
+
+        let project = folder("Project", None);
+        let sub = folder("Design", Some(project.id.clone()));
+        let other = folder("Personal", None);
+
+        let in_root = note("Kickoff", Some(project.id.clone()));
+        let in_sub = note("Wireframes", Some(sub.id.clone()));
+        let outside = note("Groceries", Some(other.id.clone()));
+        let unfiled = note("Scratch", None);
+
+        let results = vec![
+            result_for(&in_root.id),
+            result_for(&outside.id),
+            result_for(&in_sub.id),
+            result_for(&unfiled.id),
+        ];
+
+        let scope = get_folder_subtree_ids(&conn, &project.id).unwrap();
+        let filtered = filter_results(&conn, results, |note| {
+            note.folder_id.as_ref().is_some_and(|f| scope.contains(f))
+        });
+
+        let ids: Vec<&str> = filtered.iter().map(|r| r.note_id.as_str()).collect();
+        assert_eq!(ids, vec![in_root.id.as_str(), in_sub.id.as_str()]);
+    }
+
+    #[test]
+    fn test_fulltext_search_excludes_deleted_notes() {
+        let pool = init_test_pool().unwrap();
+        let dir = tempfile::TempDir::new().unwrap();
+        let index = Arc::new(SearchIndex::new(dir.path().to_path_buf()).unwrap());
+
+        let (kept, removed, stale) = {
+            let conn = pool.get().unwrap();
+            let note = |title: &str| {
+                create_note(
+                    &conn,
+                    CreateNoteInput {
+                        title: title.to_string(),
+                        content: Some("shared keyword".to_string()),
+                        content_html: None,
+                        folder_id: None,
+                    },
+                )
+                .unwrap()
+            };
+            (note("Kept"), note("Removed"), note("Stale"))
+        };
+        for note in [&kept, &removed, &stale] {
+            index.add_note(&note.id, &note.title, note.content.as_deref()).unwrap();
+        }
+
+        let ids = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.note_id).collect()
+        };
+        assert_eq!(search_fulltext(&pool, &index, "keyword", 10).unwrap().len(), 3);
+
+        // Deleting a note removes its document
+        {
+            let conn = pool.get().unwrap();
+            db::notes::delete_note(&conn, &removed.id).unwrap();
+        }
+        index.delete_document(&removed.id).unwrap();
+        assert_eq!(index.doc_count(), 2);
+
+        // A soft-deleted note whose document lingers is still filtered out
+        {
+            let conn = pool.get().unwrap();
+            db::notes::delete_note(&conn, &stale.id).unwrap();
+        }
+        assert_eq!(ids(search_fulltext(&pool, &index, "keyword", 10).unwrap()), vec![kept.id]);
+    }
+
+    #[test]
+    fn test_note_matched_by_content_and_url_is_one_result() {
+        let result = |score: f32, snippet: &str, mode: &str| SearchResult {
+            note_id: "note".to_string(),
+            title: "Trip planning".to_string(),
+            snippet: Some(snippet.to_string()),
+            score,
+            mode: mode.to_string(),
+        };
+        let content = result(0.8, "Flights to Lisbon in May", "semantic");
+        let url = result(0.9 * 0.9, "Matched via linked URL: Lisbon guide", "semantic");
+        let other = SearchResult { note_id: "other".to_string(), ..result(0.5, "Packing list", "semantic") };
+
+        // Within one search method
+        let deduped = dedupe_results(vec![content.clone(), other.clone(), url.clone()]);
+        assert_eq!(deduped.len(), 2);
+        assert_eq!(deduped[0].note_id, "note");
+        assert_eq!(deduped[0].score, 0.9 * 0.9);
+        assert_eq!(
+            deduped[0].snippet.as_deref(),
+            Some("Matched via linked URL: Lisbon guide ... Flights to Lisbon in May")
+        );
+
+        // Across fulltext and semantic results in hybrid search
+        let fulltext = vec![result(3.2, "...Flights to Lisbon...", "fulltext")];
+        let combined = combine_results(fulltext, vec![other, url], 10);
+        assert_eq!(combined.len(), 2);
+        let note = combined.iter().find(|r| r.note_id == "note").unwrap();
+        assert_eq!(note.mode, "fulltext");
+        assert_eq!(
+            note.snippet.as_deref(),
+            Some("...Flights to Lisbon... ... Matched via linked URL: Lisbon guide")
+        );
+        assert_eq!(combined[0].note_id, "note");
+    }
+
+    #[test]
+    fn test_find_matches_case_sensitivity() {
+        let content = "Rust is fun. rust never sleeps. RUST!";
+        assert_eq!(find_matches(content, "rust", false), vec![(0, 4), (13, 17), (32, 36)]);
+        assert_eq!(find_matches(content, "rust", true), vec![(13, 17)]);
+        assert_eq!(find_matches(content, "Rust", true), vec![(0, 4)]);
+        assert!(find_matches(content, "python", false).is_empty());
+        assert!(find_matches(content, "  ", false).is_empty());
+    }
+
+    #[test]
+    fn test_find_matches_multiple_terms_and_overlaps() {
+        // Overlapping occurrences of one term merge into a single range
+        assert_eq!(find_matches("banana", "ana", false), vec![(1, 6)]);
+
+        // Each term is found, and overlapping terms merge
+        let content = "The quick brown fox jumps over the lazy dog";
+        assert_eq!(
+            find_matches(content, "fox quick", false),
+            vec![(4, 9), (16, 19)]
+        );
+        assert_eq!(find_matches(content, "brown own", false), vec![(10, 15)]);
+
+        // Quoted phrases match as a whole
+        assert_eq!(find_matches(content, "\"lazy dog\"", false), vec![(35, 43)]);
+        assert_eq!(find_matches(content, "\"dog lazy\"", false), vec![]);
+    }
+
+    #[test]
+    fn test_find_matches_uses_utf16_offsets() {
+        let content = "Café ☕ and café";
+        assert_eq!(find_matches(content, "CAFÉ", false), vec![(0, 4), (11, 15)]);
+
+        // The emoji is one char but two UTF-16 code units
+        let content = "🎉 party 🎉 party";
+        assert_eq!(find_matches(content, "party", false), vec![(3, 8), (12, 17)]);
+        assert_eq!(find_matches(content, "🎉", false), vec![(0, 2), (9, 11)]);
+    }
+
+    #[test]
+    fn test_load_search_analyzer() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        assert_eq!(load_search_analyzer(&conn), AnalyzerConfig::default());
+
+        db::settings::set_setting(&conn, SEARCH_ANALYZER_SETTING, r#"{"language":"German","stopWords":false}"#).unwrap();
+        let config = load_search_analyzer(&conn);
+        assert_eq!(config.language, tantivy::tokenizer::Language::German);
+        assert!(!config.stop_words);
+
+        db::settings::set_setting(&conn, SEARCH_ANALYZER_SETTING, "not json").unwrap();
+        assert_eq!(load_search_analyzer(&conn), AnalyzerConfig::default());
+    }
+
+    #[test]
+    fn test_filter_results_by_date_range() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let dated_note = |title: &str, created: &str, updated: &str| {
+            let note = create_note(
+                &conn,
+                CreateNoteInput {
+                    title: title.to_string(),
+                    content: None,
+                    content_html: None,
+                    folder_id: None,
+                },
+            )
+            .unwrap();
+            conn.execute(
+                "UPDATE notes SET created_at = ?1, updated_at = ?2 WHERE id = ?3",
+                rusqlite::params![created, updated, note.id],
+            )
+            .unwrap();
+            note
+        };
+
+        let january = dated_note("January", "2025-01-10 09:00:00", "2025-01-15 09:00:00");
+        let april = dated_note("April", "2025-04-02 09:00:00", "2025-06-20 09:00:00");
+        let august = dated_note("August", "2025-08-05 09:00:00", "2025-08-06 09:00:00");
+
+        let results = || {
+            vec![
+                result_for(&january.id),
+                result_for(&april.id),
+                result_for(&august.id),
+            ]
+        };
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+        // Created in Q2
+        let q2 = DateRangeFilter {
+            created_after: Some(at("2025-04-01T00:00:00Z")),
+            created_before: Some(at("2025-06-30T23:59:59Z")),
+            ..Default::default()
+        };
+        let filtered = filter_results(&conn, results(), |note| q2.matches(note));
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].note_id, april.id);
+
+        // Updated since June
+        let recent = DateRangeFilter {
+            updated_after: Some(at("2025-06-01T00:00:00Z")),
+            ..Default::default()
+        };
+        let filtered = filter_results(&conn, results(), |note| recent.matches(note));
+        let ids: Vec<&str> = filtered.iter().map(|r| r.note_id.as_str()).collect();
+        assert_eq!(ids, vec![april.id.as_str(), august.id.as_str()]);
+
+        // Updated before February
+        let early = DateRangeFilter {
+            updated_before: Some(at("2025-02-01T00:00:00Z")),
+            ..Default::default()
+        };
+        let filtered = filter_results(&conn, results(), |note| early.matches(note));
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].note_id, january.id);
+
+        assert!(DateRangeFilter::default().is_empty());
+        assert!(!q2.is_empty());
+    }
+
+    #[test]
+    fn test_swap_in_index_keeps_old_index_on_failure() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let index_path = dir.path().join("search_index");
+        let staging_path = index_path.with_extension("rebuild");
+        let stemmed = AnalyzerConfig::default();
+        let exact = AnalyzerConfig { stop_words: false, ..stemmed };
+
+        let old = SearchIndex::with_analyzer(index_path.clone(), stemmed).unwrap();
+        old.add_note("old", "Old note", None).unwrap();
+        drop(old);
+
+        // Nothing was staged, so the old index has to come back untouched
+        assert!(swap_in_index(&index_path, &staging_path, exact).is_err());
+        let restored = SearchIndex::with_analyzer(index_path.clone(), stemmed).unwrap();
+        assert!(!restored.needs_rebuild());
+        assert_eq!(restored.search("old", 10).unwrap().len(), 1);
+        drop(restored);
+
+        let staged = SearchIndex::with_analyzer(staging_path.clone(), exact).unwrap();
+        staged.add_note("new", "New note", None).unwrap();
+        drop(staged);
+
+        let swapped = swap_in_index(&index_path, &staging_path, exact).unwrap();
+        assert_eq!(swapped.analyzer(), exact);
+        assert!(!swapped.needs_rebuild());
+        assert_eq!(swapped.search("new", 10).unwrap()[0].id, "new");
+        assert!(!staging_path.exists());
+        assert!(!index_path.with_extension("old").exists());
+    }
+}
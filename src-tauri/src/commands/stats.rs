@@ -0,0 +1,371 @@
+//! Tauri commands for note and vault statistics
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use tauri::State;
+
+use crate::ai::{estimate_cost, load_model_pricing, CostEstimate};
+use crate::db::connection::get_connection;
+use crate::db::conversations;
+use crate::db::notes as notes_db;
+use crate::exports::html_to_markdown;
+use crate::AppPool;
+
+/// Average reading speed used for the reading time estimate
+const WORDS_PER_MINUTE: usize = 200;
+
+/// Number of tags listed in the vault stats by default
+const DEFAULT_TOP_TAGS: usize = 10;
+
+static WIKILINK_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[\[[^\[\]]+\]\]").unwrap());
+
+/// At-a-glance statistics for a note
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteStats {
+    pub word_count: usize,
+    /// Characters of rendered text, including spaces but not markdown syntax
+    pub character_count: usize,
+    pub heading_count: usize,
+    /// Markdown links, autolinks and `[[wikilinks]]`
+    pub link_count: usize,
+    /// Estimated minutes to read, rounded up
+    pub reading_time_minutes: usize,
+}
+
+/// How many notes carry a tag
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagUsage {
+    pub id: String,
+    pub name: String,
+    pub color: Option<String>,
+    pub note_count: usize,
+}
+
+/// Vault-wide totals for the overview panel
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultStats {
+    pub note_count: usize,
+    pub folder_count: usize,
+    pub tag_count: usize,
+    /// Words across all notes, code blocks excluded
+    pub word_count: usize,
+    /// Notes with an embedding for semantic search
+    pub embedded_note_count: usize,
+    pub url_attachment_count: usize,
+    pub export_count: usize,
+    /// Tags on the most notes, most used first
+    pub top_tags: Vec<TagUsage>,
+    /// Token usage and estimated cost of all chat responses
+    pub ai_usage: CostEstimate,
+}
+
+/// Get word, character, heading and link counts plus reading time for a note
+///
+/// Code blocks are left out of the word and character counts unless
+/// `include_code` is set.
+#[tauri::command]
+pub fn get_note_stats(
+    pool: State<AppPool>,
+    note_id: String,
+    include_code: Option<bool>,
+) -> Result<NoteStats, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+
+    let note = notes_db::get_note(&conn, &note_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Note not found: {}", note_id))?;
+
+    let markdown = note_markdown(note.content, note.content_html);
+    Ok(compute_note_stats(&markdown, include_code.unwrap_or(false)))
+}
+
+/// Markdown for a note's stats
+///
+/// The editor saves `content` as plain text, so headings and links only
+/// survive in the HTML; the plain text is used when there is no HTML.
+fn note_markdown(content: Option<String>, content_html: Option<String>) -> String {
+    match (content_html, content) {
+        (Some(html), _) if !html.trim().is_empty() => html_to_markdown::html_to_markdown(&html),
+        (_, Some(content)) => content,
+        _ => String::new(),
+    }
+}
+
+/// Get totals across the vault: notes, folders, tags, words, embeddings,
+/// URL attachments, exports and AI spend, plus the `top_tags` most used tags
+#[tauri::command]
+pub fn get_vault_stats(pool: State<AppPool>, top_tags: Option<usize>) -> Result<VaultStats, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+
+    collect_vault_stats(&conn, top_tags.unwrap_or(DEFAULT_TOP_TAGS)).map_err(|e| e.to_string())
+}
+
+/// Aggregate the vault stats; deleted notes and anything attached to them are left out
+fn collect_vault_stats(conn: &Connection, top_tags: usize) -> Result<VaultStats, rusqlite::Error> {
+    let count = |sql: &str| conn.query_row(sql, [], |row| row.get::<_, i64>(0)).map(|n| n as usize);
+
+    // Words are counted one note at a time so the whole vault is never in memory
+    let mut word_count = 0;
+    let mut stmt = conn.prepare("SELECT content FROM notes WHERE is_deleted = FALSE AND content IS NOT NULL")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let content: String = row.get(0)?;
+        word_count += compute_note_stats(&content, false).word_count;
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.name, t.color, COUNT(n.id) AS note_count
+         FROM tags t
+         JOIN note_tags nt ON nt.tag_id = t.id
+         JOIN notes n ON n.id = nt.note_id AND n.is_deleted = FALSE
+         GROUP BY t.id
+         ORDER BY note_count DESC, t.name COLLATE NOCASE
+         LIMIT ?1",
+    )?;
+    let top_tags = stmt
+        .query_map([top_tags as i64], |row| {
+            Ok(TagUsage {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+                note_count: row.get::<_, i64>(3)? as usize,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    // Unreadable message metadata shouldn't hide the rest of the stats
+    let usage = conversations::get_message_usage(conn, None).unwrap_or_else(|e| {
+        log::warn!("Failed to read token usage for vault stats: {}", e);
+        Vec::new()
+    });
+    let ai_usage = estimate_cost(
+        usage.iter().map(|(model, u)| (model.as_deref(), u)),
+        &load_model_pricing(conn),
+    );
+
+    Ok(VaultStats {
+        note_count: count("SELECT COUNT(*) FROM notes WHERE is_deleted = FALSE")?,
+        folder_count: count("SELECT COUNT(*) FROM folders")?,
+        tag_count: count("SELECT COUNT(*) FROM tags")?,
+        word_count,
+        embedded_note_count: count(
+            "SELECT COUNT(*) FROM note_embeddings e JOIN notes n ON n.id = e.note_id WHERE n.is_deleted = FALSE",
+        )?,
+        url_attachment_count: count(
+            "SELECT COUNT(*) FROM url_attachments u JOIN notes n ON n.id = u.note_id WHERE n.is_deleted = FALSE",
+        )?,
+        export_count: count("SELECT COUNT(*) FROM exports")?,
+        top_tags,
+        ai_usage,
+    })
+}
+
+/// Count the statistics for a markdown document
+pub fn compute_note_stats(markdown: &str, include_code: bool) -> NoteStats {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+
+    let mut stats = NoteStats::default();
+    // Rendered text, with blocks separated by newlines
+    let mut text = String::new();
+    let mut in_code_block = false;
+
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => stats.heading_count += 1,
+            Event::Start(Tag::Link { .. }) => stats.link_count += 1,
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                text.push('\n');
+            }
+            Event::Text(t) if in_code_block && include_code => text.push_str(&t),
+            Event::Text(_) if in_code_block => {}
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::TableCell) => {
+                text.push('\n')
+            }
+            _ => {}
+        }
+    }
+
+    stats.link_count += WIKILINK_REGEX.find_iter(&text).count();
+    stats.word_count = text.split_whitespace().count();
+    stats.character_count = text.lines().map(|line| line.chars().count()).sum();
+    stats.reading_time_minutes = stats.word_count.div_ceil(WORDS_PER_MINUTE);
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+    use crate::db::exports::{self, CreateExportInput, ExportFormat, ExportGenerator, ExportSource};
+    use crate::db::url_attachments::{self, CreateUrlAttachmentInput};
+    use crate::db::{embeddings, folders, tags};
+    use crate::models::{CreateFolderInput, CreateNoteInput, MessageMetadata, MessageRole, TokenUsage};
+
+    const SAMPLE: &str = "# Release notes\n\nShipped the **new** parser. See [the docs](https://example.com) and [[Roadmap]].\n\n## Code\n\n```rust\nfn main() { println!(\"hi\"); }\n```\n\n- Run `cargo test`\n- Visit <https://inkling.dev>\n";
+
+    #[test]
+    fn test_counts_headings_links_and_words() {
+        let stats = compute_note_stats(SAMPLE, false);
+
+        assert_eq!(stats.heading_count, 2);
+        // Markdown link, autolink and wikilink
+        assert_eq!(stats.link_count, 3);
+        // "Release notes" (2) + "Shipped the new parser. See the docs and [[Roadmap]]." (9)
+        // + "Code" (1) + "Run cargo test" (3) + "Visit https://inkling.dev" (2)
+        assert_eq!(stats.word_count, 17);
+        assert_eq!(stats.reading_time_minutes, 1);
+    }
+
+    #[test]
+    fn test_code_blocks_are_optional() {
+        let without = compute_note_stats(SAMPLE, false);
+        let with = compute_note_stats(SAMPLE, true);
+
+        // `fn main() { println!("hi"); }` adds 5 words
+        assert_eq!(with.word_count, without.word_count + 5);
+        assert!(with.character_count > without.character_count);
+        assert_eq!(with.heading_count, without.heading_count);
+        assert_eq!(with.link_count, without.link_count);
+    }
+
+    #[test]
+    fn test_character_count_ignores_markdown_syntax() {
+        let stats = compute_note_stats("## Hi\n\n**Bold** and _it_", false);
+        // "Hi" + "Bold and it"
+        assert_eq!(stats.character_count, 2 + 11);
+        assert_eq!(stats.word_count, 4);
+    }
+
+    #[test]
+    fn test_reading_time_rounds_up() {
+        assert_eq!(compute_note_stats("", false), NoteStats::default());
+        assert_eq!(compute_note_stats(&"word ".repeat(200), false).reading_time_minutes, 1);
+        assert_eq!(compute_note_stats(&"word ".repeat(201), false).reading_time_minutes, 2);
+    }
+
+    #[test]
+    fn test_note_stats_read_headings_and_links_from_html() {
+        // What the editor saves: plain text alongside the HTML
+        let content = "Plan\nShip it, then read the docs.".to_string();
+        let html = "<h1>Plan</h1><p>Ship it, then read the <a href=\"https://example.com\">docs</a>.</p>".to_string();
+
+        let stats = compute_note_stats(&note_markdown(Some(content.clone()), Some(html)), false);
+        assert_eq!(stats.heading_count, 1);
+        assert_eq!(stats.link_count, 1);
+        assert_eq!(stats.word_count, 7);
+
+        // Without HTML the plain text is all there is
+        let stats = compute_note_stats(&note_markdown(Some(content), None), false);
+        assert_eq!((stats.heading_count, stats.link_count), (0, 0));
+    }
+
+    #[test]
+    fn test_vault_stats_aggregates_small_vault() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let folder = folders::create_folder(&conn, CreateFolderInput {
+            name: "Projects".to_string(),
+            parent_id: None,
+        })
+        .unwrap();
+        let note = |title: &str, content: &str| {
+            notes_db::create_note(&conn, CreateNoteInput {
+                title: title.to_string(),
+                content: Some(content.to_string()),
+                content_html: None,
+                folder_id: Some(folder.id.clone()),
+            })
+            .unwrap()
+        };
+        let alpha = note("Alpha", "# Alpha\n\nOne two three");
+        let beta = note("Beta", "Four five\n\n```\nnot counted\n```");
+        let gone = note("Gone", "these words are deleted");
+        notes_db::delete_note(&conn, &gone.id).unwrap();
+
+        let work = tags::find_or_create_tag(&conn, "work", Some("blue")).unwrap();
+        let idea = tags::find_or_create_tag(&conn, "idea", None).unwrap();
+        tags::find_or_create_tag(&conn, "unused", None).unwrap();
+        tags::add_tag_to_note(&conn, &alpha.id, &work.id).unwrap();
+        tags::add_tag_to_note(&conn, &beta.id, &work.id).unwrap();
+        tags::add_tag_to_note(&conn, &alpha.id, &idea.id).unwrap();
+        // Tags on deleted notes don't count towards usage
+        tags::add_tag_to_note(&conn, &gone.id, &idea.id).unwrap();
+
+        embeddings::store_embedding(&conn, &alpha.id, &[0.1, 0.2], "test-model", None, None).unwrap();
+        embeddings::store_embedding(&conn, &gone.id, &[0.1, 0.2], "test-model", None, None).unwrap();
+        url_attachments::create_url_attachment(&conn, CreateUrlAttachmentInput {
+            note_id: beta.id.clone(),
+            url: "https://example.com".to_string(),
+        })
+        .unwrap();
+        exports::create_export(&conn, CreateExportInput {
+            filename: "alpha.pdf".to_string(),
+            title: "Alpha".to_string(),
+            format: ExportFormat::Pdf,
+            source_note_ids: vec![alpha.id.clone()],
+            file_size: None,
+            path: "/path/to/alpha.pdf".to_string(),
+            duration_ms: None,
+            generator: ExportGenerator::Mechanical,
+            model: None,
+            source: ExportSource::Notes,
+            conversation_id: None,
+            options: Default::default(),
+        })
+        .unwrap();
+
+        let conversation = conversations::create_conversation(&conn, Some("Chat"), None).unwrap();
+        let metadata = MessageMetadata {
+            citations: Vec::new(),
+            model: Some("gpt-4o".to_string()),
+            usage: Some(TokenUsage {
+                prompt_tokens: 1_000,
+                completion_tokens: 500,
+                total_tokens: 1_500,
+            }),
+            tool_calls: Vec::new(),
+            thinking_content: None,
+            images: Vec::new(),
+        };
+        conversations::create_message(&conn, &conversation.id, MessageRole::Assistant, "Hi", Some(&metadata)).unwrap();
+
+        let stats = collect_vault_stats(&conn, 10).unwrap();
+        assert_eq!(stats.note_count, 2);
+        assert_eq!(stats.folder_count, 1);
+        assert_eq!(stats.tag_count, 3);
+        // "Alpha" + "One two three" + "Four five"
+        assert_eq!(stats.word_count, 6);
+        assert_eq!(stats.embedded_note_count, 1);
+        assert_eq!(stats.url_attachment_count, 1);
+        assert_eq!(stats.export_count, 1);
+        // 1k prompt tokens at $2.50/M plus 500 completion tokens at $10/M
+        assert_eq!(stats.ai_usage.prompt_tokens, 1_000);
+        assert!((stats.ai_usage.estimated_cost_usd - 0.0075).abs() < 1e-9);
+
+        let usage: Vec<_> = stats.top_tags.iter().map(|t| (t.name.as_str(), t.note_count)).collect();
+        assert_eq!(usage, vec![("work", 2), ("idea", 1)]);
+        assert_eq!(stats.top_tags[0].color.as_deref(), Some("blue"));
+
+        let limited = collect_vault_stats(&conn, 1).unwrap();
+        assert_eq!(limited.top_tags.len(), 1);
+        assert_eq!(limited.top_tags[0].name, "work");
+    }
+}
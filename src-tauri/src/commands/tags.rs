@@ -1,8 +1,14 @@
 //! Tauri commands for tag operations and the tagging agent
 
-use tauri::State;
+use tauri::{Emitter, State};
 
-use crate::ai::{load_ai_config, run_tagging_agent, TaggingResult};
+use crate::ai::{
+    create_client, get_notes_for_tagging, has_taggable_content, load_ai_config, run_tagging_agent,
+    run_tagging_agent_batch_with_client, CancellationToken, TaggingBatchResult, TaggingResult,
+    TAGGING_BATCH_CONCURRENCY,
+};
+use crate::commands::agents::AgentExecutions;
+use crate::db::connection::get_connection;
 use crate::db::{self};
 use crate::models::Tag;
 use crate::AppPool;
@@ -16,7 +22,7 @@ use crate::AppPool;
 pub fn get_all_tags(pool: State<AppPool>) -> Result<Vec<Tag>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_all_tags(&conn).map_err(|e| e.to_string())
 }
 
@@ -25,7 +31,7 @@ pub fn get_all_tags(pool: State<AppPool>) -> Result<Vec<Tag>, String> {
 pub fn search_tags(pool: State<AppPool>, query: String) -> Result<Vec<Tag>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::search_tags(&conn, &query).map_err(|e| e.to_string())
 }
 
@@ -34,7 +40,7 @@ pub fn search_tags(pool: State<AppPool>, query: String) -> Result<Vec<Tag>, Stri
 pub fn get_note_tags(pool: State<AppPool>, note_id: String) -> Result<Vec<Tag>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::get_note_tags(&conn, &note_id).map_err(|e| e.to_string())
 }
 
@@ -44,11 +50,12 @@ pub fn create_tag(
     pool: State<AppPool>,
     name: String,
     color: Option<String>,
+    description: Option<String>,
 ) -> Result<Tag, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
-    db::create_tag(&conn, &name, color.as_deref()).map_err(|e| e.to_string())
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+    db::create_tag(&conn, &name, color.as_deref(), description.as_deref()).map_err(|e| e.to_string())
 }
 
 /// Add a tag to a note (creates the tag if it doesn't exist)
@@ -61,7 +68,7 @@ pub fn add_tag_to_note(
 ) -> Result<Tag, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     
     // Find or create the tag
     let tag = db::find_or_create_tag(&conn, &tag_name, color.as_deref())
@@ -82,7 +89,7 @@ pub fn remove_tag_from_note(
 ) -> Result<bool, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::remove_tag_from_note(&conn, &note_id, &tag_id).map_err(|e| e.to_string())
 }
 
@@ -91,7 +98,7 @@ pub fn remove_tag_from_note(
 pub fn delete_tag(pool: State<AppPool>, tag_id: String) -> Result<bool, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
     db::delete_tag(&conn, &tag_id).map_err(|e| e.to_string())
 }
 
@@ -102,11 +109,41 @@ pub fn update_tag(
     tag_id: String,
     name: Option<String>,
     color: Option<String>,
+    description: Option<String>,
 ) -> Result<Tag, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| e.to_string())?;
-    db::update_tag(&conn, &tag_id, name.as_deref(), color.as_deref()).map_err(|e| e.to_string())
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+    db::update_tag(&conn, &tag_id, name.as_deref(), color.as_deref(), description.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Suggest tags for a note based on the tags of semantically similar notes
+///
+/// A cheaper alternative to the tagging agent: no LLM call is made.
+#[tauri::command]
+pub fn suggest_tags_for_note(
+    pool: State<AppPool>,
+    note_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<db::TagSuggestion>, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+    db::suggest_tags_for_note(&conn, &note_id, limit.unwrap_or(5)).map_err(|e| e.to_string())
+}
+
+/// Merge several tags into one, moving their notes to the target tag
+#[tauri::command]
+pub fn merge_tags(
+    pool: State<AppPool>,
+    source_ids: Vec<String>,
+    target_id: String,
+) -> Result<Tag, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| e.to_string())?;
+    db::merge_tags(&conn, &source_ids, &target_id).map_err(|e| e.to_string())
 }
 
 // ============================================================================
@@ -135,7 +172,7 @@ pub async fn run_tagging_agent_cmd(
     
     // Get the note content
     let (title, content) = {
-        let conn = db_pool.get().map_err(|e| {
+        let conn = get_connection(&db_pool).map_err(|e| {
             log::error!("[TaggingAgent] Failed to get DB connection: {}", e);
             e.to_string()
         })?;
@@ -154,14 +191,14 @@ pub async fn run_tagging_agent_cmd(
     };
     
     // Skip if content is too short
-    if content.len() < 10 && title.len() < 5 {
+    if !has_taggable_content(&title, &content) {
         log::warn!("[TaggingAgent] Note content too short, skipping");
         return Err("Note content is too short to analyze".to_string());
     }
     
     // Get the model and provider to use
     let (model, provider) = {
-        let conn = db_pool.get().map_err(|e| {
+        let conn = get_connection(&db_pool).map_err(|e| {
             log::error!("[TaggingAgent] Failed to get DB connection for config: {}", e);
             e.to_string()
         })?;
@@ -191,6 +228,67 @@ pub async fn run_tagging_agent_cmd(
     Ok(result)
 }
 
+/// Run the tagging agent over every note in a folder
+///
+/// Notes are tagged a few at a time. The frontend should listen for
+/// `tagging-batch-progress-{execution_id}` events, and can stop the batch with
+/// `cancel_agent_execution`.
+#[tauri::command]
+pub async fn run_tagging_agent_batch(
+    app_handle: tauri::AppHandle,
+    pool: State<'_, AppPool>,
+    agent_executions: State<'_, AgentExecutions>,
+    execution_id: String,
+    folder_id: String,
+    recursive: bool,
+) -> Result<TaggingBatchResult, String> {
+    log::info!(
+        "[TaggingAgent] Starting batch {} for folder {} (recursive: {})",
+        execution_id, folder_id, recursive
+    );
+
+    let db_pool = {
+        let pool_guard = pool.0.read().unwrap();
+        pool_guard.clone().ok_or("Database not initialized")?
+    };
+
+    let (notes, model, provider) = {
+        let conn = get_connection(&db_pool).map_err(|e| e.to_string())?;
+        let notes = get_notes_for_tagging(&conn, &folder_id, recursive)?;
+        let config = load_ai_config(&conn)?;
+        let (model, provider) = get_tagging_model_and_provider(&config)?;
+        (notes, model, provider)
+    };
+    let client = create_client(&provider).map_err(|e| e.to_string())?;
+
+    let cancellation_token = CancellationToken::new();
+    {
+        let mut executions = agent_executions.0.write().unwrap();
+        executions.insert(execution_id.clone(), cancellation_token.clone());
+    }
+
+    let event_name = format!("tagging-batch-progress-{}", execution_id);
+    let result = run_tagging_agent_batch_with_client(
+        &db_pool,
+        client.as_ref(),
+        &model,
+        notes,
+        TAGGING_BATCH_CONCURRENCY,
+        &cancellation_token,
+        |progress| {
+            let _ = app_handle.emit(&event_name, progress);
+        },
+    )
+    .await;
+
+    {
+        let mut executions = agent_executions.0.write().unwrap();
+        executions.remove(&execution_id);
+    }
+
+    Ok(result)
+}
+
 /// Get the chat model from AI config
 #[allow(dead_code)]
 fn get_chat_model(config: &crate::ai::AIConfig) -> Result<String, String> {
@@ -8,6 +8,7 @@ use tauri::{AppHandle, State};
 
 use crate::ai::url_indexing_agent::run_url_indexing_agent;
 use crate::ai::url_scraper::scrape_url;
+use crate::db::connection::get_connection;
 use crate::db::url_attachments::{
     self as db, CreateUrlAttachmentInput, UrlAttachment, UrlStatus,
 };
@@ -51,7 +52,7 @@ pub async fn add_url_attachment(
     let attachment = {
         let pool_guard = pool.0.read().unwrap();
         let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-        let conn = pool_ref.get().map_err(|e| e.to_string())?;
+        let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
         db::create_url_attachment(
             &conn,
@@ -91,7 +92,7 @@ pub fn get_url_attachments(
 ) -> Result<Vec<UrlAttachment>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
     db::get_url_attachments_for_note(&conn, &note_id).map_err(|e| e.to_string())
 }
@@ -104,7 +105,7 @@ pub fn get_url_attachment(
 ) -> Result<Option<UrlAttachment>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
     db::get_url_attachment(&conn, &id).map_err(|e| e.to_string())
 }
@@ -116,7 +117,7 @@ pub fn remove_url_attachment(pool: State<AppPool>, id: String) -> Result<bool, S
 
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
     db::delete_url_attachment(&conn, &id).map_err(|e| e.to_string())
 }
@@ -136,7 +137,7 @@ pub async fn refresh_url_attachment(
     let attachment = {
         let pool_guard = pool.0.read().unwrap();
         let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-        let conn = pool_ref.get().map_err(|e| e.to_string())?;
+        let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
         // Get the attachment first
         let attachment = db::get_url_attachment(&conn, &id)
@@ -173,7 +174,7 @@ pub async fn refresh_url_attachment(
     // Return the attachment with pending status
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
     db::get_url_attachment(&conn, &id)
         .map_err(|e| e.to_string())?
@@ -185,7 +186,7 @@ pub async fn refresh_url_attachment(
 pub fn get_pending_url_attachments(pool: State<AppPool>) -> Result<Vec<UrlAttachment>, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool_ref.get().map_err(|e| e.to_string())?;
+    let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
 
     db::get_pending_url_attachments(&conn).map_err(|e| e.to_string())
 }
@@ -247,7 +248,7 @@ pub async fn discover_and_index_urls(
     let (all_notes, existing_urls) = {
         let pool_guard = pool.0.read().unwrap();
         let pool_ref = pool_guard.as_ref().ok_or("Database not initialized")?;
-        let conn = pool_ref.get().map_err(|e| e.to_string())?;
+        let conn = get_connection(pool_ref).map_err(|e| e.to_string())?;
         
         let notes = notes::get_all_notes(&conn).map_err(|e| e.to_string())?;
         
@@ -325,7 +326,7 @@ pub async fn discover_and_index_urls(
             
             // Create the URL attachment
             let attachment_result = {
-                let conn = pool_clone.get().map_err(|e| e.to_string())?;
+                let conn = get_connection(&pool_clone).map_err(|e| e.to_string())?;
                 db::create_url_attachment(
                     &conn,
                     CreateUrlAttachmentInput {
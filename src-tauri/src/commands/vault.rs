@@ -1,7 +1,13 @@
 //! Vault management commands
 
 use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
+use rand::Rng;
+
+use crate::db::connection::get_connection;
+use crate::db::migrations;
 use crate::vault::{self, VaultInfo, VaultStatus};
 
 /// Get the current vault path
@@ -35,6 +41,21 @@ pub fn set_vault_path(path: String) -> Result<(), String> {
     Ok(())
 }
 
+/// Get the custom exports directory (or null if exports go to the vault)
+#[tauri::command]
+pub fn get_exports_dir_override() -> Option<String> {
+    vault::get_exports_dir_override().map(|p| p.to_string_lossy().to_string())
+}
+
+/// Set or clear the custom exports directory
+///
+/// The directory is created if missing and must be writable.
+#[tauri::command]
+pub fn set_exports_dir_override(path: Option<String>) -> Result<(), String> {
+    let path = path.filter(|p| !p.trim().is_empty()).map(PathBuf::from);
+    vault::save_exports_dir_override(path.as_deref()).map_err(|e| e.to_string())
+}
+
 /// Create a new vault at the specified path
 #[tauri::command]
 pub fn create_vault(path: String) -> Result<VaultInfo, String> {
@@ -56,14 +77,26 @@ pub fn has_existing_data() -> bool {
 }
 
 /// Migrate existing data from the legacy location to the new vault
+///
+/// Emits `vault-migration-progress` events as notes are written. An
+/// interrupted migration resumes where it left off when run again. The
+/// copying runs on a blocking thread so the app stays responsive.
 #[tauri::command]
-pub fn migrate_to_vault(vault_path: String) -> Result<(), String> {
+pub async fn migrate_to_vault(app: tauri::AppHandle, vault_path: String) -> Result<(), String> {
+    let vault_path = PathBuf::from(&vault_path);
+    tauri::async_runtime::spawn_blocking(move || run_vault_migration(&app, &vault_path))
+        .await
+        .map_err(|e| format!("Migration task failed: {}", e))?
+}
+
+/// Copy legacy notes, database and search index into `vault_path`
+fn run_vault_migration(app: &tauri::AppHandle, vault_path: &std::path::Path) -> Result<(), String> {
     use crate::db;
-    use crate::vault::markdown;
+    use crate::vault::legacy_migration::{self, MIGRATION_PROGRESS_EVENT};
     use std::fs;
+    use tauri::Emitter;
     
-    let vault_path = PathBuf::from(&vault_path);
-    
+
     // Check for legacy database
     let legacy_db = db::connection::get_legacy_db_path()
         .map_err(|e| format!("Failed to get legacy path: {}", e))?;
@@ -75,62 +108,19 @@ pub fn migrate_to_vault(vault_path: String) -> Result<(), String> {
     // Initialize legacy pool
     let legacy_pool = db::connection::init_legacy_pool()
         .map_err(|e| format!("Failed to open legacy database: {}", e))?;
+    let conn = get_connection(&legacy_pool).map_err(|e| format!("Database error: {}", e))?;
     
-    // Get all notes from legacy database
-    let conn = legacy_pool.get().map_err(|e| format!("Database error: {}", e))?;
-    let notes = db::notes::get_all_notes(&conn)
-        .map_err(|e| format!("Failed to get notes: {}", e))?;
-    let folders = db::folders::get_all_folders(&conn)
-        .map_err(|e| format!("Failed to get folders: {}", e))?;
-    
-    // Create folder name mapping
-    let folder_map: std::collections::HashMap<String, String> = folders
-        .iter()
-        .map(|f| (f.id.clone(), f.name.clone()))
-        .collect();
+    // Export notes as markdown files
+    let progress = legacy_migration::migrate_legacy_notes(&conn, vault_path, |progress| {
+        let _ = app.emit(MIGRATION_PROGRESS_EVENT, progress);
+    })
+    .map_err(|e| format!("Failed to migrate notes: {}", e))?;
+    log::info!(
+        "Migrated {} of {} notes ({} already done, {} failed)",
+        progress.migrated, progress.total, progress.skipped, progress.failed
+    );
     
-    // Ensure vault structure exists
-    let notes_dir = vault_path.join("notes");
     let inkling_dir = vault_path.join(".inkling");
-    fs::create_dir_all(&notes_dir).map_err(|e| format!("Failed to create notes dir: {}", e))?;
-    fs::create_dir_all(&inkling_dir).map_err(|e| format!("Failed to create .inkling dir: {}", e))?;
-    
-    // Create folder directories and export notes
-    for folder in &folders {
-        let folder_path = notes_dir.join(&folder.name);
-        fs::create_dir_all(&folder_path)
-            .map_err(|e| format!("Failed to create folder {}: {}", folder.name, e))?;
-    }
-    
-    // Export notes as markdown files
-    for note in notes {
-        if note.is_deleted {
-            continue;
-        }
-        
-        let folder_name = note.folder_id.as_ref()
-            .and_then(|id| folder_map.get(id))
-            .cloned();
-        
-        let file_path = markdown::get_note_path(
-            &notes_dir,
-            &note.title,
-            folder_name.as_deref(),
-        );
-        
-        if let Err(e) = markdown::write_note_file(
-            &file_path,
-            &note.id,
-            &note.title,
-            note.content.as_deref(),
-            note.folder_id.as_deref(),
-            folder_name.as_deref(),
-            note.created_at,
-            note.updated_at,
-        ) {
-            log::warn!("Failed to export note {}: {}", note.title, e);
-        }
-    }
     
     // Copy the SQLite database to the new location for metadata
     let new_db_path = inkling_dir.join("inkling.db");
@@ -182,7 +172,7 @@ fn copy_dir_recursive(src: &std::path::Path, dst: &std::path::Path) -> Result<()
 pub fn clear_chats(pool: tauri::State<crate::AppPool>) -> Result<u32, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
     
     // Get count of conversations before deletion
     let count: u32 = conn
@@ -209,7 +199,7 @@ pub fn clear_chats(pool: tauri::State<crate::AppPool>) -> Result<u32, String> {
 pub fn clear_notes(pool: tauri::State<crate::AppPool>) -> Result<u32, String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
     
     // Get count of notes before deletion
     let count: u32 = conn
@@ -235,12 +225,70 @@ pub fn clear_notes(pool: tauri::State<crate::AppPool>) -> Result<u32, String> {
     Ok(count)
 }
 
+/// Clear the notes in a folder, and in its subfolders if `recursive`
+///
+/// Notes are soft-deleted, like deleting them one by one: their files are
+/// removed from the vault and they drop out of search, but they can be restored.
+#[tauri::command]
+pub fn clear_notes_in_folder(
+    pool: tauri::State<crate::AppPool>,
+    search_index: tauri::State<crate::AppSearchIndex>,
+    folder_id: String,
+    recursive: bool,
+) -> Result<u32, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+
+    let deleted = {
+        let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+        let folder_ids: Vec<String> = if recursive {
+            crate::db::folders::get_folder_subtree_ids(&conn, &folder_id)
+                .map_err(|e| format!("Failed to get subfolders: {}", e))?
+                .into_iter()
+                .collect()
+        } else {
+            vec![folder_id]
+        };
+        crate::db::notes::delete_notes_in_folders(&conn, &folder_ids)
+            .map_err(|e| format!("Failed to delete notes: {}", e))?
+    };
+
+    let search_guard = search_index.0.read().unwrap();
+    for id in &deleted {
+        // Deleted notes keep their title and folder, so their files can still be found
+        if let Err(e) = vault::sync::delete_note_file(pool, id) {
+            log::warn!("Failed to delete note file: {}", e);
+        }
+        if let Some(ref index) = *search_guard {
+            if let Err(e) = index.delete_document(id) {
+                log::warn!("Failed to delete note from search index: {}", e);
+            }
+        }
+    }
+
+    Ok(deleted.len() as u32)
+}
+
+/// Delete conversations not updated in the last `days` days
+#[tauri::command]
+pub fn clear_conversations_older_than(pool: tauri::State<crate::AppPool>, days: u32) -> Result<u32, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+    let count = crate::db::conversations::delete_conversations_older_than(&conn, cutoff)
+        .map_err(|e| format!("Failed to delete conversations: {}", e))?;
+
+    Ok(count as u32)
+}
+
 /// Clear AI configuration (reset to defaults)
 #[tauri::command]
 pub fn clear_ai_config(pool: tauri::State<crate::AppPool>) -> Result<(), String> {
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
     
     // Delete AI config
     conn.execute("DELETE FROM ai_config", [])
@@ -249,14 +297,57 @@ pub fn clear_ai_config(pool: tauri::State<crate::AppPool>) -> Result<(), String>
     Ok(())
 }
 
+/// How long a factory reset token stays valid
+const FACTORY_RESET_TOKEN_TTL: Duration = Duration::from_secs(60);
+
+/// The outstanding factory reset token and when it was issued
+static FACTORY_RESET_TOKEN: Mutex<Option<(String, Instant)>> = Mutex::new(None);
+
+fn issue_factory_reset_token(now: Instant) -> String {
+    let token: String = rand::thread_rng()
+        .gen::<[u8; 8]>()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    *FACTORY_RESET_TOKEN.lock().unwrap() = Some((token.clone(), now));
+    token
+}
+
+/// Check a factory reset token; any attempt uses up the outstanding token
+fn consume_factory_reset_token(token: Option<&str>, now: Instant) -> Result<(), String> {
+    let issued = FACTORY_RESET_TOKEN.lock().unwrap().take();
+    let token = token.ok_or("Factory reset requires a confirmation token")?;
+    match issued {
+        Some((expected, issued_at)) if expected == token => {
+            if now.duration_since(issued_at) > FACTORY_RESET_TOKEN_TTL {
+                Err("Factory reset token has expired".to_string())
+            } else {
+                Ok(())
+            }
+        }
+        _ => Err("Invalid factory reset token".to_string()),
+    }
+}
+
+/// Get a single-use token to pass to `factory_reset`, valid for one minute
+#[tauri::command]
+pub fn get_factory_reset_token() -> String {
+    issue_factory_reset_token(Instant::now())
+}
+
 /// Factory reset - clear everything and reset vault path
+///
+/// Requires a token from `get_factory_reset_token`, so a stray call can't
+/// wipe the vault.
 #[tauri::command]
-pub fn factory_reset(pool: tauri::State<crate::AppPool>) -> Result<(), String> {
+pub fn factory_reset(pool: tauri::State<crate::AppPool>, token: Option<String>) -> Result<(), String> {
+    consume_factory_reset_token(token.as_deref(), Instant::now())?;
+    
     // Clear everything in the database
     {
         let pool_guard = pool.0.read().unwrap();
         if let Some(pool) = pool_guard.as_ref() {
-            if let Ok(conn) = pool.get() {
+            if let Ok(conn) = get_connection(pool) {
                 // Delete in order respecting foreign keys
                 let _ = conn.execute("DELETE FROM message_contexts", []);
                 let _ = conn.execute("DELETE FROM messages", []);
@@ -314,6 +405,177 @@ pub fn save_attachment(data: Vec<u8>, filename: String) -> Result<String, String
     Ok(format!("../attachments/{}", unique_name))
 }
 
+/// Import a folder of markdown files, such as an Obsidian vault, as notes
+///
+/// Subdirectories become folders and `[[wikilinks]]` between the imported
+/// notes become links. Files that fail to parse are listed in the report.
+#[tauri::command]
+pub fn import_markdown_directory(
+    pool: tauri::State<crate::AppPool>,
+    search_index: tauri::State<crate::AppSearchIndex>,
+    path: String,
+) -> Result<vault::import::ImportReport, String> {
+    use crate::vault::sync as vault_sync;
+    
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    
+    let report = {
+        let mut conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+        let tx = conn.transaction().map_err(|e| format!("Database error: {}", e))?;
+        let report = vault::import::import_markdown_directory(&tx, std::path::Path::new(&path))
+            .map_err(|e| format!("Failed to import {}: {}", path, e))?;
+        tx.commit().map_err(|e| format!("Database error: {}", e))?;
+        report
+    };
+    
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+    let search_guard = search_index.0.read().unwrap();
+    for note_id in &report.note_ids {
+        if let (Some(ref index), Ok(Some(note))) = (&*search_guard, crate::db::notes::get_note(&conn, note_id)) {
+            if let Err(e) = index.update_note(&note.id, &note.title, note.content.as_deref()) {
+                log::warn!("Failed to index imported note {}: {}", note.title, e);
+            }
+        }
+    }
+    drop(conn);
+    
+    // Write the imported notes into the vault
+    for note_id in &report.note_ids {
+        if let Err(e) = vault_sync::sync_note_to_file(pool, note_id) {
+            log::warn!("Failed to sync imported note to filesystem: {}", e);
+        }
+    }
+    
+    log::info!(
+        "Imported {} new and {} updated notes and {} folders from {} ({} errors)",
+        report.notes_imported, report.notes_updated, report.folders_created, path, report.errors.len()
+    );
+    
+    Ok(report)
+}
+
+/// Write a zip backup of the vault's database, search index, attachments and notes
+#[tauri::command]
+pub fn backup_vault(
+    pool: tauri::State<crate::AppPool>,
+    output_path: String,
+) -> Result<vault::backup::BackupManifest, String> {
+    let vault_path = vault::get_current_vault_path().ok_or("Vault not configured")?;
+    
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+    
+    vault::backup::create_backup(&conn, &vault_path, std::path::Path::new(&output_path))
+        .map_err(|e| format!("Failed to back up vault: {}", e))
+}
+
+/// How long a restore waits for in-flight database work to finish
+const RESTORE_QUIESCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Replace the vault's data with a backup made by `backup_vault`
+///
+/// The archive is validated before the database, search index and vault
+/// watcher are closed. The restore only goes ahead once every database
+/// connection has been returned to the pool, then all three are reopened on
+/// the restored data and both search indexes are rebuilt from it.
+#[tauri::command]
+pub fn restore_vault(
+    app: tauri::AppHandle,
+    pool: tauri::State<crate::AppPool>,
+    search_index: tauri::State<crate::AppSearchIndex>,
+    watcher: tauri::State<crate::AppVaultWatcher>,
+    archive_path: String,
+) -> Result<vault::backup::BackupManifest, String> {
+    let vault_path = vault::get_current_vault_path().ok_or("Vault not configured")?;
+    let archive_path = std::path::Path::new(&archive_path);
+    vault::backup::read_manifest(archive_path).map_err(|e| e.to_string())?;
+    
+    // Close everything holding the vault's data open. The watcher goes first:
+    // its sync thread may need the search index lock to finish a batch.
+    let mut watcher = watcher.0.lock().unwrap();
+    *watcher = None;
+    let mut pool = pool.0.write().unwrap();
+    let mut search_index = search_index.0.write().unwrap();
+    let old_pool = pool.take();
+    let old_index = search_index.take();
+    if let Some(ref old_pool) = old_pool {
+        if !crate::db::connection::wait_for_idle(old_pool, RESTORE_QUIESCE_TIMEOUT) {
+            *watcher = crate::start_vault_watcher(&app, old_pool.clone());
+            *pool = Some(old_pool.clone());
+            *search_index = old_index;
+            return Err("The database is busy; try restoring again once current work finishes".to_string());
+        }
+    }
+    drop(old_pool);
+    drop(old_index);
+    
+    let restored = vault::backup::restore_backup(archive_path, &vault_path)
+        .map_err(|e| format!("Failed to restore backup: {}", e));
+    
+    // Reopen even if the restore failed, so the app keeps working on what's there
+    let (new_pool, new_index) = crate::initialize_for_vault()?;
+    if restored.is_ok() {
+        let rebuilt = new_pool
+            .get()
+            .map_err(|e| e.to_string())
+            .and_then(|conn| crate::db::conversations::rebuild_message_search_index(&conn).map_err(|e| e.to_string()));
+        if let Err(e) = rebuilt {
+            log::warn!("Failed to rebuild message search index after restore: {}", e);
+        }
+        let reindexed = new_pool
+            .get()
+            .map_err(|e| e.to_string())
+            .and_then(|conn| crate::reindex_notes(&conn, &new_index).map_err(|e| e.to_string()));
+        if let Err(e) = reindexed {
+            log::warn!("Failed to rebuild note search index after restore: {}", e);
+        }
+    }
+    *watcher = crate::start_vault_watcher(&app, new_pool.clone());
+    *pool = Some(new_pool);
+    *search_index = Some(new_index);
+    
+    restored
+}
+
+/// Report the database's applied schema version and the version this build expects
+#[tauri::command]
+pub fn get_schema_version(pool: tauri::State<crate::AppPool>) -> Result<migrations::SchemaVersion, String> {
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+    
+    migrations::get_schema_version(&conn).map_err(|e| e.to_string())
+}
+
+/// List the migrations that were pending when the database was opened, in the order they ran
+///
+/// The database is migrated as soon as it's opened, so by the time the
+/// frontend can ask, these have all been applied.
+#[tauri::command]
+pub fn list_pending_migrations() -> Vec<String> {
+    migrations::startup_migrations().into_iter().map(String::from).collect()
+}
+
+/// Find attachment files no note or export references, and delete them unless `dry_run`
+#[tauri::command]
+pub fn gc_attachments(
+    pool: tauri::State<crate::AppPool>,
+    dry_run: bool,
+) -> Result<vault::attachments::AttachmentGcResult, String> {
+    let attachments_dir = vault::get_attachments_dir().map_err(|e| e.to_string())?;
+    let notes_dir = vault::get_notes_dir().map_err(|e| e.to_string())?;
+    let exports_dir = vault::get_exports_dir().map_err(|e| e.to_string())?;
+    
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
+    
+    vault::attachments::gc_attachments(&conn, &attachments_dir, &notes_dir, &exports_dir, dry_run)
+        .map_err(|e| format!("Failed to collect attachments: {}", e))
+}
+
 /// Result of syncing vault to filesystem
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -322,6 +584,9 @@ pub struct SyncResult {
     pub folders_synced: usize,
     #[serde(default)]
     pub boards_synced: usize,
+    /// Notes edited on both sides since the last sync, left for the user to resolve
+    #[serde(default)]
+    pub conflicts: Vec<crate::vault::sync::SyncConflict>,
 }
 
 /// Sync all notes and folders from database to filesystem
@@ -333,7 +598,7 @@ pub fn sync_vault_to_disk(pool: tauri::State<crate::AppPool>) -> Result<SyncResu
     
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
-    let conn = pool.get().map_err(|e| format!("Database error: {}", e))?;
+    let conn = get_connection(pool).map_err(|e| format!("Database error: {}", e))?;
     
     let notes_dir = vault::get_notes_dir().map_err(|e| e.to_string())?;
     
@@ -364,19 +629,24 @@ pub fn sync_vault_to_disk(pool: tauri::State<crate::AppPool>) -> Result<SyncResu
     }
     
     // Sync all notes to files
-    let notes_synced = vault_sync::sync_all_to_files(pool)
+    let outcome = vault_sync::sync_all_to_files(pool)
         .map_err(|e| format!("Failed to sync notes: {}", e))?;
+    let notes_synced = outcome.synced;
     
     // Sync all boards to files
     let boards_synced = vault::sync_all_boards_to_files(pool)
         .map_err(|e| format!("Failed to sync boards: {}", e))?;
     
-    log::info!("Synced {} notes, {} folders, and {} boards to disk", notes_synced, folders_synced, boards_synced);
+    log::info!(
+        "Synced {} notes, {} folders, and {} boards to disk ({} conflicts)",
+        notes_synced, folders_synced, boards_synced, outcome.conflicts.len()
+    );
     
     Ok(SyncResult {
         notes_synced,
         folders_synced,
         boards_synced,
+        conflicts: outcome.conflicts,
     })
 }
 
@@ -417,18 +687,73 @@ pub fn sync_disk_to_vault(pool: tauri::State<crate::AppPool>) -> Result<SyncResu
     let pool_guard = pool.0.read().unwrap();
     let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
     
-    let notes_synced = vault_sync::scan_and_sync_from_files(pool)
+    let outcome = vault_sync::scan_and_sync_from_files(pool)
         .map_err(|e| format!("Failed to sync from disk: {}", e))?;
+    let notes_synced = outcome.synced;
     
     // Sync boards from disk
     let boards_synced = vault::scan_and_sync_boards_from_files(pool)
         .map_err(|e| format!("Failed to sync boards from disk: {}", e))?;
     
-    log::info!("Synced {} notes and {} boards from disk to database", notes_synced, boards_synced);
+    log::info!(
+        "Synced {} notes and {} boards from disk to database ({} conflicts)",
+        notes_synced, boards_synced, outcome.conflicts.len()
+    );
     
     Ok(SyncResult {
         notes_synced,
         folders_synced: 0, // Folders are inferred from note frontmatter
         boards_synced,
+        conflicts: outcome.conflicts,
     })
 }
+
+/// Resolve a note edited both on disk and in the app
+///
+/// `resolution` is one of "keep_disk", "keep_db" or "merge".
+#[tauri::command]
+pub fn resolve_conflict(
+    pool: tauri::State<crate::AppPool>,
+    note_id: String,
+    resolution: String,
+) -> Result<crate::models::Note, String> {
+    use crate::vault::sync::{self as vault_sync, ConflictResolution};
+    
+    let resolution = ConflictResolution::from_str(&resolution)
+        .ok_or_else(|| format!("Invalid resolution: {} (expected keep_disk, keep_db or merge)", resolution))?;
+    
+    let pool_guard = pool.0.read().unwrap();
+    let pool = pool_guard.as_ref().ok_or("Database not initialized")?;
+    
+    vault_sync::resolve_sync_conflict(pool, &note_id, resolution)
+        .map_err(|e| format!("Failed to resolve conflict: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_factory_reset_token() {
+        let now = Instant::now();
+
+        // No token, or none issued
+        assert!(consume_factory_reset_token(None, now).is_err());
+        assert!(consume_factory_reset_token(Some("guess"), now).is_err());
+
+        // A valid token works once
+        let token = issue_factory_reset_token(now);
+        assert!(consume_factory_reset_token(Some(&token), now + Duration::from_secs(5)).is_ok());
+        assert!(consume_factory_reset_token(Some(&token), now + Duration::from_secs(6)).is_err());
+
+        // A wrong guess uses up the outstanding token
+        let token = issue_factory_reset_token(now);
+        assert!(consume_factory_reset_token(Some("guess"), now).is_err());
+        assert!(consume_factory_reset_token(Some(&token), now).is_err());
+
+        // Expired tokens are rejected
+        let token = issue_factory_reset_token(now);
+        let err = consume_factory_reset_token(Some(&token), now + FACTORY_RESET_TOKEN_TTL + Duration::from_secs(1)).unwrap_err();
+        assert!(err.contains("expired"));
+    }
+}
@@ -5,9 +5,11 @@ use rusqlite::{params, Connection, OptionalExtension, Row};
 use thiserror::Error;
 use uuid::Uuid;
 
+use crate::db::{links, notes};
 use crate::models::{
-    AddCardInput, Board, BoardCard, BoardCardWithNote, BoardLane, BoardWithDetails,
-    CreateBoardInput, CreateLaneInput, MoveCardInput, UpdateBoardInput, UpdateLaneInput,
+    AddCardInput, Board, BoardCard, BoardCardWithNote, BoardLane, BoardTemplate, BoardWithDetails,
+    CardPlacement, CardSortOrder, CreateBoardInput, CreateLaneInput, CreateNoteInput,
+    MoveCardInput, Note, UpdateBoardInput, UpdateLaneInput,
 };
 
 #[derive(Error, Debug)]
@@ -22,6 +24,19 @@ pub enum BoardDbError {
     CardNotFound(String),
     #[error("Board already exists for folder: {0}")]
     BoardAlreadyExists(String),
+    #[error("Invalid board template: {0}")]
+    InvalidTemplate(String),
+    #[error("Lane \"{lane}\" is at its WIP limit of {limit}")]
+    WipLimitReached { lane: String, limit: u32 },
+    #[error("Note error: {0}")]
+    NoteError(#[from] notes::NoteDbError),
+    #[error("Link error: {0}")]
+    LinkError(#[from] links::LinkDbError),
+}
+
+/// Format a datetime the way it's stored, so stored values sort chronologically
+fn format_datetime(dt: &DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%d %H:%M:%S").to_string()
 }
 
 /// Parse a datetime string from SQLite into a DateTime<Utc>
@@ -46,6 +61,7 @@ fn row_to_board(row: &Row) -> Result<Board, rusqlite::Error> {
         name: row.get(2)?,
         created_at: parse_datetime(&created_at_str),
         updated_at: parse_datetime(&updated_at_str),
+        enforce_wip_limits: row.get(5)?,
     })
 }
 
@@ -57,6 +73,7 @@ fn row_to_lane(row: &Row) -> Result<BoardLane, rusqlite::Error> {
         name: row.get(2)?,
         color: row.get(3)?,
         position: row.get(4)?,
+        wip_limit: row.get(5)?,
     })
 }
 
@@ -68,6 +85,7 @@ fn row_to_card(row: &Row) -> Result<BoardCard, rusqlite::Error> {
         lane_id: row.get(2)?,
         note_id: row.get(3)?,
         position: row.get(4)?,
+        due_date: row.get::<_, Option<String>>(5)?.as_deref().map(parse_datetime),
     })
 }
 
@@ -79,8 +97,9 @@ fn row_to_card_with_note(row: &Row) -> Result<BoardCardWithNote, rusqlite::Error
         lane_id: row.get(2)?,
         note_id: row.get(3)?,
         position: row.get(4)?,
-        note_title: row.get(5)?,
-        note_folder_path: row.get(6)?,
+        due_date: row.get::<_, Option<String>>(5)?.as_deref().map(parse_datetime),
+        note_title: row.get(6)?,
+        note_folder_path: row.get(7)?,
     })
 }
 
@@ -88,40 +107,89 @@ fn row_to_card_with_note(row: &Row) -> Result<BoardCardWithNote, rusqlite::Error
 // Board CRUD
 // ============================================================================
 
+/// Lane colors, assigned in order and repeated for longer custom templates
+const LANE_COLORS: &[&str] = &[
+    "#6b7280", // gray
+    "#3b82f6", // blue
+    "#22c55e", // green
+    "#f59e0b", // amber
+    "#a855f7", // purple
+    "#ef4444", // red
+];
+
+/// Lane names and colors for a template, in order
+fn template_lanes(template: &BoardTemplate) -> Result<Vec<(String, &'static str)>, BoardDbError> {
+    let lanes: Vec<(String, &'static str)> = match template {
+        BoardTemplate::Kanban => vec![
+            ("To Do".to_string(), "#6b7280"),
+            ("In Progress".to_string(), "#3b82f6"),
+            ("Done".to_string(), "#22c55e"),
+        ],
+        BoardTemplate::Sprint => vec![
+            ("Backlog".to_string(), "#6b7280"),
+            ("To Do".to_string(), "#a855f7"),
+            ("In Progress".to_string(), "#3b82f6"),
+            ("In Review".to_string(), "#f59e0b"),
+            ("Done".to_string(), "#22c55e"),
+        ],
+        BoardTemplate::Custom(names) => names
+            .iter()
+            .zip(LANE_COLORS.iter().cycle())
+            .map(|(name, color)| (name.trim().to_string(), *color))
+            .collect(),
+    };
+
+    if lanes.is_empty() {
+        return Err(BoardDbError::InvalidTemplate("a board needs at least one lane".to_string()));
+    }
+    if lanes.iter().any(|(name, _)| name.is_empty()) {
+        return Err(BoardDbError::InvalidTemplate("lane names can't be empty".to_string()));
+    }
+    Ok(lanes)
+}
+
 /// Create a new board with default lanes
 pub fn create_board(conn: &Connection, input: CreateBoardInput) -> Result<Board, BoardDbError> {
+    create_board_from_template(conn, input, &BoardTemplate::Kanban)
+}
+
+/// Create a new board with the lanes of a template
+///
+/// The board and its lanes are created in one transaction, so a failure
+/// part way leaves no half-built board behind.
+pub fn create_board_from_template(
+    conn: &Connection,
+    input: CreateBoardInput,
+    template: &BoardTemplate,
+) -> Result<Board, BoardDbError> {
     // Check if board already exists for this folder
     if get_board_by_folder(conn, &input.folder_id)?.is_some() {
         return Err(BoardDbError::BoardAlreadyExists(input.folder_id));
     }
+    let lanes = template_lanes(template)?;
 
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
-    conn.execute(
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
         "INSERT INTO boards (id, folder_id, name, created_at, updated_at)
          VALUES (?1, ?2, ?3, ?4, ?5)",
         params![id, input.folder_id, input.name, now, now],
     )?;
 
-    // Create default lanes
-    let default_lanes = [
-        ("To Do", "#6b7280"),      // gray
-        ("In Progress", "#3b82f6"), // blue
-        ("Done", "#22c55e"),        // green
-    ];
-
-    for (position, (name, color)) in default_lanes.iter().enumerate() {
+    for (position, (name, color)) in lanes.into_iter().enumerate() {
         create_lane(
-            conn,
+            &tx,
             CreateLaneInput {
                 board_id: id.clone(),
-                name: name.to_string(),
+                name,
                 color: Some(color.to_string()),
             },
             Some(position as i32),
         )?;
     }
+    tx.commit()?;
 
     get_board(conn, &id)?.ok_or(BoardDbError::BoardNotFound(id))
 }
@@ -129,7 +197,7 @@ pub fn create_board(conn: &Connection, input: CreateBoardInput) -> Result<Board,
 /// Get a board by ID
 pub fn get_board(conn: &Connection, id: &str) -> Result<Option<Board>, BoardDbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, folder_id, name, created_at, updated_at FROM boards WHERE id = ?1",
+        "SELECT id, folder_id, name, created_at, updated_at, enforce_wip_limits FROM boards WHERE id = ?1",
     )?;
 
     let board = stmt.query_row([id], row_to_board).optional()?;
@@ -139,7 +207,7 @@ pub fn get_board(conn: &Connection, id: &str) -> Result<Option<Board>, BoardDbEr
 /// Get a board by folder ID
 pub fn get_board_by_folder(conn: &Connection, folder_id: &str) -> Result<Option<Board>, BoardDbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, folder_id, name, created_at, updated_at FROM boards WHERE folder_id = ?1",
+        "SELECT id, folder_id, name, created_at, updated_at, enforce_wip_limits FROM boards WHERE folder_id = ?1",
     )?;
 
     let board = stmt.query_row([folder_id], row_to_board).optional()?;
@@ -149,7 +217,7 @@ pub fn get_board_by_folder(conn: &Connection, folder_id: &str) -> Result<Option<
 /// Get all boards
 pub fn get_all_boards(conn: &Connection) -> Result<Vec<Board>, BoardDbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, folder_id, name, created_at, updated_at FROM boards ORDER BY updated_at DESC",
+        "SELECT id, folder_id, name, created_at, updated_at, enforce_wip_limits FROM boards ORDER BY updated_at DESC",
     )?;
 
     let boards = stmt
@@ -170,10 +238,11 @@ pub fn update_board(
 
     let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
     let name = input.name.unwrap_or(existing.name);
+    let enforce_wip_limits = input.enforce_wip_limits.unwrap_or(existing.enforce_wip_limits);
 
     conn.execute(
-        "UPDATE boards SET name = ?1, updated_at = ?2 WHERE id = ?3",
-        params![name, now, id],
+        "UPDATE boards SET name = ?1, enforce_wip_limits = ?2, updated_at = ?3 WHERE id = ?4",
+        params![name, enforce_wip_limits, now, id],
     )?;
 
     get_board(conn, id)?.ok_or(BoardDbError::BoardNotFound(id.to_string()))
@@ -244,7 +313,7 @@ pub fn create_lane(
 /// Get a lane by ID
 pub fn get_lane(conn: &Connection, id: &str) -> Result<Option<BoardLane>, BoardDbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, board_id, name, color, position FROM board_lanes WHERE id = ?1",
+        "SELECT id, board_id, name, color, position, wip_limit FROM board_lanes WHERE id = ?1",
     )?;
 
     let lane = stmt.query_row([id], row_to_lane).optional()?;
@@ -254,7 +323,7 @@ pub fn get_lane(conn: &Connection, id: &str) -> Result<Option<BoardLane>, BoardD
 /// Get all lanes for a board, ordered by position
 pub fn get_lanes_for_board(conn: &Connection, board_id: &str) -> Result<Vec<BoardLane>, BoardDbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, board_id, name, color, position FROM board_lanes 
+        "SELECT id, board_id, name, color, position, wip_limit FROM board_lanes 
          WHERE board_id = ?1 ORDER BY position",
     )?;
 
@@ -292,6 +361,29 @@ pub fn update_lane(
     get_lane(conn, id)?.ok_or(BoardDbError::LaneNotFound(id.to_string()))
 }
 
+/// Set or clear a lane's WIP limit
+pub fn set_lane_wip_limit(
+    conn: &Connection,
+    id: &str,
+    wip_limit: Option<u32>,
+) -> Result<BoardLane, BoardDbError> {
+    let existing = get_lane(conn, id)?.ok_or_else(|| BoardDbError::LaneNotFound(id.to_string()))?;
+
+    conn.execute(
+        "UPDATE board_lanes SET wip_limit = ?1 WHERE id = ?2",
+        params![wip_limit, id],
+    )?;
+
+    // Update board's updated_at
+    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    conn.execute(
+        "UPDATE boards SET updated_at = ?1 WHERE id = ?2",
+        params![now, existing.board_id],
+    )?;
+
+    get_lane(conn, id)?.ok_or(BoardDbError::LaneNotFound(id.to_string()))
+}
+
 /// Delete a lane (cards in the lane are also deleted)
 pub fn delete_lane(conn: &Connection, id: &str) -> Result<bool, BoardDbError> {
     let lane = get_lane(conn, id)?;
@@ -335,6 +427,14 @@ pub fn reorder_lanes(conn: &Connection, board_id: &str, lane_ids: &[String]) ->
 
 /// Add a card (note) to a lane
 pub fn add_card(conn: &Connection, input: AddCardInput) -> Result<BoardCard, BoardDbError> {
+    check_wip_limit(conn, &input.board_id, &input.lane_id)?;
+    insert_card(conn, input)
+}
+
+/// Add a card (note) to a lane without checking the lane's WIP limit
+///
+/// Used when mirroring a board file, which records cards that are already there.
+pub(crate) fn insert_card(conn: &Connection, input: AddCardInput) -> Result<BoardCard, BoardDbError> {
     let id = Uuid::new_v4().to_string();
 
     // Get the next position in the lane
@@ -347,9 +447,16 @@ pub fn add_card(conn: &Connection, input: AddCardInput) -> Result<BoardCard, Boa
         .unwrap_or(-1);
 
     conn.execute(
-        "INSERT INTO board_cards (id, board_id, lane_id, note_id, position)
-         VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![id, input.board_id, input.lane_id, input.note_id, max_pos + 1],
+        "INSERT INTO board_cards (id, board_id, lane_id, note_id, position, due_date)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            id,
+            input.board_id,
+            input.lane_id,
+            input.note_id,
+            max_pos + 1,
+            input.due_date.as_ref().map(format_datetime),
+        ],
     )?;
 
     // Update board's updated_at
@@ -365,7 +472,7 @@ pub fn add_card(conn: &Connection, input: AddCardInput) -> Result<BoardCard, Boa
 /// Get a card by ID
 pub fn get_card(conn: &Connection, id: &str) -> Result<Option<BoardCard>, BoardDbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, board_id, lane_id, note_id, position FROM board_cards WHERE id = ?1",
+        "SELECT id, board_id, lane_id, note_id, position, due_date FROM board_cards WHERE id = ?1",
     )?;
 
     let card = stmt.query_row([id], row_to_card).optional()?;
@@ -385,7 +492,7 @@ pub fn get_cards_for_board(conn: &Connection, board_id: &str) -> Result<Vec<Boar
             JOIN folder_path fp ON f.parent_id = fp.folder_id
         )
         SELECT 
-            bc.id, bc.board_id, bc.lane_id, bc.note_id, bc.position,
+            bc.id, bc.board_id, bc.lane_id, bc.note_id, bc.position, bc.due_date,
             n.title,
             fp.path
         FROM board_cards bc
@@ -406,7 +513,23 @@ pub fn get_cards_for_board(conn: &Connection, board_id: &str) -> Result<Vec<Boar
 
 /// Get cards in a specific lane
 pub fn get_cards_in_lane(conn: &Connection, lane_id: &str) -> Result<Vec<BoardCardWithNote>, BoardDbError> {
-    let mut stmt = conn.prepare(
+    get_cards_in_lane_sorted(conn, lane_id, CardSortOrder::Manual)
+}
+
+/// Get cards in a specific lane in the given order
+///
+/// Ties (same due date or title) fall back to the manual order.
+pub fn get_cards_in_lane_sorted(
+    conn: &Connection,
+    lane_id: &str,
+    order: CardSortOrder,
+) -> Result<Vec<BoardCardWithNote>, BoardDbError> {
+    let order_by = match order {
+        CardSortOrder::Manual => "bc.position",
+        CardSortOrder::DueDate => "bc.due_date IS NULL, bc.due_date, bc.position",
+        CardSortOrder::Title => "n.title COLLATE NOCASE, bc.position",
+    };
+    let mut stmt = conn.prepare(&format!(
         r#"
         WITH RECURSIVE folder_path(folder_id, path) AS (
             SELECT id, name FROM folders WHERE parent_id IS NULL
@@ -416,16 +539,17 @@ pub fn get_cards_in_lane(conn: &Connection, lane_id: &str) -> Result<Vec<BoardCa
             JOIN folder_path fp ON f.parent_id = fp.folder_id
         )
         SELECT 
-            bc.id, bc.board_id, bc.lane_id, bc.note_id, bc.position,
+            bc.id, bc.board_id, bc.lane_id, bc.note_id, bc.position, bc.due_date,
             n.title,
             fp.path
         FROM board_cards bc
         JOIN notes n ON bc.note_id = n.id
         LEFT JOIN folder_path fp ON n.folder_id = fp.folder_id
         WHERE bc.lane_id = ?1
-        ORDER BY bc.position
+        ORDER BY {}
         "#,
-    )?;
+        order_by
+    ))?;
 
     let cards = stmt
         .query_map([lane_id], row_to_card_with_note)?
@@ -435,6 +559,28 @@ pub fn get_cards_in_lane(conn: &Connection, lane_id: &str) -> Result<Vec<BoardCa
     Ok(cards)
 }
 
+/// Refuse to add a card to a lane at its WIP limit, if the board enforces limits
+fn check_wip_limit(conn: &Connection, board_id: &str, lane_id: &str) -> Result<(), BoardDbError> {
+    let board = get_board(conn, board_id)?.ok_or_else(|| BoardDbError::BoardNotFound(board_id.to_string()))?;
+    if !board.enforce_wip_limits {
+        return Ok(());
+    }
+    let lane = get_lane(conn, lane_id)?.ok_or_else(|| BoardDbError::LaneNotFound(lane_id.to_string()))?;
+    let Some(limit) = lane.wip_limit else {
+        return Ok(());
+    };
+
+    let count: u32 = conn.query_row(
+        "SELECT COUNT(*) FROM board_cards WHERE lane_id = ?1",
+        [lane_id],
+        |row| row.get(0),
+    )?;
+    if count >= limit {
+        return Err(BoardDbError::WipLimitReached { lane: lane.name, limit });
+    }
+    Ok(())
+}
+
 /// Move a card to a different lane and/or position
 pub fn move_card(conn: &Connection, input: MoveCardInput) -> Result<BoardCard, BoardDbError> {
     let card = get_card(conn, &input.card_id)?
@@ -442,6 +588,8 @@ pub fn move_card(conn: &Connection, input: MoveCardInput) -> Result<BoardCard, B
 
     // If moving to a different lane, shift positions in both lanes
     if card.lane_id != input.target_lane_id {
+        check_wip_limit(conn, &card.board_id, &input.target_lane_id)?;
+
         // Shift cards in old lane up
         conn.execute(
             "UPDATE board_cards SET position = position - 1 
@@ -479,6 +627,12 @@ pub fn move_card(conn: &Connection, input: MoveCardInput) -> Result<BoardCard, B
         "UPDATE board_cards SET lane_id = ?1, position = ?2 WHERE id = ?3",
         params![input.target_lane_id, input.target_position, input.card_id],
     )?;
+    if let Some(due_date) = &input.due_date {
+        conn.execute(
+            "UPDATE board_cards SET due_date = ?1 WHERE id = ?2",
+            params![format_datetime(due_date), input.card_id],
+        )?;
+    }
 
     // Update board's updated_at
     let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
@@ -490,6 +644,30 @@ pub fn move_card(conn: &Connection, input: MoveCardInput) -> Result<BoardCard, B
     get_card(conn, &input.card_id)?.ok_or(BoardDbError::CardNotFound(input.card_id))
 }
 
+/// Set or clear a card's due date
+pub fn set_card_due_date(
+    conn: &Connection,
+    card_id: &str,
+    due_date: Option<DateTime<Utc>>,
+) -> Result<BoardCard, BoardDbError> {
+    let card = get_card(conn, card_id)?
+        .ok_or_else(|| BoardDbError::CardNotFound(card_id.to_string()))?;
+
+    conn.execute(
+        "UPDATE board_cards SET due_date = ?1 WHERE id = ?2",
+        params![due_date.as_ref().map(format_datetime), card_id],
+    )?;
+
+    // Update board's updated_at
+    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    conn.execute(
+        "UPDATE boards SET updated_at = ?1 WHERE id = ?2",
+        params![now, card.board_id],
+    )?;
+
+    get_card(conn, card_id)?.ok_or(BoardDbError::CardNotFound(card_id.to_string()))
+}
+
 /// Remove a card from a board
 pub fn remove_card(conn: &Connection, id: &str) -> Result<bool, BoardDbError> {
     let card = get_card(conn, id)?;
@@ -528,7 +706,7 @@ pub fn is_note_on_board(conn: &Connection, note_id: &str, board_id: &str) -> Res
 /// Get all boards a note appears on
 pub fn get_boards_for_note(conn: &Connection, note_id: &str) -> Result<Vec<Board>, BoardDbError> {
     let mut stmt = conn.prepare(
-        "SELECT DISTINCT b.id, b.folder_id, b.name, b.created_at, b.updated_at
+        "SELECT DISTINCT b.id, b.folder_id, b.name, b.created_at, b.updated_at, b.enforce_wip_limits
          FROM boards b
          JOIN board_cards bc ON b.id = bc.board_id
          WHERE bc.note_id = ?1
@@ -543,6 +721,100 @@ pub fn get_boards_for_note(conn: &Connection, note_id: &str) -> Result<Vec<Board
     Ok(boards)
 }
 
+/// Get every card a note appears as, with its board and lane
+pub fn get_card_placements_for_note(conn: &Connection, note_id: &str) -> Result<Vec<CardPlacement>, BoardDbError> {
+    let mut stmt = conn.prepare(
+        "SELECT bc.id, b.id, b.name, l.id, l.name, l.color, bc.position, bc.due_date
+         FROM board_cards bc
+         JOIN boards b ON b.id = bc.board_id
+         JOIN board_lanes l ON l.id = bc.lane_id
+         WHERE bc.note_id = ?1
+         ORDER BY b.updated_at DESC, l.position",
+    )?;
+
+    let placements = stmt
+        .query_map([note_id], |row| {
+            Ok(CardPlacement {
+                card_id: row.get(0)?,
+                board_id: row.get(1)?,
+                board_name: row.get(2)?,
+                lane_id: row.get(3)?,
+                lane_name: row.get(4)?,
+                lane_color: row.get(5)?,
+                position: row.get(6)?,
+                due_date: row.get::<_, Option<String>>(7)?.as_deref().map(parse_datetime),
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    Ok(placements)
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+/// Lane names treated as holding finished work
+const DONE_LANE_NAMES: &[&str] = &["done", "complete", "completed", "closed", "finished", "shipped"];
+
+/// Whether a lane holds finished work, judged by its name
+fn is_done_lane(name: &str) -> bool {
+    DONE_LANE_NAMES.contains(&name.trim().to_lowercase().as_str())
+}
+
+/// Render a board as markdown: a section per lane with a task list of its cards
+///
+/// Cards in a done lane are checked. Each card links back to its note.
+pub fn board_to_markdown(details: &BoardWithDetails) -> String {
+    let mut sections = Vec::new();
+    for lane in &details.lanes {
+        let checkbox = if is_done_lane(&lane.name) { "x" } else { " " };
+        let mut section = format!("## {}\n", lane.name);
+        let mut cards = details.cards.iter().filter(|c| c.lane_id == lane.id).peekable();
+        if cards.peek().is_none() {
+            section.push_str("\n_No cards_\n");
+        }
+        for card in cards {
+            section.push_str(&format!("- [{}] [[{}]]", checkbox, card.note_title));
+            if let Some(due) = card.due_date {
+                section.push_str(&format!(" (due {})", due.format("%Y-%m-%d")));
+            }
+            section.push('\n');
+        }
+        sections.push(section);
+    }
+    sections.join("\n")
+}
+
+/// Create a note holding a snapshot of a board, in the board's folder
+pub fn export_board_to_note(conn: &Connection, board_id: &str) -> Result<Note, BoardDbError> {
+    let details = get_board_with_details(conn, board_id)?
+        .ok_or_else(|| BoardDbError::BoardNotFound(board_id.to_string()))?;
+
+    let note = notes::create_note(
+        conn,
+        CreateNoteInput {
+            title: format!("{} snapshot {}", details.board.name, Utc::now().format("%Y-%m-%d %H:%M")),
+            content: Some(board_to_markdown(&details)),
+            content_html: None,
+            folder_id: Some(details.board.folder_id.clone()),
+        },
+    )?;
+
+    let mut link_inputs: Vec<links::LinkInput> = Vec::new();
+    for card in &details.cards {
+        if !link_inputs.iter().any(|l| l.target_note_id == card.note_id) {
+            link_inputs.push(links::LinkInput {
+                target_note_id: card.note_id.clone(),
+                context: Some(card.note_title.clone()),
+            });
+        }
+    }
+    links::sync_links(conn, &note.id, &link_inputs)?;
+
+    Ok(note)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -659,6 +931,7 @@ mod tests {
                 board_id: board.id.clone(),
                 lane_id: todo_lane.id.clone(),
                 note_id: note_id.clone(),
+                due_date: None,
             },
         )
         .unwrap();
@@ -673,6 +946,7 @@ mod tests {
                 card_id: card.id.clone(),
                 target_lane_id: done_lane.id.clone(),
                 target_position: 0,
+                due_date: None,
             },
         )
         .unwrap();
@@ -706,6 +980,7 @@ mod tests {
                 board_id: board.id.clone(),
                 lane_id: lanes[0].id.clone(),
                 note_id,
+                due_date: None,
             },
         )
         .unwrap();
@@ -744,4 +1019,418 @@ mod tests {
         assert_eq!(reordered[1].name, "In Progress");
         assert_eq!(reordered[2].name, "To Do");
     }
+
+    #[test]
+    fn test_card_due_dates_persist() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let folder_id = setup_test_folder(&conn);
+        let note_id = setup_test_note(&conn, &folder_id);
+        let board = create_board(
+            &conn,
+            CreateBoardInput {
+                folder_id,
+                name: "Board".to_string(),
+            },
+        )
+        .unwrap();
+        let lanes = get_lanes_for_board(&conn, &board.id).unwrap();
+
+        let due = Utc.with_ymd_and_hms(2026, 3, 1, 9, 30, 0).unwrap();
+        let card = add_card(
+            &conn,
+            AddCardInput {
+                board_id: board.id.clone(),
+                lane_id: lanes[0].id.clone(),
+                note_id,
+                due_date: Some(due),
+            },
+        )
+        .unwrap();
+        assert_eq!(card.due_date, Some(due));
+        assert_eq!(get_cards_in_lane(&conn, &lanes[0].id).unwrap()[0].due_date, Some(due));
+
+        // Moving without a due date keeps it; moving with one replaces it
+        let moved = move_card(
+            &conn,
+            MoveCardInput {
+                card_id: card.id.clone(),
+                target_lane_id: lanes[1].id.clone(),
+                target_position: 0,
+                due_date: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(moved.due_date, Some(due));
+        let later = Utc.with_ymd_and_hms(2026, 4, 1, 0, 0, 0).unwrap();
+        let moved = move_card(
+            &conn,
+            MoveCardInput {
+                card_id: card.id.clone(),
+                target_lane_id: lanes[1].id.clone(),
+                target_position: 0,
+                due_date: Some(later),
+            },
+        )
+        .unwrap();
+        assert_eq!(moved.due_date, Some(later));
+
+        assert_eq!(set_card_due_date(&conn, &card.id, None).unwrap().due_date, None);
+    }
+
+    #[test]
+    fn test_get_cards_in_lane_sorted() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let folder_id = setup_test_folder(&conn);
+        let board = create_board(
+            &conn,
+            CreateBoardInput {
+                folder_id: folder_id.clone(),
+                name: "Board".to_string(),
+            },
+        )
+        .unwrap();
+        let lane_id = get_lanes_for_board(&conn, &board.id).unwrap()[0].id.clone();
+
+        // Added in manual order: (title, due day of month)
+        for (title, day) in [("charlie", Some(20)), ("Alpha", None), ("bravo", Some(5)), ("Delta", Some(12))] {
+            let note = notes::create_note(
+                &conn,
+                CreateNoteInput {
+                    title: title.to_string(),
+                    content: None,
+                    content_html: None,
+                    folder_id: Some(folder_id.clone()),
+                },
+            )
+            .unwrap();
+            add_card(
+                &conn,
+                AddCardInput {
+                    board_id: board.id.clone(),
+                    lane_id: lane_id.clone(),
+                    note_id: note.id,
+                    due_date: day.map(|d| Utc.with_ymd_and_hms(2026, 1, d, 12, 0, 0).unwrap()),
+                },
+            )
+            .unwrap();
+        }
+
+        let titles = |order| -> Vec<String> {
+            get_cards_in_lane_sorted(&conn, &lane_id, order)
+                .unwrap()
+                .into_iter()
+                .map(|c| c.note_title)
+                .collect()
+        };
+        assert_eq!(titles(CardSortOrder::Manual), vec!["charlie", "Alpha", "bravo", "Delta"]);
+        assert_eq!(titles(CardSortOrder::DueDate), vec!["bravo", "Delta", "charlie", "Alpha"]);
+        assert_eq!(titles(CardSortOrder::Title), vec!["Alpha", "bravo", "charlie", "Delta"]);
+    }
+
+    #[test]
+    fn test_create_board_from_templates() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let lane_names = |template: BoardTemplate, folder: &str| -> Vec<String> {
+            let folder = folders::create_folder(
+                &conn,
+                CreateFolderInput {
+                    name: folder.to_string(),
+                    parent_id: None,
+                },
+            )
+            .unwrap();
+            let board = create_board_from_template(
+                &conn,
+                CreateBoardInput {
+                    folder_id: folder.id,
+                    name: "Board".to_string(),
+                },
+                &template,
+            )
+            .unwrap();
+            get_lanes_for_board(&conn, &board.id)
+                .unwrap()
+                .into_iter()
+                .map(|lane| lane.name)
+                .collect()
+        };
+
+        assert_eq!(lane_names(BoardTemplate::Kanban, "Kanban"), vec!["To Do", "In Progress", "Done"]);
+        assert_eq!(
+            lane_names(BoardTemplate::Sprint, "Sprint"),
+            vec!["Backlog", "To Do", "In Progress", "In Review", "Done"]
+        );
+        assert_eq!(
+            lane_names(
+                BoardTemplate::Custom(vec!["Ideas".to_string(), " Drafting ".to_string(), "Published".to_string()]),
+                "Custom"
+            ),
+            vec!["Ideas", "Drafting", "Published"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_template_creates_nothing() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let folder_id = setup_test_folder(&conn);
+        for template in [
+            BoardTemplate::Custom(vec![]),
+            BoardTemplate::Custom(vec!["Ideas".to_string(), "  ".to_string()]),
+        ] {
+            let result = create_board_from_template(
+                &conn,
+                CreateBoardInput {
+                    folder_id: folder_id.clone(),
+                    name: "Board".to_string(),
+                },
+                &template,
+            );
+            assert!(matches!(result, Err(BoardDbError::InvalidTemplate(_))));
+        }
+        assert!(get_board_by_folder(&conn, &folder_id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_move_card_respects_wip_limit() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let folder_id = setup_test_folder(&conn);
+        let board = create_board(
+            &conn,
+            CreateBoardInput {
+                folder_id: folder_id.clone(),
+                name: "Board".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(board.enforce_wip_limits);
+        let lanes = get_lanes_for_board(&conn, &board.id).unwrap();
+        let (todo, doing) = (&lanes[0], &lanes[1]);
+        let doing = set_lane_wip_limit(&conn, &doing.id, Some(2)).unwrap();
+        assert_eq!(doing.wip_limit, Some(2));
+
+        let mut cards = Vec::new();
+        for title in ["One", "Two", "Three"] {
+            let note = notes::create_note(
+                &conn,
+                CreateNoteInput {
+                    title: title.to_string(),
+                    content: None,
+                    content_html: None,
+                    folder_id: Some(folder_id.clone()),
+                },
+            )
+            .unwrap();
+            let card = add_card(
+                &conn,
+                AddCardInput {
+                    board_id: board.id.clone(),
+                    lane_id: todo.id.clone(),
+                    note_id: note.id,
+                    due_date: None,
+                },
+            )
+            .unwrap();
+            cards.push(card);
+        }
+        let move_to_doing = |card: &BoardCard| {
+            move_card(
+                &conn,
+                MoveCardInput {
+                    card_id: card.id.clone(),
+                    target_lane_id: doing.id.clone(),
+                    target_position: 0,
+                    due_date: None,
+                },
+            )
+        };
+
+        // Under the limit, moves are allowed
+        move_to_doing(&cards[0]).unwrap();
+        move_to_doing(&cards[1]).unwrap();
+
+        // The lane is full, so the third card is refused and stays put
+        let result = move_to_doing(&cards[2]);
+        assert!(matches!(result, Err(BoardDbError::WipLimitReached { limit: 2, .. })));
+        assert_eq!(get_card(&conn, &cards[2].id).unwrap().unwrap().lane_id, todo.id);
+
+        // Adding a new card straight into the full lane is refused too
+        let note = notes::create_note(
+            &conn,
+            CreateNoteInput {
+                title: "Four".to_string(),
+                content: None,
+                content_html: None,
+                folder_id: Some(folder_id.clone()),
+            },
+        )
+        .unwrap();
+        let result = add_card(
+            &conn,
+            AddCardInput {
+                board_id: board.id.clone(),
+                lane_id: doing.id.clone(),
+                note_id: note.id.clone(),
+                due_date: None,
+            },
+        );
+        assert!(matches!(result, Err(BoardDbError::WipLimitReached { limit: 2, .. })));
+        assert!(!is_note_on_board(&conn, &note.id, &board.id).unwrap());
+
+        // Reordering within the full lane still works
+        move_card(
+            &conn,
+            MoveCardInput {
+                card_id: cards[0].id.clone(),
+                target_lane_id: doing.id.clone(),
+                target_position: 1,
+                due_date: None,
+            },
+        )
+        .unwrap();
+
+        // With enforcement off, the limit no longer blocks moves
+        update_board(
+            &conn,
+            &board.id,
+            UpdateBoardInput {
+                name: None,
+                enforce_wip_limits: Some(false),
+            },
+        )
+        .unwrap();
+        move_to_doing(&cards[2]).unwrap();
+        assert_eq!(get_cards_in_lane(&conn, &doing.id).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_export_board_to_note() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let folder_id = setup_test_folder(&conn);
+        let board = create_board_from_template(
+            &conn,
+            CreateBoardInput {
+                folder_id: folder_id.clone(),
+                name: "Launch".to_string(),
+            },
+            &BoardTemplate::Custom(vec!["Doing".to_string(), "Done".to_string()]),
+        )
+        .unwrap();
+        let lanes = get_lanes_for_board(&conn, &board.id).unwrap();
+
+        let mut note_ids = Vec::new();
+        for (title, lane, due) in [
+            ("Write copy", &lanes[0], Some(Utc.with_ymd_and_hms(2026, 5, 4, 12, 0, 0).unwrap())),
+            ("Pick date", &lanes[1], None),
+            ("Book venue", &lanes[1], None),
+        ] {
+            let note = notes::create_note(
+                &conn,
+                CreateNoteInput {
+                    title: title.to_string(),
+                    content: None,
+                    content_html: None,
+                    folder_id: Some(folder_id.clone()),
+                },
+            )
+            .unwrap();
+            add_card(
+                &conn,
+                AddCardInput {
+                    board_id: board.id.clone(),
+                    lane_id: lane.id.clone(),
+                    note_id: note.id.clone(),
+                    due_date: due,
+                },
+            )
+            .unwrap();
+            note_ids.push(note.id);
+        }
+
+        let note = export_board_to_note(&conn, &board.id).unwrap();
+        assert!(note.title.starts_with("Launch snapshot "));
+        assert_eq!(note.folder_id.as_deref(), Some(folder_id.as_str()));
+        assert_eq!(
+            note.content.as_deref(),
+            Some(
+                "## Doing\n- [ ] [[Write copy]] (due 2026-05-04)\n\n\
+                 ## Done\n- [x] [[Pick date]]\n- [x] [[Book venue]]\n"
+            )
+        );
+
+        // Every card links back to its note
+        let mut linked: Vec<String> = links::get_outgoing_links(&conn, &note.id)
+            .unwrap()
+            .into_iter()
+            .map(|l| l.target_note_id)
+            .collect();
+        linked.sort();
+        note_ids.sort();
+        assert_eq!(linked, note_ids);
+    }
+
+    #[test]
+    fn test_get_card_placements_for_note() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let folder_id = setup_test_folder(&conn);
+        let note_id = setup_test_note(&conn, &folder_id);
+        let other_folder = folders::create_folder(
+            &conn,
+            CreateFolderInput {
+                name: "Other Folder".to_string(),
+                parent_id: None,
+            },
+        )
+        .unwrap();
+
+        let mut expected = Vec::new();
+        for (folder, lane_index) in [(folder_id.clone(), 0), (other_folder.id.clone(), 2)] {
+            let board = create_board(
+                &conn,
+                CreateBoardInput {
+                    folder_id: folder,
+                    name: format!("Board {}", lane_index),
+                },
+            )
+            .unwrap();
+            let lane = get_lanes_for_board(&conn, &board.id).unwrap().remove(lane_index);
+            let card = add_card(
+                &conn,
+                AddCardInput {
+                    board_id: board.id.clone(),
+                    lane_id: lane.id.clone(),
+                    note_id: note_id.clone(),
+                    due_date: None,
+                },
+            )
+            .unwrap();
+            expected.push((card.id, board.id, lane.name));
+        }
+
+        let mut placements: Vec<(String, String, String)> = get_card_placements_for_note(&conn, &note_id)
+            .unwrap()
+            .into_iter()
+            .map(|p| (p.card_id, p.board_id, p.lane_name))
+            .collect();
+        placements.sort();
+        expected.sort();
+        assert_eq!(placements, expected);
+        assert!(expected.iter().any(|(_, _, lane)| lane == "To Do"));
+        assert!(expected.iter().any(|(_, _, lane)| lane == "Done"));
+
+        assert!(get_card_placements_for_note(&conn, "missing").unwrap().is_empty());
+    }
 }
@@ -1,11 +1,13 @@
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use rusqlite::{params, Connection, OptionalExtension, Row};
 use thiserror::Error;
 use uuid::Uuid;
 
+use super::recurrence::RecurrenceRule;
 use crate::models::{
     CalendarEvent, CalendarEventSource, CalendarEventType, CalendarEventWithNote, CreateCalendarEventInput,
-    EventAttendee, EventResponseStatus, UpdateCalendarEventInput,
+    EventAttendee, EventResponseStatus, GoogleSyncState, UpdateCalendarEventInput,
 };
 
 #[derive(Error, Debug)]
@@ -14,16 +16,38 @@ pub enum CalendarEventDbError {
     SqliteError(#[from] rusqlite::Error),
     #[error("Calendar event not found: {0}")]
     NotFound(String),
+    #[error("Unknown timezone: {0}")]
+    InvalidTimezone(String),
+    #[error("Invalid date: {0}")]
+    InvalidDate(String),
 }
 
+/// IANA name of the machine's local timezone, falling back to UTC
+pub fn local_timezone_name() -> String {
+    iana_time_zone::get_timezone()
+        .ok()
+        .filter(|name| name.parse::<Tz>().is_ok())
+        .unwrap_or_else(|| "UTC".to_string())
+}
+
+/// Parse an IANA timezone name
+pub fn parse_timezone(name: &str) -> Result<Tz, CalendarEventDbError> {
+    name.parse::<Tz>()
+        .map_err(|_| CalendarEventDbError::InvalidTimezone(name.to_string()))
+}
+
+/// Format `updated_at` is stored in; sub-second precision lets a push tell
+/// an edit made in the same second apart from the version it sent
+const UPDATED_AT_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.6f";
+
 /// Parse a datetime string from SQLite into a DateTime<Utc>
 fn parse_datetime(s: &str) -> DateTime<Utc> {
     // Try RFC3339 format first
     if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
         return dt.with_timezone(&Utc);
     }
-    // Try SQLite's default format
-    if let Ok(naive) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
+    // Try SQLite's default format, with optional fractional seconds
+    if let Ok(naive) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f") {
         return Utc.from_utc_datetime(&naive);
     }
     // Fallback to now if parsing fails
@@ -71,6 +95,10 @@ fn row_to_event(row: &Row) -> Result<CalendarEvent, rusqlite::Error> {
         response_status,
         attendees,
         meeting_link,
+        timezone: row.get(16)?,
+        google_event_id: row.get(17)?,
+        sync_state: GoogleSyncState::from_str(&row.get::<_, String>(18)?),
+        location: row.get(19)?,
         created_at: parse_datetime(&created_at_str),
         updated_at: parse_datetime(&updated_at_str),
     })
@@ -79,21 +107,127 @@ fn row_to_event(row: &Row) -> Result<CalendarEvent, rusqlite::Error> {
 /// Map a database row to a CalendarEventWithNote struct
 fn row_to_event_with_note(row: &Row) -> Result<CalendarEventWithNote, rusqlite::Error> {
     let event = row_to_event(row)?;
-    let linked_note_title: Option<String> = row.get(16)?;
+    let linked_note_title: Option<String> = row.get(20)?;
 
     Ok(CalendarEventWithNote {
         event,
         linked_note_title,
+        recurrence_master_id: None,
+        occurrence_date: None,
+        local_start_time: None,
+        local_end_time: None,
     })
 }
 
+/// Columns selected for CalendarEventWithNote queries
+const EVENT_WITH_NOTE_COLUMNS: &str = "e.id, e.title, e.description, e.start_time, e.end_time, e.all_day, e.recurrence_rule, e.source, e.external_id, e.linked_note_id, e.event_type, e.response_status, e.attendees, e.meeting_link, e.created_at, e.updated_at, e.timezone, e.google_event_id, e.sync_state, e.location, n.title as note_title";
+
+/// Matches events that should be expanded locally. Google events are fetched with
+/// `singleEvents=true`, so their instances are already stored individually.
+const IS_RECURRING_MASTER: &str = "(NULLIF(e.recurrence_rule, '') IS NOT NULL AND e.source = 'manual')";
+
+/// Expand recurring events into concrete occurrences.
+///
+/// Timed occurrences must start within `[start, end)`; they repeat on the wall-clock time of
+/// the event's own timezone. All-day occurrences are matched by date against `days`
+/// (inclusive), like in `get_events_in_range`. Each occurrence keeps the master event's id
+/// and carries its occurrence date. An event whose rule cannot be parsed is treated as a
+/// single, non-recurring event.
+fn expand_recurring_events(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    days: (NaiveDate, NaiveDate),
+) -> Result<Vec<CalendarEventWithNote>, CalendarEventDbError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM calendar_events e
+         LEFT JOIN notes n ON e.linked_note_id = n.id
+         WHERE {} AND e.start_time < ?1
+         ORDER BY e.start_time ASC",
+        EVENT_WITH_NOTE_COLUMNS, IS_RECURRING_MASTER
+    ))?;
+
+    // All-day events are stored at midnight UTC and compared on whole days
+    let day_start = Utc.from_utc_datetime(&days.0.and_time(NaiveTime::MIN));
+    let day_end = Utc.from_utc_datetime(&(days.1 + Duration::days(1)).and_time(NaiveTime::MIN));
+    let latest_start = day_end.max(end);
+    let masters: Vec<CalendarEventWithNote> = stmt
+        .query_map(params![latest_start.format("%Y-%m-%d %H:%M:%S").to_string()], row_to_event_with_note)?
+        .filter_map(Result::ok)
+        .collect();
+
+    let mut occurrences = Vec::new();
+    for master in masters {
+        let (window_start, window_end) = if master.event.all_day {
+            (day_start, day_end)
+        } else {
+            (start, end)
+        };
+        let tz = if master.event.all_day {
+            Tz::UTC
+        } else {
+            master
+                .event
+                .timezone
+                .as_deref()
+                .and_then(|name| name.parse::<Tz>().ok())
+                .unwrap_or(Tz::UTC)
+        };
+        let dtstart = master.event.start_time;
+        let rule = master.event.recurrence_rule.as_deref().unwrap_or_default();
+
+        let starts = match RecurrenceRule::parse(rule) {
+            Ok(rule) => rule.occurrences_between(&tz, dtstart, window_start, window_end),
+            Err(e) => {
+                log::warn!("[calendar] Ignoring recurrence for event {}: {}", master.event.id, e);
+                if dtstart >= window_start && dtstart < window_end {
+                    vec![dtstart]
+                } else {
+                    Vec::new()
+                }
+            }
+        };
+
+        let duration = master.event.end_time.map(|end_time| end_time - dtstart);
+        for occurrence_start in starts {
+            let mut occurrence = master.clone();
+            occurrence.event.start_time = occurrence_start;
+            occurrence.event.end_time = duration.map(|d| occurrence_start + d);
+            occurrence.recurrence_master_id = Some(master.event.id.clone());
+            occurrence.occurrence_date = Some(occurrence_start.with_timezone(&tz).date_naive());
+            occurrences.push(occurrence);
+        }
+    }
+
+    Ok(occurrences)
+}
+
+/// Fill in `local_start_time`/`local_end_time` for display in `tz`.
+/// All-day events keep their calendar date rather than shifting with the offset.
+pub fn localize_events(events: &mut [CalendarEventWithNote], tz: &Tz) {
+    for item in events.iter_mut() {
+        let all_day = item.event.all_day;
+        item.local_start_time = to_local(item.event.start_time, all_day, tz);
+        item.local_end_time = item.event.end_time.and_then(|end| to_local(end, all_day, tz));
+    }
+}
+
+fn to_local(dt: DateTime<Utc>, all_day: bool, tz: &Tz) -> Option<DateTime<FixedOffset>> {
+    if all_day {
+        tz.from_local_datetime(&dt.naive_utc()).earliest().map(|local| local.fixed_offset())
+    } else {
+        Some(dt.with_timezone(tz).fixed_offset())
+    }
+}
+
 /// Create a new calendar event
 pub fn create_event(
     conn: &Connection,
     input: CreateCalendarEventInput,
 ) -> Result<CalendarEvent, CalendarEventDbError> {
     let id = Uuid::new_v4().to_string();
-    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let now = Utc::now().format(UPDATED_AT_FORMAT).to_string();
     let start_time = input.start_time.format("%Y-%m-%d %H:%M:%S").to_string();
     let end_time = input
         .end_time
@@ -103,10 +237,14 @@ pub fn create_event(
     let event_type_str = input.event_type.unwrap_or(CalendarEventType::Default).as_str();
     let response_status_str = input.response_status.as_ref().map(|s| s.as_str());
     let attendees_json = input.attendees.as_ref().and_then(|a| serde_json::to_string(a).ok());
+    let timezone = match input.timezone {
+        Some(name) => parse_timezone(&name)?.name().to_string(),
+        None => local_timezone_name(),
+    };
 
     conn.execute(
-        "INSERT INTO calendar_events (id, title, description, start_time, end_time, all_day, recurrence_rule, source, linked_note_id, event_type, response_status, attendees, meeting_link, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'manual', ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        "INSERT INTO calendar_events (id, title, description, start_time, end_time, all_day, recurrence_rule, source, linked_note_id, event_type, response_status, attendees, meeting_link, location, timezone, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'manual', ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
         params![
             id,
             input.title,
@@ -120,6 +258,8 @@ pub fn create_event(
             response_status_str,
             attendees_json,
             input.meeting_link,
+            input.location,
+            timezone,
             now,
             now,
         ],
@@ -128,10 +268,26 @@ pub fn create_event(
     get_event(conn, &id)?.ok_or(CalendarEventDbError::NotFound(id))
 }
 
+/// Create several calendar events in one transaction
+///
+/// Either every event is created or, if one fails, none are.
+pub fn create_events(
+    conn: &Connection,
+    inputs: Vec<CreateCalendarEventInput>,
+) -> Result<Vec<CalendarEvent>, CalendarEventDbError> {
+    let tx = conn.unchecked_transaction()?;
+    let events = inputs
+        .into_iter()
+        .map(|input| create_event(&tx, input))
+        .collect::<Result<Vec<_>, _>>()?;
+    tx.commit()?;
+    Ok(events)
+}
+
 /// Get a calendar event by ID
 pub fn get_event(conn: &Connection, id: &str) -> Result<Option<CalendarEvent>, CalendarEventDbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, event_type, response_status, attendees, meeting_link, created_at, updated_at
+        "SELECT id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, event_type, response_status, attendees, meeting_link, created_at, updated_at, timezone, google_event_id, sync_state, location
          FROM calendar_events WHERE id = ?1",
     )?;
 
@@ -145,7 +301,7 @@ pub fn get_event_with_note(
     id: &str,
 ) -> Result<Option<CalendarEventWithNote>, CalendarEventDbError> {
     let mut stmt = conn.prepare(
-        "SELECT e.id, e.title, e.description, e.start_time, e.end_time, e.all_day, e.recurrence_rule, e.source, e.external_id, e.linked_note_id, e.event_type, e.response_status, e.attendees, e.meeting_link, e.created_at, e.updated_at, n.title as note_title
+        "SELECT e.id, e.title, e.description, e.start_time, e.end_time, e.all_day, e.recurrence_rule, e.source, e.external_id, e.linked_note_id, e.event_type, e.response_status, e.attendees, e.meeting_link, e.created_at, e.updated_at, e.timezone, e.google_event_id, e.sync_state, e.location, n.title as note_title
          FROM calendar_events e
          LEFT JOIN notes n ON e.linked_note_id = n.id
          WHERE e.id = ?1",
@@ -158,7 +314,7 @@ pub fn get_event_with_note(
 /// Get all calendar events
 pub fn get_all_events(conn: &Connection) -> Result<Vec<CalendarEvent>, CalendarEventDbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, event_type, response_status, attendees, meeting_link, created_at, updated_at
+        "SELECT id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, event_type, response_status, attendees, meeting_link, created_at, updated_at, timezone, google_event_id, sync_state, location
          FROM calendar_events ORDER BY start_time ASC",
     )?;
 
@@ -170,11 +326,15 @@ pub fn get_all_events(conn: &Connection) -> Result<Vec<CalendarEvent>, CalendarE
     Ok(events)
 }
 
-/// Get calendar events within a date range
+/// Get calendar events within a date range.
+///
+/// If `timezone` is given, all-day events are matched against the range's dates in that
+/// timezone and results carry local start/end times for display.
 pub fn get_events_in_range(
     conn: &Connection,
     start: DateTime<Utc>,
     end: DateTime<Utc>,
+    timezone: Option<&Tz>,
 ) -> Result<Vec<CalendarEventWithNote>, CalendarEventDbError> {
     let start_str = start.format("%Y-%m-%d %H:%M:%S").to_string();
     let end_str = end.format("%Y-%m-%d %H:%M:%S").to_string();
@@ -187,15 +347,18 @@ pub fn get_events_in_range(
     // We use the DATE portion for all-day events comparison:
     // - Extract just the date from start_time for all-day events
     // - Compare against the date range boundaries
-    let start_date = start.format("%Y-%m-%d").to_string();
-    let end_date = end.format("%Y-%m-%d").to_string();
+    let tz = timezone.unwrap_or(&Tz::UTC);
+    let start_day = start.with_timezone(tz).date_naive();
+    let end_day = end.with_timezone(tz).date_naive();
+    let start_date = start_day.format("%Y-%m-%d").to_string();
+    let end_date = end_day.format("%Y-%m-%d").to_string();
 
-    let mut stmt = conn.prepare(
-        "SELECT e.id, e.title, e.description, e.start_time, e.end_time, e.all_day, e.recurrence_rule, e.source, e.external_id, e.linked_note_id, e.event_type, e.response_status, e.attendees, e.meeting_link, e.created_at, e.updated_at, n.title as note_title
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
          FROM calendar_events e
          LEFT JOIN notes n ON e.linked_note_id = n.id
-         WHERE 
-           CASE 
+         WHERE NOT {}
+           AND CASE 
              WHEN e.all_day = 1 THEN 
                -- For all-day events, compare using date only
                date(e.start_time) >= date(?3) AND date(e.start_time) <= date(?4)
@@ -204,42 +367,170 @@ pub fn get_events_in_range(
                e.start_time >= ?1 AND e.start_time < ?2
            END
          ORDER BY e.start_time ASC",
-    )?;
+        EVENT_WITH_NOTE_COLUMNS, IS_RECURRING_MASTER
+    ))?;
 
-    let events = stmt
+    let mut events: Vec<CalendarEventWithNote> = stmt
         .query_map(params![start_str, end_str, start_date, end_date], row_to_event_with_note)?
         .filter_map(Result::ok)
         .collect();
 
+    events.extend(expand_recurring_events(conn, start, end, (start_day, end_day))?);
+    events.sort_by_key(|e| e.event.start_time);
+    if let Some(tz) = timezone {
+        localize_events(&mut events, tz);
+    }
+
     Ok(events)
 }
 
-/// Get calendar events for a specific date (all events that occur on that day)
+/// Get calendar events for a specific date (all events that occur on that day).
+///
+/// The day runs from midnight to midnight in `timezone` (UTC if not given), and results
+/// carry local start/end times when a timezone is given.
 pub fn get_events_for_date(
     conn: &Connection,
     date: &str, // YYYY-MM-DD format
+    timezone: Option<&Tz>,
 ) -> Result<Vec<CalendarEventWithNote>, CalendarEventDbError> {
-    let start_str = format!("{} 00:00:00", date);
-    let end_str = format!("{} 23:59:59", date);
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| CalendarEventDbError::InvalidDate(date.to_string()))?;
+    let tz = timezone.unwrap_or(&Tz::UTC);
+    let local_midnight = |d: NaiveDate| {
+        tz.from_local_datetime(&d.and_time(NaiveTime::MIN))
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&d.and_time(NaiveTime::MIN)))
+    };
+    let day_start = local_midnight(day);
+    let day_end = local_midnight(day + Duration::days(1)) - Duration::seconds(1);
+    let start_str = day_start.format("%Y-%m-%d %H:%M:%S").to_string();
+    let end_str = day_end.format("%Y-%m-%d %H:%M:%S").to_string();
 
-    let mut stmt = conn.prepare(
-        "SELECT e.id, e.title, e.description, e.start_time, e.end_time, e.all_day, e.recurrence_rule, e.source, e.external_id, e.linked_note_id, e.event_type, e.response_status, e.attendees, e.meeting_link, e.created_at, e.updated_at, n.title as note_title
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
          FROM calendar_events e
          LEFT JOIN notes n ON e.linked_note_id = n.id
-         WHERE (e.start_time >= ?1 AND e.start_time <= ?2)
-            OR (e.end_time >= ?1 AND e.end_time <= ?2)
-            OR (e.start_time <= ?1 AND e.end_time >= ?2)
+         WHERE NOT {}
+           AND CASE
+             WHEN e.all_day = 1 THEN
+               -- All-day events cover their start date up to an exclusive end date
+               date(e.start_time) <= ?3 AND (date(e.start_time) = ?3 OR date(e.end_time) > ?3)
+             ELSE
+               (e.start_time >= ?1 AND e.start_time <= ?2)
+               OR (e.end_time >= ?1 AND e.end_time <= ?2)
+               OR (e.start_time <= ?1 AND e.end_time >= ?2)
+           END
          ORDER BY e.start_time ASC",
-    )?;
+        EVENT_WITH_NOTE_COLUMNS, IS_RECURRING_MASTER
+    ))?;
 
-    let events = stmt
-        .query_map(params![start_str, end_str], row_to_event_with_note)?
+    let mut events: Vec<CalendarEventWithNote> = stmt
+        .query_map(params![start_str, end_str, date], row_to_event_with_note)?
         .filter_map(Result::ok)
         .collect();
 
+    events.extend(expand_recurring_events(conn, day_start, day_end, (day, day))?);
+    events.sort_by_key(|e| e.event.start_time);
+    if let Some(tz) = timezone {
+        localize_events(&mut events, tz);
+    }
+
     Ok(events)
 }
 
+/// Interval an event occupies. All-day events cover whole days (at least one);
+/// timed events without an end are treated as a single instant.
+fn event_span(
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+    all_day: bool,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    if all_day {
+        let day_start = Utc.from_utc_datetime(&start.date_naive().and_time(NaiveTime::MIN));
+        let day_end = end
+            .filter(|e| *e > day_start)
+            .unwrap_or(day_start + Duration::days(1));
+        (day_start, day_end)
+    } else {
+        (start, end.filter(|e| *e > start).unwrap_or(start))
+    }
+}
+
+/// Whether two half-open spans overlap. Back-to-back spans do not overlap.
+fn spans_overlap(a: (DateTime<Utc>, DateTime<Utc>), b: (DateTime<Utc>, DateTime<Utc>)) -> bool {
+    let contains = |span: (DateTime<Utc>, DateTime<Utc>), t: DateTime<Utc>| span.0 <= t && t < span.1;
+    let is_instant = |span: (DateTime<Utc>, DateTime<Utc>)| span.0 == span.1;
+
+    (a.0 < b.1 && b.0 < a.1)
+        || (is_instant(a) && (contains(b, a.0) || a.0 == b.0))
+        || (is_instant(b) && contains(a, b.0))
+}
+
+/// Find existing events (including recurring occurrences) that overlap a proposed time.
+///
+/// All-day proposals are checked against other all-day events by day, and timed proposals
+/// against timed events, so holidays and other day markers don't flag every meeting.
+/// `exclude_event_id` skips the event being edited, including its own occurrences.
+/// Results include the linked note title so callers can show what each conflict is for.
+pub fn find_conflicting_events(
+    conn: &Connection,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+    all_day: bool,
+    exclude_event_id: Option<&str>,
+) -> Result<Vec<CalendarEventWithNote>, CalendarEventDbError> {
+    let proposed = event_span(start, end, all_day);
+
+    // Only events still running at the proposed start can overlap it. An event
+    // without a usable end spans at most its (all-day) start day, hence the day of slack.
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM calendar_events e
+         LEFT JOIN notes n ON e.linked_note_id = n.id
+         WHERE NOT {} AND e.all_day = ?1 AND e.start_time <= ?2
+           AND max(COALESCE(e.end_time, e.start_time), e.start_time) >= ?3
+         ORDER BY e.start_time ASC",
+        EVENT_WITH_NOTE_COLUMNS, IS_RECURRING_MASTER
+    ))?;
+    let mut candidates: Vec<CalendarEventWithNote> = stmt
+        .query_map(
+            params![
+                all_day,
+                proposed.1.format("%Y-%m-%d %H:%M:%S").to_string(),
+                (proposed.0 - Duration::days(1)).format("%Y-%m-%d %H:%M:%S").to_string(),
+            ],
+            row_to_event_with_note,
+        )?
+        .filter_map(Result::ok)
+        .collect();
+
+    // Occurrences rarely last longer than a week, so that is how far back we look
+    candidates.extend(expand_recurring_events(
+        conn,
+        proposed.0 - Duration::weeks(1),
+        proposed.1 + Duration::seconds(1),
+        (proposed.0.date_naive() - Duration::weeks(1), proposed.1.date_naive()),
+    )?);
+
+    let mut conflicts: Vec<CalendarEventWithNote> = candidates
+        .into_iter()
+        .filter(|c| c.event.all_day == all_day)
+        .filter(|c| {
+            exclude_event_id.is_none_or(|id| c.event.id != id && c.recurrence_master_id.as_deref() != Some(id))
+        })
+        .filter(|c| {
+            spans_overlap(
+                proposed,
+                event_span(c.event.start_time, c.event.end_time, c.event.all_day),
+            )
+        })
+        .collect();
+    conflicts.sort_by_key(|c| c.event.start_time);
+
+    Ok(conflicts)
+}
+
 /// Update an existing calendar event
 pub fn update_event(
     conn: &Connection,
@@ -248,7 +539,7 @@ pub fn update_event(
 ) -> Result<CalendarEvent, CalendarEventDbError> {
     let existing = get_event(conn, id)?.ok_or_else(|| CalendarEventDbError::NotFound(id.to_string()))?;
 
-    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let now = Utc::now().format(UPDATED_AT_FORMAT).to_string();
     let title = input.title.unwrap_or(existing.title);
     let description = input.description.or(existing.description);
     let start_time = input.start_time.unwrap_or(existing.start_time);
@@ -260,6 +551,11 @@ pub fn update_event(
     let response_status = input.response_status.or(existing.response_status);
     let attendees = input.attendees.or(existing.attendees);
     let meeting_link = input.meeting_link.or(existing.meeting_link);
+    let location = input.location.or(existing.location);
+    let timezone = match input.timezone {
+        Some(name) => Some(parse_timezone(&name)?.name().to_string()),
+        None => existing.timezone,
+    };
 
     let start_time_str = start_time.format("%Y-%m-%d %H:%M:%S").to_string();
     let end_time_str = end_time.map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string());
@@ -268,8 +564,9 @@ pub fn update_event(
     let attendees_json = attendees.as_ref().and_then(|a| serde_json::to_string(a).ok());
 
     conn.execute(
-        "UPDATE calendar_events SET title = ?1, description = ?2, start_time = ?3, end_time = ?4, all_day = ?5, recurrence_rule = ?6, linked_note_id = ?7, event_type = ?8, response_status = ?9, attendees = ?10, meeting_link = ?11, updated_at = ?12
-         WHERE id = ?13",
+        "UPDATE calendar_events SET title = ?1, description = ?2, start_time = ?3, end_time = ?4, all_day = ?5, recurrence_rule = ?6, linked_note_id = ?7, event_type = ?8, response_status = ?9, attendees = ?10, meeting_link = ?11, location = ?12, timezone = ?13, updated_at = ?14,
+             sync_state = CASE WHEN sync_state = 'synced' THEN 'modified' ELSE sync_state END
+         WHERE id = ?15",
         params![
             title,
             description,
@@ -282,6 +579,8 @@ pub fn update_event(
             response_status_str,
             attendees_json,
             meeting_link,
+            location,
+            timezone,
             now,
             id
         ],
@@ -290,16 +589,101 @@ pub fn update_event(
     get_event(conn, id)?.ok_or(CalendarEventDbError::NotFound(id.to_string()))
 }
 
-/// Delete a calendar event
+/// Delete a calendar event. If it was pushed to Google, the Google event is queued for deletion.
 pub fn delete_event(conn: &Connection, id: &str) -> Result<bool, CalendarEventDbError> {
+    conn.execute(
+        "INSERT OR IGNORE INTO google_event_deletions (google_event_id)
+         SELECT google_event_id FROM calendar_events WHERE id = ?1 AND google_event_id IS NOT NULL",
+        [id],
+    )?;
+    delete_event_locally(conn, id)
+}
+
+/// Delete a calendar event without queuing its Google copy for deletion, for
+/// events that were already cancelled in Google
+pub fn delete_event_locally(conn: &Connection, id: &str) -> Result<bool, CalendarEventDbError> {
     let rows_affected = conn.execute("DELETE FROM calendar_events WHERE id = ?1", [id])?;
     Ok(rows_affected > 0)
 }
 
+/// Get local (manual) events, for pushing to Google
+pub fn get_local_events(conn: &Connection) -> Result<Vec<CalendarEvent>, CalendarEventDbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, event_type, response_status, attendees, meeting_link, created_at, updated_at, timezone, google_event_id, sync_state, location
+         FROM calendar_events WHERE source = 'manual' ORDER BY start_time ASC",
+    )?;
+
+    let events = stmt
+        .query_map([], row_to_event)?
+        .filter_map(Result::ok)
+        .collect();
+
+    Ok(events)
+}
+
+/// Get a local event by the id it was given when pushed to Google
+pub fn get_event_by_google_event_id(
+    conn: &Connection,
+    google_event_id: &str,
+) -> Result<Option<CalendarEvent>, CalendarEventDbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, event_type, response_status, attendees, meeting_link, created_at, updated_at, timezone, google_event_id, sync_state, location
+         FROM calendar_events WHERE google_event_id = ?1",
+    )?;
+
+    let event = stmt.query_row([google_event_id], row_to_event).optional()?;
+    Ok(event)
+}
+
+/// Record that a local event was pushed to Google as of `pushed_updated_at`.
+/// If the event was edited while the push was in flight, it stays marked as modified.
+pub fn mark_event_pushed(
+    conn: &Connection,
+    id: &str,
+    google_event_id: &str,
+    pushed_updated_at: DateTime<Utc>,
+) -> Result<(), CalendarEventDbError> {
+    let rows_affected = conn.execute(
+        "UPDATE calendar_events SET google_event_id = ?1,
+             sync_state = CASE WHEN updated_at IN (?3, ?4) THEN 'synced' ELSE 'modified' END
+         WHERE id = ?2",
+        params![
+            google_event_id,
+            id,
+            pushed_updated_at.format(UPDATED_AT_FORMAT).to_string(),
+            // Rows last written before sub-second timestamps
+            pushed_updated_at.format("%Y-%m-%d %H:%M:%S").to_string()
+        ],
+    )?;
+    if rows_affected == 0 {
+        return Err(CalendarEventDbError::NotFound(id.to_string()));
+    }
+    Ok(())
+}
+
+/// Google event ids of pushed events that were deleted locally
+pub fn get_pending_google_deletions(conn: &Connection) -> Result<Vec<String>, CalendarEventDbError> {
+    let mut stmt = conn.prepare("SELECT google_event_id FROM google_event_deletions ORDER BY deleted_at")?;
+    let ids = stmt
+        .query_map([], |row| row.get(0))?
+        .filter_map(Result::ok)
+        .collect();
+    Ok(ids)
+}
+
+/// Forget a pending Google deletion once it has been applied
+pub fn clear_google_deletion(conn: &Connection, google_event_id: &str) -> Result<(), CalendarEventDbError> {
+    conn.execute(
+        "DELETE FROM google_event_deletions WHERE google_event_id = ?1",
+        [google_event_id],
+    )?;
+    Ok(())
+}
+
 /// Get a calendar event by its external_id (for Google sync deduplication)
 pub fn get_event_by_external_id(conn: &Connection, external_id: &str) -> Result<Option<CalendarEvent>, CalendarEventDbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, event_type, response_status, attendees, meeting_link, created_at, updated_at
+        "SELECT id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, event_type, response_status, attendees, meeting_link, created_at, updated_at, timezone, google_event_id, sync_state, location
          FROM calendar_events WHERE external_id = ?1",
     )?;
 
@@ -309,13 +693,21 @@ pub fn get_event_by_external_id(conn: &Connection, external_id: &str) -> Result<
 
 /// Get all Google calendar events (for sync cleanup)
 pub fn get_all_google_events(conn: &Connection) -> Result<Vec<CalendarEvent>, CalendarEventDbError> {
+    get_events_by_source(conn, &CalendarEventSource::Google)
+}
+
+/// Get all events that came from a given source
+pub fn get_events_by_source(
+    conn: &Connection,
+    source: &CalendarEventSource,
+) -> Result<Vec<CalendarEvent>, CalendarEventDbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, event_type, response_status, attendees, meeting_link, created_at, updated_at
-         FROM calendar_events WHERE source = 'google' ORDER BY start_time ASC",
+        "SELECT id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, event_type, response_status, attendees, meeting_link, created_at, updated_at, timezone, google_event_id, sync_state, location
+         FROM calendar_events WHERE source = ?1 ORDER BY start_time ASC",
     )?;
 
     let events = stmt
-        .query_map([], row_to_event)?
+        .query_map([source.as_str()], row_to_event)?
         .filter_map(Result::ok)
         .collect();
 
@@ -350,7 +742,7 @@ pub fn link_note_to_event(
     event_id: &str,
     note_id: &str,
 ) -> Result<CalendarEvent, CalendarEventDbError> {
-    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let now = Utc::now().format(UPDATED_AT_FORMAT).to_string();
 
     conn.execute(
         "UPDATE calendar_events SET linked_note_id = ?1, updated_at = ?2 WHERE id = ?3",
@@ -365,7 +757,7 @@ pub fn unlink_note_from_event(
     conn: &Connection,
     event_id: &str,
 ) -> Result<CalendarEvent, CalendarEventDbError> {
-    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let now = Utc::now().format(UPDATED_AT_FORMAT).to_string();
 
     conn.execute(
         "UPDATE calendar_events SET linked_note_id = NULL, updated_at = ?1 WHERE id = ?2",
@@ -397,6 +789,8 @@ mod tests {
             response_status: None,
             attendees: None,
             meeting_link: None,
+            location: None,
+            timezone: None,
         };
 
         let event = create_event(&conn, input).unwrap();
@@ -425,6 +819,8 @@ mod tests {
             response_status: None,
             attendees: None,
             meeting_link: None,
+            location: None,
+            timezone: None,
         };
 
         let event = create_event(&conn, input).unwrap();
@@ -441,6 +837,8 @@ mod tests {
             response_status: None,
             attendees: None,
             meeting_link: None,
+            location: None,
+            timezone: None,
         };
 
         let updated = update_event(&conn, &event.id, update).unwrap();
@@ -449,6 +847,69 @@ mod tests {
         assert!(updated.all_day);
     }
 
+    #[test]
+    fn test_location_and_attendees_persist() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let attendee = EventAttendee {
+            email: "ada@example.com".to_string(),
+            name: Some("Ada".to_string()),
+            response_status: Some("accepted".to_string()),
+            is_organizer: true,
+        };
+        let event = create_event(
+            &conn,
+            CreateCalendarEventInput {
+                title: "Design review".to_string(),
+                description: None,
+                start_time: Utc::now(),
+                end_time: None,
+                all_day: false,
+                recurrence_rule: None,
+                linked_note_id: None,
+                event_type: None,
+                response_status: None,
+                attendees: Some(vec![attendee]),
+                meeting_link: None,
+                location: Some("Room 4".to_string()),
+                timezone: None,
+            },
+        )
+        .unwrap();
+
+        let fetched = get_event(&conn, &event.id).unwrap().unwrap();
+        assert_eq!(fetched.location.as_deref(), Some("Room 4"));
+        let attendees = fetched.attendees.unwrap();
+        assert_eq!(attendees.len(), 1);
+        assert_eq!(attendees[0].email, "ada@example.com");
+        assert!(attendees[0].is_organizer);
+
+        // Updating other fields keeps the location; a new location replaces it
+        let update = |location: Option<&str>| UpdateCalendarEventInput {
+            title: Some("Design review (moved)".to_string()),
+            description: None,
+            start_time: None,
+            end_time: None,
+            all_day: None,
+            recurrence_rule: None,
+            linked_note_id: None,
+            event_type: None,
+            response_status: None,
+            attendees: None,
+            meeting_link: None,
+            location: location.map(str::to_string),
+            timezone: None,
+        };
+        let updated = update_event(&conn, &event.id, update(None)).unwrap();
+        assert_eq!(updated.location.as_deref(), Some("Room 4"));
+        assert_eq!(updated.attendees.map(|a| a.len()), Some(1));
+
+        update_event(&conn, &event.id, update(Some("https://zoom.us/j/123"))).unwrap();
+        let with_note = get_events_in_range(&conn, Utc::now() - Duration::days(1), Utc::now() + Duration::days(1), None).unwrap();
+        assert_eq!(with_note[0].event.location.as_deref(), Some("https://zoom.us/j/123"));
+    }
+
     #[test]
     fn test_delete_event() {
         let pool = init_test_pool().unwrap();
@@ -466,6 +927,8 @@ mod tests {
             response_status: None,
             attendees: None,
             meeting_link: None,
+            location: None,
+            timezone: None,
         };
 
         let event = create_event(&conn, input).unwrap();
@@ -497,6 +960,8 @@ mod tests {
                     response_status: None,
                     attendees: None,
                     meeting_link: None,
+                    location: None,
+                    timezone: None,
                 },
             )
             .unwrap();
@@ -505,5 +970,245 @@ mod tests {
         let all = get_all_events(&conn).unwrap();
         assert!(all.len() >= 3);
     }
-}
 
+    #[test]
+    fn test_weekly_event_expands_across_month_boundary() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let standup = create_event(
+            &conn,
+            CreateCalendarEventInput {
+                title: "Standup".to_string(),
+                description: None,
+                start_time: Utc.with_ymd_and_hms(2024, 1, 17, 9, 0, 0).unwrap(),
+                end_time: Some(Utc.with_ymd_and_hms(2024, 1, 17, 9, 15, 0).unwrap()),
+                all_day: false,
+                recurrence_rule: Some("RRULE:FREQ=WEEKLY;UNTIL=20240207T235959Z".to_string()),
+                linked_note_id: None,
+                event_type: None,
+                response_status: None,
+                attendees: None,
+                meeting_link: None,
+                location: None,
+                timezone: None,
+            },
+        )
+        .unwrap();
+
+        let events = get_events_in_range(
+            &conn,
+            Utc.with_ymd_and_hms(2024, 1, 22, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 2, 29, 0, 0, 0).unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let dates: Vec<String> = events
+            .iter()
+            .map(|e| e.occurrence_date.unwrap().to_string())
+            .collect();
+        assert_eq!(dates, vec!["2024-01-24", "2024-01-31", "2024-02-07"]);
+        for occurrence in &events {
+            assert_eq!(occurrence.event.id, standup.id);
+            assert_eq!(occurrence.recurrence_master_id.as_deref(), Some(standup.id.as_str()));
+            assert_eq!(
+                occurrence.event.end_time.unwrap() - occurrence.event.start_time,
+                Duration::minutes(15)
+            );
+        }
+
+        let on_day = get_events_for_date(&conn, "2024-02-07", None).unwrap();
+        assert_eq!(on_day.len(), 1);
+        assert_eq!(
+            on_day[0].event.start_time,
+            Utc.with_ymd_and_hms(2024, 2, 7, 9, 0, 0).unwrap()
+        );
+    }
+
+    fn timed_input(title: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> CreateCalendarEventInput {
+        CreateCalendarEventInput {
+            title: title.to_string(),
+            description: None,
+            start_time: start,
+            end_time: Some(end),
+            all_day: false,
+            recurrence_rule: None,
+            linked_note_id: None,
+            event_type: None,
+            response_status: None,
+            attendees: None,
+            meeting_link: None,
+            location: None,
+            timezone: None,
+        }
+    }
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 12, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_create_events_is_all_or_nothing() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let result = create_events(
+            &conn,
+            vec![
+                timed_input("Kickoff", at(9, 0), at(10, 0)),
+                CreateCalendarEventInput {
+                    timezone: Some("Not/AZone".to_string()),
+                    ..timed_input("Broken", at(11, 0), at(12, 0))
+                },
+            ],
+        );
+        assert!(matches!(result, Err(CalendarEventDbError::InvalidTimezone(_))));
+        assert!(get_all_events(&conn).unwrap().is_empty());
+
+        let created = create_events(
+            &conn,
+            vec![
+                timed_input("Kickoff", at(9, 0), at(10, 0)),
+                timed_input("Retro", at(11, 0), at(12, 0)),
+            ],
+        )
+        .unwrap();
+        assert_eq!(created.len(), 2);
+        assert_eq!(get_all_events(&conn).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_find_conflicting_events() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let review = create_event(&conn, timed_input("Design review", at(10, 0), at(11, 0))).unwrap();
+        create_event(
+            &conn,
+            CreateCalendarEventInput {
+                all_day: true,
+                ..timed_input("Offsite", at(0, 0), at(0, 0) + Duration::days(1))
+            },
+        )
+        .unwrap();
+
+        let titles = |start, end| -> Vec<String> {
+            find_conflicting_events(&conn, start, Some(end), false, None)
+                .unwrap()
+                .into_iter()
+                .map(|c| c.event.title)
+                .collect()
+        };
+
+        // Partial overlap on either side
+        assert_eq!(titles(at(9, 30), at(10, 30)), vec!["Design review"]);
+        assert_eq!(titles(at(10, 45), at(11, 15)), vec!["Design review"]);
+        // Full containment, in both directions
+        assert_eq!(titles(at(10, 15), at(10, 45)), vec!["Design review"]);
+        assert_eq!(titles(at(9, 0), at(12, 0)), vec!["Design review"]);
+        // Adjacent meetings don't conflict
+        assert!(titles(at(9, 0), at(10, 0)).is_empty());
+        assert!(titles(at(11, 0), at(12, 0)).is_empty());
+
+        // The event being edited doesn't conflict with itself
+        let excluded = find_conflicting_events(&conn, at(10, 0), Some(at(11, 0)), false, Some(&review.id)).unwrap();
+        assert!(excluded.is_empty());
+
+        // All-day events only conflict with other all-day events on the same day
+        let all_day = find_conflicting_events(&conn, at(0, 0), None, true, None).unwrap();
+        assert_eq!(all_day.len(), 1);
+        assert_eq!(all_day[0].event.title, "Offsite");
+        let next_day = find_conflicting_events(&conn, at(0, 0) + Duration::days(1), None, true, None).unwrap();
+        assert!(next_day.is_empty());
+
+        // A long event that started days earlier still conflicts
+        create_event(&conn, timed_input("Conference", at(8, 0) - Duration::days(3), at(18, 0))).unwrap();
+        assert_eq!(titles(at(12, 0), at(13, 0)), vec!["Conference"]);
+        assert_eq!(titles(at(10, 0), at(10, 30)), vec!["Conference", "Design review"]);
+    }
+
+    #[test]
+    fn test_recurring_occurrence_conflicts() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        create_event(
+            &conn,
+            CreateCalendarEventInput {
+                recurrence_rule: Some("FREQ=DAILY;COUNT=5".to_string()),
+                ..timed_input("Standup", at(9, 0), at(9, 15))
+            },
+        )
+        .unwrap();
+
+        let two_days_later = Duration::days(2);
+        let conflicts = find_conflicting_events(
+            &conn,
+            at(9, 10) + two_days_later,
+            Some(at(9, 30) + two_days_later),
+            false,
+            None,
+        )
+        .unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].occurrence_date, Some((at(9, 0) + two_days_later).date_naive()));
+
+        let after_series = at(9, 0) + Duration::days(6);
+        assert!(find_conflicting_events(&conn, after_series, Some(after_series + Duration::minutes(30)), false, None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_event_created_in_one_timezone_read_in_another() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        // 23:30 on March 11th in New York is 03:30 UTC and 04:30 in Paris on March 12th
+        let new_york = parse_timezone("America/New_York").unwrap();
+        let paris = parse_timezone("Europe/Paris").unwrap();
+        let start = new_york
+            .with_ymd_and_hms(2024, 3, 11, 23, 30, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+        let event = create_event(
+            &conn,
+            CreateCalendarEventInput {
+                timezone: Some("America/New_York".to_string()),
+                ..timed_input("Late call", start, start + Duration::hours(1))
+            },
+        )
+        .unwrap();
+        assert_eq!(event.timezone.as_deref(), Some("America/New_York"));
+        assert_eq!(event.start_time, Utc.with_ymd_and_hms(2024, 3, 12, 3, 30, 0).unwrap());
+
+        let in_new_york = get_events_for_date(&conn, "2024-03-11", Some(&new_york)).unwrap();
+        assert_eq!(in_new_york.len(), 1);
+        assert_eq!(
+            in_new_york[0].local_start_time.unwrap().to_rfc3339(),
+            "2024-03-11T23:30:00-04:00"
+        );
+        assert!(get_events_for_date(&conn, "2024-03-11", Some(&paris)).unwrap().is_empty());
+
+        let in_paris = get_events_for_date(&conn, "2024-03-12", Some(&paris)).unwrap();
+        assert_eq!(in_paris.len(), 1);
+        assert_eq!(
+            in_paris[0].local_start_time.unwrap().to_rfc3339(),
+            "2024-03-12T04:30:00+01:00"
+        );
+        assert_eq!(
+            in_paris[0].local_end_time.unwrap().to_rfc3339(),
+            "2024-03-12T05:30:00+01:00"
+        );
+
+        let invalid = create_event(
+            &conn,
+            CreateCalendarEventInput {
+                timezone: Some("Mars/Olympus_Mons".to_string()),
+                ..timed_input("Nope", start, start)
+            },
+        );
+        assert!(matches!(invalid, Err(CalendarEventDbError::InvalidTimezone(_))));
+    }
+}
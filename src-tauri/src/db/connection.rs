@@ -1,7 +1,7 @@
 //! Database connection pool management
 
 use directories::ProjectDirs;
-use r2d2::Pool;
+use r2d2::{Pool, PooledConnection};
 use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::ffi::sqlite3_auto_extension;
 use rusqlite::OpenFlags;
@@ -9,12 +9,58 @@ use sqlite_vec::sqlite3_vec_init;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Once;
+use std::time::Duration;
 use thiserror::Error;
 
 use super::migrations::{self, MigrationError};
 
 pub type DbPool = Pool<SqliteConnectionManager>;
 
+/// Environment variable overriding the maximum number of pooled connections
+pub const POOL_SIZE_ENV: &str = "INKLING_DB_POOL_SIZE";
+/// Environment variable overriding how long to wait for a free connection, in milliseconds
+pub const POOL_TIMEOUT_ENV: &str = "INKLING_DB_POOL_TIMEOUT_MS";
+
+/// Connection pool sizing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolConfig {
+    /// Maximum number of open connections
+    pub max_size: u32,
+    /// How long `get_connection` waits for a free connection before giving up
+    pub acquire_timeout: Duration,
+    /// How long a statement waits for another connection's lock before
+    /// failing with "database is locked"
+    pub busy_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 10,
+            acquire_timeout: Duration::from_secs(30),
+            busy_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Read overrides from the environment, keeping defaults for unset or invalid values
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let mut config = Self::default();
+        if let Some(size) = lookup(POOL_SIZE_ENV).and_then(|v| v.trim().parse::<u32>().ok()).filter(|&n| n > 0) {
+            config.max_size = size;
+        }
+        if let Some(ms) = lookup(POOL_TIMEOUT_ENV).and_then(|v| v.trim().parse::<u64>().ok()).filter(|&n| n > 0) {
+            config.acquire_timeout = Duration::from_millis(ms);
+        }
+        config
+    }
+}
+
 static SQLITE_VEC_INIT: Once = Once::new();
 
 /// Initialize sqlite-vec extension globally
@@ -36,6 +82,8 @@ pub enum DbError {
     VaultNotConfigured,
     #[error("Failed to create database directory: {0}")]
     CreateDirError(#[from] std::io::Error),
+    #[error("All {max_size} database connections are busy (waited {timeout_ms} ms); try again shortly")]
+    PoolExhausted { max_size: u32, timeout_ms: u128 },
     #[error("Database connection error: {0}")]
     ConnectionError(#[from] r2d2::Error),
     #[error("SQLite error: {0}")]
@@ -92,8 +140,50 @@ pub fn get_search_index_path() -> Result<PathBuf, DbError> {
     get_legacy_search_index_path()
 }
 
+/// Get a connection from the pool
+///
+/// Unlike `pool.get()`, running out of connections is reported as
+/// `DbError::PoolExhausted` rather than a generic connection error.
+pub fn get_connection(pool: &DbPool) -> Result<PooledConnection<SqliteConnectionManager>, DbError> {
+    pool.get().map_err(|e| {
+        if pool.state().connections >= pool.max_size() && pool.state().idle_connections == 0 {
+            DbError::PoolExhausted {
+                max_size: pool.max_size(),
+                timeout_ms: pool.connection_timeout().as_millis(),
+            }
+        } else {
+            DbError::ConnectionError(e)
+        }
+    })
+}
+
+/// Wait for every connection checked out of `pool` to be returned
+///
+/// Used before the database file is replaced, so no in-flight query is still
+/// writing to it. Returns false if connections are still busy after `timeout`.
+pub fn wait_for_idle(pool: &DbPool, timeout: Duration) -> bool {
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        let state = pool.state();
+        if state.idle_connections == state.connections {
+            return true;
+        }
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
 /// Initialize the database connection pool at a specific path
+///
+/// Pool sizing comes from the environment (see [`PoolConfig::from_env`]).
 pub fn init_pool_at_path(db_path: &PathBuf) -> Result<DbPool, DbError> {
+    init_pool_at_path_with_config(db_path, PoolConfig::from_env())
+}
+
+/// Initialize the database connection pool at a specific path with the given sizing
+pub fn init_pool_at_path_with_config(db_path: &PathBuf, config: PoolConfig) -> Result<DbPool, DbError> {
     // Initialize sqlite-vec extension before creating any connections
     init_sqlite_vec();
 
@@ -102,27 +192,37 @@ pub fn init_pool_at_path(db_path: &PathBuf) -> Result<DbPool, DbError> {
         fs::create_dir_all(parent)?;
     }
 
-    // Configure SQLite connection
-    let manager = SqliteConnectionManager::file(db_path).with_flags(
-        OpenFlags::SQLITE_OPEN_READ_WRITE
-            | OpenFlags::SQLITE_OPEN_CREATE
-            | OpenFlags::SQLITE_OPEN_FULL_MUTEX,
-    );
+    // Configure SQLite connection. Pragmas are per connection, so they're set
+    // as each pooled connection is opened: WAL lets readers run alongside a
+    // writer, and the busy timeout makes a second writer wait for the lock
+    // instead of failing straight away.
+    let busy_timeout_ms = config.busy_timeout.as_millis();
+    let manager = SqliteConnectionManager::file(db_path)
+        .with_flags(
+            OpenFlags::SQLITE_OPEN_READ_WRITE
+                | OpenFlags::SQLITE_OPEN_CREATE
+                | OpenFlags::SQLITE_OPEN_FULL_MUTEX,
+        )
+        .with_init(move |conn| {
+            conn.execute_batch(&format!(
+                "PRAGMA foreign_keys = ON;
+                 PRAGMA journal_mode = WAL;
+                 PRAGMA busy_timeout = {};",
+                busy_timeout_ms
+            ))
+        });
 
-    let pool = Pool::builder().max_size(10).build(manager)?;
+    let pool = Pool::builder()
+        .max_size(config.max_size)
+        .connection_timeout(config.acquire_timeout)
+        .build(manager)?;
 
-    // Configure the database and run migrations
+    // Run migrations, noting which ones this open applies
     {
         let conn = pool.get()?;
-
-        // Enable foreign keys
-        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
-
-        // Enable WAL mode for better concurrent read performance
-        conn.execute_batch("PRAGMA journal_mode = WAL;")?;
-
-        // Run migrations
+        let pending = migrations::list_pending_migrations(&conn)?;
         migrations::run_migrations(&conn)?;
+        migrations::record_startup_migrations(pending);
     }
 
     Ok(pool)
@@ -172,3 +272,114 @@ pub fn init_test_pool() -> Result<DbPool, DbError> {
 
     Ok(pool)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_config_from_lookup() {
+        let config = PoolConfig::from_lookup(|key| match key {
+            POOL_SIZE_ENV => Some("4".to_string()),
+            POOL_TIMEOUT_ENV => Some("250".to_string()),
+            _ => None,
+        });
+        assert_eq!(config.max_size, 4);
+        assert_eq!(config.acquire_timeout, Duration::from_millis(250));
+
+        // Unset, zero or garbage values keep the defaults
+        assert_eq!(PoolConfig::from_lookup(|_| None), PoolConfig::default());
+        assert_eq!(PoolConfig::from_lookup(|_| Some("0".to_string())), PoolConfig::default());
+        assert_eq!(PoolConfig::from_lookup(|_| Some("lots".to_string())), PoolConfig::default());
+    }
+
+    #[test]
+    fn test_exhausted_pool_reports_pool_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = PoolConfig {
+            max_size: 2,
+            acquire_timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let pool = init_pool_at_path_with_config(&dir.path().join("inkling.db"), config).unwrap();
+
+        let first = get_connection(&pool).unwrap();
+        let _second = get_connection(&pool).unwrap();
+
+        let err = get_connection(&pool).expect_err("pool should be exhausted");
+        assert!(matches!(err, DbError::PoolExhausted { max_size: 2, timeout_ms: 50 }), "{:?}", err);
+        assert!(err.to_string().contains("All 2 database connections are busy"));
+
+        // A connection is available again once one is returned
+        drop(first);
+        assert!(get_connection(&pool).is_ok());
+    }
+
+    #[test]
+    fn test_wait_for_idle() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = init_pool_at_path(&dir.path().join("inkling.db")).unwrap();
+
+        let conn = pool.get().unwrap();
+        assert!(!wait_for_idle(&pool, Duration::from_millis(50)));
+        drop(conn);
+        assert!(wait_for_idle(&pool, Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn test_pooled_connections_use_wal_and_busy_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = init_pool_at_path_with_config(&dir.path().join("inkling.db"), PoolConfig::default()).unwrap();
+
+        // Every connection gets the pragmas, not just the one that ran migrations
+        let conns: Vec<_> = (0..3).map(|_| pool.get().unwrap()).collect();
+        for conn in &conns {
+            let mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+            let timeout: i64 = conn.query_row("PRAGMA busy_timeout", [], |row| row.get(0)).unwrap();
+            let foreign_keys: bool = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0)).unwrap();
+            assert_eq!(mode, "wal");
+            assert_eq!(timeout, 5000);
+            assert!(foreign_keys);
+        }
+
+        // Migrations applied under WAL
+        let applied: i64 = conns[0]
+            .query_row("SELECT COUNT(*) FROM _migrations", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(applied as usize, migrations::schema_version());
+    }
+
+    #[test]
+    fn test_concurrent_read_and_write_do_not_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = PoolConfig {
+            busy_timeout: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let pool = init_pool_at_path_with_config(&dir.path().join("inkling.db"), config).unwrap();
+
+        let writer = pool.get().unwrap();
+        writer.execute_batch("BEGIN IMMEDIATE").unwrap();
+        writer
+            .execute("INSERT INTO settings (key, value) VALUES ('a', '1')", [])
+            .unwrap();
+
+        // A reader isn't blocked by the open write transaction and sees the last commit
+        let reader = pool.get().unwrap();
+        let count: i64 = reader.query_row("SELECT COUNT(*) FROM settings", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+
+        // A second writer waits for the lock instead of failing immediately
+        let second_pool = pool.clone();
+        let second_writer = std::thread::spawn(move || {
+            let conn = second_pool.get().unwrap();
+            conn.execute("INSERT INTO settings (key, value) VALUES ('b', '2')", [])
+        });
+        std::thread::sleep(Duration::from_millis(200));
+        writer.execute_batch("COMMIT").unwrap();
+
+        assert_eq!(second_writer.join().unwrap().unwrap(), 1);
+        let count: i64 = reader.query_row("SELECT COUNT(*) FROM settings", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 2);
+    }
+}
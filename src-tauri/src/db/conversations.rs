@@ -8,7 +8,8 @@ use thiserror::Error;
 use uuid::Uuid;
 
 use crate::models::{
-    Conversation, Message, MessageContext, MessageMetadata, MessageRole,
+    Conversation, ConversationSearchResult, Message, MessageContext, MessageMetadata, MessageRole,
+    ReasoningSettings, TokenUsage,
 };
 
 #[derive(Error, Debug)]
@@ -27,6 +28,18 @@ pub enum ConversationDbError {
 // Conversation Operations
 // ============================================================================
 
+/// Map a row of (id, title, system_prompt, created_at, updated_at, parent_message_id)
+fn row_to_conversation(row: &rusqlite::Row) -> Result<Conversation, rusqlite::Error> {
+    Ok(Conversation {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        system_prompt: row.get(2)?,
+        created_at: parse_datetime(row.get::<_, String>(3)?),
+        updated_at: parse_datetime(row.get::<_, String>(4)?),
+        parent_message_id: row.get(5)?,
+    })
+}
+
 /// Create a new conversation
 pub fn create_conversation(
     conn: &Connection,
@@ -48,6 +61,7 @@ pub fn create_conversation(
         system_prompt: system_prompt.map(String::from),
         created_at: now,
         updated_at: now,
+        parent_message_id: None,
     })
 }
 
@@ -58,18 +72,10 @@ pub fn get_conversation(
 ) -> Result<Option<Conversation>, ConversationDbError> {
     let result = conn
         .query_row(
-            "SELECT id, title, system_prompt, created_at, updated_at
+            "SELECT id, title, system_prompt, created_at, updated_at, parent_message_id
              FROM conversations WHERE id = ?1",
             [id],
-            |row| {
-                Ok(Conversation {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    system_prompt: row.get(2)?,
-                    created_at: parse_datetime(row.get::<_, String>(3)?),
-                    updated_at: parse_datetime(row.get::<_, String>(4)?),
-                })
-            },
+            row_to_conversation,
         )
         .optional()?;
 
@@ -79,21 +85,13 @@ pub fn get_conversation(
 /// List all conversations, ordered by updated_at descending
 pub fn list_conversations(conn: &Connection) -> Result<Vec<Conversation>, ConversationDbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, title, system_prompt, created_at, updated_at
+        "SELECT id, title, system_prompt, created_at, updated_at, parent_message_id
          FROM conversations
          ORDER BY updated_at DESC",
     )?;
 
     let conversations = stmt
-        .query_map([], |row| {
-            Ok(Conversation {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                system_prompt: row.get(2)?,
-                created_at: parse_datetime(row.get::<_, String>(3)?),
-                updated_at: parse_datetime(row.get::<_, String>(4)?),
-            })
-        })?
+        .query_map([], row_to_conversation)?
         .filter_map(Result::ok)
         .collect();
 
@@ -108,7 +106,7 @@ pub fn list_conversation_previews(
     
     let mut stmt = conn.prepare(
         "SELECT 
-            c.id, c.title, c.system_prompt, c.created_at, c.updated_at,
+            c.id, c.title, c.system_prompt, c.created_at, c.updated_at, c.parent_message_id,
             (SELECT COUNT(*) FROM messages WHERE conversation_id = c.id) as message_count,
             (SELECT content FROM messages WHERE conversation_id = c.id AND role = 'user' ORDER BY created_at ASC LIMIT 1) as first_message
          FROM conversations c
@@ -117,15 +115,9 @@ pub fn list_conversation_previews(
 
     let previews = stmt
         .query_map([], |row| {
-            let conversation = Conversation {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                system_prompt: row.get(2)?,
-                created_at: parse_datetime(row.get::<_, String>(3)?),
-                updated_at: parse_datetime(row.get::<_, String>(4)?),
-            };
-            let message_count: i64 = row.get(5)?;
-            let first_message: Option<String> = row.get(6)?;
+            let conversation = row_to_conversation(row)?;
+            let message_count: i64 = row.get(6)?;
+            let first_message: Option<String> = row.get(7)?;
             
             // Truncate first message for preview
             let first_message_preview = first_message.map(|m| {
@@ -175,6 +167,7 @@ pub fn update_conversation(
         system_prompt: new_system_prompt,
         created_at: existing.created_at,
         updated_at: now,
+        parent_message_id: existing.parent_message_id,
     })
 }
 
@@ -184,6 +177,155 @@ pub fn delete_conversation(conn: &Connection, id: &str) -> Result<bool, Conversa
     Ok(rows_affected > 0)
 }
 
+/// Delete conversations last updated before `cutoff`, returning how many
+///
+/// Messages and their contexts go with them through the cascading foreign keys.
+pub fn delete_conversations_older_than(
+    conn: &Connection,
+    cutoff: DateTime<Utc>,
+) -> Result<usize, ConversationDbError> {
+    let rows_affected = conn.execute(
+        "DELETE FROM conversations WHERE updated_at < ?1",
+        [cutoff.to_rfc3339()],
+    )?;
+    Ok(rows_affected)
+}
+
+/// Fork a conversation at a message
+///
+/// Creates a new conversation holding copies of every message up to and
+/// including `from_message_id`, so the original and the branch can diverge
+/// independently afterwards. The branch records the message it was forked from.
+pub fn branch_conversation(
+    conn: &Connection,
+    from_message_id: &str,
+) -> Result<Conversation, ConversationDbError> {
+    let from_message = get_message(conn, from_message_id)?
+        .ok_or_else(|| ConversationDbError::NotFound(from_message_id.to_string()))?;
+    let source = get_conversation(conn, &from_message.conversation_id)?
+        .ok_or_else(|| ConversationDbError::NotFound(from_message.conversation_id.clone()))?;
+    let messages = get_conversation_messages(conn, &source.id)?;
+    let branch_len = messages
+        .iter()
+        .position(|m| m.id == from_message_id)
+        .map(|i| i + 1)
+        .unwrap_or(messages.len());
+
+    let tx = conn.unchecked_transaction()?;
+
+    let title = source.title.as_ref().map(|t| format!("{} (branch)", t));
+    let mut branch = create_conversation(&tx, title.as_deref(), source.system_prompt.as_deref())?;
+    tx.execute(
+        "UPDATE conversations SET parent_message_id = ?1 WHERE id = ?2",
+        params![from_message_id, branch.id],
+    )?;
+    branch.parent_message_id = Some(from_message_id.to_string());
+
+    for message in &messages[..branch_len] {
+        let new_id = Uuid::new_v4().to_string();
+        let metadata_json = message.metadata.as_ref().map(serde_json::to_string).transpose()?;
+        // Keep the original timestamps so the copied history stays in order
+        tx.execute(
+            "INSERT INTO messages (id, conversation_id, role, content, metadata, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                new_id,
+                branch.id,
+                message.role.as_str(),
+                message.content,
+                metadata_json,
+                message.created_at.to_rfc3339()
+            ],
+        )?;
+
+        let mut stmt = tx.prepare(
+            "SELECT note_id, content_snippet, is_full_note FROM message_context WHERE message_id = ?1",
+        )?;
+        let contexts = stmt
+            .query_map([&message.id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, bool>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (note_id, snippet, is_full_note) in contexts {
+            add_message_context(&tx, &new_id, &note_id, snippet.as_deref(), is_full_note)?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(branch)
+}
+
+/// Get the cached summary of a conversation's oldest messages
+///
+/// Returns the summary and the number of leading messages it covers.
+pub fn get_history_summary(
+    conn: &Connection,
+    conversation_id: &str,
+) -> Result<Option<(String, usize)>, ConversationDbError> {
+    let summary: Option<(Option<String>, i64)> = conn
+        .query_row(
+            "SELECT history_summary, history_summary_count FROM conversations WHERE id = ?1",
+            [conversation_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    Ok(summary.and_then(|(summary, count)| summary.map(|s| (s, count as usize))))
+}
+
+/// Cache a summary covering the first `message_count` messages of a conversation
+pub fn set_history_summary(
+    conn: &Connection,
+    conversation_id: &str,
+    summary: &str,
+    message_count: usize,
+) -> Result<(), ConversationDbError> {
+    conn.execute(
+        "UPDATE conversations SET history_summary = ?1, history_summary_count = ?2 WHERE id = ?3",
+        params![summary, message_count as i64, conversation_id],
+    )?;
+    Ok(())
+}
+
+/// Get a conversation's reasoning settings (all unset if it has none)
+pub fn get_reasoning_settings(
+    conn: &Connection,
+    conversation_id: &str,
+) -> Result<ReasoningSettings, ConversationDbError> {
+    conn.query_row(
+        "SELECT reasoning_effort, thinking_budget FROM conversations WHERE id = ?1",
+        [conversation_id],
+        |row| {
+            Ok(ReasoningSettings {
+                reasoning_effort: row.get(0)?,
+                thinking_budget: row.get(1)?,
+            })
+        },
+    )
+    .optional()?
+    .ok_or_else(|| ConversationDbError::NotFound(conversation_id.to_string()))
+}
+
+/// Save a conversation's reasoning settings
+pub fn set_reasoning_settings(
+    conn: &Connection,
+    conversation_id: &str,
+    settings: &ReasoningSettings,
+) -> Result<(), ConversationDbError> {
+    let updated = conn.execute(
+        "UPDATE conversations SET reasoning_effort = ?1, thinking_budget = ?2 WHERE id = ?3",
+        params![settings.reasoning_effort, settings.thinking_budget, conversation_id],
+    )?;
+    if updated == 0 {
+        return Err(ConversationDbError::NotFound(conversation_id.to_string()));
+    }
+    Ok(())
+}
+
 /// Touch conversation updated_at timestamp
 pub fn touch_conversation(conn: &Connection, id: &str) -> Result<(), ConversationDbError> {
     let now = Utc::now();
@@ -272,6 +414,38 @@ pub fn get_message(conn: &Connection, id: &str) -> Result<Option<Message>, Conve
     }
 }
 
+/// Model and token usage recorded on messages, for one conversation or all of them
+///
+/// Messages whose metadata can't be parsed are logged and skipped.
+pub fn get_message_usage(
+    conn: &Connection,
+    conversation_id: Option<&str>,
+) -> Result<Vec<(Option<String>, TokenUsage)>, ConversationDbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, metadata FROM messages
+         WHERE metadata IS NOT NULL AND (?1 IS NULL OR conversation_id = ?1)",
+    )?;
+    let rows = stmt.query_map([conversation_id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    let mut usage = Vec::new();
+    for row in rows {
+        let (id, json) = row?;
+        let metadata: MessageMetadata = match serde_json::from_str(&json) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                log::warn!("[DB] Skipping usage for message {} with unreadable metadata: {}", id, e);
+                continue;
+            }
+        };
+        if let Some(u) = metadata.usage {
+            usage.push((metadata.model, u));
+        }
+    }
+    Ok(usage)
+}
+
 /// Get all messages for a conversation, ordered by created_at
 pub fn get_conversation_messages(
     conn: &Connection,
@@ -394,6 +568,12 @@ pub fn get_conversation_messages_paginated(
     Ok(messages)
 }
 
+/// Delete a single message
+pub fn delete_message(conn: &Connection, id: &str) -> Result<bool, ConversationDbError> {
+    let rows_affected = conn.execute("DELETE FROM messages WHERE id = ?1", [id])?;
+    Ok(rows_affected > 0)
+}
+
 /// Delete all messages from a given message onwards (inclusive)
 /// Used when editing a message - deletes the original and all subsequent messages
 pub fn delete_messages_from(
@@ -468,6 +648,86 @@ pub fn generate_title_from_message(content: &str, max_len: usize) -> String {
     }
 }
 
+/// Turn free text into an FTS5 query that matches messages containing every word
+///
+/// Each word is quoted so punctuation in the user's query can't be parsed as
+/// FTS5 syntax.
+fn to_fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Re-index every message for full-text search
+///
+/// Used after the database file is replaced (e.g. by a backup restore), so the
+/// index can't refer to messages that are no longer there.
+pub fn rebuild_message_search_index(conn: &Connection) -> Result<(), ConversationDbError> {
+    conn.execute_batch(
+        "DELETE FROM messages_fts;
+         DELETE FROM messages_fts_keys;
+         INSERT INTO messages_fts_keys(message_id) SELECT id FROM messages;
+         INSERT INTO messages_fts(rowid, message_id, content)
+             SELECT k.id, m.id, m.content FROM messages m JOIN messages_fts_keys k ON k.message_id = m.id;",
+    )?;
+    Ok(())
+}
+
+/// Full-text search over message content
+///
+/// Returns one result per conversation, using its best-matching message for the
+/// snippet, ordered by relevance.
+pub fn search_conversations(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<ConversationSearchResult>, ConversationDbError> {
+    let fts_query = to_fts_query(query);
+    if fts_query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT m.conversation_id, c.title, m.id,
+                snippet(messages_fts, 1, '', '', '...', 24) AS snippet,
+                bm25(messages_fts) AS rank
+         FROM messages_fts
+         JOIN messages m ON m.id = messages_fts.message_id
+         JOIN conversations c ON c.id = m.conversation_id
+         WHERE messages_fts MATCH ?1
+         ORDER BY rank",
+    )?;
+
+    let rows = stmt.query_map([fts_query], |row| {
+        let rank: f64 = row.get(4)?;
+        Ok(ConversationSearchResult {
+            conversation_id: row.get(0)?,
+            title: row.get(1)?,
+            message_id: row.get(2)?,
+            snippet: row.get(3)?,
+            // bm25 is negative with lower meaning better; map it into 0-1
+            score: (-rank / (1.0 - rank)) as f32,
+            mode: "fulltext".to_string(),
+        })
+    })?;
+
+    let mut results: Vec<ConversationSearchResult> = Vec::new();
+    for result in rows {
+        let result = result?;
+        if results.iter().any(|r| r.conversation_id == result.conversation_id) {
+            continue;
+        }
+        results.push(result);
+        if results.len() >= limit {
+            break;
+        }
+    }
+
+    Ok(results)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -516,6 +776,36 @@ mod tests {
         assert!(messages.is_empty());
     }
 
+    #[test]
+    fn test_delete_conversations_older_than() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let old = create_conversation(&conn, Some("Old"), None).unwrap();
+        create_message(&conn, &old.id, MessageRole::User, "Hello", None).unwrap();
+        let later = create_conversation(&conn, Some("Later"), None).unwrap();
+        let recent = create_conversation(&conn, Some("Recent"), None).unwrap();
+
+        // Stored the way conversations are written, an hour either side of the cutoff
+        let cutoff = DateTime::parse_from_rfc3339("2024-06-15T12:00:00Z").unwrap().with_timezone(&Utc);
+        for (id, updated_at) in [
+            (&old.id, cutoff - chrono::Duration::hours(1)),
+            (&later.id, cutoff + chrono::Duration::hours(1)),
+        ] {
+            conn.execute(
+                "UPDATE conversations SET updated_at = ?1 WHERE id = ?2",
+                [updated_at.to_rfc3339(), id.clone()],
+            )
+            .unwrap();
+        }
+
+        assert_eq!(delete_conversations_older_than(&conn, cutoff).unwrap(), 1);
+        assert!(get_conversation(&conn, &old.id).unwrap().is_none());
+        assert!(get_conversation_messages(&conn, &old.id).unwrap().is_empty());
+        assert!(get_conversation(&conn, &later.id).unwrap().is_some());
+        assert!(get_conversation(&conn, &recent.id).unwrap().is_some());
+    }
+
     #[test]
     fn test_generate_title() {
         assert_eq!(generate_title_from_message("Hello", 10), "Hello");
@@ -524,4 +814,213 @@ mod tests {
             "This is a ve..."
         );
     }
+
+    #[test]
+    fn test_search_conversations() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let rust = create_conversation(&conn, Some("Rust help"), None).unwrap();
+        create_message(&conn, &rust.id, MessageRole::User, "How do lifetimes work?", None).unwrap();
+        create_message(
+            &conn,
+            &rust.id,
+            MessageRole::Assistant,
+            "Lifetimes tell the borrow checker how long references are valid.",
+            None,
+        )
+        .unwrap();
+
+        let cooking = create_conversation(&conn, Some("Dinner"), None).unwrap();
+        create_message(&conn, &cooking.id, MessageRole::User, "What goes well with risotto?", None).unwrap();
+        create_message(&conn, &cooking.id, MessageRole::Assistant, "Try a crisp white wine.", None).unwrap();
+
+        let results = search_conversations(&conn, "borrow checker", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].conversation_id, rust.id);
+        assert_eq!(results[0].title, Some("Rust help".to_string()));
+        assert!(results[0].snippet.as_ref().unwrap().contains("borrow checker"));
+        assert!(results[0].score > 0.0 && results[0].score <= 1.0);
+
+        // Both messages in a conversation match, but it is returned once
+        let results = search_conversations(&conn, "lifetimes", 10).unwrap();
+        assert_eq!(results.len(), 1);
+
+        // Punctuation in the query is treated as text, not FTS syntax
+        let results = search_conversations(&conn, "risotto?", 10).unwrap();
+        assert_eq!(results[0].conversation_id, cooking.id);
+        assert!(search_conversations(&conn, "\"AND OR -", 10).unwrap().is_empty());
+
+        // Deleted conversations drop out of the index
+        delete_conversation(&conn, &rust.id).unwrap();
+        assert!(search_conversations(&conn, "borrow", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rebuild_message_search_index() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let conv = create_conversation(&conn, Some("Garden"), None).unwrap();
+        create_message(&conn, &conv.id, MessageRole::User, "When should I prune tomatoes?", None).unwrap();
+        conn.execute_batch(
+            "DELETE FROM messages_fts;
+             INSERT INTO messages_fts(message_id, content) VALUES ('gone', 'stale tomatoes');",
+        )
+        .unwrap();
+
+        rebuild_message_search_index(&conn).unwrap();
+        let results = search_conversations(&conn, "tomatoes", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].conversation_id, conv.id);
+        assert!(search_conversations(&conn, "stale", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_conversations_survives_rowid_changes() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let old = create_conversation(&conn, Some("Old"), None).unwrap();
+        create_message(&conn, &old.id, MessageRole::User, "Something forgettable", None).unwrap();
+        let kept = create_conversation(&conn, Some("Kept"), None).unwrap();
+        let message = create_message(&conn, &kept.id, MessageRole::User, "Ask about sourdough starters", None).unwrap();
+
+        // messages has a TEXT primary key, so VACUUM or a restore may renumber
+        // its rowids without touching content
+        delete_conversation(&conn, &old.id).unwrap();
+        conn.execute_batch("UPDATE messages SET rowid = rowid + 100").unwrap();
+
+        let results = search_conversations(&conn, "sourdough", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].message_id, message.id);
+        assert!(search_conversations(&conn, "forgettable", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_message_edits_replace_their_index_entry() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let conv = create_conversation(&conn, Some("Bread"), None).unwrap();
+        let message = create_message(&conn, &conv.id, MessageRole::User, "Rye needs a long proof", None).unwrap();
+        create_message(&conn, &conv.id, MessageRole::Assistant, "Keep the dough warm", None).unwrap();
+
+        conn.execute(
+            "UPDATE messages SET content = 'Spelt needs a short proof' WHERE id = ?1",
+            [&message.id],
+        )
+        .unwrap();
+
+        assert!(search_conversations(&conn, "rye", 10).unwrap().is_empty());
+        assert_eq!(search_conversations(&conn, "spelt", 10).unwrap()[0].message_id, message.id);
+        let indexed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM messages_fts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(indexed, 2);
+    }
+
+    #[test]
+    fn test_message_usage_skips_unreadable_metadata() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let conv = create_conversation(&conn, Some("Usage"), None).unwrap();
+        let metadata = MessageMetadata {
+            model: Some("gpt-4o".to_string()),
+            usage: Some(TokenUsage {
+                prompt_tokens: 10,
+                completion_tokens: 5,
+                total_tokens: 15,
+            }),
+            citations: Vec::new(),
+            tool_calls: Vec::new(),
+            thinking_content: None,
+            images: Vec::new(),
+        };
+        create_message(&conn, &conv.id, MessageRole::Assistant, "Counted", Some(&metadata)).unwrap();
+        let broken = create_message(&conn, &conv.id, MessageRole::Assistant, "Broken", Some(&metadata)).unwrap();
+        conn.execute("UPDATE messages SET metadata = '{not json' WHERE id = ?1", [&broken.id])
+            .unwrap();
+
+        let usage = get_message_usage(&conn, Some(&conv.id)).unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].0.as_deref(), Some("gpt-4o"));
+        assert_eq!(usage[0].1.total_tokens, 15);
+    }
+
+    #[test]
+    fn test_branch_conversation() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let conv = create_conversation(&conn, Some("Ideas"), Some("Be brief")).unwrap();
+        create_message(&conn, &conv.id, MessageRole::User, "Give me an idea", None).unwrap();
+        let reply = create_message(&conn, &conv.id, MessageRole::Assistant, "Idea A", None).unwrap();
+        create_message(&conn, &conv.id, MessageRole::User, "Another", None).unwrap();
+        create_message(&conn, &conv.id, MessageRole::Assistant, "Idea B", None).unwrap();
+
+        let branch = branch_conversation(&conn, &reply.id).unwrap();
+        assert_ne!(branch.id, conv.id);
+        assert_eq!(branch.title, Some("Ideas (branch)".to_string()));
+        assert_eq!(branch.system_prompt, Some("Be brief".to_string()));
+        assert_eq!(branch.parent_message_id, Some(reply.id.clone()));
+        assert_eq!(
+            get_conversation(&conn, &branch.id).unwrap().unwrap().parent_message_id,
+            Some(reply.id.clone())
+        );
+
+        let contents = |id: &str| -> Vec<String> {
+            get_conversation_messages(&conn, id).unwrap().into_iter().map(|m| m.content).collect()
+        };
+        assert_eq!(contents(&branch.id), vec!["Give me an idea", "Idea A"]);
+
+        // The branch and the original evolve independently
+        create_message(&conn, &branch.id, MessageRole::User, "Something else", None).unwrap();
+        assert_eq!(contents(&conv.id).len(), 4);
+        assert_eq!(contents(&branch.id).len(), 3);
+
+        delete_conversation(&conn, &conv.id).unwrap();
+        assert_eq!(contents(&branch.id).len(), 3);
+        assert_eq!(get_conversation(&conn, &branch.id).unwrap().unwrap().parent_message_id, None);
+    }
+
+    #[test]
+    fn test_history_summary_cache() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let conv = create_conversation(&conn, Some("Long chat"), None).unwrap();
+        assert!(get_history_summary(&conn, &conv.id).unwrap().is_none());
+
+        set_history_summary(&conn, &conv.id, "Talked about Rust", 12).unwrap();
+        assert_eq!(
+            get_history_summary(&conn, &conv.id).unwrap(),
+            Some(("Talked about Rust".to_string(), 12))
+        );
+        assert!(get_history_summary(&conn, "missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_reasoning_settings_roundtrip() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let conv = create_conversation(&conn, Some("Hard problem"), None).unwrap();
+        let settings = get_reasoning_settings(&conn, &conv.id).unwrap();
+        assert!(!settings.is_enabled());
+
+        let settings = ReasoningSettings {
+            reasoning_effort: Some("high".to_string()),
+            thinking_budget: Some(8000),
+        };
+        set_reasoning_settings(&conn, &conv.id, &settings).unwrap();
+        assert_eq!(get_reasoning_settings(&conn, &conv.id).unwrap(), settings);
+
+        assert!(matches!(
+            get_reasoning_settings(&conn, "missing"),
+            Err(ConversationDbError::NotFound(_))
+        ));
+        assert!(set_reasoning_settings(&conn, "missing", &settings).is_err());
+    }
 }
@@ -15,6 +15,27 @@ pub enum EmbeddingDbError {
     NotFound(String),
     #[error("Invalid embedding data")]
     InvalidData,
+    #[error(transparent)]
+    DimensionMismatch(#[from] DimensionMismatch),
+}
+
+/// An embedding whose length doesn't match the configured model dimension
+#[derive(Error, Debug)]
+#[error("Embedding has {actual} dimensions but the configured model expects {expected}; reindex embeddings after changing the embedding model")]
+pub struct DimensionMismatch {
+    pub expected: u32,
+    pub actual: u32,
+}
+
+/// Check an embedding against the expected dimension (`None` skips the check)
+pub fn check_dimension(embedding: &[f32], expected: Option<u32>) -> Result<(), DimensionMismatch> {
+    match expected {
+        Some(expected) if embedding.len() as u32 != expected => Err(DimensionMismatch {
+            expected,
+            actual: embedding.len() as u32,
+        }),
+        _ => Ok(()),
+    }
 }
 
 /// Stored embedding data
@@ -41,13 +62,18 @@ pub struct SimilarityResult {
 }
 
 /// Store an embedding for a note
+///
+/// Rejects the embedding if `expected_dimension` is set and doesn't match its length.
 pub fn store_embedding(
     conn: &Connection,
     note_id: &str,
     embedding: &[f32],
     model: &str,
     model_version: Option<&str>,
+    expected_dimension: Option<u32>,
 ) -> Result<(), EmbeddingDbError> {
+    check_dimension(embedding, expected_dimension)?;
+
     // Serialize embedding to bytes (little-endian f32 values)
     let embedding_bytes: Vec<u8> = embedding
         .iter()
@@ -222,6 +248,105 @@ pub fn search_similar_to_note(
     Ok(results)
 }
 
+/// A cluster of notes whose embeddings are nearly identical
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub note_ids: Vec<String>,
+    /// Highest similarity between two notes in the group, on the same scale as `SimilarityResult::score`
+    pub similarity: f32,
+}
+
+/// Group notes whose pairwise similarity is at least `min_similarity`
+///
+/// Groups are transitive: if A matches B and B matches C, all three end up in
+/// one group even if A and C fall just under the threshold. Only embeddings
+/// produced by `model` are compared, since vectors from different models aren't
+/// comparable even at the same dimension. Compares every pair, so this is meant
+/// for an occasional review rather than per-keystroke use.
+pub fn find_duplicate_groups(
+    conn: &Connection,
+    model: &str,
+    min_similarity: f32,
+) -> Result<Vec<DuplicateGroup>, EmbeddingDbError> {
+    let mut stmt = conn.prepare(
+        "SELECT ne.note_id, ne.embedding
+         FROM note_embeddings ne
+         JOIN notes n ON n.id = ne.note_id
+         WHERE n.is_deleted = FALSE
+           AND ne.model = ?1
+         ORDER BY n.updated_at DESC",
+    )?;
+    let mut embeddings: Vec<(String, Vec<f32>)> = Vec::new();
+    for row in stmt.query_map([model], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?)))? {
+        let (note_id, bytes) = row?;
+        embeddings.push((note_id, bytes_to_embedding(&bytes)?));
+    }
+
+    // Union-find over note indices, tracking each root's best pair score
+    let mut parent: Vec<usize> = (0..embeddings.len()).collect();
+    fn find(parent: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parent[root] != root {
+            root = parent[root];
+        }
+        parent[i] = root;
+        root
+    }
+
+    let mut best: Vec<f32> = vec![0.0; embeddings.len()];
+    for i in 0..embeddings.len() {
+        for j in (i + 1)..embeddings.len() {
+            let (a, b) = (&embeddings[i].1, &embeddings[j].1);
+            if a.len() != b.len() {
+                continue;
+            }
+            let score = (1.0 + cosine_similarity(a, b)) / 2.0;
+            if score < min_similarity {
+                continue;
+            }
+            let (root_i, root_j) = (find(&mut parent, i), find(&mut parent, j));
+            let merged = best[root_i].max(best[root_j]).max(score);
+            parent[root_j] = root_i;
+            best[root_i] = merged;
+        }
+    }
+
+    let mut groups: Vec<(usize, DuplicateGroup)> = Vec::new();
+    for (i, (note_id, _)) in embeddings.iter().enumerate() {
+        let root = find(&mut parent, i);
+        match groups.iter_mut().find(|(r, _)| *r == root) {
+            Some((_, group)) => group.note_ids.push(note_id.clone()),
+            None => groups.push((
+                root,
+                DuplicateGroup {
+                    note_ids: vec![note_id.clone()],
+                    similarity: best[root],
+                },
+            )),
+        }
+    }
+
+    let mut groups: Vec<DuplicateGroup> = groups
+        .into_iter()
+        .map(|(_, group)| group)
+        .filter(|group| group.note_ids.len() > 1)
+        .collect();
+    groups.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    Ok(groups)
+}
+
+/// Cosine similarity of two vectors of equal length, 0 if either is all zeros
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
 /// Get embedding statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -318,7 +443,7 @@ mod tests {
 
         // Store embedding
         let embedding = vec![0.1, 0.2, 0.3, 0.4, 0.5];
-        store_embedding(&conn, "note1", &embedding, "test-model", Some("v1")).unwrap();
+        store_embedding(&conn, "note1", &embedding, "test-model", Some("v1"), None).unwrap();
 
         // Get embedding
         let retrieved = get_embedding(&conn, "note1").unwrap().unwrap();
@@ -328,6 +453,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_store_embedding_rejects_dimension_mismatch() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        conn.execute(
+            "INSERT INTO notes (id, title, content) VALUES ('note1', 'Test', 'Content')",
+            [],
+        )
+        .unwrap();
+
+        let err = store_embedding(&conn, "note1", &[0.1, 0.2, 0.3], "test-model", None, Some(768))
+            .unwrap_err();
+        assert!(err.to_string().contains("reindex"));
+        match err {
+            EmbeddingDbError::DimensionMismatch(DimensionMismatch { expected, actual }) => {
+                assert_eq!(expected, 768);
+                assert_eq!(actual, 3);
+            }
+            other => panic!("expected dimension mismatch, got {:?}", other),
+        }
+        assert!(get_embedding(&conn, "note1").unwrap().is_none());
+
+        store_embedding(&conn, "note1", &[0.1, 0.2, 0.3], "test-model", None, Some(3)).unwrap();
+        assert!(get_embedding(&conn, "note1").unwrap().is_some());
+    }
+
     #[test]
     fn test_bytes_to_embedding() {
         let original = [0.1f32, 0.2, 0.3, 0.4];
@@ -339,4 +491,42 @@ mod tests {
             assert!((a - b).abs() < 0.0001);
         }
     }
+
+    #[test]
+    fn test_find_duplicate_groups() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        for (id, embedding) in [
+            ("original", vec![0.9, 0.1, 0.0, 0.2]),
+            ("copy", vec![0.88, 0.12, 0.01, 0.2]),
+            ("distinct", vec![0.0, 0.1, 0.95, -0.3]),
+        ] {
+            conn.execute(
+                "INSERT INTO notes (id, title, content) VALUES (?1, ?1, 'Content')",
+                [id],
+            )
+            .unwrap();
+            store_embedding(&conn, id, &embedding, "test-model", None, None).unwrap();
+        }
+
+        // Embeddings from another model aren't compared against the current one
+        conn.execute(
+            "INSERT INTO notes (id, title, content) VALUES ('stale', 'stale', 'Content')",
+            [],
+        )
+        .unwrap();
+        store_embedding(&conn, "stale", &[0.9, 0.1, 0.0, 0.2], "old-model", None, None).unwrap();
+
+        let groups = find_duplicate_groups(&conn, "test-model", 0.97).unwrap();
+        assert_eq!(groups.len(), 1);
+        let mut ids = groups[0].note_ids.clone();
+        ids.sort();
+        assert_eq!(ids, vec!["copy", "original"]);
+        assert!(groups[0].similarity > 0.99);
+
+        // Deleted notes aren't reported
+        conn.execute("UPDATE notes SET is_deleted = TRUE WHERE id = 'copy'", []).unwrap();
+        assert!(find_duplicate_groups(&conn, "test-model", 0.97).unwrap().is_empty());
+    }
 }
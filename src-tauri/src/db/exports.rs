@@ -2,7 +2,8 @@
 //!
 //! Handles CRUD operations for document exports metadata.
 
-use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -24,6 +25,7 @@ pub enum ExportFormat {
     Docx,
     Xlsx,
     Pptx,
+    Markdown,
 }
 
 impl std::fmt::Display for ExportFormat {
@@ -39,6 +41,7 @@ impl ExportFormat {
             ExportFormat::Docx => "docx",
             ExportFormat::Xlsx => "xlsx",
             ExportFormat::Pptx => "pptx",
+            ExportFormat::Markdown => "markdown",
         }
     }
 
@@ -48,12 +51,89 @@ impl ExportFormat {
             "docx" => Some(ExportFormat::Docx),
             "xlsx" => Some(ExportFormat::Xlsx),
             "pptx" => Some(ExportFormat::Pptx),
+            "markdown" | "md" => Some(ExportFormat::Markdown),
             _ => None,
         }
     }
 
 }
 
+/// What produced an export
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportGenerator {
+    /// Direct conversion of note content
+    Mechanical,
+    /// Written by an AI agent
+    Agent,
+}
+
+impl ExportGenerator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportGenerator::Mechanical => "mechanical",
+            ExportGenerator::Agent => "agent",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "mechanical" => Some(ExportGenerator::Mechanical),
+            "agent" => Some(ExportGenerator::Agent),
+            _ => None,
+        }
+    }
+}
+
+/// What an export was built from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportSource {
+    /// The content of its source notes
+    Notes,
+    /// A chat conversation's transcript
+    Conversation,
+    /// Content passed in directly (e.g. a table selection)
+    Content,
+}
+
+impl ExportSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportSource::Notes => "notes",
+            ExportSource::Conversation => "conversation",
+            ExportSource::Content => "content",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "notes" => Some(ExportSource::Notes),
+            "conversation" => Some(ExportSource::Conversation),
+            "content" => Some(ExportSource::Content),
+            _ => None,
+        }
+    }
+}
+
+/// Options an export was rendered with, so regenerating it gives the same
+/// document. Unset options use the generator's defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRenderOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_images: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_break_between_notes: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight_code: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paper_size: Option<String>,
+    /// Whether a conversation export included the assistant's reasoning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_thinking: Option<bool>,
+}
+
 /// Export record from database
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -66,6 +146,18 @@ pub struct Export {
     pub file_size: Option<i64>,
     pub path: String,
     pub created_at: String,
+    /// How long generating the file took (unknown for older exports)
+    pub duration_ms: Option<i64>,
+    /// What produced the file (unknown for older exports)
+    pub generator: Option<ExportGenerator>,
+    /// Model used when an agent produced the file
+    pub model: Option<String>,
+    /// What the export was built from (unknown for older exports)
+    pub source: Option<ExportSource>,
+    /// The conversation a conversation export was rendered from
+    pub conversation_id: Option<String>,
+    /// Options the file was rendered with
+    pub options: ExportRenderOptions,
 }
 
 /// Input for creating a new export
@@ -78,16 +170,65 @@ pub struct CreateExportInput {
     pub source_note_ids: Vec<String>,
     pub file_size: Option<i64>,
     pub path: String,
+    pub duration_ms: Option<i64>,
+    pub generator: ExportGenerator,
+    pub model: Option<String>,
+    pub source: ExportSource,
+    pub conversation_id: Option<String>,
+    pub options: ExportRenderOptions,
+}
+
+const EXPORT_COLUMNS: &str =
+    "id, filename, title, format, source_note_ids, file_size, path, created_at, duration_ms, generator, model, \
+     source, conversation_id, options";
+
+/// Map a database row to an Export, or `None` if its format is unknown
+fn row_to_export(row: &Row) -> Result<Option<Export>, rusqlite::Error> {
+    let format_str: String = row.get(3)?;
+    let Some(format) = ExportFormat::from_str(&format_str) else {
+        return Ok(None);
+    };
+
+    let source_note_ids: Vec<String> = row
+        .get::<_, Option<String>>(4)?
+        .map(|json| serde_json::from_str(&json).unwrap_or_default())
+        .unwrap_or_default();
+
+    Ok(Some(Export {
+        id: row.get(0)?,
+        filename: row.get(1)?,
+        title: row.get(2)?,
+        format,
+        source_note_ids,
+        file_size: row.get(5)?,
+        path: row.get(6)?,
+        created_at: row.get(7)?,
+        duration_ms: row.get(8)?,
+        generator: row
+            .get::<_, Option<String>>(9)?
+            .and_then(|g| ExportGenerator::from_str(&g)),
+        model: row.get(10)?,
+        source: row
+            .get::<_, Option<String>>(11)?
+            .and_then(|s| ExportSource::from_str(&s)),
+        conversation_id: row.get(12)?,
+        options: row
+            .get::<_, Option<String>>(13)?
+            .map(|json| serde_json::from_str(&json).unwrap_or_default())
+            .unwrap_or_default(),
+    }))
 }
 
 /// Create a new export record
 pub fn create_export(conn: &Connection, input: CreateExportInput) -> Result<Export, ExportDbError> {
     let id = uuid::Uuid::new_v4().to_string();
     let source_note_ids_json = serde_json::to_string(&input.source_note_ids)?;
+    let options_json = serde_json::to_string(&input.options)?;
 
     conn.execute(
-        "INSERT INTO exports (id, filename, title, format, source_note_ids, file_size, path)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "INSERT INTO exports (id, filename, title, format, source_note_ids, file_size, path, duration_ms, generator, model,
+                              source, conversation_id, options)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
         params![
             id,
             input.filename,
@@ -96,6 +237,12 @@ pub fn create_export(conn: &Connection, input: CreateExportInput) -> Result<Expo
             source_note_ids_json,
             input.file_size,
             input.path,
+            input.duration_ms,
+            input.generator.as_str(),
+            input.model,
+            input.source.as_str(),
+            input.conversation_id,
+            options_json,
         ],
     )?;
 
@@ -106,86 +253,33 @@ pub fn create_export(conn: &Connection, input: CreateExportInput) -> Result<Expo
 pub fn get_export(conn: &Connection, id: &str) -> Result<Option<Export>, ExportDbError> {
     let result = conn
         .query_row(
-            "SELECT id, filename, title, format, source_note_ids, file_size, path, created_at
-             FROM exports WHERE id = ?1",
+            &format!("SELECT {} FROM exports WHERE id = ?1", EXPORT_COLUMNS),
             [id],
-            |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, String>(1)?,
-                    row.get::<_, String>(2)?,
-                    row.get::<_, String>(3)?,
-                    row.get::<_, Option<String>>(4)?,
-                    row.get::<_, Option<i64>>(5)?,
-                    row.get::<_, String>(6)?,
-                    row.get::<_, String>(7)?,
-                ))
-            },
+            row_to_export,
         )
         .optional()?;
 
-    if let Some((id, filename, title, format_str, source_note_ids_json, file_size, path, created_at)) = result {
-        let format = ExportFormat::from_str(&format_str)
-            .ok_or_else(|| ExportDbError::NotFound(format!("Invalid format: {}", format_str)))?;
-        
-        let source_note_ids: Vec<String> = source_note_ids_json
-            .map(|json| serde_json::from_str(&json).unwrap_or_default())
-            .unwrap_or_default();
-
-        Ok(Some(Export {
-            id,
-            filename,
-            title,
-            format,
-            source_note_ids,
-            file_size,
-            path,
-            created_at,
-        }))
-    } else {
-        Ok(None)
+    match result {
+        Some(Some(export)) => Ok(Some(export)),
+        Some(None) => Err(ExportDbError::NotFound(format!("Invalid format for export: {}", id))),
+        None => Ok(None),
     }
 }
 
 /// Get all exports, ordered by creation date (newest first)
 pub fn get_all_exports(conn: &Connection) -> Result<Vec<Export>, ExportDbError> {
-    let mut stmt = conn.prepare(
-        "SELECT id, filename, title, format, source_note_ids, file_size, path, created_at
-         FROM exports ORDER BY created_at DESC",
-    )?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM exports ORDER BY created_at DESC",
+        EXPORT_COLUMNS
+    ))?;
 
-    let exports = stmt.query_map([], |row| {
-        Ok((
-            row.get::<_, String>(0)?,
-            row.get::<_, String>(1)?,
-            row.get::<_, String>(2)?,
-            row.get::<_, String>(3)?,
-            row.get::<_, Option<String>>(4)?,
-            row.get::<_, Option<i64>>(5)?,
-            row.get::<_, String>(6)?,
-            row.get::<_, String>(7)?,
-        ))
-    })?;
+    let exports = stmt.query_map([], row_to_export)?;
 
     let mut result = Vec::new();
     for export in exports {
-        let (id, filename, title, format_str, source_note_ids_json, file_size, path, created_at) = export?;
-        
-        if let Some(format) = ExportFormat::from_str(&format_str) {
-            let source_note_ids: Vec<String> = source_note_ids_json
-                .map(|json| serde_json::from_str(&json).unwrap_or_default())
-                .unwrap_or_default();
-
-            result.push(Export {
-                id,
-                filename,
-                title,
-                format,
-                source_note_ids,
-                file_size,
-                path,
-                created_at,
-            });
+        // Skip records with a format this build doesn't know
+        if let Some(export) = export? {
+            result.push(export);
         }
     }
 
@@ -194,55 +288,92 @@ pub fn get_all_exports(conn: &Connection) -> Result<Vec<Export>, ExportDbError>
 
 /// Get exports by format
 pub fn get_exports_by_format(conn: &Connection, format: &ExportFormat) -> Result<Vec<Export>, ExportDbError> {
-    let mut stmt = conn.prepare(
-        "SELECT id, filename, title, format, source_note_ids, file_size, path, created_at
-         FROM exports WHERE format = ?1 ORDER BY created_at DESC",
-    )?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM exports WHERE format = ?1 ORDER BY created_at DESC",
+        EXPORT_COLUMNS
+    ))?;
 
-    let exports = stmt.query_map([format.as_str()], |row| {
-        Ok((
-            row.get::<_, String>(0)?,
-            row.get::<_, String>(1)?,
-            row.get::<_, String>(2)?,
-            row.get::<_, String>(3)?,
-            row.get::<_, Option<String>>(4)?,
-            row.get::<_, Option<i64>>(5)?,
-            row.get::<_, String>(6)?,
-            row.get::<_, String>(7)?,
-        ))
-    })?;
+    let exports = stmt.query_map([format.as_str()], row_to_export)?;
 
     let mut result = Vec::new();
     for export in exports {
-        let (id, filename, title, format_str, source_note_ids_json, file_size, path, created_at) = export?;
-        
-        if let Some(fmt) = ExportFormat::from_str(&format_str) {
-            let source_note_ids: Vec<String> = source_note_ids_json
-                .map(|json| serde_json::from_str(&json).unwrap_or_default())
-                .unwrap_or_default();
-
-            result.push(Export {
-                id,
-                filename,
-                title,
-                format: fmt,
-                source_note_ids,
-                file_size,
-                path,
-                created_at,
-            });
+        if let Some(export) = export? {
+            result.push(export);
         }
     }
 
     Ok(result)
 }
 
+/// Update an export record after its file was regenerated
+///
+/// Regenerated files are always a mechanical conversion, so the generator is
+/// reset and any model cleared.
+pub fn update_export_file(
+    conn: &Connection,
+    id: &str,
+    source_note_ids: &[String],
+    file_size: Option<i64>,
+    duration_ms: Option<i64>,
+) -> Result<Export, ExportDbError> {
+    let source_note_ids_json = serde_json::to_string(source_note_ids)?;
+
+    let rows_affected = conn.execute(
+        "UPDATE exports
+         SET source_note_ids = ?1, file_size = ?2, duration_ms = ?3, generator = ?4, model = NULL
+         WHERE id = ?5",
+        params![
+            source_note_ids_json,
+            file_size,
+            duration_ms,
+            ExportGenerator::Mechanical.as_str(),
+            id,
+        ],
+    )?;
+    if rows_affected == 0 {
+        return Err(ExportDbError::NotFound(id.to_string()));
+    }
+
+    get_export(conn, id)?.ok_or_else(|| ExportDbError::NotFound(id.to_string()))
+}
+
 /// Delete an export record (does not delete the file)
 pub fn delete_export(conn: &Connection, id: &str) -> Result<bool, ExportDbError> {
     let rows_affected = conn.execute("DELETE FROM exports WHERE id = ?1", [id])?;
     Ok(rows_affected > 0)
 }
 
+/// Delete every export record of a format, returning the deleted records'
+/// file paths (the files are not deleted)
+pub fn delete_exports_by_format(conn: &Connection, format: &ExportFormat) -> Result<Vec<String>, ExportDbError> {
+    delete_exports_where(conn, "format = ?1", format.as_str())
+}
+
+/// Delete export records created before `cutoff`, returning the deleted
+/// records' file paths (the files are not deleted)
+///
+/// Records of formats this build doesn't know are deleted too, so their files
+/// aren't left behind with nothing pointing at them.
+pub fn delete_exports_older_than(conn: &Connection, cutoff: DateTime<Utc>) -> Result<Vec<String>, ExportDbError> {
+    let cutoff = cutoff.format("%Y-%m-%d %H:%M:%S").to_string();
+    delete_exports_where(conn, "created_at < ?1", &cutoff)
+}
+
+/// Delete the export records matching `condition`, returning their file paths
+///
+/// The paths are read and the rows deleted in one transaction, so a record
+/// created in between can't lose its row while keeping its file.
+fn delete_exports_where(conn: &Connection, condition: &str, param: &str) -> Result<Vec<String>, ExportDbError> {
+    let tx = conn.unchecked_transaction()?;
+    let paths = tx
+        .prepare(&format!("SELECT path FROM exports WHERE {}", condition))?
+        .query_map([param], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    tx.execute(&format!("DELETE FROM exports WHERE {}", condition), [param])?;
+    tx.commit()?;
+    Ok(paths)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,6 +397,12 @@ mod tests {
             source_note_ids: vec!["note-1".to_string(), "note-2".to_string()],
             file_size: Some(12345),
             path: "/path/to/exports/test-export-2024-12-21.pdf".to_string(),
+            duration_ms: Some(420),
+            generator: ExportGenerator::Mechanical,
+            model: None,
+            source: ExportSource::Notes,
+            conversation_id: None,
+            options: ExportRenderOptions::default(),
         };
 
         let export = create_export(&conn, input).unwrap();
@@ -290,6 +427,12 @@ mod tests {
                 source_note_ids: vec![],
                 file_size: None,
                 path: format!("/path/to/export-{}.pdf", i),
+                duration_ms: None,
+                generator: ExportGenerator::Mechanical,
+                model: None,
+                source: ExportSource::Notes,
+                conversation_id: None,
+                options: ExportRenderOptions::default(),
             };
             create_export(&conn, input).unwrap();
         }
@@ -309,6 +452,12 @@ mod tests {
             source_note_ids: vec![],
             file_size: None,
             path: "/path/to/to-delete.pdf".to_string(),
+            duration_ms: None,
+            generator: ExportGenerator::Mechanical,
+            model: None,
+            source: ExportSource::Notes,
+            conversation_id: None,
+            options: ExportRenderOptions::default(),
         };
 
         let export = create_export(&conn, input).unwrap();
@@ -319,6 +468,149 @@ mod tests {
         assert!(retrieved.is_none());
     }
 
+    #[test]
+    fn test_export_metadata_roundtrip() {
+        let conn = setup_test_db();
+
+        let input = CreateExportInput {
+            filename: "report.docx".to_string(),
+            title: "Report".to_string(),
+            format: ExportFormat::Docx,
+            source_note_ids: vec!["note-1".to_string()],
+            file_size: Some(2048),
+            path: "/path/to/report.docx".to_string(),
+            duration_ms: Some(15_300),
+            generator: ExportGenerator::Agent,
+            model: Some("gpt-4o".to_string()),
+            source: ExportSource::Notes,
+            conversation_id: None,
+            options: ExportRenderOptions::default(),
+        };
+        let export = create_export(&conn, input).unwrap();
+
+        let retrieved = get_export(&conn, &export.id).unwrap().unwrap();
+        assert_eq!(retrieved.duration_ms, Some(15_300));
+        assert_eq!(retrieved.generator, Some(ExportGenerator::Agent));
+        assert_eq!(retrieved.model.as_deref(), Some("gpt-4o"));
+
+        let listed = get_exports_by_format(&conn, &ExportFormat::Docx).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].generator, Some(ExportGenerator::Agent));
+        assert_eq!(listed[0].model.as_deref(), Some("gpt-4o"));
+    }
+
+    #[test]
+    fn test_exports_recorded_before_metadata_have_none() {
+        let conn = setup_test_db();
+
+        conn.execute(
+            "INSERT INTO exports (id, filename, title, format, source_note_ids, file_size, path)
+             VALUES ('old', 'old.pdf', 'Old', 'pdf', '[]', 10, '/path/to/old.pdf')",
+            [],
+        )
+        .unwrap();
+
+        let export = get_export(&conn, "old").unwrap().unwrap();
+        assert_eq!(export.duration_ms, None);
+        assert_eq!(export.generator, None);
+        assert_eq!(export.model, None);
+        assert_eq!(export.source, None);
+        assert_eq!(export.options, ExportRenderOptions::default());
+    }
+
+    #[test]
+    fn test_update_export_file() {
+        let conn = setup_test_db();
+
+        let input = CreateExportInput {
+            filename: "notes.pdf".to_string(),
+            title: "Notes".to_string(),
+            format: ExportFormat::Pdf,
+            source_note_ids: vec!["note-1".to_string(), "note-2".to_string()],
+            file_size: Some(100),
+            path: "/path/to/notes.pdf".to_string(),
+            duration_ms: Some(5),
+            generator: ExportGenerator::Agent,
+            model: Some("gpt-4o".to_string()),
+            source: ExportSource::Notes,
+            conversation_id: None,
+            options: ExportRenderOptions::default(),
+        };
+        let export = create_export(&conn, input).unwrap();
+
+        let updated = update_export_file(&conn, &export.id, &["note-2".to_string()], Some(250), Some(12)).unwrap();
+        assert_eq!(updated.source_note_ids, vec!["note-2".to_string()]);
+        assert_eq!(updated.file_size, Some(250));
+        assert_eq!(updated.duration_ms, Some(12));
+        assert_eq!(updated.generator, Some(ExportGenerator::Mechanical));
+        assert_eq!(updated.model, None);
+        assert_eq!(updated.created_at, export.created_at);
+
+        assert!(matches!(
+            update_export_file(&conn, "missing", &[], None, None),
+            Err(ExportDbError::NotFound(_))
+        ));
+    }
+
+    fn seed_export(conn: &Connection, filename: &str, format: ExportFormat) -> Export {
+        let input = CreateExportInput {
+            filename: filename.to_string(),
+            title: filename.to_string(),
+            format,
+            source_note_ids: vec![],
+            file_size: None,
+            path: format!("/path/to/{}", filename),
+            duration_ms: None,
+            generator: ExportGenerator::Mechanical,
+            model: None,
+            source: ExportSource::Notes,
+            conversation_id: None,
+            options: ExportRenderOptions::default(),
+        };
+        create_export(conn, input).unwrap()
+    }
+
+    #[test]
+    fn test_delete_exports_by_format() {
+        let conn = setup_test_db();
+        seed_export(&conn, "a.pdf", ExportFormat::Pdf);
+        seed_export(&conn, "b.pdf", ExportFormat::Pdf);
+        let docx = seed_export(&conn, "c.docx", ExportFormat::Docx);
+
+        let deleted = delete_exports_by_format(&conn, &ExportFormat::Pdf).unwrap();
+        let mut paths: Vec<_> = deleted.iter().map(String::as_str).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["/path/to/a.pdf", "/path/to/b.pdf"]);
+
+        let remaining = get_all_exports(&conn).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, docx.id);
+        assert!(delete_exports_by_format(&conn, &ExportFormat::Pdf).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_delete_exports_older_than() {
+        let conn = setup_test_db();
+        let old_pdf = seed_export(&conn, "old.pdf", ExportFormat::Pdf);
+        let old_xlsx = seed_export(&conn, "old.xlsx", ExportFormat::Xlsx);
+        let recent = seed_export(&conn, "recent.pdf", ExportFormat::Pdf);
+        conn.execute(
+            "UPDATE exports SET created_at = '2020-01-01 00:00:00' WHERE id IN (?1, ?2)",
+            [&old_pdf.id, &old_xlsx.id],
+        )
+        .unwrap();
+
+        let cutoff = Utc::now() - chrono::Duration::days(30);
+        let deleted = delete_exports_older_than(&conn, cutoff).unwrap();
+        let mut paths: Vec<_> = deleted.iter().map(String::as_str).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["/path/to/old.pdf", "/path/to/old.xlsx"]);
+
+        let remaining = get_all_exports(&conn).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, recent.id);
+    }
+
     #[test]
     fn test_export_format() {
         assert_eq!(ExportFormat::Pdf.as_str(), "pdf");
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use chrono::{DateTime, TimeZone, Utc};
 use rusqlite::{params, Connection, OptionalExtension, Row};
 use thiserror::Error;
@@ -99,6 +101,27 @@ pub fn get_child_folders(
     Ok(folders)
 }
 
+/// Get the IDs of a folder and all of its descendant folders
+pub fn get_folder_subtree_ids(
+    conn: &Connection,
+    folder_id: &str,
+) -> Result<HashSet<String>, FolderDbError> {
+    let mut subtree = HashSet::new();
+    let mut pending = vec![folder_id.to_string()];
+
+    while let Some(id) = pending.pop() {
+        // Guard against cycles in malformed folder hierarchies
+        if !subtree.insert(id.clone()) {
+            continue;
+        }
+        for child in get_child_folders(conn, Some(&id))? {
+            pending.push(child.id);
+        }
+    }
+
+    Ok(subtree)
+}
+
 /// Update an existing folder
 pub fn update_folder(
     conn: &Connection,
@@ -194,6 +217,38 @@ mod tests {
         assert_eq!(children[0].id, child.id);
     }
 
+    #[test]
+    fn test_get_folder_subtree_ids() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let make = |name: &str, parent_id: Option<String>| {
+            create_folder(
+                &conn,
+                CreateFolderInput {
+                    name: name.to_string(),
+                    parent_id,
+                },
+            )
+            .unwrap()
+        };
+
+        let root = make("Projects", None);
+        let child = make("Alpha", Some(root.id.clone()));
+        let grandchild = make("Specs", Some(child.id.clone()));
+        let sibling = make("Archive", None);
+
+        let subtree = get_folder_subtree_ids(&conn, &root.id).unwrap();
+        assert_eq!(subtree.len(), 3);
+        assert!(subtree.contains(&root.id));
+        assert!(subtree.contains(&child.id));
+        assert!(subtree.contains(&grandchild.id));
+        assert!(!subtree.contains(&sibling.id));
+
+        let leaf = get_folder_subtree_ids(&conn, &grandchild.id).unwrap();
+        assert_eq!(leaf.len(), 1);
+    }
+
     #[test]
     fn test_update_folder() {
         let pool = init_test_pool().unwrap();
@@ -4,6 +4,7 @@
 //! created via `[[note]]` syntax.
 
 use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -11,6 +12,8 @@ use thiserror::Error;
 pub enum LinkDbError {
     #[error("SQLite error: {0}")]
     SqliteError(#[from] rusqlite::Error),
+    #[error("Alias '{0}' is already used by another note")]
+    AliasTaken(String),
 }
 
 /// A link between two notes
@@ -39,6 +42,270 @@ pub struct LinkInput {
     pub context: Option<String>,
 }
 
+/// Markers delimiting the auto-maintained backlinks block in note content
+pub const BACKLINKS_START: &str = "<!-- backlinks:start -->";
+pub const BACKLINKS_END: &str = "<!-- backlinks:end -->";
+
+/// A `[[wikilink]]` reference found in note content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WikiLinkRef {
+    /// The link target used for resolution (the part before any `|`)
+    pub target: String,
+    /// Display text from `[[Target|display]]` syntax
+    pub display: Option<String>,
+    /// Character offset of the opening `[[` in the content
+    pub position: usize,
+}
+
+/// A link whose target does not resolve to an existing note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokenLink {
+    pub source_note_id: String,
+    pub source_title: String,
+    /// The unresolved link text
+    pub link_text: String,
+    /// Character offset of the link in the source note content, if present
+    pub position: Option<usize>,
+}
+
+/// Extract all `[[wikilink]]` references from content
+pub fn parse_wikilinks(content: &str) -> Vec<WikiLinkRef> {
+    let mut links = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(rel_start) = content[search_from..].find("[[") {
+        let start = search_from + rel_start;
+        let inner_start = start + 2;
+
+        let Some(rel_end) = content[inner_start..].find("]]") else {
+            break;
+        };
+        let inner = &content[inner_start..inner_start + rel_end];
+
+        // A nested opener means this one was never closed; retry from the inner one
+        if let Some(nested) = inner.rfind("[[") {
+            search_from = inner_start + nested;
+            continue;
+        }
+
+        // [[Target|display text]] resolves by the part before the pipe
+        let (target, display) = match inner.split_once('|') {
+            Some((target, display)) => {
+                let display = display.trim();
+                (target.trim(), (!display.is_empty()).then(|| display.to_string()))
+            }
+            None => (inner.trim(), None),
+        };
+
+        if !target.is_empty() && !inner.contains('\n') {
+            links.push(WikiLinkRef {
+                target: target.to_string(),
+                display,
+                position: content[..start].chars().count(),
+            });
+        }
+
+        search_from = inner_start + rel_end + 2;
+    }
+
+    links
+}
+
+/// Remove the generated backlinks block, keeping whatever surrounds it
+pub fn strip_backlinks_section(content: &str) -> String {
+    match (content.find(BACKLINKS_START), content.find(BACKLINKS_END)) {
+        (Some(start), Some(end)) if end > start => {
+            let before = content[..start].trim_end();
+            let after = content[end + BACKLINKS_END.len()..].trim();
+            if after.is_empty() {
+                before.to_string()
+            } else if before.is_empty() {
+                after.to_string()
+            } else {
+                format!("{}\n\n{}", before, after)
+            }
+        }
+        _ => content.trim_end().to_string(),
+    }
+}
+
+/// Resolve a link target to a note ID by case-insensitive title match
+/// Falls back to note aliases when no title matches.
+pub fn resolve_link_target(conn: &Connection, target: &str) -> Result<Option<String>, LinkDbError> {
+    let target = target.trim();
+
+    let by_title: Option<String> = conn
+        .query_row(
+            "SELECT id FROM notes
+             WHERE LOWER(title) = LOWER(?1) AND is_deleted = FALSE
+             ORDER BY updated_at DESC
+             LIMIT 1",
+            [target],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    if by_title.is_some() {
+        return Ok(by_title);
+    }
+
+    let by_alias = conn
+        .query_row(
+            "SELECT n.id FROM note_aliases a
+             JOIN notes n ON n.id = a.note_id
+             WHERE LOWER(a.alias) = LOWER(?1) AND n.is_deleted = FALSE
+             ORDER BY n.updated_at DESC
+             LIMIT 1",
+            [target],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(by_alias)
+}
+
+/// Resolve the `[[wikilinks]]` in a note's content into link inputs
+/// Self-links, unresolvable targets, and the generated backlinks block are skipped.
+pub fn resolve_content_links(
+    conn: &Connection,
+    source_id: &str,
+    content: &str,
+) -> Result<Vec<LinkInput>, LinkDbError> {
+    let mut links: Vec<LinkInput> = Vec::new();
+
+    for link in parse_wikilinks(&strip_backlinks_section(content)) {
+        let Some(target_id) = resolve_link_target(conn, &link.target)? else {
+            continue;
+        };
+        if target_id == source_id || links.iter().any(|l| l.target_note_id == target_id) {
+            continue;
+        }
+        links.push(LinkInput {
+            target_note_id: target_id,
+            context: link.display,
+        });
+    }
+
+    Ok(links)
+}
+
+/// Add an alternate title that links can resolve to
+///
+/// Aliases are matched case-insensitively, so one that differs from an existing
+/// alias only by case is a no-op on the same note and rejected on another.
+pub fn add_note_alias(conn: &Connection, note_id: &str, alias: &str) -> Result<(), LinkDbError> {
+    let alias = alias.trim();
+    let owner: Option<String> = conn
+        .query_row(
+            "SELECT note_id FROM note_aliases WHERE alias = ?1 COLLATE NOCASE LIMIT 1",
+            [alias],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match owner {
+        Some(owner) if owner == note_id => Ok(()),
+        Some(_) => Err(LinkDbError::AliasTaken(alias.to_string())),
+        None => {
+            conn.execute(
+                "INSERT INTO note_aliases (note_id, alias) VALUES (?1, ?2)",
+                params![note_id, alias],
+            )?;
+            Ok(())
+        }
+    }
+}
+
+/// Remove an alias from a note
+pub fn remove_note_alias(conn: &Connection, note_id: &str, alias: &str) -> Result<bool, LinkDbError> {
+    let rows_affected = conn.execute(
+        "DELETE FROM note_aliases WHERE note_id = ?1 AND LOWER(alias) = LOWER(?2)",
+        params![note_id, alias.trim()],
+    )?;
+    Ok(rows_affected > 0)
+}
+
+/// Get all aliases declared for a note
+pub fn get_note_aliases(conn: &Connection, note_id: &str) -> Result<Vec<String>, LinkDbError> {
+    let mut stmt = conn.prepare(
+        "SELECT alias FROM note_aliases WHERE note_id = ?1 ORDER BY alias",
+    )?;
+
+    let aliases = stmt
+        .query_map([note_id], |row| row.get(0))?
+        .filter_map(Result::ok)
+        .collect();
+
+    Ok(aliases)
+}
+
+/// Find links that point to notes which don't exist
+///
+/// Reports `[[wikilinks]]` in note content whose title resolves to no note,
+/// as well as stored links whose target note is missing or has since been
+/// deleted.
+pub fn get_broken_links(conn: &Connection) -> Result<Vec<BrokenLink>, LinkDbError> {
+    // Stored links whose target was deleted (or removed) after the link was made
+    let mut stmt = conn.prepare(
+        "SELECT nl.source_note_id, COALESCE(t.title, nl.target_note_id) FROM note_links nl
+         LEFT JOIN notes t ON t.id = nl.target_note_id
+         WHERE t.id IS NULL OR t.is_deleted = TRUE",
+    )?;
+    let mut dead_targets: HashMap<String, Vec<String>> = HashMap::new();
+    for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))? {
+        let (source_id, target_title) = row?;
+        dead_targets.entry(source_id).or_default().push(target_title);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, title, content FROM notes
+         WHERE is_deleted = FALSE
+         ORDER BY updated_at DESC",
+    )?;
+    let notes: Vec<(String, String, Option<String>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .filter_map(Result::ok)
+        .collect();
+
+    let mut broken = Vec::new();
+
+    for (note_id, note_title, content) in &notes {
+        let content = content.as_deref().unwrap_or("");
+        let wikilinks = parse_wikilinks(content);
+        let mut reported: Vec<String> = Vec::new();
+
+        for link in &wikilinks {
+            if resolve_link_target(conn, &link.target)?.is_none() {
+                reported.push(link.target.to_lowercase());
+                broken.push(BrokenLink {
+                    source_note_id: note_id.clone(),
+                    source_title: note_title.clone(),
+                    link_text: link.target.clone(),
+                    position: Some(link.position),
+                });
+            }
+        }
+
+        for target_title in dead_targets.remove(note_id).unwrap_or_default() {
+            if reported.contains(&target_title.to_lowercase()) {
+                continue;
+            }
+            let position = wikilinks
+                .iter()
+                .find(|l| l.target.to_lowercase() == target_title.to_lowercase())
+                .map(|l| l.position);
+            broken.push(BrokenLink {
+                source_note_id: note_id.clone(),
+                source_title: note_title.clone(),
+                link_text: target_title,
+                position,
+            });
+        }
+    }
+
+    Ok(broken)
+}
+
 /// Create a link from source to target
 pub fn create_link(
     conn: &Connection,
@@ -235,6 +502,7 @@ fn build_folder_path(
     Ok(Some(path_parts.join(" / ")))
 }
 
+/// Notes are matched by title or by any of their aliases
 pub fn search_notes_by_title(
     conn: &Connection,
     query: &str,
@@ -247,7 +515,10 @@ pub fn search_notes_by_title(
     let notes: Vec<(String, String, Option<String>)> = if let Some(exclude) = exclude_id {
         let mut stmt = conn.prepare(
             "SELECT id, title, folder_id FROM notes
-             WHERE title LIKE ?1 AND is_deleted = FALSE AND id != ?2
+             WHERE (title LIKE ?1 OR EXISTS (
+                 SELECT 1 FROM note_aliases a WHERE a.note_id = notes.id AND a.alias LIKE ?1
+             ))
+             AND is_deleted = FALSE AND id != ?2
              ORDER BY updated_at DESC
              LIMIT ?3",
         )?;
@@ -261,7 +532,10 @@ pub fn search_notes_by_title(
     } else {
         let mut stmt = conn.prepare(
             "SELECT id, title, folder_id FROM notes
-             WHERE title LIKE ?1 AND is_deleted = FALSE
+             WHERE (title LIKE ?1 OR EXISTS (
+                 SELECT 1 FROM note_aliases a WHERE a.note_id = notes.id AND a.alias LIKE ?1
+             ))
+             AND is_deleted = FALSE
              ORDER BY updated_at DESC
              LIMIT ?2",
         )?;
@@ -291,6 +565,59 @@ pub fn search_notes_by_title(
     Ok(results)
 }
 
+/// Get notes with no outgoing links and no backlinks
+/// Links to or from deleted notes are ignored. When `require_untagged` is
+/// set, notes that have any tag are excluded as well.
+pub fn get_orphan_notes(
+    conn: &Connection,
+    require_untagged: bool,
+) -> Result<Vec<NoteSummary>, LinkDbError> {
+    let tag_clause = if require_untagged {
+        "AND NOT EXISTS (SELECT 1 FROM note_tags nt WHERE nt.note_id = n.id)"
+    } else {
+        ""
+    };
+
+    let sql = format!(
+        "SELECT n.id, n.title, n.folder_id FROM notes n
+         WHERE n.is_deleted = FALSE
+           AND NOT EXISTS (
+               SELECT 1 FROM note_links nl
+               JOIN notes t ON t.id = nl.target_note_id
+               WHERE nl.source_note_id = n.id AND t.is_deleted = FALSE
+           )
+           AND NOT EXISTS (
+               SELECT 1 FROM note_links nl
+               JOIN notes src ON src.id = nl.source_note_id
+               WHERE nl.target_note_id = n.id AND src.is_deleted = FALSE
+           )
+           {}
+         ORDER BY n.updated_at DESC",
+        tag_clause
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let notes: Vec<(String, String, Option<String>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .filter_map(Result::ok)
+        .collect();
+
+    let results = notes
+        .into_iter()
+        .map(|(id, title, folder_id)| {
+            let folder_path = build_folder_path(conn, folder_id.as_deref()).unwrap_or(None);
+            NoteSummary {
+                id,
+                title,
+                folder_id,
+                folder_path,
+            }
+        })
+        .collect();
+
+    Ok(results)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -368,6 +695,169 @@ mod tests {
         assert_eq!(links[0].target_note_id, "note3");
     }
 
+    #[test]
+    fn test_get_orphan_notes() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        setup_test_notes(&conn);
+        conn.execute(
+            "INSERT INTO notes (id, title, content) VALUES ('note4', 'Fourth Note', 'Content 4')",
+            [],
+        )
+        .unwrap();
+
+        // note1 -> note2; note3 and note4 are unlinked
+        create_link(&conn, "note1", "note2", None).unwrap();
+
+        let orphans = get_orphan_notes(&conn, false).unwrap();
+        let mut ids: Vec<&str> = orphans.iter().map(|n| n.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["note3", "note4"]);
+
+        // A tagged note is only excluded when untagged orphans are requested
+        conn.execute("INSERT INTO tags (id, name) VALUES ('tag1', 'draft')", [])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO note_tags (note_id, tag_id) VALUES ('note4', 'tag1')",
+            [],
+        )
+        .unwrap();
+
+        let untagged = get_orphan_notes(&conn, true).unwrap();
+        assert_eq!(untagged.len(), 1);
+        assert_eq!(untagged[0].id, "note3");
+
+        // Links to deleted notes don't count
+        conn.execute("UPDATE notes SET is_deleted = TRUE WHERE id = 'note2'", [])
+            .unwrap();
+        let orphans = get_orphan_notes(&conn, false).unwrap();
+        assert!(orphans.iter().any(|n| n.id == "note1"));
+        assert!(orphans.iter().all(|n| n.id != "note2"));
+    }
+
+    #[test]
+    fn test_parse_wikilinks() {
+        let links = parse_wikilinks("See [[First Note]] and [[ Missing ]].\nAlso [[unclosed");
+        assert_eq!(
+            links,
+            vec![
+                WikiLinkRef {
+                    target: "First Note".to_string(),
+                    display: None,
+                    position: 4,
+                },
+                WikiLinkRef {
+                    target: "Missing".to_string(),
+                    display: None,
+                    position: 23,
+                },
+            ]
+        );
+
+        // Positions are character offsets, not bytes
+        let links = parse_wikilinks("café [[Note]]");
+        assert_eq!(links[0].position, 5);
+
+        assert!(parse_wikilinks("[[]] and [[\n]]").is_empty());
+    }
+
+    #[test]
+    fn test_parse_wikilinks_pipe_alias() {
+        let links = parse_wikilinks("Read [[Project Plan|the plan]] and [[Roadmap| ]]");
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].target, "Project Plan");
+        assert_eq!(links[0].display, Some("the plan".to_string()));
+        assert_eq!(links[1].target, "Roadmap");
+        assert_eq!(links[1].display, None);
+    }
+
+    #[test]
+    fn test_alias_resolution() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        setup_test_notes(&conn);
+
+        assert_eq!(resolve_link_target(&conn, "first note").unwrap(), Some("note1".to_string()));
+        assert_eq!(resolve_link_target(&conn, "Old Name").unwrap(), None);
+
+        add_note_alias(&conn, "note2", "Old Name").unwrap();
+        add_note_alias(&conn, "note2", "Old Name").unwrap(); // idempotent
+        add_note_alias(&conn, "note2", "old name").unwrap(); // same alias, different case
+        assert_eq!(get_note_aliases(&conn, "note2").unwrap(), vec!["Old Name".to_string()]);
+        assert!(matches!(
+            add_note_alias(&conn, "note1", "OLD NAME"),
+            Err(LinkDbError::AliasTaken(_))
+        ));
+        assert_eq!(resolve_link_target(&conn, "old name").unwrap(), Some("note2".to_string()));
+
+        // Piped links resolve by the target, carrying the display text as context
+        let links = resolve_content_links(
+            &conn,
+            "note1",
+            "See [[Old Name|the renamed note]], [[First Note]] and [[Nowhere]]",
+        )
+        .unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target_note_id, "note2");
+        assert_eq!(links[0].context, Some("the renamed note".to_string()));
+
+        // Mentions autocomplete matches aliases too
+        let results = search_notes_by_title(&conn, "Old", None, 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "note2");
+
+        // Links inside the generated backlinks block are not outgoing links
+        let with_block = format!(
+            "Body\n\n{}\n- [[Third Note]]\n{}",
+            BACKLINKS_START, BACKLINKS_END
+        );
+        assert!(resolve_content_links(&conn, "note1", &with_block).unwrap().is_empty());
+
+        assert!(remove_note_alias(&conn, "note2", "OLD NAME").unwrap());
+        assert_eq!(resolve_link_target(&conn, "Old Name").unwrap(), None);
+    }
+
+    #[test]
+    fn test_get_broken_links() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        setup_test_notes(&conn);
+
+        conn.execute(
+            "UPDATE notes SET content = 'Links to [[second note]] and [[Nonexistent Idea]]' WHERE id = 'note1'",
+            [],
+        )
+        .unwrap();
+
+        let broken = get_broken_links(&conn).unwrap();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].source_note_id, "note1");
+        assert_eq!(broken[0].link_text, "Nonexistent Idea");
+        assert_eq!(broken[0].position, Some(29));
+
+        // A stored link to a note that was later deleted is reported too
+        create_link(&conn, "note2", "note3", None).unwrap();
+        conn.execute("UPDATE notes SET is_deleted = TRUE WHERE id = 'note3'", [])
+            .unwrap();
+
+        let broken = get_broken_links(&conn).unwrap();
+        assert_eq!(broken.len(), 2);
+        let dead = broken.iter().find(|b| b.source_note_id == "note2").unwrap();
+        assert_eq!(dead.link_text, "Third Note");
+        assert_eq!(dead.position, None);
+
+        // As is one whose target row is gone entirely
+        conn.execute_batch(
+            "PRAGMA foreign_keys = OFF;
+             INSERT INTO note_links (source_note_id, target_note_id) VALUES ('note2', 'vanished');
+             PRAGMA foreign_keys = ON;",
+        )
+        .unwrap();
+        let broken = get_broken_links(&conn).unwrap();
+        assert_eq!(broken.len(), 3);
+        assert!(broken.iter().any(|b| b.source_note_id == "note2" && b.link_text == "vanished"));
+    }
+
     #[test]
     fn test_search_notes_by_title() {
         let pool = init_test_pool().unwrap();
@@ -1,4 +1,6 @@
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use std::sync::Mutex;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -7,6 +9,121 @@ pub enum MigrationError {
     SqliteError(#[from] rusqlite::Error),
 }
 
+/// All migrations, in the order they are applied
+const MIGRATIONS: &[(&str, &str)] = &[
+    ("001_initial_schema", MIGRATION_001_INITIAL_SCHEMA),
+    ("002_indexes", MIGRATION_002_INDEXES),
+    ("003_settings", MIGRATION_003_SETTINGS),
+    ("004_embeddings", MIGRATION_004_EMBEDDINGS),
+    ("005_conversations", MIGRATION_005_CONVERSATIONS),
+    ("006_boards", MIGRATION_006_BOARDS),
+    ("007_calendar_events", MIGRATION_007_CALENDAR_EVENTS),
+    ("008_google_accounts", MIGRATION_008_GOOGLE_ACCOUNTS),
+    ("009_calendar_event_type", MIGRATION_009_CALENDAR_EVENT_TYPE),
+    ("010_calendar_response_status", MIGRATION_010_CALENDAR_RESPONSE_STATUS),
+    ("011_calendar_attendees", MIGRATION_011_CALENDAR_ATTENDEES),
+    ("012_exports", MIGRATION_012_EXPORTS),
+    ("013_url_attachments", MIGRATION_013_URL_ATTACHMENTS),
+    ("014_url_metadata", MIGRATION_014_URL_METADATA),
+    ("015_url_embedding_chunks", MIGRATION_015_URL_EMBEDDING_CHUNKS),
+    ("016_note_aliases", MIGRATION_016_NOTE_ALIASES),
+    ("017_calendar_event_timezone", MIGRATION_017_CALENDAR_EVENT_TIMEZONE),
+    ("018_google_event_push", MIGRATION_018_GOOGLE_EVENT_PUSH),
+    ("019_google_sync_token", MIGRATION_019_GOOGLE_SYNC_TOKEN),
+    ("020_microsoft_accounts", MIGRATION_020_MICROSOFT_ACCOUNTS),
+    ("021_tag_description", MIGRATION_021_TAG_DESCRIPTION),
+    ("022_messages_fts", MIGRATION_022_MESSAGES_FTS),
+    ("023_markdown_exports", MIGRATION_023_MARKDOWN_EXPORTS),
+    ("024_conversation_branches", MIGRATION_024_CONVERSATION_BRANCHES),
+    ("025_system_prompts", MIGRATION_025_SYSTEM_PROMPTS),
+    ("026_history_summary", MIGRATION_026_HISTORY_SUMMARY),
+    ("027_note_sync_state", MIGRATION_027_NOTE_SYNC_STATE),
+    ("028_note_revisions", MIGRATION_028_NOTE_REVISIONS),
+    ("029_board_card_due_date", MIGRATION_029_BOARD_CARD_DUE_DATE),
+    ("030_board_wip_limits", MIGRATION_030_BOARD_WIP_LIMITS),
+    ("031_conversation_reasoning", MIGRATION_031_CONVERSATION_REASONING),
+    ("032_export_metadata", MIGRATION_032_EXPORT_METADATA),
+    ("033_calendar_event_location", MIGRATION_033_CALENDAR_EVENT_LOCATION),
+    ("034_messages_fts_message_id", MIGRATION_034_MESSAGES_FTS_MESSAGE_ID),
+    ("035_export_sources", MIGRATION_035_EXPORT_SOURCES),
+    ("036_messages_fts_keys", MIGRATION_036_MESSAGES_FTS_KEYS),
+];
+
+/// Schema version of this build: the number of known migrations
+///
+/// A database with more applied migrations than this was written by a newer
+/// version of the app.
+pub fn schema_version() -> usize {
+    MIGRATIONS.len()
+}
+
+/// Applied schema version of a database, next to the version of this build
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaVersion {
+    /// Number of migrations applied to the database
+    pub current: usize,
+    /// Number of migrations this build knows about
+    pub latest: usize,
+}
+
+/// Report the schema version of `conn`'s database without changing it
+///
+/// A database that was never migrated is at version 0.
+pub fn get_schema_version(conn: &Connection) -> Result<SchemaVersion, MigrationError> {
+    Ok(SchemaVersion {
+        current: applied_migrations(conn)?.len(),
+        latest: schema_version(),
+    })
+}
+
+/// Names of the migrations `run_migrations` would apply to `conn`'s database, in order
+pub fn list_pending_migrations(conn: &Connection) -> Result<Vec<&'static str>, MigrationError> {
+    let applied = applied_migrations(conn)?;
+    Ok(MIGRATIONS
+        .iter()
+        .map(|&(name, _)| name)
+        .filter(|name| !applied.iter().any(|a| a == name))
+        .collect())
+}
+
+/// Migrations that were pending when the app's database was last opened
+static STARTUP_MIGRATIONS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+/// Remember the migrations found pending before the app's database was migrated
+///
+/// Once `run_migrations` has run, nothing is pending any more, so this is the
+/// only point at which the list means anything.
+pub fn record_startup_migrations(pending: Vec<&'static str>) {
+    *STARTUP_MIGRATIONS.lock().unwrap() = pending;
+}
+
+/// Names of the migrations applied when the app's database was last opened, in order
+pub fn startup_migrations() -> Vec<&'static str> {
+    STARTUP_MIGRATIONS.lock().unwrap().clone()
+}
+
+/// Names recorded in `_migrations`, or none if the table doesn't exist yet
+fn applied_migrations(conn: &Connection) -> Result<Vec<String>, MigrationError> {
+    let has_table = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_migrations'",
+            [],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    if !has_table {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare("SELECT name FROM _migrations ORDER BY id")?;
+    let names = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(names)
+}
+
 /// Run all database migrations
 pub fn run_migrations(conn: &Connection) -> Result<(), MigrationError> {
     // Create migrations table if it doesn't exist
@@ -19,27 +136,11 @@ pub fn run_migrations(conn: &Connection) -> Result<(), MigrationError> {
     )?;
 
     // Run each migration
-    let migrations: Vec<(&str, &str)> = vec![
-        ("001_initial_schema", MIGRATION_001_INITIAL_SCHEMA),
-        ("002_indexes", MIGRATION_002_INDEXES),
-        ("003_settings", MIGRATION_003_SETTINGS),
-        ("004_embeddings", MIGRATION_004_EMBEDDINGS),
-        ("005_conversations", MIGRATION_005_CONVERSATIONS),
-        ("006_boards", MIGRATION_006_BOARDS),
-        ("007_calendar_events", MIGRATION_007_CALENDAR_EVENTS),
-        ("008_google_accounts", MIGRATION_008_GOOGLE_ACCOUNTS),
-        ("009_calendar_event_type", MIGRATION_009_CALENDAR_EVENT_TYPE),
-        ("010_calendar_response_status", MIGRATION_010_CALENDAR_RESPONSE_STATUS),
-        ("011_calendar_attendees", MIGRATION_011_CALENDAR_ATTENDEES),
-        ("012_exports", MIGRATION_012_EXPORTS),
-        ("013_url_attachments", MIGRATION_013_URL_ATTACHMENTS),
-        ("014_url_metadata", MIGRATION_014_URL_METADATA),
-        ("015_url_embedding_chunks", MIGRATION_015_URL_EMBEDDING_CHUNKS),
-    ];
-
-    for (name, sql) in migrations {
+
+    for &(name, sql) in MIGRATIONS {
         if !migration_applied(conn, name)? {
             conn.execute_batch(sql)?;
+            run_data_migration(conn, name)?;
             mark_migration_applied(conn, name)?;
         }
     }
@@ -47,6 +148,62 @@ pub fn run_migrations(conn: &Connection) -> Result<(), MigrationError> {
     Ok(())
 }
 
+/// Data backfills that need values only known at runtime
+fn run_data_migration(conn: &Connection, name: &str) -> Result<(), MigrationError> {
+    if name == "017_calendar_event_timezone" {
+        // Existing events were entered in the machine's local timezone
+        let local = crate::db::calendar_events::local_timezone_name();
+        backfill_event_timezones(conn, &local)?;
+    }
+    Ok(())
+}
+
+/// Stamp events that predate timezones with `timezone`
+///
+/// Timed events entered in the app were saved as naive wall-clock times in
+/// that zone, so they are moved to UTC too. Synced and imported events were
+/// already UTC, and all-day events stay at midnight.
+fn backfill_event_timezones(conn: &Connection, timezone: &str) -> Result<usize, MigrationError> {
+    let tz: chrono_tz::Tz = timezone.parse().unwrap_or(chrono_tz::UTC);
+
+    let legacy: Vec<(String, String, Option<String>)> = conn
+        .prepare(
+            "SELECT id, start_time, end_time FROM calendar_events
+             WHERE timezone IS NULL AND source = 'manual' AND COALESCE(all_day, 0) = 0",
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<_, _>>()?;
+    for (id, start_time, end_time) in legacy {
+        let start_time = local_to_utc(&start_time, &tz).unwrap_or(start_time);
+        let end_time = end_time.map(|end| local_to_utc(&end, &tz).unwrap_or(end));
+        conn.execute(
+            "UPDATE calendar_events SET start_time = ?1, end_time = ?2 WHERE id = ?3",
+            rusqlite::params![start_time, end_time, id],
+        )?;
+    }
+
+    let updated = conn.execute(
+        "UPDATE calendar_events SET timezone = ?1 WHERE timezone IS NULL",
+        [timezone],
+    )?;
+    Ok(updated)
+}
+
+/// Convert a naive `YYYY-MM-DD HH:MM:SS` wall-clock time in `tz` to UTC
+///
+/// Times in a DST gap move forward an hour; values that aren't naive (or
+/// can't be parsed) give `None` and are left alone.
+fn local_to_utc(value: &str, tz: &chrono_tz::Tz) -> Option<String> {
+    use chrono::TimeZone;
+
+    let naive = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok()?;
+    let local = tz
+        .from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(naive + chrono::Duration::hours(1))).earliest())?;
+    Some(local.with_timezone(&chrono::Utc).format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
 fn migration_applied(conn: &Connection, name: &str) -> Result<bool, MigrationError> {
     let count: i32 = conn.query_row(
         "SELECT COUNT(*) FROM _migrations WHERE name = ?1",
@@ -360,6 +517,297 @@ CREATE TABLE url_embedding_chunks (
 CREATE INDEX idx_url_embedding_chunks_url_id ON url_embedding_chunks(url_attachment_id);
 "#;
 
+const MIGRATION_016_NOTE_ALIASES: &str = r#"
+-- Alternate titles a note can be linked by (e.g. [[Old Title]] after a rename)
+CREATE TABLE note_aliases (
+    note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    alias TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (note_id, alias)
+);
+
+CREATE INDEX idx_note_aliases_alias ON note_aliases(alias COLLATE NOCASE);
+"#;
+
+const MIGRATION_017_CALENDAR_EVENT_TIMEZONE: &str = r#"
+-- IANA timezone an event was scheduled in; start/end times remain stored as UTC
+ALTER TABLE calendar_events ADD COLUMN timezone TEXT DEFAULT NULL;
+"#;
+
+const MIGRATION_018_GOOGLE_EVENT_PUSH: &str = r#"
+-- Google event id and push state for local events pushed to Google Calendar
+-- sync_state: local (never pushed), synced, modified (changed since last push)
+ALTER TABLE calendar_events ADD COLUMN google_event_id TEXT DEFAULT NULL;
+ALTER TABLE calendar_events ADD COLUMN sync_state TEXT NOT NULL DEFAULT 'local';
+
+-- Pushed events deleted locally, still to be deleted from Google
+CREATE TABLE google_event_deletions (
+    google_event_id TEXT PRIMARY KEY,
+    deleted_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+"#;
+
+const MIGRATION_019_GOOGLE_SYNC_TOKEN: &str = r#"
+-- Incremental sync token from the last full calendar sync, and the range it covers
+ALTER TABLE google_accounts ADD COLUMN sync_token TEXT DEFAULT NULL;
+ALTER TABLE google_accounts ADD COLUMN sync_window_start TEXT DEFAULT NULL;
+ALTER TABLE google_accounts ADD COLUMN sync_window_end TEXT DEFAULT NULL;
+"#;
+
+const MIGRATION_020_MICROSOFT_ACCOUNTS: &str = r#"
+-- Microsoft OAuth account storage
+CREATE TABLE microsoft_accounts (
+    id TEXT PRIMARY KEY DEFAULT 'default',
+    email TEXT NOT NULL,
+    access_token TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    token_expires_at INTEGER,
+    connected_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Rebuild calendar_events so the source CHECK constraint allows 'microsoft'
+CREATE TABLE calendar_events_new (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    description TEXT,
+    start_time DATETIME NOT NULL,
+    end_time DATETIME,
+    all_day BOOLEAN DEFAULT FALSE,
+    recurrence_rule TEXT,
+    source TEXT NOT NULL DEFAULT 'manual' CHECK(source IN ('manual', 'google', 'microsoft')),
+    external_id TEXT,
+    linked_note_id TEXT REFERENCES notes(id) ON DELETE SET NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    event_type TEXT NOT NULL DEFAULT 'default',
+    response_status TEXT DEFAULT NULL,
+    attendees TEXT DEFAULT NULL,
+    meeting_link TEXT DEFAULT NULL,
+    timezone TEXT DEFAULT NULL,
+    google_event_id TEXT DEFAULT NULL,
+    sync_state TEXT NOT NULL DEFAULT 'local'
+);
+
+INSERT INTO calendar_events_new (id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, created_at, updated_at, event_type, response_status, attendees, meeting_link, timezone, google_event_id, sync_state)
+SELECT id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, created_at, updated_at, event_type, response_status, attendees, meeting_link, timezone, google_event_id, sync_state
+FROM calendar_events;
+
+DROP TABLE calendar_events;
+ALTER TABLE calendar_events_new RENAME TO calendar_events;
+
+CREATE INDEX idx_calendar_events_start_time ON calendar_events(start_time);
+CREATE INDEX idx_calendar_events_end_time ON calendar_events(end_time);
+CREATE INDEX idx_calendar_events_source ON calendar_events(source);
+CREATE INDEX idx_calendar_events_external_id ON calendar_events(external_id);
+CREATE INDEX idx_calendar_events_linked_note ON calendar_events(linked_note_id);
+CREATE INDEX idx_calendar_events_date_range ON calendar_events(start_time, end_time);
+"#;
+
+const MIGRATION_021_TAG_DESCRIPTION: &str = r#"
+-- Short description to disambiguate similar tags
+ALTER TABLE tags ADD COLUMN description TEXT DEFAULT NULL;
+"#;
+
+const MIGRATION_022_MESSAGES_FTS: &str = r#"
+-- Full-text index over chat message content, kept in sync with triggers
+CREATE VIRTUAL TABLE messages_fts USING fts5(
+    content,
+    content='messages',
+    content_rowid='rowid'
+);
+
+CREATE TRIGGER messages_fts_insert AFTER INSERT ON messages BEGIN
+    INSERT INTO messages_fts(rowid, content) VALUES (new.rowid, new.content);
+END;
+
+CREATE TRIGGER messages_fts_delete AFTER DELETE ON messages BEGIN
+    INSERT INTO messages_fts(messages_fts, rowid, content) VALUES ('delete', old.rowid, old.content);
+END;
+
+CREATE TRIGGER messages_fts_update AFTER UPDATE OF content ON messages BEGIN
+    INSERT INTO messages_fts(messages_fts, rowid, content) VALUES ('delete', old.rowid, old.content);
+    INSERT INTO messages_fts(rowid, content) VALUES (new.rowid, new.content);
+END;
+
+-- Index messages that existed before this migration
+INSERT INTO messages_fts(messages_fts) VALUES ('rebuild');
+"#;
+
+const MIGRATION_023_MARKDOWN_EXPORTS: &str = r#"
+-- Rebuild exports so the format CHECK constraint allows 'markdown'
+CREATE TABLE exports_new (
+    id TEXT PRIMARY KEY,
+    filename TEXT NOT NULL,
+    title TEXT NOT NULL,
+    format TEXT NOT NULL CHECK(format IN ('pdf', 'docx', 'xlsx', 'pptx', 'markdown')),
+    source_note_ids TEXT,  -- JSON array of note IDs
+    file_size INTEGER,
+    path TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO exports_new (id, filename, title, format, source_note_ids, file_size, path, created_at)
+SELECT id, filename, title, format, source_note_ids, file_size, path, created_at FROM exports;
+
+DROP TABLE exports;
+ALTER TABLE exports_new RENAME TO exports;
+
+CREATE INDEX idx_exports_format ON exports(format);
+CREATE INDEX idx_exports_created_at ON exports(created_at);
+"#;
+
+const MIGRATION_024_CONVERSATION_BRANCHES: &str = r#"
+-- Message a conversation was branched from (NULL for regular conversations)
+ALTER TABLE conversations ADD COLUMN parent_message_id TEXT REFERENCES messages(id) ON DELETE SET NULL;
+"#;
+
+const MIGRATION_025_SYSTEM_PROMPTS: &str = r#"
+-- Named system prompt presets for conversations
+CREATE TABLE system_prompts (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    prompt TEXT NOT NULL,
+    is_default BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- At most one preset can be the default
+CREATE UNIQUE INDEX idx_system_prompts_default ON system_prompts(is_default) WHERE is_default = 1;
+"#;
+
+const MIGRATION_026_HISTORY_SUMMARY: &str = r#"
+-- Cached summary of the oldest messages dropped from long conversations
+ALTER TABLE conversations ADD COLUMN history_summary TEXT;
+-- Number of leading messages the summary covers
+ALTER TABLE conversations ADD COLUMN history_summary_count INTEGER NOT NULL DEFAULT 0;
+"#;
+
+const MIGRATION_027_NOTE_SYNC_STATE: &str = r#"
+-- Note content as of the last disk/database sync, the base for detecting conflicting edits
+CREATE TABLE IF NOT EXISTS note_sync_state (
+    note_id TEXT PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+    content_hash TEXT NOT NULL,
+    base_content TEXT NOT NULL,
+    synced_at TEXT NOT NULL
+);
+"#;
+
+const MIGRATION_028_NOTE_REVISIONS: &str = r#"
+-- Snapshots of a note taken before each update, so edits can be reverted
+CREATE TABLE IF NOT EXISTS note_revisions (
+    id TEXT PRIMARY KEY,
+    note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    content TEXT,
+    content_html TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_note_revisions_note_id ON note_revisions(note_id, created_at);
+"#;
+
+const MIGRATION_029_BOARD_CARD_DUE_DATE: &str = r#"
+-- Optional due date for board cards, used for sorting and deadlines
+ALTER TABLE board_cards ADD COLUMN due_date TEXT;
+"#;
+
+const MIGRATION_030_BOARD_WIP_LIMITS: &str = r#"
+-- Work-in-progress limits: the most cards a lane should hold, and whether moves past it are refused
+ALTER TABLE board_lanes ADD COLUMN wip_limit INTEGER;
+ALTER TABLE boards ADD COLUMN enforce_wip_limits BOOLEAN NOT NULL DEFAULT TRUE;
+"#;
+
+const MIGRATION_031_CONVERSATION_REASONING: &str = r#"
+-- Per-conversation reasoning: effort for OpenAI o-series models, thinking budget for Claude/Gemini
+ALTER TABLE conversations ADD COLUMN reasoning_effort TEXT;
+ALTER TABLE conversations ADD COLUMN thinking_budget INTEGER;
+"#;
+
+const MIGRATION_032_EXPORT_METADATA: &str = r#"
+-- How an export was produced: generation time, 'mechanical' or 'agent', and the agent's model
+ALTER TABLE exports ADD COLUMN duration_ms INTEGER;
+ALTER TABLE exports ADD COLUMN generator TEXT;
+ALTER TABLE exports ADD COLUMN model TEXT;
+"#;
+
+const MIGRATION_033_CALENDAR_EVENT_LOCATION: &str = r#"
+-- Free-text event location (a room, an address, or a video call URL)
+ALTER TABLE calendar_events ADD COLUMN location TEXT DEFAULT NULL;
+"#;
+
+const MIGRATION_034_MESSAGES_FTS_MESSAGE_ID: &str = r#"
+-- Key the chat message index on message id: messages has a TEXT primary key,
+-- so its implicit rowids can be renumbered by VACUUM or a backup restore
+DROP TRIGGER IF EXISTS messages_fts_insert;
+DROP TRIGGER IF EXISTS messages_fts_delete;
+DROP TRIGGER IF EXISTS messages_fts_update;
+DROP TABLE IF EXISTS messages_fts;
+
+CREATE VIRTUAL TABLE messages_fts USING fts5(
+    message_id UNINDEXED,
+    content
+);
+
+CREATE TRIGGER messages_fts_insert AFTER INSERT ON messages BEGIN
+    INSERT INTO messages_fts(message_id, content) VALUES (new.id, new.content);
+END;
+
+CREATE TRIGGER messages_fts_delete AFTER DELETE ON messages BEGIN
+    DELETE FROM messages_fts WHERE message_id = old.id;
+END;
+
+CREATE TRIGGER messages_fts_update AFTER UPDATE OF content ON messages BEGIN
+    UPDATE messages_fts SET content = new.content WHERE message_id = old.id;
+END;
+
+INSERT INTO messages_fts(message_id, content) SELECT id, content FROM messages;
+"#;
+
+const MIGRATION_035_EXPORT_SOURCES: &str = r#"
+-- What an export was built from ('notes', 'conversation' or 'content'), the
+-- conversation it came from, and its render options as JSON, for regenerating
+ALTER TABLE exports ADD COLUMN source TEXT;
+ALTER TABLE exports ADD COLUMN conversation_id TEXT;
+ALTER TABLE exports ADD COLUMN options TEXT;
+"#;
+
+const MIGRATION_036_MESSAGES_FTS_KEYS: &str = r#"
+-- message_id is UNINDEXED in messages_fts, so the triggers' lookups by it scanned
+-- the whole index. Give each message a stable integer key to use as the FTS rowid,
+-- so updates and deletes touch one row.
+DROP TRIGGER IF EXISTS messages_fts_insert;
+DROP TRIGGER IF EXISTS messages_fts_delete;
+DROP TRIGGER IF EXISTS messages_fts_update;
+
+CREATE TABLE messages_fts_keys (
+    id INTEGER PRIMARY KEY,
+    message_id TEXT NOT NULL UNIQUE
+);
+
+CREATE TRIGGER messages_fts_insert AFTER INSERT ON messages BEGIN
+    INSERT INTO messages_fts_keys(message_id) VALUES (new.id);
+    INSERT INTO messages_fts(rowid, message_id, content)
+        VALUES ((SELECT id FROM messages_fts_keys WHERE message_id = new.id), new.id, new.content);
+END;
+
+CREATE TRIGGER messages_fts_delete AFTER DELETE ON messages BEGIN
+    DELETE FROM messages_fts WHERE rowid = (SELECT id FROM messages_fts_keys WHERE message_id = old.id);
+    DELETE FROM messages_fts_keys WHERE message_id = old.id;
+END;
+
+CREATE TRIGGER messages_fts_update AFTER UPDATE OF content ON messages BEGIN
+    DELETE FROM messages_fts WHERE rowid = (SELECT id FROM messages_fts_keys WHERE message_id = old.id);
+    INSERT INTO messages_fts(rowid, message_id, content)
+        VALUES ((SELECT id FROM messages_fts_keys WHERE message_id = old.id), new.id, new.content);
+END;
+
+DELETE FROM messages_fts;
+INSERT INTO messages_fts_keys(message_id) SELECT id FROM messages;
+INSERT INTO messages_fts(rowid, message_id, content)
+    SELECT k.id, m.id, m.content FROM messages m JOIN messages_fts_keys k ON k.message_id = m.id;
+"#;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -396,6 +844,9 @@ mod tests {
         assert!(tables.contains(&"exports".to_string()));
         assert!(tables.contains(&"url_attachments".to_string()));
         assert!(tables.contains(&"url_embeddings".to_string()));
+        assert!(tables.contains(&"note_aliases".to_string()));
+        assert!(tables.contains(&"google_event_deletions".to_string()));
+        assert!(tables.contains(&"note_revisions".to_string()));
     }
 
     #[test]
@@ -407,4 +858,89 @@ mod tests {
         run_migrations(&conn).unwrap();
         run_migrations(&conn).unwrap();
     }
+
+    #[test]
+    fn test_schema_report_on_fresh_db() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        let version = get_schema_version(&conn).unwrap();
+        assert_eq!(version, SchemaVersion { current: 0, latest: MIGRATIONS.len() });
+        let pending = list_pending_migrations(&conn).unwrap();
+        assert_eq!(pending.len(), MIGRATIONS.len());
+        assert_eq!(pending[0], "001_initial_schema");
+
+        run_migrations(&conn).unwrap();
+        let version = get_schema_version(&conn).unwrap();
+        assert_eq!(version.current, version.latest);
+        assert!(list_pending_migrations(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_schema_report_on_partially_migrated_db() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE _migrations (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            );",
+        )
+        .unwrap();
+        for &(name, sql) in &MIGRATIONS[..3] {
+            conn.execute_batch(sql).unwrap();
+            mark_migration_applied(&conn, name).unwrap();
+        }
+
+        assert_eq!(get_schema_version(&conn).unwrap().current, 3);
+        let pending = list_pending_migrations(&conn).unwrap();
+        let expected: Vec<&str> = MIGRATIONS[3..].iter().map(|&(name, _)| name).collect();
+        assert_eq!(pending, expected);
+
+        // Listing doesn't apply anything
+        assert_eq!(get_schema_version(&conn).unwrap().current, 3);
+        run_migrations(&conn).unwrap();
+        assert!(list_pending_migrations(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_backfill_event_timezones() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        conn.execute_batch(
+            "INSERT INTO calendar_events (id, title, start_time, end_time, source) VALUES ('winter', 'Winter', '2024-01-15 09:00:00', '2024-01-15 10:00:00', 'manual');
+             INSERT INTO calendar_events (id, title, start_time, source) VALUES ('summer', 'Summer', '2024-07-01 09:00:00', 'manual');
+             INSERT INTO calendar_events (id, title, start_time, all_day, source) VALUES ('holiday', 'Holiday', '2024-01-15 00:00:00', 1, 'manual');
+             INSERT INTO calendar_events (id, title, start_time, source) VALUES ('synced', 'Synced', '2024-01-15 14:00:00', 'google');
+             INSERT INTO calendar_events (id, title, start_time, source, timezone) VALUES ('zoned', 'Zoned', '2024-01-15 09:00:00', 'manual', 'Asia/Tokyo');",
+        )
+        .unwrap();
+
+        assert_eq!(backfill_event_timezones(&conn, "America/New_York").unwrap(), 4);
+        let events: Vec<(String, String, Option<String>, String)> = conn
+            .prepare("SELECT id, start_time, end_time, timezone FROM calendar_events ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap()
+            .filter_map(Result::ok)
+            .collect();
+        let event = |id: &str| events.iter().find(|e| e.0 == id).unwrap().clone();
+
+        // Wall-clock times entered in New York move to UTC, across DST
+        assert_eq!(
+            event("winter"),
+            (
+                "winter".to_string(),
+                "2024-01-15 14:00:00".to_string(),
+                Some("2024-01-15 15:00:00".to_string()),
+                "America/New_York".to_string()
+            )
+        );
+        assert_eq!(event("summer").1, "2024-07-01 13:00:00");
+        // All-day, synced and already zoned events keep their times
+        assert_eq!(event("holiday").1, "2024-01-15 00:00:00");
+        assert_eq!(event("synced").1, "2024-01-15 14:00:00");
+        assert_eq!(event("synced").3, "America/New_York");
+        assert_eq!((event("zoned").1, event("zoned").3), ("2024-01-15 09:00:00".to_string(), "Asia/Tokyo".to_string()));
+    }
 }
@@ -7,8 +7,12 @@ pub mod exports;
 pub mod folders;
 pub mod links;
 pub mod migrations;
+pub mod note_revisions;
+pub mod note_sync_state;
 pub mod notes;
+pub mod recurrence;
 pub mod settings;
+pub mod system_prompts;
 pub mod tags;
 pub mod url_attachments;
 
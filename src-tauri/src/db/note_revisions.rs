@@ -0,0 +1,128 @@
+//! Database operations for note revisions
+//!
+//! Before a note is updated its current title and content are saved as a
+//! revision, so an edit (by the user or an AI tool) can be undone later. Editor
+//! saves within a few minutes of the last revision are coalesced into it, so
+//! autosave doesn't flood the history; other edits are always snapshotted.
+//! Only the most recent revisions of each note are kept.
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{Note, NoteRevision};
+
+/// Revisions kept per note; older ones are pruned as new ones are recorded
+pub const MAX_REVISIONS_PER_NOTE: usize = 50;
+
+/// Minimum minutes between revisions recorded by ordinary saves
+pub const REVISION_INTERVAL_MINUTES: i64 = 5;
+
+/// Format revision timestamps are stored in; sub-second precision keeps
+/// revisions from rapid saves in order
+const REVISION_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.6f";
+
+/// Notes larger than this (content plus HTML, in bytes) are not snapshotted
+pub const MAX_REVISION_SIZE: usize = 1024 * 1024;
+
+#[derive(Error, Debug)]
+pub enum NoteRevisionDbError {
+    #[error("SQLite error: {0}")]
+    SqliteError(#[from] rusqlite::Error),
+}
+
+fn parse_datetime(s: &str) -> DateTime<Utc> {
+    chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+        .map(|naive| Utc.from_utc_datetime(&naive))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+fn row_to_revision(row: &Row) -> Result<NoteRevision, rusqlite::Error> {
+    Ok(NoteRevision {
+        id: row.get(0)?,
+        note_id: row.get(1)?,
+        title: row.get(2)?,
+        content: row.get(3)?,
+        content_html: row.get(4)?,
+        created_at: parse_datetime(&row.get::<_, String>(5)?),
+    })
+}
+
+/// Save `note` as it is now as a revision, pruning the oldest beyond the cap
+///
+/// Returns false if the note was too large to snapshot.
+pub fn record_revision(conn: &Connection, note: &Note) -> Result<bool, NoteRevisionDbError> {
+    let size = note.content.as_ref().map_or(0, String::len) + note.content_html.as_ref().map_or(0, String::len);
+    if size > MAX_REVISION_SIZE {
+        log::warn!("Note {} is too large ({} bytes) to keep a revision of", note.id, size);
+        return Ok(false);
+    }
+
+    let now = Utc::now().format(REVISION_TIME_FORMAT).to_string();
+    conn.execute(
+        "INSERT INTO note_revisions (id, note_id, title, content, content_html, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            Uuid::new_v4().to_string(),
+            note.id,
+            note.title,
+            note.content,
+            note.content_html,
+            now,
+        ],
+    )?;
+
+    conn.execute(
+        "DELETE FROM note_revisions WHERE note_id = ?1 AND id NOT IN (
+            SELECT id FROM note_revisions WHERE note_id = ?1
+            ORDER BY created_at DESC LIMIT ?2
+         )",
+        params![note.id, MAX_REVISIONS_PER_NOTE as i64],
+    )?;
+    Ok(true)
+}
+
+/// Save `note` as a revision unless one was recorded in the last
+/// `REVISION_INTERVAL_MINUTES`
+///
+/// Returns false if no revision was recorded.
+pub fn record_revision_if_due(conn: &Connection, note: &Note) -> Result<bool, NoteRevisionDbError> {
+    let cutoff = (Utc::now() - chrono::Duration::minutes(REVISION_INTERVAL_MINUTES))
+        .format(REVISION_TIME_FORMAT)
+        .to_string();
+    let recent: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM note_revisions WHERE note_id = ?1 AND created_at > ?2)",
+        params![note.id, cutoff],
+        |row| row.get(0),
+    )?;
+    if recent {
+        return Ok(false);
+    }
+    record_revision(conn, note)
+}
+
+/// List a note's revisions, newest first
+pub fn list_note_revisions(conn: &Connection, note_id: &str) -> Result<Vec<NoteRevision>, NoteRevisionDbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, note_id, title, content, content_html, created_at
+         FROM note_revisions WHERE note_id = ?1 ORDER BY created_at DESC",
+    )?;
+    let revisions = stmt
+        .query_map([note_id], row_to_revision)?
+        .collect::<Result<_, _>>()?;
+    Ok(revisions)
+}
+
+/// Get a revision by ID
+pub fn get_revision(conn: &Connection, id: &str) -> Result<Option<NoteRevision>, NoteRevisionDbError> {
+    let revision = conn
+        .query_row(
+            "SELECT id, note_id, title, content, content_html, created_at
+             FROM note_revisions WHERE id = ?1",
+            [id],
+            row_to_revision,
+        )
+        .optional()?;
+    Ok(revision)
+}
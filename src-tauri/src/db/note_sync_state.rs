@@ -0,0 +1,84 @@
+//! Database operations for note sync state
+//!
+//! Records each note's content as of its last sync between the vault files
+//! and the database. Comparing both sides against this base tells an edit on
+//! one side apart from conflicting edits on both.
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum NoteSyncStateDbError {
+    #[error("SQLite error: {0}")]
+    SqliteError(#[from] rusqlite::Error),
+}
+
+/// A note's content as of its last sync
+#[derive(Debug, Clone)]
+pub struct NoteSyncState {
+    pub content_hash: String,
+    pub base_content: String,
+}
+
+/// Hash note content for comparison, ignoring surrounding whitespace
+pub fn content_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.trim().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Get the last synced state of a note
+pub fn get_sync_state(conn: &Connection, note_id: &str) -> Result<Option<NoteSyncState>, NoteSyncStateDbError> {
+    let state = conn
+        .query_row(
+            "SELECT content_hash, base_content FROM note_sync_state WHERE note_id = ?1",
+            [note_id],
+            |row| {
+                Ok(NoteSyncState {
+                    content_hash: row.get(0)?,
+                    base_content: row.get(1)?,
+                })
+            },
+        )
+        .optional()?;
+    Ok(state)
+}
+
+/// Record that a note's file and database content now match `content`
+pub fn record_sync_state(conn: &Connection, note_id: &str, content: &str) -> Result<(), NoteSyncStateDbError> {
+    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    conn.execute(
+        "INSERT INTO note_sync_state (note_id, content_hash, base_content, synced_at)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(note_id) DO UPDATE SET
+            content_hash = excluded.content_hash,
+            base_content = excluded.base_content,
+            synced_at = excluded.synced_at",
+        params![note_id, content_hash(content), content.trim(), now],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+
+    #[test]
+    fn test_record_and_get_sync_state() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        conn.execute("INSERT INTO notes (id, title) VALUES ('n1', 'Note')", []).unwrap();
+
+        assert!(get_sync_state(&conn, "n1").unwrap().is_none());
+
+        record_sync_state(&conn, "n1", "first\n").unwrap();
+        record_sync_state(&conn, "n1", "second").unwrap();
+        let state = get_sync_state(&conn, "n1").unwrap().unwrap();
+        assert_eq!(state.base_content, "second");
+        assert_eq!(state.content_hash, content_hash("  second \n"));
+        assert_ne!(state.content_hash, content_hash("first"));
+    }
+}
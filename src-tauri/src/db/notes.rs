@@ -3,7 +3,8 @@ use rusqlite::{params, Connection, OptionalExtension, Row};
 use thiserror::Error;
 use uuid::Uuid;
 
-use crate::models::{CreateNoteInput, Note, UpdateNoteInput};
+use crate::db::note_revisions;
+use crate::models::{CreateNoteInput, Note, NotePage, NoteSort, UpdateNoteInput};
 
 #[derive(Error, Debug)]
 pub enum NoteDbError {
@@ -11,6 +12,10 @@ pub enum NoteDbError {
     SqliteError(#[from] rusqlite::Error),
     #[error("Note not found: {0}")]
     NotFound(String),
+    #[error("Revision not found: {0}")]
+    RevisionNotFound(String),
+    #[error("Revision error: {0}")]
+    RevisionError(#[from] note_revisions::NoteRevisionDbError),
 }
 
 /// Parse a datetime string from SQLite into a DateTime<Utc>
@@ -28,7 +33,7 @@ fn parse_datetime(s: &str) -> DateTime<Utc> {
 }
 
 /// Map a database row to a Note struct
-fn row_to_note(row: &Row) -> Result<Note, rusqlite::Error> {
+pub(crate) fn row_to_note(row: &Row) -> Result<Note, rusqlite::Error> {
     let created_at_str: String = row.get(5)?;
     let updated_at_str: String = row.get(6)?;
 
@@ -92,6 +97,66 @@ pub fn get_all_notes(conn: &Connection) -> Result<Vec<Note>, NoteDbError> {
     Ok(notes)
 }
 
+/// Get one page of non-deleted notes in the given order, plus the total count
+pub fn get_notes_paginated(
+    conn: &Connection,
+    limit: usize,
+    offset: usize,
+    sort_by: NoteSort,
+) -> Result<NotePage, NoteDbError> {
+    // The id tie-breaker keeps pages stable when sort keys are equal
+    let order_by = match sort_by {
+        NoteSort::Updated => "updated_at DESC, id",
+        NoteSort::Created => "created_at DESC, id",
+        NoteSort::Title => "title COLLATE NOCASE, id",
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, title, content, content_html, folder_id, created_at, updated_at, is_deleted
+         FROM notes WHERE is_deleted = FALSE ORDER BY {} LIMIT ?1 OFFSET ?2",
+        order_by
+    ))?;
+
+    let notes = stmt
+        .query_map(params![limit as i64, offset as i64], row_to_note)?
+        .collect::<Result<Vec<_>, _>>()?;
+    let total: i64 = conn.query_row("SELECT COUNT(*) FROM notes WHERE is_deleted = FALSE", [], |row| row.get(0))?;
+
+    Ok(NotePage {
+        notes,
+        total: total as usize,
+    })
+}
+
+/// Get up to `limit` notes following the note `after_id` in (created_at, id) order
+///
+/// Keyset paging for walking every note: unlike an offset, the position doesn't
+/// shift when notes are added or deleted between pages. Pass `None` for the
+/// first page and the last note's ID for each page after.
+pub fn get_notes_after(conn: &Connection, after_id: Option<&str>, limit: usize) -> Result<Vec<Note>, NoteDbError> {
+    let Some(after_id) = after_id else {
+        let mut stmt = conn.prepare(
+            "SELECT id, title, content, content_html, folder_id, created_at, updated_at, is_deleted
+             FROM notes WHERE is_deleted = FALSE ORDER BY created_at, id LIMIT ?1",
+        )?;
+        let notes = stmt.query_map([limit as i64], row_to_note)?.collect::<Result<Vec<_>, _>>()?;
+        return Ok(notes);
+    };
+
+    let cursor: Option<String> = conn
+        .query_row("SELECT created_at FROM notes WHERE id = ?1", [after_id], |row| row.get(0))
+        .optional()?;
+    let cursor = cursor.ok_or_else(|| NoteDbError::NotFound(after_id.to_string()))?;
+    let mut stmt = conn.prepare(
+        "SELECT id, title, content, content_html, folder_id, created_at, updated_at, is_deleted
+         FROM notes WHERE is_deleted = FALSE AND (created_at, id) > (?1, ?2)
+         ORDER BY created_at, id LIMIT ?3",
+    )?;
+    let notes = stmt
+        .query_map(params![cursor, after_id, limit as i64], row_to_note)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(notes)
+}
+
 /// Get all notes in a specific folder
 pub fn get_notes_in_folder(
     conn: &Connection,
@@ -127,15 +192,48 @@ pub fn update_note(
     conn: &Connection,
     id: &str,
     input: UpdateNoteInput,
+) -> Result<Note, NoteDbError> {
+    apply_note_update(conn, id, input, true)
+}
+
+/// Update a note, always keeping the version being replaced
+///
+/// For edits that don't come from the editor (AI tools, syncing changed files,
+/// bulk rewrites), which shouldn't be folded into a revision an autosave took
+/// moments ago.
+pub fn update_note_with_revision(
+    conn: &Connection,
+    id: &str,
+    input: UpdateNoteInput,
+) -> Result<Note, NoteDbError> {
+    apply_note_update(conn, id, input, false)
+}
+
+/// Update a note, coalescing its revision with a recent one if `coalesce`
+fn apply_note_update(
+    conn: &Connection,
+    id: &str,
+    input: UpdateNoteInput,
+    coalesce: bool,
 ) -> Result<Note, NoteDbError> {
     // First check if the note exists
     let existing = get_note(conn, id)?.ok_or_else(|| NoteDbError::NotFound(id.to_string()))?;
 
     let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-    let title = input.title.unwrap_or(existing.title);
-    let content = input.content.or(existing.content);
-    let content_html = input.content_html.or(existing.content_html);
-    let folder_id = input.folder_id.or(existing.folder_id);
+    let title = input.title.unwrap_or_else(|| existing.title.clone());
+    let content = input.content.or_else(|| existing.content.clone());
+    let content_html = input.content_html.or_else(|| existing.content_html.clone());
+    let folder_id = input.folder_id.or_else(|| existing.folder_id.clone());
+
+    // Keep the version being replaced, unless only the folder changed or
+    // (for editor saves) a revision was taken moments ago
+    if title != existing.title || content != existing.content || content_html != existing.content_html {
+        if coalesce {
+            note_revisions::record_revision_if_due(conn, &existing)?;
+        } else {
+            note_revisions::record_revision(conn, &existing)?;
+        }
+    }
 
     conn.execute(
         "UPDATE notes SET title = ?1, content = ?2, content_html = ?3, folder_id = ?4, updated_at = ?5
@@ -146,6 +244,41 @@ pub fn update_note(
     get_note(conn, id)?.ok_or(NoteDbError::NotFound(id.to_string()))
 }
 
+/// Restore a note's title and content from one of its revisions
+///
+/// The version being replaced is always kept as a revision, so the revert
+/// can be undone.
+pub fn revert_note_to_revision(conn: &Connection, note_id: &str, revision_id: &str) -> Result<Note, NoteDbError> {
+    let revision = note_revisions::get_revision(conn, revision_id)?
+        .filter(|r| r.note_id == note_id)
+        .ok_or_else(|| NoteDbError::RevisionNotFound(revision_id.to_string()))?;
+
+    update_note_with_revision(
+        conn,
+        note_id,
+        UpdateNoteInput {
+            title: Some(revision.title),
+            // An empty string rather than None, which would keep the current content
+            content: Some(revision.content.unwrap_or_default()),
+            content_html: Some(revision.content_html.unwrap_or_default()),
+            folder_id: None,
+        },
+    )
+}
+
+/// Move a note into a folder, or out of all folders when `folder_id` is None
+pub fn move_note_to_folder(conn: &Connection, id: &str, folder_id: Option<&str>) -> Result<Note, NoteDbError> {
+    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let rows_affected = conn.execute(
+        "UPDATE notes SET folder_id = ?1, updated_at = ?2 WHERE id = ?3",
+        params![folder_id, now, id],
+    )?;
+    if rows_affected == 0 {
+        return Err(NoteDbError::NotFound(id.to_string()));
+    }
+    get_note(conn, id)?.ok_or_else(|| NoteDbError::NotFound(id.to_string()))
+}
+
 /// Soft delete a note (sets is_deleted to true)
 pub fn delete_note(conn: &Connection, id: &str) -> Result<bool, NoteDbError> {
     let rows_affected = conn.execute(
@@ -156,6 +289,25 @@ pub fn delete_note(conn: &Connection, id: &str) -> Result<bool, NoteDbError> {
     Ok(rows_affected > 0)
 }
 
+/// Soft-delete every note in the given folders, returning the IDs deleted
+pub fn delete_notes_in_folders(conn: &Connection, folder_ids: &[String]) -> Result<Vec<String>, NoteDbError> {
+    let mut deleted = Vec::new();
+    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let mut select = conn.prepare("SELECT id FROM notes WHERE folder_id = ?1 AND is_deleted = FALSE")?;
+    for folder_id in folder_ids {
+        let ids: Vec<String> = select
+            .query_map([folder_id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        for id in ids {
+            conn.execute(
+                "UPDATE notes SET is_deleted = TRUE, updated_at = ?1 WHERE id = ?2",
+                params![now, id],
+            )?;
+            deleted.push(id);
+        }
+    }
+    Ok(deleted)
+}
 
 #[cfg(test)]
 mod tests {
@@ -232,4 +384,339 @@ mod tests {
         let all = get_all_notes(&conn).unwrap();
         assert!(all.iter().all(|n| n.id != note.id));
     }
+
+    fn create_titled_note(conn: &Connection, title: &str, created_at: &str, updated_at: &str) -> Note {
+        let note = create_note(
+            conn,
+            CreateNoteInput {
+                title: title.to_string(),
+                content: None,
+                content_html: None,
+                folder_id: None,
+            },
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE notes SET created_at = ?1, updated_at = ?2 WHERE id = ?3",
+            params![created_at, updated_at, note.id],
+        )
+        .unwrap();
+        note
+    }
+
+    #[test]
+    fn test_get_notes_paginated_ordering() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        create_titled_note(&conn, "banana", "2024-01-02 00:00:00", "2024-03-01 00:00:00");
+        create_titled_note(&conn, "Apple", "2024-01-03 00:00:00", "2024-02-01 00:00:00");
+        create_titled_note(&conn, "cherry", "2024-01-01 00:00:00", "2024-04-01 00:00:00");
+        let deleted = create_titled_note(&conn, "aardvark", "2024-01-04 00:00:00", "2024-05-01 00:00:00");
+        delete_note(&conn, &deleted.id).unwrap();
+
+        let titles = |sort: NoteSort| -> Vec<String> {
+            get_notes_paginated(&conn, 10, 0, sort)
+                .unwrap()
+                .notes
+                .into_iter()
+                .map(|n| n.title)
+                .collect()
+        };
+        // Deleted notes never appear
+        assert_eq!(titles(NoteSort::Updated), vec!["cherry", "banana", "Apple"]);
+        assert_eq!(titles(NoteSort::Created), vec!["Apple", "banana", "cherry"]);
+        // Title order ignores case
+        assert_eq!(titles(NoteSort::Title), vec!["Apple", "banana", "cherry"]);
+    }
+
+    #[test]
+    fn test_get_notes_after_walks_every_note_once() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        // Shared timestamps fall back to the id for order
+        for i in 0..5 {
+            create_titled_note(&conn, &format!("Note {}", i), "2024-01-01 00:00:00", "2024-01-01 00:00:00");
+        }
+        create_titled_note(&conn, "Oldest", "2023-01-01 00:00:00", "2023-01-01 00:00:00");
+
+        let first = get_notes_after(&conn, None, 2).unwrap();
+        assert_eq!(first[0].title, "Oldest");
+
+        // A note added mid-walk doesn't shift the pages
+        create_titled_note(&conn, "Older", "2022-01-01 00:00:00", "2022-01-01 00:00:00");
+        let mut seen: Vec<String> = first.iter().map(|n| n.id.clone()).collect();
+        loop {
+            let page = get_notes_after(&conn, seen.last().map(String::as_str), 2).unwrap();
+            if page.is_empty() {
+                break;
+            }
+            seen.extend(page.into_iter().map(|n| n.id));
+        }
+        assert_eq!(seen.len(), 6);
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 6);
+
+        assert!(matches!(get_notes_after(&conn, Some("missing"), 2), Err(NoteDbError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_get_notes_paginated_offsets() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        for i in 0..5 {
+            create_titled_note(&conn, &format!("Note {}", i), "2024-01-01 00:00:00", "2024-01-01 00:00:00");
+        }
+
+        let first = get_notes_paginated(&conn, 2, 0, NoteSort::Title).unwrap();
+        let second = get_notes_paginated(&conn, 2, 2, NoteSort::Title).unwrap();
+        let last = get_notes_paginated(&conn, 2, 4, NoteSort::Title).unwrap();
+        let past_end = get_notes_paginated(&conn, 2, 6, NoteSort::Title).unwrap();
+
+        assert_eq!(first.total, 5);
+        assert_eq!(past_end.total, 5);
+        let pages: Vec<Vec<String>> = [first, second, last, past_end]
+            .into_iter()
+            .map(|page| page.notes.into_iter().map(|n| n.title).collect())
+            .collect();
+        assert_eq!(
+            pages,
+            vec![
+                vec!["Note 0", "Note 1"],
+                vec!["Note 2", "Note 3"],
+                vec!["Note 4"],
+                vec![],
+            ]
+        );
+
+        // Identical timestamps still page without repeats or gaps
+        let mut ids: Vec<String> = (0..3)
+            .flat_map(|page| get_notes_paginated(&conn, 2, page * 2, NoteSort::Updated).unwrap().notes)
+            .map(|n| n.id)
+            .collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 5);
+    }
+
+    #[test]
+    fn test_delete_notes_in_folders_only_touches_scope() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let folder = |name: &str| {
+            crate::db::folders::create_folder(
+                &conn,
+                crate::models::CreateFolderInput {
+                    name: name.to_string(),
+                    parent_id: None,
+                },
+            )
+            .unwrap()
+        };
+        let inside = folder("Inside");
+        let outside = folder("Outside");
+        let create = |title: &str, folder_id: Option<&str>| {
+            create_note(
+                &conn,
+                CreateNoteInput {
+                    title: title.to_string(),
+                    content: None,
+                    content_html: None,
+                    folder_id: folder_id.map(String::from),
+                },
+            )
+            .unwrap()
+            .id
+        };
+        let target = create("Target", Some(&inside.id));
+        let other = create("Other", Some(&outside.id));
+        let root = create("Root", None);
+
+        let deleted = delete_notes_in_folders(&conn, std::slice::from_ref(&inside.id)).unwrap();
+        assert_eq!(deleted, vec![target.clone()]);
+
+        // Soft-deleted, so the note can still be restored
+        assert!(get_note(&conn, &target).unwrap().unwrap().is_deleted);
+        assert!(!get_note(&conn, &other).unwrap().unwrap().is_deleted);
+        assert!(!get_note(&conn, &root).unwrap().unwrap().is_deleted);
+    }
+
+    /// Move every revision back an hour, as if the edits were spread out
+    fn age_revisions(conn: &Connection) {
+        conn.execute(
+            "UPDATE note_revisions SET created_at = strftime('%Y-%m-%d %H:%M:%f', created_at, '-1 hour')",
+            [],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_update_records_revisions_and_revert_restores() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let note = create_note(
+            &conn,
+            CreateNoteInput {
+                title: "Draft".to_string(),
+                content: Some("First version".to_string()),
+                content_html: None,
+                folder_id: None,
+            },
+        )
+        .unwrap();
+        let edit = |title: &str, content: &str| {
+            update_note(
+                &conn,
+                &note.id,
+                UpdateNoteInput {
+                    title: Some(title.to_string()),
+                    content: Some(content.to_string()),
+                    content_html: None,
+                    folder_id: None,
+                },
+            )
+            .unwrap()
+        };
+        edit("Draft", "Second version");
+        age_revisions(&conn);
+        edit("Final", "Third version");
+        age_revisions(&conn);
+        // Saving unchanged content doesn't add a revision
+        edit("Final", "Third version");
+
+        let revisions = note_revisions::list_note_revisions(&conn, &note.id).unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].content.as_deref(), Some("Second version"));
+        assert_eq!(revisions[1].content.as_deref(), Some("First version"));
+
+        let reverted = revert_note_to_revision(&conn, &note.id, &revisions[1].id).unwrap();
+        assert_eq!(reverted.title, "Draft");
+        assert_eq!(reverted.content.as_deref(), Some("First version"));
+
+        // The reverted-over version is kept, so the revert can be undone
+        let revisions = note_revisions::list_note_revisions(&conn, &note.id).unwrap();
+        assert_eq!(revisions.len(), 3);
+        assert_eq!(revisions[0].content.as_deref(), Some("Third version"));
+
+        // A revision of another note is rejected
+        assert!(matches!(
+            revert_note_to_revision(&conn, "other", &revisions[0].id),
+            Err(NoteDbError::RevisionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_revisions_are_capped() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let note = create_note(
+            &conn,
+            CreateNoteInput {
+                title: "Busy".to_string(),
+                content: None,
+                content_html: None,
+                folder_id: None,
+            },
+        )
+        .unwrap();
+        for i in 0..note_revisions::MAX_REVISIONS_PER_NOTE + 5 {
+            age_revisions(&conn);
+            update_note(
+                &conn,
+                &note.id,
+                UpdateNoteInput {
+                    title: None,
+                    content: Some(format!("Version {}", i)),
+                    content_html: None,
+                    folder_id: None,
+                },
+            )
+            .unwrap();
+        }
+
+        let revisions = note_revisions::list_note_revisions(&conn, &note.id).unwrap();
+        assert_eq!(revisions.len(), note_revisions::MAX_REVISIONS_PER_NOTE);
+        // The newest are kept; the latest is the version before the final update
+        let last = note_revisions::MAX_REVISIONS_PER_NOTE + 3;
+        assert_eq!(revisions[0].content, Some(format!("Version {}", last)));
+    }
+
+    #[test]
+    fn test_rapid_saves_share_one_revision() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let note = create_note(
+            &conn,
+            CreateNoteInput {
+                title: "Typing".to_string(),
+                content: Some("Before".to_string()),
+                content_html: None,
+                folder_id: None,
+            },
+        )
+        .unwrap();
+        let save = |content: &str| {
+            update_note(
+                &conn,
+                &note.id,
+                UpdateNoteInput {
+                    title: None,
+                    content: Some(content.to_string()),
+                    content_html: None,
+                    folder_id: None,
+                },
+            )
+            .unwrap()
+        };
+
+        // Autosaves while typing keep only the version from before the burst
+        for content in ["B", "Be", "Bee", "Beet"] {
+            save(content);
+        }
+        let revisions = note_revisions::list_note_revisions(&conn, &note.id).unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].content.as_deref(), Some("Before"));
+
+        // Once the interval has passed, the next save is snapshotted again
+        age_revisions(&conn);
+        save("Beetroot");
+        let revisions = note_revisions::list_note_revisions(&conn, &note.id).unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].content.as_deref(), Some("Beet"));
+    }
+
+    #[test]
+    fn test_non_editor_edits_always_keep_a_revision() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let note = create_note(
+            &conn,
+            CreateNoteInput {
+                title: "Plan".to_string(),
+                content: Some("Typed".to_string()),
+                content_html: None,
+                folder_id: None,
+            },
+        )
+        .unwrap();
+        let input = |content: &str| UpdateNoteInput {
+            title: None,
+            content: Some(content.to_string()),
+            content_html: None,
+            folder_id: None,
+        };
+
+        // An AI edit right after an autosave still snapshots the autosaved text
+        update_note(&conn, &note.id, input("Typed more")).unwrap();
+        update_note_with_revision(&conn, &note.id, input("Rewritten by AI")).unwrap();
+        update_note_with_revision(&conn, &note.id, input("Rewritten again")).unwrap();
+
+        let revisions = note_revisions::list_note_revisions(&conn, &note.id).unwrap();
+        let contents: Vec<_> = revisions.iter().map(|r| r.content.as_deref().unwrap()).collect();
+        assert_eq!(contents, vec!["Rewritten by AI", "Typed more", "Typed"]);
+    }
 }
@@ -0,0 +1,332 @@
+//! RFC 5545-style recurrence rules for calendar events.
+//!
+//! Supports the subset of RRULE that the calendar UI can produce: DAILY, WEEKLY
+//! (optionally with BYDAY) and MONTHLY frequencies, with INTERVAL, COUNT and UNTIL.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use thiserror::Error;
+
+/// Hard cap on candidates generated from the start of the expansion, so a
+/// malformed rule can never loop forever
+const MAX_ITERATIONS: usize = 10_000;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RecurrenceError {
+    #[error("Invalid recurrence rule: {0}")]
+    Invalid(String),
+    #[error("Unsupported recurrence frequency: {0}")]
+    UnsupportedFrequency(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// A parsed recurrence rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<DateTime<Utc>>,
+    /// Weekdays for WEEKLY rules; empty means "the weekday of the first occurrence"
+    pub by_day: Vec<Weekday>,
+}
+
+impl RecurrenceRule {
+    /// Parse a rule such as `RRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10`.
+    /// The `RRULE:` prefix is optional.
+    pub fn parse(rule: &str) -> Result<Self, RecurrenceError> {
+        let body = rule.trim();
+        let body = body.strip_prefix("RRULE:").unwrap_or(body);
+
+        let mut freq = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+        let mut by_day = Vec::new();
+
+        for part in body.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| RecurrenceError::Invalid(part.to_string()))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        other => return Err(RecurrenceError::UnsupportedFrequency(other.to_string())),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|i| *i > 0)
+                        .ok_or_else(|| RecurrenceError::Invalid(part.to_string()))?;
+                }
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .parse::<u32>()
+                            .map_err(|_| RecurrenceError::Invalid(part.to_string()))?,
+                    );
+                }
+                "UNTIL" => {
+                    until = Some(
+                        parse_until(value).ok_or_else(|| RecurrenceError::Invalid(part.to_string()))?,
+                    );
+                }
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(|d| parse_weekday(d).ok_or_else(|| RecurrenceError::Invalid(part.to_string())))
+                        .collect::<Result<Vec<_>, _>>()?;
+                }
+                // WKST and other parts are accepted but ignored
+                _ => {}
+            }
+        }
+
+        let freq = freq.ok_or_else(|| RecurrenceError::Invalid("missing FREQ".to_string()))?;
+        by_day.sort_by_key(|d| d.num_days_from_monday());
+        by_day.dedup();
+
+        Ok(Self {
+            freq,
+            interval,
+            count,
+            until,
+            by_day,
+        })
+    }
+
+    /// Expand the rule into occurrence start times that fall within
+    /// `[window_start, window_end)`. COUNT is applied from `dtstart`, so
+    /// occurrences before the window still use up the count. Rules without a
+    /// COUNT skip straight to the window, so old series still expand.
+    ///
+    /// Occurrences repeat on the wall-clock time of `dtstart` in `tz`, so a 9:00 meeting
+    /// stays at 9:00 local time across daylight saving changes. Wall-clock times that
+    /// don't exist in `tz` (skipped by a DST jump) are left out.
+    pub fn occurrences_between<Z: TimeZone>(
+        &self,
+        tz: &Z,
+        dtstart: DateTime<Utc>,
+        window_start: DateTime<Utc>,
+        window_end: DateTime<Utc>,
+    ) -> Vec<DateTime<Utc>> {
+        let mut occurrences = Vec::new();
+        let mut emitted = 0u32;
+        let local_start = dtstart.with_timezone(tz).naive_local();
+
+        // COUNT rules have to count every occurrence from dtstart, but can't run
+        // past their count. Other rules start a day ahead of the window, which is
+        // more than any UTC offset change.
+        let (skip_to, max_candidates) = match self.count {
+            Some(count) => (None, MAX_ITERATIONS + count as usize),
+            None => (
+                Some(window_start.with_timezone(tz).naive_local() - Duration::days(1)),
+                MAX_ITERATIONS,
+            ),
+        };
+
+        for local in self.candidates(local_start, skip_to).take(max_candidates) {
+            let Some(candidate) = tz.from_local_datetime(&local).earliest().map(|dt| dt.with_timezone(&Utc)) else {
+                continue;
+            };
+            if candidate >= window_end || self.until.is_some_and(|until| candidate > until) {
+                break;
+            }
+            if self.count.is_some_and(|count| emitted >= count) {
+                break;
+            }
+            emitted += 1;
+            if candidate >= window_start {
+                occurrences.push(candidate);
+            }
+        }
+
+        occurrences
+    }
+
+    /// Generate candidate wall-clock start times in ascending order, beginning with `dtstart`
+    ///
+    /// With `skip_to`, whole periods ending before it are skipped, so the first
+    /// candidate is in the period containing `skip_to` (or `dtstart`, if later).
+    fn candidates(
+        &self,
+        dtstart: NaiveDateTime,
+        skip_to: Option<NaiveDateTime>,
+    ) -> Box<dyn Iterator<Item = NaiveDateTime> + '_> {
+        let interval = self.interval as i64;
+        // Number of whole intervals between two period indices, never negative
+        let first_period = |periods_between: i64| periods_between.max(0) / interval;
+        match self.freq {
+            Frequency::Daily => {
+                let first = skip_to.map_or(0, |to| first_period((to.date() - dtstart.date()).num_days()));
+                Box::new((first..).map(move |n| dtstart + Duration::days(n * interval)))
+            }
+            Frequency::Weekly => {
+                let days = if self.by_day.is_empty() {
+                    vec![dtstart.weekday()]
+                } else {
+                    self.by_day.clone()
+                };
+                let week_start = dtstart - Duration::days(dtstart.weekday().num_days_from_monday() as i64);
+                let first = skip_to.map_or(0, |to| first_period((to - week_start).num_days().div_euclid(7)));
+                Box::new(
+                    (first..)
+                        .flat_map(move |n| {
+                            let week = week_start + Duration::weeks(n * interval);
+                            days.clone()
+                                .into_iter()
+                                .map(move |d| week + Duration::days(d.num_days_from_monday() as i64))
+                        })
+                        .filter(move |c| *c >= dtstart),
+                )
+            }
+            Frequency::Monthly => {
+                let time = dtstart.time();
+                let day = dtstart.day();
+                let month_index = |dt: NaiveDateTime| dt.year() as i64 * 12 + dt.month0() as i64;
+                let first = skip_to.map_or(0, |to| first_period(month_index(to) - month_index(dtstart)));
+                Box::new((first..).filter_map(move |n| {
+                    let months = dtstart.month0() as i64 + n * interval;
+                    let year = dtstart.year() as i64 + months.div_euclid(12);
+                    let month = months.rem_euclid(12) as u32 + 1;
+                    // Months without this day (e.g. the 31st) are skipped, per RFC 5545
+                    NaiveDate::from_ymd_opt(year as i32, month, day).map(|date| date.and_time(time))
+                }))
+            }
+        }
+    }
+}
+
+/// Parse an UNTIL value: `YYYYMMDDTHHMMSSZ`, `YYYYMMDDTHHMMSS` or `YYYYMMDD`.
+/// A date-only UNTIL includes the whole day.
+fn parse_until(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim_end_matches('Z');
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return Some(Utc.from_utc_datetime(&naive));
+    }
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(23, 59, 59))
+        .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    match code.trim().to_ascii_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        let rule = RecurrenceRule::parse("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=FR,MO;UNTIL=20240301T000000Z").unwrap();
+        assert_eq!(rule.freq, Frequency::Weekly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.by_day, vec![Weekday::Mon, Weekday::Fri]);
+        assert_eq!(rule.until, Some(utc(2024, 3, 1, 0)));
+
+        assert!(RecurrenceRule::parse("INTERVAL=2").is_err());
+        assert_eq!(
+            RecurrenceRule::parse("FREQ=YEARLY"),
+            Err(RecurrenceError::UnsupportedFrequency("YEARLY".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_weekly_across_month_boundary() {
+        // Thursday standup starting Jan 18th 2024
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY").unwrap();
+        let occurrences = rule.occurrences_between(&Utc, utc(2024, 1, 18, 9), utc(2024, 1, 22, 0), utc(2024, 2, 12, 0));
+        assert_eq!(
+            occurrences,
+            vec![utc(2024, 1, 25, 9), utc(2024, 2, 1, 9), utc(2024, 2, 8, 9)]
+        );
+    }
+
+    #[test]
+    fn test_weekly_byday_with_count() {
+        // Wednesday Jan 31st start; MO,WE,FR; COUNT applies from the first occurrence
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=MO,WE,FR;COUNT=4").unwrap();
+        let occurrences = rule.occurrences_between(&Utc, utc(2024, 1, 31, 9), utc(2024, 1, 1, 0), utc(2024, 3, 1, 0));
+        assert_eq!(
+            occurrences,
+            vec![utc(2024, 1, 31, 9), utc(2024, 2, 2, 9), utc(2024, 2, 5, 9), utc(2024, 2, 7, 9)]
+        );
+
+        // Occurrences before the window still count towards COUNT
+        let later = rule.occurrences_between(&Utc, utc(2024, 1, 31, 9), utc(2024, 2, 3, 0), utc(2024, 3, 1, 0));
+        assert_eq!(later, vec![utc(2024, 2, 5, 9), utc(2024, 2, 7, 9)]);
+    }
+
+    #[test]
+    fn test_daily_until_and_monthly_skips_short_months() {
+        let daily = RecurrenceRule::parse("FREQ=DAILY;INTERVAL=2;UNTIL=20240105").unwrap();
+        let occurrences = daily.occurrences_between(&Utc, utc(2024, 1, 1, 8), utc(2024, 1, 1, 0), utc(2024, 2, 1, 0));
+        assert_eq!(occurrences, vec![utc(2024, 1, 1, 8), utc(2024, 1, 3, 8), utc(2024, 1, 5, 8)]);
+
+        let monthly = RecurrenceRule::parse("FREQ=MONTHLY").unwrap();
+        let occurrences = monthly.occurrences_between(&Utc, utc(2024, 1, 31, 12), utc(2024, 1, 1, 0), utc(2024, 6, 1, 0));
+        assert_eq!(
+            occurrences,
+            vec![utc(2024, 1, 31, 12), utc(2024, 3, 31, 12), utc(2024, 5, 31, 12)]
+        );
+    }
+
+    #[test]
+    fn test_long_running_series_expands_in_a_late_window() {
+        // A daily series started 40 years ago has far more past occurrences than the cap
+        let daily = RecurrenceRule::parse("FREQ=DAILY;INTERVAL=3").unwrap();
+        let occurrences = daily.occurrences_between(&Utc, utc(1984, 1, 1, 9), utc(2024, 3, 1, 0), utc(2024, 3, 8, 0));
+        assert_eq!(
+            occurrences,
+            vec![utc(2024, 3, 1, 9), utc(2024, 3, 4, 9), utc(2024, 3, 7, 9)]
+        );
+
+        let weekly = RecurrenceRule::parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH").unwrap();
+        let occurrences = weekly.occurrences_between(&Utc, utc(1990, 1, 1, 9), utc(2024, 3, 1, 0), utc(2024, 3, 15, 0));
+        // 1990-01-01 and 2024-03-11 are both Mondays, 1784 weeks apart
+        assert_eq!(occurrences, vec![utc(2024, 3, 11, 9), utc(2024, 3, 14, 9)]);
+
+        let monthly = RecurrenceRule::parse("FREQ=MONTHLY;INTERVAL=5").unwrap();
+        let occurrences = monthly.occurrences_between(&Utc, utc(1200, 1, 15, 9), utc(2024, 1, 1, 0), utc(2025, 1, 1, 0));
+        // January 2024 is 9888 months in, 3 past a multiple of 5, so March comes next
+        assert_eq!(occurrences, vec![utc(2024, 3, 15, 9), utc(2024, 8, 15, 9)]);
+
+        // COUNT still counts from the series start
+        let counted = RecurrenceRule::parse("FREQ=DAILY;COUNT=3").unwrap();
+        assert!(counted.occurrences_between(&Utc, utc(1984, 1, 1, 9), utc(2024, 3, 1, 0), utc(2024, 3, 8, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_weekly_keeps_local_time_across_dst() {
+        let tz: chrono_tz::Tz = "America/New_York".parse().unwrap();
+        // 9:00 EST on Monday March 4th 2024; DST starts March 10th
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;COUNT=2").unwrap();
+        let occurrences = rule.occurrences_between(&tz, utc(2024, 3, 4, 14), utc(2024, 3, 1, 0), utc(2024, 4, 1, 0));
+        assert_eq!(occurrences, vec![utc(2024, 3, 4, 14), utc(2024, 3, 11, 13)]);
+    }
+}
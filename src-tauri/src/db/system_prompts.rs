@@ -0,0 +1,231 @@
+//! Database operations for system prompt presets
+//!
+//! Presets are named system prompts that can be picked when starting a
+//! conversation. At most one preset is the default for new conversations.
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{SaveSystemPromptInput, SystemPrompt};
+
+#[derive(Error, Debug)]
+pub enum SystemPromptDbError {
+    #[error("SQLite error: {0}")]
+    SqliteError(#[from] rusqlite::Error),
+    #[error("System prompt not found: {0}")]
+    NotFound(String),
+    #[error("System prompt already exists: {0}")]
+    AlreadyExists(String),
+    #[error("Invalid data: {0}")]
+    InvalidData(String),
+}
+
+/// Parse a datetime string from SQLite into a DateTime<Utc>
+fn parse_datetime(s: &str) -> DateTime<Utc> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return dt.with_timezone(&Utc);
+    }
+    if let Ok(naive) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
+        return Utc.from_utc_datetime(&naive);
+    }
+    Utc::now()
+}
+
+/// Map a database row to a SystemPrompt struct
+fn row_to_system_prompt(row: &Row) -> Result<SystemPrompt, rusqlite::Error> {
+    let created_at_str: String = row.get(4)?;
+    let updated_at_str: String = row.get(5)?;
+
+    Ok(SystemPrompt {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        prompt: row.get(2)?,
+        is_default: row.get(3)?,
+        created_at: parse_datetime(&created_at_str),
+        updated_at: parse_datetime(&updated_at_str),
+    })
+}
+
+const SELECT_COLUMNS: &str = "SELECT id, name, prompt, is_default, created_at, updated_at FROM system_prompts";
+
+/// List all presets, default first, then by name
+pub fn list_system_prompts(conn: &Connection) -> Result<Vec<SystemPrompt>, SystemPromptDbError> {
+    let mut stmt = conn.prepare(&format!(
+        "{} ORDER BY is_default DESC, name COLLATE NOCASE",
+        SELECT_COLUMNS
+    ))?;
+    let prompts = stmt
+        .query_map([], row_to_system_prompt)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(prompts)
+}
+
+/// Get a preset by ID
+pub fn get_system_prompt(conn: &Connection, id: &str) -> Result<Option<SystemPrompt>, SystemPromptDbError> {
+    let prompt = conn
+        .query_row(&format!("{} WHERE id = ?1", SELECT_COLUMNS), [id], row_to_system_prompt)
+        .optional()?;
+    Ok(prompt)
+}
+
+/// Get the default preset, if one is set
+pub fn get_default_system_prompt(conn: &Connection) -> Result<Option<SystemPrompt>, SystemPromptDbError> {
+    let prompt = conn
+        .query_row(&format!("{} WHERE is_default = 1", SELECT_COLUMNS), [], row_to_system_prompt)
+        .optional()?;
+    Ok(prompt)
+}
+
+/// Create a preset, or update it when `input.id` is set
+///
+/// Marking a preset as default clears the flag on every other preset.
+pub fn save_system_prompt(
+    conn: &Connection,
+    input: SaveSystemPromptInput,
+) -> Result<SystemPrompt, SystemPromptDbError> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(SystemPromptDbError::InvalidData("Name cannot be empty".to_string()));
+    }
+    if input.prompt.trim().is_empty() {
+        return Err(SystemPromptDbError::InvalidData("Prompt cannot be empty".to_string()));
+    }
+
+    let name_taken: Option<String> = conn
+        .query_row(
+            "SELECT id FROM system_prompts WHERE name = ?1 COLLATE NOCASE",
+            [name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if name_taken.is_some_and(|id| Some(&id) != input.id.as_ref()) {
+        return Err(SystemPromptDbError::AlreadyExists(name.to_string()));
+    }
+
+    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let tx = conn.unchecked_transaction()?;
+
+    if input.is_default {
+        tx.execute("UPDATE system_prompts SET is_default = 0 WHERE is_default = 1", [])?;
+    }
+
+    let id = match input.id {
+        Some(id) => {
+            let updated = tx.execute(
+                "UPDATE system_prompts SET name = ?1, prompt = ?2, is_default = ?3, updated_at = ?4
+                 WHERE id = ?5",
+                params![name, input.prompt, input.is_default, now, id],
+            )?;
+            if updated == 0 {
+                return Err(SystemPromptDbError::NotFound(id));
+            }
+            id
+        }
+        None => {
+            let id = Uuid::new_v4().to_string();
+            tx.execute(
+                "INSERT INTO system_prompts (id, name, prompt, is_default, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![id, name, input.prompt, input.is_default, now, now],
+            )?;
+            id
+        }
+    };
+
+    tx.commit()?;
+    get_system_prompt(conn, &id)?.ok_or(SystemPromptDbError::NotFound(id))
+}
+
+/// Delete a preset
+pub fn delete_system_prompt(conn: &Connection, id: &str) -> Result<bool, SystemPromptDbError> {
+    let affected = conn.execute("DELETE FROM system_prompts WHERE id = ?1", [id])?;
+    Ok(affected > 0)
+}
+
+/// Resolve the system prompt for a new conversation
+///
+/// An explicit prompt wins, then the given preset, then the default preset.
+pub fn resolve_system_prompt(
+    conn: &Connection,
+    system_prompt: Option<&str>,
+    preset_id: Option<&str>,
+) -> Result<Option<String>, SystemPromptDbError> {
+    if let Some(prompt) = system_prompt {
+        return Ok(Some(prompt.to_string()));
+    }
+
+    let preset = match preset_id {
+        Some(id) => Some(get_system_prompt(conn, id)?.ok_or_else(|| SystemPromptDbError::NotFound(id.to_string()))?),
+        None => get_default_system_prompt(conn)?,
+    };
+
+    Ok(preset.map(|p| p.prompt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+
+    fn input(id: Option<&str>, name: &str, prompt: &str, is_default: bool) -> SaveSystemPromptInput {
+        SaveSystemPromptInput {
+            id: id.map(String::from),
+            name: name.to_string(),
+            prompt: prompt.to_string(),
+            is_default,
+        }
+    }
+
+    #[test]
+    fn test_system_prompt_crud() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let editor = save_system_prompt(&conn, input(None, "Editor", "Fix grammar only.", false)).unwrap();
+        let tutor = save_system_prompt(&conn, input(None, "Tutor", "Explain step by step.", false)).unwrap();
+        assert_eq!(list_system_prompts(&conn).unwrap().len(), 2);
+
+        let updated =
+            save_system_prompt(&conn, input(Some(&editor.id), "Copy editor", "Fix grammar and tone.", false))
+                .unwrap();
+        assert_eq!(updated.id, editor.id);
+        assert_eq!(updated.name, "Copy editor");
+        assert_eq!(updated.prompt, "Fix grammar and tone.");
+
+        assert!(matches!(
+            save_system_prompt(&conn, input(None, "tutor", "Duplicate", false)),
+            Err(SystemPromptDbError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            save_system_prompt(&conn, input(Some("missing"), "Other", "Prompt", false)),
+            Err(SystemPromptDbError::NotFound(_))
+        ));
+
+        assert!(delete_system_prompt(&conn, &tutor.id).unwrap());
+        assert!(!delete_system_prompt(&conn, &tutor.id).unwrap());
+        assert!(get_system_prompt(&conn, &tutor.id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_default_system_prompt_selection() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        assert_eq!(resolve_system_prompt(&conn, None, None).unwrap(), None);
+
+        let first = save_system_prompt(&conn, input(None, "First", "Be brief.", true)).unwrap();
+        let second = save_system_prompt(&conn, input(None, "Second", "Be thorough.", true)).unwrap();
+
+        // Only the most recently marked preset stays default
+        assert!(!get_system_prompt(&conn, &first.id).unwrap().unwrap().is_default);
+        assert_eq!(get_default_system_prompt(&conn).unwrap().unwrap().id, second.id);
+        assert_eq!(list_system_prompts(&conn).unwrap()[0].id, second.id);
+
+        assert_eq!(resolve_system_prompt(&conn, None, None).unwrap().as_deref(), Some("Be thorough."));
+        assert_eq!(resolve_system_prompt(&conn, None, Some(&first.id)).unwrap().as_deref(), Some("Be brief."));
+        assert_eq!(resolve_system_prompt(&conn, Some("Custom"), Some(&first.id)).unwrap().as_deref(), Some("Custom"));
+        assert!(resolve_system_prompt(&conn, None, Some("missing")).is_err());
+    }
+}
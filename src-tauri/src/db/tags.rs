@@ -3,10 +3,16 @@
 //! Provides CRUD operations for tags and note-tag associations.
 
 use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use thiserror::Error;
 use uuid::Uuid;
 
-use crate::models::Tag;
+use super::embeddings::{search_similar_to_note, EmbeddingDbError};
+use crate::models::{Note, Tag};
+
+/// Separator between levels of a nested tag, e.g. `project/alpha`
+pub const TAG_PATH_SEPARATOR: char = '/';
 
 #[derive(Error, Debug)]
 pub enum TagDbError {
@@ -16,6 +22,29 @@ pub enum TagDbError {
     NotFound(String),
     #[error("Tag already exists: {0}")]
     AlreadyExists(String),
+    #[error("Invalid tag color: {0}")]
+    InvalidColor(String),
+    #[error("Embedding error: {0}")]
+    EmbeddingError(#[from] EmbeddingDbError),
+}
+
+/// Named colors in the tag palette
+pub const TAG_PALETTE_COLORS: &[&str] = &["red", "orange", "yellow", "green", "blue", "purple", "pink", "gray"];
+
+/// Check that a color is a palette name or a `#rgb` / `#rrggbb` hex string
+pub fn validate_tag_color(color: &str) -> Result<(), TagDbError> {
+    if TAG_PALETTE_COLORS.contains(&color) {
+        return Ok(());
+    }
+    let is_hex = color
+        .strip_prefix('#')
+        .map(|hex| (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .unwrap_or(false);
+    if is_hex {
+        Ok(())
+    } else {
+        Err(TagDbError::InvalidColor(color.to_string()))
+    }
 }
 
 /// Map a database row to a Tag struct
@@ -24,12 +53,13 @@ fn row_to_tag(row: &rusqlite::Row) -> Result<Tag, rusqlite::Error> {
         id: row.get(0)?,
         name: row.get(1)?,
         color: row.get(2)?,
+        description: row.get(3)?,
     })
 }
 
 /// Get all tags in the database
 pub fn get_all_tags(conn: &Connection) -> Result<Vec<Tag>, TagDbError> {
-    let mut stmt = conn.prepare("SELECT id, name, color FROM tags ORDER BY name")?;
+    let mut stmt = conn.prepare("SELECT id, name, color, description FROM tags ORDER BY name")?;
     
     let tags = stmt
         .query_map([], row_to_tag)?
@@ -44,7 +74,7 @@ pub fn search_tags(conn: &Connection, query: &str) -> Result<Vec<Tag>, TagDbErro
     let search_pattern = format!("%{}%", query.to_lowercase());
     
     let mut stmt = conn.prepare(
-        "SELECT id, name, color FROM tags WHERE LOWER(name) LIKE ?1 ORDER BY name"
+        "SELECT id, name, color, description FROM tags WHERE LOWER(name) LIKE ?1 ORDER BY name"
     )?;
     
     let tags = stmt
@@ -57,7 +87,7 @@ pub fn search_tags(conn: &Connection, query: &str) -> Result<Vec<Tag>, TagDbErro
 
 /// Get a tag by ID
 pub fn get_tag(conn: &Connection, id: &str) -> Result<Option<Tag>, TagDbError> {
-    let mut stmt = conn.prepare("SELECT id, name, color FROM tags WHERE id = ?1")?;
+    let mut stmt = conn.prepare("SELECT id, name, color, description FROM tags WHERE id = ?1")?;
     let tag = stmt.query_row([id], row_to_tag).optional()?;
     Ok(tag)
 }
@@ -65,14 +95,23 @@ pub fn get_tag(conn: &Connection, id: &str) -> Result<Option<Tag>, TagDbError> {
 /// Find a tag by exact name (case-insensitive)
 pub fn find_tag_by_name(conn: &Connection, name: &str) -> Result<Option<Tag>, TagDbError> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, color FROM tags WHERE LOWER(name) = LOWER(?1)"
+        "SELECT id, name, color, description FROM tags WHERE LOWER(name) = LOWER(?1)"
     )?;
     let tag = stmt.query_row([name], row_to_tag).optional()?;
     Ok(tag)
 }
 
 /// Create a new tag
-pub fn create_tag(conn: &Connection, name: &str, color: Option<&str>) -> Result<Tag, TagDbError> {
+pub fn create_tag(
+    conn: &Connection,
+    name: &str,
+    color: Option<&str>,
+    description: Option<&str>,
+) -> Result<Tag, TagDbError> {
+    if let Some(color) = color {
+        validate_tag_color(color)?;
+    }
+
     // Check if tag with this name already exists
     if let Some(existing) = find_tag_by_name(conn, name)? {
         return Err(TagDbError::AlreadyExists(existing.name));
@@ -82,8 +121,8 @@ pub fn create_tag(conn: &Connection, name: &str, color: Option<&str>) -> Result<
     let normalized_name = name.to_lowercase().trim().to_string();
     
     conn.execute(
-        "INSERT INTO tags (id, name, color) VALUES (?1, ?2, ?3)",
-        params![id, normalized_name, color],
+        "INSERT INTO tags (id, name, color, description) VALUES (?1, ?2, ?3, ?4)",
+        params![id, normalized_name, color, description],
     )?;
     
     get_tag(conn, &id)?.ok_or(TagDbError::NotFound(id))
@@ -95,22 +134,77 @@ pub fn find_or_create_tag(conn: &Connection, name: &str, color: Option<&str>) ->
     if let Some(existing) = find_tag_by_name(conn, name)? {
         return Ok(existing);
     }
-    create_tag(conn, name, color)
+    create_tag(conn, name, color, None)
 }
 
 /// Update a tag
-pub fn update_tag(conn: &Connection, id: &str, name: Option<&str>, color: Option<&str>) -> Result<Tag, TagDbError> {
-    let existing = get_tag(conn, id)?.ok_or_else(|| TagDbError::NotFound(id.to_string()))?;
+///
+/// Renaming keeps the tag's ID, so its note associations are preserved. Renaming
+/// to the name of another existing tag fails; use `merge_tags` to combine them.
+/// An empty description clears it.
+pub fn update_tag(
+    conn: &Connection,
+    id: &str,
+    name: Option<&str>,
+    color: Option<&str>,
+    description: Option<&str>,
+) -> Result<Tag, TagDbError> {
+    if let Some(color) = color {
+        validate_tag_color(color)?;
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let existing = get_tag(&tx, id)?.ok_or_else(|| TagDbError::NotFound(id.to_string()))?;
     
     let new_name = name.map(|n| n.to_lowercase().trim().to_string()).unwrap_or(existing.name);
     let new_color = color.map(|c| Some(c.to_string())).unwrap_or(existing.color);
+    let new_description = match description.map(str::trim) {
+        Some("") => None,
+        Some(d) => Some(d.to_string()),
+        None => existing.description,
+    };
+
+    if let Some(other) = find_tag_by_name(&tx, &new_name)? {
+        if other.id != id {
+            return Err(TagDbError::AlreadyExists(other.name));
+        }
+    }
     
-    conn.execute(
-        "UPDATE tags SET name = ?1, color = ?2 WHERE id = ?3",
-        params![new_name, new_color, id],
+    tx.execute(
+        "UPDATE tags SET name = ?1, color = ?2, description = ?3 WHERE id = ?4",
+        params![new_name, new_color, new_description, id],
     )?;
     
-    get_tag(conn, id)?.ok_or(TagDbError::NotFound(id.to_string()))
+    let tag = get_tag(&tx, id)?.ok_or(TagDbError::NotFound(id.to_string()))?;
+    tx.commit()?;
+    Ok(tag)
+}
+
+/// Merge tags into a target tag
+///
+/// Every note tagged with one of the sources ends up tagged with the target
+/// (without duplicate associations), then the sources are deleted. Either the
+/// whole merge is applied or nothing is.
+pub fn merge_tags(conn: &Connection, source_ids: &[String], target_id: &str) -> Result<Tag, TagDbError> {
+    let tx = conn.unchecked_transaction()?;
+    let target = get_tag(&tx, target_id)?.ok_or_else(|| TagDbError::NotFound(target_id.to_string()))?;
+
+    for source_id in source_ids.iter().filter(|id| id.as_str() != target_id) {
+        if get_tag(&tx, source_id)?.is_none() {
+            return Err(TagDbError::NotFound(source_id.clone()));
+        }
+
+        tx.execute(
+            "INSERT OR IGNORE INTO note_tags (note_id, tag_id)
+             SELECT note_id, ?1 FROM note_tags WHERE tag_id = ?2",
+            params![target_id, source_id],
+        )?;
+        tx.execute("DELETE FROM note_tags WHERE tag_id = ?1", [source_id])?;
+        tx.execute("DELETE FROM tags WHERE id = ?1", [source_id])?;
+    }
+
+    tx.commit()?;
+    Ok(target)
 }
 
 /// Delete a tag (cascades to note_tags)
@@ -122,7 +216,7 @@ pub fn delete_tag(conn: &Connection, id: &str) -> Result<bool, TagDbError> {
 /// Get all tags for a specific note
 pub fn get_note_tags(conn: &Connection, note_id: &str) -> Result<Vec<Tag>, TagDbError> {
     let mut stmt = conn.prepare(
-        "SELECT t.id, t.name, t.color 
+        "SELECT t.id, t.name, t.color, t.description
          FROM tags t 
          INNER JOIN note_tags nt ON t.id = nt.tag_id 
          WHERE nt.note_id = ?1 
@@ -165,6 +259,92 @@ pub fn remove_tag_from_note(conn: &Connection, note_id: &str, tag_id: &str) -> R
     Ok(rows_affected > 0)
 }
 
+/// Get all notes tagged with `prefix` or any tag nested beneath it
+///
+/// Nesting is by the `/` separator, so `project` matches `project` and
+/// `project/alpha` but not `projectx`. Matching is case-insensitive.
+pub fn get_notes_by_tag_prefix(conn: &Connection, prefix: &str) -> Result<Vec<Note>, TagDbError> {
+    let prefix = prefix.trim().trim_end_matches(TAG_PATH_SEPARATOR).to_lowercase();
+    let child_prefix = format!("{}{}", prefix, TAG_PATH_SEPARATOR);
+
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT n.id, n.title, n.content, n.content_html, n.folder_id, n.created_at, n.updated_at, n.is_deleted
+         FROM notes n
+         INNER JOIN note_tags nt ON n.id = nt.note_id
+         INNER JOIN tags t ON t.id = nt.tag_id
+         WHERE n.is_deleted = FALSE
+           AND (LOWER(t.name) = ?1 OR SUBSTR(LOWER(t.name), 1, LENGTH(?2)) = ?2)
+         ORDER BY n.updated_at DESC",
+    )?;
+
+    let notes = stmt
+        .query_map(params![prefix, child_prefix], super::notes::row_to_note)?
+        .filter_map(Result::ok)
+        .collect();
+
+    Ok(notes)
+}
+
+/// Number of similar notes whose tags are considered for suggestions
+const SUGGESTION_NEIGHBORS: usize = 10;
+
+/// A tag suggested for a note from the tags on similar notes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagSuggestion {
+    pub tag: Tag,
+    /// How many of the similar notes carry this tag
+    pub note_count: usize,
+    /// Summed similarity of those notes, used to break ties
+    pub score: f32,
+}
+
+/// Suggest tags for a note from its most similar notes
+///
+/// Looks up the note's nearest neighbours by embedding and ranks their tags by
+/// how many neighbours share them. Tags already on the note are left out, and a
+/// note without an embedding gets no suggestions.
+pub fn suggest_tags_for_note(
+    conn: &Connection,
+    note_id: &str,
+    limit: usize,
+) -> Result<Vec<TagSuggestion>, TagDbError> {
+    let similar = match search_similar_to_note(conn, note_id, SUGGESTION_NEIGHBORS, Some(0.3)) {
+        Ok(similar) => similar,
+        Err(EmbeddingDbError::NotFound(_)) => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let existing: Vec<String> = get_note_tags(conn, note_id)?.into_iter().map(|t| t.id).collect();
+
+    let mut suggestions: HashMap<String, TagSuggestion> = HashMap::new();
+    for neighbour in &similar {
+        for tag in get_note_tags(conn, &neighbour.note_id)? {
+            if existing.contains(&tag.id) {
+                continue;
+            }
+            let entry = suggestions.entry(tag.id.clone()).or_insert(TagSuggestion {
+                tag,
+                note_count: 0,
+                score: 0.0,
+            });
+            entry.note_count += 1;
+            entry.score += neighbour.score;
+        }
+    }
+
+    let mut suggestions: Vec<TagSuggestion> = suggestions.into_values().collect();
+    suggestions.sort_by(|a, b| {
+        b.note_count
+            .cmp(&a.note_count)
+            .then(b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal))
+            .then_with(|| a.tag.name.cmp(&b.tag.name))
+    });
+    suggestions.truncate(limit);
+
+    Ok(suggestions)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,7 +355,7 @@ mod tests {
         let pool = init_test_pool().unwrap();
         let conn = pool.get().unwrap();
 
-        let tag = create_tag(&conn, "Test Tag", Some("blue")).unwrap();
+        let tag = create_tag(&conn, "Test Tag", Some("blue"), None).unwrap();
         assert_eq!(tag.name, "test tag"); // Should be normalized to lowercase
         assert_eq!(tag.color, Some("blue".to_string()));
 
@@ -188,7 +368,7 @@ mod tests {
         let pool = init_test_pool().unwrap();
         let conn = pool.get().unwrap();
 
-        create_tag(&conn, "machine-learning", Some("blue")).unwrap();
+        create_tag(&conn, "machine-learning", Some("blue"), None).unwrap();
         
         let found = find_tag_by_name(&conn, "MACHINE-LEARNING").unwrap();
         assert!(found.is_some());
@@ -200,9 +380,9 @@ mod tests {
         let pool = init_test_pool().unwrap();
         let conn = pool.get().unwrap();
 
-        create_tag(&conn, "python", Some("blue")).unwrap();
-        create_tag(&conn, "python-web", Some("green")).unwrap();
-        create_tag(&conn, "rust", Some("orange")).unwrap();
+        create_tag(&conn, "python", Some("blue"), None).unwrap();
+        create_tag(&conn, "python-web", Some("green"), None).unwrap();
+        create_tag(&conn, "rust", Some("orange"), None).unwrap();
 
         let results = search_tags(&conn, "python").unwrap();
         assert_eq!(results.len(), 2);
@@ -220,8 +400,8 @@ mod tests {
             params![note_id, "Test Note"],
         ).unwrap();
 
-        let tag1 = create_tag(&conn, "tag1", Some("blue")).unwrap();
-        let tag2 = create_tag(&conn, "tag2", Some("red")).unwrap();
+        let tag1 = create_tag(&conn, "tag1", Some("blue"), None).unwrap();
+        let tag2 = create_tag(&conn, "tag2", Some("red"), None).unwrap();
 
         // Add tags to note
         add_tag_to_note(&conn, note_id, &tag1.id).unwrap();
@@ -250,4 +430,203 @@ mod tests {
         // Color should be from the first creation
         assert_eq!(tag2.color, Some("purple".to_string()));
     }
+
+    fn insert_tagged_note(conn: &Connection, note_id: &str, tag: &Tag) {
+        conn.execute(
+            "INSERT INTO notes (id, title, is_deleted) VALUES (?1, ?2, FALSE)",
+            params![note_id, note_id],
+        )
+        .unwrap();
+        add_tag_to_note(conn, note_id, &tag.id).unwrap();
+    }
+
+    #[test]
+    fn test_get_notes_by_tag_prefix() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let project = create_tag(&conn, "project", None, None).unwrap();
+        let alpha = create_tag(&conn, "project/alpha", None, None).unwrap();
+        let deep = create_tag(&conn, "project/alpha/design", None, None).unwrap();
+        let projectx = create_tag(&conn, "projectx", None, None).unwrap();
+
+        insert_tagged_note(&conn, "n-project", &project);
+        insert_tagged_note(&conn, "n-alpha", &alpha);
+        insert_tagged_note(&conn, "n-deep", &deep);
+        insert_tagged_note(&conn, "n-projectx", &projectx);
+        // A note carrying both a parent and child tag is only returned once
+        add_tag_to_note(&conn, "n-alpha", &project.id).unwrap();
+
+        let mut ids: Vec<String> = get_notes_by_tag_prefix(&conn, "Project")
+            .unwrap()
+            .into_iter()
+            .map(|n| n.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["n-alpha", "n-deep", "n-project"]);
+
+        let mut ids: Vec<String> = get_notes_by_tag_prefix(&conn, "project/alpha/")
+            .unwrap()
+            .into_iter()
+            .map(|n| n.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["n-alpha", "n-deep"]);
+
+        let ids: Vec<String> = get_notes_by_tag_prefix(&conn, "projectx")
+            .unwrap()
+            .into_iter()
+            .map(|n| n.id)
+            .collect();
+        assert_eq!(ids, vec!["n-projectx"]);
+    }
+
+    #[test]
+    fn test_merge_tags_moves_associations_without_duplicates() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let ml = create_tag(&conn, "ml", None, None).unwrap();
+        let m_l = create_tag(&conn, "m-l", None, None).unwrap();
+        let target = create_tag(&conn, "machine-learning", Some("blue"), None).unwrap();
+
+        insert_tagged_note(&conn, "n1", &ml);
+        insert_tagged_note(&conn, "n2", &m_l);
+        insert_tagged_note(&conn, "n3", &target);
+        // n1 already carries the target as well as a source
+        add_tag_to_note(&conn, "n1", &target.id).unwrap();
+        add_tag_to_note(&conn, "n1", &m_l.id).unwrap();
+
+        let merged = merge_tags(&conn, &[ml.id.clone(), m_l.id.clone()], &target.id).unwrap();
+        assert_eq!(merged.id, target.id);
+
+        assert!(get_tag(&conn, &ml.id).unwrap().is_none());
+        assert!(get_tag(&conn, &m_l.id).unwrap().is_none());
+        for note_id in ["n1", "n2", "n3"] {
+            let tags = get_note_tags(&conn, note_id).unwrap();
+            assert_eq!(tags.len(), 1, "{} should only have the target tag", note_id);
+            assert_eq!(tags[0].id, target.id);
+        }
+        let associations: i64 = conn
+            .query_row("SELECT COUNT(*) FROM note_tags", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(associations, 3);
+    }
+
+    #[test]
+    fn test_merge_tags_is_all_or_nothing() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let source = create_tag(&conn, "ml", None, None).unwrap();
+        let target = create_tag(&conn, "machine-learning", None, None).unwrap();
+        insert_tagged_note(&conn, "n1", &source);
+
+        let result = merge_tags(&conn, &[source.id.clone(), "missing".to_string()], &target.id);
+        assert!(matches!(result, Err(TagDbError::NotFound(_))));
+
+        // The first source was rolled back along with the failed one
+        assert!(get_tag(&conn, &source.id).unwrap().is_some());
+        assert_eq!(get_note_tags(&conn, "n1").unwrap()[0].id, source.id);
+    }
+
+    #[test]
+    fn test_rename_tag_preserves_associations() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let tag = create_tag(&conn, "ml", Some("blue"), None).unwrap();
+        create_tag(&conn, "ai", None, None).unwrap();
+        insert_tagged_note(&conn, "n1", &tag);
+
+        let renamed = update_tag(&conn, &tag.id, Some("Machine-Learning"), None, None).unwrap();
+        assert_eq!(renamed.name, "machine-learning");
+        assert_eq!(renamed.color, Some("blue".to_string()));
+        assert_eq!(get_note_tags(&conn, "n1").unwrap()[0].name, "machine-learning");
+
+        // Renaming onto another tag's name is rejected
+        let result = update_tag(&conn, &tag.id, Some("AI"), None, None);
+        assert!(matches!(result, Err(TagDbError::AlreadyExists(_))));
+        assert_eq!(get_tag(&conn, &tag.id).unwrap().unwrap().name, "machine-learning");
+    }
+
+    #[test]
+    fn test_tag_color_and_description() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let tag = create_tag(&conn, "rust", Some("#DEA584"), Some("The programming language")).unwrap();
+        assert_eq!(tag.color, Some("#DEA584".to_string()));
+        assert_eq!(tag.description, Some("The programming language".to_string()));
+
+        let listed = get_all_tags(&conn).unwrap();
+        assert_eq!(listed[0].description, Some("The programming language".to_string()));
+
+        // Leaving fields out keeps them; an empty description clears it
+        let updated = update_tag(&conn, &tag.id, None, Some("#fff"), None).unwrap();
+        assert_eq!(updated.color, Some("#fff".to_string()));
+        assert_eq!(updated.description, Some("The programming language".to_string()));
+        let updated = update_tag(&conn, &tag.id, None, None, Some("")).unwrap();
+        assert_eq!(updated.description, None);
+        assert_eq!(updated.color, Some("#fff".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_tag_colors_are_rejected() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        for color in ["#12345", "#ggg", "3b82f6", "teal", ""] {
+            let result = create_tag(&conn, "bad", Some(color), None);
+            assert!(matches!(result, Err(TagDbError::InvalidColor(_))), "{:?} should be rejected", color);
+        }
+
+        let tag = create_tag(&conn, "good", Some("blue"), None).unwrap();
+        let result = update_tag(&conn, &tag.id, None, Some("not-a-color"), None);
+        assert!(matches!(result, Err(TagDbError::InvalidColor(_))));
+        assert_eq!(get_tag(&conn, &tag.id).unwrap().unwrap().color, Some("blue".to_string()));
+    }
+
+    #[test]
+    fn test_suggest_tags_from_similar_notes() {
+        use crate::db::embeddings::store_embedding;
+
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let rust = create_tag(&conn, "rust", None, None).unwrap();
+        let systems = create_tag(&conn, "systems", None, None).unwrap();
+        let cooking = create_tag(&conn, "cooking", None, None).unwrap();
+
+        insert_tagged_note(&conn, "r1", &rust);
+        insert_tagged_note(&conn, "r2", &rust);
+        insert_tagged_note(&conn, "r3", &rust);
+        add_tag_to_note(&conn, "r1", &systems.id).unwrap();
+        insert_tagged_note(&conn, "recipe", &cooking);
+        conn.execute("INSERT INTO notes (id, title, is_deleted) VALUES ('new', 'new', FALSE)", [])
+            .unwrap();
+
+        store_embedding(&conn, "new", &[1.0, 0.0, 0.0], "test-model", None, None).unwrap();
+        store_embedding(&conn, "r1", &[0.9, 0.1, 0.0], "test-model", None, None).unwrap();
+        store_embedding(&conn, "r2", &[0.95, 0.05, 0.0], "test-model", None, None).unwrap();
+        store_embedding(&conn, "r3", &[0.8, 0.2, 0.1], "test-model", None, None).unwrap();
+        store_embedding(&conn, "recipe", &[-1.0, 0.0, 0.0], "test-model", None, None).unwrap();
+
+        let suggestions = suggest_tags_for_note(&conn, "new", 5).unwrap();
+        assert_eq!(suggestions[0].tag.name, "rust");
+        assert_eq!(suggestions[0].note_count, 3);
+        assert_eq!(suggestions[1].tag.name, "systems");
+        // The dissimilar note's tag is not suggested
+        assert!(suggestions.iter().all(|s| s.tag.id != cooking.id));
+
+        // Tags the note already has are not suggested again
+        add_tag_to_note(&conn, "new", &rust.id).unwrap();
+        let suggestions = suggest_tags_for_note(&conn, "new", 5).unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].tag.name, "systems");
+
+        // Notes without an embedding get no suggestions
+        insert_tagged_note(&conn, "unembedded", &cooking);
+        assert!(suggest_tags_for_note(&conn, "unembedded", 5).unwrap().is_empty());
+    }
 }
@@ -9,6 +9,8 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use uuid::Uuid;
 
+use super::embeddings::{check_dimension, DimensionMismatch};
+
 #[derive(Error, Debug)]
 #[allow(dead_code)]
 pub enum UrlAttachmentDbError {
@@ -20,6 +22,8 @@ pub enum UrlAttachmentDbError {
     InvalidData,
     #[error("Duplicate URL: {0}")]
     DuplicateUrl(String),
+    #[error(transparent)]
+    DimensionMismatch(#[from] DimensionMismatch),
 }
 
 /// Status of URL content fetching
@@ -385,12 +389,17 @@ pub fn delete_url_attachments_for_note(
 // ============================================================================
 
 /// Store an embedding for a URL attachment
+///
+/// Rejects the embedding if `expected_dimension` is set and doesn't match its length.
 pub fn store_url_embedding(
     conn: &Connection,
     url_attachment_id: &str,
     embedding: &[f32],
     model: &str,
+    expected_dimension: Option<u32>,
 ) -> Result<(), UrlAttachmentDbError> {
+    check_dimension(embedding, expected_dimension)?;
+
     // Serialize embedding to bytes (little-endian f32 values)
     let embedding_bytes: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
 
@@ -675,13 +684,19 @@ pub struct UrlChunkSimilarityResult {
 }
 
 /// Store embedding chunks for a URL attachment
-/// This replaces any existing chunks for the URL attachment
+/// This replaces any existing chunks for the URL attachment, and stores nothing
+/// if any chunk's dimension doesn't match `expected_dimension`
 pub fn store_url_embedding_chunks(
     conn: &Connection,
     url_attachment_id: &str,
     chunks: &[(String, usize, usize, Vec<f32>)], // (chunk_text, char_start, char_end, embedding)
     model: &str,
+    expected_dimension: Option<u32>,
 ) -> Result<usize, UrlAttachmentDbError> {
+    for (_, _, _, embedding) in chunks {
+        check_dimension(embedding, expected_dimension)?;
+    }
+
     // Delete existing chunks for this URL attachment
     conn.execute(
         "DELETE FROM url_embedding_chunks WHERE url_attachment_id = ?1",
@@ -948,7 +963,7 @@ mod tests {
         let attachment = create_url_attachment(&conn, input).unwrap();
 
         let embedding = vec![0.1, 0.2, 0.3, 0.4, 0.5];
-        store_url_embedding(&conn, &attachment.id, &embedding, "test-model").unwrap();
+        store_url_embedding(&conn, &attachment.id, &embedding, "test-model", None).unwrap();
 
         let retrieved = get_url_embedding(&conn, &attachment.id).unwrap().unwrap();
         assert_eq!(embedding.len(), retrieved.len());
@@ -957,6 +972,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_store_url_embedding_rejects_dimension_mismatch() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let note_id = setup_test_note(&conn);
+
+        let input = CreateUrlAttachmentInput {
+            note_id,
+            url: "https://example.com/mismatch".to_string(),
+        };
+
+        let attachment = create_url_attachment(&conn, input).unwrap();
+        let result = store_url_embedding(&conn, &attachment.id, &[0.1, 0.2], "test-model", Some(4));
+        assert!(matches!(result, Err(UrlAttachmentDbError::DimensionMismatch(_))));
+        assert!(get_url_embedding(&conn, &attachment.id).unwrap().is_none());
+
+        let chunks = vec![
+            ("first".to_string(), 0, 5, vec![0.1, 0.2, 0.3, 0.4]),
+            ("second".to_string(), 5, 11, vec![0.1, 0.2]),
+        ];
+        let result = store_url_embedding_chunks(&conn, &attachment.id, &chunks, "test-model", Some(4));
+        assert!(matches!(result, Err(UrlAttachmentDbError::DimensionMismatch(_))));
+    }
+
     #[test]
     fn test_delete_url_attachment_cascades_embedding() {
         let pool = init_test_pool().unwrap();
@@ -970,7 +1009,7 @@ mod tests {
 
         let attachment = create_url_attachment(&conn, input).unwrap();
         let embedding = vec![0.1, 0.2, 0.3];
-        store_url_embedding(&conn, &attachment.id, &embedding, "test-model").unwrap();
+        store_url_embedding(&conn, &attachment.id, &embedding, "test-model", None).unwrap();
 
         // Delete attachment - should cascade to embedding
         delete_url_attachment(&conn, &attachment.id).unwrap();
@@ -0,0 +1,187 @@
+//! Conversation Export
+//!
+//! Renders a chat conversation as a markdown document. The markdown can be
+//! saved as-is or fed through the same parser and generators used for notes.
+
+use std::fs;
+use std::path::Path;
+
+use crate::models::{Message, MessageRole};
+
+use super::{export_markdown_link, ExportError, ExportResult};
+
+/// Label shown above each message
+fn role_label(role: &MessageRole) -> &'static str {
+    match role {
+        MessageRole::User => "User",
+        MessageRole::Assistant => "Assistant",
+        MessageRole::System => "System",
+    }
+}
+
+/// Separate inline `<think>...</think>` blocks some models put in their output
+///
+/// Returns the content without the blocks and the text inside them, each
+/// trimmed. Several blocks are joined with blank lines.
+pub fn split_think_tags(content: &str) -> (String, String) {
+    let mut result = String::with_capacity(content.len());
+    let mut thinking: Vec<&str> = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find("<think>") {
+        result.push_str(&rest[..start]);
+        let inner = &rest[start + "<think>".len()..];
+        match inner.find("</think>") {
+            Some(end) => {
+                thinking.push(inner[..end].trim());
+                rest = &inner[end + "</think>".len()..];
+            }
+            // Unclosed block: everything after it is reasoning
+            None => {
+                thinking.push(inner.trim());
+                rest = "";
+            }
+        }
+    }
+    result.push_str(rest);
+
+    let thinking: Vec<&str> = thinking.into_iter().filter(|t| !t.is_empty()).collect();
+    (result.trim().to_string(), thinking.join("\n\n"))
+}
+
+/// Remove inline `<think>...</think>` blocks some models put in their output
+fn strip_think_tags(content: &str) -> String {
+    split_think_tags(content).0
+}
+
+/// Render a conversation as markdown with role-labelled messages
+///
+/// Message content is kept as markdown. Citations are listed after the message
+/// that made them. Reasoning content is included as a quote when
+/// `include_thinking` is set and stripped otherwise.
+pub fn conversation_to_markdown(title: &str, messages: &[Message], include_thinking: bool) -> String {
+    let mut sections = vec![format!("# {}", title)];
+
+    for message in messages {
+        let mut section = format!("## {}\n\n", role_label(&message.role));
+
+        let thinking = message
+            .metadata
+            .as_ref()
+            .and_then(|m| m.thinking_content.as_deref())
+            .filter(|t| !t.trim().is_empty());
+        if let (true, Some(thinking)) = (include_thinking, thinking) {
+            section.push_str("> **Reasoning**\n");
+            for line in thinking.trim().lines() {
+                section.push_str(&format!("> {}\n", line));
+            }
+            section.push('\n');
+        }
+
+        if include_thinking {
+            section.push_str(message.content.trim());
+        } else {
+            section.push_str(&strip_think_tags(&message.content));
+        }
+
+        let citations = message.metadata.as_ref().map(|m| &m.citations[..]).unwrap_or(&[]);
+        if !citations.is_empty() {
+            section.push_str("\n\n**Sources:**\n");
+            for citation in citations {
+                section.push_str(&format!("- [[{}]]\n", citation.note_title));
+            }
+        }
+
+        sections.push(section.trim_end().to_string());
+    }
+
+    sections.join("\n\n") + "\n"
+}
+
+/// Write markdown to a file
+pub fn generate_markdown(markdown: &str, title: &str, output_path: &Path) -> Result<ExportResult, ExportError> {
+    fs::write(output_path, markdown)?;
+
+    let filename = output_path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "export.md".to_string());
+
+    Ok(ExportResult {
+        path: output_path.to_string_lossy().to_string(),
+        filename,
+        file_size: markdown.len() as u64,
+        markdown_link: export_markdown_link(title, output_path),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Citation, MessageMetadata};
+    use chrono::Utc;
+
+    fn message(role: MessageRole, content: &str, metadata: Option<MessageMetadata>) -> Message {
+        Message {
+            id: "m".to_string(),
+            conversation_id: "c".to_string(),
+            role,
+            content: content.to_string(),
+            metadata,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_conversation_to_markdown_thinking_and_citations() {
+        let metadata = MessageMetadata {
+            citations: vec![Citation {
+                note_id: "n1".to_string(),
+                note_title: "Ownership".to_string(),
+                relevance: 0.9,
+                start_offset: None,
+                end_offset: None,
+            }],
+            model: None,
+            usage: None,
+            tool_calls: vec![],
+            thinking_content: Some("The user is asking about borrowing.".to_string()),
+            images: Vec::new(),
+        };
+        let messages = vec![
+            message(MessageRole::User, "What is **borrowing**?", None),
+            message(
+                MessageRole::Assistant,
+                "<think>inline reasoning</think>Borrowing lends a reference.",
+                Some(metadata),
+            ),
+        ];
+
+        let stripped = conversation_to_markdown("Rust", &messages, false);
+        assert!(stripped.starts_with("# Rust\n\n## User\n\nWhat is **borrowing**?"));
+        assert!(stripped.contains("## Assistant\n\nBorrowing lends a reference."));
+        assert!(stripped.contains("**Sources:**\n- [[Ownership]]"));
+        assert!(!stripped.contains("Reasoning"));
+        assert!(!stripped.contains("inline reasoning"));
+
+        let with_thinking = conversation_to_markdown("Rust", &messages, true);
+        assert!(with_thinking.contains("> **Reasoning**\n> The user is asking about borrowing."));
+        assert!(with_thinking.contains("inline reasoning"));
+    }
+
+    #[test]
+    fn test_strip_think_tags() {
+        assert_eq!(strip_think_tags("a<think>x</think>b"), "ab");
+        assert_eq!(strip_think_tags("answer<think>never closed"), "answer");
+        assert_eq!(strip_think_tags("plain"), "plain");
+    }
+
+    #[test]
+    fn test_split_think_tags() {
+        assert_eq!(
+            split_think_tags("<think>first</think>Answer <think> second </think>done"),
+            ("Answer done".to_string(), "first\n\nsecond".to_string())
+        );
+        assert_eq!(split_think_tags("plain"), ("plain".to_string(), String::new()));
+    }
+}
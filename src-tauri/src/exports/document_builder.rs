@@ -7,12 +7,15 @@
 //! 3. Saving the final document
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Mutex, LazyLock};
+use std::time::Instant;
 use uuid::Uuid;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
-use crate::db::exports::{Export, ExportFormat};
+use crate::db::exports::{Export, ExportFormat, ExportGenerator, ExportSource};
+use crate::exports::images::{resolve_image_path, ExportImage};
 use crate::vault::get_exports_dir;
 
 /// Represents a section of content in a document
@@ -53,6 +56,19 @@ pub struct DocumentDraft {
     pub format: ExportFormat,
     pub sections: Vec<DocumentSection>,
     pub tables: Vec<DocumentTable>,
+    /// When the draft was created
+    pub created: Instant,
+}
+
+/// Who produced a document, recorded on its export
+#[derive(Debug, Clone, Default)]
+pub struct DocumentProvenance {
+    /// Model that built the document, if known
+    pub model: Option<String>,
+    /// Notes the document was built from
+    pub source_note_ids: Vec<String>,
+    /// When generation started (defaults to when the draft was created)
+    pub started: Option<Instant>,
 }
 
 impl DocumentDraft {
@@ -63,6 +79,7 @@ impl DocumentDraft {
             format,
             sections: Vec::new(),
             tables: Vec::new(),
+            created: Instant::now(),
         }
     }
 
@@ -145,7 +162,8 @@ impl DocumentDraft {
                     }
                 }
                 SectionType::Image => {
-                    md.push_str(&format!("![Image]({})\n\n", section.content));
+                    // Angle brackets keep paths with spaces intact
+                    md.push_str(&format!("![Image](<{}>)\n\n", section.content));
                 }
                 SectionType::CodeBlock => {
                     md.push_str(&format!("```\n{}\n```\n\n", section.content));
@@ -240,9 +258,38 @@ pub fn add_document_table(
     Ok(())
 }
 
+/// Add an image to a document draft
+///
+/// `source` is a local path (absolute, vault-relative, or an attachment
+/// filename). It's resolved and decoded up front so a bad reference fails
+/// here rather than at save time. Returns the resolved path.
+pub fn add_document_image(document_id: &str, source: &str) -> Result<PathBuf, String> {
+    let path = resolve_image_path(source)
+        .ok_or_else(|| format!("Image not found: {}", source))?;
+    ExportImage::load(&path.to_string_lossy())?;
 
-/// Save a document draft to file
-pub fn save_document(document_id: &str) -> Result<Export, String> {
+    let mut drafts = DOCUMENT_DRAFTS
+        .lock()
+        .map_err(|e| format!("Failed to lock drafts: {}", e))?;
+
+    let draft = drafts
+        .get_mut(document_id)
+        .ok_or_else(|| format!("Document not found: {}", document_id))?;
+
+    draft.add_section(DocumentSection {
+        section_type: SectionType::Image,
+        content: path.to_string_lossy().to_string(),
+        heading_level: None,
+    });
+
+    Ok(path)
+}
+
+/// Save a document draft to file, recording who produced it on the export
+pub fn save_document_with_provenance(
+    document_id: &str,
+    provenance: &DocumentProvenance,
+) -> Result<Export, String> {
     use std::fs;
     use crate::db::connection::init_vault_pool;
     use crate::db::exports::{create_export, CreateExportInput};
@@ -291,6 +338,7 @@ pub fn save_document(document_id: &str) -> Result<Export, String> {
         ExportFormat::Docx => "docx",
         ExportFormat::Xlsx => "xlsx",
         ExportFormat::Pptx => "pptx",
+        ExportFormat::Markdown => "md",
     };
     let filename = format!("{}_{}.{}", safe_title, timestamp, extension);
     let file_path = exports_dir.join(&filename);
@@ -326,6 +374,10 @@ pub fn save_document(document_id: &str) -> Result<Export, String> {
             crate::exports::pptx_generator::generate_pptx(&parsed_content, &draft.title, &file_path, &options)
                 .map_err(|e| format!("PPTX generation failed: {}", e))?;
         }
+        ExportFormat::Markdown => {
+            fs::write(&file_path, &markdown)
+                .map_err(|e| format!("Markdown export failed: {}", e))?;
+        }
     }
 
     // Get file size
@@ -344,9 +396,15 @@ pub fn save_document(document_id: &str) -> Result<Export, String> {
         filename: filename.clone(),
         title: draft.title.clone(),
         format: draft.format,
-        source_note_ids: vec![], // No source note IDs for builder-created documents
+        source_note_ids: provenance.source_note_ids.clone(),
         file_size: Some(file_size),
         path: file_path.to_string_lossy().to_string(),
+        duration_ms: Some(provenance.started.unwrap_or(draft.created).elapsed().as_millis() as i64),
+        generator: ExportGenerator::Agent,
+        model: provenance.model.clone(),
+        source: ExportSource::Notes,
+        conversation_id: None,
+        options: Default::default(),
     };
 
     let export = create_export(&conn, input)
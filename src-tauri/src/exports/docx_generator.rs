@@ -7,20 +7,31 @@ use std::path::Path;
 
 use docx_rs::*;
 
-use super::markdown_parser::{ContentBlock, ParsedContent};
-use super::{DocxExportOptions, ExportError, ExportResult};
+use super::highlight::highlight_code;
+use super::images::ExportImage;
+use super::markdown_parser::{runs_or_plain, ContentBlock, FootnoteDefinition, ParsedContent, TextRun};
+use super::{export_markdown_link, DocxExportOptions, ExportError, ExportResult};
 
 /// Heading sizes in half-points (Word uses half-points for font size)
 const HEADING_SIZES: [usize; 6] = [48, 40, 32, 28, 26, 24]; // 24pt, 20pt, 16pt, 14pt, 13pt, 12pt
 const BODY_SIZE: usize = 22; // 11pt
 const CODE_SIZE: usize = 20; // 10pt
+const FOOTNOTE_SIZE: usize = 18; // 9pt
+
+/// Largest image size in EMUs (6" wide by 4.5" tall fits a Letter/A4 page with margins)
+const MAX_IMAGE_WIDTH_EMU: f32 = 5_486_400.0;
+const MAX_IMAGE_HEIGHT_EMU: f32 = 4_114_800.0;
+/// EMUs per pixel at 96 DPI
+const EMU_PER_PX: f32 = 9525.0;
+/// Color of link text (hex RGB)
+const LINK_COLOR: &str = "0563C1";
 
 /// Generate a DOCX from parsed content
 pub fn generate_docx(
     content: &ParsedContent,
     title: &str,
     output_path: &Path,
-    _options: &DocxExportOptions,
+    options: &DocxExportOptions,
 ) -> Result<ExportResult, ExportError> {
     let mut docx = Docx::new();
 
@@ -40,7 +51,7 @@ pub fn generate_docx(
 
     // Render content blocks
     for block in &content.blocks {
-        docx = render_block(docx, block)?;
+        docx = render_block(docx, block, &content.footnotes, options.highlight_code)?;
     }
 
     // Write to file
@@ -60,39 +71,36 @@ pub fn generate_docx(
 
     Ok(ExportResult {
         path: output_path.to_string_lossy().to_string(),
-        filename: filename.clone(),
+        filename,
         file_size,
-        markdown_link: format!("[{}](exports/{})", title, filename),
+        markdown_link: export_markdown_link(title, output_path),
     })
 }
 
 /// Render a content block to the DOCX document
-fn render_block(mut docx: Docx, block: &ContentBlock) -> Result<Docx, ExportError> {
+///
+/// Footnote references become Word footnotes, which Word places at the
+/// bottom of the page and links back to the reference.
+fn render_block(
+    mut docx: Docx,
+    block: &ContentBlock,
+    footnotes: &[FootnoteDefinition],
+    highlight: bool,
+) -> Result<Docx, ExportError> {
     match block {
-        ContentBlock::Heading { level, text } => {
+        ContentBlock::Heading { level, text, runs } => {
             let size = HEADING_SIZES.get(*level as usize - 1).copied().unwrap_or(24);
             let style = format!("Heading{}", level);
             
             docx = docx.add_paragraph(
-                Paragraph::new()
-                    .add_run(
-                        Run::new()
-                            .add_text(text)
-                            .size(size)
-                            .bold()
-                    )
+                add_runs(Paragraph::new(), &runs_or_plain(runs, text), size, true, footnotes)
                     .style(&style)
             );
         }
 
-        ContentBlock::Paragraph { text } => {
+        ContentBlock::Paragraph { text, runs } => {
             docx = docx.add_paragraph(
-                Paragraph::new()
-                    .add_run(
-                        Run::new()
-                            .add_text(text)
-                            .size(BODY_SIZE)
-                    )
+                add_runs(Paragraph::new(), &runs_or_plain(runs, text), BODY_SIZE, false, footnotes)
             );
         }
 
@@ -110,6 +118,35 @@ fn render_block(mut docx: Docx, block: &ContentBlock) -> Result<Docx, ExportErro
                 );
             }
 
+            let highlighted = if highlight {
+                highlight_code(code, language.as_deref())
+            } else {
+                None
+            };
+            if let Some(lines) = highlighted {
+                for spans in lines {
+                    let mut paragraph = Paragraph::new()
+                        .add_run(Run::new().add_text("  ").size(CODE_SIZE).fonts(RunFonts::new().ascii("Courier New")))
+                        .indent(Some(720), None, None, None);
+                    for span in &spans {
+                        let mut run = Run::new()
+                            .add_text(&span.text)
+                            .size(CODE_SIZE)
+                            .fonts(RunFonts::new().ascii("Courier New"))
+                            .color(span.hex_color());
+                        if span.bold {
+                            run = run.bold();
+                        }
+                        if span.italic {
+                            run = run.italic();
+                        }
+                        paragraph = paragraph.add_run(run);
+                    }
+                    docx = docx.add_paragraph(paragraph);
+                }
+                return Ok(docx);
+            }
+
             // Add code lines with monospace styling
             for line in code.lines() {
                 docx = docx.add_paragraph(
@@ -125,31 +162,15 @@ fn render_block(mut docx: Docx, block: &ContentBlock) -> Result<Docx, ExportErro
             }
         }
 
-        ContentBlock::UnorderedList { items } => {
+        ContentBlock::UnorderedList { items } | ContentBlock::OrderedList { items, .. } => {
             for item in items {
+                // Nested items are indented a further quarter inch per level
+                let indent = 720 + 360 * item.depth as i32;
+                let marker = Paragraph::new()
+                    .add_run(Run::new().add_text(format!("{} ", item.marker())).size(BODY_SIZE));
                 docx = docx.add_paragraph(
-                    Paragraph::new()
-                        .add_run(
-                            Run::new()
-                                .add_text(format!("• {}", item))
-                                .size(BODY_SIZE)
-                        )
-                        .indent(Some(720), None, None, None)
-                );
-            }
-        }
-
-        ContentBlock::OrderedList { items, start } => {
-            for (i, item) in items.iter().enumerate() {
-                let num = start + i as u64;
-                docx = docx.add_paragraph(
-                    Paragraph::new()
-                        .add_run(
-                            Run::new()
-                                .add_text(format!("{}. {}", num, item))
-                                .size(BODY_SIZE)
-                        )
-                        .indent(Some(720), None, None, None)
+                    add_runs(marker, &runs_or_plain(&item.runs, &item.text), BODY_SIZE, false, footnotes)
+                        .indent(Some(indent), None, None, None)
                 );
             }
         }
@@ -179,7 +200,15 @@ fn render_block(mut docx: Docx, block: &ContentBlock) -> Result<Docx, ExportErro
         }
 
         ContentBlock::Image { url, alt, .. } => {
-            // Add placeholder text for images
+            match ExportImage::load(url).and_then(|image| image_pic(&image)) {
+                Ok(pic) => {
+                    docx = docx.add_paragraph(Paragraph::new().add_run(Run::new().add_image(pic)));
+                    return Ok(docx);
+                }
+                Err(e) => log::warn!("[DOCXGenerator] Could not embed image {}: {}", url, e),
+            }
+
+            // Fall back to placeholder text so the export still succeeds
             let alt_display = if alt.is_empty() { "Image" } else { alt };
             docx = docx.add_paragraph(
                 Paragraph::new()
@@ -262,6 +291,57 @@ fn render_block(mut docx: Docx, block: &ContentBlock) -> Result<Docx, ExportErro
     Ok(docx)
 }
 
+/// Append styled runs to a paragraph; `bold` makes every run bold (for headings)
+fn add_runs(
+    mut paragraph: Paragraph,
+    runs: &[TextRun],
+    size: usize,
+    bold: bool,
+    footnotes: &[FootnoteDefinition],
+) -> Paragraph {
+    for text_run in runs {
+        let definition = text_run
+            .footnote
+            .and_then(|number| footnotes.iter().find(|f| f.number == number));
+        if let Some(definition) = definition {
+            let note_runs = runs_or_plain(&definition.runs, &definition.text);
+            let note = Footnote::new().add_content(add_runs(Paragraph::new(), &note_runs, FOOTNOTE_SIZE, false, &[]));
+            paragraph = paragraph.add_run(Run::new().add_footnote_reference(note));
+            continue;
+        }
+
+        let mut run = Run::new().add_text(&text_run.text).size(size);
+        if bold || text_run.bold {
+            run = run.bold();
+        }
+        if text_run.italic {
+            run = run.italic();
+        }
+        if text_run.strikethrough {
+            run = run.strike();
+        }
+        if text_run.code {
+            run = run.fonts(RunFonts::new().ascii("Courier New"));
+        }
+
+        paragraph = match &text_run.link {
+            Some(url) => paragraph.add_hyperlink(
+                Hyperlink::new(url, HyperlinkType::External)
+                    .add_run(run.color(LINK_COLOR).underline("single")),
+            ),
+            None => paragraph.add_run(run),
+        };
+    }
+    paragraph
+}
+
+/// Build an inline picture scaled to fit the page
+fn image_pic(image: &ExportImage) -> Result<Pic, String> {
+    let png = image.to_png()?;
+    let (width, height) = image.fit_within(MAX_IMAGE_WIDTH_EMU, MAX_IMAGE_HEIGHT_EMU, EMU_PER_PX);
+    Ok(Pic::new_with_dimensions(png, image.width(), image.height()).size(width as u32, height as u32))
+}
+
 /// Generate a DOCX from multiple notes
 pub fn generate_docx_from_notes(
     notes: &[(String, String)], // (title, content) pairs
@@ -283,11 +363,11 @@ pub fn generate_docx_from_notes(
         combined.blocks.push(ContentBlock::Heading {
             level: 1,
             text: note_title.clone(),
+            runs: Vec::new(),
         });
 
         // Parse and add content
-        let parsed = super::markdown_parser::parse_markdown(content);
-        combined.blocks.extend(parsed.blocks);
+        combined.append(super::markdown_parser::parse_markdown(content));
     }
 
     generate_docx(&combined, document_title, output_path, options)
@@ -308,9 +388,11 @@ mod tests {
         content.blocks.push(ContentBlock::Heading {
             level: 1,
             text: "Test Heading".to_string(),
+            runs: Vec::new(),
         });
         content.blocks.push(ContentBlock::Paragraph {
             text: "This is a test paragraph.".to_string(),
+            runs: Vec::new(),
         });
 
         let options = DocxExportOptions::default();
@@ -346,5 +428,94 @@ mod tests {
         assert!(result.is_ok());
         assert!(output_path.exists());
     }
-}
 
+    #[test]
+    fn test_generate_docx_embeds_image() {
+        let temp_dir = TempDir::new().unwrap();
+        let image_path = crate::exports::images::tests::write_test_png(temp_dir.path());
+        let output_path = temp_dir.path().join("image_test.docx");
+
+        let mut content = ParsedContent::new();
+        content.blocks.push(ContentBlock::Image {
+            url: image_path.to_string_lossy().to_string(),
+            alt: "Chart".to_string(),
+            title: None,
+        });
+
+        generate_docx(&content, "Image Test", &output_path, &DocxExportOptions::default()).unwrap();
+
+        let archive = zip::ZipArchive::new(File::open(&output_path).unwrap()).unwrap();
+        assert!(
+            archive.file_names().any(|name| name.starts_with("word/media/")),
+            "DOCX should contain the embedded image"
+        );
+    }
+
+    #[test]
+    fn test_generate_docx_renders_inline_formatting() {
+        use std::io::Read;
+
+        let temp_dir = TempDir::new().unwrap();
+        let output_path = temp_dir.path().join("inline_test.docx");
+        let content = super::super::markdown_parser::parse_markdown(
+            "Some **bold** text and a [link](https://example.com/docs).",
+        );
+
+        generate_docx(&content, "Inline Test", &output_path, &DocxExportOptions::default()).unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&output_path).unwrap()).unwrap();
+        let mut document = String::new();
+        archive.by_name("word/document.xml").unwrap().read_to_string(&mut document).unwrap();
+        assert!(document.contains("<w:b />"), "bold run should be marked bold");
+        assert!(document.contains("<w:hyperlink"), "link should be a hyperlink");
+
+        let mut rels = String::new();
+        archive.by_name("word/_rels/document.xml.rels").unwrap().read_to_string(&mut rels).unwrap();
+        assert!(rels.contains("https://example.com/docs"));
+    }
+
+    #[test]
+    fn test_generate_docx_writes_footnotes() {
+        use std::io::Read;
+
+        let temp_dir = TempDir::new().unwrap();
+        let output_path = temp_dir.path().join("footnote_test.docx");
+        let content = super::super::markdown_parser::parse_markdown(
+            "A claim[^1] and an undefined one[^x].\n\n[^1]: The supporting source.",
+        );
+
+        generate_docx(&content, "Footnote Test", &output_path, &DocxExportOptions::default()).unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&output_path).unwrap()).unwrap();
+        let mut document = String::new();
+        archive.by_name("word/document.xml").unwrap().read_to_string(&mut document).unwrap();
+        assert!(document.contains("<w:footnoteReference"));
+        assert!(document.contains("[^x]"), "undefined references stay as text");
+
+        let mut notes = String::new();
+        archive.by_name("word/footnotes.xml").unwrap().read_to_string(&mut notes).unwrap();
+        assert!(notes.contains("The supporting source."));
+    }
+
+    #[test]
+    fn test_generate_docx_highlights_known_languages_only() {
+        use std::io::Read;
+
+        let temp_dir = TempDir::new().unwrap();
+        let document_xml = |markdown: &str, name: &str| {
+            let output_path = temp_dir.path().join(name);
+            let content = super::super::markdown_parser::parse_markdown(markdown);
+            generate_docx(&content, "Code", &output_path, &DocxExportOptions::default()).unwrap();
+            let mut archive = zip::ZipArchive::new(File::open(&output_path).unwrap()).unwrap();
+            let mut document = String::new();
+            archive.by_name("word/document.xml").unwrap().read_to_string(&mut document).unwrap();
+            document
+        };
+
+        let rust = document_xml("```rust\nlet x = \"hi\";\n```", "rust.docx");
+        assert!(rust.matches("<w:color ").count() > 1, "rust code should have colored runs");
+
+        let unknown = document_xml("```nonsense\nlet x = \"hi\";\n```", "unknown.docx");
+        assert!(!unknown.contains("<w:color "), "unknown languages stay plain");
+    }
+}
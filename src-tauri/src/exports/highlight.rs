@@ -0,0 +1,110 @@
+//! Syntax Highlighting for Exported Code Blocks
+//!
+//! Colors code block tokens with syntect so the PDF and DOCX generators can
+//! render highlighted listings. Unknown languages return `None` and are
+//! exported as plain monospace text.
+
+use std::sync::LazyLock;
+
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+/// Light theme that reads well on a white page
+const THEME_NAME: &str = "InspiredGitHub";
+
+static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEME: LazyLock<Theme> = LazyLock::new(|| {
+    let mut themes = ThemeSet::load_defaults();
+    themes.themes.remove(THEME_NAME).unwrap_or_default()
+});
+
+/// A span of code in one color
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeSpan {
+    pub text: String,
+    /// RGB color
+    pub color: (u8, u8, u8),
+    pub bold: bool,
+    pub italic: bool,
+}
+
+impl CodeSpan {
+    /// Color as a hex string (for DOCX)
+    pub fn hex_color(&self) -> String {
+        let (r, g, b) = self.color;
+        format!("{:02X}{:02X}{:02X}", r, g, b)
+    }
+}
+
+/// Highlight a code block, returning the spans for each line
+///
+/// The language is matched against syntax names and file extensions
+/// (`rust`, `rs`, `Python`, `js`, ...). Returns `None` for a missing or
+/// unknown language, or if highlighting fails.
+pub fn highlight_code(code: &str, language: Option<&str>) -> Option<Vec<Vec<CodeSpan>>> {
+    let language = language?.trim();
+    if language.is_empty() {
+        return None;
+    }
+    let syntax = SYNTAX_SET
+        .find_syntax_by_token(language)
+        .or_else(|| SYNTAX_SET.find_syntax_by_name(language))?;
+
+    let mut highlighter = HighlightLines::new(syntax, &THEME);
+    let mut lines = Vec::new();
+    for line in LinesWithEndings::from(code) {
+        let ranges = highlighter.highlight_line(line, &SYNTAX_SET).ok()?;
+        let spans = ranges
+            .into_iter()
+            .filter_map(|(style, text)| {
+                let text = text.trim_end_matches(['\n', '\r']);
+                if text.is_empty() {
+                    return None;
+                }
+                let fg = style.foreground;
+                Some(CodeSpan {
+                    text: text.to_string(),
+                    color: (fg.r, fg.g, fg.b),
+                    bold: style.font_style.contains(FontStyle::BOLD),
+                    italic: style.font_style.contains(FontStyle::ITALIC),
+                })
+            })
+            .collect();
+        lines.push(spans);
+    }
+    Some(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_language_produces_multiple_colors() {
+        let code = "fn main() {\n    let answer = 42; // comment\n    println!(\"{}\", answer);\n}";
+        let lines = highlight_code(code, Some("rust")).expect("rust should be recognized");
+
+        assert_eq!(lines.len(), 4);
+        let colors: std::collections::HashSet<_> = lines.iter().flatten().map(|s| s.color).collect();
+        assert!(colors.len() > 1, "expected several token colors, got {:?}", colors);
+
+        // The spans still spell out the original code
+        let second: String = lines[1].iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(second, "    let answer = 42; // comment");
+    }
+
+    #[test]
+    fn test_unknown_or_missing_language_is_not_highlighted() {
+        assert!(highlight_code("some text", Some("not-a-real-language")).is_none());
+        assert!(highlight_code("some text", None).is_none());
+        assert!(highlight_code("some text", Some("")).is_none());
+    }
+
+    #[test]
+    fn test_language_matches_extension_and_name() {
+        assert!(highlight_code("x = 1", Some("py")).is_some());
+        assert!(highlight_code("x = 1", Some("Python")).is_some());
+    }
+}
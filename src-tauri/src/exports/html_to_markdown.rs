@@ -107,45 +107,15 @@ pub fn html_to_markdown(html: &str) -> String {
         })
         .to_string();
 
-    // Convert unordered lists (using (?s) for multiline content)
-    result = Regex::new(r"(?s)<ul[^>]*>(.*?)</ul>")
+    // Convert tables to GFM pipe tables
+    result = Regex::new(r"(?s)<table[^>]*>(.*?)</table>")
         .unwrap()
-        .replace_all(&result, |caps: &regex::Captures| {
-            let inner = &caps[1];
-            let items = Regex::new(r"(?s)<li[^>]*>(.*?)</li>")
-                .unwrap()
-                .captures_iter(inner)
-                .map(|cap| format!("- {}", cap[1].trim()))
-                .collect::<Vec<_>>()
-                .join("\n");
-            format!("{}\n\n", items)
-        })
+        .replace_all(&result, |caps: &regex::Captures| convert_table(&caps[1]))
         .to_string();
 
-    // Convert ordered lists (using (?s) for multiline content)
-    result = Regex::new(r"(?s)<ol[^>]*>(.*?)</ol>")
-        .unwrap()
-        .replace_all(&result, |caps: &regex::Captures| {
-            let inner = &caps[1];
-            let items: Vec<String> = Regex::new(r"(?s)<li[^>]*>(.*?)</li>")
-                .unwrap()
-                .captures_iter(inner)
-                .enumerate()
-                .map(|(i, cap)| format!("{}. {}", i + 1, cap[1].trim()))
-                .collect();
-            format!("{}\n\n", items.join("\n"))
-        })
-        .to_string();
-
-    // Convert task lists (checkbox items) - using (?s) for multiline content
-    result = Regex::new(r#"(?s)<li[^>]*data-checked="true"[^>]*>(.*?)</li>"#)
-        .unwrap()
-        .replace_all(&result, "- [x] $1")
-        .to_string();
-    result = Regex::new(r#"(?s)<li[^>]*data-checked="false"[^>]*>(.*?)</li>"#)
-        .unwrap()
-        .replace_all(&result, "- [ ] $1")
-        .to_string();
+    // Convert lists, keeping nested lists indented under their parent item
+    // and checkbox items as task list items
+    result = convert_lists(&result);
 
     // Convert links (using (?s) for multiline content)
     result = Regex::new(r#"(?s)<a[^>]*href="([^"]*)"[^>]*>(.*?)</a>"#)
@@ -205,6 +175,181 @@ pub fn html_to_markdown(html: &str) -> String {
     result.trim().to_string()
 }
 
+/// Convert the inside of a `<table>` to a GFM pipe table
+///
+/// The first row becomes the header, since GFM tables require one. Short
+/// rows are padded so every row has the same number of cells.
+fn convert_table(inner: &str) -> String {
+    let row_re = Regex::new(r"(?s)<tr[^>]*>(.*?)</tr>").unwrap();
+    let cell_re = Regex::new(r"(?s)<t[hd][^>]*>(.*?)</t[hd]>").unwrap();
+
+    let rows: Vec<Vec<String>> = row_re
+        .captures_iter(inner)
+        .map(|row| {
+            cell_re
+                .captures_iter(&row[1])
+                .map(|cell| table_cell_text(&cell[1]))
+                .collect()
+        })
+        .filter(|cells: &Vec<String>| !cells.is_empty())
+        .collect();
+    let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    if columns == 0 {
+        return String::new();
+    }
+
+    let format_row = |cells: &[String]| {
+        let mut padded = cells.to_vec();
+        padded.resize(columns, String::new());
+        format!("| {} |", padded.join(" | "))
+    };
+
+    let mut lines = vec![format_row(&rows[0]), format!("|{}", " --- |".repeat(columns))];
+    lines.extend(rows[1..].iter().map(|row| format_row(row)));
+    format!("\n{}\n\n", lines.join("\n"))
+}
+
+/// Flatten a table cell to a single line, escaping pipes
+fn table_cell_text(html: &str) -> String {
+    let text = Regex::new(r"<br[^>]*/?>").unwrap().replace_all(html, " ");
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+/// An open `<li>` while converting lists
+struct ListItemState {
+    /// The item's marker and indentation, e.g. `    - ` or `1. `
+    prefix: String,
+    text: String,
+    /// Checked state from the `<li>` itself (TipTap's `data-checked`)
+    checked: Option<bool>,
+    /// Whether the item's line was written (when its sub-list started)
+    written: bool,
+}
+
+impl ListItemState {
+    /// Write the item's line, once, with its text collapsed onto one line
+    ///
+    /// Task items (a `data-checked` attribute or a checkbox `<input>`) are
+    /// written as `- [ ]` / `- [x]`.
+    fn write(&mut self, out: &mut String) {
+        if self.written {
+            return;
+        }
+        let checkbox_re = Regex::new(r#"(?i)<input\b[^>]*type=["']?checkbox[^>]*>"#).unwrap();
+        let mut checked = self.checked;
+        let text = match checkbox_re.find(&self.text) {
+            Some(input) => {
+                checked = checked.or_else(|| Some(checkbox_is_checked(input.as_str())));
+                checkbox_re.replace(&self.text, "").to_string()
+            }
+            None => self.text.clone(),
+        };
+        // Drop TipTap's task item wrappers (<label><span></span></label><div>...</div>)
+        // so the item stays on one line
+        let text = Regex::new(r"</?(?:div|label)[^>]*>|<span[^>]*>\s*</span>")
+            .unwrap()
+            .replace_all(&text, " ");
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let checkbox = match checked {
+            Some(true) => "[x] ",
+            Some(false) => "[ ] ",
+            None => "",
+        };
+        out.push_str(&format!("{}{}{}\n", self.prefix, checkbox, text));
+        self.written = true;
+    }
+}
+
+/// Whether a checkbox `<input>` tag is checked (`checked`, `checked="checked"`, ...)
+fn checkbox_is_checked(input: &str) -> bool {
+    Regex::new(r#"(?i)\bchecked(\s*=\s*["']?(\w*))?"#)
+        .unwrap()
+        .captures(input)
+        .is_some_and(|caps| !caps.get(2).is_some_and(|v| v.as_str().eq_ignore_ascii_case("false")))
+}
+
+/// Convert `<ul>`/`<ol>` lists to markdown, indenting nested lists
+///
+/// Regexes can't match nested lists, so this walks the list tags with a
+/// stack. Sub-lists are indented four spaces per level, which CommonMark
+/// reads as nesting under both `- ` and `1. ` items.
+fn convert_lists(html: &str) -> String {
+    let tag_re = Regex::new(r"(?i)<(/?)(ul|ol|li)\b([^>]*)>").unwrap();
+    let start_re = Regex::new(r#"start="(\d+)""#).unwrap();
+    let data_checked_re = Regex::new(r#"data-checked="(true|false)""#).unwrap();
+
+    let mut out = String::new();
+    // Open lists: the next item number for <ol>, None for <ul>
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut items: Vec<ListItemState> = Vec::new();
+    let mut last_end = 0;
+
+    for caps in tag_re.captures_iter(html) {
+        let whole = caps.get(0).unwrap();
+        let between = &html[last_end..whole.start()];
+        last_end = whole.end();
+
+        match items.last_mut() {
+            Some(item) => item.text.push_str(between),
+            None if lists.is_empty() => out.push_str(between),
+            // Whitespace between </li> and <li>
+            None => {}
+        }
+
+        let closing = &caps[1] == "/";
+        match (caps[2].to_ascii_lowercase().as_str(), closing) {
+            ("ul", false) | ("ol", false) => {
+                if let Some(parent) = items.last_mut() {
+                    parent.write(&mut out);
+                }
+                let number = if caps[2].eq_ignore_ascii_case("ol") {
+                    Some(
+                        start_re
+                            .captures(&caps[3])
+                            .and_then(|c| c[1].parse().ok())
+                            .unwrap_or(1),
+                    )
+                } else {
+                    None
+                };
+                lists.push(number);
+            }
+            ("ul", true) | ("ol", true) => {
+                lists.pop();
+                if lists.is_empty() {
+                    out.push('\n');
+                }
+            }
+            ("li", false) => {
+                let indent = "    ".repeat(lists.len().saturating_sub(1));
+                let marker = match lists.last_mut() {
+                    Some(Some(next)) => {
+                        *next += 1;
+                        format!("{}.", *next - 1)
+                    }
+                    _ => "-".to_string(),
+                };
+                items.push(ListItemState {
+                    prefix: format!("{}{} ", indent, marker),
+                    text: String::new(),
+                    checked: data_checked_re.captures(&caps[3]).map(|c| &c[1] == "true"),
+                    written: false,
+                });
+            }
+            _ => {
+                if let Some(mut item) = items.pop() {
+                    item.write(&mut out);
+                }
+            }
+        }
+    }
+    out.push_str(&html[last_end..]);
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -238,6 +383,103 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_nested_lists() {
+        let html = "<ul><li><p>Fruit</p><ul><li><p>Apple</p></li><li><p>Pear</p><ol><li>Ripe</li></ol></li></ul></li><li><p>Kale</p></li></ul>";
+        assert_eq!(
+            html_to_markdown(html),
+            "- Fruit\n    - Apple\n    - Pear\n        1. Ripe\n- Kale"
+        );
+    }
+
+    #[test]
+    fn test_nested_lists_round_trip() {
+        use crate::exports::markdown_parser::{parse_markdown, ContentBlock, ListItem};
+
+        let html = r#"<ol start="2"><li>Prepare<ul><li>Wash</li></ul></li><li>Cook</li></ol>"#;
+        let parsed = parse_markdown(&html_to_markdown(html));
+
+        if let ContentBlock::OrderedList { items, start } = &parsed.blocks[0] {
+            assert_eq!(*start, 2);
+            assert_eq!(
+                items,
+                &vec![
+                    ListItem::new("Prepare", 0, Some(2)),
+                    ListItem::new("Wash", 1, None),
+                    ListItem::new("Cook", 0, Some(3)),
+                ]
+            );
+        } else {
+            panic!("Expected ordered list, got {:?}", parsed.blocks);
+        }
+    }
+
+    #[test]
+    fn test_table() {
+        let html = "<table><tbody><tr><th><p>Name</p></th><th><p>Qty</p></th></tr><tr><td><p>Apples</p></td><td><p>3</p></td></tr></tbody></table>";
+        assert_eq!(html_to_markdown(html), "| Name | Qty |\n| --- | --- |\n| Apples | 3 |");
+    }
+
+    #[test]
+    fn test_table_round_trip() {
+        use crate::exports::markdown_parser::{parse_markdown, ContentBlock};
+
+        let html = "<p>Before</p><table><tr><th>Item</th><th>Price</th><th>Notes</th></tr>\
+                    <tr><td><strong>Tea</strong></td><td>$4</td><td>a | b</td></tr>\
+                    <tr><td>Cake</td><td>$6</td></tr></table><p>After</p>";
+        let parsed = parse_markdown(&html_to_markdown(html));
+
+        let table = parsed
+            .blocks
+            .iter()
+            .find_map(|b| if let ContentBlock::Table(t) = b { Some(t) } else { None })
+            .expect("table should survive the round trip");
+        assert_eq!(
+            table.headers.as_deref(),
+            Some(&["Item".to_string(), "Price".to_string(), "Notes".to_string()][..])
+        );
+        assert_eq!(
+            table.rows,
+            vec![
+                vec!["Tea".to_string(), "$4".to_string(), "a | b".to_string()],
+                vec!["Cake".to_string(), "$6".to_string(), String::new()],
+            ]
+        );
+        assert!(matches!(parsed.blocks.first(), Some(ContentBlock::Paragraph { text, .. }) if text == "Before"));
+        assert!(matches!(parsed.blocks.last(), Some(ContentBlock::Paragraph { text, .. }) if text == "After"));
+    }
+
+    #[test]
+    fn test_tiptap_task_list() {
+        let html = r#"<ul data-type="taskList"><li data-checked="true" data-type="taskItem"><label><input type="checkbox" checked="checked"><span></span></label><div><p>Done</p></div></li><li data-checked="false" data-type="taskItem"><label><input type="checkbox"><span></span></label><div><p>Todo</p></div></li></ul>"#;
+        assert_eq!(html_to_markdown(html), "- [x] Done\n- [ ] Todo");
+    }
+
+    #[test]
+    fn test_checkbox_inputs_in_list_items() {
+        let html = r#"<ul><li><input type="checkbox" checked> Ship it</li><li><input type="checkbox"> Write docs</li><li><input checked="false" type="checkbox"> Maybe</li><li>Plain</li></ul>"#;
+        assert_eq!(
+            html_to_markdown(html),
+            "- [x] Ship it\n- [ ] Write docs\n- [ ] Maybe\n- Plain"
+        );
+    }
+
+    #[test]
+    fn test_task_list_round_trip() {
+        use crate::exports::markdown_parser::{parse_markdown, ContentBlock};
+
+        let html = r#"<ul><li data-checked="false"><p>Buy milk</p></li><li data-checked="true"><p>Call mom</p></li></ul>"#;
+        let parsed = parse_markdown(&html_to_markdown(html));
+
+        if let ContentBlock::TaskList { items } = &parsed.blocks[0] {
+            assert_eq!(items.len(), 2);
+            assert_eq!((items[0].text.as_str(), items[0].checked), ("Buy milk", false));
+            assert_eq!((items[1].text.as_str(), items[1].checked), ("Call mom", true));
+        } else {
+            panic!("Expected task list, got {:?}", parsed.blocks);
+        }
+    }
+
     #[test]
     fn test_links() {
         assert_eq!(
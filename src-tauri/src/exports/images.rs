@@ -0,0 +1,236 @@
+//! Image Loading for Exports
+//!
+//! Resolves image references from markdown (absolute paths, vault-relative
+//! paths like `../attachments/photo.png`, or bare attachment filenames) and
+//! decodes them so the PDF/DOCX/PPTX generators can embed them.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use image::DynamicImage;
+
+use crate::vault::{get_attachments_dir, get_current_vault_path};
+
+/// A decoded image ready to be embedded in a document
+#[derive(Debug, Clone)]
+pub struct ExportImage {
+    pub image: DynamicImage,
+}
+
+impl ExportImage {
+    /// Decode an image from raw file bytes (PNG, JPEG, GIF, WebP, ...)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let image = image::load_from_memory(bytes)
+            .map_err(|e| format!("Failed to decode image: {}", e))?;
+        Ok(Self { image })
+    }
+
+    /// Load and decode an image from a markdown image source
+    pub fn load(source: &str) -> Result<Self, String> {
+        let path = resolve_image_path(source)
+            .ok_or_else(|| format!("Image not found: {}", source))?;
+        let bytes = std::fs::read(&path)
+            .map_err(|e| format!("Failed to read image {}: {}", path.display(), e))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Width in pixels
+    pub fn width(&self) -> u32 {
+        self.image.width()
+    }
+
+    /// Height in pixels
+    pub fn height(&self) -> u32 {
+        self.image.height()
+    }
+
+    /// Re-encode as PNG (the format DOCX and PPTX embed)
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        let mut buf = std::io::Cursor::new(Vec::new());
+        self.image
+            .write_to(&mut buf, image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode image: {}", e))?;
+        Ok(buf.into_inner())
+    }
+
+    /// Scale the image to fit within a box, preserving the aspect ratio.
+    /// Never scales up. Returns (width, height) in the box's units.
+    pub fn fit_within(&self, max_width: f32, max_height: f32, units_per_px: f32) -> (f32, f32) {
+        let natural_w = self.width() as f32 * units_per_px;
+        let natural_h = self.height() as f32 * units_per_px;
+        let scale = (max_width / natural_w).min(max_height / natural_h).min(1.0);
+        (natural_w * scale, natural_h * scale)
+    }
+}
+
+/// Whether a source refers to a remote image rather than a local file
+pub fn is_remote_image(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+/// Largest image `download_image` will fetch
+const MAX_DOWNLOAD_BYTES: usize = 20 * 1024 * 1024;
+
+/// How long an image download may take in total
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Download a remote image into the temp directory so it can be embedded
+///
+/// Returns the local path. The download is checked to be a decodable image,
+/// and abandoned if it takes too long or grows past a size limit.
+pub async fn download_image(url: &str) -> Result<PathBuf, String> {
+    download_image_limited(url, MAX_DOWNLOAD_BYTES).await
+}
+
+async fn download_image_limited(url: &str, max_bytes: usize) -> Result<PathBuf, String> {
+    let too_large = || format!("Image is larger than {} MB: {}", max_bytes / (1024 * 1024), url);
+
+    let client = reqwest::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to download image: {}", e))?;
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download image: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Failed to download image: {}", e))?;
+    if response.content_length().is_some_and(|len| len > max_bytes as u64) {
+        return Err(too_large());
+    }
+
+    // The declared length can't be trusted, so the limit is enforced as the body arrives
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to download image: {}", e))?
+    {
+        if bytes.len() + chunk.len() > max_bytes {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    let format = image::guess_format(&bytes)
+        .map_err(|_| format!("URL did not return an image: {}", url))?;
+    ExportImage::from_bytes(&bytes)?;
+
+    let dir = std::env::temp_dir().join("inkling-document-images");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create image directory: {}", e))?;
+    let extension = format.extensions_str().first().copied().unwrap_or("img");
+    let mut file = tempfile::Builder::new()
+        .suffix(&format!(".{}", extension))
+        .tempfile_in(&dir)
+        .map_err(|e| format!("Failed to save downloaded image: {}", e))?;
+    file.write_all(&bytes)
+        .map_err(|e| format!("Failed to save downloaded image: {}", e))?;
+    // Kept until the temp directory is cleaned, since the draft refers to it by path
+    let (_, path) = file
+        .keep()
+        .map_err(|e| format!("Failed to save downloaded image: {}", e))?;
+
+    Ok(path)
+}
+
+/// Resolve an image source to an existing local file
+///
+/// Tries, in order: the path as given (absolute, or `file://` URL), the path
+/// relative to the vault root (with leading `../` stripped, as note markdown
+/// references attachments that way), and the filename inside the vault's
+/// attachments directory.
+pub fn resolve_image_path(source: &str) -> Option<PathBuf> {
+    if is_remote_image(source) {
+        return None;
+    }
+
+    let source = source.strip_prefix("file://").unwrap_or(source);
+    let direct = PathBuf::from(source);
+    if direct.is_absolute() {
+        return direct.is_file().then_some(direct);
+    }
+
+    let mut relative = source;
+    while let Some(rest) = relative.strip_prefix("../").or_else(|| relative.strip_prefix("./")) {
+        relative = rest;
+    }
+
+    if let Some(vault) = get_current_vault_path() {
+        let candidate = vault.join(relative);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+    }
+
+    let filename = Path::new(relative).file_name()?;
+    let candidate = get_attachments_dir().ok()?.join(filename);
+    candidate.is_file().then_some(candidate)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Write a small solid-color PNG for generator tests
+    pub(crate) fn write_test_png(dir: &Path) -> PathBuf {
+        let img = image::RgbImage::from_pixel(8, 4, image::Rgb([200, 40, 40]));
+        let path = dir.join("test-image.png");
+        img.save(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_absolute_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write_test_png(dir.path());
+
+        let image = ExportImage::load(path.to_str().unwrap()).unwrap();
+        assert_eq!((image.width(), image.height()), (8, 4));
+        assert!(image.to_png().unwrap().starts_with(b"\x89PNG"));
+
+        let from_url = ExportImage::load(&format!("file://{}", path.display())).unwrap();
+        assert_eq!(from_url.width(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_download_image_enforces_size_limit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let png = std::fs::read(write_test_png(dir.path())).unwrap();
+
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/image.png", server.server_addr().to_ip().unwrap());
+        std::thread::spawn(move || {
+            for request in server.incoming_requests().take(2) {
+                request.respond(tiny_http::Response::from_data(png.clone())).unwrap();
+            }
+        });
+
+        let path = download_image_limited(&url, 1024 * 1024).await.unwrap();
+        assert_eq!(path.extension().unwrap(), "png");
+        assert_eq!(ExportImage::load(path.to_str().unwrap()).unwrap().width(), 8);
+        std::fs::remove_file(path).unwrap();
+
+        let err = download_image_limited(&url, 16).await.unwrap_err();
+        assert!(err.contains("larger than"), "{}", err);
+    }
+
+    #[test]
+    fn test_missing_and_remote_sources_do_not_resolve() {
+        assert!(resolve_image_path("/definitely/not/here.png").is_none());
+        assert!(resolve_image_path("https://example.com/cat.png").is_none());
+        assert!(ExportImage::load("/definitely/not/here.png").is_err());
+    }
+
+    #[test]
+    fn test_fit_within_preserves_aspect_ratio() {
+        let image = ExportImage { image: DynamicImage::new_rgb8(200, 100) };
+
+        // Too wide: width-constrained
+        assert_eq!(image.fit_within(100.0, 100.0, 1.0), (100.0, 50.0));
+        // Small images are never scaled up
+        assert_eq!(image.fit_within(1000.0, 1000.0, 1.0), (200.0, 100.0));
+    }
+}
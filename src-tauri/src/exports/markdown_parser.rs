@@ -3,6 +3,8 @@
 //! Parses markdown content into structured blocks that can be converted
 //! to various document formats (PDF, DOCX, XLSX, PPTX).
 
+use std::collections::HashMap;
+
 use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
 use serde::{Deserialize, Serialize};
 
@@ -52,23 +54,29 @@ pub enum ContentBlock {
     Heading {
         level: u8,
         text: String,
+        /// Styled runs making up `text` (empty when the block was built by hand)
+        #[serde(default)]
+        runs: Vec<TextRun>,
     },
     /// A paragraph of text
     Paragraph {
         text: String,
+        /// Styled runs making up `text` (empty when the block was built by hand)
+        #[serde(default)]
+        runs: Vec<TextRun>,
     },
     /// A code block
     CodeBlock {
         language: Option<String>,
         code: String,
     },
-    /// An unordered list
+    /// An unordered list (nested sub-lists are flattened into `items` with their depth)
     UnorderedList {
-        items: Vec<String>,
+        items: Vec<ListItem>,
     },
-    /// An ordered list
+    /// An ordered list (nested sub-lists are flattened into `items` with their depth)
     OrderedList {
-        items: Vec<String>,
+        items: Vec<ListItem>,
         start: u64,
     },
     /// A blockquote
@@ -91,6 +99,108 @@ pub enum ContentBlock {
     },
 }
 
+/// A span of inline text sharing one style
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextRun {
+    pub text: String,
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub italic: bool,
+    #[serde(default)]
+    pub strikethrough: bool,
+    /// Inline code
+    #[serde(default)]
+    pub code: bool,
+    /// Link target, when the run is link text
+    #[serde(default)]
+    pub link: Option<String>,
+    /// Footnote number, when the run is a footnote reference like `[1]`
+    #[serde(default)]
+    pub footnote: Option<usize>,
+}
+
+impl TextRun {
+    /// An unstyled run
+    pub fn plain(text: &str) -> Self {
+        Self { text: text.to_string(), ..Default::default() }
+    }
+
+    /// Whether two runs have the same styling (ignoring their text)
+    pub fn same_style(&self, other: &TextRun) -> bool {
+        self.bold == other.bold
+            && self.italic == other.italic
+            && self.strikethrough == other.strikethrough
+            && self.code == other.code
+            && self.link == other.link
+            && self.footnote == other.footnote
+    }
+}
+
+/// The runs to render for a block, falling back to its plain text
+pub fn runs_or_plain(runs: &[TextRun], text: &str) -> Vec<TextRun> {
+    if runs.is_empty() {
+        vec![TextRun::plain(text)]
+    } else {
+        runs.to_vec()
+    }
+}
+
+/// Append text in the given style, merging with the previous run when the style matches
+fn push_run(runs: &mut Vec<TextRun>, text: &str, style: &TextRun) {
+    match runs.last_mut() {
+        Some(last) if last.same_style(style) => last.text.push_str(text),
+        _ => runs.push(TextRun { text: text.to_string(), ..style.clone() }),
+    }
+}
+
+/// Take the runs collected for a block, trimming surrounding whitespace like the block's text
+fn take_runs(runs: &mut Vec<TextRun>) -> Vec<TextRun> {
+    let mut taken = std::mem::take(runs);
+    if let Some(first) = taken.first_mut() {
+        first.text = first.text.trim_start().to_string();
+    }
+    if let Some(last) = taken.last_mut() {
+        last.text = last.text.trim_end().to_string();
+    }
+    taken.retain(|run| !run.text.is_empty());
+    taken
+}
+
+/// Bullet markers by nesting depth, cycling for deeper levels
+///
+/// Limited to characters the PDF generator's built-in fonts can draw.
+const BULLET_MARKERS: [&str; 3] = ["•", "–", "·"];
+
+/// A list item and where it sits in the list's nesting
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListItem {
+    pub text: String,
+    /// Nesting depth (0 for top-level items)
+    pub depth: u8,
+    /// The item's number when its own (sub-)list is numbered
+    pub number: Option<u64>,
+    /// Styled runs making up `text`
+    #[serde(default)]
+    pub runs: Vec<TextRun>,
+}
+
+impl ListItem {
+    /// An item whose text is a single unstyled run
+    pub fn new(text: &str, depth: u8, number: Option<u64>) -> Self {
+        Self { text: text.to_string(), depth, number, runs: vec![TextRun::plain(text)] }
+    }
+
+    /// The bullet or number shown before the item
+    pub fn marker(&self) -> String {
+        match self.number {
+            Some(n) => format!("{}.", n),
+            None => BULLET_MARKERS[self.depth as usize % BULLET_MARKERS.len()].to_string(),
+        }
+    }
+}
+
 /// A task list item
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskItem {
@@ -98,6 +208,17 @@ pub struct TaskItem {
     pub text: String,
 }
 
+/// A footnote definition (`[^label]: text`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FootnoteDefinition {
+    /// The label used in the markdown (`note` for `[^note]`)
+    pub label: String,
+    /// Display number, in order of first reference
+    pub number: usize,
+    pub text: String,
+    pub runs: Vec<TextRun>,
+}
+
 /// Parsed markdown content
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParsedContent {
@@ -105,6 +226,9 @@ pub struct ParsedContent {
     pub title: Option<String>,
     /// Content blocks
     pub blocks: Vec<ContentBlock>,
+    /// Footnote definitions, ordered by number
+    #[serde(default)]
+    pub footnotes: Vec<FootnoteDefinition>,
 }
 
 impl ParsedContent {
@@ -112,9 +236,34 @@ impl ParsedContent {
         Self {
             title: None,
             blocks: Vec::new(),
+            footnotes: Vec::new(),
         }
     }
-    
+
+    /// Append another document's blocks, renumbering its footnotes to follow ours
+    pub fn append(&mut self, mut other: ParsedContent) {
+        let offset = self.footnotes.len();
+        if offset > 0 {
+            for block in &mut other.blocks {
+                match block {
+                    ContentBlock::Heading { text, runs, .. } | ContentBlock::Paragraph { text, runs } => {
+                        offset_footnotes(text, runs, offset);
+                    }
+                    ContentBlock::UnorderedList { items } | ContentBlock::OrderedList { items, .. } => {
+                        for item in items {
+                            offset_footnotes(&mut item.text, &mut item.runs, offset);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            for footnote in &mut other.footnotes {
+                footnote.number += offset;
+            }
+        }
+        self.blocks.extend(other.blocks);
+        self.footnotes.extend(other.footnotes);
+    }
 }
 
 impl Default for ParsedContent {
@@ -123,22 +272,49 @@ impl Default for ParsedContent {
     }
 }
 
+/// Shift the footnote references in a block's runs, keeping its text in sync
+fn offset_footnotes(text: &mut String, runs: &mut [TextRun], offset: usize) {
+    if !runs.iter().any(|run| run.footnote.is_some()) {
+        return;
+    }
+    for run in runs.iter_mut() {
+        if let Some(number) = run.footnote.as_mut() {
+            *number += offset;
+            run.text = format!("[{}]", number);
+        }
+    }
+    *text = runs
+        .iter()
+        .map(|run| if run.code { format!("`{}`", run.text) } else { run.text.clone() })
+        .collect();
+}
+
 /// Parse markdown content into structured blocks
 pub fn parse_markdown(content: &str) -> ParsedContent {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_TABLES);
     options.insert(Options::ENABLE_STRIKETHROUGH);
     options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_FOOTNOTES);
 
     let parser = Parser::new_ext(content, options);
     
     let mut result = ParsedContent::new();
     let mut current_text = String::new();
-    let mut list_items: Vec<String> = Vec::new();
+    // Styled runs for the heading, paragraph, or list item being built
+    let mut current_runs: Vec<TextRun> = Vec::new();
+    let mut run_style = TextRun::default();
+    let mut bold_depth = 0usize;
+    let mut italic_depth = 0usize;
+    let mut strikethrough_depth = 0usize;
+    let mut list_items: Vec<ListItem> = Vec::new();
     let mut task_items: Vec<TaskItem> = Vec::new();
     let mut is_ordered_list = false;
     let mut list_start: u64 = 1;
-    let mut in_list_item = false;
+    // Next item number for each open (sub-)list; None for bullet lists
+    let mut list_stack: Vec<Option<u64>> = Vec::new();
+    // Open items, innermost last: the item's number and whether its text was already emitted
+    let mut item_stack: Vec<(Option<u64>, bool)> = Vec::new();
     let mut _in_heading = false;
     let mut heading_level: u8 = 1;
     let mut in_blockquote = false;
@@ -156,6 +332,11 @@ pub fn parse_markdown(content: &str) -> ParsedContent {
     // Task list state
     let mut current_task_checked = false;
     let mut in_task_list = false;
+
+    // Footnote state: numbers by label (in order of first reference), and the
+    // label and first block index of the definition being parsed
+    let mut footnote_numbers: HashMap<String, usize> = HashMap::new();
+    let mut current_footnote: Option<(String, usize)> = None;
     
     for event in parser {
         match event {
@@ -171,6 +352,7 @@ pub fn parse_markdown(content: &str) -> ParsedContent {
                     HeadingLevel::H6 => 6,
                 };
                 current_text.clear();
+                current_runs.clear();
             }
             Event::End(TagEnd::Heading(_)) => {
                 if !current_text.is_empty() {
@@ -181,36 +363,68 @@ pub fn parse_markdown(content: &str) -> ParsedContent {
                     result.blocks.push(ContentBlock::Heading {
                         level: heading_level,
                         text: current_text.trim().to_string(),
+                        runs: take_runs(&mut current_runs),
                     });
                 }
                 _in_heading = false;
                 current_text.clear();
+                current_runs.clear();
             }
             
             // Paragraphs
             Event::Start(Tag::Paragraph) => {
+                let in_list_item = !item_stack.is_empty();
                 if !in_list_item && !in_blockquote {
                     current_text.clear();
+                    current_runs.clear();
                 }
             }
             Event::End(TagEnd::Paragraph) => {
+                let in_list_item = !item_stack.is_empty();
                 if !in_list_item && !in_blockquote && !current_text.is_empty() {
                     result.blocks.push(ContentBlock::Paragraph {
                         text: current_text.trim().to_string(),
+                        runs: take_runs(&mut current_runs),
                     });
                     current_text.clear();
+                    current_runs.clear();
                 }
             }
             
             // Lists
             Event::Start(Tag::List(start)) => {
-                is_ordered_list = start.is_some();
-                list_start = start.unwrap_or(1);
-                list_items.clear();
-                task_items.clear();
-                in_task_list = false;
+                if list_stack.is_empty() {
+                    is_ordered_list = start.is_some();
+                    list_start = start.unwrap_or(1);
+                    list_items.clear();
+                    task_items.clear();
+                    in_task_list = false;
+                } else if let Some((number, emitted)) = item_stack.last_mut() {
+                    // A sub-list starts: emit the parent item's text first so it precedes its children
+                    if !*emitted {
+                        if in_task_list {
+                            task_items.push(TaskItem {
+                                checked: current_task_checked,
+                                text: current_text.trim().to_string(),
+                            });
+                        } else {
+                            list_items.push(ListItem {
+                                runs: take_runs(&mut current_runs),
+                                ..ListItem::new(current_text.trim(), (list_stack.len() - 1) as u8, *number)
+                            });
+                        }
+                        *emitted = true;
+                        current_text.clear();
+                        current_runs.clear();
+                    }
+                }
+                list_stack.push(start);
             }
             Event::End(TagEnd::List(_)) => {
+                list_stack.pop();
+                if !list_stack.is_empty() {
+                    continue;
+                }
                 if in_task_list && !task_items.is_empty() {
                     result.blocks.push(ContentBlock::TaskList {
                         items: task_items.clone(),
@@ -231,20 +445,34 @@ pub fn parse_markdown(content: &str) -> ParsedContent {
                 task_items.clear();
             }
             Event::Start(Tag::Item) => {
-                in_list_item = true;
+                let number = list_stack.last_mut().and_then(|next| {
+                    let number = *next;
+                    if let Some(n) = next {
+                        *n += 1;
+                    }
+                    number
+                });
+                item_stack.push((number, false));
+                current_task_checked = false;
                 current_text.clear();
+                current_runs.clear();
             }
             Event::End(TagEnd::Item) => {
-                if in_task_list {
+                let (number, emitted) = item_stack.pop().unwrap_or((None, false));
+                // Items with a sub-list were emitted when the sub-list started
+                if !emitted && in_task_list {
                     task_items.push(TaskItem {
                         checked: current_task_checked,
                         text: current_text.trim().to_string(),
                     });
-                } else {
-                    list_items.push(current_text.trim().to_string());
+                } else if !emitted {
+                    list_items.push(ListItem {
+                        runs: take_runs(&mut current_runs),
+                        ..ListItem::new(current_text.trim(), list_stack.len().saturating_sub(1) as u8, number)
+                    });
                 }
-                in_list_item = false;
                 current_text.clear();
+                current_runs.clear();
             }
             
             // Task list checkboxes
@@ -257,6 +485,7 @@ pub fn parse_markdown(content: &str) -> ParsedContent {
             Event::Start(Tag::BlockQuote(_)) => {
                 in_blockquote = true;
                 current_text.clear();
+                current_runs.clear();
             }
             Event::End(TagEnd::BlockQuote(_)) => {
                 if !current_text.is_empty() {
@@ -266,6 +495,7 @@ pub fn parse_markdown(content: &str) -> ParsedContent {
                 }
                 in_blockquote = false;
                 current_text.clear();
+                current_runs.clear();
             }
             
             // Code blocks
@@ -340,6 +570,75 @@ pub fn parse_markdown(content: &str) -> ParsedContent {
                 });
             }
             
+            // Inline styles
+            Event::Start(Tag::Strong) => {
+                bold_depth += 1;
+                run_style.bold = true;
+            }
+            Event::End(TagEnd::Strong) => {
+                bold_depth = bold_depth.saturating_sub(1);
+                run_style.bold = bold_depth > 0;
+            }
+            Event::Start(Tag::Emphasis) => {
+                italic_depth += 1;
+                run_style.italic = true;
+            }
+            Event::End(TagEnd::Emphasis) => {
+                italic_depth = italic_depth.saturating_sub(1);
+                run_style.italic = italic_depth > 0;
+            }
+            Event::Start(Tag::Strikethrough) => {
+                strikethrough_depth += 1;
+                run_style.strikethrough = true;
+            }
+            Event::End(TagEnd::Strikethrough) => {
+                strikethrough_depth = strikethrough_depth.saturating_sub(1);
+                run_style.strikethrough = strikethrough_depth > 0;
+            }
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                run_style.link = Some(dest_url.to_string());
+            }
+            Event::End(TagEnd::Link) => {
+                run_style.link = None;
+            }
+            
+            // Footnotes: a definition's blocks are parsed as usual, then moved
+            // out of the document body into `footnotes`
+            Event::FootnoteReference(label) => {
+                let next = footnote_numbers.len() + 1;
+                let number = *footnote_numbers.entry(label.to_string()).or_insert(next);
+                let marker = format!("[{}]", number);
+                current_text.push_str(&marker);
+                let reference_style = TextRun { footnote: Some(number), ..run_style.clone() };
+                push_run(&mut current_runs, &marker, &reference_style);
+            }
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                current_footnote = Some((label.to_string(), result.blocks.len()));
+            }
+            Event::End(TagEnd::FootnoteDefinition) => {
+                if let Some((label, first_block)) = current_footnote.take() {
+                    let next = footnote_numbers.len() + 1;
+                    let number = *footnote_numbers.entry(label.clone()).or_insert(next);
+                    let mut text = Vec::new();
+                    let mut runs = Vec::new();
+                    for block in result.blocks.drain(first_block..) {
+                        if let ContentBlock::Paragraph { text: block_text, runs: block_runs } = block {
+                            if !runs.is_empty() {
+                                runs.push(TextRun::plain(" "));
+                            }
+                            text.push(block_text);
+                            runs.extend(block_runs);
+                        }
+                    }
+                    result.footnotes.push(FootnoteDefinition {
+                        label,
+                        number,
+                        text: text.join(" "),
+                        runs,
+                    });
+                }
+            }
+
             // Horizontal rule
             Event::Rule => {
                 result.blocks.push(ContentBlock::HorizontalRule);
@@ -353,18 +652,22 @@ pub fn parse_markdown(content: &str) -> ParsedContent {
                     current_cell_text.push_str(&text);
                 } else {
                     current_text.push_str(&text);
+                    push_run(&mut current_runs, &text, &run_style);
                 }
             }
             Event::Code(code) => {
                 current_text.push('`');
                 current_text.push_str(&code);
                 current_text.push('`');
+                let code_style = TextRun { code: true, ..run_style.clone() };
+                push_run(&mut current_runs, &code, &code_style);
             }
             Event::SoftBreak | Event::HardBreak => {
                 if in_code_block {
                     code_content.push('\n');
                 } else if !in_table {
                     current_text.push(' ');
+                    push_run(&mut current_runs, " ", &run_style);
                 }
             }
             
@@ -373,6 +676,7 @@ pub fn parse_markdown(content: &str) -> ParsedContent {
         }
     }
     
+    result.footnotes.sort_by_key(|f| f.number);
     result
 }
 
@@ -404,7 +708,7 @@ mod tests {
         assert_eq!(parsed.title, Some("Hello World".to_string()));
         assert!(!parsed.blocks.is_empty());
         
-        if let ContentBlock::Heading { level, text } = &parsed.blocks[0] {
+        if let ContentBlock::Heading { level, text, .. } = &parsed.blocks[0] {
             assert_eq!(*level, 1);
             assert_eq!(text, "Hello World");
         } else {
@@ -419,7 +723,7 @@ mod tests {
         
         if let ContentBlock::UnorderedList { items } = &parsed.blocks[0] {
             assert_eq!(items.len(), 3);
-            assert_eq!(items[0], "Item 1");
+            assert_eq!(items[0], ListItem::new("Item 1", 0, None));
         } else {
             panic!("Expected unordered list block");
         }
@@ -467,5 +771,206 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_two_level_nested_bullets() {
+        let content = "- Fruit\n  - Apple\n  - Pear\n- Vegetables\n  - Kale";
+        let parsed = parse_markdown(content);
+
+        assert_eq!(parsed.blocks.len(), 1);
+        if let ContentBlock::UnorderedList { items } = &parsed.blocks[0] {
+            assert_eq!(
+                items,
+                &vec![
+                    ListItem::new("Fruit", 0, None),
+                    ListItem::new("Apple", 1, None),
+                    ListItem::new("Pear", 1, None),
+                    ListItem::new("Vegetables", 0, None),
+                    ListItem::new("Kale", 1, None),
+                ]
+            );
+            assert_eq!(items[0].marker(), "•");
+            assert_eq!(items[1].marker(), "–");
+        } else {
+            panic!("Expected unordered list block");
+        }
+    }
+
+    #[test]
+    fn test_parse_three_level_nested_bullets() {
+        let content = "- One\n  - Two\n    - Three\n  - Two again\n- One again";
+        let parsed = parse_markdown(content);
+
+        assert_eq!(parsed.blocks.len(), 1);
+        if let ContentBlock::UnorderedList { items } = &parsed.blocks[0] {
+            let depths: Vec<(&str, u8)> = items.iter().map(|i| (i.text.as_str(), i.depth)).collect();
+            assert_eq!(
+                depths,
+                vec![("One", 0), ("Two", 1), ("Three", 2), ("Two again", 1), ("One again", 0)]
+            );
+        } else {
+            panic!("Expected unordered list block");
+        }
+    }
+
+    #[test]
+    fn test_parse_two_level_nested_numbered_list() {
+        let content = "1. Prepare\n   1. Wash\n   2. Chop\n2. Cook";
+        let parsed = parse_markdown(content);
+
+        assert_eq!(parsed.blocks.len(), 1);
+        if let ContentBlock::OrderedList { items, start } = &parsed.blocks[0] {
+            assert_eq!(*start, 1);
+            assert_eq!(
+                items,
+                &vec![
+                    ListItem::new("Prepare", 0, Some(1)),
+                    ListItem::new("Wash", 1, Some(1)),
+                    ListItem::new("Chop", 1, Some(2)),
+                    ListItem::new("Cook", 0, Some(2)),
+                ]
+            );
+        } else {
+            panic!("Expected ordered list block");
+        }
+    }
+
+    #[test]
+    fn test_parse_three_level_nested_numbered_list() {
+        let content = "3. Top\n   1. Middle\n      - Detail\n      - More\n   2. Middle again\n4. Top again";
+        let parsed = parse_markdown(content);
+
+        assert_eq!(parsed.blocks.len(), 1);
+        if let ContentBlock::OrderedList { items, start } = &parsed.blocks[0] {
+            assert_eq!(*start, 3);
+            let markers: Vec<(String, u8)> = items.iter().map(|i| (i.marker(), i.depth)).collect();
+            assert_eq!(
+                markers,
+                vec![
+                    ("3.".to_string(), 0),
+                    ("1.".to_string(), 1),
+                    ("·".to_string(), 2),
+                    ("·".to_string(), 2),
+                    ("2.".to_string(), 1),
+                    ("4.".to_string(), 0),
+                ]
+            );
+            assert_eq!(items[2].text, "Detail");
+        } else {
+            panic!("Expected ordered list block");
+        }
+    }
+
+    #[test]
+    fn test_parse_inline_formatting_runs() {
+        let content = "Plain **bold** and *italic* with `code` and [a link](https://example.com).";
+        let parsed = parse_markdown(content);
+
+        if let ContentBlock::Paragraph { text, runs } = &parsed.blocks[0] {
+            assert_eq!(text, "Plain bold and italic with `code` and a link.");
+            assert_eq!(
+                runs,
+                &vec![
+                    TextRun::plain("Plain "),
+                    TextRun { bold: true, ..TextRun::plain("bold") },
+                    TextRun::plain(" and "),
+                    TextRun { italic: true, ..TextRun::plain("italic") },
+                    TextRun::plain(" with "),
+                    TextRun { code: true, ..TextRun::plain("code") },
+                    TextRun::plain(" and "),
+                    TextRun { link: Some("https://example.com".to_string()), ..TextRun::plain("a link") },
+                    TextRun::plain("."),
+                ]
+            );
+        } else {
+            panic!("Expected paragraph block");
+        }
+    }
+
+    #[test]
+    fn test_parse_nested_inline_styles_in_headings_and_lists() {
+        let content = "## A ***bold italic*** heading\n\n- Item with **[bold link](https://example.com)**";
+        let parsed = parse_markdown(content);
+
+        if let ContentBlock::Heading { runs, .. } = &parsed.blocks[0] {
+            assert_eq!(runs[1], TextRun { bold: true, italic: true, ..TextRun::plain("bold italic") });
+            assert_eq!(runs[2], TextRun::plain(" heading"));
+        } else {
+            panic!("Expected heading block");
+        }
+
+        if let ContentBlock::UnorderedList { items } = &parsed.blocks[1] {
+            assert_eq!(
+                items[0].runs,
+                vec![
+                    TextRun::plain("Item with "),
+                    TextRun {
+                        bold: true,
+                        link: Some("https://example.com".to_string()),
+                        ..TextRun::plain("bold link")
+                    },
+                ]
+            );
+        } else {
+            panic!("Expected unordered list block");
+        }
+    }
+
+    #[test]
+    fn test_parse_footnotes_pairs_references_with_definitions() {
+        let content = "Claim one[^src] and two[^2].\n\nAgain[^src].\n\n[^2]: Second source.\n[^src]: First *source*.";
+        let parsed = parse_markdown(content);
+
+        // Definitions leave the body and are numbered by first reference
+        assert_eq!(parsed.blocks.len(), 2);
+        assert_eq!(parsed.footnotes.len(), 2);
+        assert_eq!(parsed.footnotes[0].label, "src");
+        assert_eq!(parsed.footnotes[0].number, 1);
+        assert_eq!(parsed.footnotes[0].text, "First source.");
+        assert_eq!(parsed.footnotes[0].runs[1], TextRun { italic: true, ..TextRun::plain("source") });
+        assert_eq!(parsed.footnotes[1].label, "2");
+        assert_eq!(parsed.footnotes[1].number, 2);
+
+        if let ContentBlock::Paragraph { text, runs } = &parsed.blocks[0] {
+            assert_eq!(text, "Claim one[1] and two[2].");
+            assert_eq!(runs[1], TextRun { footnote: Some(1), ..TextRun::plain("[1]") });
+            assert_eq!(runs[3], TextRun { footnote: Some(2), ..TextRun::plain("[2]") });
+        } else {
+            panic!("Expected paragraph block");
+        }
+        if let ContentBlock::Paragraph { runs, .. } = &parsed.blocks[1] {
+            // Repeated references share a number
+            assert_eq!(runs[1].footnote, Some(1));
+        } else {
+            panic!("Expected paragraph block");
+        }
+    }
+
+    #[test]
+    fn test_parse_undefined_footnote_reference_stays_text() {
+        let parsed = parse_markdown("See this[^missing].");
+
+        assert!(parsed.footnotes.is_empty());
+        if let ContentBlock::Paragraph { text, runs } = &parsed.blocks[0] {
+            assert_eq!(text, "See this[^missing].");
+            assert!(runs.iter().all(|r| r.footnote.is_none()));
+        } else {
+            panic!("Expected paragraph block");
+        }
+    }
+
+    #[test]
+    fn test_append_renumbers_footnotes() {
+        let mut combined = parse_markdown("A[^a].\n\n[^a]: First.");
+        combined.append(parse_markdown("B[^b].\n\n[^b]: Second."));
+
+        assert_eq!(combined.footnotes.iter().map(|f| f.number).collect::<Vec<_>>(), vec![1, 2]);
+        if let ContentBlock::Paragraph { text, runs } = &combined.blocks[1] {
+            assert_eq!(text, "B[2].");
+            assert_eq!(runs[1], TextRun { footnote: Some(2), ..TextRun::plain("[2]") });
+        } else {
+            panic!("Expected paragraph block");
+        }
+    }
+
 }
 
@@ -5,8 +5,12 @@
 //! - DOCX (via docx-rs)
 //! - XLSX (via rust_xlsxwriter)
 //! - PPTX (via zip + quick-xml)
+//! - Markdown (chat conversations)
 
+pub mod conversation;
+pub mod highlight;
 pub mod html_to_markdown;
+pub mod images;
 pub mod markdown_parser;
 pub mod pdf_generator;
 pub mod docx_generator;
@@ -17,9 +21,13 @@ pub mod document_builder;
 // Re-export document builder types and functions
 pub use document_builder::*;
 
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::db::exports::ExportRenderOptions;
+
 #[derive(Error, Debug)]
 #[allow(clippy::enum_variant_names)]
 pub enum ExportError {
@@ -38,7 +46,7 @@ pub enum ExportError {
 }
 
 /// Options for PDF export
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PdfExportOptions {
     /// Whether to include images
@@ -50,10 +58,24 @@ pub struct PdfExportOptions {
     /// Paper size (a4, letter, etc.)
     #[serde(default = "default_paper_size")]
     pub paper_size: String,
+    /// Whether to syntax-highlight code blocks with a known language
+    #[serde(default = "default_true")]
+    pub highlight_code: bool,
+}
+
+impl Default for PdfExportOptions {
+    fn default() -> Self {
+        Self {
+            include_images: false,
+            page_break_between_notes: false,
+            paper_size: default_paper_size(),
+            highlight_code: true,
+        }
+    }
 }
 
 /// Options for DOCX export
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DocxExportOptions {
     /// Whether to include images
@@ -62,6 +84,72 @@ pub struct DocxExportOptions {
     /// Whether to add page breaks between notes
     #[serde(default)]
     pub page_break_between_notes: bool,
+    /// Whether to syntax-highlight code blocks with a known language
+    #[serde(default = "default_true")]
+    pub highlight_code: bool,
+}
+
+impl Default for DocxExportOptions {
+    fn default() -> Self {
+        Self {
+            include_images: false,
+            page_break_between_notes: false,
+            highlight_code: true,
+        }
+    }
+}
+
+impl PdfExportOptions {
+    /// Rebuild the options an export was recorded with
+    pub fn from_recorded(recorded: &ExportRenderOptions) -> Self {
+        let defaults = Self::default();
+        Self {
+            // Exports recorded without it came from requests that left it to serde's default
+            include_images: recorded.include_images.unwrap_or_else(default_true),
+            page_break_between_notes: recorded
+                .page_break_between_notes
+                .unwrap_or(defaults.page_break_between_notes),
+            paper_size: recorded.paper_size.clone().unwrap_or(defaults.paper_size),
+            highlight_code: recorded.highlight_code.unwrap_or(defaults.highlight_code),
+        }
+    }
+
+    /// The options to record on the export
+    pub fn to_recorded(&self) -> ExportRenderOptions {
+        ExportRenderOptions {
+            include_images: Some(self.include_images),
+            page_break_between_notes: Some(self.page_break_between_notes),
+            highlight_code: Some(self.highlight_code),
+            paper_size: Some(self.paper_size.clone()),
+            include_thinking: None,
+        }
+    }
+}
+
+impl DocxExportOptions {
+    /// Rebuild the options an export was recorded with
+    pub fn from_recorded(recorded: &ExportRenderOptions) -> Self {
+        let defaults = Self::default();
+        Self {
+            // Exports recorded without it came from requests that left it to serde's default
+            include_images: recorded.include_images.unwrap_or_else(default_true),
+            page_break_between_notes: recorded
+                .page_break_between_notes
+                .unwrap_or(defaults.page_break_between_notes),
+            highlight_code: recorded.highlight_code.unwrap_or(defaults.highlight_code),
+        }
+    }
+
+    /// The options to record on the export
+    pub fn to_recorded(&self) -> ExportRenderOptions {
+        ExportRenderOptions {
+            include_images: Some(self.include_images),
+            page_break_between_notes: Some(self.page_break_between_notes),
+            highlight_code: Some(self.highlight_code),
+            paper_size: None,
+            include_thinking: None,
+        }
+    }
 }
 
 /// Options for XLSX export
@@ -135,6 +223,27 @@ pub fn sanitize_filename(title: &str) -> String {
     result.trim().to_string()
 }
 
+/// Markdown link to an exported file
+///
+/// Files inside the vault are linked by their vault-relative path (e.g.
+/// `exports/report.pdf`); files in an exports directory outside the vault
+/// are linked by absolute path.
+pub fn export_markdown_link(title: &str, output_path: &Path) -> String {
+    markdown_link_in(title, output_path, crate::vault::get_current_vault_path().as_deref())
+}
+
+fn markdown_link_in(title: &str, output_path: &Path, vault: Option<&Path>) -> String {
+    let target = match vault.and_then(|v| output_path.strip_prefix(v).ok()) {
+        Some(relative) => relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        None => output_path.to_string_lossy().to_string(),
+    };
+    format!("[{}]({})", title, target)
+}
+
 /// Generate a dated filename
 pub fn generate_dated_filename(title: &str, extension: &str) -> String {
     let safe_title = sanitize_filename(title);
@@ -154,6 +263,22 @@ mod tests {
         assert_eq!(sanitize_filename("  Multiple   Spaces  "), "Multiple Spaces");
     }
 
+    #[test]
+    fn test_export_markdown_link_follows_exports_dir() {
+        let vault = Path::new("/home/me/vault");
+        assert_eq!(
+            markdown_link_in("Report", &vault.join("exports").join("Report.pdf"), Some(vault)),
+            "[Report](exports/Report.pdf)"
+        );
+        // An exports directory outside the vault is linked by absolute path
+        let external = Path::new("/mnt/usb/exports/Report.pdf");
+        assert_eq!(
+            markdown_link_in("Report", external, Some(vault)),
+            "[Report](/mnt/usb/exports/Report.pdf)"
+        );
+        assert_eq!(markdown_link_in("Report", external, None), "[Report](/mnt/usb/exports/Report.pdf)");
+    }
+
     #[test]
     fn test_generate_dated_filename() {
         let filename = generate_dated_filename("Test Export", "pdf");
@@ -2,14 +2,17 @@
 //!
 //! Generates PDF documents from parsed markdown content using printpdf.
 
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufWriter;
 use std::path::Path;
 
 use printpdf::*;
 
-use super::markdown_parser::{ContentBlock, ParsedContent};
-use super::{ExportError, ExportResult, PdfExportOptions};
+use super::highlight::{highlight_code, CodeSpan};
+use super::images::ExportImage;
+use super::markdown_parser::{runs_or_plain, ContentBlock, FootnoteDefinition, ParsedContent, TextRun};
+use super::{export_markdown_link, ExportError, ExportResult, PdfExportOptions};
 
 /// Page dimensions (A4)
 const PAGE_WIDTH_MM: f32 = 210.0;
@@ -25,6 +28,7 @@ const MARGIN_BOTTOM: f32 = 25.0;
 const HEADING_SIZES: [f32; 6] = [24.0, 20.0, 16.0, 14.0, 13.0, 12.0];
 const BODY_SIZE: f32 = 11.0;
 const CODE_SIZE: f32 = 10.0;
+const FOOTNOTE_SIZE: f32 = 9.0;
 
 /// Line height multiplier
 const LINE_HEIGHT: f32 = 1.4;
@@ -32,15 +36,32 @@ const LINE_HEIGHT: f32 = 1.4;
 /// Content width in mm
 const CONTENT_WIDTH: f32 = PAGE_WIDTH_MM - MARGIN_LEFT - MARGIN_RIGHT;
 
+/// Images are capped at half a page tall so they don't crowd out the text
+const MAX_IMAGE_HEIGHT: f32 = (PAGE_HEIGHT_MM - MARGIN_TOP - MARGIN_BOTTOM) / 2.0;
+
+/// Resolution images are placed at (1px = 1/IMAGE_DPI inch before scaling)
+const IMAGE_DPI: f32 = 150.0;
+
+/// Color of link text (RGB)
+const LINK_COLOR: (f32, f32, f32) = (0.02, 0.39, 0.76);
+
 /// PDF Writer state
 struct PdfWriter {
     doc: PdfDocumentReference,
     current_page: PdfPageIndex,
     current_layer: PdfLayerIndex,
+    /// 1-based number of the current page
+    page_number: usize,
     y_position: Mm,
     font_regular: IndirectFontRef,
     font_bold: IndirectFontRef,
     font_italic: IndirectFontRef,
+    font_bold_italic: IndirectFontRef,
+    font_code: IndirectFontRef,
+    /// Page (1-based) each footnote was first referenced on
+    footnote_pages: HashMap<usize, usize>,
+    /// Whether code blocks are syntax-highlighted
+    highlight_code: bool,
 }
 
 impl PdfWriter {
@@ -59,15 +80,24 @@ impl PdfWriter {
             .map_err(|e| format!("Failed to add bold font: {}", e))?;
         let font_italic = doc.add_builtin_font(BuiltinFont::HelveticaOblique)
             .map_err(|e| format!("Failed to add italic font: {}", e))?;
+        let font_bold_italic = doc.add_builtin_font(BuiltinFont::HelveticaBoldOblique)
+            .map_err(|e| format!("Failed to add bold italic font: {}", e))?;
+        let font_code = doc.add_builtin_font(BuiltinFont::Courier)
+            .map_err(|e| format!("Failed to add code font: {}", e))?;
 
         Ok(PdfWriter {
             doc,
             current_page: page_idx,
             current_layer: layer_idx,
+            page_number: 1,
             y_position: Mm(PAGE_HEIGHT_MM - MARGIN_TOP),
             font_regular,
             font_bold,
             font_italic,
+            font_bold_italic,
+            font_code,
+            footnote_pages: HashMap::new(),
+            highlight_code: false,
         })
     }
 
@@ -87,6 +117,7 @@ impl PdfWriter {
         );
         self.current_page = page_idx;
         self.current_layer = layer_idx;
+        self.page_number += 1;
         self.y_position = Mm(PAGE_HEIGHT_MM - MARGIN_TOP);
         log::debug!("[PDFGenerator] Created new page");
     }
@@ -119,16 +150,124 @@ impl PdfWriter {
         self.y_position = Mm(self.y_position.0 - line_height_mm);
     }
 
-    /// Write text with word wrapping
-    fn write_wrapped_text(&mut self, text: &str, font: &IndirectFontRef, size_pt: f32) {
-        let chars_per_line = estimate_chars_per_line(size_pt);
-        let lines = wrap_text(text, chars_per_line);
+    /// Pick the font for a run; `bold` makes every run bold (for headings)
+    fn run_font(&self, run: &TextRun, bold: bool) -> &IndirectFontRef {
+        match (run.code, run.bold || bold, run.italic) {
+            (true, _, _) => &self.font_code,
+            (false, true, true) => &self.font_bold_italic,
+            (false, true, false) => &self.font_bold,
+            (false, false, true) => &self.font_italic,
+            (false, false, false) => &self.font_regular,
+        }
+    }
+
+    /// Write styled runs with word wrapping
+    ///
+    /// `first_prefix` starts the first line (e.g. a list marker) and
+    /// `continuation_prefix` indents the wrapped lines after it.
+    fn write_runs(
+        &mut self,
+        runs: &[TextRun],
+        size_pt: f32,
+        bold: bool,
+        first_prefix: &str,
+        continuation_prefix: &str,
+    ) {
+        let chars_per_line = estimate_chars_per_line(size_pt)
+            .saturating_sub(first_prefix.chars().count())
+            .max(20);
         let line_height_mm = (size_pt / 72.0 * 25.4) * LINE_HEIGHT;
 
-        for line in lines {
+        for (i, line) in wrap_runs(runs, chars_per_line).iter().enumerate() {
             self.ensure_space(line_height_mm);
-            self.write_text(&line, font, size_pt);
+
+            // One text section per line, so each run continues where the last ended
+            let layer = self.current_layer();
+            layer.begin_text_section();
+            layer.set_text_cursor(Mm(MARGIN_LEFT), self.y_position);
+
+            let prefix = if i == 0 { first_prefix } else { continuation_prefix };
+            if !prefix.is_empty() {
+                layer.set_font(&self.font_regular, size_pt);
+                layer.write_text(prefix, &self.font_regular);
+            }
+
+            for run in line {
+                if let Some(number) = run.footnote {
+                    self.footnote_pages.entry(number).or_insert(self.page_number);
+                }
+                let font = self.run_font(run, bold);
+                layer.set_font(font, size_pt);
+                let (r, g, b) = if run.link.is_some() { LINK_COLOR } else { (0.0, 0.0, 0.0) };
+                layer.set_fill_color(Color::Rgb(Rgb::new(r, g, b, None)));
+                layer.write_text(run.text.clone(), font);
+            }
+
+            layer.end_text_section();
+            layer.set_fill_color(Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None)));
+            self.y_position = Mm(self.y_position.0 - line_height_mm);
+        }
+    }
+
+    /// Write one line of highlighted code, coloring each span
+    fn write_code_spans(&mut self, spans: &[CodeSpan]) {
+        let line_height_mm = (CODE_SIZE / 72.0 * 25.4) * LINE_HEIGHT;
+        self.ensure_space(line_height_mm);
+
+        let layer = self.current_layer();
+        layer.begin_text_section();
+        layer.set_text_cursor(Mm(MARGIN_LEFT), self.y_position);
+        layer.set_font(&self.font_code, CODE_SIZE);
+        layer.write_text("  ", &self.font_code);
+        for span in spans {
+            let (r, g, b) = span.color;
+            layer.set_fill_color(Color::Rgb(Rgb::new(
+                r as f32 / 255.0,
+                g as f32 / 255.0,
+                b as f32 / 255.0,
+                None,
+            )));
+            layer.write_text(span.text.clone(), &self.font_code);
         }
+        layer.end_text_section();
+        layer.set_fill_color(Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None)));
+
+        self.y_position = Mm(self.y_position.0 - line_height_mm);
+    }
+
+    /// Draw an image at the current position, scaled to fit the content width
+    fn write_image(&mut self, image: &ExportImage) {
+        let mm_per_px = 25.4 / IMAGE_DPI;
+        let (width_mm, height_mm) = image.fit_within(CONTENT_WIDTH, MAX_IMAGE_HEIGHT, mm_per_px);
+        self.ensure_space(height_mm);
+
+        let rgb = image.image.to_rgb8();
+        let xobject = ImageXObject {
+            width: Px(image.width() as usize),
+            height: Px(image.height() as usize),
+            color_space: ColorSpace::Rgb,
+            bits_per_component: ColorBits::Bit8,
+            interpolate: true,
+            image_data: rgb.into_raw(),
+            image_filter: None,
+            smask: None,
+            clipping_bbox: None,
+        };
+
+        // At IMAGE_DPI the image is natural size; scale down from there
+        let scale = width_mm / (image.width() as f32 * mm_per_px);
+        self.y_position = Mm(self.y_position.0 - height_mm);
+        Image::from(xobject).add_to_layer(
+            self.current_layer(),
+            ImageTransform {
+                translate_x: Some(Mm(MARGIN_LEFT)),
+                translate_y: Some(self.y_position),
+                scale_x: Some(scale),
+                scale_y: Some(scale),
+                dpi: Some(IMAGE_DPI),
+                ..Default::default()
+            },
+        );
     }
 
     /// Add vertical space
@@ -223,6 +362,53 @@ fn wrap_text(text: &str, max_chars: usize) -> Vec<String> {
     lines
 }
 
+/// Word wrapping for styled runs
+///
+/// Like `wrap_text`, but each line keeps the styling of the words on it.
+/// Adjacent words with the same style are merged into one run.
+fn wrap_runs(runs: &[TextRun], max_chars: usize) -> Vec<Vec<TextRun>> {
+    let mut lines: Vec<Vec<TextRun>> = vec![Vec::new()];
+    let mut line_len = 0;
+    let mut pending_space = false;
+
+    for run in runs {
+        for (i, word) in run.text.split(char::is_whitespace).enumerate() {
+            if i > 0 {
+                pending_space = true;
+            }
+            if word.is_empty() {
+                continue;
+            }
+
+            // Break words longer than a line into line-sized chunks
+            let chars: Vec<char> = word.chars().collect();
+            for chunk in chars.chunks(max_chars) {
+                let space = pending_space && line_len > 0;
+                if line_len > 0 && line_len + usize::from(space) + chunk.len() > max_chars {
+                    lines.push(Vec::new());
+                    line_len = 0;
+                }
+
+                let mut text = String::new();
+                if pending_space && line_len > 0 {
+                    text.push(' ');
+                }
+                text.extend(chunk);
+                line_len += text.chars().count();
+                pending_space = false;
+
+                let line = lines.last_mut().expect("lines is never empty");
+                match line.last_mut() {
+                    Some(last) if last.same_style(run) => last.text.push_str(&text),
+                    _ => line.push(TextRun { text, ..run.clone() }),
+                }
+            }
+        }
+    }
+
+    lines
+}
+
 /// Strip markdown formatting for plain text
 fn strip_markdown(text: &str) -> String {
     let mut result = text.to_string();
@@ -255,7 +441,7 @@ pub fn generate_pdf(
     content: &ParsedContent,
     title: &str,
     output_path: &Path,
-    _options: &PdfExportOptions,
+    options: &PdfExportOptions,
 ) -> Result<ExportResult, ExportError> {
     log::info!(
         "[PDFGenerator] Starting PDF generation: title='{}', blocks={}",
@@ -265,6 +451,7 @@ pub fn generate_pdf(
 
     let mut writer = PdfWriter::new(title)
         .map_err(ExportError::PdfError)?;
+    writer.highlight_code = options.highlight_code;
 
     // Render each block
     for (i, block) in content.blocks.iter().enumerate() {
@@ -276,6 +463,7 @@ pub fn generate_pdf(
         );
         render_block(&mut writer, block);
     }
+    render_footnotes(&mut writer, &content.footnotes);
 
     // Save
     let file_size = writer.save(output_path)
@@ -290,16 +478,16 @@ pub fn generate_pdf(
 
     Ok(ExportResult {
         path: output_path.to_string_lossy().to_string(),
-        filename: filename.clone(),
+        filename,
         file_size,
-        markdown_link: format!("[{}](exports/{})", title, filename),
+        markdown_link: export_markdown_link(title, output_path),
     })
 }
 
 /// Render a content block
 fn render_block(writer: &mut PdfWriter, block: &ContentBlock) {
     match block {
-        ContentBlock::Heading { level, text } => {
+        ContentBlock::Heading { level, text, runs } => {
             let size = HEADING_SIZES.get(*level as usize - 1).copied().unwrap_or(12.0);
             let line_height_mm = (size / 72.0 * 25.4) * LINE_HEIGHT;
 
@@ -310,14 +498,12 @@ fn render_block(writer: &mut PdfWriter, block: &ContentBlock) {
 
             writer.ensure_space(line_height_mm + 4.0);
             
-            let font = writer.font_bold.clone();
-            writer.write_text(&strip_markdown(text), &font, size);
+            writer.write_runs(&runs_or_plain(runs, text), size, true, "", "");
             writer.add_space(2.0);
         }
 
-        ContentBlock::Paragraph { text } => {
-            let font = writer.font_regular.clone();
-            writer.write_wrapped_text(&strip_markdown(text), &font, BODY_SIZE);
+        ContentBlock::Paragraph { text, runs } => {
+            writer.write_runs(&runs_or_plain(runs, text), BODY_SIZE, false, "", "");
             writer.add_space(3.0);
         }
 
@@ -327,67 +513,48 @@ fn render_block(writer: &mut PdfWriter, block: &ContentBlock) {
                 writer.write_text(&format!("[{}]", lang), &font, CODE_SIZE - 1.0);
             }
 
-            let font = writer.font_regular.clone();
-            for line in code.lines() {
-                let line_height_mm = (CODE_SIZE / 72.0 * 25.4) * LINE_HEIGHT;
-                writer.ensure_space(line_height_mm);
-                writer.write_text(&format!("  {}", line), &font, CODE_SIZE);
-            }
-            writer.add_space(3.0);
-        }
-
-        ContentBlock::UnorderedList { items } => {
-            let font = writer.font_regular.clone();
-            for item in items {
-                // Skip empty items
-                let trimmed = item.trim();
-                if trimmed.is_empty() {
-                    continue;
+            let highlighted = if writer.highlight_code {
+                highlight_code(code, language.as_deref())
+            } else {
+                None
+            };
+            match highlighted {
+                Some(lines) => {
+                    for spans in &lines {
+                        writer.write_code_spans(spans);
+                    }
                 }
-                
-                let chars_per_line = estimate_chars_per_line(BODY_SIZE) - 4;
-                let lines = wrap_text(&strip_markdown(trimmed), chars_per_line);
-                
-                for (i, line) in lines.iter().enumerate() {
-                    let line_height_mm = (BODY_SIZE / 72.0 * 25.4) * LINE_HEIGHT;
-                    writer.ensure_space(line_height_mm);
-                    
-                    let bullet_text = if i == 0 {
-                        format!("  •  {}", line)
-                    } else {
-                        format!("     {}", line)
-                    };
-                    writer.write_text(&bullet_text, &font, BODY_SIZE);
+                None => {
+                    let font = writer.font_code.clone();
+                    for line in code.lines() {
+                        let line_height_mm = (CODE_SIZE / 72.0 * 25.4) * LINE_HEIGHT;
+                        writer.ensure_space(line_height_mm);
+                        writer.write_text(&format!("  {}", line), &font, CODE_SIZE);
+                    }
                 }
             }
             writer.add_space(3.0);
         }
 
-        ContentBlock::OrderedList { items, start } => {
-            let font = writer.font_regular.clone();
-            let mut num = *start;
+        ContentBlock::UnorderedList { items } | ContentBlock::OrderedList { items, .. } => {
             for item in items {
                 // Skip empty items
-                let trimmed = item.trim();
-                if trimmed.is_empty() {
+                if item.text.trim().is_empty() {
                     continue;
                 }
-                
-                let chars_per_line = estimate_chars_per_line(BODY_SIZE) - 6;
-                let lines = wrap_text(&strip_markdown(trimmed), chars_per_line);
-                
-                for (j, line) in lines.iter().enumerate() {
-                    let line_height_mm = (BODY_SIZE / 72.0 * 25.4) * LINE_HEIGHT;
-                    writer.ensure_space(line_height_mm);
-                    
-                    let numbered_text = if j == 0 {
-                        format!("  {}.  {}", num, line)
-                    } else {
-                        format!("      {}", line)
-                    };
-                    writer.write_text(&numbered_text, &font, BODY_SIZE);
-                }
-                num += 1;
+
+                // Nested items are indented one step per level
+                let indent = "    ".repeat(item.depth as usize);
+                let marker = item.marker();
+                let first_prefix = format!("{}  {}  ", indent, marker);
+                let continuation_prefix = format!("{}{}", indent, " ".repeat(marker.chars().count() + 4));
+                writer.write_runs(
+                    &runs_or_plain(&item.runs, &item.text),
+                    BODY_SIZE,
+                    false,
+                    &first_prefix,
+                    &continuation_prefix,
+                );
             }
             writer.add_space(3.0);
         }
@@ -413,9 +580,16 @@ fn render_block(writer: &mut PdfWriter, block: &ContentBlock) {
         }
 
         ContentBlock::Image { url, alt, .. } => {
-            let font = writer.font_italic.clone();
-            let alt_display = if alt.is_empty() { "Image" } else { alt };
-            writer.write_text(&format!("[Image: {} - {}]", alt_display, url), &font, BODY_SIZE);
+            match ExportImage::load(url) {
+                Ok(image) => writer.write_image(&image),
+                Err(e) => {
+                    // Fall back to a placeholder so the export still succeeds
+                    log::warn!("[PDFGenerator] Could not embed image {}: {}", url, e);
+                    let font = writer.font_italic.clone();
+                    let alt_display = if alt.is_empty() { "Image" } else { alt };
+                    writer.write_text(&format!("[Image: {} - {}]", alt_display, url), &font, BODY_SIZE);
+                }
+            }
             writer.add_space(3.0);
         }
 
@@ -530,6 +704,31 @@ fn render_block(writer: &mut PdfWriter, block: &ContentBlock) {
     }
 }
 
+/// Render footnotes as a "Notes" section at the end of the document
+///
+/// The flowing layout can't reserve room at the bottom of each page, so the
+/// notes are collected at the end, each pointing back to the page that
+/// first referenced it.
+fn render_footnotes(writer: &mut PdfWriter, footnotes: &[FootnoteDefinition]) {
+    if footnotes.is_empty() {
+        return;
+    }
+
+    render_block(writer, &ContentBlock::HorizontalRule);
+    writer.write_runs(&[TextRun::plain("Notes")], BODY_SIZE, true, "", "");
+    writer.add_space(1.0);
+
+    for footnote in footnotes {
+        let mut runs = runs_or_plain(&footnote.runs, &footnote.text);
+        if let Some(page) = writer.footnote_pages.get(&footnote.number) {
+            runs.push(TextRun { italic: true, ..TextRun::plain(&format!(" (p. {})", page)) });
+        }
+        let prefix = format!("[{}]  ", footnote.number);
+        let continuation = " ".repeat(prefix.len() + 1);
+        writer.write_runs(&runs, FOOTNOTE_SIZE, false, &prefix, &continuation);
+    }
+}
+
 /// Generate a PDF from multiple notes
 pub fn generate_pdf_from_notes(
     notes: &[(String, String)],
@@ -548,10 +747,10 @@ pub fn generate_pdf_from_notes(
         combined.blocks.push(ContentBlock::Heading {
             level: 1,
             text: note_title.clone(),
+            runs: Vec::new(),
         });
 
-        let parsed = super::markdown_parser::parse_markdown(content);
-        combined.blocks.extend(parsed.blocks);
+        combined.append(super::markdown_parser::parse_markdown(content));
     }
 
     generate_pdf(&combined, document_title, output_path, options)
@@ -560,6 +759,7 @@ pub fn generate_pdf_from_notes(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::exports::markdown_parser::ListItem;
     use tempfile::TempDir;
 
     #[test]
@@ -571,15 +771,17 @@ mod tests {
         content.blocks.push(ContentBlock::Heading {
             level: 1,
             text: "Test Document".to_string(),
+            runs: Vec::new(),
         });
         content.blocks.push(ContentBlock::Paragraph {
             text: "This is a test paragraph with some text that should appear in the PDF.".to_string(),
+            runs: Vec::new(),
         });
         content.blocks.push(ContentBlock::UnorderedList {
             items: vec![
-                "First item".to_string(),
-                "Second item".to_string(),
-                "Third item".to_string(),
+                ListItem::new("First item", 0, None),
+                ListItem::new("Second item", 0, None),
+                ListItem::new("Third item", 0, None),
             ],
         });
 
@@ -594,6 +796,69 @@ mod tests {
         println!("Generated PDF: {} bytes", export_result.file_size);
     }
 
+    #[test]
+    fn test_generate_pdf_embeds_image() {
+        let temp_dir = TempDir::new().unwrap();
+        let image_path = crate::exports::images::tests::write_test_png(temp_dir.path());
+        let output_path = temp_dir.path().join("image.pdf");
+
+        let mut content = ParsedContent::new();
+        content.blocks.push(ContentBlock::Image {
+            url: image_path.to_string_lossy().to_string(),
+            alt: "Chart".to_string(),
+            title: None,
+        });
+
+        generate_pdf(&content, "Images", &output_path, &PdfExportOptions::default()).unwrap();
+
+        let bytes = std::fs::read(&output_path).unwrap();
+        assert!(contains_bytes(&bytes, b"/Image"), "PDF should contain an image XObject");
+    }
+
+    #[test]
+    fn test_generate_pdf_missing_image_falls_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_path = temp_dir.path().join("missing.pdf");
+
+        let mut content = ParsedContent::new();
+        content.blocks.push(ContentBlock::Image {
+            url: "/no/such/image.png".to_string(),
+            alt: "Missing".to_string(),
+            title: None,
+        });
+
+        let result = generate_pdf(&content, "Images", &output_path, &PdfExportOptions::default());
+        assert!(result.is_ok());
+        let bytes = std::fs::read(&output_path).unwrap();
+        assert!(!contains_bytes(&bytes, b"/Image"));
+    }
+
+    #[test]
+    fn test_footnotes_record_reference_pages() {
+        let content = super::super::markdown_parser::parse_markdown(
+            "Intro[^a].\n\n[^a]: A note with **bold** text.",
+        );
+        let mut writer = PdfWriter::new("Footnotes").unwrap();
+        for block in &content.blocks {
+            render_block(&mut writer, block);
+        }
+        assert_eq!(writer.footnote_pages.get(&1), Some(&1));
+
+        // The notes section follows the body without breaking the layout
+        let y_before = writer.y_position.0;
+        render_footnotes(&mut writer, &content.footnotes);
+        assert!(writer.y_position.0 < y_before);
+
+        let temp_dir = TempDir::new().unwrap();
+        let output_path = temp_dir.path().join("footnotes.pdf");
+        generate_pdf(&content, "Footnotes", &output_path, &PdfExportOptions::default()).unwrap();
+        assert!(output_path.exists());
+    }
+
+    fn contains_bytes(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
     #[test]
     fn test_wrap_text() {
         let text = "This is a long line of text that should be wrapped at the specified width";
@@ -615,9 +880,11 @@ mod tests {
         content.blocks.push(ContentBlock::Heading {
             level: 1,
             text: "Document with Table".to_string(),
+            runs: Vec::new(),
         });
         content.blocks.push(ContentBlock::Paragraph {
             text: "Here is a data table:".to_string(),
+            runs: Vec::new(),
         });
         
         // Create a table
@@ -654,14 +921,15 @@ mod tests {
         content.blocks.push(ContentBlock::Heading {
             level: 1,
             text: "List with Empty Items".to_string(),
+            runs: Vec::new(),
         });
         content.blocks.push(ContentBlock::UnorderedList {
             items: vec![
-                "First item".to_string(),
-                "".to_string(),  // Empty item - should be skipped
-                "Second item".to_string(),
-                "   ".to_string(),  // Whitespace only - should be skipped
-                "Third item".to_string(),
+                ListItem::new("First item", 0, None),
+                ListItem::new("", 0, None),  // Empty item - should be skipped
+                ListItem::new("Second item", 0, None),
+                ListItem::new("   ", 0, None),  // Whitespace only - should be skipped
+                ListItem::new("Third item", 0, None),
             ],
         });
 
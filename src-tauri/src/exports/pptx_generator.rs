@@ -13,14 +13,25 @@ use quick_xml::Writer;
 use zip::write::{FileOptions, ZipWriter};
 use zip::CompressionMethod;
 
+use super::images::ExportImage;
 use super::markdown_parser::{ParsedContent, ContentBlock};
-use super::{ExportError, ExportResult, sanitize_filename};
+use super::{export_markdown_link, ExportError, ExportResult};
+
+/// Content area below the slide title, in EMUs
+const CONTENT_X: i64 = 457200;
+const CONTENT_Y: i64 = 1600200;
+const CONTENT_WIDTH: i64 = 8229600;
+const CONTENT_HEIGHT: i64 = 4525963;
+
+/// EMUs per pixel at 96 DPI
+const EMU_PER_PX: f32 = 9525.0;
 
 /// Slide content representation
 #[derive(Debug, Clone)]
 pub struct Slide {
     pub title: String,
     pub content: Vec<String>,
+    pub images: Vec<ExportImage>,
 }
 
 impl Slide {
@@ -28,9 +39,15 @@ impl Slide {
         Self {
             title: title.to_string(),
             content: Vec::new(),
+            images: Vec::new(),
         }
     }
 
+    /// Whether the slide has anything besides its title
+    fn has_body(&self) -> bool {
+        !self.content.is_empty() || !self.images.is_empty()
+    }
+
     pub fn add_bullet(&mut self, text: &str) {
         self.content.push(text.to_string());
     }
@@ -74,10 +91,17 @@ pub fn generate_pptx(
     // Write ppt/presentation.xml
     write_presentation(&mut zip, &options, slides.len())?;
     
-    // Write slides
+    // Write slides, numbering images across the whole presentation
+    let mut next_image = 1;
     for (i, slide) in slides.iter().enumerate() {
         write_slide(&mut zip, &options, i + 1, slide)?;
-        write_slide_rels(&mut zip, &options, i + 1)?;
+        write_slide_rels(&mut zip, &options, i + 1, next_image, slide.images.len())?;
+        next_image += slide.images.len();
+    }
+
+    // Write embedded images
+    for (i, image) in slides.iter().flat_map(|slide| &slide.images).enumerate() {
+        write_media(&mut zip, &options, i + 1, image)?;
     }
     
     // Write slide layouts and masters
@@ -107,7 +131,7 @@ pub fn generate_pptx(
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| "presentation.pptx".to_string()),
         file_size,
-        markdown_link: format!("[{}](exports/{})", title, sanitize_filename(title)),
+        markdown_link: export_markdown_link(title, output_path),
     })
 }
 
@@ -125,10 +149,10 @@ fn content_to_slides(content: &ParsedContent, title: &str) -> Vec<Slide> {
     
     for block in &content.blocks {
         match block {
-            ContentBlock::Heading { level, text } => {
+            ContentBlock::Heading { level, text, .. } => {
                 // Save current slide if exists
                 if let Some(slide) = current_slide.take() {
-                    if !slide.content.is_empty() || !slide.title.is_empty() {
+                    if slide.has_body() || !slide.title.is_empty() {
                         slides.push(slide);
                     }
                 }
@@ -145,7 +169,7 @@ fn content_to_slides(content: &ParsedContent, title: &str) -> Vec<Slide> {
                     }
                 }
             }
-            ContentBlock::Paragraph { text } => {
+            ContentBlock::Paragraph { text, .. } => {
                 if let Some(ref mut slide) = current_slide {
                     // Don't add empty paragraphs
                     if !text.trim().is_empty() {
@@ -153,26 +177,14 @@ fn content_to_slides(content: &ParsedContent, title: &str) -> Vec<Slide> {
                     }
                 }
             }
-            ContentBlock::UnorderedList { items } => {
-                if let Some(ref mut slide) = current_slide {
-                    for item in items {
-                        // Skip empty items
-                        let trimmed = item.trim();
-                        if !trimmed.is_empty() {
-                            slide.add_bullet(&format!("• {}", trimmed));
-                        }
-                    }
-                }
-            }
-            ContentBlock::OrderedList { items, start } => {
+            ContentBlock::UnorderedList { items } | ContentBlock::OrderedList { items, .. } => {
                 if let Some(ref mut slide) = current_slide {
-                    let mut num = *start;
                     for item in items {
                         // Skip empty items
-                        let trimmed = item.trim();
+                        let trimmed = item.text.trim();
                         if !trimmed.is_empty() {
-                            slide.add_bullet(&format!("{}. {}", num, trimmed));
-                            num += 1;
+                            let indent = "    ".repeat(item.depth as usize);
+                            slide.add_bullet(&format!("{}{} {}", indent, item.marker(), trimmed));
                         }
                     }
                 }
@@ -227,15 +239,21 @@ fn content_to_slides(content: &ParsedContent, title: &str) -> Vec<Slide> {
             ContentBlock::HorizontalRule => {
                 // Page breaks between sections - save current slide and start fresh
                 if let Some(slide) = current_slide.take() {
-                    if !slide.content.is_empty() || !slide.title.is_empty() {
+                    if slide.has_body() || !slide.title.is_empty() {
                         slides.push(slide);
                     }
                 }
             }
-            ContentBlock::Image { alt, .. } => {
+            ContentBlock::Image { url, alt, .. } => {
                 if let Some(ref mut slide) = current_slide {
-                    let alt_display = if alt.is_empty() { "Image" } else { alt };
-                    slide.add_bullet(&format!("[Image: {}]", alt_display));
+                    match ExportImage::load(url) {
+                        Ok(image) => slide.images.push(image),
+                        Err(e) => {
+                            log::warn!("[PPTXGenerator] Could not embed image {}: {}", url, e);
+                            let alt_display = if alt.is_empty() { "Image" } else { alt };
+                            slide.add_bullet(&format!("[Image: {}]", alt_display));
+                        }
+                    }
                 }
             }
         }
@@ -243,7 +261,7 @@ fn content_to_slides(content: &ParsedContent, title: &str) -> Vec<Slide> {
     
     // Add final slide
     if let Some(slide) = current_slide {
-        if !slide.content.is_empty() || !slide.title.is_empty() {
+        if slide.has_body() || !slide.title.is_empty() {
             slides.push(slide);
         }
     }
@@ -272,6 +290,7 @@ fn write_content_types<W: Write + Seek>(
     let defaults = [
         ("rels", "application/vnd.openxmlformats-package.relationships+xml"),
         ("xml", "application/xml"),
+        ("png", "image/png"),
     ];
     
     for (ext, ct) in defaults {
@@ -444,6 +463,14 @@ fn write_slide<W: Write + Seek>(
         }
     }
 
+    // Text shares the content area with images: text on the left, images on the right
+    let (text_width, image_x, image_width) = match (slide.images.is_empty(), slide.content.is_empty()) {
+        (true, _) => (CONTENT_WIDTH, 0, 0),
+        (false, true) => (CONTENT_WIDTH, CONTENT_X, CONTENT_WIDTH),
+        (false, false) => (CONTENT_WIDTH / 2, CONTENT_X + CONTENT_WIDTH / 2, CONTENT_WIDTH / 2),
+    };
+    let pictures = slide_pictures(&slide.images, image_x, image_width);
+
     let escaped_title = escape_xml(&slide.title);
     let content = format!(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <p:sld xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main">
@@ -487,8 +514,8 @@ fn write_slide<W: Write + Seek>(
         </p:nvSpPr>
         <p:spPr>
           <a:xfrm>
-            <a:off x="457200" y="1600200"/>
-            <a:ext cx="8229600" cy="4525963"/>
+            <a:off x="{}" y="{}"/>
+            <a:ext cx="{}" cy="{}"/>
           </a:xfrm>
           <a:prstGeom prst="rect"/>
         </p:spPr>
@@ -497,13 +524,13 @@ fn write_slide<W: Write + Seek>(
           <a:lstStyle/>
           {}
         </p:txBody>
-      </p:sp>
+      </p:sp>{}
     </p:spTree>
   </p:cSld>
   <p:clrMapOvr>
     <a:masterClrMapping/>
   </p:clrMapOvr>
-</p:sld>"#, escaped_title, body_paragraphs);
+</p:sld>"#, escaped_title, CONTENT_X, CONTENT_Y, text_width, CONTENT_HEIGHT, body_paragraphs, pictures);
     
     zip.write_all(content.as_bytes())
         .map_err(|e| ExportError::PptxError(e.to_string()))?;
@@ -511,18 +538,69 @@ fn write_slide<W: Write + Seek>(
     Ok(())
 }
 
+/// Build `p:pic` elements for a slide's images, stacked vertically in the given column.
+/// Image relationships start at rId2 (rId1 is the slide layout).
+fn slide_pictures(images: &[ExportImage], x: i64, width: i64) -> String {
+    if images.is_empty() {
+        return String::new();
+    }
+
+    let slot_height = CONTENT_HEIGHT / images.len() as i64;
+    let mut pictures = String::new();
+    for (i, image) in images.iter().enumerate() {
+        let (cx, cy) = image.fit_within(width as f32, slot_height as f32, EMU_PER_PX);
+        let (cx, cy) = (cx as i64, cy as i64);
+        // Center within the slot
+        let off_x = x + (width - cx) / 2;
+        let off_y = CONTENT_Y + slot_height * i as i64 + (slot_height - cy) / 2;
+        let shape_id = i + 4;
+        pictures.push_str(&format!(r#"
+      <p:pic>
+        <p:nvPicPr>
+          <p:cNvPr id="{}" name="Image {}"/>
+          <p:cNvPicPr><a:picLocks noChangeAspect="1"/></p:cNvPicPr>
+          <p:nvPr/>
+        </p:nvPicPr>
+        <p:blipFill>
+          <a:blip r:embed="rId{}"/>
+          <a:stretch><a:fillRect/></a:stretch>
+        </p:blipFill>
+        <p:spPr>
+          <a:xfrm>
+            <a:off x="{}" y="{}"/>
+            <a:ext cx="{}" cy="{}"/>
+          </a:xfrm>
+          <a:prstGeom prst="rect"/>
+        </p:spPr>
+      </p:pic>"#, shape_id, i + 1, i + 2, off_x, off_y, cx, cy));
+    }
+    pictures
+}
+
 fn write_slide_rels<W: Write + Seek>(
     zip: &mut ZipWriter<W>,
     options: &FileOptions<'_, ()>,
     slide_num: usize,
+    first_image: usize,
+    image_count: usize,
 ) -> Result<(), ExportError> {
     zip.start_file(format!("ppt/slides/_rels/slide{}.xml.rels", slide_num), *options)
         .map_err(|e| ExportError::PptxError(e.to_string()))?;
 
-    let content = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+    let mut image_rels = String::new();
+    for i in 0..image_count {
+        image_rels.push_str(&format!(
+            r#"
+  <Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="../media/image{}.png"/>"#,
+            i + 2,
+            first_image + i
+        ));
+    }
+
+    let content = format!(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
-  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slideLayout" Target="../slideLayouts/slideLayout1.xml"/>
-</Relationships>"#;
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slideLayout" Target="../slideLayouts/slideLayout1.xml"/>{}
+</Relationships>"#, image_rels);
     
     zip.write_all(content.as_bytes())
         .map_err(|e| ExportError::PptxError(e.to_string()))?;
@@ -530,6 +608,22 @@ fn write_slide_rels<W: Write + Seek>(
     Ok(())
 }
 
+fn write_media<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    options: &FileOptions<'_, ()>,
+    image_num: usize,
+    image: &ExportImage,
+) -> Result<(), ExportError> {
+    let png = image.to_png().map_err(ExportError::PptxError)?;
+
+    zip.start_file(format!("ppt/media/image{}.png", image_num), *options)
+        .map_err(|e| ExportError::PptxError(e.to_string()))?;
+    zip.write_all(&png)
+        .map_err(|e| ExportError::PptxError(e.to_string()))?;
+
+    Ok(())
+}
+
 fn write_slide_layout<W: Write + Seek>(
     zip: &mut ZipWriter<W>,
     options: &FileOptions<'_, ()>,
@@ -772,6 +866,7 @@ fn escape_xml(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::exports::markdown_parser::ListItem;
     
     #[test]
     fn test_escape_xml() {
@@ -784,14 +879,15 @@ mod tests {
         let content = ParsedContent {
             title: Some("Test".to_string()),
             blocks: vec![
-                ContentBlock::Heading { level: 1, text: "Title".to_string() },
-                ContentBlock::Paragraph { text: "First paragraph".to_string() },
-                ContentBlock::Heading { level: 2, text: "Section".to_string() },
+                ContentBlock::Heading { level: 1, text: "Title".to_string(), runs: Vec::new() },
+                ContentBlock::Paragraph { text: "First paragraph".to_string(), runs: Vec::new() },
+                ContentBlock::Heading { level: 2, text: "Section".to_string(), runs: Vec::new() },
                 ContentBlock::UnorderedList { items: vec![
-                    "Item 1".to_string(),
-                    "Item 2".to_string(),
+                    ListItem::new("Item 1", 0, None),
+                    ListItem::new("Item 2", 0, None),
                 ]},
             ],
+            footnotes: Vec::new(),
         };
         
         let slides = content_to_slides(&content, "Test Presentation");
@@ -810,9 +906,10 @@ mod tests {
         let content = ParsedContent {
             title: Some("Test".to_string()),
             blocks: vec![
-                ContentBlock::Heading { level: 1, text: "Main Section".to_string() },
-                ContentBlock::Paragraph { text: "This is content".to_string() },
+                ContentBlock::Heading { level: 1, text: "Main Section".to_string(), runs: Vec::new() },
+                ContentBlock::Paragraph { text: "This is content".to_string(), runs: Vec::new() },
             ],
+            footnotes: Vec::new(),
         };
         
         let slides = content_to_slides(&content, "Presentation Title");
@@ -836,16 +933,17 @@ mod tests {
         let content = ParsedContent {
             title: Some("Test Presentation".to_string()),
             blocks: vec![
-                ContentBlock::Heading { level: 1, text: "Introduction".to_string() },
-                ContentBlock::Paragraph { text: "Welcome to this presentation.".to_string() },
+                ContentBlock::Heading { level: 1, text: "Introduction".to_string(), runs: Vec::new() },
+                ContentBlock::Paragraph { text: "Welcome to this presentation.".to_string(), runs: Vec::new() },
                 ContentBlock::UnorderedList { items: vec![
-                    "Point one".to_string(),
-                    "Point two".to_string(),
-                    "Point three".to_string(),
+                    ListItem::new("Point one", 0, None),
+                    ListItem::new("Point two", 0, None),
+                    ListItem::new("Point three", 0, None),
                 ]},
-                ContentBlock::Heading { level: 2, text: "Details".to_string() },
-                ContentBlock::Paragraph { text: "Here are more details about the topic.".to_string() },
+                ContentBlock::Heading { level: 2, text: "Details".to_string(), runs: Vec::new() },
+                ContentBlock::Paragraph { text: "Here are more details about the topic.".to_string(), runs: Vec::new() },
             ],
+            footnotes: Vec::new(),
         };
         
         let options = PptxExportOptions::default();
@@ -880,5 +978,43 @@ mod tests {
             );
         }
     }
-}
 
+    #[test]
+    fn test_generate_pptx_embeds_image() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let image_path = crate::exports::images::tests::write_test_png(temp_dir.path());
+        let output_path = temp_dir.path().join("images.pptx");
+
+        let content = ParsedContent {
+            title: Some("Images".to_string()),
+            blocks: vec![
+                ContentBlock::Heading { level: 1, text: "Chart".to_string(), runs: Vec::new() },
+                ContentBlock::Image {
+                    url: image_path.to_string_lossy().to_string(),
+                    alt: "Chart".to_string(),
+                    title: None,
+                },
+            ],
+            footnotes: Vec::new(),
+        };
+
+        let slides = content_to_slides(&content, "Images");
+        assert_eq!(slides[1].images.len(), 1);
+        assert!(slides[1].content.is_empty());
+
+        generate_pptx(&content, "Images", &output_path, &PptxExportOptions::default()).unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&output_path).unwrap()).unwrap();
+        assert!(archive.by_name("ppt/media/image1.png").is_ok());
+
+        let mut rels = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("ppt/slides/_rels/slide2.xml.rels").unwrap(), &mut rels).unwrap();
+        assert!(rels.contains("../media/image1.png"));
+
+        let mut slide = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("ppt/slides/slide2.xml").unwrap(), &mut slide).unwrap();
+        assert!(slide.contains(r#"<a:blip r:embed="rId2"/>"#));
+    }
+}
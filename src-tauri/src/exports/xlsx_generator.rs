@@ -7,7 +7,7 @@ use std::path::Path;
 use rust_xlsxwriter::{Format, Workbook, Worksheet};
 
 use super::markdown_parser::{extract_tables_from_markdown, TableData};
-use super::{ExportError, ExportResult, XlsxExportOptions};
+use super::{export_markdown_link, ExportError, ExportResult, XlsxExportOptions};
 
 /// Generate an XLSX from table data
 pub fn generate_xlsx(
@@ -64,9 +64,9 @@ pub fn generate_xlsx(
 
     Ok(ExportResult {
         path: output_path.to_string_lossy().to_string(),
-        filename: filename.clone(),
+        filename,
         file_size,
-        markdown_link: format!("[{}](exports/{})", title, filename),
+        markdown_link: export_markdown_link(title, output_path),
     })
 }
 
@@ -1,16 +1,18 @@
 //! Google Calendar API client
 //!
-//! Fetches calendar events from Google Calendar and syncs them to the local database.
+//! Fetches calendar events from Google Calendar and syncs them to the local database,
+//! and pushes locally created events back to Google.
 
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::db::calendar_events;
 use crate::db::connection::DbPool;
-use crate::models::{CalendarEvent, CreateCalendarEventInput};
+use crate::models::{CalendarEvent, CreateCalendarEventInput, GoogleSyncState};
 
 use super::oauth::{refresh_token_if_needed_with_pool, urlencoding, GoogleAuthError};
 
@@ -29,6 +31,10 @@ pub enum GoogleCalendarError {
     DbError(String),
     #[error("API error: {0}")]
     ApiError(String),
+    #[error("Google Calendar write access was not granted. Please reconnect your Google account in Settings.")]
+    InsufficientScope,
+    #[error("Sync token is no longer valid; a full sync is required")]
+    SyncTokenExpired,
 }
 
 /// Google Calendar creator/organizer info
@@ -48,6 +54,7 @@ pub struct GoogleCalendarEvent {
     pub id: String,
     pub summary: Option<String>,
     pub description: Option<String>,
+    pub location: Option<String>,
     pub start: GoogleDateTime,
     pub end: Option<GoogleDateTime>,
     pub attendees: Option<Vec<GoogleAttendee>>,
@@ -60,6 +67,9 @@ pub struct GoogleCalendarEvent {
     #[serde(rename = "htmlLink")]
     pub html_link: Option<String>,
     pub recurrence: Option<Vec<String>>,
+    /// For instances of a recurring event, the id of the recurring event
+    #[serde(rename = "recurringEventId")]
+    pub recurring_event_id: Option<String>,
     pub status: Option<String>,
     /// Event type: "default", "outOfOffice", "focusTime", "workingLocation"
     #[serde(rename = "eventType")]
@@ -92,11 +102,12 @@ pub struct GoogleConferenceEntryPoint {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GoogleDateTime {
     /// For timed events
-    #[serde(rename = "dateTime")]
+    #[serde(rename = "dateTime", skip_serializing_if = "Option::is_none")]
     pub date_time: Option<String>,
     /// For all-day events
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub date: Option<String>,
-    #[serde(rename = "timeZone")]
+    #[serde(rename = "timeZone", skip_serializing_if = "Option::is_none")]
     pub time_zone: Option<String>,
 }
 
@@ -113,12 +124,37 @@ pub struct GoogleAttendee {
     pub organizer: Option<bool>,
 }
 
-/// Response from Google Calendar events list API
-#[derive(Debug, Deserialize)]
-struct EventsListResponse {
-    items: Option<Vec<GoogleCalendarEvent>>,
+/// One page of the Google Calendar events list API
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EventsPage {
+    #[serde(default)]
+    pub items: Vec<GoogleCalendarEvent>,
     #[serde(rename = "nextPageToken")]
-    next_page_token: Option<String>,
+    pub next_page_token: Option<String>,
+    /// Only present on the last page
+    #[serde(rename = "nextSyncToken")]
+    pub next_sync_token: Option<String>,
+}
+
+/// Which events to list
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventListQuery {
+    /// Every event in a time range (a full sync)
+    Range {
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    },
+    /// Only events changed since the sync token was issued, including cancelled ones
+    Incremental { sync_token: String },
+}
+
+/// Stored incremental sync state for the connected account
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarSyncState {
+    pub sync_token: String,
+    /// Range covered by the full sync the token came from
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
 }
 
 /// Sync result information
@@ -155,93 +191,24 @@ impl GoogleDateTime {
     }
 }
 
-/// Fetch events from Google Calendar API (pool version)
-pub async fn fetch_events_with_pool(
-    pool: &DbPool,
-    start: DateTime<Utc>,
-    end: DateTime<Utc>,
-) -> Result<Vec<GoogleCalendarEvent>, GoogleCalendarError> {
-    // Get a valid access token (refreshing if needed)
-    let access_token = refresh_token_if_needed_with_pool(pool).await?;
-    
-    let client = Client::new();
+/// Fetch all pages of events for a query.
+/// Returns the events (including cancelled ones) and the sync token from the last page.
+async fn fetch_all_events<A: CalendarApi + ?Sized>(
+    api: &A,
+    query: &EventListQuery,
+) -> Result<(Vec<GoogleCalendarEvent>, Option<String>), GoogleCalendarError> {
     let mut all_events = Vec::new();
     let mut page_token: Option<String> = None;
-    
-    // Extend the query range by 1 day on each side to account for timezone differences
-    // with all-day events. Google's Calendar API interprets all-day event dates in the
-    // calendar's timezone, which may differ from UTC. By padding the range, we ensure
-    // we capture all-day events that might otherwise be excluded due to timezone boundary issues.
-    let padded_start = start - chrono::Duration::days(1);
-    let padded_end = end + chrono::Duration::days(1);
-    
+
     loop {
-        // URL-encode the datetime values (they contain + and : which need encoding)
-        let time_min = urlencoding::encode(&padded_start.to_rfc3339());
-        let time_max = urlencoding::encode(&padded_end.to_rfc3339());
-        
-        let mut url = format!(
-            "{}/calendars/primary/events?timeMin={}&timeMax={}&singleEvents=true&orderBy=startTime&maxResults=250",
-            GOOGLE_CALENDAR_API,
-            time_min,
-            time_max,
-        );
-        
-        if let Some(ref token) = page_token {
-            url.push_str(&format!("&pageToken={}", token));
-        }
-        
-        let response = client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", access_token))
-            .send()
-            .await?;
-        
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            
-            // Check for insufficient scopes error (403 with ACCESS_TOKEN_SCOPE_INSUFFICIENT)
-            // This happens when the token was obtained with different scopes than currently required.
-            // The only fix is to re-authenticate with the correct scopes.
-            if status.as_u16() == 403 && 
-               (error_text.contains("ACCESS_TOKEN_SCOPE_INSUFFICIENT") || 
-                error_text.contains("insufficientPermissions") ||
-                error_text.contains("insufficient authentication scopes")) {
-                // Clear the account to force re-authentication with correct scopes
-                if let Ok(conn) = pool.get() {
-                    let _ = super::oauth::disconnect_account(&conn);
-                    log::warn!("Google token has insufficient scopes - cleared account. User needs to reconnect.");
-                }
-                return Err(GoogleCalendarError::ApiError(
-                    "Your Google Calendar permissions have changed. Please reconnect your Google account in Settings to restore sync.".to_string()
-                ));
-            }
-            
-            return Err(GoogleCalendarError::ApiError(format!(
-                "Failed to fetch events: {}",
-                error_text
-            )));
-        }
-        
-        let events_response: EventsListResponse = response.json().await?;
-        
-        if let Some(items) = events_response.items {
-            // Filter out cancelled events
-            let active_events: Vec<_> = items
-                .into_iter()
-                .filter(|e| e.status.as_deref() != Some("cancelled"))
-                .collect();
-            all_events.extend(active_events);
-        }
-        
-        page_token = events_response.next_page_token;
+        let page = api.list_events(query, page_token.as_deref()).await?;
+        all_events.extend(page.items);
+
+        page_token = page.next_page_token;
         if page_token.is_none() {
-            break;
+            return Ok((all_events, page.next_sync_token));
         }
     }
-    
-    Ok(all_events)
 }
 
 /// Convert a Google Calendar event to our internal format
@@ -338,6 +305,12 @@ fn google_event_to_internal(event: &GoogleCalendarEvent) -> Option<CreateCalenda
         response_status,
         attendees,
         meeting_link,
+        location: event.location.clone().filter(|l| !l.trim().is_empty()),
+        timezone: event
+            .start
+            .time_zone
+            .clone()
+            .filter(|tz| tz.parse::<chrono_tz::Tz>().is_ok()),
     })
 }
 
@@ -347,12 +320,110 @@ pub async fn sync_events_to_db_with_pool(
     start: DateTime<Utc>,
     end: DateTime<Utc>,
 ) -> Result<SyncResult, GoogleCalendarError> {
-    // Fetch events from Google (async HTTP calls)
-    let google_events = fetch_events_with_pool(pool, start, end).await?;
-    
-    // Now do all database operations synchronously
-    let conn = pool.get().map_err(|e| GoogleCalendarError::DbError(e.to_string()))?;
-    
+    let client = GoogleCalendarClient::new(pool.clone());
+
+    // A token granted before the required scopes changed keeps working for what it
+    // covers; reconnecting asks for the missing scopes on top of the granted ones.
+    let result = sync_events_to_db(&client, pool, start, end).await;
+    if matches!(result, Err(GoogleCalendarError::InsufficientScope)) {
+        log::warn!("Google token is missing a required scope; the account needs to be reconnected");
+    }
+    result
+}
+
+/// Sync Google Calendar events for a date range into the local database.
+///
+/// If a sync token from an earlier full sync covers the range, only changes since then are
+/// fetched. Otherwise (or if Google reports the token expired) the range is fully re-fetched
+/// and the new sync token is stored.
+pub async fn sync_events_to_db<A: CalendarApi + ?Sized>(
+    api: &A,
+    pool: &DbPool,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<SyncResult, GoogleCalendarError> {
+    let db_error = |e: &dyn std::fmt::Display| GoogleCalendarError::DbError(e.to_string());
+
+    let sync_state = {
+        let conn = pool.get().map_err(|e| db_error(&e))?;
+        get_sync_state(&conn).map_err(|e| db_error(&e))?
+    };
+
+    if let Some(state) = sync_state.filter(|s| s.window_start <= start && end <= s.window_end) {
+        let query = EventListQuery::Incremental {
+            sync_token: state.sync_token.clone(),
+        };
+        match fetch_all_events(api, &query).await {
+            Ok((google_events, next_sync_token)) => {
+                let conn = pool.get().map_err(|e| db_error(&e))?;
+                let result = apply_incremental_changes(&conn, &google_events)?;
+                if let Some(token) = next_sync_token {
+                    save_sync_state(
+                        &conn,
+                        &CalendarSyncState {
+                            sync_token: token,
+                            ..state
+                        },
+                    )
+                    .map_err(|e| db_error(&e))?;
+                }
+                return Ok(result);
+            }
+            Err(GoogleCalendarError::SyncTokenExpired) => {
+                log::info!("[google] Sync token expired, falling back to a full sync");
+                let conn = pool.get().map_err(|e| db_error(&e))?;
+                clear_sync_token(&conn).map_err(|e| db_error(&e))?;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    // Extend the query range by 1 day on each side to account for timezone differences
+    // with all-day events. Google's Calendar API interprets all-day event dates in the
+    // calendar's timezone, which may differ from UTC. By padding the range, we ensure
+    // we capture all-day events that might otherwise be excluded due to timezone boundary issues.
+    let padded_start = start - chrono::Duration::days(1);
+    let padded_end = end + chrono::Duration::days(1);
+    let query = EventListQuery::Range {
+        time_min: padded_start,
+        time_max: padded_end,
+    };
+    let (google_events, next_sync_token) = fetch_all_events(api, &query).await?;
+
+    // Cancelled events are removed locally rather than upserted
+    let (cancelled, google_events): (Vec<_>, Vec<_>) = google_events
+        .into_iter()
+        .partition(|e| e.status.as_deref() == Some("cancelled"));
+
+    let conn = pool.get().map_err(|e| db_error(&e))?;
+    let mut result = apply_full_sync(&conn, &google_events, start, end)?;
+    for google_event in &cancelled {
+        remove_cancelled_event(&conn, google_event, &mut result)?;
+    }
+    match next_sync_token {
+        Some(token) => save_sync_state(
+            &conn,
+            &CalendarSyncState {
+                sync_token: token,
+                window_start: padded_start,
+                window_end: padded_end,
+            },
+        ),
+        None => clear_sync_token(&conn),
+    }
+    .map_err(|e| db_error(&e))?;
+
+    Ok(result)
+}
+
+/// Apply the complete set of events for a range: upsert them, and remove local Google
+/// events in the range that Google no longer returned.
+fn apply_full_sync(
+    conn: &Connection,
+    google_events: &[GoogleCalendarEvent],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<SyncResult, GoogleCalendarError> {
     let mut result = SyncResult {
         events_synced: google_events.len(),
         events_added: 0,
@@ -361,7 +432,7 @@ pub async fn sync_events_to_db_with_pool(
     };
     
     // First, clean up any existing duplicate events from previous syncs
-    let duplicates_removed = calendar_events::cleanup_duplicate_google_events(&conn)
+    let duplicates_removed = calendar_events::cleanup_duplicate_google_events(conn)
         .map_err(|e| GoogleCalendarError::DbError(e.to_string()))?;
     if duplicates_removed > 0 {
         result.events_removed += duplicates_removed;
@@ -371,41 +442,9 @@ pub async fn sync_events_to_db_with_pool(
     let mut google_event_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
     
     // Upsert events from Google
-    for google_event in &google_events {
+    for google_event in google_events {
         google_event_ids.insert(google_event.id.clone());
-        
-        if let Some(input) = google_event_to_internal(google_event) {
-            // Check for existing event by external_id across ALL events (not just date range)
-            // This prevents duplicates when all-day events have timezone edge cases
-            let existing = calendar_events::get_event_by_external_id(&conn, &google_event.id)
-                .map_err(|e| GoogleCalendarError::DbError(e.to_string()))?;
-            
-            if let Some(existing_event) = existing {
-                // Update existing event
-                let update = crate::models::UpdateCalendarEventInput {
-                    title: Some(input.title),
-                    description: input.description,
-                    start_time: Some(input.start_time),
-                    end_time: input.end_time,
-                    all_day: Some(input.all_day),
-                    recurrence_rule: input.recurrence_rule,
-                    linked_note_id: None, // Preserve existing link
-                    event_type: input.event_type,
-                    response_status: input.response_status,
-                    attendees: input.attendees,
-                    meeting_link: input.meeting_link,
-                };
-                
-                if calendar_events::update_event(&conn, &existing_event.id, update).is_ok() {
-                    result.events_updated += 1;
-                }
-            } else {
-                // Create new event
-                if create_google_event(&conn, &google_event.id, input).is_ok() {
-                    result.events_added += 1;
-                }
-            }
-        }
+        upsert_google_event(conn, google_event, &mut result)?;
     }
     
     // Remove Google events that are in our database but no longer exist in Google for this date range
@@ -416,7 +455,7 @@ pub async fn sync_events_to_db_with_pool(
     // because all-day events are stored with midnight UTC times, but the sync range uses the user's
     // local timezone converted to UTC. This can cause misalignment where an all-day event's start_time
     // (midnight UTC) doesn't fall within the sync range (e.g., 08:00 UTC for a UTC-8 user).
-    let all_our_google_events = calendar_events::get_all_google_events(&conn)
+    let all_our_google_events = calendar_events::get_all_google_events(conn)
         .map_err(|e| GoogleCalendarError::DbError(e.to_string()))?;
     
     // Calculate date-only boundaries for comparing all-day events
@@ -440,7 +479,7 @@ pub async fn sync_events_to_db_with_pool(
             if let Some(ref external_id) = event.external_id {
                 // If this event wasn't returned by Google, it was deleted
                 if !google_event_ids.contains(external_id)
-                    && calendar_events::delete_event(&conn, &event.id).is_ok()
+                    && calendar_events::delete_event(conn, &event.id).is_ok()
                 {
                     result.events_removed += 1;
                 }
@@ -451,6 +490,189 @@ pub async fn sync_events_to_db_with_pool(
     Ok(result)
 }
 
+/// Apply changes since the last sync token: cancelled events are removed, others upserted
+fn apply_incremental_changes(
+    conn: &Connection,
+    google_events: &[GoogleCalendarEvent],
+) -> Result<SyncResult, GoogleCalendarError> {
+    let mut result = SyncResult {
+        events_synced: google_events.len(),
+        events_added: 0,
+        events_updated: 0,
+        events_removed: 0,
+    };
+
+    for google_event in google_events {
+        if google_event.status.as_deref() == Some("cancelled") {
+            remove_cancelled_event(conn, google_event, &mut result)?;
+            continue;
+        }
+        upsert_google_event(conn, google_event, &mut result)?;
+    }
+
+    Ok(result)
+}
+
+/// Remove the local copy of an event cancelled in Google
+///
+/// Covers both events imported from Google and local events we pushed. The
+/// latter are deleted without queuing a Google deletion, since Google already
+/// dropped them. Cancelled instances of recurring events are left alone, as
+/// recurrences are expanded locally.
+fn remove_cancelled_event(
+    conn: &Connection,
+    google_event: &GoogleCalendarEvent,
+    result: &mut SyncResult,
+) -> Result<(), GoogleCalendarError> {
+    let db_error = |e: calendar_events::CalendarEventDbError| GoogleCalendarError::DbError(e.to_string());
+
+    if let Some(event) = calendar_events::get_event_by_external_id(conn, &google_event.id).map_err(db_error)? {
+        if calendar_events::delete_event(conn, &event.id).map_err(db_error)? {
+            result.events_removed += 1;
+        }
+    }
+    if google_event.recurring_event_id.is_none() {
+        if let Some(event) = calendar_events::get_event_by_google_event_id(conn, &google_event.id).map_err(db_error)? {
+            if calendar_events::delete_event_locally(conn, &event.id).map_err(db_error)? {
+                result.events_removed += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Insert or update the local copy of a Google event
+fn upsert_google_event(
+    conn: &Connection,
+    google_event: &GoogleCalendarEvent,
+    result: &mut SyncResult,
+) -> Result<(), GoogleCalendarError> {
+    // Events we pushed from Inkling already exist locally as manual events
+    let pushed_id = google_event.recurring_event_id.as_deref().unwrap_or(&google_event.id);
+    let pushed = calendar_events::get_event_by_google_event_id(conn, pushed_id)
+        .map_err(|e| GoogleCalendarError::DbError(e.to_string()))?;
+    if let Some(local_event) = pushed {
+        // Recurring instances are expanded locally; local edits win until they are pushed
+        if google_event.recurring_event_id.is_none() && local_event.sync_state == GoogleSyncState::Synced {
+            if let Some(input) = google_event_to_internal(google_event) {
+                apply_remote_changes(conn, &local_event, input)?;
+                result.events_updated += 1;
+            }
+        }
+        return Ok(());
+    }
+    
+    if let Some(input) = google_event_to_internal(google_event) {
+        // Check for existing event by external_id across ALL events (not just date range)
+        // This prevents duplicates when all-day events have timezone edge cases
+        let existing = calendar_events::get_event_by_external_id(conn, &google_event.id)
+            .map_err(|e| GoogleCalendarError::DbError(e.to_string()))?;
+        
+        if let Some(existing_event) = existing {
+            // Update existing event
+            let update = crate::models::UpdateCalendarEventInput {
+                title: Some(input.title),
+                description: input.description,
+                start_time: Some(input.start_time),
+                end_time: input.end_time,
+                all_day: Some(input.all_day),
+                recurrence_rule: input.recurrence_rule,
+                linked_note_id: None, // Preserve existing link
+                event_type: input.event_type,
+                response_status: input.response_status,
+                attendees: input.attendees,
+                meeting_link: input.meeting_link,
+                location: input.location,
+                timezone: input.timezone,
+            };
+            
+            if calendar_events::update_event(conn, &existing_event.id, update).is_ok() {
+                result.events_updated += 1;
+            }
+        } else {
+            // Create new event
+            if create_google_event(conn, &google_event.id, input).is_ok() {
+                result.events_added += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Load the stored sync token for the connected account
+pub fn get_sync_state(conn: &Connection) -> Result<Option<CalendarSyncState>, rusqlite::Error> {
+    use rusqlite::OptionalExtension;
+
+    let row: Option<(Option<String>, Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT sync_token, sync_window_start, sync_window_end FROM google_accounts WHERE id = 'default'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+
+    let parse = |s: &str| DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.with_timezone(&Utc));
+    Ok(row.and_then(|(token, window_start, window_end)| {
+        Some(CalendarSyncState {
+            sync_token: token?,
+            window_start: parse(&window_start?)?,
+            window_end: parse(&window_end?)?,
+        })
+    }))
+}
+
+/// Store the sync token for the connected account
+pub fn save_sync_state(conn: &Connection, state: &CalendarSyncState) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE google_accounts SET sync_token = ?1, sync_window_start = ?2, sync_window_end = ?3 WHERE id = 'default'",
+        rusqlite::params![
+            state.sync_token,
+            state.window_start.to_rfc3339(),
+            state.window_end.to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Forget the sync token so the next sync is a full one
+pub fn clear_sync_token(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE google_accounts SET sync_token = NULL, sync_window_start = NULL, sync_window_end = NULL WHERE id = 'default'",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Apply changes made in Google to a local event we pushed, keeping it marked as synced
+fn apply_remote_changes(
+    conn: &Connection,
+    local_event: &CalendarEvent,
+    input: CreateCalendarEventInput,
+) -> Result<(), GoogleCalendarError> {
+    let update = crate::models::UpdateCalendarEventInput {
+        title: Some(input.title),
+        description: input.description,
+        start_time: Some(input.start_time),
+        end_time: input.end_time,
+        all_day: Some(input.all_day),
+        recurrence_rule: input.recurrence_rule,
+        linked_note_id: None,
+        event_type: None,
+        response_status: None,
+        attendees: input.attendees,
+        meeting_link: input.meeting_link,
+        location: input.location,
+        timezone: input.timezone,
+    };
+    let updated = calendar_events::update_event(conn, &local_event.id, update)
+        .map_err(|e| GoogleCalendarError::DbError(e.to_string()))?;
+    if let Some(google_event_id) = &local_event.google_event_id {
+        calendar_events::mark_event_pushed(conn, &updated.id, google_event_id, updated.updated_at)
+            .map_err(|e| GoogleCalendarError::DbError(e.to_string()))?;
+    }
+    Ok(())
+}
+
 /// Create a new event from Google Calendar
 fn create_google_event(
     conn: &Connection,
@@ -466,8 +688,8 @@ fn create_google_event(
     let attendees_json = input.attendees.as_ref().and_then(|a| serde_json::to_string(a).ok());
     
     conn.execute(
-        "INSERT INTO calendar_events (id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, event_type, response_status, attendees, meeting_link, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'google', ?8, NULL, ?9, ?10, ?11, ?12, ?13, ?14)",
+        "INSERT INTO calendar_events (id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, event_type, response_status, attendees, meeting_link, location, timezone, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'google', ?8, NULL, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
         rusqlite::params![
             id,
             input.title,
@@ -481,6 +703,8 @@ fn create_google_event(
             response_status,
             attendees_json,
             input.meeting_link,
+            input.location,
+            input.timezone,
             now,
             now,
         ],
@@ -491,20 +715,414 @@ fn create_google_event(
         .ok_or_else(|| GoogleCalendarError::DbError("Failed to retrieve created event".to_string()))
 }
 
-/// Get extended event info for meeting notes (attendees, meet link)
+// ============================================================================
+// Pushing local events to Google
+// ============================================================================
+
+/// Event body sent to the Calendar API insert/patch endpoints
+#[derive(Debug, Clone, Serialize)]
+pub struct GoogleEventWrite {
+    pub summary: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    pub start: GoogleDateTime,
+    pub end: GoogleDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Vec<String>>,
+}
+
+/// Calendar API operations, abstracted so sync logic can be tested without HTTP
+#[async_trait]
+pub trait CalendarApi: Send + Sync {
+    /// List one page of events. Returns `SyncTokenExpired` if Google rejects the sync token.
+    async fn list_events(
+        &self,
+        query: &EventListQuery,
+        page_token: Option<&str>,
+    ) -> Result<EventsPage, GoogleCalendarError>;
+    /// Insert an event and return its Google id
+    async fn insert_event(&self, event: &GoogleEventWrite) -> Result<String, GoogleCalendarError>;
+    async fn update_event(&self, google_event_id: &str, event: &GoogleEventWrite) -> Result<(), GoogleCalendarError>;
+    /// Delete an event. Events that are already gone are not an error.
+    async fn delete_event(&self, google_event_id: &str) -> Result<(), GoogleCalendarError>;
+}
+
+/// Calendar API client for the user's primary calendar
+pub struct GoogleCalendarClient {
+    client: Client,
+    pool: DbPool,
+}
+
+impl GoogleCalendarClient {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            client: Client::new(),
+            pool,
+        }
+    }
+
+    /// Get a valid access token, refreshing it first if it's about to expire
+    async fn access_token(&self) -> Result<String, GoogleCalendarError> {
+        Ok(refresh_token_if_needed_with_pool(&self.pool).await?)
+    }
+
+    async fn check_response(
+        response: reqwest::Response,
+        action: &str,
+    ) -> Result<reqwest::Response, GoogleCalendarError> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        if status == StatusCode::FORBIDDEN
+            && (error_text.contains("ACCESS_TOKEN_SCOPE_INSUFFICIENT")
+                || error_text.contains("insufficientPermissions")
+                || error_text.contains("insufficient authentication scopes"))
+        {
+            return Err(GoogleCalendarError::InsufficientScope);
+        }
+        Err(GoogleCalendarError::ApiError(format!(
+            "Failed to {}: {}",
+            action, error_text
+        )))
+    }
+}
+
+#[async_trait]
+impl CalendarApi for GoogleCalendarClient {
+    async fn list_events(
+        &self,
+        query: &EventListQuery,
+        page_token: Option<&str>,
+    ) -> Result<EventsPage, GoogleCalendarError> {
+        // singleEvents must match between the full sync and later incremental syncs
+        let mut url = format!(
+            "{}/calendars/primary/events?singleEvents=true&maxResults=250",
+            GOOGLE_CALENDAR_API
+        );
+        match query {
+            EventListQuery::Range { time_min, time_max } => {
+                // URL-encode the datetime values (they contain + and : which need encoding)
+                url.push_str(&format!(
+                    "&timeMin={}&timeMax={}",
+                    urlencoding::encode(&time_min.to_rfc3339()),
+                    urlencoding::encode(&time_max.to_rfc3339())
+                ));
+            }
+            EventListQuery::Incremental { sync_token } => {
+                url.push_str(&format!("&syncToken={}", urlencoding::encode(sync_token)));
+            }
+        }
+        if let Some(token) = page_token {
+            url.push_str(&format!("&pageToken={}", urlencoding::encode(token)));
+        }
+
+        let response = self
+            .client
+            .get(&url)
+            .bearer_auth(self.access_token().await?)
+            .send()
+            .await?;
+        if response.status() == StatusCode::GONE {
+            return Err(GoogleCalendarError::SyncTokenExpired);
+        }
+        let page = Self::check_response(response, "fetch events").await?.json().await?;
+        Ok(page)
+    }
+
+    async fn insert_event(&self, event: &GoogleEventWrite) -> Result<String, GoogleCalendarError> {
+        let response = self
+            .client
+            .post(format!("{}/calendars/primary/events", GOOGLE_CALENDAR_API))
+            .bearer_auth(self.access_token().await?)
+            .json(event)
+            .send()
+            .await?;
+        let created: GoogleCalendarEvent = Self::check_response(response, "create event").await?.json().await?;
+        Ok(created.id)
+    }
+
+    async fn update_event(&self, google_event_id: &str, event: &GoogleEventWrite) -> Result<(), GoogleCalendarError> {
+        let response = self
+            .client
+            .patch(format!(
+                "{}/calendars/primary/events/{}",
+                GOOGLE_CALENDAR_API,
+                urlencoding::encode(google_event_id)
+            ))
+            .bearer_auth(self.access_token().await?)
+            .json(event)
+            .send()
+            .await?;
+        Self::check_response(response, "update event").await?;
+        Ok(())
+    }
+
+    async fn delete_event(&self, google_event_id: &str) -> Result<(), GoogleCalendarError> {
+        let response = self
+            .client
+            .delete(format!(
+                "{}/calendars/primary/events/{}",
+                GOOGLE_CALENDAR_API,
+                urlencoding::encode(google_event_id)
+            ))
+            .bearer_auth(self.access_token().await?)
+            .send()
+            .await?;
+        if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE) {
+            return Ok(());
+        }
+        Self::check_response(response, "delete event").await?;
+        Ok(())
+    }
+}
+
+/// Push result information
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PushResult {
+    pub events_created: usize,
+    pub events_updated: usize,
+    pub events_deleted: usize,
+    /// Already pushed and unchanged since
+    pub events_skipped: usize,
+    pub events_failed: usize,
+}
+
+/// Convert a local event into a Calendar API event body
+fn internal_to_google_event(event: &CalendarEvent) -> GoogleEventWrite {
+    let (start, end) = if event.all_day {
+        let start_date = event.start_time.date_naive();
+        // Google's all-day end date is exclusive and must be after the start
+        let end_date = event
+            .end_time
+            .map(|end| end.date_naive())
+            .filter(|end| *end > start_date)
+            .unwrap_or(start_date + chrono::Duration::days(1));
+        let date = |d: chrono::NaiveDate| GoogleDateTime {
+            date_time: None,
+            date: Some(d.format("%Y-%m-%d").to_string()),
+            time_zone: None,
+        };
+        (date(start_date), date(end_date))
+    } else {
+        let tz = event
+            .timezone
+            .as_deref()
+            .and_then(|name| name.parse::<chrono_tz::Tz>().ok())
+            .unwrap_or(chrono_tz::Tz::UTC);
+        let date_time = |dt: DateTime<Utc>| GoogleDateTime {
+            date_time: Some(dt.with_timezone(&tz).to_rfc3339()),
+            date: None,
+            time_zone: Some(tz.name().to_string()),
+        };
+        (
+            date_time(event.start_time),
+            date_time(event.end_time.unwrap_or(event.start_time)),
+        )
+    };
+
+    GoogleEventWrite {
+        summary: event.title.clone(),
+        description: event.description.clone().filter(|d| !d.is_empty()),
+        location: event.location.clone().filter(|l| !l.is_empty()),
+        start,
+        end,
+        recurrence: event
+            .recurrence_rule
+            .as_deref()
+            .filter(|r| !r.is_empty())
+            .map(|r| vec![format!("RRULE:{}", r.strip_prefix("RRULE:").unwrap_or(r))]),
+    }
+}
+
+/// Push local events to Google: insert new ones, update ones changed since their last push,
+/// and delete Google copies of events deleted locally. Unchanged events are skipped.
+pub async fn push_local_events<A: CalendarApi + ?Sized>(
+    api: &A,
+    pool: &DbPool,
+) -> Result<PushResult, GoogleCalendarError> {
+    let db_error = |e: &dyn std::fmt::Display| GoogleCalendarError::DbError(e.to_string());
+    let (events, deletions) = {
+        let conn = pool.get().map_err(|e| db_error(&e))?;
+        (
+            calendar_events::get_local_events(&conn).map_err(|e| db_error(&e))?,
+            calendar_events::get_pending_google_deletions(&conn).map_err(|e| db_error(&e))?,
+        )
+    };
+
+    let mut result = PushResult::default();
+
+    for event in events {
+        let payload = internal_to_google_event(&event);
+        let pushed = match (&event.google_event_id, &event.sync_state) {
+            (Some(_), GoogleSyncState::Synced) => {
+                result.events_skipped += 1;
+                continue;
+            }
+            (Some(google_event_id), _) => api
+                .update_event(google_event_id, &payload)
+                .await
+                .map(|_| (google_event_id.clone(), false)),
+            (None, _) => api.insert_event(&payload).await.map(|id| (id, true)),
+        };
+
+        match pushed {
+            Ok((google_event_id, created)) => {
+                let conn = pool.get().map_err(|e| db_error(&e))?;
+                calendar_events::mark_event_pushed(&conn, &event.id, &google_event_id, event.updated_at)
+                    .map_err(|e| db_error(&e))?;
+                if created {
+                    result.events_created += 1;
+                } else {
+                    result.events_updated += 1;
+                }
+            }
+            Err(GoogleCalendarError::InsufficientScope) => return Err(GoogleCalendarError::InsufficientScope),
+            Err(e) => {
+                log::warn!("[google] Failed to push event {}: {}", event.id, e);
+                result.events_failed += 1;
+            }
+        }
+    }
+
+    for google_event_id in deletions {
+        match api.delete_event(&google_event_id).await {
+            Ok(()) => {
+                let conn = pool.get().map_err(|e| db_error(&e))?;
+                calendar_events::clear_google_deletion(&conn, &google_event_id).map_err(|e| db_error(&e))?;
+                result.events_deleted += 1;
+            }
+            Err(GoogleCalendarError::InsufficientScope) => return Err(GoogleCalendarError::InsufficientScope),
+            Err(e) => {
+                log::warn!("[google] Failed to delete Google event {}: {}", google_event_id, e);
+                result.events_failed += 1;
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Push local events to the connected Google account (pool version)
+pub async fn push_local_events_to_google(pool: &DbPool) -> Result<PushResult, GoogleCalendarError> {
+    let client = GoogleCalendarClient::new(pool.clone());
+    push_local_events(&client, pool).await
+}
+
+/// Get extended event info for meeting notes (attendees, meet link, location)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventMeetingInfo {
     pub attendees: Vec<String>,
     pub meeting_link: Option<String>,
+    pub location: Option<String>,
+    /// Join link of a recognized video conferencing service
+    pub video_conference: Option<VideoConference>,
     pub original_description: Option<String>,
 }
 
+/// Video conferencing services we recognize join links for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConferenceProvider {
+    Zoom,
+    GoogleMeet,
+    Teams,
+    Webex,
+}
+
+/// A video conference join link and the service it belongs to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VideoConference {
+    pub provider: ConferenceProvider,
+    pub join_url: String,
+}
+
+/// Assemble meeting info for an event from its stored attendees, link and location,
+/// falling back to what can be parsed from the description. Without an explicit
+/// meeting link, a recognized join link in the location or description is used;
+/// other URLs (agendas, docs) are never reported as the meeting link.
+pub fn event_meeting_info(event: &CalendarEvent) -> EventMeetingInfo {
+    let mut info = parse_meeting_info(event.description.as_deref());
+
+    let attendees: Vec<String> = event
+        .attendees
+        .iter()
+        .flatten()
+        .map(|a| a.name.clone().filter(|n| !n.is_empty()).unwrap_or_else(|| a.email.clone()))
+        .collect();
+    if !attendees.is_empty() {
+        info.attendees = attendees;
+    }
+
+    let meeting_link = event.meeting_link.clone().or(info.meeting_link.take());
+    let texts = [meeting_link.as_deref(), event.location.as_deref(), event.description.as_deref()];
+    info.video_conference = texts.into_iter().flatten().find_map(detect_video_conference);
+    info.meeting_link = meeting_link
+        .clone()
+        .or_else(|| info.video_conference.as_ref().map(|v| v.join_url.clone()));
+    info.location = event.location.clone();
+    info
+}
+
+/// Find the first Zoom, Google Meet, Teams or Webex join link in free text
+pub fn detect_video_conference(text: &str) -> Option<VideoConference> {
+    find_urls(text).into_iter().find_map(|url| {
+        let without_scheme = url.split_once("://")?.1;
+        let (host, path) = without_scheme.split_once('/').unwrap_or((without_scheme, ""));
+        let host = host.to_lowercase();
+        let is_host = |domain: &str| host == domain || host.ends_with(&format!(".{}", domain));
+
+        let provider = if (is_host("zoom.us") || is_host("zoomgov.com"))
+            && ["j/", "my/", "w/", "s/"].iter().any(|p| path.starts_with(p))
+        {
+            ConferenceProvider::Zoom
+        } else if host == "meet.google.com" && !path.is_empty() {
+            ConferenceProvider::GoogleMeet
+        } else if (host == "teams.microsoft.com" && path.starts_with("l/meetup-join/"))
+            || (host == "teams.live.com" && path.starts_with("meet/"))
+        {
+            ConferenceProvider::Teams
+        } else if is_host("webex.com") && !path.is_empty() {
+            ConferenceProvider::Webex
+        } else {
+            return None;
+        };
+        Some(VideoConference {
+            provider,
+            join_url: url.to_string(),
+        })
+    })
+}
+
+/// http(s) URLs in free text, without surrounding brackets or trailing punctuation
+fn find_urls(text: &str) -> Vec<&str> {
+    let mut urls = Vec::new();
+    let mut rest = text;
+    while let Some(start) = [rest.find("https://"), rest.find("http://")].into_iter().flatten().min() {
+        let candidate = &rest[start..];
+        let end = candidate
+            .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\'' | '(' | ')' | '[' | ']'))
+            .unwrap_or(candidate.len());
+        let url = candidate[..end].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        if !url.ends_with("//") {
+            urls.push(url);
+        }
+        rest = &candidate[end..];
+    }
+    urls
+}
+
 /// Parse meeting info from a Google event's description
 /// (We embed this info when syncing)
 pub fn parse_meeting_info(description: Option<&str>) -> EventMeetingInfo {
     let mut info = EventMeetingInfo {
         attendees: Vec::new(),
         meeting_link: None,
+        location: None,
+        video_conference: None,
         original_description: None,
     };
     
@@ -550,3 +1168,478 @@ pub fn parse_meeting_info(description: Option<&str>) -> EventMeetingInfo {
     info
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+    use crate::models::UpdateCalendarEventInput;
+    use chrono::TimeZone;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    /// Records Calendar API calls instead of making HTTP requests
+    #[derive(Default)]
+    struct MockCalendarApi {
+        calls: Mutex<Vec<String>>,
+        /// Scripted list responses; `None` answers with 410 Gone
+        pages: Mutex<VecDeque<Option<EventsPage>>>,
+        queries: Mutex<Vec<EventListQuery>>,
+        inserted: Mutex<usize>,
+    }
+
+    impl MockCalendarApi {
+        fn take_calls(&self) -> Vec<String> {
+            std::mem::take(&mut *self.calls.lock().unwrap())
+        }
+
+        fn respond(&self, page: Option<EventsPage>) {
+            self.pages.lock().unwrap().push_back(page);
+        }
+
+        fn take_queries(&self) -> Vec<EventListQuery> {
+            std::mem::take(&mut *self.queries.lock().unwrap())
+        }
+    }
+
+    #[async_trait]
+    impl CalendarApi for MockCalendarApi {
+        async fn list_events(
+            &self,
+            query: &EventListQuery,
+            _page_token: Option<&str>,
+        ) -> Result<EventsPage, GoogleCalendarError> {
+            self.queries.lock().unwrap().push(query.clone());
+            let page = self.pages.lock().unwrap().pop_front().expect("unexpected list_events call");
+            page.ok_or(GoogleCalendarError::SyncTokenExpired)
+        }
+
+        async fn insert_event(&self, event: &GoogleEventWrite) -> Result<String, GoogleCalendarError> {
+            self.calls.lock().unwrap().push(format!("insert {}", event.summary));
+            let mut inserted = self.inserted.lock().unwrap();
+            *inserted += 1;
+            Ok(format!("g{}", inserted))
+        }
+
+        async fn update_event(&self, google_event_id: &str, event: &GoogleEventWrite) -> Result<(), GoogleCalendarError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("update {} {}", google_event_id, event.summary));
+            Ok(())
+        }
+
+        async fn delete_event(&self, google_event_id: &str) -> Result<(), GoogleCalendarError> {
+            self.calls.lock().unwrap().push(format!("delete {}", google_event_id));
+            Ok(())
+        }
+    }
+
+    fn local_event(conn: &Connection, title: &str) -> CalendarEvent {
+        calendar_events::create_event(
+            conn,
+            CreateCalendarEventInput {
+                title: title.to_string(),
+                description: None,
+                start_time: Utc.with_ymd_and_hms(2024, 5, 6, 15, 0, 0).unwrap(),
+                end_time: Some(Utc.with_ymd_and_hms(2024, 5, 6, 16, 0, 0).unwrap()),
+                all_day: false,
+                recurrence_rule: None,
+                linked_note_id: None,
+                event_type: None,
+                response_status: None,
+                attendees: None,
+                meeting_link: None,
+                location: None,
+                timezone: Some("Europe/London".to_string()),
+            },
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_push_creates_updates_and_skips_unchanged() {
+        let pool = init_test_pool().unwrap();
+        let api = MockCalendarApi::default();
+        let event = {
+            let conn = pool.get().unwrap();
+            local_event(&conn, "Dentist")
+        };
+
+        // First push inserts the event and records its Google id
+        let result = push_local_events(&api, &pool).await.unwrap();
+        assert_eq!(result.events_created, 1);
+        assert_eq!(api.take_calls(), vec!["insert Dentist"]);
+        {
+            let conn = pool.get().unwrap();
+            let pushed = calendar_events::get_event(&conn, &event.id).unwrap().unwrap();
+            assert_eq!(pushed.google_event_id.as_deref(), Some("g1"));
+            assert_eq!(pushed.sync_state, GoogleSyncState::Synced);
+        }
+
+        // Unchanged events are not pushed again
+        let result = push_local_events(&api, &pool).await.unwrap();
+        assert_eq!(result.events_skipped, 1);
+        assert!(api.take_calls().is_empty());
+
+        // Local edits are pushed as updates to the same Google event
+        {
+            let conn = pool.get().unwrap();
+            let update = UpdateCalendarEventInput {
+                title: Some("Dentist (moved)".to_string()),
+                description: None,
+                start_time: None,
+                end_time: None,
+                all_day: None,
+                recurrence_rule: None,
+                linked_note_id: None,
+                event_type: None,
+                response_status: None,
+                attendees: None,
+                meeting_link: None,
+                location: None,
+                timezone: None,
+            };
+            let updated = calendar_events::update_event(&conn, &event.id, update).unwrap();
+            assert_eq!(updated.sync_state, GoogleSyncState::Modified);
+        }
+        let result = push_local_events(&api, &pool).await.unwrap();
+        assert_eq!(result.events_updated, 1);
+        assert_eq!(api.take_calls(), vec!["update g1 Dentist (moved)"]);
+    }
+
+    #[tokio::test]
+    async fn test_push_deletes_events_removed_locally() {
+        let pool = init_test_pool().unwrap();
+        let api = MockCalendarApi::default();
+        let event = {
+            let conn = pool.get().unwrap();
+            local_event(&conn, "Cancelled")
+        };
+        push_local_events(&api, &pool).await.unwrap();
+        api.take_calls();
+
+        {
+            let conn = pool.get().unwrap();
+            calendar_events::delete_event(&conn, &event.id).unwrap();
+        }
+        let result = push_local_events(&api, &pool).await.unwrap();
+        assert_eq!(result.events_deleted, 1);
+        assert_eq!(api.take_calls(), vec!["delete g1"]);
+
+        // The deletion is only sent once
+        push_local_events(&api, &pool).await.unwrap();
+        assert!(api.take_calls().is_empty());
+    }
+
+    #[test]
+    fn test_internal_to_google_event_uses_event_timezone() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let event = local_event(&conn, "Standup");
+
+        let payload = internal_to_google_event(&event);
+        assert_eq!(payload.start.date_time.as_deref(), Some("2024-05-06T16:00:00+01:00"));
+        assert_eq!(payload.start.time_zone.as_deref(), Some("Europe/London"));
+        assert_eq!(payload.end.date_time.as_deref(), Some("2024-05-06T17:00:00+01:00"));
+        assert!(payload.recurrence.is_none());
+    }
+
+    fn google_event(id: &str, summary: &str, status: &str) -> GoogleCalendarEvent {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "summary": summary,
+            "status": status,
+            "start": { "dateTime": "2024-05-06T09:00:00Z" },
+            "end": { "dateTime": "2024-05-06T10:00:00Z" },
+        }))
+        .unwrap()
+    }
+
+    fn page(events: Vec<GoogleCalendarEvent>, sync_token: &str) -> Option<EventsPage> {
+        Some(EventsPage {
+            items: events,
+            next_page_token: None,
+            next_sync_token: Some(sync_token.to_string()),
+        })
+    }
+
+    fn connect_account(pool: &DbPool) {
+        let conn = pool.get().unwrap();
+        conn.execute(
+            "INSERT INTO google_accounts (id, email, access_token, refresh_token) VALUES ('default', 'me@example.com', 'a', 'r')",
+            [],
+        )
+        .unwrap();
+    }
+
+    fn may(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, day, 0, 0, 0).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sync_token_is_persisted_and_sent() {
+        let pool = init_test_pool().unwrap();
+        connect_account(&pool);
+        let api = MockCalendarApi::default();
+
+        // First sync is a full range sync whose token is stored
+        api.respond(page(
+            vec![google_event("a", "Review", "confirmed"), google_event("b", "1:1", "confirmed")],
+            "token-1",
+        ));
+        let result = sync_events_to_db(&api, &pool, may(1), may(31)).await.unwrap();
+        assert_eq!(result.events_added, 2);
+        assert!(matches!(api.take_queries()[..], [EventListQuery::Range { .. }]));
+        {
+            let conn = pool.get().unwrap();
+            let state = get_sync_state(&conn).unwrap().unwrap();
+            assert_eq!(state.sync_token, "token-1");
+            assert_eq!(state.window_start, may(1) - chrono::Duration::days(1));
+        }
+
+        // The next sync within the window only asks for changes
+        api.respond(page(vec![google_event("b", "1:1", "cancelled")], "token-2"));
+        let result = sync_events_to_db(&api, &pool, may(6), may(13)).await.unwrap();
+        assert_eq!(result.events_removed, 1);
+        assert_eq!(
+            api.take_queries(),
+            vec![EventListQuery::Incremental {
+                sync_token: "token-1".to_string()
+            }]
+        );
+        let conn = pool.get().unwrap();
+        assert_eq!(get_sync_state(&conn).unwrap().unwrap().sync_token, "token-2");
+        assert!(calendar_events::get_event_by_external_id(&conn, "a").unwrap().is_some());
+        assert!(calendar_events::get_event_by_external_id(&conn, "b").unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_remote_cancellation_deletes_pushed_event() {
+        let pool = init_test_pool().unwrap();
+        connect_account(&pool);
+        let api = MockCalendarApi::default();
+        let (kept, cancelled) = {
+            let conn = pool.get().unwrap();
+            (local_event(&conn, "Standup"), local_event(&conn, "Offsite"))
+        };
+        push_local_events(&api, &pool).await.unwrap();
+        assert_eq!(api.take_calls(), vec!["insert Standup", "insert Offsite"]);
+
+        // The Offsite event (pushed as g2) is cancelled in Google
+        api.respond(page(vec![google_event("g2", "Offsite", "cancelled")], "token-1"));
+        let result = sync_events_to_db(&api, &pool, may(1), may(31)).await.unwrap();
+        assert_eq!(result.events_removed, 1);
+        {
+            let conn = pool.get().unwrap();
+            assert!(calendar_events::get_event(&conn, &cancelled.id).unwrap().is_none());
+            assert!(calendar_events::get_event(&conn, &kept.id).unwrap().is_some());
+        }
+
+        // Google already dropped it, so the next push doesn't send a deletion
+        let result = push_local_events(&api, &pool).await.unwrap();
+        assert_eq!(result.events_deleted, 0);
+        assert!(api.take_calls().is_empty());
+
+        // Cancellations arriving through the sync token are applied the same way
+        let retro = {
+            let conn = pool.get().unwrap();
+            local_event(&conn, "Retro")
+        };
+        push_local_events(&api, &pool).await.unwrap();
+        assert_eq!(api.take_calls(), vec!["insert Retro"]);
+        let google_id = {
+            let conn = pool.get().unwrap();
+            calendar_events::get_event(&conn, &retro.id).unwrap().unwrap().google_event_id.unwrap()
+        };
+        api.respond(page(vec![google_event(&google_id, "Retro", "cancelled")], "token-2"));
+        let result = sync_events_to_db(&api, &pool, may(6), may(13)).await.unwrap();
+        assert_eq!(result.events_removed, 1);
+        let conn = pool.get().unwrap();
+        assert!(calendar_events::get_event(&conn, &retro.id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_edit_in_the_same_second_as_a_push_stays_modified() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let event = local_event(&conn, "Dentist");
+
+        // The push started from `event`, but an edit landed before it was recorded
+        let edited = calendar_events::update_event(
+            &conn,
+            &event.id,
+            UpdateCalendarEventInput {
+                title: Some("Dentist (moved)".to_string()),
+                description: None,
+                start_time: None,
+                end_time: None,
+                all_day: None,
+                recurrence_rule: None,
+                linked_note_id: None,
+                event_type: None,
+                response_status: None,
+                attendees: None,
+                meeting_link: None,
+                location: None,
+                timezone: None,
+            },
+        )
+        .unwrap();
+        assert_ne!(edited.updated_at, event.updated_at);
+
+        calendar_events::mark_event_pushed(&conn, &event.id, "g1", event.updated_at).unwrap();
+        let pushed = calendar_events::get_event(&conn, &event.id).unwrap().unwrap();
+        assert_eq!(pushed.sync_state, GoogleSyncState::Modified);
+    }
+
+    #[tokio::test]
+    async fn test_expired_sync_token_triggers_full_resync() {
+        let pool = init_test_pool().unwrap();
+        connect_account(&pool);
+        let api = MockCalendarApi::default();
+
+        api.respond(page(vec![google_event("a", "Review", "confirmed")], "token-1"));
+        sync_events_to_db(&api, &pool, may(1), may(31)).await.unwrap();
+        api.take_queries();
+
+        // Google answers 410 Gone, so the range is fetched again from scratch
+        api.respond(None);
+        api.respond(page(vec![google_event("c", "Planning", "confirmed")], "token-fresh"));
+        let result = sync_events_to_db(&api, &pool, may(6), may(13)).await.unwrap();
+
+        let queries = api.take_queries();
+        assert_eq!(queries.len(), 2);
+        assert!(matches!(queries[0], EventListQuery::Incremental { .. }));
+        assert_eq!(
+            queries[1],
+            EventListQuery::Range {
+                time_min: may(5),
+                time_max: may(14)
+            }
+        );
+        assert_eq!(result.events_added, 1);
+
+        let conn = pool.get().unwrap();
+        let state = get_sync_state(&conn).unwrap().unwrap();
+        assert_eq!(state.sync_token, "token-fresh");
+        assert_eq!(state.window_end, may(14));
+    }
+
+    #[test]
+    fn test_meeting_info_prefers_stored_fields() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let mut event = local_event(&conn, "Standup");
+        event.description = Some("Daily sync\n\n**Attendees:** Old Name\n**Meeting Link:** https://meet.google.com/old".to_string());
+        event.attendees = Some(vec![
+            crate::models::EventAttendee {
+                email: "ada@example.com".to_string(),
+                name: Some("Ada".to_string()),
+                response_status: None,
+                is_organizer: true,
+            },
+            crate::models::EventAttendee {
+                email: "bob@example.com".to_string(),
+                name: None,
+                response_status: None,
+                is_organizer: false,
+            },
+        ]);
+        event.meeting_link = Some("https://meet.google.com/abc-defg-hij".to_string());
+        event.location = Some("Room 4".to_string());
+
+        let info = event_meeting_info(&event);
+        assert_eq!(info.attendees, vec!["Ada", "bob@example.com"]);
+        assert_eq!(info.meeting_link.as_deref(), Some("https://meet.google.com/abc-defg-hij"));
+        assert_eq!(info.location.as_deref(), Some("Room 4"));
+        assert_eq!(info.original_description.as_deref(), Some("Daily sync"));
+        assert_eq!(
+            info.video_conference.map(|v| v.provider),
+            Some(ConferenceProvider::GoogleMeet)
+        );
+    }
+
+    #[test]
+    fn test_meeting_info_finds_video_link_in_location_or_description() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let mut event = local_event(&conn, "Review");
+
+        event.location = Some("Zoom (https://us02web.zoom.us/j/123456789?pwd=abc).".to_string());
+        event.description = Some("See https://example.com/agenda".to_string());
+        let info = event_meeting_info(&event);
+        assert_eq!(info.meeting_link.as_deref(), Some("https://us02web.zoom.us/j/123456789?pwd=abc"));
+
+        event.location = Some("Conference room B".to_string());
+        event.description = Some("Join: <https://teams.microsoft.com/l/meetup-join/xyz>, thanks.".to_string());
+        let info = event_meeting_info(&event);
+        assert_eq!(info.meeting_link.as_deref(), Some("https://teams.microsoft.com/l/meetup-join/xyz"));
+        assert_eq!(info.location.as_deref(), Some("Conference room B"));
+
+        event.description = None;
+        assert_eq!(event_meeting_info(&event).meeting_link, None);
+
+        // A recognized join link wins over other URLs
+        event.description = Some("Agenda: https://docs.example.com/x\nJoin: https://meet.google.com/abc-defg-hij".to_string());
+        let info = event_meeting_info(&event);
+        assert_eq!(info.meeting_link.as_deref(), Some("https://meet.google.com/abc-defg-hij"));
+    }
+
+    #[test]
+    fn test_meeting_info_ignores_unrecognized_urls() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let mut event = local_event(&conn, "Planning");
+
+        event.location = Some("https://example.com/agenda".to_string());
+        event.description = Some("Spec: https://docs.google.com/document/d/123/edit".to_string());
+        let info = event_meeting_info(&event);
+        assert_eq!(info.meeting_link, None);
+        assert_eq!(info.video_conference, None);
+        assert_eq!(info.location.as_deref(), Some("https://example.com/agenda"));
+    }
+
+    #[test]
+    fn test_detect_zoom_link() {
+        let conference = detect_video_conference(
+            "Join Zoom Meeting\nhttps://us02web.zoom.us/j/87654321012?pwd=aBcD1234.1\n\nMeeting ID: 876 5432 1012",
+        )
+        .unwrap();
+        assert_eq!(conference.provider, ConferenceProvider::Zoom);
+        assert_eq!(conference.join_url, "https://us02web.zoom.us/j/87654321012?pwd=aBcD1234.1");
+
+        // Zoom's own marketing links are not join links
+        assert_eq!(detect_video_conference("Download at https://zoom.us/download"), None);
+    }
+
+    #[test]
+    fn test_detect_google_meet_link() {
+        let conference = detect_video_conference("Video call: https://meet.google.com/abc-defg-hij.").unwrap();
+        assert_eq!(
+            conference,
+            VideoConference {
+                provider: ConferenceProvider::GoogleMeet,
+                join_url: "https://meet.google.com/abc-defg-hij".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_detect_teams_link() {
+        let description = "Microsoft Teams meeting\nJoin on your computer\nClick here to join the meeting<https://teams.microsoft.com/l/meetup-join/19%3ameeting_ABC%40thread.v2/0?context=%7b%22Tid%22%7d>\nLearn More<https://aka.ms/JoinTeamsMeeting>";
+        let conference = detect_video_conference(description).unwrap();
+        assert_eq!(conference.provider, ConferenceProvider::Teams);
+        assert_eq!(
+            conference.join_url,
+            "https://teams.microsoft.com/l/meetup-join/19%3ameeting_ABC%40thread.v2/0?context=%7b%22Tid%22%7d"
+        );
+    }
+
+    #[test]
+    fn test_detect_no_conference_link() {
+        assert_eq!(detect_video_conference("Lunch at the usual place"), None);
+        assert_eq!(detect_video_conference("Notes: https://docs.google.com/document/d/123"), None);
+        assert_eq!(detect_video_conference(""), None);
+    }
+}
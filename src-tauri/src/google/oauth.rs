@@ -3,6 +3,7 @@
 //! Implements secure OAuth authentication for desktop applications using the
 //! authorization code flow with PKCE (Proof Key for Code Exchange).
 
+use async_trait::async_trait;
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use rand::Rng;
 use reqwest::Client;
@@ -24,8 +25,8 @@ const GOOGLE_USERINFO_URL: &str = "https://www.googleapis.com/oauth2/v2/userinfo
 /// Ports to try for the callback server (in order)
 const CALLBACK_PORTS: [u16; 5] = [8234, 8235, 8236, 8237, 8238];
 
-/// Scopes required for calendar read access
-const SCOPES: &str = "https://www.googleapis.com/auth/calendar.readonly email profile";
+/// Scopes required for reading and writing calendar events
+const SCOPES: &str = "https://www.googleapis.com/auth/calendar.events email profile";
 
 #[derive(Error, Debug)]
 pub enum GoogleAuthError {
@@ -79,14 +80,14 @@ struct UserInfoResponse {
 }
 
 /// Generate a cryptographically random code verifier for PKCE
-fn generate_code_verifier() -> String {
+pub(crate) fn generate_code_verifier() -> String {
     let mut rng = rand::thread_rng();
     let bytes: Vec<u8> = (0..32).map(|_| rng.gen::<u8>()).collect();
     URL_SAFE_NO_PAD.encode(&bytes)
 }
 
 /// Generate code challenge from verifier using S256 method (RFC 7636)
-fn generate_code_challenge(verifier: &str) -> String {
+pub(crate) fn generate_code_challenge(verifier: &str) -> String {
     use sha2::{Sha256, Digest};
     
     // SHA256 hash of the verifier
@@ -200,7 +201,7 @@ pub fn init_google_credentials_from_env(conn: &rusqlite::Connection) -> bool {
 fn build_auth_url(client_id: &str, code_challenge: &str, state: &str, port: u16) -> String {
     let redirect_uri = format!("http://127.0.0.1:{}/callback", port);
     format!(
-        "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&code_challenge={}&code_challenge_method=S256&state={}&access_type=offline&prompt=consent",
+        "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&code_challenge={}&code_challenge_method=S256&state={}&access_type=offline&prompt=consent&include_granted_scopes=true",
         GOOGLE_AUTH_URL,
         urlencoding::encode(client_id),
         urlencoding::encode(&redirect_uri),
@@ -211,13 +212,13 @@ fn build_auth_url(client_id: &str, code_challenge: &str, state: &str, port: u16)
 }
 
 /// Get redirect URI for a given port
-fn get_redirect_uri(port: u16) -> String {
+pub(crate) fn get_redirect_uri(port: u16) -> String {
     format!("http://127.0.0.1:{}/callback", port)
 }
 
 /// Start a temporary HTTP server to receive the OAuth callback
 /// Returns the receiver and the port that was successfully bound
-fn start_callback_server(expected_state: String) -> Result<(mpsc::Receiver<Result<String, String>>, u16), GoogleAuthError> {
+pub(crate) fn start_callback_server(expected_state: String) -> Result<(mpsc::Receiver<Result<String, String>>, u16), GoogleAuthError> {
     let (tx, rx) = mpsc::channel();
     
     // Try to find an available port
@@ -443,7 +444,10 @@ fn save_account(
              access_token = excluded.access_token,
              refresh_token = CASE WHEN excluded.refresh_token = '' THEN google_accounts.refresh_token ELSE excluded.refresh_token END,
              token_expires_at = excluded.token_expires_at,
-             connected_at = excluded.connected_at",
+             connected_at = excluded.connected_at,
+             sync_token = NULL,
+             sync_window_start = NULL,
+             sync_window_end = NULL",
         rusqlite::params![email, access_token, refresh_token, expires_at, now],
     )?;
     
@@ -482,57 +486,104 @@ pub fn disconnect_account(conn: &Connection) -> Result<(), GoogleAuthError> {
     Ok(())
 }
 
-/// Refresh the access token if it's expired or about to expire (pool version)
+/// Refresh access tokens this many seconds before they expire
+const REFRESH_BUFFER_SECS: i64 = 300;
+
+/// Whether a token expiring at `expires_at` (unix seconds) should be refreshed at `now`.
+/// Tokens without a known expiry are used as-is.
+pub fn token_needs_refresh(expires_at: Option<i64>, now: i64) -> bool {
+    expires_at.is_some_and(|exp| exp - REFRESH_BUFFER_SECS <= now)
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+/// Exchanges a refresh token for a new access token
+#[async_trait]
+trait TokenExchange: Send + Sync {
+    async fn refresh(&self, refresh_token: &str) -> Result<TokenResponse, GoogleAuthError>;
+}
+
+/// Google's OAuth token endpoint, using the client credentials stored in the database
+struct GoogleTokenEndpoint<'a> {
+    pool: &'a DbPool,
+}
+
+#[async_trait]
+impl TokenExchange for GoogleTokenEndpoint<'_> {
+    async fn refresh(&self, refresh_token: &str) -> Result<TokenResponse, GoogleAuthError> {
+        // Get client_id and client_secret from database or fallback
+        let (client_id, client_secret) = {
+            let conn = self.pool.get().map_err(|e| GoogleAuthError::OAuthError(e.to_string()))?;
+            (get_client_id_with_db(&conn)?, get_client_secret_with_db(&conn)?)
+        };
+
+        let params = [
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+            ("refresh_token", refresh_token),
+            ("grant_type", "refresh_token"),
+        ];
+
+        let response = Client::new()
+            .post(GOOGLE_TOKEN_URL)
+            .form(&params)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(GoogleAuthError::RefreshFailed(error_text));
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+/// Refresh the access token if it's expired or about to expire (pool version).
+///
+/// Called before every Calendar API request, so a token that expires mid-session is
+/// replaced transparently. Only a failed refresh is surfaced as an error.
 pub async fn refresh_token_if_needed_with_pool(pool: &DbPool) -> Result<String, GoogleAuthError> {
-    // Get account info from database
-    let (account, now) = {
+    refresh_token_if_needed(pool, &GoogleTokenEndpoint { pool }, unix_now()).await
+}
+
+/// Held while refreshing, so concurrent callers don't each spend the same refresh token
+static REFRESH_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+async fn refresh_token_if_needed<E: TokenExchange>(
+    pool: &DbPool,
+    exchange: &E,
+    now: i64,
+) -> Result<String, GoogleAuthError> {
+    let load_account = || {
         let conn = pool.get().map_err(|e| GoogleAuthError::OAuthError(e.to_string()))?;
-        let account = get_connection_status(&conn)?.ok_or(GoogleAuthError::NotConnected)?;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        (account, now)
+        get_connection_status(&conn)?.ok_or(GoogleAuthError::NotConnected)
     };
-    
-    // Check if token is expired or will expire in the next 5 minutes
-    let needs_refresh = account.token_expires_at
-        .map(|exp| exp - 300 < now) // 5 minute buffer
-        .unwrap_or(false);
-    
-    if !needs_refresh {
+
+    let account = load_account()?;
+    if !token_needs_refresh(account.token_expires_at, now) {
         return Ok(account.access_token);
     }
-    
-    // Refresh the token (async HTTP call)
-    // Get client_id and client_secret from database or fallback
-    let (client_id, client_secret) = {
-        let conn = pool.get().map_err(|e| GoogleAuthError::OAuthError(e.to_string()))?;
-        let id = get_client_id_with_db(&conn)?;
-        let secret = get_client_secret_with_db(&conn)?;
-        (id, secret)
-    };
-    let client = Client::new();
-    
-    let params = [
-        ("client_id", client_id.as_str()),
-        ("client_secret", client_secret.as_str()),
-        ("refresh_token", &account.refresh_token),
-        ("grant_type", "refresh_token"),
-    ];
-    
-    let response = client
-        .post(GOOGLE_TOKEN_URL)
-        .form(&params)
-        .send()
-        .await?;
-    
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        
-        // Check if this is a token revocation or invalid grant error
-        // These indicate the refresh token is no longer valid and user needs to re-authenticate
-        if error_text.contains("invalid_grant") || error_text.contains("Token has been revoked") {
+
+    // Another caller may have refreshed while we waited, so read the account again
+    let _guard = REFRESH_LOCK.lock().await;
+    let account = load_account()?;
+    if !token_needs_refresh(account.token_expires_at, now) {
+        return Ok(account.access_token);
+    }
+
+    let tokens = match exchange.refresh(&account.refresh_token).await {
+        Ok(tokens) => tokens,
+        // A revoked or invalid grant means the refresh token is no longer valid and the
+        // user needs to re-authenticate
+        Err(GoogleAuthError::RefreshFailed(error_text))
+            if error_text.contains("invalid_grant") || error_text.contains("Token has been revoked") =>
+        {
             // Clear the invalid account from database to force re-authentication
             if let Ok(conn) = pool.get() {
                 let _ = disconnect_account(&conn);
@@ -542,24 +593,23 @@ pub async fn refresh_token_if_needed_with_pool(pool: &DbPool) -> Result<String,
                 "Your Google connection has expired. Please reconnect your Google account in Settings.".to_string()
             ));
         }
-        
-        return Err(GoogleAuthError::RefreshFailed(error_text));
-    }
-    
-    let tokens: TokenResponse = response.json().await?;
-    
+        Err(e) => return Err(e),
+    };
+
     // Calculate new expiry
     let expires_at = tokens.expires_in.map(|secs| now + secs as i64);
-    
-    // Update database with new token
+
+    // Update database with new token. Google may also rotate the refresh token.
     {
         let conn = pool.get().map_err(|e| GoogleAuthError::OAuthError(e.to_string()))?;
         conn.execute(
-            "UPDATE google_accounts SET access_token = ?1, token_expires_at = ?2 WHERE id = 'default'",
-            rusqlite::params![tokens.access_token, expires_at],
+            "UPDATE google_accounts
+             SET access_token = ?1, token_expires_at = ?2, refresh_token = COALESCE(?3, refresh_token)
+             WHERE id = 'default'",
+            rusqlite::params![tokens.access_token, expires_at, tokens.refresh_token],
         )?;
     }
-    
+
     Ok(tokens.access_token)
 }
 
@@ -609,7 +659,7 @@ pub mod urlencoding {
 }
 
 // Add the open crate functionality inline
-mod open {
+pub(crate) mod open {
     pub fn that(url: &str) -> Result<(), std::io::Error> {
         #[cfg(target_os = "macos")]
         {
@@ -631,3 +681,120 @@ mod open {
 
 use rusqlite::OptionalExtension;
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+    use std::sync::Mutex;
+
+    /// Token endpoint stand-in that records the refresh tokens it was given
+    struct MockTokenExchange {
+        response: Result<(&'static str, Option<&'static str>), &'static str>,
+        requests: Mutex<Vec<String>>,
+    }
+
+    impl MockTokenExchange {
+        fn new(response: Result<(&'static str, Option<&'static str>), &'static str>) -> Self {
+            Self {
+                response,
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl TokenExchange for MockTokenExchange {
+        async fn refresh(&self, refresh_token: &str) -> Result<TokenResponse, GoogleAuthError> {
+            self.requests.lock().unwrap().push(refresh_token.to_string());
+            // Like a network round trip, lets concurrent callers interleave
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            match self.response {
+                Ok((access_token, refresh_token)) => Ok(TokenResponse {
+                    access_token: access_token.to_string(),
+                    refresh_token: refresh_token.map(str::to_string),
+                    expires_in: Some(3600),
+                }),
+                Err(error) => Err(GoogleAuthError::RefreshFailed(error.to_string())),
+            }
+        }
+    }
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn connected_pool(expires_at: i64) -> DbPool {
+        let pool = init_test_pool().unwrap();
+        save_account(&pool.get().unwrap(), "me@example.com", "old-access", "old-refresh", Some(expires_at)).unwrap();
+        pool
+    }
+
+    #[test]
+    fn test_token_needs_refresh() {
+        assert!(!token_needs_refresh(None, NOW));
+        assert!(!token_needs_refresh(Some(NOW + 3600), NOW));
+        // Inside the buffer window
+        assert!(token_needs_refresh(Some(NOW + 60), NOW));
+        assert!(token_needs_refresh(Some(NOW + REFRESH_BUFFER_SECS), NOW));
+        assert!(token_needs_refresh(Some(NOW - 10), NOW));
+    }
+
+    #[tokio::test]
+    async fn test_valid_token_is_not_refreshed() {
+        let pool = connected_pool(NOW + 3600);
+        let exchange = MockTokenExchange::new(Ok(("new-access", None)));
+
+        let token = refresh_token_if_needed(&pool, &exchange, NOW).await.unwrap();
+        assert_eq!(token, "old-access");
+        assert!(exchange.requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_expiring_token_is_refreshed_and_persisted() {
+        let pool = connected_pool(NOW + 60);
+        let exchange = MockTokenExchange::new(Ok(("new-access", Some("new-refresh"))));
+
+        let token = refresh_token_if_needed(&pool, &exchange, NOW).await.unwrap();
+        assert_eq!(token, "new-access");
+        assert_eq!(*exchange.requests.lock().unwrap(), vec!["old-refresh".to_string()]);
+
+        let account = get_connection_status(&pool.get().unwrap()).unwrap().unwrap();
+        assert_eq!(account.access_token, "new-access");
+        assert_eq!(account.refresh_token, "new-refresh");
+        assert_eq!(account.token_expires_at, Some(NOW + 3600));
+
+        // The persisted token is reused until it nears expiry again
+        let token = refresh_token_if_needed(&pool, &exchange, NOW + 60).await.unwrap();
+        assert_eq!(token, "new-access");
+        assert_eq!(exchange.requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_refreshes_spend_the_refresh_token_once() {
+        let pool = connected_pool(NOW + 60);
+        let exchange = MockTokenExchange::new(Ok(("new-access", Some("new-refresh"))));
+
+        let (first, second) = tokio::join!(
+            refresh_token_if_needed(&pool, &exchange, NOW),
+            refresh_token_if_needed(&pool, &exchange, NOW),
+        );
+        assert_eq!(first.unwrap(), "new-access");
+        assert_eq!(second.unwrap(), "new-access");
+        assert_eq!(*exchange.requests.lock().unwrap(), vec!["old-refresh".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_surfaces_error() {
+        // A transient failure keeps the account connected
+        let pool = connected_pool(NOW - 10);
+        let exchange = MockTokenExchange::new(Err("backend error"));
+        let err = refresh_token_if_needed(&pool, &exchange, NOW).await.unwrap_err();
+        assert!(matches!(err, GoogleAuthError::RefreshFailed(msg) if msg == "backend error"));
+        assert!(get_connection_status(&pool.get().unwrap()).unwrap().is_some());
+
+        // A revoked grant disconnects so the user is prompted to reconnect
+        let exchange = MockTokenExchange::new(Err(r#"{"error": "invalid_grant"}"#));
+        let err = refresh_token_if_needed(&pool, &exchange, NOW).await.unwrap_err();
+        assert!(err.to_string().contains("reconnect"));
+        assert!(get_connection_status(&pool.get().unwrap()).unwrap().is_none());
+    }
+}
@@ -1,15 +1,17 @@
 mod ai;
+mod calendar;
 mod commands;
 mod db;
 mod exports;
 mod google;
+mod microsoft;
 mod models;
 mod search;
 mod sidecar;
 mod vault;
 
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use tauri::{
     menu::{MenuBuilder, MenuItemBuilder, SubmenuBuilder, PredefinedMenuItem},
     Emitter, Manager,
@@ -20,6 +22,9 @@ use ai::init_ai_config;
 use db::connection::{self, DbPool};
 use search::SearchIndex;
 
+/// Notes read per query when rebuilding the search index at startup
+const REINDEX_PAGE_SIZE: usize = 500;
+
 /// Open a path or URL in the default system handler
 fn open_path(path: &str) -> std::io::Result<()> {
     #[cfg(target_os = "macos")]
@@ -45,6 +50,9 @@ pub struct AppPool(pub RwLock<Option<DbPool>>);
 /// Wrapper for optional search index
 pub struct AppSearchIndex(pub RwLock<Option<Arc<SearchIndex>>>);
 
+/// File watcher for the vault's notes directory, if one is running
+pub struct AppVaultWatcher(pub Mutex<Option<vault::watcher::VaultWatcher>>);
+
 /// Active stream cancellation tokens, keyed by session ID
 /// Used to stop generation when user requests it
 pub struct ActiveStreams(pub RwLock<HashMap<String, watch::Sender<bool>>>);
@@ -60,30 +68,98 @@ fn initialize_for_vault() -> Result<(DbPool, Arc<SearchIndex>), String> {
     // Initialize the search index
     let search_index_path = connection::get_search_index_path()
         .map_err(|e| format!("Failed to get search index path: {}", e))?;
+    let analyzer = pool
+        .get()
+        .map(|conn| commands::load_search_analyzer(&conn))
+        .unwrap_or_default();
     let search_index = Arc::new(
-        SearchIndex::new(search_index_path).map_err(|e| format!("Failed to initialize search index: {}", e))?
+        SearchIndex::with_analyzer(search_index_path, analyzer)
+            .map_err(|e| format!("Failed to initialize search index: {}", e))?
     );
 
-    // Rebuild index if empty (first run or index was deleted)
-    if search_index.is_empty() {
-        if let Ok(conn) = pool.get() {
-            if let Ok(notes) = db::notes::get_all_notes(&conn) {
-                let note_data: Vec<(String, String, Option<String>)> = notes
-                    .into_iter()
-                    .filter(|n| !n.is_deleted)
-                    .map(|n| (n.id, n.title, n.content))
-                    .collect();
-                
-                if !note_data.is_empty() {
-                    let _ = search_index.rebuild(note_data);
+    // Rebuild index if empty (first run or index was deleted) or it was
+    // discarded for being from an older schema or corrupt
+    if search_index.needs_rebuild() {
+        match pool.get() {
+            Ok(conn) => {
+                if let Err(e) = reindex_notes(&conn, &search_index) {
+                    log::warn!("Failed to rebuild search index: {}", e);
                 }
             }
+            Err(e) => log::warn!("Failed to rebuild search index: {}", e),
         }
     }
 
     Ok((pool, search_index))
 }
 
+/// Rebuild the search index from every note in the database
+///
+/// Notes are read a page at a time so large vaults aren't loaded into memory at
+/// once. A page that fails to load aborts the rebuild rather than leaving the
+/// index with only the notes read so far.
+fn reindex_notes(conn: &rusqlite::Connection, search_index: &SearchIndex) -> Result<(), search::SearchError> {
+    let mut last_id: Option<String> = None;
+    let mut done = false;
+    let pages = std::iter::from_fn(|| {
+        if done {
+            return None;
+        }
+        match db::notes::get_notes_after(conn, last_id.as_deref(), REINDEX_PAGE_SIZE) {
+            Ok(page) if page.is_empty() => None,
+            Ok(page) => {
+                last_id = page.last().map(|n| n.id.clone());
+                Some(page.into_iter().map(|n| Ok((n.id, n.title, n.content))).collect::<Vec<_>>())
+            }
+            Err(e) => {
+                done = true;
+                Some(vec![Err(search::SearchError::SourceError(e.to_string()))])
+            }
+        }
+    });
+
+    search_index.rebuild(pages.flatten())
+}
+
+/// Watch the vault's notes directory, telling the frontend about notes changed,
+/// removed or in conflict on disk
+fn start_vault_watcher(app: &tauri::AppHandle, pool: DbPool) -> Option<vault::watcher::VaultWatcher> {
+    let notes_dir = vault::get_notes_dir().ok()?;
+    let app = app.clone();
+    let index_pool = pool.clone();
+    let result = vault::watcher::start_watcher(pool, &notes_dir, vault::watcher::DEFAULT_DEBOUNCE, move |outcome| {
+        {
+            let search_index = app.state::<AppSearchIndex>();
+            let search_guard = search_index.0.read().unwrap();
+            if let Some(ref index) = *search_guard {
+                vault::watcher::update_search_index(&index_pool, index, &outcome);
+            }
+        }
+        for note_id in outcome.updated_note_ids {
+            let _ = app.emit("note-content-updated", serde_json::json!({
+                "noteId": note_id,
+                "source": "vault-watcher",
+            }));
+        }
+        for conflict in outcome.conflicts {
+            let _ = app.emit("note-sync-conflict", conflict);
+        }
+        for note_id in &outcome.removed_note_ids {
+            let _ = app.emit("note-removed", serde_json::json!({
+                "noteId": note_id,
+                "source": "vault-watcher",
+            }));
+        }
+    });
+    match result {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            log::warn!("Failed to watch vault notes: {}", e);
+            None
+        }
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize logger - show info level and above for our crate, warn for others
@@ -98,6 +174,10 @@ pub fn run() {
         log::info!("Loaded vault path: {:?}", vault_path);
         vault::set_current_vault_path(Some(vault_path));
     }
+    if let Ok(Some(exports_dir)) = vault::load_exports_dir_override() {
+        log::info!("Loaded exports directory: {:?}", exports_dir);
+        vault::set_exports_dir_override(Some(exports_dir));
+    }
 
     // Initialize pool and search index (will use vault path if set, otherwise legacy path)
     let (initial_pool, initial_search_index) = match initialize_for_vault() {
@@ -394,6 +474,11 @@ pub fn run() {
                 }
             });
 
+            // Re-import notes edited outside the app while it's running
+            let watched = app.state::<AppPool>().0.read().unwrap().clone();
+            let watcher = watched.and_then(|pool| start_vault_watcher(app.handle(), pool));
+            app.manage(AppVaultWatcher(Mutex::new(watcher)));
+
             // Open devtools automatically if DEBUG_DEVTOOLS env var is set
             // Run with: DEBUG_DEVTOOLS=1 ./Inkling.app/Contents/MacOS/Inkling
             if std::env::var("DEBUG_DEVTOOLS").is_ok() {
@@ -407,9 +492,14 @@ pub fn run() {
             // Note commands
             commands::create_note,
             commands::get_note,
+            commands::get_note_stats,
+            commands::get_vault_stats,
             commands::get_all_notes,
+            commands::get_notes_paginated,
             commands::get_notes_in_folder,
             commands::update_note,
+            commands::list_note_revisions,
+            commands::revert_note_to_revision,
             commands::move_note_to_folder,
             commands::delete_note,
             commands::search_notes,
@@ -422,6 +512,7 @@ pub fn run() {
             commands::delete_folder,
             // Board commands
             commands::create_board,
+            commands::create_board_from_template,
             commands::get_board,
             commands::get_board_by_folder,
             commands::get_all_boards,
@@ -432,31 +523,46 @@ pub fn run() {
             commands::create_lane,
             commands::get_lanes_for_board,
             commands::update_lane,
+            commands::set_lane_wip_limit,
             commands::delete_lane,
             commands::reorder_lanes,
             // Board card commands
             commands::add_card,
             commands::get_cards_for_board,
             commands::get_cards_in_lane,
+            commands::get_cards_in_lane_sorted,
             commands::move_card,
+            commands::set_card_due_date,
             commands::remove_card,
             commands::get_boards_for_note,
+            commands::get_card_placements_for_note,
+            commands::export_board_to_note,
             // AI commands
             commands::get_ai_config,
             commands::save_ai_config_cmd,
             commands::update_provider,
             commands::set_default_provider,
+            commands::set_fallback_providers,
+            commands::get_model_pricing,
+            commands::set_model_pricing,
             commands::apply_ai_config,
             commands::init_ai_config_cmd,
             commands::test_provider,
             commands::detect_local_models,
             commands::detect_ollama,
             commands::detect_lmstudio_cmd,
+            commands::refresh_provider_models,
             commands::get_default_providers,
             commands::get_provider_info,
             // Search commands
             commands::search_notes_unified,
+            commands::search_notes_in_folder,
+            commands::find_in_note,
             commands::get_related_notes,
+            commands::find_duplicate_notes,
+            commands::rebuild_search_index,
+            commands::get_search_analyzer,
+            commands::set_search_analyzer,
             commands::get_embedding_stats,
             commands::get_embedding_models,
             commands::detect_embedding_dimension,
@@ -470,36 +576,65 @@ pub fn run() {
             commands::sync_note_links,
             commands::search_notes_for_mention,
             commands::get_outgoing_links,
+            commands::get_orphan_notes,
+            commands::get_broken_links,
+            commands::get_note_aliases,
+            commands::add_note_alias,
+            commands::remove_note_alias,
+            commands::update_backlinks_section,
             // Chat commands
             commands::create_conversation,
             commands::get_conversation,
             commands::get_conversation_with_messages,
             commands::list_conversations,
             commands::list_conversation_previews,
+            commands::search_conversations,
+            commands::branch_conversation,
+            commands::list_system_prompts,
+            commands::save_system_prompt,
+            commands::delete_system_prompt,
             commands::update_conversation,
+            commands::get_conversation_reasoning,
+            commands::get_conversation_cost,
+            commands::update_conversation_reasoning,
             commands::delete_conversation,
             commands::get_conversation_messages,
             commands::send_chat_message,
             commands::send_chat_message_sync,
             commands::edit_message_and_regenerate,
+            commands::regenerate_last_response,
             commands::get_default_system_prompt,
             commands::stop_generation,
+            commands::list_active_streams,
+            commands::prune_active_streams,
             // Vault commands
             commands::get_vault_path,
             commands::get_vault_status,
             commands::set_vault_path,
+            commands::get_exports_dir_override,
+            commands::set_exports_dir_override,
             commands::create_vault,
             commands::validate_vault,
             commands::has_existing_data,
             commands::migrate_to_vault,
             commands::save_attachment,
+            commands::gc_attachments,
+            commands::import_markdown_directory,
+            commands::backup_vault,
+            commands::restore_vault,
+            commands::get_schema_version,
+            commands::list_pending_migrations,
             // Data management commands
             commands::clear_chats,
             commands::clear_notes,
+            commands::clear_notes_in_folder,
+            commands::clear_conversations_older_than,
             commands::clear_ai_config,
+            commands::get_factory_reset_token,
             commands::factory_reset,
             commands::sync_vault_to_disk,
             commands::sync_disk_to_vault,
+            commands::resolve_conflict,
             // Tag commands
             commands::get_all_tags,
             commands::search_tags,
@@ -509,14 +644,25 @@ pub fn run() {
             commands::remove_tag_from_note,
             commands::delete_tag,
             commands::update_tag,
+            commands::merge_tags,
+            commands::suggest_tags_for_note,
             commands::run_tagging_agent_cmd,
+            commands::run_tagging_agent_batch,
             // Daily Notes commands
             commands::get_or_create_daily_notes_folder,
             commands::get_daily_note,
             commands::create_daily_note,
+            commands::get_daily_note_template,
+            commands::set_daily_note_template,
             commands::get_adjacent_daily_note,
             commands::get_all_daily_notes,
             commands::is_daily_note,
+            commands::get_or_create_periodic_notes_folder,
+            commands::get_periodic_note,
+            commands::create_periodic_note,
+            commands::get_adjacent_periodic_note,
+            commands::get_periodic_note_template,
+            commands::set_periodic_note_template,
             // Agent commands
             commands::get_agent_config,
             commands::save_agent_config,
@@ -535,24 +681,37 @@ pub fn run() {
             commands::get_all_calendar_events,
             commands::get_calendar_events_in_range,
             commands::get_calendar_events_for_date,
+            commands::check_event_conflicts,
             commands::update_calendar_event,
             commands::delete_calendar_event,
             commands::link_note_to_calendar_event,
+            commands::create_meeting_note,
             commands::unlink_note_from_calendar_event,
+            commands::import_calendar_ics,
+            commands::export_calendar_ics,
             // Google integration commands
             commands::is_google_configured,
             commands::initiate_google_auth,
             commands::get_google_connection_status,
             commands::disconnect_google_account,
             commands::sync_google_calendar,
+            commands::push_google_calendar,
             commands::get_event_meeting_info,
             commands::save_google_credentials,
             commands::clear_google_credentials,
             commands::get_google_credential_source,
             commands::get_current_google_credentials,
+            // Microsoft integration commands
+            commands::initiate_microsoft_auth,
+            commands::get_microsoft_connection_status,
+            commands::disconnect_microsoft_account,
+            commands::sync_microsoft_calendar,
             // Export commands
             commands::list_exports,
             commands::list_exports_by_format,
+            commands::regenerate_export,
+            commands::delete_exports_by_format,
+            commands::delete_exports_older_than,
             commands::get_export,
             commands::delete_export,
             commands::open_export,
@@ -563,10 +722,12 @@ pub fn run() {
             commands::export_notes_to_pdf,
             commands::export_notes_to_docx,
             commands::export_content_to_xlsx,
+            commands::export_conversation,
             commands::export_notes_to_pptx,
             commands::run_export_agent_cmd,
             // Assistant commands
             commands::generate_assistant_content,
+            commands::stream_assistant_content,
             commands::get_assistant_fallback,
             // URL attachment commands
             commands::add_url_attachment,
@@ -0,0 +1,563 @@
+//! Outlook calendar integration through the Microsoft Graph API
+//!
+//! Fetches events from the signed-in user's calendar and syncs them into the local
+//! database as `microsoft` events, using the same event model as Google events.
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use reqwest::Client;
+use rusqlite::Connection;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::db::calendar_events;
+use crate::db::connection::DbPool;
+use crate::google::calendar::SyncResult;
+use crate::google::oauth::urlencoding;
+use crate::models::{
+    CalendarEvent, CalendarEventSource, CalendarEventType, CreateCalendarEventInput, EventAttendee,
+    EventResponseStatus,
+};
+
+use super::oauth::{refresh_token_if_needed_with_pool, MicrosoftAuthError};
+
+const GRAPH_API: &str = "https://graph.microsoft.com/v1.0";
+
+#[derive(Error, Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum MicrosoftCalendarError {
+    #[error("Auth error: {0}")]
+    AuthError(#[from] MicrosoftAuthError),
+    #[error("HTTP request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("Database error: {0}")]
+    DbError(String),
+    #[error("API error: {0}")]
+    ApiError(String),
+}
+
+/// Graph email address
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphEmailAddress {
+    pub name: Option<String>,
+    pub address: Option<String>,
+}
+
+/// Graph attendee
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphAttendee {
+    #[serde(rename = "emailAddress")]
+    pub email_address: GraphEmailAddress,
+    pub status: Option<GraphResponseStatus>,
+}
+
+/// Graph organizer
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphOrganizer {
+    #[serde(rename = "emailAddress")]
+    pub email_address: GraphEmailAddress,
+}
+
+/// Graph response status: none, organizer, tentativelyAccepted, accepted, declined, notResponded
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphResponseStatus {
+    pub response: Option<String>,
+}
+
+/// Graph date/time with the timezone it is expressed in
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphDateTime {
+    #[serde(rename = "dateTime")]
+    pub date_time: String,
+    #[serde(rename = "timeZone")]
+    pub time_zone: Option<String>,
+}
+
+/// Graph item body
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphItemBody {
+    #[serde(rename = "contentType")]
+    pub content_type: Option<String>,
+    pub content: Option<String>,
+}
+
+/// Graph online meeting info
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphOnlineMeeting {
+    #[serde(rename = "joinUrl")]
+    pub join_url: Option<String>,
+}
+
+/// Graph event location
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphLocation {
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+}
+
+/// Graph calendar event
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphEvent {
+    pub id: String,
+    pub subject: Option<String>,
+    #[serde(rename = "bodyPreview")]
+    pub body_preview: Option<String>,
+    pub body: Option<GraphItemBody>,
+    pub start: GraphDateTime,
+    pub end: Option<GraphDateTime>,
+    #[serde(rename = "isAllDay", default)]
+    pub is_all_day: bool,
+    #[serde(rename = "isCancelled", default)]
+    pub is_cancelled: bool,
+    /// free, tentative, busy, oof, workingElsewhere, unknown
+    #[serde(rename = "showAs")]
+    pub show_as: Option<String>,
+    #[serde(rename = "responseStatus")]
+    pub response_status: Option<GraphResponseStatus>,
+    pub attendees: Option<Vec<GraphAttendee>>,
+    pub organizer: Option<GraphOrganizer>,
+    #[serde(rename = "onlineMeeting")]
+    pub online_meeting: Option<GraphOnlineMeeting>,
+    #[serde(rename = "onlineMeetingUrl")]
+    pub online_meeting_url: Option<String>,
+    pub location: Option<GraphLocation>,
+    /// Timezone the organizer scheduled the event in (may be a Windows zone name)
+    #[serde(rename = "originalStartTimeZone")]
+    pub original_start_time_zone: Option<String>,
+}
+
+/// One page of a Graph collection
+#[derive(Debug, Deserialize)]
+struct GraphEventsPage {
+    #[serde(default)]
+    value: Vec<GraphEvent>,
+    #[serde(rename = "@odata.nextLink")]
+    next_link: Option<String>,
+}
+
+impl GraphDateTime {
+    /// Parse into a DateTime<Utc>. Graph returns local times without an offset,
+    /// e.g. `2024-05-06T09:00:00.0000000`, alongside the zone they are in.
+    pub fn to_datetime(&self) -> Option<DateTime<Utc>> {
+        let naive = NaiveDateTime::parse_from_str(&self.date_time, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+        match self.time_zone.as_deref() {
+            None | Some("UTC") => Some(Utc.from_utc_datetime(&naive)),
+            Some(name) => {
+                let tz: chrono_tz::Tz = name.parse().ok()?;
+                tz.from_local_datetime(&naive)
+                    .earliest()
+                    .map(|dt| dt.with_timezone(&Utc))
+            }
+        }
+    }
+
+    /// Parse as a calendar date at midnight UTC, the way all-day events are stored
+    fn to_date(&self) -> Option<DateTime<Utc>> {
+        let naive = NaiveDateTime::parse_from_str(&self.date_time, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+        naive
+            .date()
+            .and_hms_opt(0, 0, 0)
+            .map(|midnight| Utc.from_utc_datetime(&midnight))
+    }
+}
+
+/// Map a Graph response to the Google-style status strings used by the event model
+fn response_to_status(response: &str) -> Option<&'static str> {
+    match response {
+        "accepted" | "organizer" => Some("accepted"),
+        "tentativelyAccepted" => Some("tentative"),
+        "declined" => Some("declined"),
+        "notResponded" => Some("needsAction"),
+        _ => None,
+    }
+}
+
+/// Convert a Graph event to our internal format
+pub fn graph_event_to_internal(event: &GraphEvent) -> Option<CreateCalendarEventInput> {
+    let (start_time, end_time) = if event.is_all_day {
+        (event.start.to_date()?, event.end.as_ref().and_then(|e| e.to_date()))
+    } else {
+        (event.start.to_datetime()?, event.end.as_ref().and_then(|e| e.to_datetime()))
+    };
+
+    // Prefer the plain-text body; HTML bodies fall back to Graph's text preview
+    let description = match &event.body {
+        Some(GraphItemBody {
+            content_type: Some(content_type),
+            content: Some(content),
+        }) if content_type.eq_ignore_ascii_case("text") => Some(content.clone()),
+        _ => event.body_preview.clone(),
+    }
+    .filter(|d| !d.trim().is_empty());
+
+    let organizer_email = event
+        .organizer
+        .as_ref()
+        .and_then(|o| o.email_address.address.as_deref())
+        .map(str::to_lowercase);
+
+    let attendees: Vec<EventAttendee> = event
+        .attendees
+        .iter()
+        .flatten()
+        .filter_map(|attendee| {
+            let email = attendee.email_address.address.clone()?;
+            Some(EventAttendee {
+                is_organizer: organizer_email.as_deref() == Some(email.to_lowercase().as_str()),
+                name: attendee.email_address.name.clone(),
+                response_status: attendee
+                    .status
+                    .as_ref()
+                    .and_then(|s| s.response.as_deref())
+                    .and_then(response_to_status)
+                    .map(str::to_string),
+                email,
+            })
+        })
+        .collect();
+
+    let response_status = event
+        .response_status
+        .as_ref()
+        .and_then(|s| s.response.as_deref())
+        .and_then(response_to_status)
+        .map(EventResponseStatus::from_str)
+        // Events on our own calendar with nobody invited are implicitly accepted
+        .or_else(|| attendees.is_empty().then_some(EventResponseStatus::Accepted));
+
+    let event_type = match event.show_as.as_deref() {
+        Some("oof") => CalendarEventType::OutOfOffice,
+        Some("workingElsewhere") => CalendarEventType::WorkingLocation,
+        _ => CalendarEventType::Default,
+    };
+
+    let meeting_link = event
+        .online_meeting
+        .as_ref()
+        .and_then(|m| m.join_url.clone())
+        .or_else(|| event.online_meeting_url.clone());
+
+    Some(CreateCalendarEventInput {
+        title: event
+            .subject
+            .clone()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "(No title)".to_string()),
+        description,
+        start_time,
+        end_time,
+        all_day: event.is_all_day,
+        // Occurrences are already expanded by calendarView
+        recurrence_rule: None,
+        linked_note_id: None,
+        event_type: Some(event_type),
+        response_status,
+        attendees: if attendees.is_empty() { None } else { Some(attendees) },
+        meeting_link,
+        location: event
+            .location
+            .as_ref()
+            .and_then(|l| l.display_name.clone())
+            .filter(|l| !l.trim().is_empty()),
+        timezone: event
+            .original_start_time_zone
+            .clone()
+            .filter(|tz| tz.parse::<chrono_tz::Tz>().is_ok()),
+    })
+}
+
+/// Fetch every event occurrence in a range.
+///
+/// Uses `/me/calendarView`, the `/me/events` view that expands recurring series into
+/// individual occurrences for the range, and asks for all times in UTC.
+async fn fetch_events(
+    access_token: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<GraphEvent>, MicrosoftCalendarError> {
+    let client = Client::new();
+    let mut events = Vec::new();
+    let mut url = Some(format!(
+        "{}/me/calendarView?startDateTime={}&endDateTime={}&$top=100",
+        GRAPH_API,
+        urlencoding::encode(&start.to_rfc3339()),
+        urlencoding::encode(&end.to_rfc3339())
+    ));
+
+    while let Some(next) = url {
+        let response = client
+            .get(&next)
+            .bearer_auth(access_token)
+            .header("Prefer", "outlook.timezone=\"UTC\"")
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(MicrosoftCalendarError::ApiError(format!(
+                "Failed to fetch events: {}",
+                error_text
+            )));
+        }
+
+        let page: GraphEventsPage = response.json().await?;
+        events.extend(page.value);
+        url = page.next_link;
+    }
+
+    Ok(events)
+}
+
+/// Sync Outlook calendar events for a date range into the local database (pool version)
+pub async fn sync_events_to_db_with_pool(
+    pool: &DbPool,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<SyncResult, MicrosoftCalendarError> {
+    let access_token = refresh_token_if_needed_with_pool(pool).await?;
+
+    // Pad the range by a day on each side so all-day events near the edges are included
+    let events = fetch_events(
+        &access_token,
+        start - chrono::Duration::days(1),
+        end + chrono::Duration::days(1),
+    )
+    .await?;
+    let events: Vec<_> = events.into_iter().filter(|e| !e.is_cancelled).collect();
+
+    let conn = pool
+        .get()
+        .map_err(|e| MicrosoftCalendarError::DbError(e.to_string()))?;
+    apply_events(&conn, &events, start, end)
+}
+
+/// Upsert fetched events and remove local Microsoft events in the range that
+/// Graph no longer returned
+fn apply_events(
+    conn: &Connection,
+    events: &[GraphEvent],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<SyncResult, MicrosoftCalendarError> {
+    let db_error = |e: calendar_events::CalendarEventDbError| MicrosoftCalendarError::DbError(e.to_string());
+    let mut result = SyncResult {
+        events_synced: events.len(),
+        events_added: 0,
+        events_updated: 0,
+        events_removed: 0,
+    };
+    let mut seen_ids = std::collections::HashSet::new();
+
+    for event in events {
+        seen_ids.insert(event.id.as_str());
+        let Some(input) = graph_event_to_internal(event) else {
+            continue;
+        };
+
+        match calendar_events::get_event_by_external_id(conn, &event.id).map_err(db_error)? {
+            Some(existing) => {
+                let update = crate::models::UpdateCalendarEventInput {
+                    title: Some(input.title),
+                    description: input.description,
+                    start_time: Some(input.start_time),
+                    end_time: input.end_time,
+                    all_day: Some(input.all_day),
+                    recurrence_rule: input.recurrence_rule,
+                    linked_note_id: None, // Preserve existing link
+                    event_type: input.event_type,
+                    response_status: input.response_status,
+                    attendees: input.attendees,
+                    meeting_link: input.meeting_link,
+                    location: input.location,
+                    timezone: input.timezone,
+                };
+                if calendar_events::update_event(conn, &existing.id, update).is_ok() {
+                    result.events_updated += 1;
+                }
+            }
+            None => {
+                if create_microsoft_event(conn, &event.id, input).is_ok() {
+                    result.events_added += 1;
+                }
+            }
+        }
+    }
+
+    // All-day events are stored at midnight UTC, so compare them by date
+    let (start_date, end_date) = (start.date_naive(), end.date_naive());
+    for event in calendar_events::get_events_by_source(conn, &CalendarEventSource::Microsoft).map_err(db_error)? {
+        let in_range = if event.all_day {
+            let date = event.start_time.date_naive();
+            date >= start_date && date <= end_date
+        } else {
+            event.start_time >= start && event.start_time < end
+        };
+        let gone = event
+            .external_id
+            .as_deref()
+            .is_some_and(|id| !seen_ids.contains(id));
+        if in_range && gone && calendar_events::delete_event(conn, &event.id).is_ok() {
+            result.events_removed += 1;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Create a new event with the microsoft source
+fn create_microsoft_event(
+    conn: &Connection,
+    external_id: &str,
+    input: CreateCalendarEventInput,
+) -> Result<CalendarEvent, MicrosoftCalendarError> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let start_time = input.start_time.format("%Y-%m-%d %H:%M:%S").to_string();
+    let end_time = input.end_time.map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string());
+    let event_type = input.event_type.as_ref().map(|t| t.as_str()).unwrap_or("default");
+    let response_status = input.response_status.as_ref().map(|s| s.as_str());
+    let attendees_json = input.attendees.as_ref().and_then(|a| serde_json::to_string(a).ok());
+
+    conn.execute(
+        "INSERT INTO calendar_events (id, title, description, start_time, end_time, all_day, recurrence_rule, source, external_id, linked_note_id, event_type, response_status, attendees, meeting_link, location, timezone, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'microsoft', ?8, NULL, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        rusqlite::params![
+            id,
+            input.title,
+            input.description,
+            start_time,
+            end_time,
+            input.all_day,
+            input.recurrence_rule,
+            external_id,
+            event_type,
+            response_status,
+            attendees_json,
+            input.meeting_link,
+            input.location,
+            input.timezone,
+            now,
+            now,
+        ],
+    )
+    .map_err(|e| MicrosoftCalendarError::DbError(e.to_string()))?;
+
+    calendar_events::get_event(conn, &id)
+        .map_err(|e| MicrosoftCalendarError::DbError(e.to_string()))?
+        .ok_or_else(|| MicrosoftCalendarError::DbError("Failed to retrieve created event".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+
+    fn graph_event(value: serde_json::Value) -> GraphEvent {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn utc(d: u32, h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, d, h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_maps_timed_meeting() {
+        let event = graph_event(serde_json::json!({
+            "id": "AAMk1",
+            "subject": "Design review",
+            "bodyPreview": "Agenda: mockups",
+            "body": { "contentType": "html", "content": "<p>Agenda: mockups</p>" },
+            "start": { "dateTime": "2024-05-06T14:30:00.0000000", "timeZone": "UTC" },
+            "end": { "dateTime": "2024-05-06T15:00:00.0000000", "timeZone": "UTC" },
+            "isAllDay": false,
+            "showAs": "busy",
+            "responseStatus": { "response": "tentativelyAccepted" },
+            "organizer": { "emailAddress": { "name": "Ada", "address": "Ada@example.com" } },
+            "attendees": [
+                { "emailAddress": { "name": "Ada", "address": "ada@example.com" }, "status": { "response": "organizer" } },
+                { "emailAddress": { "name": "Bob", "address": "bob@example.com" }, "status": { "response": "declined" } }
+            ],
+            "onlineMeeting": { "joinUrl": "https://teams.microsoft.com/l/meetup-join/abc" },
+            "originalStartTimeZone": "Pacific Standard Time"
+        }));
+
+        let input = graph_event_to_internal(&event).unwrap();
+        assert_eq!(input.title, "Design review");
+        assert_eq!(input.description.as_deref(), Some("Agenda: mockups"));
+        assert_eq!(input.start_time, utc(6, 14, 30));
+        assert_eq!(input.end_time, Some(utc(6, 15, 0)));
+        assert!(!input.all_day);
+        assert_eq!(input.response_status, Some(EventResponseStatus::Tentative));
+        assert_eq!(input.event_type, Some(CalendarEventType::Default));
+        assert_eq!(input.meeting_link.as_deref(), Some("https://teams.microsoft.com/l/meetup-join/abc"));
+        // Windows zone names aren't IANA zones, so the timezone is left unset
+        assert_eq!(input.timezone, None);
+
+        let attendees = input.attendees.unwrap();
+        assert_eq!(attendees.len(), 2);
+        assert!(attendees[0].is_organizer);
+        assert_eq!(attendees[0].response_status.as_deref(), Some("accepted"));
+        assert_eq!(attendees[1].name.as_deref(), Some("Bob"));
+        assert_eq!(attendees[1].response_status.as_deref(), Some("declined"));
+    }
+
+    #[test]
+    fn test_maps_all_day_out_of_office() {
+        let event = graph_event(serde_json::json!({
+            "id": "AAMk2",
+            "subject": "",
+            "body": { "contentType": "text", "content": "Back Monday" },
+            "start": { "dateTime": "2024-05-09T00:00:00.0000000", "timeZone": "UTC" },
+            "end": { "dateTime": "2024-05-11T00:00:00.0000000", "timeZone": "UTC" },
+            "isAllDay": true,
+            "showAs": "oof",
+            "responseStatus": { "response": "none" },
+            "originalStartTimeZone": "Europe/Berlin"
+        }));
+
+        let input = graph_event_to_internal(&event).unwrap();
+        assert_eq!(input.title, "(No title)");
+        assert_eq!(input.description.as_deref(), Some("Back Monday"));
+        assert!(input.all_day);
+        assert_eq!(input.start_time, utc(9, 0, 0));
+        assert_eq!(input.end_time, Some(utc(11, 0, 0)));
+        assert_eq!(input.event_type, Some(CalendarEventType::OutOfOffice));
+        // No attendees and no response means it's our own event
+        assert_eq!(input.response_status, Some(EventResponseStatus::Accepted));
+        assert!(input.attendees.is_none());
+        assert_eq!(input.timezone.as_deref(), Some("Europe/Berlin"));
+    }
+
+    #[test]
+    fn test_converts_non_utc_times() {
+        let time = GraphDateTime {
+            date_time: "2024-05-06T09:00:00.0000000".to_string(),
+            time_zone: Some("America/New_York".to_string()),
+        };
+        assert_eq!(time.to_datetime(), Some(utc(6, 13, 0)));
+    }
+
+    #[test]
+    fn test_apply_events_upserts_and_removes() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let event = |id: &str, subject: &str| {
+            graph_event(serde_json::json!({
+                "id": id,
+                "subject": subject,
+                "start": { "dateTime": "2024-05-06T09:00:00", "timeZone": "UTC" },
+                "end": { "dateTime": "2024-05-06T10:00:00", "timeZone": "UTC" }
+            }))
+        };
+
+        let result = apply_events(&conn, &[event("a", "Standup"), event("b", "1:1")], utc(1, 0, 0), utc(31, 0, 0)).unwrap();
+        assert_eq!(result.events_added, 2);
+
+        let result = apply_events(&conn, &[event("a", "Standup (moved)")], utc(1, 0, 0), utc(31, 0, 0)).unwrap();
+        assert_eq!((result.events_updated, result.events_removed), (1, 1));
+
+        let events = calendar_events::get_events_by_source(&conn, &CalendarEventSource::Microsoft).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].title, "Standup (moved)");
+        assert_eq!(events[0].source, CalendarEventSource::Microsoft);
+    }
+}
@@ -0,0 +1,14 @@
+//! Microsoft OAuth configuration
+//!
+//! The application (client) ID is injected at build time via an environment variable.
+//! Desktop apps are registered as public clients, so no client secret is needed;
+//! security comes from PKCE.
+//!
+//! To build with Outlook calendar support:
+//!   MICROSOFT_CLIENT_ID="your-app-id" npm run build
+
+/// The Microsoft application (client) ID, injected at compile time
+///
+/// Set MICROSOFT_CLIENT_ID environment variable before building.
+/// For development: export MICROSOFT_CLIENT_ID="your-app-id"
+pub const EMBEDDED_CLIENT_ID: Option<&str> = option_env!("MICROSOFT_CLIENT_ID");
@@ -0,0 +1,12 @@
+//! Microsoft integration module
+//!
+//! Provides OAuth 2.0 authentication with the Microsoft identity platform and
+//! Outlook / Microsoft 365 calendar access through the Graph API.
+
+pub mod config;
+pub mod oauth;
+pub mod calendar;
+
+pub use oauth::{
+    MicrosoftAccount, get_connection_status, disconnect_account,
+};
@@ -0,0 +1,308 @@
+//! Microsoft OAuth 2.0 with PKCE flow
+//!
+//! Signs in against the Microsoft identity platform (`/common` endpoint, so both
+//! work/school and personal accounts work) using the authorization code flow with PKCE.
+//! The loopback callback server and PKCE helpers are shared with the Google integration.
+
+use reqwest::Client;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+use crate::db::connection::DbPool;
+use crate::google::oauth::{
+    generate_code_challenge, generate_code_verifier, get_redirect_uri, open, start_callback_server,
+    token_needs_refresh, urlencoding,
+};
+
+/// Microsoft identity platform configuration
+const MICROSOFT_AUTH_URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/authorize";
+const MICROSOFT_TOKEN_URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/token";
+const GRAPH_ME_URL: &str = "https://graph.microsoft.com/v1.0/me";
+
+/// Scopes required for reading calendar events; offline_access grants a refresh token
+const SCOPES: &str = "offline_access User.Read Calendars.Read";
+
+#[derive(Error, Debug)]
+pub enum MicrosoftAuthError {
+    #[error("No Microsoft Client ID configured. Set MICROSOFT_CLIENT_ID environment variable.")]
+    NoClientId,
+    #[error("Failed to start callback server: {0}")]
+    ServerError(String),
+    #[error("OAuth callback timeout")]
+    Timeout,
+    #[error("OAuth error: {0}")]
+    OAuthError(String),
+    #[error("HTTP request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("Database error: {0}")]
+    DbError(#[from] rusqlite::Error),
+    #[error("No account connected")]
+    NotConnected,
+    #[error("Token refresh failed: {0}")]
+    RefreshFailed(String),
+}
+
+/// Microsoft account information stored in the database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MicrosoftAccount {
+    pub id: String,
+    pub email: String,
+    #[serde(skip_serializing)]
+    pub access_token: String,
+    #[serde(skip_serializing)]
+    pub refresh_token: String,
+    pub token_expires_at: Option<i64>,
+    pub connected_at: String,
+}
+
+/// OAuth token response from the Microsoft identity platform
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+}
+
+/// Graph `/me` response. Personal accounts may not have `mail` set.
+#[derive(Debug, Deserialize)]
+struct UserInfoResponse {
+    mail: Option<String>,
+    #[serde(rename = "userPrincipalName")]
+    user_principal_name: Option<String>,
+}
+
+/// Get the Microsoft Client ID, checking the database, then the environment, then
+/// the compile-time value
+pub fn get_client_id_with_db(conn: &Connection) -> Result<String, MicrosoftAuthError> {
+    if let Ok(Some(id)) = crate::db::settings::get_setting(conn, "microsoft_client_id") {
+        if !id.is_empty() {
+            return Ok(id);
+        }
+    }
+
+    if let Ok(id) = std::env::var("MICROSOFT_CLIENT_ID") {
+        return Ok(id);
+    }
+
+    super::config::EMBEDDED_CLIENT_ID
+        .map(|s| s.to_string())
+        .ok_or(MicrosoftAuthError::NoClientId)
+}
+
+/// Build the OAuth authorization URL
+fn build_auth_url(client_id: &str, code_challenge: &str, state: &str, port: u16) -> String {
+    format!(
+        "{}?client_id={}&redirect_uri={}&response_type=code&response_mode=query&scope={}&code_challenge={}&code_challenge_method=S256&state={}&prompt=select_account",
+        MICROSOFT_AUTH_URL,
+        urlencoding::encode(client_id),
+        urlencoding::encode(&get_redirect_uri(port)),
+        urlencoding::encode(SCOPES),
+        urlencoding::encode(code_challenge),
+        urlencoding::encode(state),
+    )
+}
+
+/// POST a form to the token endpoint
+async fn request_tokens(params: &[(&str, &str)]) -> Result<TokenResponse, MicrosoftAuthError> {
+    let response = Client::new()
+        .post(MICROSOFT_TOKEN_URL)
+        .form(params)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(MicrosoftAuthError::OAuthError(error_text));
+    }
+
+    Ok(response.json().await?)
+}
+
+/// Get the signed-in user's email address
+async fn get_user_email(access_token: &str) -> Result<String, MicrosoftAuthError> {
+    let response = Client::new()
+        .get(GRAPH_ME_URL)
+        .bearer_auth(access_token)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(MicrosoftAuthError::OAuthError(format!(
+            "Failed to get user info: {}",
+            error_text
+        )));
+    }
+
+    let user_info: UserInfoResponse = response.json().await?;
+    user_info
+        .mail
+        .or(user_info.user_principal_name)
+        .ok_or_else(|| MicrosoftAuthError::OAuthError("Account has no email address".to_string()))
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+/// Initiate the Microsoft OAuth flow using a connection pool
+pub async fn initiate_auth_with_pool(pool: &DbPool) -> Result<MicrosoftAccount, MicrosoftAuthError> {
+    let client_id = {
+        let conn = pool.get().map_err(|e| MicrosoftAuthError::OAuthError(e.to_string()))?;
+        get_client_id_with_db(&conn)?
+    };
+
+    // Generate PKCE values
+    let code_verifier = generate_code_verifier();
+    let code_challenge = generate_code_challenge(&code_verifier);
+    let state = generate_code_verifier(); // Use random value for state
+
+    // Start callback server (tries multiple ports)
+    let (rx, port) =
+        start_callback_server(state.clone()).map_err(|e| MicrosoftAuthError::ServerError(e.to_string()))?;
+    log::info!("Microsoft OAuth callback server started on port {}", port);
+
+    let auth_url = build_auth_url(&client_id, &code_challenge, &state, port);
+    if let Err(e) = open::that(&auth_url) {
+        log::warn!("Failed to open browser: {}", e);
+    }
+
+    // Wait for the callback (with timeout)
+    let code = rx
+        .recv_timeout(Duration::from_secs(300)) // 5 minute timeout
+        .map_err(|_| MicrosoftAuthError::Timeout)?
+        .map_err(MicrosoftAuthError::OAuthError)?;
+
+    // Exchange code for tokens
+    let redirect_uri = get_redirect_uri(port);
+    let tokens = request_tokens(&[
+        ("client_id", client_id.as_str()),
+        ("code", code.as_str()),
+        ("code_verifier", code_verifier.as_str()),
+        ("grant_type", "authorization_code"),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("scope", SCOPES),
+    ])
+    .await?;
+
+    let email = get_user_email(&tokens.access_token).await?;
+    let expires_at = tokens.expires_in.map(|secs| unix_now() + secs as i64);
+
+    let conn = pool.get().map_err(|e| MicrosoftAuthError::OAuthError(e.to_string()))?;
+    save_account(
+        &conn,
+        &email,
+        &tokens.access_token,
+        &tokens.refresh_token.unwrap_or_default(),
+        expires_at,
+    )
+}
+
+/// Save Microsoft account to database
+fn save_account(
+    conn: &Connection,
+    email: &str,
+    access_token: &str,
+    refresh_token: &str,
+    expires_at: Option<i64>,
+) -> Result<MicrosoftAccount, MicrosoftAuthError> {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    conn.execute(
+        "INSERT INTO microsoft_accounts (id, email, access_token, refresh_token, token_expires_at, connected_at)
+         VALUES ('default', ?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(id) DO UPDATE SET
+             email = excluded.email,
+             access_token = excluded.access_token,
+             refresh_token = CASE WHEN excluded.refresh_token = '' THEN microsoft_accounts.refresh_token ELSE excluded.refresh_token END,
+             token_expires_at = excluded.token_expires_at,
+             connected_at = excluded.connected_at",
+        rusqlite::params![email, access_token, refresh_token, expires_at, now],
+    )?;
+
+    get_connection_status(conn)?.ok_or(MicrosoftAuthError::NotConnected)
+}
+
+/// Get the current Microsoft connection status
+pub fn get_connection_status(conn: &Connection) -> Result<Option<MicrosoftAccount>, MicrosoftAuthError> {
+    let account = conn
+        .query_row(
+            "SELECT id, email, access_token, refresh_token, token_expires_at, connected_at
+             FROM microsoft_accounts WHERE id = 'default'",
+            [],
+            |row| {
+                Ok(MicrosoftAccount {
+                    id: row.get(0)?,
+                    email: row.get(1)?,
+                    access_token: row.get(2)?,
+                    refresh_token: row.get(3)?,
+                    token_expires_at: row.get(4)?,
+                    connected_at: row.get(5)?,
+                })
+            },
+        )
+        .optional()?;
+
+    Ok(account)
+}
+
+/// Disconnect the Microsoft account and remove its synced events
+pub fn disconnect_account(conn: &Connection) -> Result<(), MicrosoftAuthError> {
+    conn.execute("DELETE FROM microsoft_accounts WHERE id = 'default'", [])?;
+    conn.execute("DELETE FROM calendar_events WHERE source = 'microsoft'", [])?;
+    Ok(())
+}
+
+/// Refresh the access token if it's expired or about to expire (pool version)
+pub async fn refresh_token_if_needed_with_pool(pool: &DbPool) -> Result<String, MicrosoftAuthError> {
+    let (account, client_id) = {
+        let conn = pool.get().map_err(|e| MicrosoftAuthError::OAuthError(e.to_string()))?;
+        let account = get_connection_status(&conn)?.ok_or(MicrosoftAuthError::NotConnected)?;
+        (account, get_client_id_with_db(&conn)?)
+    };
+
+    let now = unix_now();
+    if !token_needs_refresh(account.token_expires_at, now) {
+        return Ok(account.access_token);
+    }
+
+    let tokens = match request_tokens(&[
+        ("client_id", client_id.as_str()),
+        ("refresh_token", account.refresh_token.as_str()),
+        ("grant_type", "refresh_token"),
+        ("scope", SCOPES),
+    ])
+    .await
+    {
+        Ok(tokens) => tokens,
+        // The refresh token was revoked or expired; the user needs to sign in again
+        Err(MicrosoftAuthError::OAuthError(error_text)) if error_text.contains("invalid_grant") => {
+            if let Ok(conn) = pool.get() {
+                let _ = disconnect_account(&conn);
+                log::warn!("Microsoft refresh token is invalid/revoked - cleared account. User needs to reconnect.");
+            }
+            return Err(MicrosoftAuthError::RefreshFailed(
+                "Your Microsoft connection has expired. Please reconnect your Microsoft account in Settings.".to_string()
+            ));
+        }
+        Err(MicrosoftAuthError::OAuthError(error_text)) => return Err(MicrosoftAuthError::RefreshFailed(error_text)),
+        Err(e) => return Err(e),
+    };
+
+    // Microsoft rotates refresh tokens, so store the new one when given
+    let expires_at = tokens.expires_in.map(|secs| now + secs as i64);
+    let conn = pool.get().map_err(|e| MicrosoftAuthError::OAuthError(e.to_string()))?;
+    conn.execute(
+        "UPDATE microsoft_accounts
+         SET access_token = ?1, token_expires_at = ?2, refresh_token = COALESCE(?3, refresh_token)
+         WHERE id = 'default'",
+        rusqlite::params![tokens.access_token, expires_at, tokens.refresh_token],
+    )?;
+
+    Ok(tokens.access_token)
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
 /// A note in the knowledge base
@@ -15,6 +15,28 @@ pub struct Note {
     pub is_deleted: bool,
 }
 
+/// Sort order for paginated note listings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteSort {
+    /// Most recently updated first
+    #[default]
+    Updated,
+    /// Most recently created first
+    Created,
+    /// Alphabetical by title
+    Title,
+}
+
+/// One page of notes plus the total across all pages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotePage {
+    pub notes: Vec<Note>,
+    /// Number of non-deleted notes
+    pub total: usize,
+}
+
 /// Input for creating a new note
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -35,6 +57,19 @@ pub struct UpdateNoteInput {
     pub folder_id: Option<String>,
 }
 
+/// A snapshot of a note taken before it was updated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteRevision {
+    pub id: String,
+    pub note_id: String,
+    pub title: String,
+    pub content: Option<String>,
+    pub content_html: Option<String>,
+    /// When the snapshot was taken, i.e. when this version was replaced
+    pub created_at: DateTime<Utc>,
+}
+
 /// A folder for organizing notes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -67,7 +102,9 @@ pub struct UpdateFolderInput {
 pub struct Tag {
     pub id: String,
     pub name: String,
+    /// A palette color name (e.g. "blue") or a hex color like "#3b82f6"
     pub color: Option<String>,
+    pub description: Option<String>,
 }
 
 /// A link between two notes (wiki-style reference)
@@ -92,6 +129,8 @@ pub struct Board {
     pub name: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Whether moving a card into a lane at its WIP limit is refused
+    pub enforce_wip_limits: bool,
 }
 
 /// Input for creating a new board
@@ -102,11 +141,26 @@ pub struct CreateBoardInput {
     pub name: String,
 }
 
+/// Preset lane layouts for a new board
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum BoardTemplate {
+    /// To Do, In Progress, Done
+    #[default]
+    Kanban,
+    /// Backlog, To Do, In Progress, In Review, Done
+    Sprint,
+    /// Lanes with the given names, in order
+    Custom(Vec<String>),
+}
+
 /// Input for updating a board
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateBoardInput {
     pub name: Option<String>,
+    #[serde(default)]
+    pub enforce_wip_limits: Option<bool>,
 }
 
 /// A lane (column) in a Kanban board
@@ -118,6 +172,8 @@ pub struct BoardLane {
     pub name: String,
     pub color: Option<String>,
     pub position: i32,
+    /// Most cards the lane should hold, if limited
+    pub wip_limit: Option<u32>,
 }
 
 /// Input for creating a new lane
@@ -146,6 +202,7 @@ pub struct BoardCard {
     pub lane_id: String,
     pub note_id: String,
     pub position: i32,
+    pub due_date: Option<DateTime<Utc>>,
 }
 
 /// A card with note details for display
@@ -157,10 +214,25 @@ pub struct BoardCardWithNote {
     pub lane_id: String,
     pub note_id: String,
     pub position: i32,
+    pub due_date: Option<DateTime<Utc>>,
     pub note_title: String,
     pub note_folder_path: Option<String>,
 }
 
+/// Where a note appears as a card: which board, which lane, and where in it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardPlacement {
+    pub card_id: String,
+    pub board_id: String,
+    pub board_name: String,
+    pub lane_id: String,
+    pub lane_name: String,
+    pub lane_color: Option<String>,
+    pub position: i32,
+    pub due_date: Option<DateTime<Utc>>,
+}
+
 /// Input for adding a card to a lane
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -168,6 +240,8 @@ pub struct AddCardInput {
     pub board_id: String,
     pub lane_id: String,
     pub note_id: String,
+    #[serde(default)]
+    pub due_date: Option<DateTime<Utc>>,
 }
 
 /// Input for moving a card
@@ -177,6 +251,22 @@ pub struct MoveCardInput {
     pub card_id: String,
     pub target_lane_id: String,
     pub target_position: i32,
+    /// New due date; `None` leaves the card's due date unchanged
+    #[serde(default)]
+    pub due_date: Option<DateTime<Utc>>,
+}
+
+/// How to order the cards in a lane
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum CardSortOrder {
+    /// The order cards were arranged in by hand
+    #[default]
+    Manual,
+    /// Soonest due first; cards without a due date last
+    DueDate,
+    /// Alphabetically by note title
+    Title,
 }
 
 /// A board with all its lanes and cards
@@ -229,6 +319,28 @@ pub struct Conversation {
     pub system_prompt: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Message this conversation was branched from, if it is a branch
+    #[serde(default)]
+    pub parent_message_id: Option<String>,
+}
+
+/// Reasoning settings for a conversation, sent with each chat request
+///
+/// Reasoning is off when both fields are unset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReasoningSettings {
+    /// Effort level for OpenAI reasoning models ("minimal", "low", "medium", "high")
+    pub reasoning_effort: Option<String>,
+    /// Token budget for Claude extended thinking and Gemini thinking
+    pub thinking_budget: Option<u32>,
+}
+
+impl ReasoningSettings {
+    /// Whether any reasoning option is set
+    pub fn is_enabled(&self) -> bool {
+        self.reasoning_effort.is_some() || self.thinking_budget.is_some()
+    }
 }
 
 /// A message in a conversation
@@ -260,6 +372,21 @@ pub struct MessageMetadata {
     /// Thinking/reasoning content from the AI (for display in history)
     #[serde(default)]
     pub thinking_content: Option<String>,
+    /// Images the user attached to this message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImageAttachment>,
+}
+
+/// A reference to an image attached to a chat message
+///
+/// The image bytes aren't stored; pasted images only keep their type and size.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageAttachment {
+    /// File the image was read from, if it was attached by path
+    pub path: Option<String>,
+    pub mime_type: String,
+    pub size_bytes: usize,
 }
 
 /// A record of a tool call made during message generation
@@ -282,6 +409,12 @@ pub struct Citation {
     pub note_title: String,
     /// Relevance score (0-1)
     pub relevance: f32,
+    /// Start of the supporting span in the note content (UTF-16 offset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_offset: Option<usize>,
+    /// End of the supporting span in the note content (UTF-16 offset, exclusive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_offset: Option<usize>,
 }
 
 /// Token usage statistics
@@ -331,6 +464,28 @@ pub struct SendMessageInput {
     /// Number of notes to auto-retrieve via RAG
     #[serde(default = "default_auto_retrieve_count")]
     pub auto_retrieve_count: usize,
+    /// Let the assistant call agent tools (search/read notes) in non-streaming chat.
+    /// Streaming chat always runs with tools.
+    #[serde(default)]
+    pub use_tools: bool,
+    /// Images to send with the message
+    #[serde(default)]
+    pub images: Vec<ChatImageInput>,
+    /// Reasoning settings to save on the conversation before sending
+    #[serde(default)]
+    pub reasoning: Option<ReasoningSettings>,
+}
+
+/// An image attached to a chat message, either by file path or as base64 data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatImageInput {
+    /// Path to an image file
+    pub path: Option<String>,
+    /// Base64-encoded image bytes (e.g. a pasted screenshot)
+    pub data: Option<String>,
+    /// MIME type; inferred from the file extension when attaching by path
+    pub mime_type: Option<String>,
 }
 
 fn default_auto_retrieve_count() -> usize {
@@ -355,6 +510,34 @@ pub struct ChatResponse {
 pub struct CreateConversationInput {
     pub title: Option<String>,
     pub system_prompt: Option<String>,
+    /// System prompt preset to use when no explicit prompt is given
+    #[serde(default)]
+    pub system_prompt_id: Option<String>,
+}
+
+/// A named, reusable system prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemPrompt {
+    pub id: String,
+    pub name: String,
+    pub prompt: String,
+    /// Used for new conversations that don't specify a prompt
+    pub is_default: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input for creating or updating a system prompt preset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveSystemPromptInput {
+    /// Existing preset to update (None to create a new one)
+    pub id: Option<String>,
+    pub name: String,
+    pub prompt: String,
+    #[serde(default)]
+    pub is_default: bool,
 }
 
 /// Input for updating a conversation
@@ -384,6 +567,30 @@ pub struct ConversationPreview {
     pub first_message_preview: Option<String>,
 }
 
+/// A conversation matching a full-text search over its messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationSearchResult {
+    pub conversation_id: String,
+    pub title: Option<String>,
+    /// The best-matching message in the conversation
+    pub message_id: String,
+    pub snippet: Option<String>,
+    /// Relevance score (0-1, higher is better)
+    pub score: f32,
+    /// Which search mode produced this result
+    pub mode: String,
+}
+
+/// Status of a tool call reported while streaming
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolCallStatus {
+    Started,
+    Completed,
+    Failed,
+}
+
 /// Stream event for chat responses
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -426,6 +633,7 @@ pub enum ChatStreamEvent {
 pub enum CalendarEventSource {
     Manual,
     Google,
+    Microsoft,
 }
 
 impl CalendarEventSource {
@@ -433,9 +641,18 @@ impl CalendarEventSource {
         match s {
             "manual" => Some(CalendarEventSource::Manual),
             "google" => Some(CalendarEventSource::Google),
+            "microsoft" => Some(CalendarEventSource::Microsoft),
             _ => None,
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CalendarEventSource::Manual => "manual",
+            CalendarEventSource::Google => "google",
+            CalendarEventSource::Microsoft => "microsoft",
+        }
+    }
 }
 
 /// Event type for Google Calendar events
@@ -512,6 +729,28 @@ pub struct EventAttendee {
     pub is_organizer: bool,
 }
 
+/// Push state of a local event relative to Google Calendar
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum GoogleSyncState {
+    /// Never pushed to Google
+    Local,
+    /// Pushed and unchanged since
+    Synced,
+    /// Changed locally since the last push
+    Modified,
+}
+
+impl GoogleSyncState {
+    pub fn from_str(s: &str) -> Self {
+        match s {
+            "synced" => GoogleSyncState::Synced,
+            "modified" => GoogleSyncState::Modified,
+            _ => GoogleSyncState::Local,
+        }
+    }
+}
+
 /// A calendar event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -534,6 +773,14 @@ pub struct CalendarEvent {
     pub attendees: Option<Vec<EventAttendee>>,
     /// Video call link (for Google events with Meet/Zoom/etc)
     pub meeting_link: Option<String>,
+    /// Where the event takes place: a room, an address, or a video call URL
+    pub location: Option<String>,
+    /// IANA timezone the event was scheduled in (times are always stored as UTC)
+    pub timezone: Option<String>,
+    /// Google Calendar id of a local event that has been pushed to Google
+    pub google_event_id: Option<String>,
+    /// Push state of a local event relative to Google Calendar
+    pub sync_state: GoogleSyncState,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -557,6 +804,12 @@ pub struct CreateCalendarEventInput {
     pub attendees: Option<Vec<EventAttendee>>,
     /// Video call link
     pub meeting_link: Option<String>,
+    /// Where the event takes place
+    #[serde(default)]
+    pub location: Option<String>,
+    /// IANA timezone name, e.g. "America/New_York"
+    #[serde(default)]
+    pub timezone: Option<String>,
 }
 
 /// Input for updating a calendar event
@@ -578,6 +831,12 @@ pub struct UpdateCalendarEventInput {
     pub attendees: Option<Vec<EventAttendee>>,
     /// Video call link
     pub meeting_link: Option<String>,
+    /// Where the event takes place
+    #[serde(default)]
+    pub location: Option<String>,
+    /// IANA timezone name, e.g. "America/New_York"
+    #[serde(default)]
+    pub timezone: Option<String>,
 }
 
 /// A calendar event with linked note details
@@ -587,4 +846,25 @@ pub struct CalendarEventWithNote {
     #[serde(flatten)]
     pub event: CalendarEvent,
     pub linked_note_title: Option<String>,
+    /// For an expanded occurrence of a recurring event, the id of the master event
+    #[serde(default)]
+    pub recurrence_master_id: Option<String>,
+    /// For an expanded occurrence of a recurring event, the date of this occurrence (YYYY-MM-DD)
+    #[serde(default)]
+    pub occurrence_date: Option<NaiveDate>,
+    /// Start time converted to the timezone requested by the caller
+    #[serde(default)]
+    pub local_start_time: Option<DateTime<FixedOffset>>,
+    /// End time converted to the timezone requested by the caller
+    #[serde(default)]
+    pub local_end_time: Option<DateTime<FixedOffset>>,
+}
+
+/// Result of importing an .ics file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IcsImportResult {
+    pub imported: Vec<CalendarEvent>,
+    /// Number of VEVENTs that could not be parsed
+    pub skipped: usize,
 }
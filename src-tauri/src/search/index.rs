@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
 use tantivy::collector::TopDocs;
@@ -7,7 +7,10 @@ use tantivy::schema::Value;
 use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term};
 use thiserror::Error;
 
-use super::schema::{build_schema, FIELD_CONTENT, FIELD_ID, FIELD_TITLE};
+use super::schema::{
+    build_schema, index_version, AnalyzerConfig, FIELD_CONTENT, FIELD_ID, FIELD_TITLE, SCHEMA_VERSION_FILE,
+    TOKENIZER_NAME,
+};
 
 #[derive(Error, Debug)]
 #[allow(clippy::enum_variant_names)]
@@ -20,6 +23,8 @@ pub enum SearchError {
     WriteError(String),
     #[error("Failed to create index directory: {0}")]
     DirectoryError(#[from] std::io::Error),
+    #[error("Failed to read notes to index: {0}")]
+    SourceError(String),
 }
 
 /// A search result with note ID and relevance score
@@ -34,22 +39,45 @@ pub struct SearchIndex {
     index: Index,
     reader: IndexReader,
     writer: RwLock<IndexWriter>,
+    analyzer: AnalyzerConfig,
+    /// Whether an existing index was discarded on open because it was outdated or unreadable
+    recreated: bool,
 }
 
 impl SearchIndex {
-    /// Create or open a search index at the given path
+    /// Create or open a search index at the given path, with the default analyzer
     pub fn new(index_path: PathBuf) -> Result<Self, SearchError> {
+        Self::with_analyzer(index_path, AnalyzerConfig::default())
+    }
+
+    /// Create or open a search index at the given path, tokenizing text with `analyzer`
+    ///
+    /// An existing index built with another `SCHEMA_VERSION` or analyzer, or
+    /// that can't be opened, is deleted and recreated empty; `needs_rebuild`
+    /// reports this.
+    pub fn with_analyzer(index_path: PathBuf, analyzer: AnalyzerConfig) -> Result<Self, SearchError> {
         // Ensure the index directory exists
         std::fs::create_dir_all(&index_path)?;
 
         let schema = build_schema();
 
         // Try to open existing index, or create a new one
-        let index = if index_path.join("meta.json").exists() {
-            Index::open_in_dir(&index_path)?
-        } else {
-            Index::create_in_dir(&index_path, schema.clone())?
+        let existing = index_path.join("meta.json").exists();
+        let index = if existing { open_current_index(&index_path, &analyzer) } else { None };
+        let recreated = existing && index.is_none();
+        let index = match index {
+            Some(index) => index,
+            None => {
+                if recreated {
+                    std::fs::remove_dir_all(&index_path)?;
+                    std::fs::create_dir_all(&index_path)?;
+                }
+                let index = Index::create_in_dir(&index_path, schema.clone())?;
+                std::fs::write(index_path.join(SCHEMA_VERSION_FILE), index_version(&analyzer))?;
+                index
+            }
         };
+        index.tokenizers().register(TOKENIZER_NAME, analyzer.build());
 
         // Create a reader with automatic reloading
         let reader = index
@@ -64,9 +92,22 @@ impl SearchIndex {
             index,
             reader,
             writer: RwLock::new(writer),
+            analyzer,
+            recreated,
         })
     }
 
+    /// The analyzer notes are tokenized with
+    pub fn analyzer(&self) -> AnalyzerConfig {
+        self.analyzer
+    }
+
+    /// Whether the index should be rebuilt from the database: it's empty, or was
+    /// recreated on open because it was outdated or corrupt
+    pub fn needs_rebuild(&self) -> bool {
+        self.recreated || self.is_empty()
+    }
+
     /// Add a note to the search index
     pub fn add_note(&self, id: &str, title: &str, content: Option<&str>) -> Result<(), SearchError> {
         let schema = self.index.schema();
@@ -94,12 +135,14 @@ impl SearchIndex {
 
     /// Update a note in the search index (delete + re-add)
     pub fn update_note(&self, id: &str, title: &str, content: Option<&str>) -> Result<(), SearchError> {
-        self.delete_note(id)?;
+        self.delete_document(id)?;
         self.add_note(id, title, content)
     }
 
-    /// Delete a note from the search index
-    pub fn delete_note(&self, id: &str) -> Result<(), SearchError> {
+    /// Delete the document for note `id` from the search index
+    ///
+    /// Deleting an ID that isn't indexed is a no-op.
+    pub fn delete_document(&self, id: &str) -> Result<(), SearchError> {
         let schema = self.index.schema();
         let id_field = schema.get_field(FIELD_ID).unwrap();
         let term = Term::from_field_text(id_field, id);
@@ -152,7 +195,13 @@ impl SearchIndex {
     }
 
     /// Rebuild the entire index from a list of notes
-    pub fn rebuild(&self, notes: Vec<(String, String, Option<String>)>) -> Result<(), SearchError> {
+    ///
+    /// If reading a note fails, the rebuild is rolled back and the index keeps
+    /// its previous contents.
+    pub fn rebuild(
+        &self,
+        notes: impl IntoIterator<Item = Result<(String, String, Option<String>), SearchError>>,
+    ) -> Result<(), SearchError> {
         let schema = self.index.schema();
         let id_field = schema.get_field(FIELD_ID).unwrap();
         let title_field = schema.get_field(FIELD_TITLE).unwrap();
@@ -164,7 +213,14 @@ impl SearchIndex {
         writer.delete_all_documents()?;
         
         // Add all notes
-        for (id, title, content) in notes {
+        for note in notes {
+            let (id, title, content) = match note {
+                Ok(note) => note,
+                Err(e) => {
+                    writer.rollback()?;
+                    return Err(e);
+                }
+            };
             let mut doc = TantivyDocument::new();
             doc.add_text(id_field, &id);
             doc.add_text(title_field, &title);
@@ -195,6 +251,31 @@ impl SearchIndex {
     }
 }
 
+/// Open the index at `index_path` if it was built with the current schema and `analyzer`
+fn open_current_index(index_path: &Path, analyzer: &AnalyzerConfig) -> Option<Index> {
+    let expected = index_version(analyzer);
+    let version = std::fs::read_to_string(index_path.join(SCHEMA_VERSION_FILE)).ok();
+    if version.as_deref().map(str::trim) != Some(expected.as_str()) {
+        log::info!(
+            "Search index version is {:?}, expected {:?}; rebuilding",
+            version, expected
+        );
+        return None;
+    }
+
+    match Index::open_in_dir(index_path) {
+        Ok(index) if index.schema() == build_schema() => Some(index),
+        Ok(_) => {
+            log::warn!("Search index schema doesn't match version {:?}; rebuilding", expected);
+            None
+        }
+        Err(e) => {
+            log::warn!("Failed to open search index, rebuilding: {}", e);
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -241,16 +322,23 @@ mod tests {
     }
 
     #[test]
-    fn test_delete_note() {
+    fn test_delete_document() {
         let (index, _dir) = create_test_index();
         
         index.add_note("1", "Test Note", Some("Test content")).unwrap();
-        assert_eq!(index.doc_count(), 1);
+        index.add_note("2", "Other Test", Some("More content")).unwrap();
+        assert_eq!(index.doc_count(), 2);
         
-        index.delete_note("1").unwrap();
+        index.delete_document("1").unwrap();
+        assert_eq!(index.doc_count(), 1);
         
         let results = index.search("test", 10).unwrap();
-        assert_eq!(results.len(), 0);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "2");
+
+        // Deleting again is harmless
+        index.delete_document("1").unwrap();
+        assert_eq!(index.doc_count(), 1);
     }
 
     #[test]
@@ -264,7 +352,7 @@ mod tests {
             ("2".to_string(), "Second Note".to_string(), Some("Content two".to_string())),
         ];
         
-        index.rebuild(notes).unwrap();
+        index.rebuild(notes.into_iter().map(Ok)).unwrap();
         
         // Old note should be gone
         let results = index.search("old", 10).unwrap();
@@ -274,6 +362,109 @@ mod tests {
         let results = index.search("first", 10).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].id, "1");
+
+        // A failed read leaves the index as it was
+        let failing = vec![
+            Ok(("3".to_string(), "Third Note".to_string(), None)),
+            Err(SearchError::SourceError("disk error".to_string())),
+        ];
+        assert!(matches!(index.rebuild(failing), Err(SearchError::SourceError(_))));
+        assert_eq!(index.search("first", 10).unwrap().len(), 1);
+        assert!(index.search("third", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_schema_version_mismatch_recreates_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().to_path_buf();
+
+        let index = SearchIndex::new(path.clone()).unwrap();
+        index.add_note("1", "Test Note", Some("Test content")).unwrap();
+        drop(index);
+
+        // Reopening at the same version keeps the documents
+        let index = SearchIndex::new(path.clone()).unwrap();
+        assert!(!index.needs_rebuild());
+        assert_eq!(index.doc_count(), 1);
+        drop(index);
+
+        // An index from an older schema is thrown away
+        std::fs::write(path.join(SCHEMA_VERSION_FILE), "0").unwrap();
+        let index = SearchIndex::new(path.clone()).unwrap();
+        assert!(index.needs_rebuild());
+        assert_eq!(index.doc_count(), 0);
+
+        index
+            .rebuild([Ok(("1".to_string(), "Test Note".to_string(), Some("Test content".to_string())))])
+            .unwrap();
+        assert_eq!(index.search("test", 10).unwrap().len(), 1);
+        drop(index);
+
+        let index = SearchIndex::new(path).unwrap();
+        assert!(!index.needs_rebuild());
+        assert_eq!(index.doc_count(), 1);
+    }
+
+    #[test]
+    fn test_stemmed_query_matches_inflected_forms() {
+        let (index, _dir) = create_test_index();
+
+        index.add_note("1", "Training Log", Some("Went running twice this week")).unwrap();
+        index.add_note("2", "Recipes", Some("Bake the bread for an hour")).unwrap();
+
+        for query in ["run", "runs", "running"] {
+            let results = index.search(query, 10).unwrap();
+            assert_eq!(results.len(), 1, "query {:?}", query);
+            assert_eq!(results[0].id, "1");
+        }
+        let results = index.search("baking", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "2");
+    }
+
+    #[test]
+    fn test_stop_words_are_not_indexed() {
+        let (index, _dir) = create_test_index();
+        index.add_note("1", "Recipes", Some("Bake the bread for an hour")).unwrap();
+        assert!(index.search("the", 10).unwrap().is_empty());
+        assert_eq!(index.search("the bread", 10).unwrap().len(), 1);
+
+        let dir = TempDir::new().unwrap();
+        let analyzer = AnalyzerConfig { stop_words: false, ..AnalyzerConfig::default() };
+        let index = SearchIndex::with_analyzer(dir.path().to_path_buf(), analyzer).unwrap();
+        index.add_note("1", "Recipes", Some("Bake the bread for an hour")).unwrap();
+        assert_eq!(index.search("the", 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_analyzer_change_recreates_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().to_path_buf();
+
+        let index = SearchIndex::new(path.clone()).unwrap();
+        index.add_note("1", "Test Note", Some("Test content")).unwrap();
+        drop(index);
+
+        let french = AnalyzerConfig { language: tantivy::tokenizer::Language::French, stop_words: true };
+        let index = SearchIndex::with_analyzer(path.clone(), french).unwrap();
+        assert!(index.needs_rebuild());
+        assert_eq!(index.doc_count(), 0);
+        assert_eq!(index.analyzer(), french);
+
+        index.add_note("1", "Voyage", Some("Les maisons du village")).unwrap();
+        assert_eq!(index.search("maison", 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_corrupt_index_is_recreated() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().to_path_buf();
+        drop(SearchIndex::new(path.clone()).unwrap());
+
+        std::fs::write(path.join("meta.json"), "not json").unwrap();
+        let index = SearchIndex::new(path).unwrap();
+        assert!(index.needs_rebuild());
+        assert_eq!(index.doc_count(), 0);
     }
 
     #[test]
@@ -1,4 +1,5 @@
 mod index;
 mod schema;
 
-pub use index::SearchIndex;
+pub use index::{SearchError, SearchIndex};
+pub use schema::AnalyzerConfig;
@@ -1,4 +1,64 @@
-use tantivy::schema::{Schema, STORED, STRING, TEXT};
+use serde::{Deserialize, Serialize};
+use tantivy::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, STORED, STRING};
+use tantivy::tokenizer::{
+    Language, LowerCaser, RemoveLongFilter, SimpleTokenizer, Stemmer, StopWordFilter, TextAnalyzer,
+};
+
+/// Version of the index layout: the schema below and how its fields are tokenized
+///
+/// Bump this whenever either changes. An index written with a different
+/// version is discarded and rebuilt from the database when it's opened.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// File in the index directory recording the `SCHEMA_VERSION` and analyzer it was built with
+pub const SCHEMA_VERSION_FILE: &str = "inkling_schema_version";
+
+/// Name the note text analyzer is registered under
+pub const TOKENIZER_NAME: &str = "inkling_text";
+
+/// How note titles and content are split into searchable terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzerConfig {
+    /// Language words are stemmed in, so "running" matches "run"
+    pub language: Language,
+    /// Whether to leave out the language's common words, like "the" and "of"
+    pub stop_words: bool,
+}
+
+impl Default for AnalyzerConfig {
+    fn default() -> Self {
+        Self {
+            language: Language::English,
+            stop_words: true,
+        }
+    }
+}
+
+impl AnalyzerConfig {
+    /// Build the analyzer: split on non-alphanumerics, lowercase, drop stop words, stem
+    pub fn build(&self) -> TextAnalyzer {
+        let mut builder = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(RemoveLongFilter::limit(40))
+            .filter(LowerCaser)
+            .dynamic();
+        if self.stop_words {
+            // Not every stemmer language has a stop word list
+            if let Some(filter) = StopWordFilter::new(self.language) {
+                builder = builder.filter_dynamic(filter);
+            }
+        }
+        builder.filter_dynamic(Stemmer::new(self.language)).build()
+    }
+}
+
+/// Version recorded in `SCHEMA_VERSION_FILE` for an index built with `analyzer`
+///
+/// Documents are tokenized when they're indexed, so an index built with a
+/// different analyzer has to be rebuilt just like one with a different schema.
+pub fn index_version(analyzer: &AnalyzerConfig) -> String {
+    format!("{} {:?} stop_words={}", SCHEMA_VERSION, analyzer.language, analyzer.stop_words)
+}
 
 /// Field names for the search index
 pub const FIELD_ID: &str = "id";
@@ -8,15 +68,20 @@ pub const FIELD_CONTENT: &str = "content";
 /// Build the Tantivy schema for indexing notes
 pub fn build_schema() -> Schema {
     let mut schema_builder = Schema::builder();
+    let text = TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer(TOKENIZER_NAME)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+    );
 
     // ID field: stored and indexed as a single token (not tokenized) for deletion and retrieval
     schema_builder.add_text_field(FIELD_ID, STRING | STORED);
 
-    // Title field: stored and indexed with TEXT preset (includes tokenization)
-    schema_builder.add_text_field(FIELD_TITLE, TEXT | STORED);
+    // Title field: stored and indexed with the note text analyzer
+    schema_builder.add_text_field(FIELD_TITLE, text.clone().set_stored());
 
-    // Content field: indexed with TEXT preset but not stored
-    schema_builder.add_text_field(FIELD_CONTENT, TEXT);
+    // Content field: indexed with the note text analyzer but not stored
+    schema_builder.add_text_field(FIELD_CONTENT, text);
 
     schema_builder.build()
 }
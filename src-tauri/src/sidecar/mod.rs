@@ -1 +1,3 @@
-// Sidecar module - placeholder for future sidecar processes
+// Sidecar module - external processes the app shells out to
+
+pub mod ocr;
@@ -0,0 +1,175 @@
+//! OCR via a Tesseract sidecar
+//!
+//! Recognizes text in image attachments so scanned pages and screenshots can
+//! be searched and embedded. Tesseract is optional: it is looked up from
+//! `INKLING_TESSERACT_PATH`, next to the app executable (bundled sidecar),
+//! then on the PATH. Every failure is reported as an error string so callers
+//! can treat OCR as best-effort.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::Duration;
+use wait_timeout::ChildExt;
+
+/// Default time allowed for recognizing a single image
+pub const DEFAULT_OCR_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Environment variable overriding the tesseract binary location
+const TESSERACT_PATH_ENV: &str = "INKLING_TESSERACT_PATH";
+
+/// Image extensions Tesseract can read
+pub const OCR_IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "bmp", "tif", "tiff", "pbm", "pgm", "ppm",
+];
+
+/// Whether a file extension (lowercase, without the dot) is an OCR-able image
+pub fn is_ocr_image_extension(extension: &str) -> bool {
+    OCR_IMAGE_EXTENSIONS.contains(&extension)
+}
+
+/// Locate the tesseract binary, if installed
+pub fn find_tesseract() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(TESSERACT_PATH_ENV) {
+        let path = PathBuf::from(path);
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+
+    let binary = if cfg!(windows) { "tesseract.exe" } else { "tesseract" };
+
+    // Bundled sidecars are placed next to the app executable
+    if let Some(dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        let bundled = dir.join(binary);
+        if bundled.is_file() {
+            return Some(bundled);
+        }
+    }
+
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(binary))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// The tesseract binary, looked up once per run
+fn tesseract_path() -> Option<&'static PathBuf> {
+    static TESSERACT: OnceLock<Option<PathBuf>> = OnceLock::new();
+    TESSERACT.get_or_init(find_tesseract).as_ref()
+}
+
+/// Whether OCR is available on this machine
+pub fn is_ocr_available() -> bool {
+    tesseract_path().is_some()
+}
+
+/// Recognize text in an image, killing tesseract if it runs past `timeout`
+pub fn recognize_text(path: &str, timeout: Duration) -> Result<String, String> {
+    let tesseract = tesseract_path().ok_or("OCR is not available (tesseract not found)")?;
+
+    let mut child = Command::new(tesseract)
+        .arg(path)
+        .arg("stdout")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start tesseract: {}", e))?;
+
+    // Drain both pipes on threads so a full pipe can't block the process
+    let drain = |mut pipe: Box<dyn Read + Send>| {
+        std::thread::spawn(move || {
+            let mut output = String::new();
+            pipe.read_to_string(&mut output).map(|_| output)
+        })
+    };
+    let stdout = child.stdout.take().ok_or("Failed to capture tesseract output")?;
+    let stderr = child.stderr.take().ok_or("Failed to capture tesseract output")?;
+    let reader = drain(Box::new(stdout));
+    let error_reader = drain(Box::new(stderr));
+
+    let status = match child.wait_timeout(timeout) {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("OCR timed out after {}s", timeout.as_secs()));
+        }
+        Err(e) => return Err(format!("Failed to wait for tesseract: {}", e)),
+    };
+
+    if !status.success() {
+        let stderr = error_reader.join().ok().and_then(Result::ok).unwrap_or_default();
+        return Err(format!("tesseract failed: {}", stderr.trim()));
+    }
+
+    let text = reader
+        .join()
+        .map_err(|_| "Failed to read tesseract output".to_string())?
+        .map_err(|e| format!("Failed to read tesseract output: {}", e))?;
+
+    Ok(text.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 5x7 glyphs for the letters used in the test image
+    fn glyph(c: char) -> [&'static str; 7] {
+        match c {
+            'H' => ["#...#", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"],
+            'E' => ["#####", "#....", "#....", "####.", "#....", "#....", "#####"],
+            'L' => ["#....", "#....", "#....", "#....", "#....", "#....", "#####"],
+            'O' => [".###.", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."],
+            _ => ["....."; 7],
+        }
+    }
+
+    /// Render black text on a white background, scaled up for legibility
+    fn render_text_png(text: &str, path: &Path) {
+        const SCALE: u32 = 12;
+        const MARGIN: u32 = 40;
+        let columns = text.chars().count() as u32 * 6;
+        let width = columns * SCALE + MARGIN * 2;
+        let height = 7 * SCALE + MARGIN * 2;
+
+        let image = image::GrayImage::from_fn(width, height, |x, y| {
+            let (Some(col), Some(row)) = (
+                x.checked_sub(MARGIN).map(|x| x / SCALE),
+                y.checked_sub(MARGIN).map(|y| y / SCALE),
+            ) else {
+                return image::Luma([255]);
+            };
+            if col >= columns || row >= 7 {
+                return image::Luma([255]);
+            }
+            let c = text.chars().nth((col / 6) as usize).unwrap_or(' ');
+            let on = col % 6 < 5 && glyph(c)[row as usize].as_bytes()[(col % 6) as usize] == b'#';
+            image::Luma([if on { 0 } else { 255 }])
+        });
+        image.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_ocr_image_extensions() {
+        assert!(is_ocr_image_extension("png"));
+        assert!(is_ocr_image_extension("tiff"));
+        assert!(!is_ocr_image_extension("svg"));
+        assert!(!is_ocr_image_extension("pdf"));
+    }
+
+    #[test]
+    #[ignore = "requires the tesseract binary"]
+    fn test_recognize_text_from_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hello.png");
+        render_text_png("HELLO", &path);
+
+        let text = recognize_text(path.to_str().unwrap(), DEFAULT_OCR_TIMEOUT).unwrap();
+        assert!(!text.is_empty());
+    }
+}
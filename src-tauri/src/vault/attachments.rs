@@ -0,0 +1,266 @@
+//! Garbage collection for attachment files
+//!
+//! Attachments are saved to the vault's `attachments` directory and linked
+//! from notes as `../attachments/<name>`. Removing the link or the note leaves
+//! the file behind; this module finds and deletes files nothing refers to.
+
+use crate::db::exports::{self, ExportFormat};
+use regex::Regex;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum AttachmentGcError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Database error: {0}")]
+    DbError(String),
+}
+
+impl From<rusqlite::Error> for AttachmentGcError {
+    fn from(e: rusqlite::Error) -> Self {
+        AttachmentGcError::DbError(e.to_string())
+    }
+}
+
+impl From<exports::ExportDbError> for AttachmentGcError {
+    fn from(e: exports::ExportDbError) -> Self {
+        AttachmentGcError::DbError(e.to_string())
+    }
+}
+
+/// Result of an attachment garbage collection
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentGcResult {
+    /// File names in the attachments directory that nothing references
+    pub orphaned: Vec<String>,
+    /// Total size of the orphaned files in bytes
+    pub bytes: u64,
+    /// Whether the orphaned files were left in place
+    pub dry_run: bool,
+}
+
+/// Names of attachment files referenced from `text`
+pub fn attachment_references(text: &str) -> Vec<String> {
+    let re = Regex::new(r#"\.\./attachments/([^\s\)\"\'\]<>]+)"#).unwrap();
+    re.captures_iter(text).map(|cap| cap[1].to_string()).collect()
+}
+
+/// Collect every attachment name referenced by notes or exports
+///
+/// Deleted notes and saved revisions still count, since they can be
+/// restored. Note files on disk
+/// are scanned too, in case they were edited outside the app and not yet
+/// synced, as are markdown exports, which keep linking to the vault's
+/// attachments after the notes they came from change.
+pub fn referenced_attachments(
+    conn: &Connection,
+    notes_dir: &Path,
+    exports_dir: &Path,
+) -> Result<HashSet<String>, AttachmentGcError> {
+    let mut referenced = HashSet::new();
+
+    let mut stmt = conn.prepare(
+        "SELECT content, content_html FROM notes
+         UNION ALL SELECT content, content_html FROM note_revisions",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, Option<String>>(0)?, row.get::<_, Option<String>>(1)?))
+    })?;
+    for row in rows {
+        let (content, content_html) = row?;
+        for text in [content, content_html].into_iter().flatten() {
+            referenced.extend(attachment_references(&text));
+        }
+    }
+
+    let mut text_files = collect_markdown_files(notes_dir)?;
+    text_files.extend(collect_markdown_files(exports_dir)?);
+    for export in exports::get_all_exports(conn)? {
+        if export.format == ExportFormat::Markdown {
+            text_files.push(PathBuf::from(export.path));
+        }
+    }
+    for path in text_files {
+        // Exports may have been removed from disk since they were recorded
+        if let Ok(text) = fs::read_to_string(&path) {
+            referenced.extend(attachment_references(&text));
+        }
+    }
+
+    Ok(referenced)
+}
+
+/// Find attachments no note or export references, deleting them unless `dry_run`
+pub fn gc_attachments(
+    conn: &Connection,
+    attachments_dir: &Path,
+    notes_dir: &Path,
+    exports_dir: &Path,
+    dry_run: bool,
+) -> Result<AttachmentGcResult, AttachmentGcError> {
+    let mut result = AttachmentGcResult {
+        dry_run,
+        ..Default::default()
+    };
+    if !attachments_dir.is_dir() {
+        return Ok(result);
+    }
+
+    let referenced = referenced_attachments(conn, notes_dir, exports_dir)?;
+
+    let mut entries: Vec<_> = fs::read_dir(attachments_dir)?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        // Skip hidden files like .DS_Store
+        if name.starts_with('.') || referenced.contains(&name) {
+            continue;
+        }
+
+        if !dry_run {
+            fs::remove_file(entry.path())?;
+        }
+        result.bytes += metadata.len();
+        result.orphaned.push(name);
+    }
+
+    if !dry_run && !result.orphaned.is_empty() {
+        log::info!(
+            "Removed {} unreferenced attachments ({} bytes)",
+            result.orphaned.len(),
+            result.bytes
+        );
+    }
+
+    Ok(result)
+}
+
+/// Collect markdown files in a directory recursively
+fn collect_markdown_files(dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(collect_markdown_files(&path)?);
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+    use crate::db::exports::{CreateExportInput, ExportGenerator, ExportSource};
+    use crate::db::notes;
+    use crate::models::{CreateNoteInput, UpdateNoteInput};
+
+    #[test]
+    fn test_gc_collects_only_orphans() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let vault = tempfile::tempdir().unwrap();
+        let attachments_dir = vault.path().join("attachments");
+        let notes_dir = vault.path().join("notes");
+        let exports_dir = vault.path().join("exports");
+        for dir in [&attachments_dir, &notes_dir, &exports_dir] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        for name in ["in-note.png", "in-deleted-note.pdf", "in-revision.png", "in-export.png", "orphan.png", "orphan.pdf"] {
+            fs::write(attachments_dir.join(name), b"data").unwrap();
+        }
+
+        notes::create_note(
+            &conn,
+            CreateNoteInput {
+                title: "Live".to_string(),
+                content: Some("![chart](../attachments/in-note.png)".to_string()),
+                content_html: None,
+                folder_id: None,
+            },
+        )
+        .unwrap();
+        let deleted = notes::create_note(
+            &conn,
+            CreateNoteInput {
+                title: "Trashed".to_string(),
+                content: Some("[report](../attachments/in-deleted-note.pdf)".to_string()),
+                content_html: None,
+                folder_id: None,
+            },
+        )
+        .unwrap();
+        notes::delete_note(&conn, &deleted.id).unwrap();
+        let revised = notes::create_note(
+            &conn,
+            CreateNoteInput {
+                title: "Revised".to_string(),
+                content: Some("![before](../attachments/in-revision.png)".to_string()),
+                content_html: None,
+                folder_id: None,
+            },
+        )
+        .unwrap();
+        notes::update_note(
+            &conn,
+            &revised.id,
+            UpdateNoteInput {
+                title: None,
+                content: Some("Image removed".to_string()),
+                content_html: None,
+                folder_id: None,
+            },
+        )
+        .unwrap();
+
+        let export_path = exports_dir.join("summary.md");
+        fs::write(&export_path, "![old](../attachments/in-export.png)").unwrap();
+        exports::create_export(
+            &conn,
+            CreateExportInput {
+                filename: "summary.md".to_string(),
+                title: "Summary".to_string(),
+                format: ExportFormat::Markdown,
+                source_note_ids: vec![],
+                file_size: None,
+                path: export_path.to_string_lossy().to_string(),
+                duration_ms: None,
+                generator: ExportGenerator::Mechanical,
+                model: None,
+                source: ExportSource::Content,
+                conversation_id: None,
+                options: Default::default(),
+            },
+        )
+        .unwrap();
+
+        // A dry run reports orphans without deleting them
+        let result = gc_attachments(&conn, &attachments_dir, &notes_dir, &exports_dir, true).unwrap();
+        assert_eq!(result.orphaned, vec!["orphan.pdf", "orphan.png"]);
+        assert_eq!(result.bytes, 8);
+        assert!(attachments_dir.join("orphan.png").exists());
+
+        let result = gc_attachments(&conn, &attachments_dir, &notes_dir, &exports_dir, false).unwrap();
+        assert_eq!(result.orphaned, vec!["orphan.pdf", "orphan.png"]);
+        assert!(!attachments_dir.join("orphan.png").exists());
+        assert!(!attachments_dir.join("orphan.pdf").exists());
+        for kept in ["in-note.png", "in-deleted-note.pdf", "in-revision.png", "in-export.png"] {
+            assert!(attachments_dir.join(kept).exists(), "{} should be kept", kept);
+        }
+    }
+}
@@ -0,0 +1,346 @@
+//! Vault backup and restore archives
+//!
+//! A backup is a single zip holding a snapshot of the database, the search
+//! index, the attachments and the note files, plus a `manifest.json` used to
+//! check compatibility before anything is restored.
+
+use crate::db::migrations;
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use zip::write::{FileOptions, ZipWriter};
+use zip::{CompressionMethod, ZipArchive};
+
+/// Version of the archive layout, bumped when it changes incompatibly
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_NAME: &str = "manifest.json";
+const DATABASE_NAME: &str = "inkling.db";
+/// Vault-relative directories included in a backup, and their names in the archive
+const BACKUP_DIRS: &[(&str, &str)] = &[
+    (".inkling/search_index", "search_index"),
+    ("attachments", "attachments"),
+    ("notes", "notes"),
+];
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Archive error: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+    #[error("Manifest error: {0}")]
+    ManifestError(#[from] serde_json::Error),
+    #[error("Database error: {0}")]
+    DbError(#[from] rusqlite::Error),
+    #[error("Invalid backup: {0}")]
+    InvalidBackup(String),
+    #[error("Incompatible backup: {0}")]
+    Incompatible(String),
+}
+
+/// Describes a backup archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub format_version: u32,
+    /// Number of migrations applied to the backed up database
+    pub schema_version: usize,
+    pub app_version: String,
+    pub created_at: String,
+    pub note_count: usize,
+}
+
+/// Write a backup of the vault at `vault_path` to `output`
+///
+/// The database is snapshotted with `VACUUM INTO`, so the backup is
+/// consistent even while the app keeps using `conn`'s database.
+pub fn create_backup(conn: &Connection, vault_path: &Path, output: &Path) -> Result<BackupManifest, BackupError> {
+    let schema_version: usize = conn.query_row("SELECT COUNT(*) FROM _migrations", [], |row| row.get(0))?;
+    let note_count: usize = conn.query_row("SELECT COUNT(*) FROM notes WHERE is_deleted = FALSE", [], |row| row.get(0))?;
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        schema_version,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        note_count,
+    };
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // VACUUM INTO refuses to overwrite, so clear any leftover from a failed run
+    let snapshot = output.with_extension("snapshot.db");
+    if snapshot.exists() {
+        fs::remove_file(&snapshot)?;
+    }
+    conn.execute("VACUUM INTO ?1", [snapshot.to_string_lossy()])?;
+    let result = write_archive(&manifest, &snapshot, vault_path, output);
+    fs::remove_file(&snapshot)?;
+    result?;
+    Ok(manifest)
+}
+
+fn write_archive(
+    manifest: &BackupManifest,
+    snapshot: &Path,
+    vault_path: &Path,
+    output: &Path,
+) -> Result<(), BackupError> {
+    let mut zip = ZipWriter::new(File::create(output)?);
+    let options = FileOptions::<'_, ()>::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+
+    zip.start_file(MANIFEST_NAME, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(manifest)?)?;
+
+    zip.start_file(DATABASE_NAME, options)?;
+    io::copy(&mut File::open(snapshot)?, &mut zip)?;
+
+    for (vault_dir, archive_dir) in BACKUP_DIRS {
+        add_dir(&mut zip, &vault_path.join(vault_dir), archive_dir, options)?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+fn add_dir(
+    zip: &mut ZipWriter<File>,
+    dir: &Path,
+    archive_dir: &str,
+    options: FileOptions<'_, ()>,
+) -> Result<(), BackupError> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    zip.add_directory(archive_dir, options)?;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = format!("{}/{}", archive_dir, entry.file_name().to_string_lossy());
+        if entry.file_type()?.is_dir() {
+            add_dir(zip, &entry.path(), &name, options)?;
+        } else {
+            zip.start_file(name, options)?;
+            io::copy(&mut File::open(entry.path())?, zip)?;
+        }
+    }
+    Ok(())
+}
+
+/// Read and validate the manifest of a backup archive
+///
+/// Fails if the archive is missing its database or was made by a newer
+/// version of the app than this one.
+pub fn read_manifest(archive_path: &Path) -> Result<BackupManifest, BackupError> {
+    let mut archive = ZipArchive::new(File::open(archive_path)?)?;
+    validate_archive(&mut archive)
+}
+
+fn validate_archive(archive: &mut ZipArchive<File>) -> Result<BackupManifest, BackupError> {
+    let manifest: BackupManifest = {
+        let mut text = String::new();
+        archive
+            .by_name(MANIFEST_NAME)
+            .map_err(|_| BackupError::InvalidBackup("missing manifest.json".to_string()))?
+            .read_to_string(&mut text)?;
+        serde_json::from_str(&text)?
+    };
+
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        return Err(BackupError::Incompatible(format!(
+            "archive format {} is newer than supported format {}",
+            manifest.format_version, BACKUP_FORMAT_VERSION
+        )));
+    }
+    if manifest.schema_version > migrations::schema_version() {
+        return Err(BackupError::Incompatible(format!(
+            "backup was made by a newer version of Inkling ({}, schema {})",
+            manifest.app_version, manifest.schema_version
+        )));
+    }
+    if archive.by_name(DATABASE_NAME).is_err() {
+        return Err(BackupError::InvalidBackup("missing database".to_string()));
+    }
+
+    Ok(manifest)
+}
+
+/// Replace the vault's data with the contents of a backup archive
+///
+/// The archive is validated and fully unpacked into a staging directory
+/// before anything in the vault is touched. All database pools and the search
+/// index for the vault must be closed first.
+pub fn restore_backup(archive_path: &Path, vault_path: &Path) -> Result<BackupManifest, BackupError> {
+    let mut archive = ZipArchive::new(File::open(archive_path)?)?;
+    let manifest = validate_archive(&mut archive)?;
+
+    let inkling_dir = vault_path.join(".inkling");
+    let staging = inkling_dir.join("restore-staging");
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        // Rejects absolute paths and `..` components
+        let Some(relative) = entry.enclosed_name() else {
+            fs::remove_dir_all(&staging)?;
+            return Err(BackupError::InvalidBackup(format!("unsafe path in archive: {}", entry.name())));
+        };
+        let target = staging.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = File::create(&target)?;
+        io::copy(&mut entry, &mut file)?;
+        file.sync_all()?;
+    }
+    sync_dir(&staging)?;
+
+    // Swap the staged data into place. The old WAL would be replayed onto the
+    // restored database, so it goes first; the database itself is replaced by
+    // a rename, so a crash leaves either the old file or the new one.
+    let db_path = inkling_dir.join(DATABASE_NAME);
+    for suffix in ["-wal", "-shm"] {
+        let path = PathBuf::from(format!("{}{}", db_path.display(), suffix));
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+    }
+    fs::rename(staging.join(DATABASE_NAME), &db_path)?;
+    sync_dir(&inkling_dir)?;
+
+    for (vault_dir, archive_dir) in BACKUP_DIRS {
+        let target = vault_path.join(vault_dir);
+        if target.exists() {
+            fs::remove_dir_all(&target)?;
+        }
+        let staged = staging.join(archive_dir);
+        if staged.is_dir() {
+            fs::rename(&staged, &target)?;
+        } else {
+            fs::create_dir_all(&target)?;
+        }
+    }
+
+    fs::remove_dir_all(&staging)?;
+    log::info!(
+        "Restored backup from {} ({} notes, made {})",
+        archive_path.display(),
+        manifest.note_count,
+        manifest.created_at
+    );
+    Ok(manifest)
+}
+
+/// Flush a directory's entries, so renames into it survive a crash
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+/// Directories can't be opened for syncing on this platform
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_pool_at_path;
+    use crate::db::notes;
+    use crate::models::CreateNoteInput;
+
+    fn note_count(db_path: &Path) -> usize {
+        let pool = init_pool_at_path(&db_path.to_path_buf()).unwrap();
+        let conn = pool.get().unwrap();
+        notes::get_all_notes(&conn).unwrap().len()
+    }
+
+    #[test]
+    fn test_backup_and_restore_into_fresh_vault() {
+        let source = tempfile::tempdir().unwrap();
+        let source_db = source.path().join(".inkling").join(DATABASE_NAME);
+        let pool = init_pool_at_path(&source_db).unwrap();
+        {
+            let conn = pool.get().unwrap();
+            for title in ["One", "Two", "Three"] {
+                notes::create_note(
+                    &conn,
+                    CreateNoteInput {
+                        title: title.to_string(),
+                        content: Some("Body".to_string()),
+                        content_html: None,
+                        folder_id: None,
+                    },
+                )
+                .unwrap();
+            }
+        }
+        fs::create_dir_all(source.path().join("attachments")).unwrap();
+        fs::write(source.path().join("attachments/image.png"), b"png").unwrap();
+        fs::create_dir_all(source.path().join(".inkling/search_index")).unwrap();
+        fs::write(source.path().join(".inkling/search_index/meta.json"), b"{}").unwrap();
+        fs::create_dir_all(source.path().join("notes/Work")).unwrap();
+        fs::write(source.path().join("notes/Work/One.md"), b"# One").unwrap();
+
+        let archive = source.path().join("backup.zip");
+        let manifest = create_backup(&pool.get().unwrap(), source.path(), &archive).unwrap();
+        assert_eq!(manifest.note_count, 3);
+        assert_eq!(manifest.schema_version, migrations::schema_version());
+        assert_eq!(read_manifest(&archive).unwrap().note_count, 3);
+
+        // Restore over a fresh vault that has data of its own
+        let target = tempfile::tempdir().unwrap();
+        let target_db = target.path().join(".inkling").join(DATABASE_NAME);
+        drop(init_pool_at_path(&target_db).unwrap());
+        fs::create_dir_all(target.path().join("attachments")).unwrap();
+        fs::write(target.path().join("attachments/stale.png"), b"old").unwrap();
+
+        restore_backup(&archive, target.path()).unwrap();
+        assert_eq!(note_count(&target_db), 3);
+        assert_eq!(fs::read(target.path().join("attachments/image.png")).unwrap(), b"png");
+        assert!(!target.path().join("attachments/stale.png").exists());
+        assert!(target.path().join(".inkling/search_index/meta.json").exists());
+        assert!(target.path().join("notes/Work/One.md").exists());
+        assert!(!target.path().join(".inkling/restore-staging").exists());
+    }
+
+    #[test]
+    fn test_restore_rejects_newer_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("future.zip");
+        {
+            let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+            let options = FileOptions::<'_, ()>::default();
+            let manifest = BackupManifest {
+                format_version: BACKUP_FORMAT_VERSION,
+                schema_version: migrations::schema_version() + 1,
+                app_version: "99.0.0".to_string(),
+                created_at: Utc::now().to_rfc3339(),
+                note_count: 0,
+            };
+            zip.start_file(MANIFEST_NAME, options).unwrap();
+            zip.write_all(&serde_json::to_vec(&manifest).unwrap()).unwrap();
+            zip.start_file(DATABASE_NAME, options).unwrap();
+            zip.finish().unwrap();
+        }
+
+        let err = restore_backup(&archive, dir.path()).unwrap_err();
+        assert!(matches!(err, BackupError::Incompatible(_)));
+        assert!(!dir.path().join(".inkling").exists());
+    }
+}
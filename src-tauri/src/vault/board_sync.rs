@@ -7,7 +7,9 @@ use crate::db::connection::DbPool;
 use crate::db::folders;
 use crate::models::AddCardInput;
 use crate::vault::config;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
@@ -47,7 +49,12 @@ pub struct BoardFileLane {
     pub name: String,
     pub color: Option<String>,
     pub position: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wip_limit: Option<u32>,
     pub card_note_ids: Vec<String>,
+    /// Due dates of the cards that have one, by note ID
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub card_due_dates: HashMap<String, DateTime<Utc>>,
 }
 
 /// JSON structure for the .board.json file
@@ -56,9 +63,20 @@ pub struct BoardFileLane {
 pub struct BoardFile {
     pub id: String,
     pub name: String,
+    #[serde(default = "default_enforce_wip_limits")]
+    pub enforce_wip_limits: bool,
     pub lanes: Vec<BoardFileLane>,
 }
 
+fn default_enforce_wip_limits() -> bool {
+    true
+}
+
+/// Format a due date the way board cards store it
+fn format_due_date(due: &DateTime<Utc>) -> String {
+    due.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
 /// Get the path to a folder's board file
 fn get_board_file_path(conn: &rusqlite::Connection, folder_id: &str) -> Result<PathBuf, BoardSyncError> {
     let notes_dir = config::get_notes_dir()?;
@@ -105,13 +123,20 @@ pub fn sync_board_to_file(pool: &DbPool, board_id: &str) -> Result<PathBuf, Boar
             .filter(|c| c.lane_id == lane.id)
             .map(|c| c.note_id.clone())
             .collect();
+        let card_due_dates: HashMap<String, DateTime<Utc>> = details.cards
+            .iter()
+            .filter(|c| c.lane_id == lane.id)
+            .filter_map(|c| c.due_date.map(|due| (c.note_id.clone(), due)))
+            .collect();
         
         BoardFileLane {
             id: lane.id.clone(),
             name: lane.name.clone(),
             color: lane.color.clone(),
             position: lane.position,
+            wip_limit: lane.wip_limit,
             card_note_ids,
+            card_due_dates,
         }
     }).collect();
     
@@ -121,6 +146,7 @@ pub fn sync_board_to_file(pool: &DbPool, board_id: &str) -> Result<PathBuf, Boar
     let board_file = BoardFile {
         id: details.board.id,
         name: details.board.name,
+        enforce_wip_limits: details.board.enforce_wip_limits,
         lanes,
     };
     
@@ -153,6 +179,7 @@ pub fn sync_file_to_board(pool: &DbPool, file_path: &Path, folder_id: &str) -> R
             &existing.id,
             crate::models::UpdateBoardInput {
                 name: Some(board_file.name),
+                enforce_wip_limits: Some(board_file.enforce_wip_limits),
             },
         )?;
         
@@ -166,26 +193,27 @@ pub fn sync_file_to_board(pool: &DbPool, file_path: &Path, folder_id: &str) -> R
         let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
         
         conn.execute(
-            "INSERT INTO boards (id, folder_id, name, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
-            rusqlite::params![id, folder_id, board_file.name, now, now],
+            "INSERT INTO boards (id, folder_id, name, created_at, updated_at, enforce_wip_limits)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![id, folder_id, board_file.name, now, now, board_file.enforce_wip_limits],
         ).map_err(|e| BoardSyncError::DbError(e.to_string()))?;
         
         // Create lanes from file
         for lane in &board_file.lanes {
             conn.execute(
-                "INSERT INTO board_lanes (id, board_id, name, color, position)
-                 VALUES (?1, ?2, ?3, ?4, ?5)",
-                rusqlite::params![lane.id, id, lane.name, lane.color, lane.position],
+                "INSERT INTO board_lanes (id, board_id, name, color, position, wip_limit)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![lane.id, id, lane.name, lane.color, lane.position, lane.wip_limit],
             ).map_err(|e| BoardSyncError::DbError(e.to_string()))?;
             
             // Add cards
             for (position, note_id) in lane.card_note_ids.iter().enumerate() {
                 let card_id = uuid::Uuid::new_v4().to_string();
+                let due_date = lane.card_due_dates.get(note_id).map(format_due_date);
                 conn.execute(
-                    "INSERT INTO board_cards (id, board_id, lane_id, note_id, position)
-                     VALUES (?1, ?2, ?3, ?4, ?5)",
-                    rusqlite::params![card_id, id, lane.id, note_id, position as i32],
+                    "INSERT INTO board_cards (id, board_id, lane_id, note_id, position, due_date)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    rusqlite::params![card_id, id, lane.id, note_id, position as i32, due_date],
                 ).map_err(|e| BoardSyncError::DbError(e.to_string()))?;
             }
         }
@@ -222,17 +250,29 @@ fn sync_lanes_from_file(
                     color: file_lane.color.clone(),
                 },
             )?;
+            // Only write a changed limit, since setting it bumps the board's updated_at
+            let current_limit = existing_lanes.iter().find(|l| l.id == file_lane.id).and_then(|l| l.wip_limit);
+            if current_limit != file_lane.wip_limit {
+                boards::set_lane_wip_limit(conn, &file_lane.id, file_lane.wip_limit)?;
+            }
         } else {
             // Create with specific ID
             conn.execute(
-                "INSERT INTO board_lanes (id, board_id, name, color, position)
-                 VALUES (?1, ?2, ?3, ?4, ?5)",
-                rusqlite::params![file_lane.id, board_id, file_lane.name, file_lane.color, file_lane.position],
+                "INSERT INTO board_lanes (id, board_id, name, color, position, wip_limit)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![
+                    file_lane.id,
+                    board_id,
+                    file_lane.name,
+                    file_lane.color,
+                    file_lane.position,
+                    file_lane.wip_limit,
+                ],
             ).map_err(|e| BoardSyncError::DbError(e.to_string()))?;
         }
         
         // Sync cards for this lane
-        sync_cards_for_lane(conn, board_id, &file_lane.id, &file_lane.card_note_ids)?;
+        sync_cards_for_lane(conn, board_id, file_lane)?;
     }
     
     // Reorder lanes
@@ -246,9 +286,10 @@ fn sync_lanes_from_file(
 fn sync_cards_for_lane(
     conn: &rusqlite::Connection,
     board_id: &str,
-    lane_id: &str,
-    note_ids: &[String],
+    file_lane: &BoardFileLane,
 ) -> Result<(), BoardSyncError> {
+    let lane_id = file_lane.id.as_str();
+    let note_ids = &file_lane.card_note_ids;
     // Get existing cards in this lane
     let existing_cards = boards::get_cards_in_lane(conn, lane_id)?;
     let existing_note_ids: std::collections::HashSet<String> = existing_cards.iter().map(|c| c.note_id.clone()).collect();
@@ -266,21 +307,23 @@ fn sync_cards_for_lane(
         if !existing_note_ids.contains(note_id) {
             // Check if note is already on this board in a different lane
             if !boards::is_note_on_board(conn, note_id, board_id)? {
-                boards::add_card(
+                boards::insert_card(
                     conn,
                     AddCardInput {
                         board_id: board_id.to_string(),
                         lane_id: lane_id.to_string(),
                         note_id: note_id.clone(),
+                        due_date: None,
                     },
                 )?;
             }
         }
         
-        // Update position for existing card
+        // Update position and due date for existing card
+        let due_date = file_lane.card_due_dates.get(note_id).map(format_due_date);
         conn.execute(
-            "UPDATE board_cards SET position = ?1 WHERE lane_id = ?2 AND note_id = ?3",
-            rusqlite::params![position as i32, lane_id, note_id],
+            "UPDATE board_cards SET position = ?1, due_date = ?2 WHERE lane_id = ?3 AND note_id = ?4",
+            rusqlite::params![position as i32, due_date, lane_id, note_id],
         ).map_err(|e| BoardSyncError::DbError(e.to_string()))?;
     }
     
@@ -13,6 +13,9 @@ use thiserror::Error;
 /// Global vault path storage (set on app startup)
 static VAULT_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
 
+/// Global exports directory override (loaded from config on app startup)
+static EXPORTS_DIR_OVERRIDE: RwLock<Option<PathBuf>> = RwLock::new(None);
+
 #[derive(Error, Debug)]
 pub enum VaultError {
     #[error("No vault configured")]
@@ -23,6 +26,8 @@ pub enum VaultError {
     JsonError(#[from] serde_json::Error),
     #[error("Failed to get app data directory")]
     NoAppDataDir,
+    #[error("Directory is not writable: {0}")]
+    NotWritable(String),
 }
 
 /// Information about a vault
@@ -44,9 +49,13 @@ pub struct VaultStatus {
 }
 
 /// Vault configuration stored in app data directory
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct VaultConfig {
+    #[serde(default)]
     vault_path: String,
+    /// Custom directory for exports, used instead of `<vault>/exports`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exports_dir: Option<String>,
 }
 
 /// Get the app config directory (outside vault, for storing vault path)
@@ -68,16 +77,35 @@ pub fn get_old_app_data_dir() -> Result<PathBuf, VaultError> {
         .ok_or(VaultError::NoAppDataDir)
 }
 
-/// Load vault path from config file
-pub fn load_vault_path() -> Result<Option<PathBuf>, VaultError> {
+/// Read the config file, or an empty config if there isn't one yet
+fn load_vault_config() -> Result<VaultConfig, VaultError> {
     let config_path = get_vault_config_path()?;
     
     if !config_path.exists() {
-        return Ok(None);
+        return Ok(VaultConfig::default());
     }
     
     let content = fs::read_to_string(&config_path)?;
-    let config: VaultConfig = serde_json::from_str(&content)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Write the config file
+fn save_vault_config(config: &VaultConfig) -> Result<(), VaultError> {
+    let config_dir = get_app_config_dir()?;
+    fs::create_dir_all(&config_dir)?;
+    
+    let content = serde_json::to_string_pretty(config)?;
+    fs::write(get_vault_config_path()?, content)?;
+    
+    Ok(())
+}
+
+/// Load vault path from config file
+pub fn load_vault_path() -> Result<Option<PathBuf>, VaultError> {
+    let config = load_vault_config()?;
+    if config.vault_path.is_empty() {
+        return Ok(None);
+    }
     
     let path = PathBuf::from(&config.vault_path);
     if path.exists() {
@@ -89,20 +117,44 @@ pub fn load_vault_path() -> Result<Option<PathBuf>, VaultError> {
 
 /// Save vault path to config file
 pub fn save_vault_path(path: &Path) -> Result<(), VaultError> {
-    let config_dir = get_app_config_dir()?;
-    fs::create_dir_all(&config_dir)?;
+    let mut config = load_vault_config().unwrap_or_default();
+    config.vault_path = path.to_string_lossy().to_string();
+    save_vault_config(&config)
+}
+
+/// Load the exports directory override from config file
+pub fn load_exports_dir_override() -> Result<Option<PathBuf>, VaultError> {
+    Ok(load_vault_config()?.exports_dir.map(PathBuf::from))
+}
+
+/// Save the exports directory override to config file and set it in memory
+///
+/// The directory is created if missing and must be writable. Passing `None`
+/// clears the override so exports go back to `<vault>/exports`.
+pub fn save_exports_dir_override(path: Option<&Path>) -> Result<(), VaultError> {
+    if let Some(path) = path {
+        ensure_writable_dir(path)?;
+    }
     
-    let config = VaultConfig {
-        vault_path: path.to_string_lossy().to_string(),
-    };
-    
-    let config_path = get_vault_config_path()?;
-    let content = serde_json::to_string_pretty(&config)?;
-    fs::write(config_path, content)?;
+    let mut config = load_vault_config().unwrap_or_default();
+    config.exports_dir = path.map(|p| p.to_string_lossy().to_string());
+    save_vault_config(&config)?;
     
+    set_exports_dir_override(path.map(Path::to_path_buf));
     Ok(())
 }
 
+/// Set the exports directory override in memory
+pub fn set_exports_dir_override(path: Option<PathBuf>) {
+    let mut exports_dir = EXPORTS_DIR_OVERRIDE.write().unwrap();
+    *exports_dir = path;
+}
+
+/// Get the exports directory override from memory
+pub fn get_exports_dir_override() -> Option<PathBuf> {
+    EXPORTS_DIR_OVERRIDE.read().unwrap().clone()
+}
+
 /// Set the current vault path in memory
 pub fn set_current_vault_path(path: Option<PathBuf>) {
     let mut vault = VAULT_PATH.write().unwrap();
@@ -228,8 +280,29 @@ pub fn get_attachments_dir() -> Result<PathBuf, VaultError> {
     Ok(vault.join("attachments"))
 }
 
+/// Get the exports directory, honoring the configured override
 pub fn get_exports_dir() -> Result<PathBuf, VaultError> {
     let vault = get_current_vault_path().ok_or(VaultError::NotConfigured)?;
+    resolve_exports_dir(&vault, get_exports_dir_override().as_deref())
+}
+
+/// Pick the exports directory for a vault
+///
+/// Uses the override when one is set, `<vault>/exports` otherwise. The
+/// override was checked for writability when it was set; if it has gone
+/// missing since (e.g. an external drive isn't mounted) this errors rather
+/// than quietly exporting somewhere else.
+fn resolve_exports_dir(vault: &Path, override_dir: Option<&Path>) -> Result<PathBuf, VaultError> {
+    if let Some(dir) = override_dir {
+        if !dir.is_dir() {
+            return Err(VaultError::NotWritable(format!(
+                "{}: the exports directory is missing; reconnect it or choose another in Settings",
+                dir.display()
+            )));
+        }
+        return Ok(dir.to_path_buf());
+    }
+    
     let exports_dir = vault.join("exports");
     
     // Ensure the exports directory exists
@@ -239,3 +312,71 @@ pub fn get_exports_dir() -> Result<PathBuf, VaultError> {
     
     Ok(exports_dir)
 }
+
+/// Create a directory if missing and check that files can be written to it
+fn ensure_writable_dir(dir: &Path) -> Result<(), VaultError> {
+    let not_writable = |e: std::io::Error| VaultError::NotWritable(format!("{}: {}", dir.display(), e));
+    
+    fs::create_dir_all(dir).map_err(not_writable)?;
+    let probe = dir.join(".inkling-write-test");
+    fs::write(&probe, b"").map_err(not_writable)?;
+    let _ = fs::remove_file(&probe);
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exports_dir_defaults_to_vault() {
+        let vault = tempfile::tempdir().unwrap();
+        
+        let dir = resolve_exports_dir(vault.path(), None).unwrap();
+        assert_eq!(dir, vault.path().join("exports"));
+        assert!(dir.is_dir());
+    }
+
+    #[test]
+    fn test_exports_dir_override_is_created_and_used() {
+        let vault = tempfile::tempdir().unwrap();
+        let external = tempfile::tempdir().unwrap();
+        let custom = external.path().join("inkling").join("exports");
+        
+        // Setting the override creates it and probes it once
+        ensure_writable_dir(&custom).unwrap();
+        let dir = resolve_exports_dir(vault.path(), Some(&custom)).unwrap();
+        assert_eq!(dir, custom);
+        assert!(custom.is_dir());
+        // The write probe is cleaned up
+        assert_eq!(fs::read_dir(&custom).unwrap().count(), 0);
+        assert!(!vault.path().join("exports").exists());
+    }
+
+    #[test]
+    fn test_missing_exports_dir_override_is_an_error() {
+        let vault = tempfile::tempdir().unwrap();
+        // A path under a regular file can never be created
+        let blocker = vault.path().join("not-a-dir");
+        fs::write(&blocker, "x").unwrap();
+        let custom = blocker.join("exports");
+        
+        assert!(matches!(ensure_writable_dir(&custom), Err(VaultError::NotWritable(_))));
+        
+        // An override that has gone missing isn't swapped for the vault's folder
+        let unmounted = vault.path().join("unmounted").join("exports");
+        for dir in [&custom, &unmounted] {
+            assert!(matches!(resolve_exports_dir(vault.path(), Some(dir)), Err(VaultError::NotWritable(_))));
+        }
+        assert!(!unmounted.exists());
+        assert!(!vault.path().join("exports").exists());
+    }
+
+    #[test]
+    fn test_vault_config_without_exports_dir_still_parses() {
+        let config: VaultConfig = serde_json::from_str(r#"{"vault_path": "/tmp/vault"}"#).unwrap();
+        assert_eq!(config.vault_path, "/tmp/vault");
+        assert!(config.exports_dir.is_none());
+    }
+}
@@ -0,0 +1,320 @@
+//! Import of markdown folders from other apps
+//!
+//! Reads a directory of `.md` files, such as an Obsidian or Logseq vault, into
+//! notes. Subdirectories become folders, frontmatter tags and aliases are kept,
+//! and `[[wikilinks]]` between the imported notes become note links.
+//!
+//! Importing the same directory again is safe: a file whose title matches a
+//! note already in its folder updates that note instead of adding a copy.
+
+use crate::db::{folders, links, notes, tags};
+use crate::models::{CreateFolderInput, CreateNoteInput, Note, UpdateNoteInput};
+use crate::vault::markdown;
+use crate::vault::sync::SyncError;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A file that could not be imported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportFileError {
+    /// Path relative to the imported directory
+    pub path: String,
+    pub message: String,
+}
+
+/// Summary of a markdown directory import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    /// Notes created for files not imported before
+    pub notes_imported: usize,
+    /// Previously imported notes whose file content changed
+    pub notes_updated: usize,
+    /// Previously imported notes left as they were
+    pub notes_unchanged: usize,
+    pub folders_created: usize,
+    pub links_created: usize,
+    pub errors: Vec<ImportFileError>,
+    /// IDs of the created and updated notes
+    pub note_ids: Vec<String>,
+}
+
+/// Import every markdown file under `source` as a note
+///
+/// Hidden directories (`.obsidian`, `.trash`, ...) are skipped. Folders that
+/// already exist with the same name and parent are reused.
+pub fn import_markdown_directory(conn: &Connection, source: &Path) -> Result<ImportReport, SyncError> {
+    if !source.is_dir() {
+        return Err(SyncError::IoError(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Not a directory: {}", source.display()),
+        )));
+    }
+
+    let mut report = ImportReport::default();
+    // Imported notes by lowercased title and by lowercased relative path without extension
+    let mut by_name: HashMap<String, String> = HashMap::new();
+    let mut imported: Vec<(String, String)> = Vec::new();
+
+    import_dir(conn, source, source, None, &mut report, &mut by_name, &mut imported)?;
+
+    for (note_id, content) in &imported {
+        let mut link_inputs: Vec<links::LinkInput> = Vec::new();
+        for link in links::parse_wikilinks(content) {
+            let Some(target_id) = resolve_import_link(conn, &by_name, &link.target)? else {
+                continue;
+            };
+            if &target_id == note_id || link_inputs.iter().any(|l| l.target_note_id == target_id) {
+                continue;
+            }
+            link_inputs.push(links::LinkInput {
+                target_note_id: target_id,
+                context: link.display,
+            });
+        }
+        links::sync_links(conn, note_id, &link_inputs)?;
+        report.links_created += link_inputs.len();
+    }
+
+    Ok(report)
+}
+
+fn import_dir(
+    conn: &Connection,
+    root: &Path,
+    dir: &Path,
+    folder_id: Option<&str>,
+    report: &mut ImportReport,
+    by_name: &mut HashMap<String, String>,
+    imported: &mut Vec<(String, String)>,
+) -> Result<(), SyncError> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    entries.sort();
+    let existing = notes::get_notes_in_folder(conn, folder_id)?;
+
+    for path in entries {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if name.starts_with('.') {
+            continue;
+        }
+
+        if path.is_dir() {
+            let child_id = find_or_create_folder(conn, &name, folder_id, report)?;
+            import_dir(conn, root, &path, Some(&child_id), report, by_name, imported)?;
+            continue;
+        }
+        if path.extension().is_none_or(|ext| !ext.eq_ignore_ascii_case("md")) {
+            continue;
+        }
+
+        let relative = path.strip_prefix(root).unwrap_or(&path).with_extension("");
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+
+        let parsed = fs::read_to_string(&path)
+            .map_err(markdown::MarkdownError::from)
+            .and_then(|text| markdown::parse_external_markdown(&text, &stem));
+        let parsed = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                report.errors.push(ImportFileError {
+                    path: format!("{}.md", relative),
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let note = match find_imported_note(&existing, &parsed.title) {
+            Some(note) if note.content.as_deref() == Some(parsed.content.as_str()) => {
+                report.notes_unchanged += 1;
+                note.clone()
+            }
+            Some(note) => {
+                let update = UpdateNoteInput {
+                    title: None,
+                    content: Some(parsed.content.clone()),
+                    content_html: None,
+                    folder_id: None,
+                };
+                report.notes_updated += 1;
+                let note = notes::update_note_with_revision(conn, &note.id, update)?;
+                report.note_ids.push(note.id.clone());
+                note
+            }
+            None => {
+                let note = notes::create_note(
+                    conn,
+                    CreateNoteInput {
+                        title: parsed.title.clone(),
+                        content: Some(parsed.content.clone()),
+                        content_html: None,
+                        folder_id: folder_id.map(String::from),
+                    },
+                )?;
+                report.notes_imported += 1;
+                report.note_ids.push(note.id.clone());
+                note
+            }
+        };
+        for tag_name in &parsed.tags {
+            let tag = tags::find_or_create_tag(conn, tag_name, None)?;
+            tags::add_tag_to_note(conn, &note.id, &tag.id)?;
+        }
+        for alias in &parsed.aliases {
+            match links::add_note_alias(conn, &note.id, alias) {
+                Err(links::LinkDbError::AliasTaken(alias)) => {
+                    log::warn!("Skipping alias '{}' on {}: already used by another note", alias, relative);
+                    continue;
+                }
+                result => result?,
+            }
+            by_name.entry(alias.to_lowercase()).or_insert_with(|| note.id.clone());
+        }
+
+        by_name.insert(relative.to_lowercase(), note.id.clone());
+        by_name.entry(stem.to_lowercase()).or_insert_with(|| note.id.clone());
+        by_name.entry(parsed.title.to_lowercase()).or_insert_with(|| note.id.clone());
+
+        imported.push((note.id, parsed.content));
+    }
+
+    Ok(())
+}
+
+/// The note a file was imported as before: same title, same folder
+fn find_imported_note<'a>(existing: &'a [Note], title: &str) -> Option<&'a Note> {
+    existing.iter().find(|note| note.title == title)
+}
+
+fn find_or_create_folder(
+    conn: &Connection,
+    name: &str,
+    parent_id: Option<&str>,
+    report: &mut ImportReport,
+) -> Result<String, SyncError> {
+    if let Some(existing) = folders::get_child_folders(conn, parent_id)?
+        .into_iter()
+        .find(|f| f.name == name)
+    {
+        return Ok(existing.id);
+    }
+
+    let folder = folders::create_folder(
+        conn,
+        CreateFolderInput {
+            name: name.to_string(),
+            parent_id: parent_id.map(String::from),
+        },
+    )?;
+    report.folders_created += 1;
+    Ok(folder.id)
+}
+
+/// Resolve an Obsidian-style link target to a note ID
+///
+/// Handles `Folder/Note`, `Note.md`, and `Note#Heading` forms, preferring
+/// imported notes and falling back to existing notes by title or alias.
+fn resolve_import_link(
+    conn: &Connection,
+    by_name: &HashMap<String, String>,
+    target: &str,
+) -> Result<Option<String>, SyncError> {
+    let target = target.split(['#', '^']).next().unwrap_or_default().trim();
+    let target = target.strip_suffix(".md").unwrap_or(target);
+    if target.is_empty() {
+        return Ok(None);
+    }
+
+    let lowered = target.to_lowercase();
+    let last_segment = lowered.rsplit('/').next().unwrap_or(&lowered);
+    if let Some(id) = by_name.get(&lowered).or_else(|| by_name.get(last_segment)) {
+        return Ok(Some(id.clone()));
+    }
+
+    Ok(links::resolve_link_target(conn, target)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+
+    #[test]
+    fn test_import_directory_tree() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let source = tempfile::tempdir().unwrap();
+        let root = source.path();
+        fs::create_dir_all(root.join("Projects/Alpha")).unwrap();
+        fs::create_dir_all(root.join(".obsidian")).unwrap();
+        fs::write(root.join(".obsidian/workspace.md"), "ignored").unwrap();
+        fs::write(root.join("notes.txt"), "ignored").unwrap();
+
+        fs::write(
+            root.join("Index.md"),
+            "---\ntags: [home]\naliases: [Start]\n---\n# Index\n\nSee [[Roadmap]] and [[Projects/Alpha/Spec#Goals|the spec]].",
+        )
+        .unwrap();
+        fs::write(root.join("Projects/Roadmap.md"), "Back to [[Start]]. Missing: [[Nowhere]].").unwrap();
+        fs::write(root.join("Projects/Alpha/Spec.md"), "## Goals\nShip it").unwrap();
+        fs::write(root.join("Projects/Broken.md"), "---\ntags: [unclosed\n---\nBody").unwrap();
+
+        let report = import_markdown_directory(&conn, root).unwrap();
+        assert_eq!(report.notes_imported, 3);
+        assert_eq!(report.folders_created, 2);
+        assert_eq!(report.links_created, 3);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].path, "Projects/Broken.md");
+
+        // Folder structure is preserved
+        let projects = folders::get_child_folders(&conn, None).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, "Projects");
+        let alpha = folders::get_child_folders(&conn, Some(&projects[0].id)).unwrap();
+        assert_eq!(alpha[0].name, "Alpha");
+
+        let all = notes::get_all_notes(&conn).unwrap();
+        let find = |title: &str| all.iter().find(|n| n.title == title).unwrap().clone();
+        let index = find("Index");
+        let roadmap = find("Roadmap");
+        let spec = find("Spec");
+        assert_eq!(index.content.as_deref(), Some("See [[Roadmap]] and [[Projects/Alpha/Spec#Goals|the spec]]."));
+        assert_eq!(index.folder_id, None);
+        assert_eq!(roadmap.folder_id.as_deref(), Some(projects[0].id.as_str()));
+        assert_eq!(spec.folder_id.as_deref(), Some(alpha[0].id.as_str()));
+        assert_eq!(tags::get_note_tags(&conn, &index.id).unwrap()[0].name, "home");
+
+        // Links resolve by title, path, and alias
+        let mut outgoing: Vec<String> = links::get_outgoing_links(&conn, &index.id)
+            .unwrap()
+            .into_iter()
+            .map(|l| l.target_note_id)
+            .collect();
+        outgoing.sort();
+        let mut expected = vec![roadmap.id.clone(), spec.id.clone()];
+        expected.sort();
+        assert_eq!(outgoing, expected);
+        let backlinks = links::get_backlinks(&conn, &index.id).unwrap();
+        assert_eq!(backlinks.len(), 1);
+
+        // Re-importing reuses the existing folders and notes, updating changed ones
+        fs::write(root.join("Projects/Alpha/Spec.md"), "## Goals\nShip it twice").unwrap();
+        let report = import_markdown_directory(&conn, root).unwrap();
+        assert_eq!(report.folders_created, 0);
+        assert_eq!(report.notes_imported, 0);
+        assert_eq!(report.notes_updated, 1);
+        assert_eq!(report.notes_unchanged, 2);
+        assert_eq!(report.note_ids, vec![spec.id.clone()]);
+        assert_eq!(report.links_created, 3);
+        assert_eq!(notes::get_all_notes(&conn).unwrap().len(), 3);
+        let spec = notes::get_note(&conn, &spec.id).unwrap().unwrap();
+        assert_eq!(spec.content.as_deref(), Some("## Goals\nShip it twice"));
+    }
+}
@@ -0,0 +1,214 @@
+//! Export of notes from the legacy app-data database into a vault
+//!
+//! Each note written is appended to a progress log in the vault's `.inkling`
+//! directory, so a migration interrupted part way (crash, quit) resumes where
+//! it stopped instead of starting over. The log is removed once every note
+//! has been written.
+
+use crate::db::{folders, notes};
+use crate::vault::markdown;
+use crate::vault::sync::{self, SyncError};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Event emitted to the frontend as notes are migrated
+pub const MIGRATION_PROGRESS_EVENT: &str = "vault-migration-progress";
+
+/// Progress of a legacy migration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationProgress {
+    /// Notes written so far in this run
+    pub migrated: usize,
+    /// Notes already written by an earlier, interrupted run
+    pub skipped: usize,
+    /// Notes that failed to write; they are retried on the next run
+    pub failed: usize,
+    pub total: usize,
+    /// Title of the note just handled
+    pub current_title: Option<String>,
+}
+
+/// Path of the log of note IDs already written to the vault
+pub fn progress_log_path(vault_path: &Path) -> PathBuf {
+    vault_path.join(".inkling").join("migration-progress.log")
+}
+
+/// Note IDs recorded as written by earlier runs
+fn read_progress_log(path: &Path) -> Result<HashSet<String>, SyncError> {
+    if !path.exists() {
+        return Ok(HashSet::new());
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Write every note in the legacy database to the vault as markdown
+///
+/// `on_progress` is called after each note. Notes listed in the progress log
+/// are skipped.
+pub fn migrate_legacy_notes<F>(
+    conn: &Connection,
+    vault_path: &Path,
+    mut on_progress: F,
+) -> Result<MigrationProgress, SyncError>
+where
+    F: FnMut(&MigrationProgress),
+{
+    let all_notes: Vec<_> = notes::get_all_notes(conn)?
+        .into_iter()
+        .filter(|note| !note.is_deleted)
+        .collect();
+    let all_folders = folders::get_all_folders(conn)?;
+
+    // Create folder name mapping
+    let folder_map: HashMap<String, String> = all_folders
+        .iter()
+        .map(|f| (f.id.clone(), f.name.clone()))
+        .collect();
+
+    // Ensure vault structure exists
+    let notes_dir = vault_path.join("notes");
+    fs::create_dir_all(&notes_dir)?;
+    fs::create_dir_all(vault_path.join(".inkling"))?;
+    for folder in &all_folders {
+        fs::create_dir_all(notes_dir.join(&folder.name))?;
+    }
+
+    let log_path = progress_log_path(vault_path);
+    let done = read_progress_log(&log_path)?;
+    let mut log = OpenOptions::new().create(true).append(true).open(&log_path)?;
+
+    let mut progress = MigrationProgress {
+        total: all_notes.len(),
+        ..Default::default()
+    };
+
+    for note in all_notes {
+        progress.current_title = Some(note.title.clone());
+        if done.contains(&note.id) {
+            progress.skipped += 1;
+            on_progress(&progress);
+            continue;
+        }
+
+        let folder_name = note.folder_id.as_ref()
+            .and_then(|id| folder_map.get(id))
+            .cloned();
+        let file_path = markdown::get_note_path(&notes_dir, &note.title, folder_name.as_deref());
+
+        // The legacy database may predate aliases, so missing metadata isn't fatal
+        let (tag_names, aliases) = sync::note_metadata(conn, &note.id).unwrap_or_default();
+
+        match markdown::write_note_file(
+            &file_path,
+            &note.id,
+            &note.title,
+            note.content.as_deref(),
+            &tag_names,
+            &aliases,
+            note.folder_id.as_deref(),
+            folder_name.as_deref(),
+            note.created_at,
+            note.updated_at,
+        ) {
+            Ok(()) => {
+                // Record only once the file is fully written
+                writeln!(log, "{}", note.id)?;
+                log.flush()?;
+                progress.migrated += 1;
+            }
+            Err(e) => {
+                log::warn!("Failed to export note {}: {}", note.title, e);
+                progress.failed += 1;
+            }
+        }
+        on_progress(&progress);
+    }
+
+    drop(log);
+    if progress.failed == 0 {
+        fs::remove_file(&log_path)?;
+    }
+
+    Ok(progress)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+    use crate::models::CreateNoteInput;
+
+    fn create(conn: &Connection, title: &str) -> String {
+        notes::create_note(
+            conn,
+            CreateNoteInput {
+                title: title.to_string(),
+                content: Some(format!("{} body", title)),
+                content_html: None,
+                folder_id: None,
+            },
+        )
+        .unwrap()
+        .id
+    }
+
+    #[test]
+    fn test_resume_skips_notes_written_before_interruption() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let vault = tempfile::tempdir().unwrap();
+        let first = create(&conn, "First");
+        let second = create(&conn, "Second");
+        create(&conn, "Third");
+
+        // An earlier run wrote two notes before it was interrupted
+        let log_path = progress_log_path(vault.path());
+        fs::create_dir_all(log_path.parent().unwrap()).unwrap();
+        fs::write(&log_path, format!("{}\n{}\n", first, second)).unwrap();
+
+        let mut events = Vec::new();
+        let progress = migrate_legacy_notes(&conn, vault.path(), |p| events.push(p.clone())).unwrap();
+
+        assert_eq!(progress.total, 3);
+        assert_eq!(progress.skipped, 2);
+        assert_eq!(progress.migrated, 1);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events.last().unwrap().migrated + events.last().unwrap().skipped, 3);
+
+        // Only the remaining note was written, and the finished log is cleared
+        let notes_dir = vault.path().join("notes");
+        assert!(notes_dir.join("Third.md").exists());
+        assert!(!notes_dir.join("First.md").exists());
+        assert!(!notes_dir.join("Second.md").exists());
+        assert!(!log_path.exists());
+    }
+
+    #[test]
+    fn test_progress_log_records_written_notes() {
+        let pool = init_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let vault = tempfile::tempdir().unwrap();
+        create(&conn, "First");
+        create(&conn, "Second");
+
+        // Check the log as each note completes, as a crash at that point would leave it
+        let log_path = progress_log_path(vault.path());
+        let mut logged = Vec::new();
+        migrate_legacy_notes(&conn, vault.path(), |_| {
+            logged.push(read_progress_log(&log_path).unwrap().len());
+        })
+        .unwrap();
+        assert_eq!(logged, vec![1, 2]);
+        assert_eq!(fs::read_dir(vault.path().join("notes")).unwrap().count(), 2);
+    }
+}
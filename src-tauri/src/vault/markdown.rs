@@ -21,12 +21,23 @@ pub enum MarkdownError {
 }
 
 /// Frontmatter metadata for a note
+///
+/// Field names follow Obsidian's conventions so metadata survives editing the
+/// vault there. `tags` and `aliases` are `None` in files written before they
+/// were exported, which leaves the note's existing tags and aliases alone on
+/// import; an empty list clears them.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NoteFrontmatter {
     pub id: String,
-    #[serde(with = "chrono::serde::ts_seconds")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "string_list::deserialize", skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "string_list::deserialize", skip_serializing_if = "Option::is_none")]
+    pub aliases: Option<Vec<String>>,
+    #[serde(with = "timestamp")]
     pub created: DateTime<Utc>,
-    #[serde(with = "chrono::serde::ts_seconds")]
+    #[serde(with = "timestamp")]
     pub updated: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub folder: Option<String>,
@@ -34,6 +45,71 @@ pub struct NoteFrontmatter {
     pub folder_id: Option<String>,
 }
 
+/// Timestamps are written as RFC 3339 and read as RFC 3339, a plain date, or
+/// Unix seconds (the format used by older vaults)
+mod timestamp {
+    use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_rfc3339_opts(SecondsFormat::Secs, true))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Seconds(i64),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Seconds(secs) => DateTime::from_timestamp(secs, 0)
+                .ok_or_else(|| D::Error::custom(format!("timestamp out of range: {}", secs))),
+            Raw::Text(text) => DateTime::parse_from_rfc3339(&text)
+                .map(|dt| dt.with_timezone(&Utc))
+                .ok()
+                .or_else(|| {
+                    NaiveDate::parse_from_str(&text, "%Y-%m-%d")
+                        .ok()
+                        .and_then(|date| date.and_hms_opt(0, 0, 0))
+                        .map(|dt| dt.and_utc())
+                })
+                .ok_or_else(|| D::Error::custom(format!("invalid date: {}", text))),
+        }
+    }
+}
+
+/// Reads a list of strings written either as a YAML list or as a single
+/// comma-separated string, as Obsidian accepts both
+mod string_list {
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<String>>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            One(String),
+            Many(Vec<String>),
+        }
+
+        let items = match Option::<Raw>::deserialize(deserializer)? {
+            None => return Ok(Some(Vec::new())),
+            Some(Raw::One(text)) => text.split(',').map(String::from).collect(),
+            Some(Raw::Many(items)) => items,
+        };
+        Ok(Some(
+            items
+                .iter()
+                .map(|item| item.trim().trim_start_matches('#').trim())
+                .filter(|item| !item.is_empty())
+                .map(String::from)
+                .collect(),
+        ))
+    }
+}
+
 /// A parsed markdown note with frontmatter and content
 #[derive(Debug, Clone)]
 pub struct ParsedNote {
@@ -42,6 +118,70 @@ pub struct ParsedNote {
     pub content: String,
 }
 
+/// Frontmatter fields read from markdown written by other apps
+///
+/// Everything is optional: Obsidian and Logseq files often have no
+/// frontmatter at all, and unknown keys are ignored.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExternalFrontmatter {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "string_list::deserialize")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "string_list::deserialize")]
+    pub aliases: Option<Vec<String>>,
+}
+
+/// A note read from markdown written by another app
+#[derive(Debug, Clone)]
+pub struct ExternalNote {
+    pub title: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub aliases: Vec<String>,
+}
+
+/// Parse markdown from another app, with or without frontmatter
+///
+/// The title comes from the frontmatter, falling back to `fallback_title`
+/// (the file name, as Obsidian uses). A leading `# Title` heading repeating
+/// the title is dropped from the content.
+pub fn parse_external_markdown(content: &str, fallback_title: &str) -> Result<ExternalNote, MarkdownError> {
+    let content = content.trim_start_matches('\u{feff}').trim();
+    
+    let (frontmatter, body) = match content.strip_prefix("---") {
+        Some(rest) => {
+            let end_pos = rest.find("\n---").ok_or(MarkdownError::InvalidFrontmatter)?;
+            let yaml = rest[..end_pos].trim();
+            let frontmatter = if yaml.is_empty() {
+                ExternalFrontmatter::default()
+            } else {
+                serde_yaml::from_str(yaml)?
+            };
+            (frontmatter, rest[end_pos + 4..].trim())
+        }
+        None => (ExternalFrontmatter::default(), content),
+    };
+    
+    let title = frontmatter.title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| fallback_title.to_string());
+    
+    let body = match body.split_once('\n') {
+        Some((first, rest)) if first.trim().strip_prefix("# ").is_some_and(|h| h.trim().eq_ignore_ascii_case(&title)) => rest.trim(),
+        None if body.trim().strip_prefix("# ").is_some_and(|h| h.trim().eq_ignore_ascii_case(&title)) => "",
+        _ => body,
+    };
+    
+    Ok(ExternalNote {
+        title,
+        content: body.to_string(),
+        tags: frontmatter.tags.unwrap_or_default(),
+        aliases: frontmatter.aliases.unwrap_or_default(),
+    })
+}
+
 /// Parse a markdown file with YAML frontmatter
 pub fn parse_markdown_file(path: &Path) -> Result<ParsedNote, MarkdownError> {
     let content = fs::read_to_string(path)?;
@@ -69,7 +209,12 @@ pub fn parse_markdown(content: &str) -> Result<ParsedNote, MarkdownError> {
     let frontmatter: NoteFrontmatter = serde_yaml::from_str(frontmatter_str)?;
     
     // Extract title from first heading or use filename
-    let (title, content) = extract_title_and_content(body);
+    let (heading_title, content) = extract_title_and_content(body);
+    
+    // A title in the frontmatter takes precedence over the heading
+    let title = frontmatter.title.clone()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or(heading_title);
     
     Ok(ParsedNote {
         frontmatter,
@@ -107,10 +252,13 @@ fn extract_title_and_content(body: &str) -> (String, String) {
 }
 
 /// Serialize a note to markdown with YAML frontmatter
+#[allow(clippy::too_many_arguments)]
 pub fn serialize_note(
     id: &str,
     title: &str,
     content: Option<&str>,
+    tags: &[String],
+    aliases: &[String],
     folder_id: Option<&str>,
     folder_name: Option<&str>,
     created_at: DateTime<Utc>,
@@ -118,6 +266,9 @@ pub fn serialize_note(
 ) -> Result<String, MarkdownError> {
     let frontmatter = NoteFrontmatter {
         id: id.to_string(),
+        title: Some(title.to_string()),
+        tags: Some(tags.to_vec()),
+        aliases: Some(aliases.to_vec()),
         created: created_at,
         updated: updated_at,
         folder: folder_name.map(String::from),
@@ -143,12 +294,14 @@ pub fn write_note_file(
     id: &str,
     title: &str,
     content: Option<&str>,
+    tags: &[String],
+    aliases: &[String],
     folder_id: Option<&str>,
     folder_name: Option<&str>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 ) -> Result<(), MarkdownError> {
-    let markdown = serialize_note(id, title, content, folder_id, folder_name, created_at, updated_at)?;
+    let markdown = serialize_note(id, title, content, tags, aliases, folder_id, folder_name, created_at, updated_at)?;
     
     // Ensure parent directory exists
     if let Some(parent) = path.parent() {
@@ -238,6 +391,8 @@ It has multiple paragraphs.
             "test-456",
             "Test Title",
             Some("Content here"),
+            &[],
+            &[],
             None,
             None,
             Utc::now(),
@@ -249,6 +404,77 @@ It has multiple paragraphs.
         assert!(result.contains("Content here"));
     }
     
+    #[test]
+    fn test_frontmatter_round_trip() {
+        let created = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let updated = DateTime::from_timestamp(1_700_086_400, 0).unwrap();
+        let result = serialize_note(
+            "note-1",
+            "Weekly Review",
+            Some("Body"),
+            &["work".to_string(), "project/alpha".to_string()],
+            &["Review".to_string()],
+            Some("folder-1"),
+            Some("Reviews"),
+            created,
+            updated,
+        ).unwrap();
+        
+        assert!(result.contains("title: Weekly Review"));
+        assert!(result.contains("created: 2023-11-14T22:13:20Z"));
+        
+        let parsed = parse_markdown(&result).unwrap();
+        assert_eq!(parsed.title, "Weekly Review");
+        assert_eq!(parsed.content, "Body");
+        assert_eq!(parsed.frontmatter.tags, Some(vec!["work".to_string(), "project/alpha".to_string()]));
+        assert_eq!(parsed.frontmatter.aliases, Some(vec!["Review".to_string()]));
+        assert_eq!(parsed.frontmatter.created, created);
+        assert_eq!(parsed.frontmatter.updated, updated);
+        assert_eq!(parsed.frontmatter.folder_id.as_deref(), Some("folder-1"));
+    }
+    
+    #[test]
+    fn test_frontmatter_without_tags() {
+        // Notes with no tags write empty lists, which clear tags on import
+        let result = serialize_note("note-2", "Plain", None, &[], &[], None, None, Utc::now(), Utc::now()).unwrap();
+        let parsed = parse_markdown(&result).unwrap();
+        assert_eq!(parsed.frontmatter.tags, Some(vec![]));
+        assert_eq!(parsed.frontmatter.aliases, Some(vec![]));
+        
+        // Older files have no tag fields at all
+        let parsed = parse_markdown("---\nid: old\ncreated: 1700000000\nupdated: 1700000000\n---\n\n# Old\n\nText").unwrap();
+        assert_eq!(parsed.frontmatter.tags, None);
+        assert_eq!(parsed.frontmatter.aliases, None);
+        assert_eq!(parsed.frontmatter.created.timestamp(), 1_700_000_000);
+    }
+    
+    #[test]
+    fn test_parse_obsidian_frontmatter() {
+        let content = "---\nid: obs-1\ntitle: Renamed in Obsidian\ntags: \"#reading, books\"\naliases:\ncreated: 2024-03-01\nupdated: 2024-03-02T09:30:00+02:00\n---\n\n# Old heading\n\nNotes";
+        let parsed = parse_markdown(content).unwrap();
+        assert_eq!(parsed.title, "Renamed in Obsidian");
+        assert_eq!(parsed.content, "Notes");
+        assert_eq!(parsed.frontmatter.tags, Some(vec!["reading".to_string(), "books".to_string()]));
+        assert_eq!(parsed.frontmatter.aliases, Some(vec![]));
+        assert_eq!(parsed.frontmatter.created.to_rfc3339(), "2024-03-01T00:00:00+00:00");
+        assert_eq!(parsed.frontmatter.updated.to_rfc3339(), "2024-03-02T07:30:00+00:00");
+    }
+    
+    #[test]
+    fn test_parse_external_markdown() {
+        let note = parse_external_markdown("Just text, no frontmatter", "Scratch").unwrap();
+        assert_eq!(note.title, "Scratch");
+        assert_eq!(note.content, "Just text, no frontmatter");
+        assert!(note.tags.is_empty());
+        
+        let note = parse_external_markdown("---\ntags: [a, b]\ncssclass: wide\n---\n# Scratch\n\nBody", "Scratch").unwrap();
+        assert_eq!(note.title, "Scratch");
+        assert_eq!(note.content, "Body");
+        assert_eq!(note.tags, vec!["a", "b"]);
+        
+        assert!(parse_external_markdown("---\ntags: [unclosed\n---\nBody", "Bad").is_err());
+    }
+    
     #[test]
     fn test_title_to_filename() {
         assert_eq!(title_to_filename("My Note"), "My Note.md");
@@ -2,10 +2,15 @@
 //!
 //! Handles vault path configuration, markdown file sync, and file watching.
 
+pub mod attachments;
+pub mod backup;
 pub mod board_sync;
 pub mod config;
+pub mod import;
+pub mod legacy_migration;
 pub mod markdown;
 pub mod sync;
+pub mod watcher;
 
 pub use board_sync::*;
 pub use config::*;
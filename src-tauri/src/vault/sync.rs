@@ -1,11 +1,16 @@
 //! Bidirectional sync between filesystem and SQLite
 //!
 //! Handles syncing notes between Markdown files and the SQLite database.
+//!
+//! Every sync records the note's content as the base for the next one (see
+//! `db::note_sync_state`). Bulk syncs compare both sides against that base and
+//! report notes edited on both sides as conflicts instead of overwriting either.
 
 use crate::db::connection::DbPool;
-use crate::db::{folders, notes};
+use crate::db::{folders, links, note_sync_state, notes, tags};
 use crate::models::{CreateNoteInput, Note, UpdateNoteInput};
 use crate::vault::{config, markdown};
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
@@ -23,6 +28,14 @@ pub enum SyncError {
     PoolError(#[from] r2d2::Error),
     #[error("Vault error: {0}")]
     VaultError(#[from] config::VaultError),
+    #[error("{} was edited on disk and in the app", .0.file_path)]
+    Conflict(Box<SyncConflict>),
+}
+
+impl From<rusqlite::Error> for SyncError {
+    fn from(e: rusqlite::Error) -> Self {
+        SyncError::DbError(e.to_string())
+    }
 }
 
 impl From<notes::NoteDbError> for SyncError {
@@ -37,6 +50,98 @@ impl From<folders::FolderDbError> for SyncError {
     }
 }
 
+impl From<tags::TagDbError> for SyncError {
+    fn from(e: tags::TagDbError) -> Self {
+        SyncError::DbError(e.to_string())
+    }
+}
+
+impl From<links::LinkDbError> for SyncError {
+    fn from(e: links::LinkDbError) -> Self {
+        SyncError::DbError(e.to_string())
+    }
+}
+
+impl From<note_sync_state::NoteSyncStateDbError> for SyncError {
+    fn from(e: note_sync_state::NoteSyncStateDbError) -> Self {
+        SyncError::DbError(e.to_string())
+    }
+}
+
+/// A note edited both on disk and in the database since their last sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConflict {
+    pub note_id: String,
+    pub note_title: String,
+    /// The markdown file holding the disk version
+    pub file_path: String,
+    pub db_content: String,
+    pub disk_content: String,
+    /// Content as of the last sync, if known
+    pub base_content: Option<String>,
+}
+
+/// How to resolve a sync conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// Overwrite the database with the file
+    KeepDisk,
+    /// Overwrite the file with the database
+    KeepDb,
+    /// Three-way merge both versions, marking overlapping edits
+    Merge,
+}
+
+impl ConflictResolution {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "keep_disk" => Some(Self::KeepDisk),
+            "keep_db" => Some(Self::KeepDb),
+            "merge" => Some(Self::Merge),
+            _ => None,
+        }
+    }
+}
+
+/// Result of a bulk sync
+#[derive(Debug, Clone, Default)]
+pub struct SyncOutcome {
+    /// Notes written or imported
+    pub synced: usize,
+    /// Notes left untouched because both sides changed
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// Which side of a note changed since the last sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SyncChange {
+    /// Both sides match
+    None,
+    DbOnly,
+    DiskOnly,
+    /// Both sides changed and differ
+    Both,
+    /// They differ but there's no base to tell which side changed
+    Unknown,
+}
+
+/// Compare database and disk content against the last synced base
+fn classify_change(base_hash: Option<&str>, db_content: &str, disk_content: &str) -> SyncChange {
+    let db_hash = note_sync_state::content_hash(db_content);
+    let disk_hash = note_sync_state::content_hash(disk_content);
+
+    if db_hash == disk_hash {
+        return SyncChange::None;
+    }
+    match base_hash {
+        None => SyncChange::Unknown,
+        Some(base) if base == db_hash => SyncChange::DiskOnly,
+        Some(base) if base == disk_hash => SyncChange::DbOnly,
+        Some(_) => SyncChange::Both,
+    }
+}
+
 /// Build the full folder path by traversing the parent hierarchy
 /// Returns a path like "Customers/New Folder" for nested folders
 fn build_folder_path(conn: &rusqlite::Connection, folder_id: &str) -> Result<String, SyncError> {
@@ -60,8 +165,52 @@ fn build_folder_path(conn: &rusqlite::Connection, folder_id: &str) -> Result<Str
 }
 
 /// Sync a note from database to filesystem
+///
+/// Like the bulk syncs, a file edited outside the app since the last sync isn't
+/// overwritten: if the note also changed in the database this returns
+/// `SyncError::Conflict`, otherwise the file is left for the watcher to import.
 pub fn sync_note_to_file(pool: &DbPool, note_id: &str) -> Result<PathBuf, SyncError> {
     let notes_dir = config::get_notes_dir()?;
+    sync_note_to_file_in(pool, &notes_dir, note_id)
+}
+
+/// Sync a note from database to a file under `notes_dir`, unless the file has unsynced edits
+pub(crate) fn sync_note_to_file_in(pool: &DbPool, notes_dir: &Path, note_id: &str) -> Result<PathBuf, SyncError> {
+    let (note, file_path, base) = {
+        let conn = pool.get()?;
+        let note = notes::get_note(&conn, note_id)?
+            .ok_or_else(|| SyncError::DbError(format!("Note not found: {}", note_id)))?;
+        let file_path = note_file_path(&conn, notes_dir, &note)?;
+        let base = note_sync_state::get_sync_state(&conn, note_id)?;
+        (note, file_path, base)
+    };
+    
+    if let Some(parsed) = parse_note_file(&file_path, &note.id) {
+        let db_content = note.content.clone().unwrap_or_default();
+        match classify_change(base.as_ref().map(|b| b.content_hash.as_str()), &db_content, &parsed.content) {
+            SyncChange::Both => {
+                return Err(SyncError::Conflict(Box::new(SyncConflict {
+                    note_id: note.id,
+                    note_title: note.title,
+                    file_path: file_path.to_string_lossy().to_string(),
+                    db_content,
+                    disk_content: parsed.content,
+                    base_content: base.map(|b| b.base_content),
+                })));
+            }
+            SyncChange::DiskOnly => {
+                log::info!("Skipping {:?}: changed on disk since the last sync", file_path);
+                return Ok(file_path);
+            }
+            SyncChange::None | SyncChange::DbOnly | SyncChange::Unknown => {}
+        }
+    }
+    
+    write_note_file_in(pool, notes_dir, note_id)
+}
+
+/// Write a note from the database to its file under `notes_dir`, overwriting whatever is there
+fn write_note_file_in(pool: &DbPool, notes_dir: &Path, note_id: &str) -> Result<PathBuf, SyncError> {
     let conn = pool.get()?;
     
     let note = notes::get_note(&conn, note_id)?
@@ -84,33 +233,94 @@ pub fn sync_note_to_file(pool: &DbPool, note_id: &str) -> Result<PathBuf, SyncEr
     
     // Determine file path using full folder path
     let file_path = markdown::get_note_path(
-        &notes_dir,
+        notes_dir,
         &note.title,
         folder_path.as_deref(),
     );
     
+    let (tag_names, aliases) = note_metadata(&conn, &note.id)?;
+    
     // Write the file
     markdown::write_note_file(
         &file_path,
         &note.id,
         &note.title,
         note.content.as_deref(),
+        &tag_names,
+        &aliases,
         note.folder_id.as_deref(),
         folder_name.as_deref(),
         note.created_at,
         note.updated_at,
     )?;
     
+    note_sync_state::record_sync_state(&conn, &note.id, note.content.as_deref().unwrap_or_default())?;
+    
     Ok(file_path)
 }
 
+/// Tag names and aliases of a note, as written to its frontmatter
+pub fn note_metadata(conn: &rusqlite::Connection, note_id: &str) -> Result<(Vec<String>, Vec<String>), SyncError> {
+    let tag_names = tags::get_note_tags(conn, note_id)?
+        .into_iter()
+        .map(|tag| tag.name)
+        .collect();
+    let aliases = links::get_note_aliases(conn, note_id)?;
+    Ok((tag_names, aliases))
+}
+
+/// Make a note's tags and aliases match its frontmatter
+///
+/// Fields missing from the frontmatter leave the note's existing values alone.
+fn apply_frontmatter_metadata(
+    conn: &rusqlite::Connection,
+    note_id: &str,
+    frontmatter: &markdown::NoteFrontmatter,
+) -> Result<(), SyncError> {
+    if let Some(ref wanted) = frontmatter.tags {
+        let current = tags::get_note_tags(conn, note_id)?;
+        for tag in &current {
+            if !wanted.iter().any(|name| name.eq_ignore_ascii_case(&tag.name)) {
+                tags::remove_tag_from_note(conn, note_id, &tag.id)?;
+            }
+        }
+        for name in wanted {
+            let tag = tags::find_or_create_tag(conn, name, None)?;
+            tags::add_tag_to_note(conn, note_id, &tag.id)?;
+        }
+    }
+    
+    if let Some(ref wanted) = frontmatter.aliases {
+        for alias in links::get_note_aliases(conn, note_id)? {
+            if !wanted.iter().any(|a| a.eq_ignore_ascii_case(&alias)) {
+                links::remove_note_alias(conn, note_id, &alias)?;
+            }
+        }
+        for alias in wanted {
+            match links::add_note_alias(conn, note_id, alias) {
+                Err(links::LinkDbError::AliasTaken(alias)) => {
+                    log::warn!("Skipping alias '{}' on note {}: already used by another note", alias, note_id);
+                }
+                result => result?,
+            }
+        }
+    }
+    
+    Ok(())
+}
+
 /// Sync a note from filesystem to database
 pub fn sync_file_to_note(pool: &DbPool, file_path: &Path) -> Result<Note, SyncError> {
-    let parsed = markdown::parse_markdown_file(file_path)?;
     let conn = pool.get()?;
+    import_note_file(&conn, file_path)
+}
+
+/// Import a note file using an existing connection (or transaction)
+fn import_note_file(conn: &rusqlite::Connection, file_path: &Path) -> Result<Note, SyncError> {
+    let parsed = markdown::parse_markdown_file(file_path)?;
     
     // Check if note exists by ID
-    if let Some(existing) = notes::get_note(&conn, &parsed.frontmatter.id)? {
+    if let Some(existing) = notes::get_note(conn, &parsed.frontmatter.id)? {
         // Update existing note
         // IMPORTANT: Set content_html to empty string to clear stale HTML.
         // The frontend will regenerate HTML from markdown content when content_html is empty.
@@ -118,10 +328,12 @@ pub fn sync_file_to_note(pool: &DbPool, file_path: &Path) -> Result<Note, SyncEr
             title: Some(parsed.title),
             content: Some(parsed.content),
             content_html: Some(String::new()), // Clear stale HTML so frontend uses markdown content
-            folder_id: parsed.frontmatter.folder_id,
+            folder_id: parsed.frontmatter.folder_id.clone(),
         };
         
-        let updated = notes::update_note(&conn, &existing.id, update)?;
+        let updated = notes::update_note_with_revision(conn, &existing.id, update)?;
+        apply_frontmatter_metadata(conn, &updated.id, &parsed.frontmatter)?;
+        note_sync_state::record_sync_state(conn, &updated.id, updated.content.as_deref().unwrap_or_default())?;
         Ok(updated)
     } else {
         // Create new note
@@ -129,53 +341,440 @@ pub fn sync_file_to_note(pool: &DbPool, file_path: &Path) -> Result<Note, SyncEr
             title: parsed.title,
             content: Some(parsed.content),
             content_html: None,
-            folder_id: parsed.frontmatter.folder_id,
+            folder_id: parsed.frontmatter.folder_id.clone(),
         };
         
         // Note: We need a way to set the ID, this is a limitation
         // For now, create with a new ID
-        let created = notes::create_note(&conn, input)?;
+        let created = notes::create_note(conn, input)?;
+        apply_frontmatter_metadata(conn, &created.id, &parsed.frontmatter)?;
+        note_sync_state::record_sync_state(conn, &created.id, created.content.as_deref().unwrap_or_default())?;
         Ok(created)
     }
 }
 
 /// Sync all notes from database to filesystem
-pub fn sync_all_to_files(pool: &DbPool) -> Result<usize, SyncError> {
-    let conn = pool.get()?;
-    let all_notes = notes::get_all_notes(&conn)?;
+///
+/// Files edited outside the app since the last sync are left alone; if the
+/// note also changed in the database, it is reported as a conflict.
+pub fn sync_all_to_files(pool: &DbPool) -> Result<SyncOutcome, SyncError> {
+    let notes_dir = config::get_notes_dir()?;
+    sync_all_to_files_in(pool, &notes_dir)
+}
+
+fn sync_all_to_files_in(pool: &DbPool, notes_dir: &Path) -> Result<SyncOutcome, SyncError> {
+    let all_notes = {
+        let conn = pool.get()?;
+        notes::get_all_notes(&conn)?
+    };
     
-    let mut count = 0;
+    let mut outcome = SyncOutcome::default();
     for note in all_notes {
-        if !note.is_deleted {
-            sync_note_to_file(pool, &note.id)?;
-            count += 1;
+        if note.is_deleted {
+            continue;
         }
+        
+        let file_path = {
+            let conn = pool.get()?;
+            note_file_path(&conn, notes_dir, &note)?
+        };
+        if let Some(parsed) = parse_note_file(&file_path, &note.id) {
+            let base = {
+                let conn = pool.get()?;
+                note_sync_state::get_sync_state(&conn, &note.id)?
+            };
+            let db_content = note.content.clone().unwrap_or_default();
+            match classify_change(base.as_ref().map(|b| b.content_hash.as_str()), &db_content, &parsed.content) {
+                SyncChange::Both => {
+                    outcome.conflicts.push(SyncConflict {
+                        note_id: note.id.clone(),
+                        note_title: note.title.clone(),
+                        file_path: file_path.to_string_lossy().to_string(),
+                        db_content,
+                        disk_content: parsed.content,
+                        base_content: base.map(|b| b.base_content),
+                    });
+                    continue;
+                }
+                SyncChange::DiskOnly => {
+                    log::info!("Skipping {:?}: changed on disk since the last sync", file_path);
+                    continue;
+                }
+                SyncChange::None | SyncChange::DbOnly | SyncChange::Unknown => {}
+            }
+        }
+        
+        write_note_file_in(pool, notes_dir, &note.id)?;
+        outcome.synced += 1;
     }
     
-    Ok(count)
+    Ok(outcome)
 }
 
 /// Scan filesystem for notes and sync to database
-pub fn scan_and_sync_from_files(pool: &DbPool) -> Result<usize, SyncError> {
+///
+/// Notes edited in the database since the last sync are left alone; if the
+/// file also changed, it is reported as a conflict.
+pub fn scan_and_sync_from_files(pool: &DbPool) -> Result<SyncOutcome, SyncError> {
     let notes_dir = config::get_notes_dir()?;
-    
+    scan_and_sync_from_files_in(pool, &notes_dir)
+}
+
+fn scan_and_sync_from_files_in(pool: &DbPool, notes_dir: &Path) -> Result<SyncOutcome, SyncError> {
+    let mut outcome = SyncOutcome::default();
     if !notes_dir.exists() {
-        return Ok(0);
+        return Ok(outcome);
     }
     
-    let mut count = 0;
-    let files = collect_markdown_files(&notes_dir)?;
+    let files = collect_markdown_files(notes_dir)?;
     
     for file_path in files {
+        let parsed = match markdown::parse_markdown_file(&file_path) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                log::warn!("Failed to sync file {:?}: {}", file_path, e);
+                continue;
+            }
+        };
+        
+        let (existing, base) = {
+            let conn = pool.get()?;
+            let existing = notes::get_note(&conn, &parsed.frontmatter.id)?;
+            let base = note_sync_state::get_sync_state(&conn, &parsed.frontmatter.id)?;
+            (existing, base)
+        };
+        
+        if let Some(note) = existing {
+            let db_content = note.content.clone().unwrap_or_default();
+            match classify_change(base.as_ref().map(|b| b.content_hash.as_str()), &db_content, &parsed.content) {
+                SyncChange::Both => {
+                    outcome.conflicts.push(SyncConflict {
+                        note_id: note.id,
+                        note_title: note.title,
+                        file_path: file_path.to_string_lossy().to_string(),
+                        db_content,
+                        disk_content: parsed.content,
+                        base_content: base.map(|b| b.base_content),
+                    });
+                    continue;
+                }
+                SyncChange::DbOnly => {
+                    log::info!("Skipping {:?}: note changed in the database since the last sync", file_path);
+                    continue;
+                }
+                SyncChange::None | SyncChange::DiskOnly | SyncChange::Unknown => {}
+            }
+        }
+        
         match sync_file_to_note(pool, &file_path) {
-            Ok(_) => count += 1,
+            Ok(_) => outcome.synced += 1,
             Err(e) => {
                 log::warn!("Failed to sync file {:?}: {}", file_path, e);
             }
         }
     }
     
-    Ok(count)
+    Ok(outcome)
+}
+
+/// Resolve a conflict reported by a bulk sync
+///
+/// Afterwards the file and the database hold the same content, which becomes
+/// the new sync base.
+pub fn resolve_sync_conflict(
+    pool: &DbPool,
+    note_id: &str,
+    resolution: ConflictResolution,
+) -> Result<Note, SyncError> {
+    let notes_dir = config::get_notes_dir()?;
+    resolve_sync_conflict_in(pool, &notes_dir, note_id, resolution)
+}
+
+fn resolve_sync_conflict_in(
+    pool: &DbPool,
+    notes_dir: &Path,
+    note_id: &str,
+    resolution: ConflictResolution,
+) -> Result<Note, SyncError> {
+    let (note, base) = {
+        let conn = pool.get()?;
+        let note = notes::get_note(&conn, note_id)?
+            .ok_or_else(|| SyncError::DbError(format!("Note not found: {}", note_id)))?;
+        (note, note_sync_state::get_sync_state(&conn, note_id)?)
+    };
+    
+    let file_path = find_note_file(pool, notes_dir, &note)?
+        .ok_or_else(|| SyncError::DbError(format!("No file found for note: {}", note_id)))?;
+    
+    match resolution {
+        ConflictResolution::KeepDisk => sync_file_to_note(pool, &file_path),
+        ConflictResolution::KeepDb => {
+            let written = write_note_file_in(pool, notes_dir, note_id)?;
+            if written != file_path {
+                fs::remove_file(&file_path)?;
+            }
+            Ok(note)
+        }
+        ConflictResolution::Merge => {
+            let parsed = markdown::parse_markdown_file(&file_path)?;
+            let base_content = base.map(|b| b.base_content).unwrap_or_default();
+            let merged = merge_three_way(
+                &base_content,
+                note.content.as_deref().unwrap_or_default(),
+                &parsed.content,
+            );
+            
+            let updated = {
+                let conn = pool.get()?;
+                let update = UpdateNoteInput {
+                    title: None,
+                    content: Some(merged),
+                    content_html: Some(String::new()), // Regenerated from markdown by the frontend
+                    folder_id: None,
+                };
+                notes::update_note_with_revision(&conn, note_id, update)?
+            };
+            
+            let written = write_note_file_in(pool, notes_dir, note_id)?;
+            if written != file_path {
+                fs::remove_file(&file_path)?;
+            }
+            Ok(updated)
+        }
+    }
+}
+
+/// Result of importing a batch of changed files
+#[derive(Debug, Clone, Default)]
+pub struct FileBatchOutcome {
+    /// Notes created or updated from their files
+    pub updated_note_ids: Vec<String>,
+    /// Notes whose files were deleted
+    pub removed_note_ids: Vec<String>,
+    /// Notes left untouched because both sides changed
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// Import a batch of changed markdown files in a single transaction
+///
+/// Each path is handled by its state at the time of the call, so a file
+/// deleted and recreated within the batch is imported, and a rename (one path
+/// removed, another added for the same note) doesn't delete the note. Files
+/// matching the database, like those the app just wrote, are skipped.
+pub fn sync_changed_files(
+    pool: &DbPool,
+    notes_dir: &Path,
+    paths: &[PathBuf],
+) -> Result<FileBatchOutcome, SyncError> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let mut outcome = FileBatchOutcome::default();
+    let mut removed_paths = Vec::new();
+    
+    for path in paths {
+        if path.extension().is_none_or(|ext| ext != "md") {
+            continue;
+        }
+        if !path.exists() {
+            removed_paths.push(path);
+            continue;
+        }
+        
+        let parsed = match markdown::parse_markdown_file(path) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                log::warn!("Failed to sync file {:?}: {}", path, e);
+                continue;
+            }
+        };
+        
+        if let Some(note) = notes::get_note(&tx, &parsed.frontmatter.id)? {
+            let base = note_sync_state::get_sync_state(&tx, &note.id)?;
+            let db_content = note.content.clone().unwrap_or_default();
+            match classify_change(base.as_ref().map(|b| b.content_hash.as_str()), &db_content, &parsed.content) {
+                SyncChange::None if note.title == parsed.title && !note.is_deleted => {
+                    apply_frontmatter_metadata(&tx, &note.id, &parsed.frontmatter)?;
+                    continue;
+                }
+                SyncChange::DbOnly => continue,
+                SyncChange::Both => {
+                    outcome.conflicts.push(SyncConflict {
+                        note_id: note.id,
+                        note_title: note.title,
+                        file_path: path.to_string_lossy().to_string(),
+                        db_content,
+                        disk_content: parsed.content,
+                        base_content: base.map(|b| b.base_content),
+                    });
+                    continue;
+                }
+                SyncChange::None | SyncChange::DiskOnly | SyncChange::Unknown => {}
+            }
+            if note.is_deleted {
+                tx.execute("UPDATE notes SET is_deleted = FALSE WHERE id = ?1", [&note.id])?;
+            }
+        }
+        
+        match import_note_file(&tx, path) {
+            Ok(note) => outcome.updated_note_ids.push(note.id),
+            Err(e) => log::warn!("Failed to sync file {:?}: {}", path, e),
+        }
+    }
+    
+    if !removed_paths.is_empty() {
+        for note in notes::get_all_notes(&tx)? {
+            if note.is_deleted || outcome.updated_note_ids.contains(&note.id) {
+                continue;
+            }
+            let expected = note_file_path(&tx, notes_dir, &note)?;
+            if removed_paths.contains(&&expected) {
+                notes::delete_note(&tx, &note.id)?;
+                outcome.removed_note_ids.push(note.id);
+            }
+        }
+    }
+    
+    tx.commit()?;
+    Ok(outcome)
+}
+
+/// Rewrite a moved note's file at its new location
+///
+/// `old` is the note as it was before the move. Call this once the move is
+/// committed: the old file is only removed after the new one is written.
+pub fn move_note_file(pool: &DbPool, old: &Note) -> Result<PathBuf, SyncError> {
+    let notes_dir = config::get_notes_dir()?;
+    let old_path = {
+        let conn = pool.get()?;
+        note_file_path(&conn, &notes_dir, old)?
+    };
+    let written = write_note_file_in(pool, &notes_dir, &old.id)?;
+    if written != old_path && old_path.exists() {
+        fs::remove_file(&old_path)?;
+    }
+    Ok(written)
+}
+
+/// Path a note's file is written to, from its title and folder
+fn note_file_path(conn: &rusqlite::Connection, notes_dir: &Path, note: &Note) -> Result<PathBuf, SyncError> {
+    let folder_path = match note.folder_id {
+        Some(ref folder_id) => Some(build_folder_path(conn, folder_id)?),
+        None => None,
+    };
+    Ok(markdown::get_note_path(notes_dir, &note.title, folder_path.as_deref()))
+}
+
+/// Parse a note file if it exists and belongs to the given note
+fn parse_note_file(path: &Path, note_id: &str) -> Option<markdown::ParsedNote> {
+    if !path.exists() {
+        return None;
+    }
+    markdown::parse_markdown_file(path)
+        .ok()
+        .filter(|parsed| parsed.frontmatter.id == note_id)
+}
+
+/// Find a note's file, falling back to a scan when it was renamed on disk
+fn find_note_file(pool: &DbPool, notes_dir: &Path, note: &Note) -> Result<Option<PathBuf>, SyncError> {
+    let expected = {
+        let conn = pool.get()?;
+        note_file_path(&conn, notes_dir, note)?
+    };
+    if parse_note_file(&expected, &note.id).is_some() {
+        return Ok(Some(expected));
+    }
+    if !notes_dir.exists() {
+        return Ok(None);
+    }
+    Ok(collect_markdown_files(notes_dir)?
+        .into_iter()
+        .find(|path| parse_note_file(path, &note.id).is_some()))
+}
+
+/// Line-based three-way merge of two edited versions of `base`
+///
+/// Edits made on only one side are applied. Where both sides changed the
+/// same lines differently, both versions are kept between conflict markers.
+pub fn merge_three_way(base: &str, db: &str, disk: &str) -> String {
+    let base_lines: Vec<&str> = base.lines().collect();
+    let db_lines: Vec<&str> = db.lines().collect();
+    let disk_lines: Vec<&str> = disk.lines().collect();
+    
+    // For each base line, its matching line in each version (if kept)
+    let db_match = match_lines(&base_lines, &db_lines);
+    let disk_match = match_lines(&base_lines, &disk_lines);
+    
+    let mut merged: Vec<&str> = Vec::new();
+    let (mut b, mut d, mut k) = (0, 0, 0);
+    
+    loop {
+        // Next base line kept unchanged by both sides, or the end of all three
+        let anchor = (b..base_lines.len())
+            .find(|&i| db_match[i].is_some_and(|j| j >= d) && disk_match[i].is_some_and(|j| j >= k));
+        let (b_end, d_end, k_end) = match anchor {
+            Some(i) => (i, db_match[i].unwrap(), disk_match[i].unwrap()),
+            None => (base_lines.len(), db_lines.len(), disk_lines.len()),
+        };
+        
+        let base_chunk = &base_lines[b..b_end];
+        let db_chunk = &db_lines[d..d_end];
+        let disk_chunk = &disk_lines[k..k_end];
+        
+        if db_chunk == disk_chunk || disk_chunk == base_chunk {
+            merged.extend_from_slice(db_chunk);
+        } else if db_chunk == base_chunk {
+            merged.extend_from_slice(disk_chunk);
+        } else {
+            merged.push("<<<<<<< Database");
+            merged.extend_from_slice(db_chunk);
+            merged.push("=======");
+            merged.extend_from_slice(disk_chunk);
+            merged.push(">>>>>>> Disk");
+        }
+        
+        match anchor {
+            Some(i) => {
+                merged.push(base_lines[i]);
+                b = i + 1;
+                d = d_end + 1;
+                k = k_end + 1;
+            }
+            None => break,
+        }
+    }
+    
+    merged.join("\n")
+}
+
+/// Match lines of `base` to lines of `other` along a longest common subsequence
+fn match_lines(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let (n, m) = (base.len(), other.len());
+    // lcs[i][j] = LCS length of base[i..] and other[j..]
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if base[i] == other[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    
+    let mut matches = vec![None; n];
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if base[i] == other[j] {
+            matches[i] = Some(j);
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    matches
 }
 
 /// Collect all markdown files in a directory recursively
@@ -226,3 +825,265 @@ pub fn delete_note_file(pool: &DbPool, note_id: &str) -> Result<bool, SyncError>
     
     Ok(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::init_test_pool;
+
+    /// A note synced to a temporary vault, so file and database start out matching
+    fn synced_note(pool: &DbPool, notes_dir: &Path, content: &str) -> (Note, PathBuf) {
+        let note = {
+            let conn = pool.get().unwrap();
+            notes::create_note(
+                &conn,
+                CreateNoteInput {
+                    title: "Plan".to_string(),
+                    content: Some(content.to_string()),
+                    content_html: None,
+                    folder_id: None,
+                },
+            )
+            .unwrap()
+        };
+        let path = sync_note_to_file_in(pool, notes_dir, &note.id).unwrap();
+        (note, path)
+    }
+
+    /// Edit a note's body in its file, as an external editor would
+    fn edit_file(path: &Path, content: &str) {
+        let parsed = markdown::parse_markdown_file(path).unwrap();
+        let markdown = markdown::serialize_note(
+            &parsed.frontmatter.id,
+            &parsed.title,
+            Some(content),
+            parsed.frontmatter.tags.as_deref().unwrap_or_default(),
+            parsed.frontmatter.aliases.as_deref().unwrap_or_default(),
+            None,
+            None,
+            parsed.frontmatter.created,
+            parsed.frontmatter.updated,
+        )
+        .unwrap();
+        fs::write(path, markdown).unwrap();
+    }
+
+    fn edit_db(pool: &DbPool, note_id: &str, content: &str) {
+        let conn = pool.get().unwrap();
+        let update = UpdateNoteInput {
+            title: None,
+            content: Some(content.to_string()),
+            content_html: None,
+            folder_id: None,
+        };
+        notes::update_note(&conn, note_id, update).unwrap();
+    }
+
+    fn db_content(pool: &DbPool, note_id: &str) -> String {
+        let conn = pool.get().unwrap();
+        notes::get_note(&conn, note_id).unwrap().unwrap().content.unwrap()
+    }
+
+    #[test]
+    fn test_concurrent_edits_are_reported_not_clobbered() {
+        let pool = init_test_pool().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let (note, path) = synced_note(&pool, dir.path(), "Goals\nShip v1");
+
+        edit_db(&pool, &note.id, "Goals\nShip v1 in May");
+        edit_file(&path, "Goals\nShip v1\nHire a designer");
+
+        // Neither direction overwrites the other side
+        let outcome = sync_all_to_files_in(&pool, dir.path()).unwrap();
+        assert_eq!(outcome.synced, 0);
+        assert_eq!(outcome.conflicts.len(), 1);
+        let conflict = &outcome.conflicts[0];
+        assert_eq!(conflict.note_id, note.id);
+        assert_eq!(conflict.db_content, "Goals\nShip v1 in May");
+        assert_eq!(conflict.disk_content, "Goals\nShip v1\nHire a designer");
+        assert_eq!(conflict.base_content.as_deref(), Some("Goals\nShip v1"));
+        assert_eq!(markdown::parse_markdown_file(&path).unwrap().content, "Goals\nShip v1\nHire a designer");
+
+        let outcome = scan_and_sync_from_files_in(&pool, dir.path()).unwrap();
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(db_content(&pool, &note.id), "Goals\nShip v1 in May");
+    }
+
+    #[test]
+    fn test_one_sided_edits_are_not_overwritten() {
+        let pool = init_test_pool().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let (note, path) = synced_note(&pool, dir.path(), "Draft");
+
+        // A newer database edit isn't replaced by the stale file...
+        edit_db(&pool, &note.id, "Draft, revised in app");
+        let outcome = scan_and_sync_from_files_in(&pool, dir.path()).unwrap();
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(db_content(&pool, &note.id), "Draft, revised in app");
+
+        // ...and is written out when syncing the other way
+        let outcome = sync_all_to_files_in(&pool, dir.path()).unwrap();
+        assert_eq!(outcome.synced, 1);
+        assert_eq!(markdown::parse_markdown_file(&path).unwrap().content, "Draft, revised in app");
+
+        // An external edit is imported, and not clobbered by a push first
+        edit_file(&path, "Draft, revised in editor");
+        let outcome = sync_all_to_files_in(&pool, dir.path()).unwrap();
+        assert_eq!(outcome.synced, 0);
+        assert!(outcome.conflicts.is_empty());
+        let outcome = scan_and_sync_from_files_in(&pool, dir.path()).unwrap();
+        assert_eq!(outcome.synced, 1);
+        assert_eq!(db_content(&pool, &note.id), "Draft, revised in editor");
+    }
+
+    #[test]
+    fn test_single_note_sync_reports_conflict() {
+        let pool = init_test_pool().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let (note, path) = synced_note(&pool, dir.path(), "Agenda");
+
+        // Saved in the app while an external edit is still unsynced
+        edit_file(&path, "Agenda\nAdded in editor");
+        edit_db(&pool, &note.id, "Agenda, typed in app");
+        match sync_note_to_file_in(&pool, dir.path(), &note.id) {
+            Err(SyncError::Conflict(conflict)) => {
+                assert_eq!(conflict.note_id, note.id);
+                assert_eq!(conflict.db_content, "Agenda, typed in app");
+                assert_eq!(conflict.disk_content, "Agenda\nAdded in editor");
+            }
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+        assert_eq!(markdown::parse_markdown_file(&path).unwrap().content, "Agenda\nAdded in editor");
+
+        // Once resolved, later saves write through again
+        resolve_sync_conflict_in(&pool, dir.path(), &note.id, ConflictResolution::KeepDb).unwrap();
+        edit_db(&pool, &note.id, "Agenda, final");
+        sync_note_to_file_in(&pool, dir.path(), &note.id).unwrap();
+        assert_eq!(markdown::parse_markdown_file(&path).unwrap().content, "Agenda, final");
+    }
+
+    #[test]
+    fn test_resolve_conflict() {
+        let pool = init_test_pool().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let (note, path) = synced_note(&pool, dir.path(), "Intro\nMiddle\nEnd");
+
+        edit_db(&pool, &note.id, "Intro, edited in app\nMiddle\nEnd");
+        edit_file(&path, "Intro\nMiddle\nEnd, edited on disk");
+        let merged = resolve_sync_conflict_in(&pool, dir.path(), &note.id, ConflictResolution::Merge).unwrap();
+        assert_eq!(merged.content.as_deref(), Some("Intro, edited in app\nMiddle\nEnd, edited on disk"));
+        assert_eq!(markdown::parse_markdown_file(&path).unwrap().content, "Intro, edited in app\nMiddle\nEnd, edited on disk");
+        assert!(sync_all_to_files_in(&pool, dir.path()).unwrap().conflicts.is_empty());
+
+        edit_db(&pool, &note.id, "From app");
+        edit_file(&path, "From disk");
+        resolve_sync_conflict_in(&pool, dir.path(), &note.id, ConflictResolution::KeepDisk).unwrap();
+        assert_eq!(db_content(&pool, &note.id), "From disk");
+
+        edit_db(&pool, &note.id, "From app again");
+        edit_file(&path, "From disk again");
+        resolve_sync_conflict_in(&pool, dir.path(), &note.id, ConflictResolution::KeepDb).unwrap();
+        assert_eq!(markdown::parse_markdown_file(&path).unwrap().content, "From app again");
+        assert_eq!(ConflictResolution::from_str("keep_db"), Some(ConflictResolution::KeepDb));
+    }
+
+    #[test]
+    fn test_tags_and_aliases_round_trip_through_frontmatter() {
+        let pool = init_test_pool().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let (note, _) = synced_note(&pool, dir.path(), "Body");
+        {
+            let conn = pool.get().unwrap();
+            let tag = tags::find_or_create_tag(&conn, "work", None).unwrap();
+            tags::add_tag_to_note(&conn, &note.id, &tag.id).unwrap();
+            links::add_note_alias(&conn, &note.id, "Roadmap").unwrap();
+        }
+        let path = sync_note_to_file_in(&pool, dir.path(), &note.id).unwrap();
+
+        // Retag the file as an external editor would
+        let text = fs::read_to_string(&path).unwrap()
+            .replace("tags:\n- work", "tags:\n- personal\n- '#reading'");
+        fs::write(&path, text).unwrap();
+        sync_file_to_note(&pool, &path).unwrap();
+
+        let conn = pool.get().unwrap();
+        let (tag_names, aliases) = note_metadata(&conn, &note.id).unwrap();
+        assert_eq!(tag_names, vec!["personal", "reading"]);
+        assert_eq!(aliases, vec!["Roadmap"]);
+    }
+
+    #[test]
+    fn test_sync_changed_files() {
+        let pool = init_test_pool().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let (edited, edited_path) = synced_note(&pool, dir.path(), "Old");
+        let (removed, removed_path) = {
+            let conn = pool.get().unwrap();
+            let note = notes::create_note(
+                &conn,
+                CreateNoteInput {
+                    title: "Scratch".to_string(),
+                    content: Some("Temp".to_string()),
+                    content_html: None,
+                    folder_id: None,
+                },
+            )
+            .unwrap();
+            drop(conn);
+            let path = sync_note_to_file_in(&pool, dir.path(), &note.id).unwrap();
+            (note, path)
+        };
+
+        // Unchanged files (like the app's own writes) are skipped
+        let outcome = sync_changed_files(&pool, dir.path(), std::slice::from_ref(&edited_path)).unwrap();
+        assert!(outcome.updated_note_ids.is_empty());
+
+        // A file deleted then recreated is imported, not removed
+        edit_file(&edited_path, "New");
+        let text = fs::read_to_string(&edited_path).unwrap();
+        fs::remove_file(&edited_path).unwrap();
+        fs::write(&edited_path, text).unwrap();
+        fs::remove_file(&removed_path).unwrap();
+
+        let outcome = sync_changed_files(&pool, dir.path(), &[edited_path, removed_path]).unwrap();
+        assert_eq!(outcome.updated_note_ids, vec![edited.id.clone()]);
+        assert_eq!(outcome.removed_note_ids, vec![removed.id.clone()]);
+        assert_eq!(db_content(&pool, &edited.id), "New");
+        let conn = pool.get().unwrap();
+        assert!(notes::get_note(&conn, &removed.id).unwrap().unwrap().is_deleted);
+    }
+
+    #[test]
+    fn test_renamed_file_keeps_note() {
+        let pool = init_test_pool().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let (note, old_path) = synced_note(&pool, dir.path(), "Body");
+
+        let new_path = dir.path().join("Renamed.md");
+        fs::rename(&old_path, &new_path).unwrap();
+        let text = fs::read_to_string(&new_path).unwrap().replace("title: Plan", "title: Renamed");
+        fs::write(&new_path, text).unwrap();
+
+        let outcome = sync_changed_files(&pool, dir.path(), &[old_path, new_path]).unwrap();
+        assert_eq!(outcome.updated_note_ids, vec![note.id.clone()]);
+        assert!(outcome.removed_note_ids.is_empty());
+        let conn = pool.get().unwrap();
+        let renamed = notes::get_note(&conn, &note.id).unwrap().unwrap();
+        assert_eq!(renamed.title, "Renamed");
+        assert!(!renamed.is_deleted);
+    }
+
+    #[test]
+    fn test_merge_three_way_marks_overlapping_edits() {
+        let merged = merge_three_way("a\nb\nc", "a\nB from db\nc", "a\nB from disk\nc");
+        assert_eq!(
+            merged,
+            "a\n<<<<<<< Database\nB from db\n=======\nB from disk\n>>>>>>> Disk\nc"
+        );
+
+        // Identical edits on both sides aren't a conflict
+        assert_eq!(merge_three_way("a\nb", "a\nx", "a\nx"), "a\nx");
+        // Insertions and deletions on different lines combine
+        assert_eq!(merge_three_way("a\nb\nc", "a\nc", "a\nb\nc\nd"), "a\nc\nd");
+    }
+}
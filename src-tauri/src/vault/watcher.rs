@@ -0,0 +1,259 @@
+//! File watching for the vault's notes directory
+//!
+//! External editors often write a file several times in quick succession
+//! (temp file, rename, metadata touch). Events are coalesced by a trailing
+//! debounce: once the directory has been quiet for the debounce window, every
+//! path touched during the burst is re-imported as one batch.
+
+use crate::db::connection::DbPool;
+use crate::db::notes;
+use crate::search::SearchIndex;
+use crate::vault::sync::{self, FileBatchOutcome};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Quiet period after the last event before a batch is synced
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Coalesces file events into batches of distinct paths
+///
+/// Time is passed in by the caller so the batching logic can be tested
+/// without sleeping.
+#[derive(Debug)]
+pub struct Debouncer {
+    window: Duration,
+    pending: BTreeSet<PathBuf>,
+    last_event: Option<Instant>,
+}
+
+impl Debouncer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: BTreeSet::new(),
+            last_event: None,
+        }
+    }
+
+    /// Record an event for `path` at `now`, extending the quiet period
+    pub fn push(&mut self, path: PathBuf, now: Instant) {
+        self.pending.insert(path);
+        self.last_event = Some(now);
+    }
+
+    /// When the pending batch becomes due, if there is one
+    pub fn deadline(&self) -> Option<Instant> {
+        self.last_event.map(|last| last + self.window)
+    }
+
+    /// Take the pending batch if the window has passed since the last event
+    pub fn take_ready(&mut self, now: Instant) -> Option<Vec<PathBuf>> {
+        let deadline = self.deadline()?;
+        if now < deadline {
+            return None;
+        }
+        self.last_event = None;
+        Some(std::mem::take(&mut self.pending).into_iter().collect())
+    }
+}
+
+/// A running watcher; dropping it stops watching
+///
+/// Drop waits for the sync thread to exit, so its database pool is released
+/// before the caller replaces or reopens the database.
+pub struct VaultWatcher {
+    watcher: Option<RecommendedWatcher>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Drop for VaultWatcher {
+    fn drop(&mut self) {
+        // Dropping the notify watcher disconnects the channel, ending the thread's loop
+        self.watcher.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Watch `notes_dir` and sync changed files into the database
+///
+/// `on_batch` is called after each batch is imported, so the caller can tell
+/// the frontend which notes changed.
+pub fn start_watcher<F>(
+    pool: DbPool,
+    notes_dir: &Path,
+    window: Duration,
+    on_batch: F,
+) -> Result<VaultWatcher, notify::Error>
+where
+    F: Fn(FileBatchOutcome) + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(notes_dir, RecursiveMode::Recursive)?;
+
+    let notes_dir = notes_dir.to_path_buf();
+    let thread = thread::spawn(move || {
+        let mut debouncer = Debouncer::new(window);
+        loop {
+            let received = match debouncer.deadline() {
+                Some(deadline) => rx.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                None => rx.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+            };
+
+            match received {
+                Ok(Ok(event)) => {
+                    if event.kind.is_access() {
+                        continue;
+                    }
+                    let now = Instant::now();
+                    for path in event.paths {
+                        debouncer.push(path, now);
+                    }
+                }
+                Ok(Err(e)) => log::warn!("Vault watcher error: {}", e),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                // The watcher was dropped
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+
+            if let Some(paths) = debouncer.take_ready(Instant::now()) {
+                match sync::sync_changed_files(&pool, &notes_dir, &paths) {
+                    Ok(outcome) => {
+                        log::info!(
+                            "Vault watcher synced {} changed files: {} updated, {} removed, {} conflicts",
+                            paths.len(),
+                            outcome.updated_note_ids.len(),
+                            outcome.removed_note_ids.len(),
+                            outcome.conflicts.len()
+                        );
+                        on_batch(outcome);
+                    }
+                    Err(e) => log::warn!("Vault watcher failed to sync changes: {}", e),
+                }
+            }
+        }
+    });
+
+    Ok(VaultWatcher {
+        watcher: Some(watcher),
+        thread: Some(thread),
+    })
+}
+
+/// Bring the search index in line with a batch synced from disk
+///
+/// Notes edited externally are re-indexed from the database, so the index
+/// holds what the import produced; removed notes are dropped from it.
+pub fn update_search_index(pool: &DbPool, index: &SearchIndex, outcome: &FileBatchOutcome) {
+    if !outcome.updated_note_ids.is_empty() {
+        match pool.get() {
+            Ok(conn) => {
+                for note_id in &outcome.updated_note_ids {
+                    let result = match notes::get_note(&conn, note_id) {
+                        Ok(Some(note)) if !note.is_deleted => {
+                            index.update_note(&note.id, &note.title, note.content.as_deref())
+                        }
+                        Ok(_) => index.delete_document(note_id),
+                        Err(e) => {
+                            log::warn!("Failed to load note {} for the search index: {}", note_id, e);
+                            continue;
+                        }
+                    };
+                    if let Err(e) = result {
+                        log::warn!("Failed to update note in search index: {}", e);
+                    }
+                }
+            }
+            Err(e) => log::warn!("Failed to update search index after vault sync: {}", e),
+        }
+    }
+    for note_id in &outcome.removed_note_ids {
+        if let Err(e) = index.delete_document(note_id) {
+            log::warn!("Failed to delete note from search index: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_collapses_into_one_batch() {
+        let mut debouncer = Debouncer::new(Duration::from_millis(500));
+        let start = Instant::now();
+
+        // Two files written repeatedly, each event within the window of the last
+        for i in 0..10u64 {
+            let now = start + Duration::from_millis(i * 100);
+            debouncer.push(PathBuf::from("notes/a.md"), now);
+            debouncer.push(PathBuf::from("notes/b.md"), now);
+            assert!(debouncer.take_ready(now).is_none());
+        }
+
+        let last = start + Duration::from_millis(900);
+        assert!(debouncer.take_ready(last + Duration::from_millis(499)).is_none());
+        let batch = debouncer.take_ready(last + Duration::from_millis(500)).unwrap();
+        assert_eq!(batch, vec![PathBuf::from("notes/a.md"), PathBuf::from("notes/b.md")]);
+
+        // Nothing left once the batch is taken
+        assert!(debouncer.deadline().is_none());
+        assert!(debouncer.take_ready(last + Duration::from_secs(5)).is_none());
+    }
+
+    #[test]
+    fn test_events_after_quiet_period_start_new_batch() {
+        let mut debouncer = Debouncer::new(Duration::from_millis(500));
+        let start = Instant::now();
+
+        debouncer.push(PathBuf::from("a.md"), start);
+        assert_eq!(debouncer.take_ready(start + Duration::from_secs(1)).unwrap().len(), 1);
+
+        debouncer.push(PathBuf::from("b.md"), start + Duration::from_secs(2));
+        let batch = debouncer.take_ready(start + Duration::from_secs(3)).unwrap();
+        assert_eq!(batch, vec![PathBuf::from("b.md")]);
+    }
+
+    #[test]
+    fn test_external_edits_reach_the_search_index() {
+        use crate::db::connection::init_test_pool;
+        use crate::models::CreateNoteInput;
+
+        let pool = init_test_pool().unwrap();
+        let notes_dir = tempfile::tempdir().unwrap();
+        let index_dir = tempfile::tempdir().unwrap();
+        let index = SearchIndex::new(index_dir.path().to_path_buf()).unwrap();
+
+        let note = notes::create_note(
+            &pool.get().unwrap(),
+            CreateNoteInput {
+                title: "Field notes".to_string(),
+                content: Some("Spotted a heron".to_string()),
+                content_html: None,
+                folder_id: None,
+            },
+        )
+        .unwrap();
+        index.add_note(&note.id, &note.title, note.content.as_deref()).unwrap();
+        let path = sync::sync_note_to_file_in(&pool, notes_dir.path(), &note.id).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, text.replace("Spotted a heron", "Spotted a kingfisher")).unwrap();
+        let outcome = sync::sync_changed_files(&pool, notes_dir.path(), std::slice::from_ref(&path)).unwrap();
+        update_search_index(&pool, &index, &outcome);
+
+        assert_eq!(index.search("kingfisher", 10).unwrap().len(), 1);
+        assert!(index.search("heron", 10).unwrap().is_empty());
+
+        std::fs::remove_file(&path).unwrap();
+        let outcome = sync::sync_changed_files(&pool, notes_dir.path(), &[path]).unwrap();
+        update_search_index(&pool, &index, &outcome);
+        assert!(index.search("kingfisher", 10).unwrap().is_empty());
+    }
+}
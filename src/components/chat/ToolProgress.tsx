@@ -76,11 +76,21 @@ const TOOL_INFO: Record<
     label: "Calendar Events",
     verb: "Getting calendar events",
   },
+  get_upcoming_events: {
+    icon: Calendar,
+    label: "Upcoming Events",
+    verb: "Checking your schedule",
+  },
   create_calendar_event: {
     icon: Calendar,
     label: "Create Event",
     verb: "Creating calendar event",
   },
+  create_meeting_note: {
+    icon: Calendar,
+    label: "Meeting Note",
+    verb: "Creating meeting note",
+  },
   get_daily_note: {
     icon: Clock,
     label: "Daily Note",
@@ -101,6 +111,16 @@ const TOOL_INFO: Record<
     label: "Folder Notes",
     verb: "Getting notes in folder",
   },
+  create_folder: {
+    icon: FolderOpen,
+    label: "Create Folder",
+    verb: "Creating folder",
+  },
+  move_note_to_folder: {
+    icon: FolderOpen,
+    label: "Move Note",
+    verb: "Moving note",
+  },
   get_note_tags: {
     icon: Tag,
     label: "Note Tags",
@@ -111,6 +131,16 @@ const TOOL_INFO: Record<
     label: "Search by Tag",
     verb: "Searching by tag",
   },
+  add_tag_to_note: {
+    icon: Tag,
+    label: "Add Tag",
+    verb: "Tagging note",
+  },
+  remove_tag_from_note: {
+    icon: Tag,
+    label: "Remove Tag",
+    verb: "Removing tag",
+  },
   get_related_notes: {
     icon: Link2,
     label: "Related Notes",
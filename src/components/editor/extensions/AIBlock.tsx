@@ -293,7 +293,13 @@ function AIBlockComponent({ node, updateAttributes, deleteNode, editor }: NodeVi
             const htmlContent = await marked.parse(result.content);
             deleteNode();
             if (editor) {
-              editor.chain().focus().insertContent(htmlContent).run();
+              if (result.writeMode === "append_to_note") {
+                editor.chain().focus("end").insertContent(htmlContent).run();
+              } else {
+                // The block is opened from a slash command at a collapsed cursor, so
+                // there's no selected text to replace: both other modes insert in its place
+                editor.chain().focus().insertContent(htmlContent).run();
+              }
             }
           } else {
             // No content returned - show error
@@ -61,6 +61,13 @@ function getColorStyles(color: string | null): { bg: string; text: string; borde
   if (color && colorMap[color]) {
     return colorMap[color];
   }
+  if (color && /^#([0-9a-f]{3}|[0-9a-f]{6})$/i.test(color)) {
+    return {
+      bg: `color-mix(in srgb, ${color} 15%, transparent)`,
+      text: color,
+      border: `color-mix(in srgb, ${color} 30%, transparent)`,
+    };
+  }
   // Default to gray
   return colorMap.gray;
 }
@@ -87,6 +94,7 @@ export function TagBadge({ tag, onRemove, onClick, size = "sm" }: TagBadgeProps)
         color: colors.text,
         border: `1px solid ${colors.border}`,
       }}
+      title={tag.description ?? undefined}
       onClick={onClick}
     >
       <span>{tag.name}</span>
@@ -706,11 +706,14 @@ function ProviderCard({
     setIsTesting(true);
     setTestResult(null);
     try {
-      const result = await aiLib.testProvider({
-        ...provider,
-        apiKey: apiKey || undefined,
-        baseUrl: baseUrl || undefined,
-      });
+      const result = await aiLib.testProvider(
+        {
+          ...provider,
+          apiKey: apiKey || undefined,
+          baseUrl: baseUrl || undefined,
+        },
+        true,
+      );
       setTestResult(result);
 
       if (result.success && result.models && result.models.length > 0) {
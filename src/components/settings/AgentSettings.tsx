@@ -238,11 +238,10 @@ export function AgentSettings() {
               <RWSourceToggle
                 icon={Bookmark}
                 label="Tags"
-                description="Get tags and find notes by tag"
+                description="Get tags, find notes by tag, and tag notes"
                 permission={config.sources.tags}
                 onToggleRead={(enabled) => updateRWSource("tags", "read", enabled)}
                 onToggleWrite={(enabled) => updateRWSource("tags", "write", enabled)}
-                writeDisabled
               />
               <RWSourceToggle
                 icon={Calendar}
@@ -264,11 +263,10 @@ export function AgentSettings() {
               <RWSourceToggle
                 icon={Folder}
                 label="Folders"
-                description="Browse folder structure"
+                description="Browse folders, create folders and move notes"
                 permission={config.sources.folders}
                 onToggleRead={(enabled) => updateRWSource("folders", "read", enabled)}
                 onToggleWrite={(enabled) => updateRWSource("folders", "write", enabled)}
-                writeDisabled
               />
             </div>
 
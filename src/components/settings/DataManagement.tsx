@@ -1,6 +1,6 @@
 import { useState } from "react";
 import { Trash2, AlertTriangle, RefreshCw, MessageSquare, FileText, Cpu, Loader2, Download, Upload } from "lucide-react";
-import { clearChats, clearNotes, clearAIConfig, factoryReset, syncVaultToDisk, syncDiskToVault } from "../../lib/vault";
+import { clearChats, clearNotes, clearAIConfig, factoryReset, getFactoryResetToken, syncVaultToDisk, syncDiskToVault } from "../../lib/vault";
 import { useVaultStore } from "../../stores/vaultStore";
 
 interface ActionButtonProps {
@@ -97,6 +97,7 @@ export function DataManagement() {
   const { vaultPath } = useVaultStore();
   const [isClearing, setIsClearing] = useState<string | null>(null);
   const [confirmReset, setConfirmReset] = useState(false);
+  const [resetToken, setResetToken] = useState<string | null>(null);
   const [message, setMessage] = useState<{
     type: "success" | "error";
     text: string;
@@ -144,14 +145,16 @@ export function DataManagement() {
   };
 
   const handleFactoryReset = async () => {
-    if (!confirmReset) {
+    if (!confirmReset || !resetToken) {
+      // The backend only resets with a token issued for this confirmation
+      setResetToken(await getFactoryResetToken());
       setConfirmReset(true);
       return;
     }
 
     setIsClearing("factory");
     try {
-      await factoryReset();
+      await factoryReset(resetToken);
       showMessage("success", "Factory reset complete. Reloading...");
       // Reload the app after a short delay
       setTimeout(() => {
@@ -160,6 +163,7 @@ export function DataManagement() {
     } catch (error) {
       showMessage("error", error instanceof Error ? error.message : "Factory reset failed");
       setConfirmReset(false);
+      setResetToken(null);
     } finally {
       setIsClearing(null);
     }
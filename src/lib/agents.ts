@@ -13,6 +13,8 @@ import type {
   DeepResearchResult,
   InlineAssistantResult,
   SummarizationResult,
+  SummaryLength,
+  SummaryStyle,
   ToolInfo,
 } from "../types/agent";
 
@@ -91,18 +93,24 @@ export function generateExecutionId(): string {
  * @param content - The content to summarize
  * @param contentType - Type of content ("selection" or "attachment")
  * @param attachmentPath - Path to attachment if contentType is "attachment"
+ * @param length - Target summary length (defaults to "medium")
+ * @param style - Summary format (defaults to "bullets")
  */
 export async function executeSummarizationAgent(
   executionId: string,
   content: string,
   contentType: "selection" | "attachment",
-  attachmentPath?: string
+  attachmentPath?: string,
+  length?: SummaryLength,
+  style?: SummaryStyle
 ): Promise<SummarizationResult> {
   return invoke<SummarizationResult>("execute_summarization_agent", {
     executionId,
     content,
     contentType,
     attachmentPath,
+    length,
+    style,
   });
 }
 
@@ -6,6 +6,7 @@ import type {
   AIConfig,
   AIProvider,
   LocalModelsResult,
+  ModelPricing,
   ProviderInfo,
   ProviderTestResult,
   ProviderType,
@@ -45,6 +46,29 @@ export async function setDefaultProvider(
   return invoke<AIConfig>("set_default_provider", { providerId });
 }
 
+/**
+ * Set the providers chat falls back to, in order, when the default one is unreachable
+ */
+export async function setFallbackProviders(
+  providerIds: string[],
+): Promise<AIConfig> {
+  return invoke<AIConfig>("set_fallback_providers", { providerIds });
+}
+
+/**
+ * Get the per-model prices used for cost estimates
+ */
+export async function getModelPricing(): Promise<ModelPricing> {
+  return invoke<ModelPricing>("get_model_pricing");
+}
+
+/**
+ * Replace the per-model prices used for cost estimates
+ */
+export async function setModelPricing(pricing: ModelPricing): Promise<ModelPricing> {
+  return invoke<ModelPricing>("set_model_pricing", { pricing });
+}
+
 /**
  * Apply AI config changes
  */
@@ -65,11 +89,18 @@ export async function initAIConfig(): Promise<AIConfig> {
 
 /**
  * Test connection to a specific provider
+ *
+ * With checkModel, also sends a tiny completion to the provider's selected
+ * model and reports whether that model actually works.
  */
 export async function testProvider(
   provider: AIProvider,
+  checkModel?: boolean,
 ): Promise<ProviderTestResult> {
-  return invoke<ProviderTestResult>("test_provider", { provider });
+  return invoke<ProviderTestResult>("test_provider", {
+    provider,
+    checkModel: checkModel ?? null,
+  });
 }
 
 /**
@@ -97,6 +128,16 @@ export async function detectLMStudio(
   return invoke<ProviderTestResult>("detect_lmstudio_cmd", { baseUrl: baseUrl ?? null });
 }
 
+/**
+ * Refresh a provider's model list from its API and save it to the config
+ * Providers without a model list endpoint keep their configured models
+ */
+export async function refreshProviderModels(
+  providerId: string,
+): Promise<ProviderTestResult> {
+  return invoke<ProviderTestResult>("refresh_provider_models", { providerId });
+}
+
 // ============================================================================
 // Provider Helpers
 // ============================================================================
@@ -2,6 +2,7 @@
  * Typed wrappers for Assistant Tauri IPC commands
  */
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 
 // ============================================================================
 // Types
@@ -37,6 +38,18 @@ export interface AssistantContentResponse {
   quoteAuthor: string;
 }
 
+/**
+ * A part of the assistant content, generated by its own LLM call
+ */
+export type AssistantSection = "greeting" | "daySummary" | "quote";
+
+/**
+ * Events emitted on `assistant-stream-{sessionId}` while streaming
+ */
+export type AssistantStreamEvent =
+  | { type: "chunk"; section: AssistantSection; content: string }
+  | { type: "complete"; content: AssistantContentResponse };
+
 // ============================================================================
 // API Functions
 // ============================================================================
@@ -51,6 +64,30 @@ export async function generateAssistantContent(
   return invoke<AssistantContentResponse>("generate_assistant_content", { input });
 }
 
+/**
+ * Generate assistant content, streaming each section's text as it arrives
+ * Listen with listenToAssistantStream first; stop with stopGeneration(sessionId)
+ */
+export async function streamAssistantContent(
+  sessionId: string,
+  input: AssistantContentInput
+): Promise<AssistantContentResponse> {
+  return invoke<AssistantContentResponse>("stream_assistant_content", { sessionId, input });
+}
+
+/**
+ * Listen to streaming assistant content events
+ * Returns an unlisten function to stop listening
+ */
+export async function listenToAssistantStream(
+  sessionId: string,
+  onEvent: (event: AssistantStreamEvent) => void
+): Promise<UnlistenFn> {
+  return listen<AssistantStreamEvent>(`assistant-stream-${sessionId}`, (event) => {
+    onEvent(event.payload);
+  });
+}
+
 /**
  * Get fallback assistant content without AI
  * Used when AI is not configured or fails
@@ -2,12 +2,16 @@
  * Typed wrappers for Board-related Tauri IPC commands
  */
 import { invoke } from "@tauri-apps/api/core";
+import type { Note } from "../types/note";
 import type {
   Board,
   BoardLane,
   BoardCard,
   BoardCardWithNote,
   BoardWithDetails,
+  BoardTemplate,
+  CardPlacement,
+  CardSortOrder,
 } from "../types/board";
 
 // ============================================================================
@@ -24,6 +28,21 @@ export async function createBoard(
   return invoke<BoardWithDetails>("create_board", { folderId, name });
 }
 
+/**
+ * Create a new board for a folder with a template's lanes
+ */
+export async function createBoardFromTemplate(
+  folderId: string,
+  name: string,
+  template: BoardTemplate
+): Promise<BoardWithDetails> {
+  return invoke<BoardWithDetails>("create_board_from_template", {
+    folderId,
+    name,
+    template,
+  });
+}
+
 /**
  * Get a board by ID
  */
@@ -59,9 +78,14 @@ export async function getBoardWithDetails(
  */
 export async function updateBoard(
   id: string,
-  name: string | null
+  name: string | null,
+  enforceWipLimits?: boolean | null
 ): Promise<Board> {
-  return invoke<Board>("update_board", { id, name });
+  return invoke<Board>("update_board", {
+    id,
+    name,
+    enforceWipLimits: enforceWipLimits ?? null,
+  });
 }
 
 /**
@@ -112,6 +136,16 @@ export async function updateLane(
   });
 }
 
+/**
+ * Set or clear a lane's WIP limit
+ */
+export async function setLaneWipLimit(
+  id: string,
+  wipLimit: number | null
+): Promise<BoardLane> {
+  return invoke<BoardLane>("set_lane_wip_limit", { id, wipLimit });
+}
+
 /**
  * Delete a lane
  */
@@ -139,9 +173,15 @@ export async function reorderLanes(
 export async function addCard(
   boardId: string,
   laneId: string,
-  noteId: string
+  noteId: string,
+  dueDate?: string | null
 ): Promise<BoardCard> {
-  return invoke<BoardCard>("add_card", { boardId, laneId, noteId });
+  return invoke<BoardCard>("add_card", {
+    boardId,
+    laneId,
+    noteId,
+    dueDate: dueDate ?? null,
+  });
 }
 
 /**
@@ -162,21 +202,43 @@ export async function getCardsInLane(
   return invoke<BoardCardWithNote[]>("get_cards_in_lane", { laneId });
 }
 
+/**
+ * Get cards in a specific lane, sorted manually, by due date, or by title
+ */
+export async function getCardsInLaneSorted(
+  laneId: string,
+  by: CardSortOrder
+): Promise<BoardCardWithNote[]> {
+  return invoke<BoardCardWithNote[]>("get_cards_in_lane_sorted", { laneId, by });
+}
+
 /**
  * Move a card to a different lane and/or position
  */
 export async function moveCard(
   cardId: string,
   targetLaneId: string,
-  targetPosition: number
+  targetPosition: number,
+  dueDate?: string | null
 ): Promise<BoardCard> {
   return invoke<BoardCard>("move_card", {
     cardId,
     targetLaneId,
     targetPosition,
+    dueDate: dueDate ?? null,
   });
 }
 
+/**
+ * Set or clear a card's due date
+ */
+export async function setCardDueDate(
+  cardId: string,
+  dueDate: string | null
+): Promise<BoardCard> {
+  return invoke<BoardCard>("set_card_due_date", { cardId, dueDate });
+}
+
 /**
  * Remove a card from a board
  */
@@ -190,3 +252,19 @@ export async function removeCard(id: string): Promise<boolean> {
 export async function getBoardsForNote(noteId: string): Promise<Board[]> {
   return invoke<Board[]>("get_boards_for_note", { noteId });
 }
+
+/**
+ * Get every card a note appears as, with its board and lane
+ */
+export async function getCardPlacementsForNote(
+  noteId: string
+): Promise<CardPlacement[]> {
+  return invoke<CardPlacement[]>("get_card_placements_for_note", { noteId });
+}
+
+/**
+ * Snapshot a board into a new note with a checklist per lane
+ */
+export async function exportBoardToNote(boardId: string): Promise<Note> {
+  return invoke<Note>("export_board_to_note", { boardId });
+}
@@ -10,10 +10,16 @@ import type {
   ChatResponse,
   CreateConversationInput,
   UpdateConversationInput,
+  ReasoningSettings,
   ConversationWithMessages,
   ConversationPreview,
+  ConversationSearchResult,
+  SystemPrompt,
+  SaveSystemPromptInput,
   ChatStreamEvent,
 } from "../types/chat";
+import type { CostEstimate } from "../types/ai";
+import type { ExportResult } from "../types/export";
 
 // ============================================================================
 // Conversation Management
@@ -28,6 +34,29 @@ export async function createConversation(
   return invoke<Conversation>("create_conversation", { input });
 }
 
+/**
+ * List system prompt presets (default first)
+ */
+export async function listSystemPrompts(): Promise<SystemPrompt[]> {
+  return invoke<SystemPrompt[]>("list_system_prompts");
+}
+
+/**
+ * Create or update a system prompt preset
+ */
+export async function saveSystemPrompt(
+  input: SaveSystemPromptInput
+): Promise<SystemPrompt> {
+  return invoke<SystemPrompt>("save_system_prompt", { input });
+}
+
+/**
+ * Delete a system prompt preset
+ */
+export async function deleteSystemPrompt(id: string): Promise<boolean> {
+  return invoke<boolean>("delete_system_prompt", { id });
+}
+
 /**
  * Get a conversation by ID
  */
@@ -58,6 +87,34 @@ export async function listConversationPreviews(): Promise<ConversationPreview[]>
   return invoke<ConversationPreview[]>("list_conversation_previews");
 }
 
+/**
+ * Full-text search over conversation messages
+ */
+export async function searchConversations(
+  query: string,
+  limit?: number
+): Promise<ConversationSearchResult[]> {
+  return invoke<ConversationSearchResult[]>("search_conversations", {
+    query,
+    limit: limit ?? null,
+  });
+}
+
+/**
+ * Export a conversation as a Markdown, PDF or Word document
+ */
+export async function exportConversation(
+  conversationId: string,
+  format: "markdown" | "pdf" | "docx",
+  includeThinking?: boolean
+): Promise<ExportResult> {
+  return invoke<ExportResult>("export_conversation", {
+    conversationId,
+    format,
+    includeThinking: includeThinking ?? null,
+  });
+}
+
 /**
  * Update a conversation (title or system prompt)
  */
@@ -68,6 +125,30 @@ export async function updateConversation(
   return invoke<Conversation>("update_conversation", { id, input });
 }
 
+/**
+ * Get a conversation's reasoning effort / thinking budget
+ */
+export async function getConversationReasoning(id: string): Promise<ReasoningSettings> {
+  return invoke<ReasoningSettings>("get_conversation_reasoning", { id });
+}
+
+/**
+ * Set a conversation's reasoning effort / thinking budget (both null turns reasoning off)
+ */
+export async function updateConversationReasoning(
+  id: string,
+  settings: ReasoningSettings
+): Promise<ReasoningSettings> {
+  return invoke<ReasoningSettings>("update_conversation_reasoning", { id, settings });
+}
+
+/**
+ * Estimate what a conversation's responses cost from their stored token usage
+ */
+export async function getConversationCost(conversationId: string): Promise<CostEstimate> {
+  return invoke<CostEstimate>("get_conversation_cost", { conversationId });
+}
+
 /**
  * Delete a conversation
  */
@@ -122,6 +203,26 @@ export async function editMessageAndRegenerate(
   });
 }
 
+/**
+ * Regenerate the last assistant response from the existing history
+ * Only the final assistant message is replaced
+ */
+export async function regenerateLastResponse(
+  conversationId: string
+): Promise<ChatResponse> {
+  return invoke<ChatResponse>("regenerate_last_response", { conversationId });
+}
+
+/**
+ * Fork a conversation at a message
+ * The new conversation holds a copy of the history up to and including that message
+ */
+export async function branchConversation(
+  fromMessageId: string
+): Promise<Conversation> {
+  return invoke<Conversation>("branch_conversation", { fromMessageId });
+}
+
 /**
  * Stop an active generation stream
  * @param sessionId The session ID of the stream to stop
@@ -131,6 +232,21 @@ export async function stopGeneration(sessionId: string): Promise<boolean> {
   return invoke<boolean>("stop_generation", { sessionId });
 }
 
+/**
+ * List the session IDs of generation streams that are currently registered
+ */
+export async function listActiveStreams(): Promise<string[]> {
+  return invoke<string[]>("list_active_streams");
+}
+
+/**
+ * Drop registered streams whose generation has already stopped
+ * @returns The session IDs that were removed
+ */
+export async function pruneActiveStreams(): Promise<string[]> {
+  return invoke<string[]>("prune_active_streams");
+}
+
 /**
  * Get the default system prompt
  */
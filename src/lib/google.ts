@@ -9,6 +9,7 @@ import type {
   GoogleConnectionStatus,
   GoogleSyncResult,
   EventMeetingInfo,
+  ConferenceProvider,
   GoogleAccount,
 } from "../types/google";
 
@@ -95,6 +96,8 @@ export async function getEventMeetingInfo(
   const result = await invoke<{
     attendees: string[];
     meeting_link: string | null;
+    location: string | null;
+    video_conference: { provider: ConferenceProvider; join_url: string } | null;
     original_description: string | null;
   } | null>("get_event_meeting_info", { eventId });
 
@@ -103,6 +106,11 @@ export async function getEventMeetingInfo(
   return {
     attendees: result.attendees,
     meetingLink: result.meeting_link,
+    location: result.location,
+    videoConference: result.video_conference && {
+      provider: result.video_conference.provider,
+      joinUrl: result.video_conference.join_url,
+    },
     originalDescription: result.original_description,
   };
 }
@@ -26,6 +26,22 @@ export interface EmbeddingStats {
   currentModel: string | null;
 }
 
+/** A note in a group of likely duplicates */
+export interface DuplicateNote {
+  noteId: string;
+  title: string;
+  snippet: string | null;
+  updatedAt: string;
+}
+
+/** Notes similar enough to be worth reviewing for a merge */
+export interface DuplicateNoteGroup {
+  /** Most recently updated first */
+  notes: DuplicateNote[];
+  /** Highest similarity between two notes in the group (0-1) */
+  similarity: number;
+}
+
 /** Embedding model info */
 export interface EmbeddingModelInfo {
   id: string;
@@ -50,6 +66,40 @@ export async function searchNotes(
   });
 }
 
+/**
+ * Search notes within a folder and all of its subfolders
+ */
+export async function searchNotesInFolder(
+  query: string,
+  folderId: string,
+  mode: SearchMode = "hybrid",
+  limit?: number
+): Promise<SearchResult[]> {
+  return invoke<SearchResult[]>("search_notes_in_folder", {
+    query,
+    mode,
+    folderId,
+    limit: limit ?? null,
+  });
+}
+
+/**
+ * Find matches within a note's content, for in-note find
+ * @returns [start, end) UTF-16 offsets into the note's plain-text content
+ *   (usable with `String.prototype.slice`), overlapping matches merged
+ */
+export async function findInNote(
+  noteId: string,
+  query: string,
+  caseSensitive = false
+): Promise<Array<[number, number]>> {
+  return invoke<Array<[number, number]>>("find_in_note", {
+    noteId,
+    query,
+    caseSensitive,
+  });
+}
+
 /**
  * Get notes related to a specific note (semantic similarity)
  */
@@ -63,6 +113,69 @@ export async function getRelatedNotes(
   });
 }
 
+/**
+ * Find groups of near-duplicate notes by embedding similarity
+ */
+export async function findDuplicateNotes(
+  threshold?: number
+): Promise<DuplicateNoteGroup[]> {
+  return invoke<DuplicateNoteGroup[]>("find_duplicate_notes", {
+    threshold: threshold ?? null,
+  });
+}
+
+/**
+ * Rebuild the full-text search index from the database
+ * @returns the number of notes indexed
+ */
+export async function rebuildSearchIndex(): Promise<number> {
+  return invoke<number>("rebuild_search_index");
+}
+
+/** Languages the search index can stem words in */
+export type SearchLanguage =
+  | "Arabic"
+  | "Danish"
+  | "Dutch"
+  | "English"
+  | "Finnish"
+  | "French"
+  | "German"
+  | "Greek"
+  | "Hungarian"
+  | "Italian"
+  | "Norwegian"
+  | "Portuguese"
+  | "Romanian"
+  | "Russian"
+  | "Spanish"
+  | "Swedish"
+  | "Tamil"
+  | "Turkish";
+
+/** How note text is split into searchable terms */
+export interface SearchAnalyzerConfig {
+  /** Language words are stemmed in, so "running" matches "run" */
+  language: SearchLanguage;
+  /** Whether to leave out the language's common words, like "the" and "of" */
+  stopWords: boolean;
+}
+
+/**
+ * Get the language and stop word settings the search index uses
+ */
+export async function getSearchAnalyzer(): Promise<SearchAnalyzerConfig> {
+  return invoke<SearchAnalyzerConfig>("get_search_analyzer");
+}
+
+/**
+ * Save the search analyzer settings, rebuilding the index if they changed
+ * @returns the number of notes in the index
+ */
+export async function setSearchAnalyzer(config: SearchAnalyzerConfig): Promise<number> {
+  return invoke<number>("set_search_analyzer", { config });
+}
+
 /**
  * Get embedding statistics
  */
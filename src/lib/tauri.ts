@@ -5,13 +5,17 @@ import { invoke } from "@tauri-apps/api/core";
 import { openUrl as tauriOpenUrl } from "@tauri-apps/plugin-opener";
 import type {
   Note,
+  NotePage,
+  NoteSort,
   Folder,
   Tag,
   CreateNoteInput,
   UpdateNoteInput,
+  NoteRevision,
   CreateFolderInput,
   UpdateFolderInput,
 } from "../types/note";
+import type { CostEstimate } from "../types/ai";
 
 /**
  * Result of running the tagging agent
@@ -55,6 +59,67 @@ export async function getNote(id: string): Promise<Note | null> {
   return invoke<Note | null>("get_note", { id });
 }
 
+/**
+ * At-a-glance statistics for a note
+ */
+export interface NoteStats {
+  wordCount: number;
+  /** Characters of rendered text, including spaces but not markdown syntax */
+  characterCount: number;
+  headingCount: number;
+  /** Markdown links, autolinks and [[wikilinks]] */
+  linkCount: number;
+  /** Estimated minutes to read, rounded up */
+  readingTimeMinutes: number;
+}
+
+/**
+ * Get word/character/heading/link counts and reading time for a note
+ * (code blocks are left out unless includeCode is set)
+ */
+export async function getNoteStats(
+  noteId: string,
+  includeCode = false,
+): Promise<NoteStats> {
+  return invoke<NoteStats>("get_note_stats", { noteId, includeCode });
+}
+
+/**
+ * How many notes carry a tag
+ */
+export interface TagUsage {
+  id: string;
+  name: string;
+  color: string | null;
+  noteCount: number;
+}
+
+/**
+ * Vault-wide totals for the overview panel
+ */
+export interface VaultStats {
+  noteCount: number;
+  folderCount: number;
+  tagCount: number;
+  /** Words across all notes, code blocks excluded */
+  wordCount: number;
+  /** Notes with an embedding for semantic search */
+  embeddedNoteCount: number;
+  urlAttachmentCount: number;
+  exportCount: number;
+  /** Tags on the most notes, most used first */
+  topTags: TagUsage[];
+  /** Token usage and estimated cost of all chat responses */
+  aiUsage: CostEstimate;
+}
+
+/**
+ * Get totals across the vault plus the most used tags
+ */
+export async function getVaultStats(topTags?: number): Promise<VaultStats> {
+  return invoke<VaultStats>("get_vault_stats", { topTags: topTags ?? null });
+}
+
 /**
  * Get all non-deleted notes
  */
@@ -62,6 +127,17 @@ export async function getAllNotes(): Promise<Note[]> {
   return invoke<Note[]>("get_all_notes");
 }
 
+/**
+ * Get one page of non-deleted notes, with the total for paging
+ */
+export async function getNotesPaginated(
+  limit: number,
+  offset: number,
+  sortBy: NoteSort = "updated",
+): Promise<NotePage> {
+  return invoke<NotePage>("get_notes_paginated", { limit, offset, sortBy });
+}
+
 /**
  * Get notes in a specific folder (or root notes if folderId is null)
  */
@@ -87,6 +163,23 @@ export async function updateNote(
   });
 }
 
+/**
+ * List a note's saved revisions, newest first
+ */
+export async function listNoteRevisions(noteId: string): Promise<NoteRevision[]> {
+  return invoke<NoteRevision[]>("list_note_revisions", { noteId });
+}
+
+/**
+ * Restore a note's title and content from a saved revision
+ */
+export async function revertNoteToRevision(
+  noteId: string,
+  revisionId: string,
+): Promise<Note> {
+  return invoke<Note>("revert_note_to_revision", { noteId, revisionId });
+}
+
 /**
  * Move a note to a different folder (or to root/unfiled if folderId is null)
  */
@@ -200,8 +293,13 @@ export async function getNoteTags(noteId: string): Promise<Tag[]> {
 export async function createTag(
   name: string,
   color?: string | null,
+  description?: string | null,
 ): Promise<Tag> {
-  return invoke<Tag>("create_tag", { name, color: color ?? null });
+  return invoke<Tag>("create_tag", {
+    name,
+    color: color ?? null,
+    description: description ?? null,
+  });
 }
 
 /**
@@ -243,14 +341,45 @@ export async function updateTag(
   tagId: string,
   name?: string | null,
   color?: string | null,
+  description?: string | null,
 ): Promise<Tag> {
   return invoke<Tag>("update_tag", {
     tagId,
     name: name ?? null,
     color: color ?? null,
+    description: description ?? null,
+  });
+}
+
+/**
+ * A tag suggested from semantically similar notes
+ */
+export interface TagSuggestion {
+  tag: Tag;
+  noteCount: number;
+  score: number;
+}
+
+/**
+ * Suggest tags for a note from the tags on similar notes (no LLM call)
+ */
+export async function suggestTagsForNote(
+  noteId: string,
+  limit?: number,
+): Promise<TagSuggestion[]> {
+  return invoke<TagSuggestion[]>("suggest_tags_for_note", {
+    noteId,
+    limit: limit ?? null,
   });
 }
 
+/**
+ * Merge tags into a target tag, moving all of their notes to it
+ */
+export async function mergeTags(sourceIds: string[], targetId: string): Promise<Tag> {
+  return invoke<Tag>("merge_tags", { sourceIds, targetId });
+}
+
 // ============================================================================
 // Tagging Agent Commands
 // ============================================================================
@@ -265,6 +394,36 @@ export async function runTaggingAgent(noteId: string): Promise<TaggingResult> {
   return invoke<TaggingResult>("run_tagging_agent_cmd", { noteId });
 }
 
+/**
+ * Result of running the tagging agent over a folder
+ */
+export interface TaggingBatchResult {
+  total: number;
+  succeeded: number;
+  failed: number;
+  skipped: number;
+  cancelled: boolean;
+  failures: { noteId: string; noteTitle: string; error: string }[];
+}
+
+/**
+ * Run the tagging agent on every note in a folder
+ *
+ * Progress is emitted on `tagging-batch-progress-{executionId}`; cancel with
+ * `cancel_agent_execution`.
+ */
+export async function runTaggingAgentBatch(
+  executionId: string,
+  folderId: string,
+  recursive: boolean,
+): Promise<TaggingBatchResult> {
+  return invoke<TaggingBatchResult>("run_tagging_agent_batch", {
+    executionId,
+    folderId,
+    recursive,
+  });
+}
+
 // ============================================================================
 // Daily Notes Commands
 // ============================================================================
@@ -302,6 +461,21 @@ export async function createDailyNote(
   });
 }
 
+/**
+ * Get the template applied to new daily notes
+ */
+export async function getDailyNoteTemplate(): Promise<string | null> {
+  return invoke<string | null>("get_daily_note_template");
+}
+
+/**
+ * Set the template applied to new daily notes (empty string removes it)
+ * Supports {date}, {weekday}, {yesterday_link} and {tomorrow_link}
+ */
+export async function setDailyNoteTemplate(template: string): Promise<void> {
+  return invoke<void>("set_daily_note_template", { template });
+}
+
 /**
  * Get the adjacent daily note (previous or next)
  * @param date - Current date in YYYY-MM-DD format
@@ -329,6 +503,90 @@ export async function isDailyNote(noteId: string): Promise<boolean> {
   return invoke<boolean>("is_daily_note", { noteId });
 }
 
+// ============================================================================
+// Periodic Notes Commands
+// ============================================================================
+
+/** The period a periodic note covers */
+export type NotePeriod = "daily" | "weekly" | "monthly";
+
+/**
+ * Get or create the system folder for a kind of periodic note
+ */
+export async function getOrCreatePeriodicNotesFolder(
+  period: NotePeriod,
+): Promise<Folder> {
+  return invoke<Folder>("get_or_create_periodic_notes_folder", { period });
+}
+
+/**
+ * Get the periodic note covering a date
+ * @param date - Date in YYYY-MM-DD format
+ */
+export async function getPeriodicNote(
+  period: NotePeriod,
+  date: string,
+): Promise<Note | null> {
+  return invoke<Note | null>("get_periodic_note", { period, date });
+}
+
+/**
+ * Create the periodic note covering a date, or return the existing one
+ * @param date - Date in YYYY-MM-DD format
+ * @param content - Optional initial content (the period's template is used otherwise)
+ * @param contentHtml - Optional initial HTML content
+ */
+export async function createPeriodicNote(
+  period: NotePeriod,
+  date: string,
+  content?: string | null,
+  contentHtml?: string | null,
+): Promise<Note> {
+  return invoke<Note>("create_periodic_note", {
+    period,
+    date,
+    content: content ?? null,
+    contentHtml: contentHtml ?? null,
+  });
+}
+
+/**
+ * Get the nearest existing periodic note before or after the period containing a date
+ * @param date - Date in YYYY-MM-DD format, or the period's title
+ * @param direction - 'prev' or 'next'
+ */
+export async function getAdjacentPeriodicNote(
+  period: NotePeriod,
+  date: string,
+  direction: "prev" | "next",
+): Promise<Note | null> {
+  return invoke<Note | null>("get_adjacent_periodic_note", {
+    period,
+    date,
+    direction,
+  });
+}
+
+/**
+ * Get the template applied to new notes of a period
+ */
+export async function getPeriodicNoteTemplate(
+  period: NotePeriod,
+): Promise<string | null> {
+  return invoke<string | null>("get_periodic_note_template", { period });
+}
+
+/**
+ * Set the template applied to new notes of a period (empty string removes it)
+ * Supports {title}, {date}, {weekday}, {previous_link} and {next_link}
+ */
+export async function setPeriodicNoteTemplate(
+  period: NotePeriod,
+  template: string,
+): Promise<void> {
+  return invoke<void>("set_periodic_note_template", { period, template });
+}
+
 // ============================================================================
 // Calendar Event Commands
 // ============================================================================
@@ -421,6 +679,14 @@ export async function linkNoteToCalendarEvent(
   return invoke<CalendarEvent>("link_note_to_calendar_event", { eventId, noteId });
 }
 
+/**
+ * Create a meeting notes note pre-filled from a calendar event and link it to the event.
+ * Returns the already-linked note if the event has one.
+ */
+export async function createMeetingNote(eventId: string): Promise<Note> {
+  return invoke<Note>("create_meeting_note", { eventId });
+}
+
 /**
  * Unlink a note from a calendar event
  */
@@ -2,7 +2,9 @@
  * Typed wrappers for Vault-related Tauri IPC commands
  */
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { open } from "@tauri-apps/plugin-dialog";
+import type { Note } from "../types/note";
 
 export interface VaultInfo {
   path: string;
@@ -37,6 +39,22 @@ export async function setVaultPath(path: string): Promise<void> {
   return invoke<void>("set_vault_path", { path });
 }
 
+/**
+ * Get the custom exports directory (or null if exports go to the vault)
+ */
+export async function getExportsDirOverride(): Promise<string | null> {
+  return invoke<string | null>("get_exports_dir_override");
+}
+
+/**
+ * Set the custom exports directory, or pass null to use the vault's exports folder
+ *
+ * The directory is created if missing and must be writable.
+ */
+export async function setExportsDirOverride(path: string | null): Promise<void> {
+  return invoke<void>("set_exports_dir_override", { path });
+}
+
 /**
  * Create a new vault at the specified path
  */
@@ -60,11 +78,33 @@ export async function hasExistingData(): Promise<boolean> {
 
 /**
  * Migrate existing data to the new vault
+ *
+ * Safe to re-run after an interruption: notes already written are skipped.
  */
 export async function migrateToVault(vaultPath: string): Promise<void> {
   return invoke<void>("migrate_to_vault", { vaultPath });
 }
 
+export interface MigrationProgress {
+  migrated: number;
+  /** Notes already written by an earlier, interrupted run */
+  skipped: number;
+  failed: number;
+  total: number;
+  currentTitle: string | null;
+}
+
+/**
+ * Listen for progress while migrating to a vault
+ */
+export async function listenForMigrationProgress(
+  callback: (progress: MigrationProgress) => void,
+): Promise<UnlistenFn> {
+  return listen<MigrationProgress>("vault-migration-progress", (event) => {
+    callback(event.payload);
+  });
+}
+
 /**
  * Open a folder picker dialog
  */
@@ -90,6 +130,93 @@ export async function saveAttachment(
   });
 }
 
+export interface ImportFileError {
+  /** Path relative to the imported directory */
+  path: string;
+  message: string;
+}
+
+export interface ImportReport {
+  /** Notes created for files not imported before */
+  notesImported: number;
+  /** Previously imported notes whose file content changed */
+  notesUpdated: number;
+  /** Previously imported notes left as they were */
+  notesUnchanged: number;
+  foldersCreated: number;
+  linksCreated: number;
+  errors: ImportFileError[];
+  /** IDs of the created and updated notes */
+  noteIds: string[];
+}
+
+/**
+ * Import a folder of markdown files (e.g. an Obsidian vault) as notes
+ */
+export async function importMarkdownDirectory(path: string): Promise<ImportReport> {
+  return invoke<ImportReport>("import_markdown_directory", { path });
+}
+
+export interface BackupManifest {
+  formatVersion: number;
+  /** Number of database migrations applied when the backup was made */
+  schemaVersion: number;
+  appVersion: string;
+  createdAt: string;
+  noteCount: number;
+}
+
+/**
+ * Write a zip backup of the vault (database, search index, attachments, notes)
+ */
+export async function backupVault(outputPath: string): Promise<BackupManifest> {
+  return invoke<BackupManifest>("backup_vault", { outputPath });
+}
+
+/**
+ * Replace the vault's data with a backup archive
+ */
+export async function restoreVault(archivePath: string): Promise<BackupManifest> {
+  return invoke<BackupManifest>("restore_vault", { archivePath });
+}
+
+export interface SchemaVersion {
+  /** Number of migrations applied to the database */
+  current: number;
+  /** Number of migrations this build knows about */
+  latest: number;
+}
+
+/**
+ * Get the database's applied schema version and the version this build expects
+ */
+export async function getSchemaVersion(): Promise<SchemaVersion> {
+  return invoke<SchemaVersion>("get_schema_version");
+}
+
+/**
+ * List the migrations that were pending when the database was opened, in the
+ * order they ran. They have all been applied by the time this returns.
+ */
+export async function listPendingMigrations(): Promise<string[]> {
+  return invoke<string[]>("list_pending_migrations");
+}
+
+export interface AttachmentGcResult {
+  /** Attachment file names no note or export references */
+  orphaned: string[];
+  /** Total size of the orphaned files in bytes */
+  bytes: number;
+  dryRun: boolean;
+}
+
+/**
+ * Find unreferenced attachment files, deleting them unless dryRun is set
+ */
+export async function gcAttachments(dryRun: boolean): Promise<AttachmentGcResult> {
+  return invoke<AttachmentGcResult>("gc_attachments", { dryRun });
+}
+
 /**
  * Clear all chat conversations
  * @returns Number of conversations deleted
@@ -106,6 +233,23 @@ export async function clearNotes(): Promise<number> {
   return invoke<number>("clear_notes");
 }
 
+/**
+ * Clear the notes in a folder, optionally including its subfolders.
+ * Notes are moved to the trash rather than permanently removed.
+ * @returns Number of notes cleared
+ */
+export async function clearNotesInFolder(folderId: string, recursive: boolean): Promise<number> {
+  return invoke<number>("clear_notes_in_folder", { folderId, recursive });
+}
+
+/**
+ * Delete conversations not updated in the last `days` days
+ * @returns Number of conversations deleted
+ */
+export async function clearConversationsOlderThan(days: number): Promise<number> {
+  return invoke<number>("clear_conversations_older_than", { days });
+}
+
 /**
  * Clear AI configuration (reset to defaults)
  */
@@ -113,12 +257,20 @@ export async function clearAIConfig(): Promise<void> {
   return invoke<void>("clear_ai_config");
 }
 
+/**
+ * Get a single-use confirmation token for factoryReset, valid for one minute
+ */
+export async function getFactoryResetToken(): Promise<string> {
+  return invoke<string>("get_factory_reset_token");
+}
+
 /**
  * Factory reset - clear everything and reset vault path
  * App will need to be restarted after this
+ * @param token Confirmation token from getFactoryResetToken
  */
-export async function factoryReset(): Promise<void> {
-  return invoke<void>("factory_reset");
+export async function factoryReset(token: string): Promise<void> {
+  return invoke<void>("factory_reset", { token });
 }
 
 /**
@@ -127,8 +279,23 @@ export async function factoryReset(): Promise<void> {
 export interface SyncResult {
   notesSynced: number;
   foldersSynced: number;
+  boardsSynced: number;
+  /** Notes edited both on disk and in the app, left untouched */
+  conflicts: SyncConflict[];
+}
+
+export interface SyncConflict {
+  noteId: string;
+  noteTitle: string;
+  filePath: string;
+  dbContent: string;
+  diskContent: string;
+  /** Content as of the last sync, if known */
+  baseContent: string | null;
 }
 
+export type ConflictResolution = "keep_disk" | "keep_db" | "merge";
+
 /**
  * Sync all notes and folders from database to filesystem
  */
@@ -142,3 +309,13 @@ export async function syncVaultToDisk(): Promise<SyncResult> {
 export async function syncDiskToVault(): Promise<SyncResult> {
   return invoke<SyncResult>("sync_disk_to_vault");
 }
+
+/**
+ * Resolve a sync conflict by keeping one side or merging both
+ */
+export async function resolveConflict(
+  noteId: string,
+  resolution: ConflictResolution,
+): Promise<Note> {
+  return invoke<Note>("resolve_conflict", { noteId, resolution });
+}
@@ -42,6 +42,15 @@ interface ExportAgentResult {
   exportFilename: string | null;
   iterations: number;
   toolsUsed: string[];
+  /** True if the agent hit the hard section limit and content was dropped */
+  truncated: boolean;
+}
+
+/** Result from regenerating an export */
+interface RegeneratedExport {
+  export: Export;
+  /** Source notes that have since been deleted and were left out */
+  missingNoteIds: string[];
 }
 
 /** Completed export notification */
@@ -90,6 +99,9 @@ interface ExportState {
   exportSelectionXlsx: (content: string, title: string) => void;
   
   deleteExport: (id: string) => Promise<void>;
+  deleteExportsByFormat: (format: ExportFormat) => Promise<number>;
+  deleteExportsOlderThan: (days: number) => Promise<number>;
+  regenerateExport: (id: string) => Promise<RegeneratedExport>;
   openExport: (id: string) => Promise<void>;
   revealExportsFolder: () => Promise<void>;
   
@@ -187,6 +199,9 @@ export const useExportStore = create<ExportState>((set, get) => ({
           
           // Check if export was actually created
           const success = result.exportFilename !== null;
+          if (result.truncated) {
+            console.warn('[ExportStore] Export hit the section limit; some content was left out');
+          }
           
           // Add to completed exports for notification
           get().addCompletedExport({
@@ -313,6 +328,43 @@ export const useExportStore = create<ExportState>((set, get) => ({
     }
   },
   
+  deleteExportsByFormat: async (format: ExportFormat) => {
+    try {
+      const count = await invoke<number>('delete_exports_by_format', { format });
+      set(state => ({
+        exports: state.exports.filter(e => e.format !== format),
+      }));
+      return count;
+    } catch (error) {
+      console.error('Failed to delete exports:', error);
+      throw error;
+    }
+  },
+  
+  deleteExportsOlderThan: async (days: number) => {
+    try {
+      const count = await invoke<number>('delete_exports_older_than', { days });
+      await get().loadExports();
+      return count;
+    } catch (error) {
+      console.error('Failed to delete exports:', error);
+      throw error;
+    }
+  },
+  
+  regenerateExport: async (id: string) => {
+    try {
+      const result = await invoke<RegeneratedExport>('regenerate_export', { exportId: id });
+      set(state => ({
+        exports: state.exports.map(e => (e.id === id ? result.export : e)),
+      }));
+      return result;
+    } catch (error) {
+      console.error('Failed to regenerate export:', error);
+      throw error;
+    }
+  },
+  
   openExport: async (id: string) => {
     try {
       await invoke('open_export', { id });
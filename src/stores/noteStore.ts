@@ -3,6 +3,7 @@ import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import type { Note, UpdateNoteInput } from "../types/note";
 import * as api from "../lib/tauri";
 import { embedNote } from "../lib/search";
+import type { SyncConflict } from "../lib/vault";
 import { useAgentActivityStore } from "./agentActivityStore";
 import { useDailyNotesStore } from "./dailyNotesStore";
 import { useEditorGroupStore } from "./editorGroupStore";
@@ -372,7 +373,15 @@ interface NoteContentUpdatedEvent {
   source: string;
 }
 
+/** Event payload for note-removed */
+interface NoteRemovedEvent {
+  noteId: string;
+  source: string;
+}
+
 let noteContentUnlistenFn: UnlistenFn | null = null;
+let noteRemovedUnlistenFn: UnlistenFn | null = null;
+let noteConflictUnlistenFn: UnlistenFn | null = null;
 
 /**
  * Initialize the note content updated event listener.
@@ -405,6 +414,30 @@ export async function initNoteContentListener(): Promise<void> {
       }
     }
   );
+
+  if (noteRemovedUnlistenFn) {
+    noteRemovedUnlistenFn();
+  }
+
+  // A note's file was deleted on disk: close its tab and drop it from the list
+  noteRemovedUnlistenFn = await listen<NoteRemovedEvent>("note-removed", (event) => {
+    const { noteId, source } = event.payload;
+    console.log(`[NoteStore] Note removed: ${noteId} (source: ${source})`);
+    useNoteStore.getState().closeNote(noteId);
+    useNoteStore.setState((state) => ({
+      notes: state.notes.filter((n) => n.id !== noteId),
+    }));
+  });
+
+  if (noteConflictUnlistenFn) {
+    noteConflictUnlistenFn();
+  }
+
+  // The file and the database both changed; neither side was overwritten
+  noteConflictUnlistenFn = await listen<SyncConflict>("note-sync-conflict", (event) => {
+    const { noteId, noteTitle, filePath } = event.payload;
+    console.warn(`[NoteStore] Sync conflict for "${noteTitle}" (${noteId}): ${filePath} changed on disk and in the app`);
+  });
 }
 
 /**
@@ -415,4 +448,12 @@ export function cleanupNoteContentListener(): void {
     noteContentUnlistenFn();
     noteContentUnlistenFn = null;
   }
+  if (noteRemovedUnlistenFn) {
+    noteRemovedUnlistenFn();
+    noteRemovedUnlistenFn = null;
+  }
+  if (noteConflictUnlistenFn) {
+    noteConflictUnlistenFn();
+    noteConflictUnlistenFn = null;
+  }
 }
@@ -64,6 +64,8 @@ export interface AgentConfig {
   sources: SourceConfig;
   /** Capability toggles */
   capabilities: CapabilityConfig;
+  /** Seconds a single tool call may run before the agent moves on without it */
+  toolTimeoutSecs: number;
 }
 
 /** Information about an available tool */
@@ -81,6 +83,7 @@ export type AgentProgress =
   | { type: "toolCalling"; toolName: string; arguments: Record<string, unknown> }
   | { type: "toolResult"; toolName: string; success: boolean; preview?: string }
   | { type: "thinking"; message: string }
+  | { type: "contentChunk"; content: string }
   | { type: "completed"; result: AgentResult }
   | { type: "error"; message: string }
   | { type: "cancelled" };
@@ -99,14 +102,24 @@ export interface ToolCallRecord {
   result: string;
 }
 
+/** How inline assistant content should be applied to the note */
+export type WriteMode = "insert" | "replace_selection" | "append_to_note";
+
 /** Result of the inline assistant */
 export interface InlineAssistantResult {
   content: string;
+  writeMode: WriteMode;
   toolsUsed: string[];
   iterations: number;
   toolCalls: ToolCallRecord[];
 }
 
+/** Target length of a summary */
+export type SummaryLength = "short" | "medium" | "long";
+
+/** Format of a summary */
+export type SummaryStyle = "bullets" | "paragraph" | "abstract";
+
 /** Result of the summarization agent */
 export interface SummarizationResult {
   finalResponse: string;
@@ -171,7 +184,8 @@ export const DEFAULT_SOURCE_CONFIG: SourceConfig = {
   tags: { read: true, write: true },
   calendar: { read: true, write: true },
   dailyNotes: { read: true, write: true },
-  folders: { read: true, write: true },
+  // Reorganizing the vault is opt-in
+  folders: { read: true, write: false },
   // Read-only sources
   urlAttachments: true,
   webSearch: false, // Requires API key configuration
@@ -191,4 +205,5 @@ export const DEFAULT_AGENT_CONFIG: AgentConfig = {
   },
   sources: DEFAULT_SOURCE_CONFIG,
   capabilities: DEFAULT_CAPABILITY_CONFIG,
+  toolTimeoutSecs: 30,
 };
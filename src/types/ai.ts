@@ -10,6 +10,7 @@ export type ProviderType =
   | "ollama"
   | "lmstudio"
   | "vllm"
+  | "local"
   | "custom";
 
 /** Configuration for a single AI provider */
@@ -32,6 +33,12 @@ export interface AIProvider {
   selectedModel?: string;
   /** Context window size in tokens (optional, uses provider defaults if not set) */
   contextLength?: number;
+  /** Seconds to wait for a whole LLM request (default 300) */
+  requestTimeoutSecs?: number;
+  /** Seconds to wait for the connection to be established (default 10) */
+  connectTimeoutSecs?: number;
+  /** Seconds a streamed response may go without new tokens before it is treated as stalled (default 90) */
+  streamIdleTimeoutSecs?: number;
 }
 
 /** Default context lengths by provider type (in tokens) */
@@ -42,6 +49,7 @@ export const DEFAULT_CONTEXT_LENGTHS: Record<ProviderType, number> = {
   ollama: 32_000,
   lmstudio: 32_000,
   vllm: 32_000,
+  local: 512,
   custom: 32_000,
 };
 
@@ -73,17 +81,44 @@ export interface AIConfig {
   providers: AIProvider[];
   /** ID of the default provider to use */
   defaultProvider: string | null;
+  /** Providers to try in order when the default one cannot be reached */
+  fallbackProviderIds?: string[];
   /** Embedding configuration */
   embedding: EmbeddingConfig;
 }
 
+/** Price of one model in USD per million tokens */
+export interface ModelPrice {
+  inputPerMillion: number;
+  outputPerMillion: number;
+}
+
+/** Prices keyed by model name or model name prefix */
+export interface ModelPricing {
+  models: Record<string, ModelPrice>;
+}
+
+/** Token totals and estimated cost for a set of responses */
+export interface CostEstimate {
+  promptTokens: number;
+  completionTokens: number;
+  estimatedCostUsd: number;
+  /** Models that had usage but no price, so counted as free */
+  unpricedModels: string[];
+}
+
 /** Result of testing a provider connection */
 export interface ProviderTestResult {
   success: boolean;
   message: string;
   models?: string[];
+  /** Outcome of the test completion, when the selected model was checked */
+  modelStatus?: ModelStatus;
 }
 
+/** Whether a provider actually serves a given model */
+export type ModelStatus = "available" | "authFailed" | "modelNotFound" | "failed";
+
 /** Result of local model detection */
 export interface LocalModelsResult {
   ollama: ProviderTestResult;
@@ -126,6 +161,7 @@ export const PROVIDER_DEFAULTS: Record<
     requiresApiKey: false,
     defaultBaseUrl: "http://localhost:8000",
   },
+  local: { name: "Offline", requiresApiKey: false },
   custom: { name: "Custom", requiresApiKey: true },
 };
 
@@ -141,5 +177,10 @@ export function providerRequiresApiKey(type: ProviderType): boolean {
 
 /** Check if provider type is a local provider */
 export function isLocalProvider(type: ProviderType): boolean {
-  return type === "ollama" || type === "lmstudio" || type === "vllm";
+  return (
+    type === "ollama" ||
+    type === "lmstudio" ||
+    type === "vllm" ||
+    type === "local"
+  );
 }
@@ -7,6 +7,8 @@ export interface Board {
   name: string;
   createdAt: string;
   updatedAt: string;
+  /** Whether moving a card into a lane at its WIP limit is refused */
+  enforceWipLimits: boolean;
 }
 
 /**
@@ -17,11 +19,17 @@ export interface CreateBoardInput {
   name: string;
 }
 
+/**
+ * Preset lane layouts for a new board
+ */
+export type BoardTemplate = "kanban" | "sprint" | { custom: string[] };
+
 /**
  * Input for updating a board
  */
 export interface UpdateBoardInput {
   name?: string | null;
+  enforceWipLimits?: boolean | null;
 }
 
 /**
@@ -33,6 +41,8 @@ export interface BoardLane {
   name: string;
   color: string | null;
   position: number;
+  /** Most cards the lane should hold, if limited */
+  wipLimit: number | null;
 }
 
 /**
@@ -61,6 +71,7 @@ export interface BoardCard {
   laneId: string;
   noteId: string;
   position: number;
+  dueDate: string | null;
 }
 
 /**
@@ -72,10 +83,25 @@ export interface BoardCardWithNote {
   laneId: string;
   noteId: string;
   position: number;
+  dueDate: string | null;
   noteTitle: string;
   noteFolderPath: string | null;
 }
 
+/**
+ * Where a note appears as a card: which board, which lane, and where in it
+ */
+export interface CardPlacement {
+  cardId: string;
+  boardId: string;
+  boardName: string;
+  laneId: string;
+  laneName: string;
+  laneColor: string | null;
+  position: number;
+  dueDate: string | null;
+}
+
 /**
  * Input for adding a card to a lane
  */
@@ -83,6 +109,7 @@ export interface AddCardInput {
   boardId: string;
   laneId: string;
   noteId: string;
+  dueDate?: string | null;
 }
 
 /**
@@ -92,8 +119,15 @@ export interface MoveCardInput {
   cardId: string;
   targetLaneId: string;
   targetPosition: number;
+  /** New due date; omit to leave it unchanged */
+  dueDate?: string | null;
 }
 
+/**
+ * How to order the cards in a lane
+ */
+export type CardSortOrder = "manual" | "dueDate" | "title";
+
 /**
  * A board with all its lanes and cards
  */
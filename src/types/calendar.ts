@@ -3,7 +3,7 @@
  * - manual: Created by the user in Inkling
  * - google: Synced from Google Calendar (future)
  */
-export type CalendarEventSource = "manual" | "google";
+export type CalendarEventSource = "manual" | "google" | "microsoft";
 
 /**
  * Event type for Google Calendar events
@@ -51,6 +51,7 @@ export interface CalendarEvent {
   responseStatus: EventResponseStatus | null; // Your RSVP status (for Google events)
   attendees: EventAttendee[] | null; // List of attendees (for Google events)
   meetingLink: string | null; // Video call link (for Google events)
+  location: string | null; // Room, address, or video call URL
   createdAt: string;
   updatedAt: string;
 }
@@ -74,6 +75,7 @@ export interface CreateCalendarEventInput {
   recurrenceRule?: string | null;
   linkedNoteId?: string | null;
   meetingLink?: string | null; // Video call link (optional for manual events)
+  location?: string | null;
 }
 
 /**
@@ -90,6 +92,7 @@ export interface UpdateCalendarEventInput {
   eventType?: CalendarEventType | null;
   responseStatus?: EventResponseStatus | null;
   meetingLink?: string | null; // Video call link
+  location?: string | null;
 }
 
 /**
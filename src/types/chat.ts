@@ -12,6 +12,8 @@ export interface Conversation {
   systemPrompt: string | null;
   createdAt: string;
   updatedAt: string;
+  /** Message this conversation was branched from, if any */
+  parentMessageId: string | null;
 }
 
 /** A message in a conversation */
@@ -36,6 +38,16 @@ export interface MessageMetadata {
   toolCalls?: ToolCallRecord[];
   /** Thinking/reasoning content from the AI */
   thinkingContent?: string | null;
+  /** Images the user attached to this message */
+  images?: ImageAttachment[];
+}
+
+/** A reference to an image attached to a chat message (bytes aren't stored) */
+export interface ImageAttachment {
+  /** File the image was read from, if attached by path */
+  path: string | null;
+  mimeType: string;
+  sizeBytes: number;
 }
 
 /** A record of a tool call made during message generation */
@@ -54,6 +66,10 @@ export interface Citation {
   noteTitle: string;
   /** Relevance score (0-1) */
   relevance: number;
+  /** Start of the supporting span in the note content (UTF-16 offset) */
+  startOffset?: number;
+  /** End of the supporting span in the note content (UTF-16 offset, exclusive) */
+  endOffset?: number;
 }
 
 /** Token usage statistics */
@@ -92,6 +108,30 @@ export interface SendMessageInput {
   context: ContextItem[];
   /** Number of notes to auto-retrieve via RAG */
   autoRetrieveCount?: number;
+  /** Let the assistant call note tools in non-streaming chat (streaming chat always does) */
+  useTools?: boolean;
+  /** Images to send with the message */
+  images?: ChatImageInput[];
+  /** Reasoning settings to save on the conversation before sending */
+  reasoning?: ReasoningSettings;
+}
+
+/** Per-conversation reasoning settings (reasoning is off when both are null) */
+export interface ReasoningSettings {
+  /** Effort level for OpenAI reasoning models */
+  reasoningEffort: "minimal" | "low" | "medium" | "high" | null;
+  /** Token budget for Claude extended thinking and Gemini thinking */
+  thinkingBudget: number | null;
+}
+
+/** An image attached to a chat message, by file path or as base64 data */
+export interface ChatImageInput {
+  /** Path to an image file */
+  path?: string;
+  /** Base64-encoded image bytes or a data: URL (e.g. a pasted screenshot) */
+  data?: string;
+  /** MIME type; inferred from the file extension when attaching by path */
+  mimeType?: string;
 }
 
 /** Response from sending a chat message */
@@ -108,6 +148,28 @@ export interface ChatResponse {
 export interface CreateConversationInput {
   title?: string;
   systemPrompt?: string;
+  /** Preset to use when no explicit prompt is given (falls back to the default preset) */
+  systemPromptId?: string;
+}
+
+/** A named, reusable system prompt */
+export interface SystemPrompt {
+  id: string;
+  name: string;
+  prompt: string;
+  /** Used for new conversations that don't specify a prompt */
+  isDefault: boolean;
+  createdAt: string;
+  updatedAt: string;
+}
+
+/** Input for creating or updating a system prompt preset */
+export interface SaveSystemPromptInput {
+  /** Existing preset to update (omit to create a new one) */
+  id?: string;
+  name: string;
+  prompt: string;
+  isDefault?: boolean;
 }
 
 /** Input for updating a conversation */
@@ -131,12 +193,28 @@ export interface ConversationPreview {
   firstMessagePreview: string | null;
 }
 
+/** A conversation matching a full-text search over its messages */
+export interface ConversationSearchResult {
+  conversationId: string;
+  title: string | null;
+  /** The best-matching message in the conversation */
+  messageId: string;
+  snippet: string | null;
+  /** Relevance score (0-1, higher is better) */
+  score: number;
+  mode: string;
+}
+
+/** Status of a tool call reported while streaming */
+export type ToolCallStatus = "started" | "completed" | "failed";
+
 /** Stream event types for chat responses */
 export type ChatStreamEvent =
   | { type: "chunk"; content: string }
   | { type: "thinking"; content: string }
   | { type: "tool_start"; tool: string; args: Record<string, unknown> }
   | { type: "tool_result"; tool: string; success: boolean; preview?: string }
+  | { type: "tool_call"; name: string; status: ToolCallStatus }
   | { type: "complete"; message: Message }
   | { type: "error"; message: string };
 
@@ -161,6 +239,13 @@ export function isStreamToolResult(
   return event.type === "tool_result";
 }
 
+/** Check if a stream event is a tool call progress event */
+export function isStreamToolCall(
+  event: ChatStreamEvent
+): event is { type: "tool_call"; name: string; status: ToolCallStatus } {
+  return event.type === "tool_call";
+}
+
 /** Check if a stream event is complete */
 export function isStreamComplete(
   event: ChatStreamEvent
@@ -2,7 +2,7 @@
  * Export-related types
  */
 
-export type ExportFormat = 'pdf' | 'docx' | 'xlsx' | 'pptx';
+export type ExportFormat = 'pdf' | 'docx' | 'xlsx' | 'pptx' | 'markdown';
 
 export interface Export {
   id: string;
@@ -13,6 +13,31 @@ export interface Export {
   fileSize: number | null;
   path: string;
   createdAt: string;
+  /** How long generating the file took (null for older exports) */
+  durationMs: number | null;
+  /** What produced the file (null for older exports) */
+  generator: ExportGenerator | null;
+  /** Model used when an agent produced the file */
+  model: string | null;
+  /** What the export was built from (null for older exports) */
+  source: ExportSource | null;
+  /** The conversation a conversation export was rendered from */
+  conversationId: string | null;
+  /** Options the file was rendered with */
+  options: ExportRenderOptions;
+}
+
+export type ExportGenerator = 'mechanical' | 'agent';
+
+export type ExportSource = 'notes' | 'conversation' | 'content';
+
+export interface ExportRenderOptions {
+  includeImages?: boolean;
+  pageBreakBetweenNotes?: boolean;
+  highlightCode?: boolean;
+  paperSize?: string;
+  /** Whether a conversation export included the assistant's reasoning */
+  includeThinking?: boolean;
 }
 
 export interface ExportResult {
@@ -26,12 +51,14 @@ export interface ExportNotesPdfInput {
   noteIds: string[];
   title: string;
   pageBreakBetweenNotes?: boolean;
+  highlightCode?: boolean;
 }
 
 export interface ExportNotesDocxInput {
   noteIds: string[];
   title: string;
   pageBreakBetweenNotes?: boolean;
+  highlightCode?: boolean;
 }
 
 export interface ExportContentXlsxInput {
@@ -44,6 +71,7 @@ export const FORMAT_LABELS: Record<ExportFormat, string> = {
   docx: 'Word Document',
   xlsx: 'Excel Spreadsheet',
   pptx: 'PowerPoint Presentation',
+  markdown: 'Markdown Document',
 };
 
 export const FORMAT_EXTENSIONS: Record<ExportFormat, string> = {
@@ -51,6 +79,7 @@ export const FORMAT_EXTENSIONS: Record<ExportFormat, string> = {
   docx: '.docx',
   xlsx: '.xlsx',
   pptx: '.pptx',
+  markdown: '.md',
 };
 
 export const FORMAT_ICONS: Record<ExportFormat, string> = {
@@ -58,5 +87,6 @@ export const FORMAT_ICONS: Record<ExportFormat, string> = {
   docx: '📝',
   xlsx: '📊',
   pptx: '📑',
+  markdown: '🗒️',
 };
 
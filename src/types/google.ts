@@ -27,9 +27,24 @@ export interface GoogleSyncResult {
 export interface EventMeetingInfo {
   attendees: string[];
   meetingLink: string | null;
+  location: string | null;
+  videoConference: VideoConference | null;
   originalDescription: string | null;
 }
 
+/**
+ * Video conferencing service recognized from a join link
+ */
+export type ConferenceProvider = "zoom" | "googleMeet" | "teams" | "webex";
+
+/**
+ * A video conference join link and the service it belongs to
+ */
+export interface VideoConference {
+  provider: ConferenceProvider;
+  joinUrl: string;
+}
+
 /**
  * Google account info returned after authentication
  */
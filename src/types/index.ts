@@ -15,6 +15,7 @@ export type {
   AIConfig,
   EmbeddingConfig,
   ProviderTestResult,
+  ModelStatus,
   LocalModelsResult,
   ProviderInfo,
   UpdateProviderInput,
@@ -53,6 +54,9 @@ export type {
   TokenUsage,
   ContextItem,
   SendMessageInput,
+  ChatImageInput,
+  ImageAttachment,
+  ReasoningSettings,
   ChatResponse,
   CreateConversationInput,
   UpdateConversationInput,
@@ -80,6 +84,7 @@ export type {
   AgentResult,
   ToolCallRecord,
   InlineAssistantResult,
+  WriteMode,
 } from './agent';
 
 export { DEFAULT_AGENT_CONFIG } from './agent';
@@ -118,6 +123,8 @@ export type {
   GoogleConnectionStatus,
   GoogleSyncResult,
   EventMeetingInfo,
+  ConferenceProvider,
+  VideoConference,
   GoogleAccount,
 } from './google';
 
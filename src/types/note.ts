@@ -12,6 +12,16 @@ export interface Note {
   isDeleted: boolean;
 }
 
+/** Sort order for paginated note listings */
+export type NoteSort = "updated" | "created" | "title";
+
+/** One page of notes plus the total across all pages */
+export interface NotePage {
+  notes: Note[];
+  /** Number of non-deleted notes */
+  total: number;
+}
+
 /**
  * Input for creating a new note
  */
@@ -32,6 +42,19 @@ export interface UpdateNoteInput {
   folderId?: string | null;
 }
 
+/**
+ * A snapshot of a note taken before it was updated
+ */
+export interface NoteRevision {
+  id: string;
+  noteId: string;
+  title: string;
+  content: string | null;
+  contentHtml: string | null;
+  /** When the snapshot was taken, i.e. when this version was replaced */
+  createdAt: string;
+}
+
 /**
  * A folder for organizing notes
  */
@@ -64,7 +87,9 @@ export interface UpdateFolderInput {
 export interface Tag {
   id: string;
   name: string;
+  /** Palette color name (e.g. "blue") or hex color like "#3b82f6" */
   color: string | null;
+  description: string | null;
 }
 
 /**